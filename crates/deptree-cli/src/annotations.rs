@@ -0,0 +1,124 @@
+//! Module annotations (`--annotations`)
+//!
+//! An annotations file is a JSON object keyed by module name whose values are objects
+//! of free-form metadata, for example
+//! `{"app.billing": {"team": "payments", "tier": 1}}`. An entry applies to the module
+//! it names and to everything below it; when several entries set the same key the most
+//! specific one wins. Annotations are carried into every output that has node
+//! attributes and can be used to cluster the graph with `--group-by`.
+
+use deptree_graph::{DependencyGraph, GraphId};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Contents of an annotations file: module name -> annotation key -> value
+pub type Annotations = BTreeMap<String, BTreeMap<String, String>>;
+
+/// Errors that can occur while loading an annotations file
+#[derive(Error, Debug)]
+pub enum AnnotationsError {
+    #[error("Failed to read annotations file {0}: {1}")]
+    ReadError(PathBuf, std::io::Error),
+
+    #[error("Failed to parse annotations file {0}: {1}")]
+    ParseError(PathBuf, serde_json::Error),
+
+    #[error("Invalid annotations file {0}: {1}")]
+    InvalidEntry(PathBuf, String),
+}
+
+/// Read annotations from a parsed annotations file. Values must be strings, numbers, or
+/// booleans; they are stored as strings.
+pub fn from_json(value: serde_json::Value) -> Result<Annotations, String> {
+    let serde_json::Value::Object(entries) = value else {
+        return Err("expected an object keyed by module name".to_string());
+    };
+
+    let mut annotations = Annotations::new();
+    for (module, metadata) in entries {
+        let serde_json::Value::Object(metadata) = metadata else {
+            return Err(format!("annotations of '{module}' must be an object"));
+        };
+        let mut fields = BTreeMap::new();
+        for (key, value) in metadata {
+            let value = match value {
+                serde_json::Value::String(s) => s,
+                serde_json::Value::Number(n) => n.to_string(),
+                serde_json::Value::Bool(b) => b.to_string(),
+                _ => {
+                    return Err(format!(
+                        "annotation '{key}' of '{module}' must be a string, number, or boolean"
+                    ));
+                }
+            };
+            fields.insert(key, value);
+        }
+        annotations.insert(module, fields);
+    }
+    Ok(annotations)
+}
+
+/// Read and parse an annotations file
+pub fn load(path: &Path) -> Result<Annotations, AnnotationsError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| AnnotationsError::ReadError(path.to_path_buf(), e))?;
+    let value = serde_json::from_str(&content)
+        .map_err(|e| AnnotationsError::ParseError(path.to_path_buf(), e))?;
+    from_json(value).map_err(|message| AnnotationsError::InvalidEntry(path.to_path_buf(), message))
+}
+
+/// Whether the annotations entry `key` covers `module`: the module itself or anything
+/// below it (dotted modules, or path-like ids such as Go packages)
+fn covers(key: &str, module: &str) -> bool {
+    module
+        .strip_prefix(key)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.') || rest.starts_with('/'))
+}
+
+/// Attach annotations to the modules of `graph` they cover.
+/// Returns how many entries matched at least one module.
+pub fn apply_to_graph<T: GraphId>(
+    graph: &mut DependencyGraph<T>,
+    annotations: &Annotations,
+) -> usize {
+    // Broad entries first so more specific ones overwrite their keys
+    let mut entries: Vec<_> = annotations.iter().collect();
+    entries.sort_by_key(|(key, _)| key.len());
+
+    let modules: Vec<T> = graph.nodes().cloned().collect();
+    let mut matched = 0;
+    for (key, fields) in entries {
+        let covered: Vec<&T> = modules
+            .iter()
+            .filter(|module| covers(key, &module.to_dotted()))
+            .collect();
+        if !covered.is_empty() {
+            matched += 1;
+        }
+        for module in covered {
+            for (field, value) in fields {
+                graph.set_annotation(module, field, value);
+            }
+        }
+    }
+    matched
+}
+
+/// Modules grouped by their value of the annotation `key`, as `("key=value", members)`
+/// sorted by value. Modules without the annotation are left out.
+pub fn groups<T: GraphId>(graph: &DependencyGraph<T>, key: &str) -> Vec<(String, Vec<T>)> {
+    let mut groups: BTreeMap<&str, Vec<T>> = BTreeMap::new();
+    for module in graph.nodes() {
+        if let Some(value) = graph.annotation(module, key) {
+            groups.entry(value).or_default().push(module.clone());
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(value, mut members)| {
+            members.sort_by_key(GraphId::to_dotted);
+            (format!("{key}={value}"), members)
+        })
+        .collect()
+}
@@ -0,0 +1,113 @@
+//! Anonymized output (`--anonymize`, `--anonymize-map`)
+//!
+//! Replaces every module name with opaque segments before rendering, so the structure of
+//! a graph can be shared outside the organization without its names. Each package or
+//! module name (a name prefix) gets its own `m<N>` segment, which keeps the nesting and
+//! therefore the package clusters. Segments are numbered in name order, so they are stable
+//! within a run; a mapping file keeps them stable across runs and lets its owner translate
+//! the shared output back.
+
+use deptree_graph::{DependencyGraph, GraphId};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Errors that can occur while anonymizing
+#[derive(Error, Debug)]
+pub enum AnonymizeError {
+    #[error("Failed to read anonymization map {0}: {1}")]
+    ReadError(PathBuf, std::io::Error),
+
+    #[error("Invalid anonymization map {0}: {1}")]
+    InvalidMap(PathBuf, serde_json::Error),
+
+    #[error("Failed to write anonymization map {0}: {1}")]
+    WriteError(PathBuf, std::io::Error),
+
+    #[error("Anonymized name '{0}' is not a valid node name: {1}")]
+    InvalidName(String, String),
+}
+
+/// Opaque segment of every name prefix seen so far
+#[derive(Debug, Default)]
+pub struct Anonymizer {
+    segments: BTreeMap<String, String>,
+}
+
+impl Anonymizer {
+    /// Mapping kept in `path` (a JSON object from names to segments), or an empty one if
+    /// the file does not exist yet
+    pub fn load(path: &Path) -> Result<Self, AnonymizeError> {
+        match std::fs::read_to_string(path) {
+            Ok(json) => Ok(Anonymizer {
+                segments: serde_json::from_str(&json)
+                    .map_err(|e| AnonymizeError::InvalidMap(path.to_path_buf(), e))?,
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Anonymizer::default()),
+            Err(e) => Err(AnonymizeError::ReadError(path.to_path_buf(), e)),
+        }
+    }
+
+    /// Write the mapping, including the segments assigned in this run
+    pub fn save(&self, path: &Path) -> Result<(), AnonymizeError> {
+        let json =
+            serde_json::to_string_pretty(&self.segments).expect("A string map always serializes");
+        std::fs::write(path, json + "\n")
+            .map_err(|e| AnonymizeError::WriteError(path.to_path_buf(), e))
+    }
+
+    /// Copy of `graph` with anonymized names, and the anonymized name of every node.
+    /// Names not in the mapping yet get new segments, numbered in name order after the
+    /// highest one in use.
+    pub fn anonymize<T>(
+        &mut self,
+        graph: &DependencyGraph<T>,
+    ) -> Result<(DependencyGraph<T>, HashMap<T, T>), AnonymizeError>
+    where
+        T: GraphId + std::str::FromStr,
+        T::Err: std::fmt::Display,
+    {
+        let kept = T::kind_segments();
+        let prefixes: BTreeSet<String> = graph
+            .nodes()
+            .flat_map(|node| {
+                let segments = node.segments();
+                (kept..segments.len())
+                    .map(move |end| T::join_segments(&segments[..=end]))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        let mut next = self
+            .segments
+            .values()
+            .filter_map(|segment| segment.strip_prefix('m')?.parse::<usize>().ok())
+            .max()
+            .unwrap_or(0);
+        for prefix in prefixes {
+            self.segments.entry(prefix).or_insert_with(|| {
+                next += 1;
+                format!("m{next}")
+            });
+        }
+
+        let mut mapping = HashMap::new();
+        for node in graph.nodes() {
+            let segments = node.segments();
+            let anonymized: Vec<String> = (0..segments.len())
+                .map(|end| {
+                    if end < kept {
+                        segments[end].clone()
+                    } else {
+                        self.segments[&T::join_segments(&segments[..=end])].clone()
+                    }
+                })
+                .collect();
+            let name = T::join_segments(&anonymized);
+            let id = name
+                .parse::<T>()
+                .map_err(|e| AnonymizeError::InvalidName(name.clone(), e.to_string()))?;
+            mapping.insert(node.clone(), id);
+        }
+        Ok((graph.map_ids(|node| mapping[node].clone()), mapping))
+    }
+}
@@ -0,0 +1,122 @@
+//! README badges (`--format badge`)
+//!
+//! Summarizes one module as a badge: how many modules depend on it (its blast radius) or
+//! whether it sits in an import cycle. Badges are written as shields.io endpoint JSON
+//! (`https://img.shields.io/endpoint?url=...`) or as a self-contained SVG in the same
+//! flat style.
+
+use deptree_graph::{DependencyGraph, GraphId, escape};
+
+/// What a badge shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BadgeMetric {
+    /// Modules importing the module, directly or indirectly
+    Dependents,
+    /// Whether the module is part of an import cycle
+    Cycle,
+}
+
+impl BadgeMetric {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "dependents" => Some(BadgeMetric::Dependents),
+            "cycle" => Some(BadgeMetric::Cycle),
+            _ => None,
+        }
+    }
+}
+
+/// Contents of a badge, with a shields.io named color
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Badge {
+    pub label: String,
+    pub message: String,
+    pub color: &'static str,
+}
+
+/// Badge describing `module`, which must be in `graph`
+pub fn badge<T: GraphId>(graph: &DependencyGraph<T>, module: &T, metric: BadgeMetric) -> Badge {
+    match metric {
+        BadgeMetric::Dependents => {
+            // The module itself is part of its downstream set
+            let dependents = graph
+                .find_downstream(std::slice::from_ref(module), None)
+                .len()
+                .saturating_sub(1);
+            let color = match dependents {
+                0 => "brightgreen",
+                1..=10 => "green",
+                11..=50 => "yellow",
+                51..=200 => "orange",
+                _ => "red",
+            };
+            Badge {
+                label: "dependents".to_string(),
+                message: dependents.to_string(),
+                color,
+            }
+        }
+        BadgeMetric::Cycle => {
+            let cycle = graph
+                .find_cycles()
+                .into_iter()
+                .find(|members| members.contains(module));
+            let (message, color) = match cycle {
+                None => ("none".to_string(), "brightgreen"),
+                Some(members) if members.len() == 1 => ("self-import".to_string(), "red"),
+                Some(members) => (format!("{} modules", members.len()), "red"),
+            };
+            Badge {
+                label: "import cycle".to_string(),
+                message,
+                color,
+            }
+        }
+    }
+}
+
+impl Badge {
+    /// shields.io endpoint JSON
+    pub fn to_shields_json(&self) -> String {
+        serde_json::to_string_pretty(&serde_json::json!({
+            "schemaVersion": 1,
+            "label": self.label,
+            "message": self.message,
+            "color": self.color,
+        }))
+        .expect("Badge JSON always serializes")
+    }
+
+    /// Flat-style SVG image; text widths are estimated at 7px per character
+    pub fn to_svg(&self) -> String {
+        let text_width = |text: &str| text.chars().count() * 7 + 10;
+        let label_width = text_width(&self.label);
+        let message_width = text_width(&self.message);
+        let width = label_width + message_width;
+        let label = escape::xml_attribute(&self.label);
+        let message = escape::xml_attribute(&self.message);
+        let color = hex_color(self.color);
+        format!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {message}">
+  <title>{label}: {message}</title>
+  <linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient>
+  <clipPath id="r"><rect width="{width}" height="20" rx="3" fill="#fff"/></clipPath>
+  <g clip-path="url(#r)"><rect width="{label_width}" height="20" fill="#555"/><rect x="{label_width}" width="{message_width}" height="20" fill="{color}"/><rect width="{width}" height="20" fill="url(#s)"/></g>
+  <g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11"><text x="{label_x}" y="14">{label}</text><text x="{message_x}" y="14">{message}</text></g>
+</svg>"##,
+            label_x = label_width / 2,
+            message_x = label_width + message_width / 2,
+        )
+    }
+}
+
+/// Hex value of a shields.io named color
+fn hex_color(name: &str) -> &'static str {
+    match name {
+        "brightgreen" => "#4c1",
+        "green" => "#97ca00",
+        "yellow" => "#dfb317",
+        "orange" => "#fe7d37",
+        _ => "#e05d44",
+    }
+}
@@ -0,0 +1,108 @@
+//! Baseline files for gradual enforcement
+//!
+//! A baseline records the violations that already exist so rule checks only fail on
+//! new ones. Violations are identified by contract name and the two ends of their
+//! import chain, so a chain that is merely rerouted stays tolerated.
+
+use crate::contracts::{ContractResult, Violation};
+use deptree_graph::GraphId;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Errors that can occur while reading or writing a baseline file
+#[derive(Error, Debug)]
+pub enum BaselineError {
+    #[error("Baseline file {0} does not exist (create it with --update-baseline)")]
+    NotFound(PathBuf),
+
+    #[error("Failed to read baseline file {0}: {1}")]
+    ReadError(PathBuf, std::io::Error),
+
+    #[error("Failed to parse baseline file {0}: {1}")]
+    ParseError(PathBuf, serde_json::Error),
+
+    #[error("Failed to write baseline file {0}: {1}")]
+    WriteError(PathBuf, std::io::Error),
+}
+
+/// One tolerated violation
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    pub contract: String,
+    pub importer: String,
+    pub imported: String,
+}
+
+impl BaselineEntry {
+    fn new(contract: &str, violation: &Violation) -> Option<Self> {
+        Some(BaselineEntry {
+            contract: contract.to_string(),
+            importer: violation.chain.first()?.to_dotted(),
+            imported: violation.chain.last()?.to_dotted(),
+        })
+    }
+}
+
+/// Set of violations recorded at some point in time
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub violations: BTreeSet<BaselineEntry>,
+}
+
+impl Baseline {
+    /// Record every violation found in `results`
+    pub fn from_results(results: &[ContractResult]) -> Self {
+        Baseline {
+            violations: results
+                .iter()
+                .flat_map(|result| {
+                    result
+                        .violations
+                        .iter()
+                        .filter_map(|violation| BaselineEntry::new(&result.name, violation))
+                })
+                .collect(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, BaselineError> {
+        if !path.exists() {
+            return Err(BaselineError::NotFound(path.to_path_buf()));
+        }
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| BaselineError::ReadError(path.to_path_buf(), e))?;
+        serde_json::from_str(&content).map_err(|e| BaselineError::ParseError(path.to_path_buf(), e))
+    }
+
+    /// Write the baseline as pretty-printed JSON with entries sorted for stable diffs
+    pub fn save(&self, path: &Path) -> Result<(), BaselineError> {
+        let mut content = serde_json::to_string_pretty(self)
+            .map_err(|e| BaselineError::WriteError(path.to_path_buf(), std::io::Error::other(e)))?;
+        content.push('\n');
+        std::fs::write(path, content).map_err(|e| BaselineError::WriteError(path.to_path_buf(), e))
+    }
+
+    /// Remove tolerated violations from `results`, counting them in `baselined`.
+    /// Returns the baseline entries that no longer occur (candidates for pruning).
+    pub fn apply(&self, results: &mut [ContractResult]) -> Vec<BaselineEntry> {
+        let mut seen = BTreeSet::new();
+        for result in results.iter_mut() {
+            let name = result.name.clone();
+            let before = result.violations.len();
+            result
+                .violations
+                .retain(|violation| match BaselineEntry::new(&name, violation) {
+                    Some(entry) if self.violations.contains(&entry) => {
+                        seen.insert(entry);
+                        false
+                    }
+                    _ => true,
+                });
+            result.baselined += before - result.violations.len();
+        }
+
+        self.violations.difference(&seen).cloned().collect()
+    }
+}
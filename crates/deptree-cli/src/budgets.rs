@@ -0,0 +1,221 @@
+//! Dependency budgets
+//!
+//! Evaluates the `[[budgets]]` declared in `deptree.toml`: numeric limits on how many
+//! modules a module (with everything nested below it) may import or be imported by.
+//! An exceeded budget lists every module it counts with the import chain that brings it
+//! in, so the report shows what to cut.
+
+use crate::config::Budget;
+use crate::contracts::ModuleSpec;
+use crate::polyglot::{PolyglotError, PolyglotGraph, PolyglotNode};
+use deptree_graph::{GraphId, chain_to};
+use std::collections::HashSet;
+use thiserror::Error;
+
+/// Errors that can occur while evaluating budgets
+#[derive(Error, Debug)]
+pub enum BudgetError {
+    #[error("Budget '{0}': {1}")]
+    InvalidModule(String, Box<PolyglotError>),
+
+    #[error("Budget '{0}' refers to '{1}', which matches no analyzed module")]
+    UnknownModule(String, String),
+
+    #[error(
+        "Budget '{0}' sets no limit (expected max_upstream, max_downstream, max_fan_in, or max_fan_out)"
+    )]
+    NoLimit(String),
+}
+
+/// What a budget limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// Modules imported directly or indirectly
+    Upstream,
+    /// Modules importing it directly or indirectly
+    Downstream,
+    /// Modules importing it directly
+    FanIn,
+    /// Modules imported directly
+    FanOut,
+}
+
+impl Metric {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Metric::Upstream => "upstream",
+            Metric::Downstream => "downstream",
+            Metric::FanIn => "fan-in",
+            Metric::FanOut => "fan-out",
+        }
+    }
+}
+
+/// A module counted against a budget, with an import chain linking it to the budgeted
+/// module (written in import direction, importer first)
+#[derive(Debug, Clone)]
+pub struct Counted {
+    pub module: PolyglotNode,
+    pub chain: Vec<PolyglotNode>,
+}
+
+/// Outcome of evaluating one limit of a budget
+#[derive(Debug, Clone)]
+pub struct BudgetResult {
+    pub name: String,
+    pub module: String,
+    pub metric: Metric,
+    pub limit: usize,
+    /// Counted modules, sorted by name
+    pub counted: Vec<Counted>,
+}
+
+impl BudgetResult {
+    pub fn is_kept(&self) -> bool {
+        self.counted.len() <= self.limit
+    }
+}
+
+/// Modules outside `spec` that `metric` counts
+fn count(graph: &PolyglotGraph, spec: &ModuleSpec, metric: Metric) -> Vec<Counted> {
+    let mut covered: Vec<PolyglotNode> =
+        graph.nodes().filter(|n| spec.covers(n)).cloned().collect();
+    covered.sort_by_key(GraphId::to_dotted);
+
+    let mut counted = match metric {
+        Metric::Upstream | Metric::Downstream => {
+            let reached = if metric == Metric::Upstream {
+                graph.find_upstream_with_parents(&covered, None)
+            } else {
+                graph.find_downstream_with_parents(&covered, None)
+            };
+            reached
+                .keys()
+                .filter(|module| !spec.covers(module))
+                .filter_map(|module| {
+                    let mut chain = chain_to(&reached, module)?;
+                    if metric == Metric::Downstream {
+                        chain.reverse();
+                    }
+                    Some(Counted {
+                        module: module.clone(),
+                        chain,
+                    })
+                })
+                .collect::<Vec<_>>()
+        }
+        Metric::FanIn | Metric::FanOut => {
+            let mut seen = HashSet::new();
+            let mut counted = Vec::new();
+            for inner in &covered {
+                let neighbors = if metric == Metric::FanIn {
+                    graph.direct_dependents(inner)
+                } else {
+                    graph.direct_dependencies(inner)
+                };
+                for outer in neighbors {
+                    if spec.covers(outer) || !seen.insert(outer.clone()) {
+                        continue;
+                    }
+                    let chain = if metric == Metric::FanIn {
+                        vec![outer.clone(), inner.clone()]
+                    } else {
+                        vec![inner.clone(), outer.clone()]
+                    };
+                    counted.push(Counted {
+                        module: outer.clone(),
+                        chain,
+                    });
+                }
+            }
+            counted
+        }
+    };
+    counted.sort_by_cached_key(|c| c.module.to_dotted());
+    counted
+}
+
+/// Evaluate every limit of a single budget, in the order upstream, downstream, fan-in,
+/// fan-out
+pub fn check_budget(
+    graph: &PolyglotGraph,
+    budget: &Budget,
+) -> Result<Vec<BudgetResult>, BudgetError> {
+    let limits: Vec<(Metric, usize)> = [
+        (Metric::Upstream, budget.max_upstream),
+        (Metric::Downstream, budget.max_downstream),
+        (Metric::FanIn, budget.max_fan_in),
+        (Metric::FanOut, budget.max_fan_out),
+    ]
+    .into_iter()
+    .filter_map(|(metric, limit)| Some((metric, limit?)))
+    .collect();
+    if limits.is_empty() {
+        return Err(BudgetError::NoLimit(budget.name.clone()));
+    }
+    let spec = ModuleSpec::resolve(&budget.module, graph)
+        .map_err(|e| BudgetError::InvalidModule(budget.name.clone(), Box::new(e)))?
+        .ok_or_else(|| BudgetError::UnknownModule(budget.name.clone(), budget.module.clone()))?;
+
+    Ok(limits
+        .into_iter()
+        .map(|(metric, limit)| BudgetResult {
+            name: budget.name.clone(),
+            module: spec.label.clone(),
+            metric,
+            limit,
+            counted: count(graph, &spec, metric),
+        })
+        .collect())
+}
+
+/// Evaluate every budget, in configuration order
+pub fn check_budgets(
+    graph: &PolyglotGraph,
+    budgets: &[Budget],
+) -> Result<Vec<BudgetResult>, BudgetError> {
+    let mut results = Vec::new();
+    for budget in budgets {
+        results.extend(check_budget(graph, budget)?);
+    }
+    Ok(results)
+}
+
+/// Render a human-readable report of every budget; exceeded ones list the modules they
+/// count with the chain that brings each in
+pub fn render_report(results: &[BudgetResult]) -> String {
+    let mut output = String::new();
+
+    for result in results {
+        let used = result.counted.len();
+        let (status, over) = if result.is_kept() {
+            ("KEPT", String::new())
+        } else {
+            ("EXCEEDED", format!(" ({} over)", used - result.limit))
+        };
+        output.push_str(&format!(
+            "{status:<8} {} [{}] {}: {used} of {}{over}\n",
+            result.name,
+            result.metric.as_str(),
+            result.module,
+            result.limit
+        ));
+        if !result.is_kept() {
+            for counted in &result.counted {
+                let chain: Vec<String> = counted.chain.iter().map(GraphId::to_dotted).collect();
+                output.push_str(&format!(
+                    "    {}: {}\n",
+                    counted.module.to_dotted(),
+                    chain.join(" -> ")
+                ));
+            }
+        }
+    }
+
+    let exceeded = results.iter().filter(|r| !r.is_kept()).count();
+    output.push_str(&format!(
+        "\nBudgets: {} kept, {exceeded} exceeded\n",
+        results.len() - exceeded
+    ));
+    output
+}
@@ -0,0 +1,59 @@
+//! Comment stripping shared by analyzers of C-style languages (Go, Protobuf)
+
+/// Remove `//` and `/* */` comments while leaving string and rune literals intact
+pub(crate) fn strip_c_style_comments(source: &str) -> String {
+    let mut output = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'/') => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        output.push('\n');
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = '\0';
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        output.push('\n');
+                    }
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    prev = next;
+                }
+                output.push(' ');
+            }
+            '"' | '\'' => {
+                output.push(c);
+                while let Some(next) = chars.next() {
+                    output.push(next);
+                    if next == '\\' {
+                        if let Some(escaped) = chars.next() {
+                            output.push(escaped);
+                        }
+                    } else if next == c || next == '\n' {
+                        break;
+                    }
+                }
+            }
+            '`' => {
+                output.push(c);
+                for next in chars.by_ref() {
+                    output.push(next);
+                    if next == '`' {
+                        break;
+                    }
+                }
+            }
+            _ => output.push(c),
+        }
+    }
+
+    output
+}
@@ -0,0 +1,184 @@
+//! Community detection report
+//!
+//! Groups the modules of a graph into detected communities (densely connected modules)
+//! and compares them with the declared package structure: communities spanning several
+//! packages behave like one package, and packages spread over several communities may
+//! be doing more than one job.
+
+use deptree_graph::{DependencyGraph, GraphId, OrphanScope};
+use std::collections::{BTreeMap, HashSet};
+
+/// Label used for modules that live at the top level, outside any package
+const ROOT_PACKAGE: &str = "<root>";
+
+/// Communities of the visible (non-orphan, optionally non-namespace) modules
+pub fn detect<T: GraphId>(
+    graph: &DependencyGraph<T>,
+    include_orphans: bool,
+    include_namespace_packages: bool,
+) -> Vec<Vec<T>> {
+    let orphans: HashSet<T> = if include_orphans {
+        HashSet::new()
+    } else {
+        graph.orphans_in(OrphanScope::Graph, include_namespace_packages)
+    };
+    let members: HashSet<T> = graph
+        .nodes()
+        .filter(|n| !orphans.contains(*n))
+        .filter(|n| include_namespace_packages || !graph.is_namespace_package(n))
+        .cloned()
+        .collect();
+    graph.detect_communities(Some(&members))
+}
+
+/// Segment paths of every node that has other nodes nested below it
+fn group_paths<T: GraphId>(graph: &DependencyGraph<T>) -> HashSet<Vec<String>> {
+    graph
+        .nodes()
+        .flat_map(|n| {
+            let segments = n.segments();
+            (1..segments.len()).map(move |end| segments[..end].to_vec())
+        })
+        .collect()
+}
+
+/// Package a node is declared in: a package node (one standing for the modules nested
+/// below it, like a Python `__init__`) is its own package, anything else belongs to its
+/// parent
+fn declared_package<T: GraphId>(node: &T, groups: &HashSet<Vec<String>>) -> String {
+    let mut segments = node.segments();
+    if !(node.represents_group() && groups.contains(&segments)) {
+        segments.pop();
+    }
+    if segments.is_empty() {
+        ROOT_PACKAGE.to_string()
+    } else {
+        T::join_segments(&segments)
+    }
+}
+
+/// Where detected communities and declared packages disagree
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mismatch {
+    /// A community whose members live in several packages (package -> members)
+    SpansPackages {
+        community: usize,
+        packages: BTreeMap<String, Vec<String>>,
+    },
+    /// A package whose modules fall into several communities
+    SplitPackage {
+        package: String,
+        communities: Vec<usize>,
+    },
+}
+
+/// Compare `communities` with the package structure. Communities are numbered from 1
+/// in the order given; singleton communities never count as a mismatch.
+pub fn find_mismatches<T: GraphId>(
+    graph: &DependencyGraph<T>,
+    communities: &[Vec<T>],
+) -> Vec<Mismatch> {
+    let groups = group_paths(graph);
+    let mut mismatches = Vec::new();
+    let mut package_communities: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+
+    for (index, members) in communities.iter().enumerate() {
+        if members.len() < 2 {
+            continue;
+        }
+        let mut packages: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for member in members {
+            packages
+                .entry(declared_package(member, &groups))
+                .or_default()
+                .push(member.to_dotted());
+        }
+        for package in packages.keys() {
+            package_communities
+                .entry(package.clone())
+                .or_default()
+                .push(index + 1);
+        }
+        if packages.len() > 1 {
+            mismatches.push(Mismatch::SpansPackages {
+                community: index + 1,
+                packages,
+            });
+        }
+    }
+
+    mismatches.extend(
+        package_communities
+            .into_iter()
+            .filter(|(_, communities)| communities.len() > 1)
+            .map(|(package, communities)| Mismatch::SplitPackage {
+                package,
+                communities,
+            }),
+    );
+    mismatches
+}
+
+/// Render the communities and their mismatches with the package structure
+pub fn render_report<T: GraphId>(graph: &DependencyGraph<T>, communities: &[Vec<T>]) -> String {
+    let mut output = format!(
+        "Detected {} (modularity {:.2})\n",
+        if communities.len() == 1 {
+            "1 community".to_string()
+        } else {
+            format!("{} communities", communities.len())
+        },
+        graph.modularity(communities)
+    );
+
+    for (index, members) in communities.iter().enumerate() {
+        let names: Vec<String> = members.iter().map(GraphId::to_dotted).collect();
+        output.push_str(&format!(
+            "\nCommunity {} ({}): {}\n",
+            index + 1,
+            if members.len() == 1 {
+                "1 module".to_string()
+            } else {
+                format!("{} modules", members.len())
+            },
+            names.join(", ")
+        ));
+    }
+
+    let mismatches = find_mismatches(graph, communities);
+    if mismatches.is_empty() {
+        output.push_str("\nCommunities match the package structure\n");
+        return output;
+    }
+
+    output.push_str("\nPackage mismatches:\n");
+    for mismatch in &mismatches {
+        match mismatch {
+            Mismatch::SpansPackages {
+                community,
+                packages,
+            } => {
+                let listed: Vec<String> = packages
+                    .iter()
+                    .map(|(package, members)| format!("{package} ({})", members.join(", ")))
+                    .collect();
+                output.push_str(&format!(
+                    "    community {community} behaves like one package but lives in {}: {}\n",
+                    packages.len(),
+                    listed.join("; ")
+                ));
+            }
+            Mismatch::SplitPackage {
+                package,
+                communities,
+            } => {
+                let listed: Vec<String> = communities.iter().map(usize::to_string).collect();
+                output.push_str(&format!(
+                    "    {package} is split across communities {}\n",
+                    listed.join(", ")
+                ));
+            }
+        }
+    }
+    output
+}
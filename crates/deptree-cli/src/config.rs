@@ -0,0 +1,337 @@
+//! `deptree.toml` configuration file
+//!
+//! Declares which analyzers run over which directories of a repository, plus
+//! cross-language bridges used to merge their graphs.
+
+use deptree_graph::filters::{EdgeDirection, FilterConfig, FilterPreset};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Default configuration file name, looked up in the analyzed root
+pub const CONFIG_FILE_NAME: &str = "deptree.toml";
+
+/// Errors that can occur while loading the configuration file
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read config file {0}: {1}")]
+    ReadError(PathBuf, std::io::Error),
+
+    #[error("Failed to parse config file {0}: {1}")]
+    ParseError(PathBuf, toml::de::Error),
+}
+
+/// Top-level configuration
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub python: Option<PythonConfig>,
+    pub go: Option<GoConfig>,
+    pub proto: Option<ProtoConfig>,
+    pub grouping: GroupingConfig,
+    pub bridges: Vec<Bridge>,
+    pub contracts: Vec<Contract>,
+    pub budgets: Vec<Budget>,
+    pub services: Vec<Service>,
+    pub presets: Vec<Preset>,
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// `[python]` section: a Python project to analyze
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PythonConfig {
+    /// Project root, relative to the config file
+    pub path: PathBuf,
+    /// Source root, relative to the config file (defaults to auto-detection)
+    #[serde(default)]
+    pub source_root: Option<PathBuf>,
+    /// Glob patterns to exclude from script discovery
+    #[serde(default)]
+    pub exclude_scripts: Vec<String>,
+    /// Skip script discovery entirely (see `--no-scripts`)
+    #[serde(default)]
+    pub no_scripts: bool,
+    /// Draw every script inside its directory's cluster
+    #[serde(default)]
+    pub cluster_scripts: bool,
+    /// Category of the scripts below each directory, e.g. `ops = "automation"`
+    #[serde(default)]
+    pub script_categories: BTreeMap<String, String>,
+    /// Python version the sources target, e.g. `"3.8"` (see `--python-version`)
+    #[serde(default)]
+    pub python_version: Option<String>,
+    /// Module name of the files or directories below each path (relative to `path`),
+    /// e.g. `"gen/protos" = "company.protos"` (see `--module-map`)
+    #[serde(default)]
+    pub module_map: BTreeMap<PathBuf, String>,
+    /// Lifecycle state of the modules below each module name, e.g.
+    /// `"app.legacy" = "sunset"` (see `--lifecycle`)
+    #[serde(default)]
+    pub lifecycle: BTreeMap<String, String>,
+}
+
+/// `[go]` section: a Go module to analyze
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GoConfig {
+    /// Module root (the directory containing go.mod), relative to the config file
+    pub path: PathBuf,
+    /// Include `_test.go` files when collecting imports
+    #[serde(default)]
+    pub include_tests: bool,
+}
+
+/// `[proto]` section: a tree of Protobuf schemas to analyze
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProtoConfig {
+    /// Directory containing the .proto files, relative to the config file
+    pub path: PathBuf,
+    /// Import roots relative to `path` (defaults to `path` itself)
+    #[serde(default)]
+    pub proto_paths: Vec<PathBuf>,
+}
+
+/// `[grouping]` section: when packages are drawn as clusters (see `--group-min-children`
+/// and `--group-max-depth`, which take precedence)
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GroupingConfig {
+    pub min_children: Option<NonZeroUsize>,
+    pub max_depth: Option<usize>,
+}
+
+/// `[[bridges]]` entry: a declared cross-language dependency where `from` depends on `to`.
+///
+/// Both ends are written as `<language>:<name>`, e.g. `python:svc.client_gen` or
+/// `proto:svc.v1` (a proto package) or `proto:svc/v1/user.proto` (a single schema).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Bridge {
+    pub from: String,
+    pub to: String,
+}
+
+/// `[[contracts]]` entry: an architectural rule checked by the `check` subcommand.
+///
+/// Modules are written as `<language>:<name>` and cover the named node plus every node
+/// nested below it (e.g. `python:app.api` covers `python:app.api.views`). Indirect
+/// imports count unless stated otherwise.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+pub enum Contract {
+    /// `source_modules` must not import `forbidden_modules`
+    Forbidden {
+        name: String,
+        source_modules: Vec<String>,
+        forbidden_modules: Vec<String>,
+        /// Only flag direct imports
+        #[serde(default)]
+        allow_indirect_imports: bool,
+    },
+    /// None of `modules` may import another
+    Independence { name: String, modules: Vec<String> },
+    /// Each layer may import the layers below it, never the ones above (highest first)
+    Layers { name: String, layers: Vec<String> },
+}
+
+impl Contract {
+    pub fn name(&self) -> &str {
+        match self {
+            Contract::Forbidden { name, .. }
+            | Contract::Independence { name, .. }
+            | Contract::Layers { name, .. } => name,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Contract::Forbidden { .. } => "forbidden",
+            Contract::Independence { .. } => "independence",
+            Contract::Layers { .. } => "layers",
+        }
+    }
+}
+
+/// `[[budgets]]` entry: numeric limits on the dependencies of a module, checked by the
+/// `check` subcommand.
+///
+/// `module` is written as `<language>:<name>` and covers the named node plus every node
+/// nested below it, like contract modules. Only modules outside it are counted, and at
+/// least one limit must be set.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Budget {
+    pub name: String,
+    pub module: String,
+    /// Most modules it may import, directly or indirectly
+    #[serde(default)]
+    pub max_upstream: Option<usize>,
+    /// Most modules that may import it, directly or indirectly
+    #[serde(default)]
+    pub max_downstream: Option<usize>,
+    /// Most modules that may import it directly
+    #[serde(default)]
+    pub max_fan_in: Option<usize>,
+    /// Most modules it may import directly
+    #[serde(default)]
+    pub max_fan_out: Option<usize>,
+}
+
+/// `[[services]]` entry: a deployable service built from the listed modules, used by
+/// `polyglot --impact-services`.
+///
+/// Modules are written as `<language>:<name>` and cover the named node plus every node
+/// nested below it, like contract modules. A module may belong to several services.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Service {
+    pub name: String,
+    pub modules: Vec<String>,
+}
+
+/// `[[presets]]` entry: a named set of viewer filters, offered in the Cytoscape output.
+///
+/// Roots are node IDs as shown in the graph (`<language>:<name>` for polyglot graphs).
+/// Toggles default to the viewer's initial state.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Preset {
+    pub name: String,
+    #[serde(default)]
+    pub upstream: Vec<String>,
+    #[serde(default)]
+    pub downstream: Vec<String>,
+    #[serde(default)]
+    pub max_distance: Option<usize>,
+    /// Wildcard patterns of scripts to hide
+    #[serde(default)]
+    pub exclude_patterns: Vec<String>,
+    #[serde(default = "default_true")]
+    pub show_orphans: bool,
+    #[serde(default = "default_true")]
+    pub show_namespaces: bool,
+    #[serde(default = "default_true")]
+    pub highlighted_only: bool,
+    #[serde(default)]
+    pub highlight_neighbors: usize,
+    #[serde(default)]
+    pub fade_filtered: bool,
+    #[serde(default)]
+    pub edge_direction: EdgeDirection,
+    /// Measure `max_distance` along imports weighted by `count` or a numeric annotation
+    #[serde(default)]
+    pub weight_key: Option<String>,
+}
+
+/// `[profiles.<name>]` entry: a saved set of `polyglot`/`check` options, selected with
+/// `--profile <name>` instead of passing the equivalent flags.
+///
+/// Fields mirror the command-line flags (`downstream` and `upstream` take lists of
+/// `<language>:<name>` nodes).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    pub format: Option<String>,
+    pub upstream: Vec<String>,
+    pub downstream: Vec<String>,
+    pub max_rank: Option<usize>,
+    pub show_all: bool,
+    pub include_orphans: bool,
+    pub communities: bool,
+    pub edges: Option<String>,
+    pub hide_modules: Vec<String>,
+    pub filter_state: Option<String>,
+    /// Names of the contracts `check` runs (all of them when unset)
+    pub contracts: Option<Vec<String>>,
+    /// Names of the budgets `check` runs (all of them when unset)
+    pub budgets: Option<Vec<String>>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Preset {
+    /// The preset as sent to the viewer
+    pub fn to_filter_preset(&self) -> FilterPreset {
+        FilterPreset {
+            name: self.name.clone(),
+            config: FilterConfig {
+                show_orphans: self.show_orphans,
+                show_namespaces: self.show_namespaces,
+                exclude_patterns: self.exclude_patterns.clone(),
+                upstream_roots: self.upstream.clone(),
+                downstream_roots: self.downstream.clone(),
+                max_distance: self.max_distance,
+                highlighted_only: self.highlighted_only,
+                highlight_neighbors: self.highlight_neighbors,
+                fade_filtered: self.fade_filtered,
+                edge_direction: self.edge_direction,
+                weight_key: self.weight_key.clone(),
+            },
+        }
+    }
+}
+
+impl Config {
+    /// Look up a profile by name
+    pub fn profile(&self, name: &str) -> Result<&Profile, String> {
+        self.profiles.get(name).ok_or_else(|| {
+            let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            if known.is_empty() {
+                format!("Unknown profile '{name}': the configuration defines no profiles")
+            } else {
+                format!("Unknown profile '{name}' (available: {})", known.join(", "))
+            }
+        })
+    }
+
+    /// The contracts a profile selects, in declaration order (all of them without a profile)
+    pub fn contracts_for(&self, profile: Option<&Profile>) -> Result<Vec<Contract>, String> {
+        let Some(names) = profile.and_then(|p| p.contracts.as_ref()) else {
+            return Ok(self.contracts.clone());
+        };
+        if let Some(unknown) = names
+            .iter()
+            .find(|name| !self.contracts.iter().any(|c| c.name() == name.as_str()))
+        {
+            return Err(format!("Profile refers to unknown contract '{unknown}'"));
+        }
+        Ok(self
+            .contracts
+            .iter()
+            .filter(|contract| names.iter().any(|name| name == contract.name()))
+            .cloned()
+            .collect())
+    }
+
+    /// The budgets a profile selects, in declaration order (all of them without a profile)
+    pub fn budgets_for(&self, profile: Option<&Profile>) -> Result<Vec<Budget>, String> {
+        let Some(names) = profile.and_then(|p| p.budgets.as_ref()) else {
+            return Ok(self.budgets.clone());
+        };
+        if let Some(unknown) = names
+            .iter()
+            .find(|name| !self.budgets.iter().any(|b| &b.name == *name))
+        {
+            return Err(format!("Profile refers to unknown budget '{unknown}'"));
+        }
+        Ok(self
+            .budgets
+            .iter()
+            .filter(|budget| names.contains(&budget.name))
+            .cloned()
+            .collect())
+    }
+
+    /// Load and parse a configuration file
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ConfigError::ReadError(path.to_path_buf(), e))?;
+        toml::from_str(&content).map_err(|e| ConfigError::ParseError(path.to_path_buf(), e))
+    }
+}
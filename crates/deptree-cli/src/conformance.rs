@@ -0,0 +1,421 @@
+//! Architecture conformance (`conformance` subcommand)
+//!
+//! Compares the imports of a project with an intended architecture: components made of
+//! packages, and the dependencies allowed between them, declared in a TOML file. As in
+//! reflexion models (Structure101, Sotograph), every dependency between two components is
+//! convergent (allowed and present), divergent (present without being allowed), or absent
+//! (allowed but never used).
+//!
+//! ```toml
+//! [components]
+//! ui = ["app.web", "app.cli"]
+//! core = ["app.core"]
+//! storage = ["app.db"]
+//!
+//! [[allow]]
+//! from = "ui"
+//! to = ["core"]
+//!
+//! [[allow]]
+//! from = "core"
+//! to = ["storage"]
+//! ```
+
+use deptree_graph::{
+    DependencyGraph, GRAPH_DATA_VERSION, GraphConfig, GraphData, GraphEdge, GraphId, GraphNode,
+    escape,
+};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Errors that can occur while loading an architecture
+#[derive(Error, Debug)]
+pub enum ConformanceError {
+    #[error("Failed to read architecture file {0}: {1}")]
+    ReadError(PathBuf, std::io::Error),
+
+    #[error("Failed to parse architecture file {0}: {1}")]
+    ParseError(PathBuf, toml::de::Error),
+
+    #[error("Allowed dependency refers to '{0}', which is not a declared component")]
+    UnknownComponent(String),
+
+    #[error("Package '{0}' belongs to both '{1}' and '{2}'")]
+    DuplicatePackage(String, String, String),
+}
+
+/// An allowed dependency: `from` may import each of `to`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Allow {
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+/// Intended architecture: components by name with their packages (each covering the
+/// package and everything nested below it), and the dependencies allowed between them
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Architecture {
+    pub components: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub allow: Vec<Allow>,
+}
+
+impl Architecture {
+    pub fn load(path: &Path) -> Result<Self, ConformanceError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| ConformanceError::ReadError(path.to_path_buf(), e))?;
+        let architecture: Architecture = toml::from_str(&text)
+            .map_err(|e| ConformanceError::ParseError(path.to_path_buf(), e))?;
+        architecture.validate()?;
+        Ok(architecture)
+    }
+
+    fn validate(&self) -> Result<(), ConformanceError> {
+        let mut owners: HashMap<&str, &str> = HashMap::new();
+        for (component, packages) in &self.components {
+            for package in packages {
+                if let Some(owner) = owners.insert(package.trim(), component) {
+                    return Err(ConformanceError::DuplicatePackage(
+                        package.trim().to_string(),
+                        owner.to_string(),
+                        component.clone(),
+                    ));
+                }
+            }
+        }
+        for allow in &self.allow {
+            for component in std::iter::once(&allow.from).chain(&allow.to) {
+                if !self.components.contains_key(component) {
+                    return Err(ConformanceError::UnknownComponent(component.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Component of a module: the one declaring the longest package containing it
+    fn component_of(&self, segments: &[String]) -> Option<&str> {
+        self.components
+            .iter()
+            .flat_map(|(component, packages)| {
+                packages
+                    .iter()
+                    .map(move |package| (component.as_str(), package))
+            })
+            .filter_map(|(component, package)| {
+                let package: Vec<&str> = package.trim().split('.').collect();
+                (segments.len() >= package.len()
+                    && segments.iter().zip(&package).all(|(a, b)| a == b))
+                .then_some((package.len(), component))
+            })
+            .max_by_key(|(length, _)| *length)
+            .map(|(_, component)| component)
+    }
+}
+
+/// How a dependency between two components relates to the architecture
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Status {
+    /// Allowed and present
+    Convergent,
+    /// Present but not allowed
+    Divergent,
+    /// Allowed but not present
+    Absent,
+}
+
+impl Status {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Status::Convergent => "convergent",
+            Status::Divergent => "divergent",
+            Status::Absent => "absent",
+        }
+    }
+}
+
+/// A dependency between two components, with the module imports behind it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentEdge {
+    pub from: String,
+    pub to: String,
+    pub status: Status,
+    /// Imports from a module of `from` to a module of `to`, as dotted `(importer, imported)`
+    pub imports: Vec<(String, String)>,
+}
+
+/// Conformance of a project's imports to an architecture
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conformance {
+    /// Components with their number of modules
+    pub components: Vec<(String, usize)>,
+    /// By status, then by name
+    pub edges: Vec<ComponentEdge>,
+    /// Modules outside every component, dotted and sorted
+    pub unmapped: Vec<String>,
+}
+
+impl Conformance {
+    pub fn count(&self, status: Status) -> usize {
+        self.edges
+            .iter()
+            .filter(|edge| edge.status == status)
+            .count()
+    }
+}
+
+/// Check the imports of `graph` against `architecture`. Imports inside a component and
+/// imports of modules outside every component are not part of the comparison.
+pub fn conformance<T: GraphId>(
+    graph: &DependencyGraph<T>,
+    architecture: &Architecture,
+) -> Conformance {
+    let mut members: BTreeMap<&str, usize> = architecture
+        .components
+        .keys()
+        .map(|component| (component.as_str(), 0))
+        .collect();
+    let mut owners: HashMap<&T, &str> = HashMap::new();
+    let mut unmapped = Vec::new();
+    for module in graph.nodes() {
+        match architecture.component_of(&module.segments()) {
+            Some(component) => {
+                owners.insert(module, component);
+                *members.entry(component).or_default() += 1;
+            }
+            None => unmapped.push(module.to_dotted()),
+        }
+    }
+    unmapped.sort();
+
+    let mut present: BTreeMap<(&str, &str), BTreeSet<(String, String)>> = BTreeMap::new();
+    for (from, to, _) in graph.edges() {
+        if let (Some(&source), Some(&target)) = (owners.get(from), owners.get(to))
+            && source != target
+        {
+            present
+                .entry((source, target))
+                .or_default()
+                .insert((from.to_dotted(), to.to_dotted()));
+        }
+    }
+    let allowed: BTreeSet<(&str, &str)> = architecture
+        .allow
+        .iter()
+        .flat_map(|allow| {
+            allow
+                .to
+                .iter()
+                .map(move |to| (allow.from.as_str(), to.as_str()))
+        })
+        .filter(|(from, to)| from != to)
+        .collect();
+
+    let mut edges: Vec<ComponentEdge> = present
+        .keys()
+        .chain(&allowed)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|&(from, to)| {
+            let imports = present.get(&(from, to));
+            ComponentEdge {
+                from: from.to_string(),
+                to: to.to_string(),
+                status: match (allowed.contains(&(from, to)), imports.is_some()) {
+                    (true, true) => Status::Convergent,
+                    (false, _) => Status::Divergent,
+                    (true, false) => Status::Absent,
+                },
+                imports: imports.into_iter().flatten().cloned().collect(),
+            }
+        })
+        .collect();
+    edges.sort_by(|a, b| (a.status, &a.from, &a.to).cmp(&(b.status, &b.from, &b.to)));
+
+    Conformance {
+        components: members
+            .into_iter()
+            .map(|(component, count)| (component.to_string(), count))
+            .collect(),
+        edges,
+        unmapped,
+    }
+}
+
+/// Conformance as tables of components and of the dependencies between them, with the
+/// imports behind each divergent one
+pub fn render_report(conformance: &Conformance) -> String {
+    let mut out = format!(
+        "Conformance: {} convergent, {} divergent, {} absent\n",
+        conformance.count(Status::Convergent),
+        conformance.count(Status::Divergent),
+        conformance.count(Status::Absent)
+    );
+
+    let width = conformance
+        .components
+        .iter()
+        .map(|(component, _)| component.len())
+        .chain(["Component".len()])
+        .max()
+        .unwrap_or_default();
+    let _ = writeln!(out, "\n{:width$}  Modules", "Component");
+    for (component, count) in &conformance.components {
+        let _ = writeln!(out, "{component:width$}  {count:>7}");
+    }
+
+    if !conformance.edges.is_empty() {
+        let label = |edge: &ComponentEdge| format!("{} -> {}", edge.from, edge.to);
+        let width = conformance
+            .edges
+            .iter()
+            .map(|edge| label(edge).len())
+            .chain(["Dependency".len()])
+            .max()
+            .unwrap_or_default();
+        let _ = writeln!(out, "\n{:width$}  {:10}  Imports", "Dependency", "Status");
+        for edge in &conformance.edges {
+            let _ = writeln!(
+                out,
+                "{:width$}  {:10}  {:>7}",
+                label(edge),
+                edge.status.as_str(),
+                edge.imports.len()
+            );
+            if edge.status == Status::Divergent {
+                for (importer, imported) in &edge.imports {
+                    let _ = writeln!(out, "    {importer} -> {imported}");
+                }
+            }
+        }
+    }
+
+    if !conformance.unmapped.is_empty() {
+        let _ = writeln!(
+            out,
+            "\n{} module(s) outside every component: {}",
+            conformance.unmapped.len(),
+            conformance.unmapped.join(", ")
+        );
+    }
+    out
+}
+
+/// Color of a dependency with `status`: convergent green, divergent red, absent grey
+fn color(status: Status) -> &'static str {
+    match status {
+        Status::Convergent => "#2e7d32",
+        Status::Divergent => "#c62828",
+        Status::Absent => "#9e9e9e",
+    }
+}
+
+/// DOT output of the component graph: convergent dependencies green, divergent ones red
+/// and bold, absent ones grey and dashed, each labeled with its number of imports
+pub fn render_dot(conformance: &Conformance) -> String {
+    let mut out = String::from("digraph architecture {\n");
+    out.push_str("    rankdir=LR;\n");
+    out.push_str("    node [shape=box];\n");
+    out.push_str(
+        "    // Note: Convergent dependencies are green, divergent ones red, absent ones grey and dashed\n",
+    );
+    for (component, count) in &conformance.components {
+        let _ = writeln!(
+            out,
+            "    \"{}\" [label=\"{}\\n{count} module(s)\"];",
+            escape::dot_string(component),
+            escape::dot_string(component)
+        );
+    }
+    for edge in &conformance.edges {
+        let style = match edge.status {
+            Status::Convergent => "",
+            Status::Divergent => ", penwidth=2",
+            Status::Absent => ", style=dashed",
+        };
+        let color = color(edge.status);
+        let _ = writeln!(
+            out,
+            "    \"{}\" -> \"{}\" [color=\"{color}\", fontcolor=\"{color}\", label=\"{}\"{style}];",
+            escape::dot_string(&edge.from),
+            escape::dot_string(&edge.to),
+            edge.imports.len()
+        );
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Graph data of the component graph for the viewer: one node per component, and edges
+/// tagged with their status and colored like [`render_dot`]
+pub fn graph_data(conformance: &Conformance) -> GraphData {
+    let connected: BTreeSet<&str> = conformance
+        .edges
+        .iter()
+        .flat_map(|edge| [edge.from.as_str(), edge.to.as_str()])
+        .collect();
+    let nodes = conformance
+        .components
+        .iter()
+        .map(|(component, _)| GraphNode {
+            id: component.clone(),
+            node_type: "module".to_string(),
+            is_orphan: !connected.contains(component.as_str()),
+            highlighted: None,
+            parent: None,
+            import_time_us: None,
+            coverage: None,
+            category: None,
+            lifecycle: None,
+            generated: None,
+            change: None,
+            annotations: None,
+            position: None,
+            style: None,
+            href: None,
+        })
+        .collect();
+    let edges = conformance
+        .edges
+        .iter()
+        .map(|edge| {
+            let mut style = BTreeMap::from([
+                ("line-color".to_string(), color(edge.status).to_string()),
+                (
+                    "target-arrow-color".to_string(),
+                    color(edge.status).to_string(),
+                ),
+            ]);
+            if edge.status == Status::Absent {
+                style.insert("line-style".to_string(), "dashed".to_string());
+            }
+            GraphEdge {
+                source: edge.from.clone(),
+                target: edge.to.clone(),
+                kind: None,
+                tags: Some(vec![edge.status.as_str().to_string()]),
+                count: (edge.imports.len() > 1).then_some(edge.imports.len()),
+                change: None,
+                style: Some(style),
+            }
+        })
+        .collect();
+
+    GraphData {
+        version: GRAPH_DATA_VERSION,
+        nodes,
+        edges,
+        // Components without any dependency are still part of the architecture
+        config: Some(GraphConfig {
+            include_orphans: true,
+            include_namespaces: true,
+            highlighted_modules: None,
+            presets: None,
+        }),
+    }
+}
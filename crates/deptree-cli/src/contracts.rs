@@ -0,0 +1,221 @@
+//! Import contracts
+//!
+//! Evaluates the `[[contracts]]` declared in `deptree.toml` against a cross-language
+//! graph. Contract types follow import-linter: forbidden, independence, and layers.
+
+use crate::config::Contract;
+use crate::polyglot::{PolyglotError, PolyglotGraph, PolyglotNode};
+use deptree_graph::GraphId;
+use thiserror::Error;
+
+/// Errors that can occur while evaluating contracts
+#[derive(Error, Debug)]
+pub enum ContractError {
+    #[error("Contract '{0}': {1}")]
+    InvalidModule(String, Box<PolyglotError>),
+
+    #[error("Contract '{0}' refers to '{1}', which matches no analyzed module")]
+    UnknownModule(String, String),
+
+    #[error("No [[contracts]] or [[budgets]] configured")]
+    NoContracts,
+}
+
+/// A module reference from a contract or budget, covering the node and everything
+/// nested below it
+pub(crate) struct ModuleSpec {
+    pub(crate) label: String,
+    segments: Vec<String>,
+}
+
+impl ModuleSpec {
+    /// The spec written as `input`; `Ok(None)` if it covers no node of the graph
+    pub(crate) fn resolve(
+        input: &str,
+        graph: &PolyglotGraph,
+    ) -> Result<Option<Self>, PolyglotError> {
+        let node = PolyglotNode::parse(input)?;
+        let spec = ModuleSpec {
+            label: input.trim().to_string(),
+            segments: node.segments(),
+        };
+        Ok(graph.nodes().any(|n| spec.covers(n)).then_some(spec))
+    }
+
+    fn parse(contract: &str, input: &str, graph: &PolyglotGraph) -> Result<Self, ContractError> {
+        Self::resolve(input, graph)
+            .map_err(|e| ContractError::InvalidModule(contract.to_string(), Box::new(e)))?
+            .ok_or_else(|| {
+                ContractError::UnknownModule(contract.to_string(), input.trim().to_string())
+            })
+    }
+
+    pub(crate) fn covers(&self, node: &PolyglotNode) -> bool {
+        node.segments().starts_with(&self.segments)
+    }
+}
+
+/// An import chain breaking a contract: `chain` starts in `importer` and ends in `imported`
+#[derive(Debug, Clone)]
+pub struct Violation {
+    pub importer: String,
+    pub imported: String,
+    pub chain: Vec<PolyglotNode>,
+}
+
+/// Outcome of evaluating one contract
+#[derive(Debug, Clone)]
+pub struct ContractResult {
+    pub name: String,
+    pub kind: &'static str,
+    pub violations: Vec<Violation>,
+    /// Violations tolerated by a baseline file (and removed from `violations`)
+    pub baselined: usize,
+}
+
+impl ContractResult {
+    pub fn is_kept(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Chains through which modules covered by `importer` reach modules covered by `imported`
+fn find_violations(
+    graph: &PolyglotGraph,
+    importer: &ModuleSpec,
+    imported: &ModuleSpec,
+    direct_only: bool,
+) -> Vec<Violation> {
+    let mut sources: Vec<&PolyglotNode> = graph.nodes().filter(|n| importer.covers(n)).collect();
+    sources.sort_by_key(|n| n.to_dotted());
+
+    sources
+        .into_iter()
+        .filter_map(|source| {
+            // Targets nested in the importer's own spec (overlapping specs) don't count
+            graph.shortest_chain(source, |n| imported.covers(n) && !importer.covers(n))
+        })
+        .filter(|chain| !direct_only || chain.len() == 2)
+        .map(|chain| Violation {
+            importer: importer.label.clone(),
+            imported: imported.label.clone(),
+            chain,
+        })
+        .collect()
+}
+
+fn parse_specs(
+    contract: &str,
+    inputs: &[String],
+    graph: &PolyglotGraph,
+) -> Result<Vec<ModuleSpec>, ContractError> {
+    inputs
+        .iter()
+        .map(|input| ModuleSpec::parse(contract, input, graph))
+        .collect()
+}
+
+/// Evaluate a single contract against the graph
+pub fn check_contract(
+    graph: &PolyglotGraph,
+    contract: &Contract,
+) -> Result<ContractResult, ContractError> {
+    let name = contract.name();
+    let mut violations = Vec::new();
+
+    match contract {
+        Contract::Forbidden {
+            source_modules,
+            forbidden_modules,
+            allow_indirect_imports,
+            ..
+        } => {
+            let sources = parse_specs(name, source_modules, graph)?;
+            let forbidden = parse_specs(name, forbidden_modules, graph)?;
+            for source in &sources {
+                for target in &forbidden {
+                    violations.extend(find_violations(
+                        graph,
+                        source,
+                        target,
+                        *allow_indirect_imports,
+                    ));
+                }
+            }
+        }
+        Contract::Independence { modules, .. } => {
+            let specs = parse_specs(name, modules, graph)?;
+            for (i, importer) in specs.iter().enumerate() {
+                for (j, imported) in specs.iter().enumerate() {
+                    if i != j {
+                        violations.extend(find_violations(graph, importer, imported, false));
+                    }
+                }
+            }
+        }
+        Contract::Layers { layers, .. } => {
+            // Layers are listed highest first; lower layers must not reach higher ones
+            let specs = parse_specs(name, layers, graph)?;
+            for (higher_index, higher) in specs.iter().enumerate() {
+                for lower in &specs[higher_index + 1..] {
+                    violations.extend(find_violations(graph, lower, higher, false));
+                }
+            }
+        }
+    }
+
+    Ok(ContractResult {
+        name: name.to_string(),
+        kind: contract.kind(),
+        violations,
+        baselined: 0,
+    })
+}
+
+/// Evaluate every contract, in configuration order
+pub fn check_contracts(
+    graph: &PolyglotGraph,
+    contracts: &[Contract],
+) -> Result<Vec<ContractResult>, ContractError> {
+    if contracts.is_empty() {
+        return Err(ContractError::NoContracts);
+    }
+    contracts
+        .iter()
+        .map(|contract| check_contract(graph, contract))
+        .collect()
+}
+
+/// Render a human-readable report listing every contract and the chains breaking it
+pub fn render_report(results: &[ContractResult]) -> String {
+    let mut output = String::new();
+
+    for result in results {
+        let status = if result.is_kept() { "KEPT" } else { "BROKEN" };
+        let baselined = if result.baselined > 0 {
+            format!(" ({} baselined)", result.baselined)
+        } else {
+            String::new()
+        };
+        output.push_str(&format!(
+            "{status:<6} {} [{}]{baselined}\n",
+            result.name, result.kind
+        ));
+        for violation in &result.violations {
+            let chain: Vec<String> = violation.chain.iter().map(GraphId::to_dotted).collect();
+            output.push_str(&format!(
+                "    {} must not import {}: {}\n",
+                violation.importer,
+                violation.imported,
+                chain.join(" -> ")
+            ));
+        }
+    }
+
+    let broken = results.iter().filter(|r| !r.is_kept()).count();
+    output.push_str(&format!(
+        "\nContracts: {} kept, {broken} broken\n",
+        results.len() - broken
+    ));
+    output
+}
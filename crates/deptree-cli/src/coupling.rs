@@ -0,0 +1,174 @@
+//! Cross-team coupling (`--team-coupling`)
+//!
+//! With ownership annotations (`{"app.billing": {"team": "payments"}}`, see
+//! `--annotations`), counts the imports between every pair of teams and lists the modules
+//! whose direct dependencies mostly belong to other teams. Those are the coupling hotspots
+//! a reorganization has to look at first: moving the module, or its dependencies, to one
+//! team removes the most cross-team imports.
+
+use deptree_graph::{DependencyGraph, GraphId};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+/// A module most of whose dependencies are owned by other teams
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hotspot {
+    pub module: String,
+    pub team: String,
+    /// Direct dependencies with an owner
+    pub dependencies: usize,
+    /// Dependencies owned by other teams, by team
+    pub foreign: BTreeMap<String, usize>,
+}
+
+impl Hotspot {
+    pub fn foreign_count(&self) -> usize {
+        self.foreign.values().sum()
+    }
+}
+
+/// Imports between the teams owning the modules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TeamCoupling {
+    /// Annotation naming the owner
+    pub key: String,
+    /// Teams, sorted
+    pub teams: Vec<String>,
+    /// Imports by (importing team, imported team), a team's imports of its own modules
+    /// included
+    pub pairs: BTreeMap<(String, String), usize>,
+    /// Most foreign dependencies first
+    pub hotspots: Vec<Hotspot>,
+    /// Modules without the annotation, left out of every count
+    pub unowned: usize,
+}
+
+/// Coupling between the owners of the modules of `graph`, the values of their `key`
+/// annotation
+pub fn team_coupling<T: GraphId>(graph: &DependencyGraph<T>, key: &str) -> TeamCoupling {
+    let owner = |module: &T| graph.annotation(module, key);
+    let mut teams = BTreeSet::new();
+    let mut pairs: BTreeMap<(String, String), usize> = BTreeMap::new();
+    let mut hotspots = Vec::new();
+    let mut unowned = 0;
+    for module in graph.nodes() {
+        let Some(team) = owner(module) else {
+            unowned += 1;
+            continue;
+        };
+        teams.insert(team.to_string());
+
+        let mut dependencies = 0;
+        let mut foreign: BTreeMap<String, usize> = BTreeMap::new();
+        for dependency in graph.direct_dependencies(module) {
+            let Some(other) = owner(dependency) else {
+                continue;
+            };
+            dependencies += 1;
+            *pairs
+                .entry((team.to_string(), other.to_string()))
+                .or_default() += 1;
+            if other != team {
+                *foreign.entry(other.to_string()).or_default() += 1;
+            }
+        }
+        let hotspot = Hotspot {
+            module: module.to_dotted(),
+            team: team.to_string(),
+            dependencies,
+            foreign,
+        };
+        if hotspot.foreign_count() * 2 > dependencies {
+            hotspots.push(hotspot);
+        }
+    }
+    // Highest share of foreign dependencies first, then the most of them
+    hotspots.sort_by(|a, b| {
+        (b.foreign_count() * a.dependencies)
+            .cmp(&(a.foreign_count() * b.dependencies))
+            .then(b.foreign_count().cmp(&a.foreign_count()))
+            .then(a.module.cmp(&b.module))
+    });
+
+    TeamCoupling {
+        key: key.to_string(),
+        teams: teams.into_iter().collect(),
+        pairs,
+        hotspots,
+        unowned,
+    }
+}
+
+/// Render the coupling as a matrix of imports between teams (rows import columns) and the
+/// list of hotspots
+pub fn render_report(coupling: &TeamCoupling) -> String {
+    let mut out = format!(
+        "Imports between teams (annotation '{}'; rows import columns):\n\n",
+        coupling.key
+    );
+    let width = coupling
+        .teams
+        .iter()
+        .map(String::len)
+        .max()
+        .unwrap_or_default();
+    let _ = write!(out, "{:width$}", "");
+    for team in &coupling.teams {
+        let _ = write!(out, "  {team:>w$}", w = team.len().max(5));
+    }
+    out.push('\n');
+    for from in &coupling.teams {
+        let _ = write!(out, "{from:width$}");
+        for to in &coupling.teams {
+            let count = coupling
+                .pairs
+                .get(&(from.clone(), to.clone()))
+                .copied()
+                .unwrap_or_default();
+            let _ = write!(out, "  {count:>w$}", w = to.len().max(5));
+        }
+        out.push('\n');
+    }
+
+    let crossing: usize = coupling
+        .pairs
+        .iter()
+        .filter(|((from, to), _)| from != to)
+        .map(|(_, count)| count)
+        .sum();
+    let _ = writeln!(
+        out,
+        "\n{crossing} of {} imports between owned modules cross teams",
+        coupling.pairs.values().sum::<usize>()
+    );
+
+    if coupling.hotspots.is_empty() {
+        out.push_str("\nNo module depends mostly on other teams\n");
+    } else {
+        out.push_str("\nModules depending mostly on other teams:\n");
+        for hotspot in &coupling.hotspots {
+            let foreign: Vec<String> = hotspot
+                .foreign
+                .iter()
+                .map(|(team, count)| format!("{team} ({count})"))
+                .collect();
+            let _ = writeln!(
+                out,
+                "    {} ({}): {} of {} dependencies owned by {}",
+                hotspot.module,
+                hotspot.team,
+                hotspot.foreign_count(),
+                hotspot.dependencies,
+                foreign.join(", ")
+            );
+        }
+    }
+    if coupling.unowned > 0 {
+        let _ = writeln!(
+            out,
+            "\n{} module(s) without a '{}' annotation are left out",
+            coupling.unowned, coupling.key
+        );
+    }
+    out
+}
@@ -0,0 +1,129 @@
+//! Test coverage overlay
+//!
+//! Reads a Cobertura `coverage.xml` report (as written by `coverage xml` or
+//! `pytest --cov-report=xml`) and attaches per-module line coverage to the nodes of
+//! a Python dependency graph.
+
+use crate::python::{ModulePath, PythonGraph};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Header every SQLite database (such as a raw `.coverage` data file) starts with
+const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+
+/// Errors that can occur while loading a coverage report
+#[derive(Error, Debug)]
+pub enum CoverageError {
+    #[error("Failed to read coverage report {0}: {1}")]
+    ReadError(PathBuf, std::io::Error),
+
+    #[error("Failed to parse coverage report {0}: {1}")]
+    ParseError(PathBuf, roxmltree::Error),
+
+    #[error(
+        "{0} is a raw coverage.py data file; convert it with `coverage xml` and pass the resulting coverage.xml"
+    )]
+    UnsupportedDataFile(PathBuf),
+}
+
+/// Line coverage of one source file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileCoverage {
+    /// File name as written in the report, relative to one of its `<source>` roots
+    pub filename: String,
+    pub covered_lines: usize,
+    pub total_lines: usize,
+}
+
+impl FileCoverage {
+    /// Covered lines as a percentage (0-100, one decimal); files without statements
+    /// count as fully covered
+    pub fn percent(&self) -> f64 {
+        if self.total_lines == 0 {
+            100.0
+        } else {
+            (self.covered_lines as f64 * 1000.0 / self.total_lines as f64).round() / 10.0
+        }
+    }
+}
+
+/// Parse a Cobertura XML report.
+///
+/// Coverage is recomputed from the `<line hits=..>` entries rather than the
+/// `line-rate` attributes so files split across several `<class>` elements are
+/// merged correctly. Results are sorted by file name.
+pub fn parse_cobertura(content: &str) -> Result<Vec<FileCoverage>, roxmltree::Error> {
+    let document = roxmltree::Document::parse(content)?;
+    let mut files: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+
+    for class in document.descendants().filter(|n| n.has_tag_name("class")) {
+        let Some(filename) = class.attribute("filename") else {
+            continue;
+        };
+        let counts = files.entry(filename.replace('\\', "/")).or_default();
+        for line in class.descendants().filter(|n| n.has_tag_name("line")) {
+            let hits = line
+                .attribute("hits")
+                .and_then(|h| h.parse::<u64>().ok())
+                .unwrap_or(0);
+            counts.1 += 1;
+            if hits > 0 {
+                counts.0 += 1;
+            }
+        }
+    }
+
+    Ok(files
+        .into_iter()
+        .map(|(filename, (covered_lines, total_lines))| FileCoverage {
+            filename,
+            covered_lines,
+            total_lines,
+        })
+        .collect())
+}
+
+/// Read and parse a coverage report file
+pub fn load(path: &Path) -> Result<Vec<FileCoverage>, CoverageError> {
+    let bytes = std::fs::read(path).map_err(|e| CoverageError::ReadError(path.to_path_buf(), e))?;
+    if bytes.starts_with(SQLITE_HEADER) {
+        return Err(CoverageError::UnsupportedDataFile(path.to_path_buf()));
+    }
+    let content = String::from_utf8_lossy(&bytes);
+    parse_cobertura(&content).map_err(|e| CoverageError::ParseError(path.to_path_buf(), e))
+}
+
+/// Module a report file name refers to in `graph`.
+///
+/// Report paths are relative to whatever `<source>` coverage.py was configured with
+/// (project root, `src/`, ...), so leading directories are dropped until the dotted
+/// name matches an analyzed module.
+pub(crate) fn match_module(filename: &str, graph: &PythonGraph) -> Option<ModulePath> {
+    let stem = filename.strip_suffix(".py")?;
+    let mut parts: Vec<String> = stem
+        .split('/')
+        .filter(|s| !s.is_empty() && *s != ".")
+        .map(String::from)
+        .collect();
+    if parts.last().map(String::as_str) == Some("__init__") {
+        parts.pop();
+    }
+
+    (0..parts.len())
+        .map(|start| ModulePath(parts[start..].to_vec()))
+        .find(|module| graph.contains(module))
+}
+
+/// Attach coverage percentages to the matching modules of `graph`.
+/// Returns how many modules were matched.
+pub fn apply_to_graph(graph: &mut PythonGraph, files: &[FileCoverage]) -> usize {
+    let mut matched = 0;
+    for file in files {
+        if let Some(module) = match_module(&file.filename, graph) {
+            graph.set_coverage(&module, file.percent());
+            matched += 1;
+        }
+    }
+    matched
+}
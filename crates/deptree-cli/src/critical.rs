@@ -0,0 +1,74 @@
+//! Critical import path (`--critical-path`)
+//!
+//! Prints the longest import chain below an entry module: the chain that sets how deep
+//! Python has to recurse into imports before the entry module finishes loading, and so
+//! the first place to cut when cold starts are slow. Import cycles count as one step, as
+//! in the `max_depth` metric, but their members are listed so the chain stays readable
+//! as a sequence of actual imports.
+
+use deptree_graph::{DependencyGraph, GraphId};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Render the longest chain from `entry` (see [`DependencyGraph::longest_chain`]) with the
+/// import cycles it passes through, and each module's import time when the graph carries
+/// an import profile. `None` if `entry` is not in the graph.
+pub fn render_report<T: GraphId>(graph: &DependencyGraph<T>, entry: &T) -> Option<String> {
+    let chain = graph.longest_chain(entry)?;
+    let cycles = graph.find_cycles();
+    let cycle_of: HashMap<&T, usize> = cycles
+        .iter()
+        .enumerate()
+        .flat_map(|(i, cycle)| cycle.iter().map(move |member| (member, i)))
+        .collect();
+    // Steps inside a cycle don't add depth
+    let depth = chain
+        .windows(2)
+        .filter(
+            |pair| match (cycle_of.get(&pair[0]), cycle_of.get(&pair[1])) {
+                (Some(a), Some(b)) => a != b,
+                _ => true,
+            },
+        )
+        .count();
+
+    let mut output = format!(
+        "Longest import chain from {}: depth {depth}, {} module{}\n\n",
+        entry.to_dotted(),
+        chain.len(),
+        if chain.len() == 1 { "" } else { "s" }
+    );
+    let profiled = chain
+        .iter()
+        .any(|module| graph.import_cost(module).is_some());
+    if profiled {
+        let _ = writeln!(output, "{:>10}  {:>10}  module", "self_ms", "cumul_ms");
+    }
+    let mut previous_cycle = None;
+    for (i, module) in chain.iter().enumerate() {
+        let arrow = if i == 0 { "  " } else { "->" };
+        let mut line = if profiled {
+            match graph.import_cost(module) {
+                Some(cost) => format!(
+                    "{:>10.2}  {:>10.2}  {arrow} {}",
+                    cost.self_us as f64 / 1000.0,
+                    cost.cumulative_us as f64 / 1000.0,
+                    module.to_dotted()
+                ),
+                None => format!("{:>10}  {:>10}  {arrow} {}", "-", "-", module.to_dotted()),
+            }
+        } else {
+            format!("{arrow} {}", module.to_dotted())
+        };
+        let cycle = cycle_of.get(module).copied();
+        if let Some(index) = cycle
+            && previous_cycle != cycle
+        {
+            let members: Vec<String> = cycles[index].iter().map(GraphId::to_dotted).collect();
+            let _ = write!(line, "  (import cycle: {})", members.join(", "));
+        }
+        previous_cycle = cycle;
+        let _ = writeln!(output, "{line}");
+    }
+    Some(output)
+}
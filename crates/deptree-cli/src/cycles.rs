@@ -0,0 +1,140 @@
+//! New-cycle prevention (`--forbid-new-cycles`)
+//!
+//! Compares the import cycles of the analyzed graph with those of a baseline graph (a
+//! `--format json` export) so that CI fails only when a change introduces a cycle. A
+//! cycle is new unless all of its modules already share one cycle of the baseline, so
+//! existing cycles may stay or shrink but not grow.
+
+use deptree_graph::{DependencyGraph, GraphData, GraphDataError, GraphId};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Errors that can occur while loading a baseline graph
+#[derive(Error, Debug)]
+pub enum CycleBaselineError {
+    #[error("Failed to read baseline graph {0}: {1}")]
+    ReadError(PathBuf, std::io::Error),
+
+    #[error("Invalid baseline graph {0}: {1}")]
+    InvalidGraph(PathBuf, GraphDataError),
+}
+
+/// Read a baseline graph written with `--format json`
+pub fn load_baseline<T: GraphId + std::str::FromStr>(
+    path: &Path,
+) -> Result<DependencyGraph<T>, CycleBaselineError> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| CycleBaselineError::ReadError(path.to_path_buf(), e))?;
+    GraphData::from_json(&json)
+        .and_then(|data| DependencyGraph::from_graph_data(&data))
+        .map_err(|e| CycleBaselineError::InvalidGraph(path.to_path_buf(), e))
+}
+
+/// An import cycle missing from the baseline
+#[derive(Debug, Clone)]
+pub struct NewCycle<T> {
+    /// Modules of the cycle (a strongly connected component), sorted
+    pub members: Vec<T>,
+    /// One closed import chain through an import the baseline has in no cycle; starts
+    /// and ends with the same module
+    pub chain: Vec<T>,
+}
+
+/// Cycles of `current` whose modules are not all part of a single cycle of `baseline`,
+/// ordered by their first member
+pub fn new_cycles<T: GraphId>(
+    current: &DependencyGraph<T>,
+    baseline: &DependencyGraph<T>,
+) -> Vec<NewCycle<T>> {
+    let baseline_cycle: HashMap<T, usize> = baseline
+        .find_cycles()
+        .into_iter()
+        .enumerate()
+        .flat_map(|(i, members)| members.into_iter().map(move |member| (member, i)))
+        .collect();
+    let same_baseline_cycle = |from: &T, to: &T| {
+        baseline_cycle
+            .get(from)
+            .is_some_and(|cycle| baseline_cycle.get(to) == Some(cycle))
+            && (from != to || baseline.edge_multiplicity(from, to) > 0)
+    };
+
+    let mut found = Vec::new();
+    for members in current.find_cycles() {
+        let member_set: HashSet<&T> = members.iter().collect();
+        let imports: Vec<(&T, &T)> = members
+            .iter()
+            .flat_map(|from| {
+                current
+                    .direct_dependencies(from)
+                    .into_iter()
+                    .filter(|to| member_set.contains(to))
+                    .map(move |to| (from, to))
+            })
+            .collect();
+        let Some((from, to)) = imports
+            .into_iter()
+            .find(|(from, to)| !same_baseline_cycle(from, to))
+        else {
+            continue;
+        };
+        let chain = if from == to {
+            vec![from.clone(), to.clone()]
+        } else {
+            let mut chain = vec![from.clone()];
+            // Back from `to` to `from`, which stays within the cycle
+            chain.extend(
+                current
+                    .shortest_chain(to, |n| n == from)
+                    .unwrap_or_default(),
+            );
+            chain
+        };
+        found.push(NewCycle { members, chain });
+    }
+    found
+}
+
+/// Report of the new cycles, each import of the chain with the places it is written
+pub fn render_report<T: GraphId>(
+    graph: &DependencyGraph<T>,
+    cycles: &[NewCycle<T>],
+    existing: usize,
+) -> String {
+    let mut out = String::new();
+    for cycle in cycles {
+        let members: Vec<String> = cycle.members.iter().map(GraphId::to_dotted).collect();
+        let _ = writeln!(out, "New import cycle between {}:", members.join(", "));
+        for pair in cycle.chain.windows(2) {
+            let (from, to) = (&pair[0], &pair[1]);
+            let sites: Vec<String> = graph
+                .import_sites(from, to)
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            let provenance = if sites.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", sites.join(", "))
+            };
+            let _ = writeln!(
+                out,
+                "  {} -> {}{provenance}",
+                from.to_dotted(),
+                to.to_dotted()
+            );
+        }
+        out.push('\n');
+    }
+    if cycles.is_empty() {
+        let _ = writeln!(
+            out,
+            "No new import cycles ({existing} existing cycle(s) are in the baseline)"
+        );
+    } else {
+        let _ = writeln!(out, "{} new import cycle(s)", cycles.len());
+    }
+    out
+}
@@ -1,10 +1,10 @@
-use deptree_graph::GraphData;
+use deptree_graph::{GraphData, escape};
 
 /// Render Cytoscape graph data into the bundled HTML template.
 pub fn render_cytoscape_html(graph_data: &GraphData) -> Result<String, Box<dyn std::error::Error>> {
     const TEMPLATE: &str = include_str!("../templates/cytoscape.html");
 
-    let graph_json = serde_json::to_string(graph_data)?;
+    let graph_json = escape::json_for_html(&serde_json::to_string(graph_data)?);
     let html = TEMPLATE.replace("<!--GRAPH_DATA_PLACEHOLDER-->", &graph_json);
 
     Ok(html)
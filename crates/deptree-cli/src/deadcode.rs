@@ -0,0 +1,226 @@
+//! Dead-code candidates (`--dead-code`)
+//!
+//! A module is a candidate when running the project never loads it: no entry point (a
+//! script, a `__main__` or test module, a script or plugin declared in pyproject.toml, or an
+//! `--entry-point`) imports it, directly or indirectly, and no dynamic import with a literal
+//! name in loaded code names it. A dynamic import of a computed name can load anything, so
+//! the certainty of each candidate depends on those call sites: `high` when there are none,
+//! `medium` when one has no constant start, and `low` when the constant start of one
+//! (`f"plugins.{name}"`) covers the candidate or a module below it.
+
+use crate::emit::{AnalysisTrace, DynamicName};
+use crate::python::{ModulePath, PythonGraph};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+/// How sure it is that nothing loads a candidate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Confidence {
+    /// A computed dynamic import may name it
+    Low,
+    /// A computed dynamic import may name any module
+    Medium,
+    /// No computed dynamic import is loaded
+    High,
+}
+
+impl Confidence {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Confidence::Low => "low",
+            Confidence::Medium => "medium",
+            Confidence::High => "high",
+        }
+    }
+}
+
+/// A module nothing was found to load
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub module: ModulePath,
+    pub confidence: Confidence,
+}
+
+/// A loaded call importing a computed name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComputedImport {
+    pub file: String,
+    pub line: usize,
+    /// Constant start of the name, empty when it has none
+    pub prefix: String,
+}
+
+/// Result of [`dead_code`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadCode {
+    pub modules: usize,
+    pub entry_points: usize,
+    /// Most certain first
+    pub candidates: Vec<Candidate>,
+    pub computed: Vec<ComputedImport>,
+}
+
+/// Whether pytest collects the module
+fn is_test_module(module: &ModulePath) -> bool {
+    module.0.last().is_some_and(|name| {
+        name.starts_with("test_") || name.ends_with("_test") || name == "conftest"
+    })
+}
+
+/// Node of `module`, or of the closest package above it (a reference like `app.cli.main`
+/// may name an attribute)
+fn node_of(graph: &PythonGraph, module: &ModulePath) -> Option<ModulePath> {
+    (1..=module.0.len())
+        .rev()
+        .map(|len| ModulePath(module.0[..len].to_vec()))
+        .find(|prefix| graph.contains(prefix))
+}
+
+/// Candidates of the analyzed project. `declared` are further entry points (from
+/// pyproject.toml and `--entry-point`); `trace` provides the dynamic imports of each file.
+pub fn dead_code(graph: &PythonGraph, trace: &AnalysisTrace, declared: &[ModulePath]) -> DeadCode {
+    let mut entry_points: Vec<ModulePath> = graph
+        .nodes()
+        .filter(|module| {
+            graph.is_script(module)
+                || module.0.last().is_some_and(|name| name == "__main__")
+                || is_test_module(module)
+        })
+        .cloned()
+        .chain(declared.iter().filter_map(|module| node_of(graph, module)))
+        .collect();
+    entry_points.sort_by_key(|module| module.to_dotted());
+    entry_points.dedup();
+
+    let dynamic: HashMap<&str, Vec<_>> = trace
+        .files
+        .iter()
+        .filter(|file| !file.dynamic_imports.is_empty())
+        .map(|file| (file.module.as_str(), file.dynamic_imports.iter().collect()))
+        .collect();
+
+    // Loading a module runs its packages and the modules it names dynamically too, which
+    // may load further modules
+    let mut loaded: HashSet<ModulePath> = HashSet::new();
+    let mut roots = entry_points.clone();
+    while !roots.is_empty() {
+        let reached = graph.find_upstream(&roots, None);
+        roots.clear();
+        for module in reached.into_keys() {
+            if loaded.contains(&module) {
+                continue;
+            }
+            roots.extend(
+                (1..module.0.len())
+                    .map(|len| ModulePath(module.0[..len].to_vec()))
+                    .filter(|package| graph.contains(package)),
+            );
+            for import in dynamic
+                .get(module.to_dotted().as_str())
+                .into_iter()
+                .flatten()
+            {
+                if let DynamicName::Literal { module: name } = &import.name {
+                    let named = ModulePath(name.split('.').map(String::from).collect());
+                    roots.extend(node_of(graph, &named));
+                }
+            }
+            loaded.insert(module);
+        }
+        roots.retain(|module| !loaded.contains(module));
+    }
+
+    let mut computed: Vec<ComputedImport> = trace
+        .files
+        .iter()
+        .filter(|file| {
+            loaded
+                .iter()
+                .any(|module| module.to_dotted() == file.module)
+        })
+        .flat_map(|file| {
+            file.dynamic_imports
+                .iter()
+                .filter_map(move |import| match &import.name {
+                    DynamicName::Computed { prefix } => Some(ComputedImport {
+                        file: file.file.clone(),
+                        line: import.line,
+                        prefix: prefix.clone(),
+                    }),
+                    DynamicName::Literal { .. } => None,
+                })
+        })
+        .collect();
+    computed.sort_by(|a, b| a.file.cmp(&b.file).then(a.line.cmp(&b.line)));
+
+    let unbounded = computed.iter().any(|import| import.prefix.is_empty());
+    let mut candidates: Vec<Candidate> = graph
+        .nodes()
+        .filter(|module| !loaded.contains(*module) && !graph.is_namespace_package(module))
+        .map(|module| {
+            let name = module.to_dotted();
+            let package = format!("{name}.");
+            let confidence = if computed.iter().any(|import| {
+                !import.prefix.is_empty()
+                    && (name.starts_with(&import.prefix) || import.prefix.starts_with(&package))
+            }) {
+                Confidence::Low
+            } else if unbounded {
+                Confidence::Medium
+            } else {
+                Confidence::High
+            };
+            Candidate {
+                module: module.clone(),
+                confidence,
+            }
+        })
+        .collect();
+    candidates.sort_by(|a, b| {
+        b.confidence
+            .cmp(&a.confidence)
+            .then_with(|| a.module.to_dotted().cmp(&b.module.to_dotted()))
+    });
+
+    DeadCode {
+        modules: graph.nodes().count(),
+        entry_points: entry_points.len(),
+        candidates,
+        computed,
+    }
+}
+
+/// Summary line, one `confidence  module` row per candidate, and the computed dynamic
+/// imports that lowered the confidence
+pub fn render_report(report: &DeadCode) -> String {
+    let mut output = format!(
+        "{} of {} modules unreachable from {} entry point{}\n",
+        report.candidates.len(),
+        report.modules,
+        report.entry_points,
+        if report.entry_points == 1 { "" } else { "s" }
+    );
+    if !report.candidates.is_empty() {
+        let _ = writeln!(output, "{:<10}  module", "confidence");
+        for candidate in &report.candidates {
+            let _ = writeln!(
+                output,
+                "{:<10}  {}",
+                candidate.confidence.as_str(),
+                candidate.module.to_dotted()
+            );
+        }
+    }
+    if !report.computed.is_empty() {
+        output.push_str("\nDynamic imports of computed names:\n");
+        for import in &report.computed {
+            let target = if import.prefix.is_empty() {
+                "any module".to_string()
+            } else {
+                format!("{}*", import.prefix)
+            };
+            let _ = writeln!(output, "  {}:{}  {target}", import.file, import.line);
+        }
+    }
+    output
+}
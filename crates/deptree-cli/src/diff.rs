@@ -0,0 +1,181 @@
+//! Graph diffs (`diff` subcommand)
+//!
+//! Compares two graphs exported with `--format json` (e.g. from the base branch and a pull
+//! request) and renders their union with every module and import classified as added,
+//! removed, or unchanged, so structural changes stand out in review.
+
+use deptree_graph::{GraphConfig, GraphData, GraphDataError, GraphEdge, GraphNode, escape};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Errors that can occur while reading the graphs to compare
+#[derive(Error, Debug)]
+pub enum DiffError {
+    #[error("Failed to read graph {0}: {1}")]
+    ReadError(PathBuf, std::io::Error),
+
+    #[error("Invalid graph {0}: {1}")]
+    InvalidGraph(PathBuf, GraphDataError),
+}
+
+/// Node types that only group other nodes (and are never diffed themselves)
+const GROUP_TYPES: [&str; 3] = ["namespace_group", "community", "group"];
+
+/// Read a graph written with `--format json`
+pub fn load_graph(path: &Path) -> Result<GraphData, DiffError> {
+    let json =
+        std::fs::read_to_string(path).map_err(|e| DiffError::ReadError(path.to_path_buf(), e))?;
+    GraphData::from_json(&json).map_err(|e| DiffError::InvalidGraph(path.to_path_buf(), e))
+}
+
+/// How an element differs between the two graphs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Added,
+    Removed,
+    Unchanged,
+}
+
+impl Change {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Change::Added => "added",
+            Change::Removed => "removed",
+            Change::Unchanged => "unchanged",
+        }
+    }
+
+    fn of(in_old: bool, in_new: bool) -> Self {
+        match (in_old, in_new) {
+            (false, _) => Change::Added,
+            (_, false) => Change::Removed,
+            _ => Change::Unchanged,
+        }
+    }
+}
+
+/// Union of `old` and `new`, sorted by id, with the `change` of every module and import
+/// set; modules keep their attributes from `new` unless they were removed. Group nodes
+/// are kept without a change so compound views still nest their modules.
+pub fn diff(old: &GraphData, new: &GraphData) -> GraphData {
+    let old_nodes: HashSet<&str> = old.nodes.iter().map(|n| n.id.as_str()).collect();
+    let new_nodes: HashSet<&str> = new.nodes.iter().map(|n| n.id.as_str()).collect();
+    let mut nodes: BTreeMap<&str, GraphNode> = BTreeMap::new();
+    for node in new.nodes.iter().chain(&old.nodes) {
+        nodes.entry(node.id.as_str()).or_insert_with(|| {
+            let mut node = node.clone();
+            if !GROUP_TYPES.contains(&node.node_type.as_str()) {
+                let id = node.id.as_str();
+                let change = Change::of(old_nodes.contains(id), new_nodes.contains(id));
+                node.change = Some(change.as_str().to_string());
+            }
+            node
+        });
+    }
+
+    let pair = |e: &GraphEdge| (e.source.clone(), e.target.clone());
+    let old_edges: HashSet<(String, String)> = old.edges.iter().map(pair).collect();
+    let new_edges: HashSet<(String, String)> = new.edges.iter().map(pair).collect();
+    let mut edges: BTreeMap<(String, String), GraphEdge> = BTreeMap::new();
+    for edge in new.edges.iter().chain(&old.edges) {
+        edges.entry(pair(edge)).or_insert_with_key(|key| {
+            let change = Change::of(old_edges.contains(key), new_edges.contains(key));
+            GraphEdge {
+                change: Some(change.as_str().to_string()),
+                ..edge.clone()
+            }
+        });
+    }
+
+    GraphData {
+        version: new.version,
+        nodes: nodes.into_values().collect(),
+        edges: edges.into_values().collect(),
+        // Removed modules are often orphans in the union, and still worth seeing
+        config: Some(GraphConfig {
+            include_orphans: true,
+            include_namespaces: true,
+            highlighted_modules: None,
+            presets: None,
+        }),
+    }
+}
+
+/// Graphviz attributes drawing an element with `change`
+fn dot_style(change: Option<&str>) -> &'static str {
+    match change {
+        Some("added") => "color=\"#2e7d32\", fontcolor=\"#2e7d32\", penwidth=2",
+        Some("removed") => "color=\"#c62828\", fontcolor=\"#c62828\", style=dashed",
+        _ => "color=\"#9e9e9e\", fontcolor=\"#9e9e9e\"",
+    }
+}
+
+/// DOT output of a diff: added modules and imports green, removed ones red and dashed,
+/// unchanged ones grey (drawn flat, without package clusters)
+pub fn render_dot(diff: &GraphData) -> String {
+    let mut out = String::from("digraph dependencies {\n");
+    out.push_str("    rankdir=LR;\n");
+    out.push_str(
+        "    // Note: Added elements are green, removed ones red and dashed, unchanged ones grey\n",
+    );
+    for node in &diff.nodes {
+        if GROUP_TYPES.contains(&node.node_type.as_str()) {
+            continue;
+        }
+        let shape = if node.node_type == "script" {
+            "shape=box, "
+        } else {
+            ""
+        };
+        let _ = writeln!(
+            out,
+            "    \"{}\" [{shape}{}];",
+            escape::dot_string(&node.id),
+            dot_style(node.change.as_deref())
+        );
+    }
+    for edge in &diff.edges {
+        let _ = writeln!(
+            out,
+            "    \"{}\" -> \"{}\" [{}];",
+            escape::dot_string(&edge.source),
+            escape::dot_string(&edge.target),
+            dot_style(edge.change.as_deref())
+        );
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Plain-text listing of what changed: `+`/`-` lines for modules, then for imports, and a
+/// summary line
+pub fn render_list(diff: &GraphData) -> String {
+    let marker = |change: Option<&str>| match change {
+        Some("added") => Some('+'),
+        Some("removed") => Some('-'),
+        _ => None,
+    };
+    let mut out = String::new();
+    let mut counts = [[0; 2]; 2];
+    for node in &diff.nodes {
+        if let Some(marker) = marker(node.change.as_deref()) {
+            let _ = writeln!(out, "{marker} {}", node.id);
+            counts[0][usize::from(marker == '-')] += 1;
+        }
+    }
+    for edge in &diff.edges {
+        if let Some(marker) = marker(edge.change.as_deref()) {
+            let _ = writeln!(out, "{marker} {} -> {}", edge.source, edge.target);
+            counts[1][usize::from(marker == '-')] += 1;
+        }
+    }
+    let [[added_nodes, removed_nodes], [added_edges, removed_edges]] = counts;
+    let _ = write!(
+        out,
+        "{added_nodes} module(s) added, {removed_nodes} removed; \
+         {added_edges} import(s) added, {removed_edges} removed"
+    );
+    out
+}
@@ -0,0 +1,131 @@
+//! Intermediate representations of the Python analysis (`python --emit`)
+//!
+//! A missing edge was dropped by one of three steps: extracting the imports of a file,
+//! finding the modules the project has, or resolving an imported name against them.
+//! `--emit` prints what one of the steps produced as JSON instead of the graph, so the
+//! step at fault can be seen without adding print statements to the analyzer.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Which intermediate representation to print
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emit {
+    /// The imports extracted from each file
+    Imports,
+    /// Every module found, with its file
+    Modules,
+    /// How each imported name was resolved
+    Resolution,
+}
+
+impl Emit {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "imports" => Some(Emit::Imports),
+            "modules" => Some(Emit::Modules),
+            "resolution" => Some(Emit::Resolution),
+            _ => None,
+        }
+    }
+}
+
+/// What the analyzer saw and decided, filled in by
+/// [`crate::python::analyze_project_traced`]. Paths are relative to the project root.
+#[derive(Debug, Default, Serialize)]
+pub struct AnalysisTrace {
+    pub files: Vec<FileTrace>,
+    /// Dotted module name to the file providing it
+    pub modules: BTreeMap<String, String>,
+    pub resolution: Vec<Resolution>,
+}
+
+/// Imports extracted from one file
+#[derive(Debug, Serialize)]
+pub struct FileTrace {
+    pub file: String,
+    pub module: String,
+    /// "module", "extension", or "script"
+    pub kind: &'static str,
+    /// Whether the file did not parse and its imports were recovered by scanning lines
+    pub recovered: bool,
+    pub imports: Vec<TracedImport>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dynamic_imports: Vec<DynamicImport>,
+}
+
+/// One import statement as extracted
+#[derive(Debug, Serialize)]
+pub struct TracedImport {
+    pub line: usize,
+    pub column: usize,
+    /// The statement, normalized (e.g. `from ..core import models`)
+    pub statement: String,
+    /// "import_time" or "deferred"
+    pub kind: &'static str,
+    /// Platforms the statement can run on, when enclosing checks narrow them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platforms: Option<Vec<&'static str>>,
+}
+
+/// A call importing a module by name at run time (`importlib.import_module(...)`,
+/// `__import__(...)`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DynamicImport {
+    pub line: usize,
+    #[serde(flatten)]
+    pub name: DynamicName,
+}
+
+/// Name passed to a dynamic import; leading dots are resolved against the package of the
+/// importing module
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "name", rename_all = "snake_case")]
+pub enum DynamicName {
+    /// A string literal naming `module`
+    Literal { module: String },
+    /// A name computed at run time; `prefix` is its constant start (e.g. `plugins.` for
+    /// `f"plugins.{name}"`), empty when it has none
+    Computed { prefix: String },
+}
+
+/// How one imported name (or a whole `import` statement) was resolved
+#[derive(Debug, Serialize)]
+pub struct Resolution {
+    pub file: String,
+    pub line: usize,
+    pub statement: String,
+    /// The name imported by a `from` statement; absent for `import` and `*`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub outcome: Outcome,
+}
+
+/// Decision taken for an imported name
+#[derive(Debug, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum Outcome {
+    /// An edge to `target` was added; `via` is what matched: "module", "package",
+    /// "submodule", or "lazy export"
+    Resolved { target: String, via: &'static str },
+    /// A relative import that names nothing in the project
+    Unresolved { reason: String },
+    /// An absolute import of a module the project does not have (standard library or
+    /// third party)
+    External { module: String },
+    /// Left out before resolution, e.g. by `--platform`
+    Skipped { reason: String },
+}
+
+impl AnalysisTrace {
+    /// The representation selected by `emit`, as pretty-printed JSON
+    pub fn to_json(&self, emit: Emit) -> String {
+        let json = match emit {
+            Emit::Imports => serde_json::to_string_pretty(&self.files),
+            Emit::Modules => serde_json::to_string_pretty(&self.modules),
+            Emit::Resolution => serde_json::to_string_pretty(&self.resolution),
+        };
+        json.expect("Analysis traces always serialize") + "\n"
+    }
+}
@@ -0,0 +1,412 @@
+//! Configuration explanation (`python --explain-config`)
+//!
+//! Shows what the Python analyzer would read without building the graph: the source root
+//! and how it was found, the exclusion patterns in effect, how many files each walked
+//! directory contributes, and every path left out with the reason. The directories are
+//! walked the same way [`python::analyze_project_with_options`] walks them.
+
+use crate::python::{
+    self, AnalysisOptions, Exclusion, ModulePath, PythonAnalysisError, SourceLayout,
+};
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// A directory walked for source files
+#[derive(Debug, Clone)]
+pub struct WalkedRoot {
+    /// "source root", "scripts", "module map PATH=MODULE", "namespace portion PACKAGE", or
+    /// "path dependency NAME"
+    pub label: String,
+    pub path: PathBuf,
+    /// Source files below it that become modules or scripts
+    pub files: usize,
+}
+
+/// Why a file or directory is left out of the graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// A path component is a built-in exclusion (`venv`, `__pycache__`, `build`, ...)
+    DefaultExclusion(String),
+    /// Matches an `--exclude-scripts` pattern
+    ExcludePattern(String),
+    /// Opts out with a `# deptree: ignore-file` comment
+    IgnoreFileMarker,
+    /// Names no module (an `__init__.py` directly in a walked directory)
+    NoModuleName,
+    /// Module of a path dependency that the project or an earlier library already has
+    Shadowed(String),
+    /// Path dependency without a detectable source root
+    NoSourceRoot,
+}
+
+impl SkipReason {
+    fn describe(&self) -> String {
+        match self {
+            SkipReason::DefaultExclusion(name) => format!("default exclusion '{name}'"),
+            SkipReason::ExcludePattern(pattern) => format!("--exclude-scripts '{pattern}'"),
+            SkipReason::IgnoreFileMarker => "# deptree: ignore-file".to_string(),
+            SkipReason::NoModuleName => "names no module".to_string(),
+            SkipReason::Shadowed(module) => format!("{module} is already defined by the project"),
+            SkipReason::NoSourceRoot => "path dependency without a Python source root".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SkippedPath {
+    pub path: PathBuf,
+    pub reason: SkipReason,
+}
+
+/// Everything `--explain-config` reports
+#[derive(Debug, Clone)]
+pub struct ConfigExplanation {
+    pub project_root: PathBuf,
+    /// The source root with the layout it was found by; `None` if detection found none
+    pub source_root: Option<(PathBuf, SourceLayout)>,
+    /// Candidates checked before the source root that contain no Python packages
+    pub rejected_roots: Vec<(PathBuf, SourceLayout)>,
+    /// Plausible source roots with their module counts when detection cannot choose
+    /// between them (the analysis then asks for `--source-root`)
+    pub ambiguous_roots: Vec<(PathBuf, SourceLayout, usize)>,
+    pub exclude_patterns: Vec<String>,
+    pub roots: Vec<WalkedRoot>,
+    pub skipped: Vec<SkippedPath>,
+}
+
+/// Resolve the configuration of a Python analysis of `project_root` and walk its
+/// directories, without parsing any file
+pub fn explain(
+    project_root: &Path,
+    source_root: Option<&Path>,
+    exclude_patterns: &[String],
+    options: &AnalysisOptions,
+) -> Result<ConfigExplanation, PythonAnalysisError> {
+    if !project_root.is_dir() {
+        return Err(PythonAnalysisError::InvalidRoot(project_root.to_path_buf()));
+    }
+    let search = match source_root {
+        Some(root) => python::SourceRootSearch {
+            found: Some((root.to_path_buf(), SourceLayout::Explicit)),
+            rejected: Vec::new(),
+            ambiguous: Vec::new(),
+        },
+        None => python::find_source_root(project_root)?,
+    };
+    let found = search.found;
+    let mut explanation = ConfigExplanation {
+        project_root: project_root.to_path_buf(),
+        source_root: found.clone(),
+        rejected_roots: search.rejected,
+        ambiguous_roots: search.ambiguous,
+        exclude_patterns: exclude_patterns.to_vec(),
+        roots: Vec::new(),
+        skipped: Vec::new(),
+    };
+    let Some((source_root, _)) = found else {
+        return Ok(explanation);
+    };
+    let skipped = &mut explanation.skipped;
+
+    let module_map: Vec<(PathBuf, ModulePath)> = options
+        .module_map
+        .iter()
+        .map(|(path, module)| (project_root.join(path), module.clone()))
+        .collect();
+    let module_of = |path: &Path| {
+        python::mapped_module(path, &module_map)
+            .or_else(|| ModulePath::from_file_path(path, &source_root))
+    };
+    let is_module_file = |path: &Path| {
+        path.extension().is_some_and(|ext| ext == "py") || python::is_cython_file(path)
+    };
+
+    // The source root, then mapped paths outside it (walked without custom exclusions)
+    let mut module_roots = vec![(
+        "source root".to_string(),
+        source_root.clone(),
+        exclude_patterns,
+    )];
+    for ((root, _), (path, module)) in module_map.iter().zip(&options.module_map) {
+        if !root.starts_with(&source_root) {
+            let label = format!("module map {}={}", path.display(), module.to_dotted());
+            module_roots.push((label, root.clone(), &[][..]));
+        }
+    }
+    let mut own_modules: HashSet<ModulePath> = HashSet::new();
+    for (label, root, excludes) in module_roots {
+        let mut files = 0;
+        for path in walk(&root, excludes, &[], is_module_file, skipped) {
+            match module_of(&path) {
+                None => skipped.push(SkippedPath {
+                    path,
+                    reason: SkipReason::NoModuleName,
+                }),
+                Some(_) if python::has_ignore_file_marker(&path) => skipped.push(SkippedPath {
+                    path,
+                    reason: SkipReason::IgnoreFileMarker,
+                }),
+                Some(module) => {
+                    own_modules.insert(module);
+                    files += 1;
+                }
+            }
+        }
+        explanation.roots.push(WalkedRoot {
+            label,
+            path: root,
+            files,
+        });
+    }
+
+    // Portions of the source root's namespace packages elsewhere in the project
+    let mapped_roots: Vec<&Path> = module_map.iter().map(|(root, _)| root.as_path()).collect();
+    let portions = if source_root == project_root {
+        Vec::new()
+    } else {
+        python::namespace_portions(project_root, &source_root, &mapped_roots, exclude_patterns)
+    };
+    for (portion, root) in &portions {
+        let init = portion.join("__init__.py");
+        let mut files = 0;
+        for path in walk(portion, &[], &[], is_module_file, skipped) {
+            if path == init {
+                continue;
+            }
+            let reason = match ModulePath::from_file_path(&path, root) {
+                None => SkipReason::NoModuleName,
+                Some(_) if python::has_ignore_file_marker(&path) => SkipReason::IgnoreFileMarker,
+                Some(module) if own_modules.contains(&module) => {
+                    SkipReason::Shadowed(module.to_dotted())
+                }
+                Some(module) => {
+                    own_modules.insert(module);
+                    files += 1;
+                    continue;
+                }
+            };
+            skipped.push(SkippedPath { path, reason });
+        }
+        let package = ModulePath::from_file_path(&init, root)
+            .map(|package| package.to_dotted())
+            .unwrap_or_default();
+        explanation.roots.push(WalkedRoot {
+            label: format!("namespace portion {package}"),
+            path: portion.clone(),
+            files,
+        });
+    }
+
+    // Scripts: Python files elsewhere in the project
+    if source_root != project_root && !options.skip_scripts {
+        let mut pruned: Vec<&Path> = vec![source_root.as_path()];
+        pruned.extend(&mapped_roots);
+        pruned.extend(portions.iter().map(|(portion, _)| portion.as_path()));
+        let is_script = |path: &Path| path.extension().is_some_and(|ext| ext == "py");
+        let mut files = 0;
+        for path in walk(project_root, exclude_patterns, &pruned, is_script, skipped) {
+            if python::has_ignore_file_marker(&path) {
+                skipped.push(SkippedPath {
+                    path,
+                    reason: SkipReason::IgnoreFileMarker,
+                });
+            } else {
+                files += 1;
+            }
+        }
+        explanation.roots.push(WalkedRoot {
+            label: "scripts".to_string(),
+            path: project_root.to_path_buf(),
+            files,
+        });
+    }
+
+    if !options.skip_path_dependencies {
+        let mut library_modules: HashSet<ModulePath> = HashSet::new();
+        for (name, library_root) in python::collect_path_dependencies(project_root)? {
+            let Ok(library_source_root) = python::detect_source_root(&library_root) else {
+                skipped.push(SkippedPath {
+                    path: library_root,
+                    reason: SkipReason::NoSourceRoot,
+                });
+                continue;
+            };
+            let mut files = 0;
+            for path in walk(&library_source_root, &[], &[], is_module_file, skipped) {
+                let reason = match ModulePath::from_file_path(&path, &library_source_root) {
+                    None => SkipReason::NoModuleName,
+                    Some(module)
+                        if own_modules.contains(&module) || library_modules.contains(&module) =>
+                    {
+                        SkipReason::Shadowed(module.to_dotted())
+                    }
+                    Some(_) if python::has_ignore_file_marker(&path) => {
+                        SkipReason::IgnoreFileMarker
+                    }
+                    Some(module) => {
+                        library_modules.insert(module);
+                        files += 1;
+                        continue;
+                    }
+                };
+                skipped.push(SkippedPath { path, reason });
+            }
+            explanation.roots.push(WalkedRoot {
+                label: format!("path dependency {name}"),
+                path: library_source_root,
+                files,
+            });
+        }
+    }
+    Ok(explanation)
+}
+
+/// Files below `root` accepted by `is_source`, walking the way the analyzer does:
+/// `pruned` directories are left out silently, excluded ones are recorded in `skipped`
+/// (only directories and source files, as nothing else would be read anyway)
+fn walk(
+    root: &Path,
+    exclude_patterns: &[String],
+    pruned: &[&Path],
+    is_source: impl Fn(&Path) -> bool,
+    skipped: &mut Vec<SkippedPath>,
+) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let entries = WalkDir::new(root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            let path = entry.path();
+            if pruned.contains(&path) {
+                return false;
+            }
+            let Ok(relative) = path.strip_prefix(root) else {
+                return false;
+            };
+            let Some(exclusion) =
+                python::exclusion_reason(relative, entry.file_type().is_dir(), exclude_patterns)
+            else {
+                return true;
+            };
+            if entry.file_type().is_dir() || is_source(path) {
+                let reason = match exclusion {
+                    Exclusion::Default(name) => SkipReason::DefaultExclusion(name),
+                    Exclusion::Pattern(pattern) => SkipReason::ExcludePattern(pattern),
+                };
+                skipped.push(SkippedPath {
+                    path: path.to_path_buf(),
+                    reason,
+                });
+            }
+            false
+        });
+    for entry in entries.filter_map(Result::ok) {
+        if entry.file_type().is_file() && is_source(entry.path()) {
+            files.push(entry.into_path());
+        }
+    }
+    files
+}
+
+/// `path` relative to the project root where possible, directories with a trailing `/`
+fn display_path(path: &Path, project_root: &Path) -> String {
+    let mut shown = match path.strip_prefix(project_root) {
+        Ok(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+        Ok(relative) => relative.display().to_string(),
+        Err(_) => path.display().to_string(),
+    };
+    if path.is_dir() && shown != "." && !shown.ends_with('/') {
+        shown.push('/');
+    }
+    shown
+}
+
+/// Human-readable report of an explanation
+pub fn render_report(explanation: &ConfigExplanation) -> String {
+    let root = &explanation.project_root;
+    let mut out = String::new();
+    let _ = writeln!(out, "Project root: {}", root.display());
+    match &explanation.source_root {
+        Some((path, layout)) => {
+            let _ = writeln!(
+                out,
+                "Source root: {} ({})",
+                display_path(path, root),
+                layout.describe()
+            );
+        }
+        None => out.push_str(
+            "Source root: none found (pass --source-root or declare it in pyproject.toml)\n",
+        ),
+    }
+    for (path, layout) in &explanation.rejected_roots {
+        let _ = writeln!(
+            out,
+            "  Passed over {} ({}): no Python packages",
+            display_path(path, root),
+            layout.describe()
+        );
+    }
+    if !explanation.ambiguous_roots.is_empty() {
+        let _ = writeln!(
+            out,
+            "  Ambiguous: pass --source-root with one of these, or --source-root {}:",
+            python::ALL_SOURCE_ROOTS
+        );
+        for (path, layout, modules) in &explanation.ambiguous_roots {
+            let _ = writeln!(
+                out,
+                "    {} ({}): {modules} modules",
+                display_path(path, root),
+                layout.describe()
+            );
+        }
+    }
+
+    out.push_str("\nExclusions:\n");
+    let _ = writeln!(
+        out,
+        "  default: {}, venv*, *.egg-info",
+        python::DEFAULT_EXCLUDES.join(", ")
+    );
+    if explanation.exclude_patterns.is_empty() {
+        out.push_str("  --exclude-scripts: (none)\n");
+    } else {
+        let _ = writeln!(
+            out,
+            "  --exclude-scripts: {}",
+            explanation.exclude_patterns.join(", ")
+        );
+    }
+
+    if !explanation.roots.is_empty() {
+        out.push_str("\nFiles discovered:\n");
+        for walked in &explanation.roots {
+            let _ = writeln!(
+                out,
+                "  {} {}: {}",
+                walked.label,
+                display_path(&walked.path, root),
+                walked.files
+            );
+        }
+    }
+
+    if explanation.source_root.is_some() {
+        out.push_str("\nSkipped:\n");
+        if explanation.skipped.is_empty() {
+            out.push_str("  (nothing)\n");
+        }
+        for skipped in &explanation.skipped {
+            let _ = writeln!(
+                out,
+                "  {}: {}",
+                display_path(&skipped.path, root),
+                skipped.reason.describe()
+            );
+        }
+    }
+    out
+}
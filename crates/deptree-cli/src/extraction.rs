@@ -0,0 +1,210 @@
+//! Extraction feasibility (`extract-check` subcommand)
+//!
+//! Moving a package into its own repository works when the imports across its boundary
+//! all point one way: the rest of the project depends on the package (a library), or the
+//! package depends on the rest (an application on top of it). The check lists the imports
+//! crossing the boundary both ways, the import cycles through the package and outside
+//! modules, and suggests the direction that leaves the fewest imports to remove.
+
+use deptree_graph::{DependencyGraph, GraphId};
+use std::collections::HashSet;
+use thiserror::Error;
+
+/// Errors that can occur while checking an extraction
+#[derive(Error, Debug)]
+pub enum ExtractionError {
+    #[error("Package '{0}' does not match any analyzed module")]
+    UnknownPackage(String),
+}
+
+/// Which side should depend on the other once the package is extracted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// No import crosses the boundary
+    Independent,
+    /// The rest of the project depends on the package
+    Library,
+    /// The package depends on the rest of the project
+    Application,
+}
+
+/// What extracting a package (the package and everything nested below it) entails
+#[derive(Debug, Clone)]
+pub struct Extraction<T> {
+    pub package: T,
+    pub members: usize,
+    /// Imports of outside modules by the package, as `(member, dependency)`
+    pub outgoing: Vec<(T, T)>,
+    /// Imports of the package by outside modules, as `(importer, member)`
+    pub incoming: Vec<(T, T)>,
+    /// Outside modules the package needs, directly or indirectly
+    pub transitive_dependencies: usize,
+    /// Outside modules that need the package, directly or indirectly
+    pub transitive_dependents: usize,
+    /// Import cycles with modules on both sides of the boundary
+    pub crossing_cycles: Vec<Vec<T>>,
+}
+
+impl<T> Extraction<T> {
+    /// The direction with more imports already following it; a tie favors a library
+    pub fn direction(&self) -> Direction {
+        match (self.incoming.is_empty(), self.outgoing.is_empty()) {
+            (true, true) => Direction::Independent,
+            _ if self.incoming.len() >= self.outgoing.len() => Direction::Library,
+            _ => Direction::Application,
+        }
+    }
+
+    /// Imports against the suggested direction, which have to go before extracting
+    pub fn against_direction(&self) -> &[(T, T)] {
+        match self.direction() {
+            Direction::Independent => &[],
+            Direction::Library => &self.outgoing,
+            Direction::Application => &self.incoming,
+        }
+    }
+}
+
+fn sorted_pairs<T: GraphId>(mut pairs: Vec<(T, T)>) -> Vec<(T, T)> {
+    pairs.sort_by_key(|(from, to)| (from.to_dotted(), to.to_dotted()));
+    pairs
+}
+
+/// Check what extracting `package` entails
+pub fn extraction<T: GraphId>(
+    graph: &DependencyGraph<T>,
+    package: &T,
+) -> Result<Extraction<T>, ExtractionError> {
+    let prefix = package.segments();
+    let inside = |module: &T| module.segments().starts_with(&prefix);
+    let members: Vec<T> = graph.nodes().filter(|m| inside(m)).cloned().collect();
+    if members.is_empty() {
+        return Err(ExtractionError::UnknownPackage(package.to_dotted()));
+    }
+
+    let mut outgoing = Vec::new();
+    let mut incoming = Vec::new();
+    for member in &members {
+        for dependency in graph.direct_dependencies(member) {
+            if !inside(dependency) {
+                outgoing.push((member.clone(), dependency.clone()));
+            }
+        }
+        for importer in graph.direct_dependents(member) {
+            if !inside(importer) {
+                incoming.push((importer.clone(), member.clone()));
+            }
+        }
+    }
+    let outside = |reached: std::collections::HashMap<T, usize>| {
+        reached.keys().filter(|m| !inside(m)).count()
+    };
+
+    let mut crossing_cycles: Vec<Vec<T>> = graph
+        .find_cycles()
+        .into_iter()
+        .filter(|cycle| cycle.iter().any(&inside) && !cycle.iter().all(&inside))
+        .map(|mut cycle| {
+            cycle.sort_by_key(GraphId::to_dotted);
+            cycle
+        })
+        .collect();
+    crossing_cycles.sort_by_key(|cycle| cycle[0].to_dotted());
+
+    Ok(Extraction {
+        package: package.clone(),
+        members: members.len(),
+        transitive_dependencies: outside(graph.find_upstream(&members, None)),
+        transitive_dependents: outside(graph.find_downstream(&members, None)),
+        outgoing: sorted_pairs(outgoing),
+        incoming: sorted_pairs(incoming),
+        crossing_cycles,
+    })
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    format!("{count} {noun}{}", if count == 1 { "" } else { "s" })
+}
+
+/// Render an extraction check as a human-readable report
+pub fn render_report<T: GraphId>(extraction: &Extraction<T>) -> String {
+    let package = extraction.package.to_dotted();
+    let mut output = format!(
+        "Extracting {package} ({})\n",
+        plural(extraction.members, "module")
+    );
+
+    let distinct = |modules: Vec<&T>| modules.into_iter().collect::<HashSet<_>>().len();
+    output.push_str(&format!(
+        "\nDepends on {} outside ({} transitively):\n",
+        plural(
+            distinct(extraction.outgoing.iter().map(|(_, to)| to).collect()),
+            "module"
+        ),
+        extraction.transitive_dependencies
+    ));
+    for (member, dependency) in &extraction.outgoing {
+        output.push_str(&format!(
+            "    {} -> {}\n",
+            member.to_dotted(),
+            dependency.to_dotted()
+        ));
+    }
+    output.push_str(&format!(
+        "\nImported by {} outside ({} transitively):\n",
+        plural(
+            distinct(extraction.incoming.iter().map(|(from, _)| from).collect()),
+            "module"
+        ),
+        extraction.transitive_dependents
+    ));
+    for (importer, member) in &extraction.incoming {
+        output.push_str(&format!(
+            "    {} -> {}\n",
+            importer.to_dotted(),
+            member.to_dotted()
+        ));
+    }
+
+    if extraction.crossing_cycles.is_empty() {
+        output.push_str("\nCycles crossing the boundary: none\n");
+    } else {
+        output.push_str(&format!(
+            "\nCycles crossing the boundary: {}\n",
+            extraction.crossing_cycles.len()
+        ));
+        for cycle in &extraction.crossing_cycles {
+            let names: Vec<String> = cycle.iter().map(GraphId::to_dotted).collect();
+            output.push_str(&format!("    {}\n", names.join(", ")));
+        }
+    }
+
+    let against = extraction.against_direction();
+    output.push_str(&match extraction.direction() {
+        Direction::Independent => {
+            format!(
+                "\nSuggested direction: none needed, no import crosses the boundary of {package}\n"
+            )
+        }
+        Direction::Library => format!(
+            "\nSuggested direction: {package} as a library the rest depends on ({} into it)\n",
+            plural(extraction.incoming.len(), "import")
+        ),
+        Direction::Application => format!(
+            "\nSuggested direction: {package} on top of the rest, depending on it ({} out of it)\n",
+            plural(extraction.outgoing.len(), "import")
+        ),
+    });
+    if !against.is_empty() {
+        output.push_str(&format!(
+            "{} against it to remove (or move across the boundary):\n",
+            plural(against.len(), "import")
+        ));
+        for (from, to) in against {
+            output.push_str(&format!("    {} -> {}\n", from.to_dotted(), to.to_dotted()));
+        }
+    } else if extraction.direction() != Direction::Independent {
+        output.push_str("No import goes against it\n");
+    }
+    output
+}
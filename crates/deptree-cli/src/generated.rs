@@ -0,0 +1,125 @@
+//! Generated-code detection
+//!
+//! A source file counts as generated when any of the usual markers says so:
+//! - its name: protobuf modules (`_pb2.py`, `_pb2_grpc.py`) and `*_generated.py`
+//! - an `@generated` comment before its first line of code
+//! - a `linguist-generated` attribute in the `.gitattributes` file at the project root
+//!
+//! An explicit `-linguist-generated` or `linguist-generated=false` overrides the other
+//! markers. Generated modules are tagged in the graph and can be left out of the output
+//! with `--hide-generated`.
+
+use std::path::Path;
+
+/// Marker comment of generated files (`# @generated`)
+const GENERATED_MARKER: &str = "@generated";
+
+/// File name endings of generated Python modules
+const GENERATED_SUFFIXES: [&str; 3] = ["_pb2.py", "_pb2_grpc.py", "_generated.py"];
+
+/// `linguist-generated` rules of a `.gitattributes` file, in file order
+#[derive(Debug, Clone, Default)]
+pub struct GitAttributes {
+    rules: Vec<(String, bool)>,
+}
+
+impl GitAttributes {
+    /// Parse the contents of a `.gitattributes` file, keeping the lines that set or unset
+    /// `linguist-generated`
+    pub fn parse(content: &str) -> Self {
+        let mut rules = Vec::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let Some(pattern) = fields.next() else {
+                continue;
+            };
+            // Attributes never apply to directories themselves
+            if pattern.ends_with('/') {
+                continue;
+            }
+            let generated = fields.fold(None, |state, attribute| match attribute {
+                "linguist-generated" | "linguist-generated=true" => Some(true),
+                "-linguist-generated" | "!linguist-generated" | "linguist-generated=false" => {
+                    Some(false)
+                }
+                _ => state,
+            });
+            if let Some(generated) = generated {
+                rules.push((pattern.to_string(), generated));
+            }
+        }
+        GitAttributes { rules }
+    }
+
+    /// Read `.gitattributes` at `project_root`; a missing or unreadable file has no rules
+    pub fn load(project_root: &Path) -> Self {
+        std::fs::read_to_string(project_root.join(".gitattributes"))
+            .map(|content| Self::parse(&content))
+            .unwrap_or_default()
+    }
+
+    /// Whether the last rule matching `relative_path` marks it as generated; `None` if no
+    /// rule matches
+    pub fn generated(&self, relative_path: &Path) -> Option<bool> {
+        let path = relative_path.to_string_lossy().replace('\\', "/");
+        let file_name = path.rsplit('/').next().unwrap_or(&path);
+        self.rules
+            .iter()
+            .rev()
+            .find(|(pattern, _)| match pattern.strip_prefix('/') {
+                Some(anchored) => glob_match(anchored, &path),
+                // Patterns without a slash match the file name at any depth
+                None if !pattern.contains('/') => glob_match(pattern, file_name),
+                None => glob_match(pattern, &path),
+            })
+            .map(|(_, generated)| *generated)
+    }
+}
+
+/// Match `text` against a gitattributes glob: `*` and `?` stay within a path segment and
+/// `**` spans any number of them (character classes are not supported)
+fn glob_match(pattern: &str, text: &str) -> bool {
+    fn matches(pattern: &[u8], text: &[u8]) -> bool {
+        match pattern {
+            [] => text.is_empty(),
+            [b'*', b'*', rest @ ..] => {
+                // `**/` also stands for no directory at all
+                rest.strip_prefix(b"/")
+                    .is_some_and(|rest| matches(rest, text))
+                    || (0..=text.len()).any(|i| matches(rest, &text[i..]))
+            }
+            [b'*', rest @ ..] => (0..=text.len())
+                .take_while(|&i| i == 0 || text[i - 1] != b'/')
+                .any(|i| matches(rest, &text[i..])),
+            [b'?', rest @ ..] => {
+                text.first().is_some_and(|&c| c != b'/') && matches(rest, &text[1..])
+            }
+            [c, rest @ ..] => text.first() == Some(c) && matches(rest, &text[1..]),
+        }
+    }
+    matches(pattern.as_bytes(), text.as_bytes())
+}
+
+/// Whether a comment before the first line of code carries the `@generated` marker
+pub fn has_generated_marker(source: &str) -> bool {
+    source
+        .lines()
+        .take_while(|line| line.trim().is_empty() || line.trim_start().starts_with('#'))
+        .any(|line| line.contains(GENERATED_MARKER))
+}
+
+/// Whether the file at `relative_path` (relative to the project root) with `source` is
+/// generated code
+pub fn is_generated(relative_path: &Path, source: &str, attributes: &GitAttributes) -> bool {
+    attributes.generated(relative_path).unwrap_or_else(|| {
+        let by_name = relative_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| GENERATED_SUFFIXES.iter().any(|s| name.ends_with(s)));
+        by_name || has_generated_marker(source)
+    })
+}
@@ -0,0 +1,123 @@
+//! GEXF export (`--format gexf`) for Gephi
+//!
+//! Writes the same nodes and edges as the JSON output as a directed GEXF 1.3 graph.
+//! Package groups become parent nodes referenced through `pid`, and node details
+//! (type, category, lifecycle, import time, coverage, highlighting) are node attributes.
+//! Each `--annotations` key gets an extra string attribute `annotation_<n>` titled with
+//! the key.
+
+use deptree_graph::GraphData;
+use deptree_graph::escape::xml_attribute as escape;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+const NODE_ATTRIBUTES: [(&str, &str); 7] = [
+    ("type", "string"),
+    ("category", "string"),
+    ("lifecycle", "string"),
+    ("import_time_us", "long"),
+    ("coverage", "double"),
+    ("highlighted", "boolean"),
+    ("orphan", "boolean"),
+];
+
+/// Render graph data as a GEXF document
+pub fn render_gexf(graph_data: &GraphData) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">\n");
+    out.push_str("  <meta>\n    <creator>deptree-utils</creator>\n  </meta>\n");
+    out.push_str("  <graph defaultedgetype=\"directed\" mode=\"static\">\n");
+
+    out.push_str("    <attributes class=\"node\">\n");
+    for (id, kind) in NODE_ATTRIBUTES {
+        let _ = writeln!(
+            out,
+            "      <attribute id=\"{id}\" title=\"{id}\" type=\"{kind}\"/>"
+        );
+    }
+    let annotation_keys: Vec<&String> = graph_data
+        .nodes
+        .iter()
+        .flat_map(|node| node.annotations.iter().flatten().map(|(key, _)| key))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    for (i, key) in annotation_keys.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "      <attribute id=\"annotation_{i}\" title=\"{}\" type=\"string\"/>",
+            escape(key)
+        );
+    }
+    out.push_str("    </attributes>\n");
+    out.push_str("    <attributes class=\"edge\">\n");
+    out.push_str("      <attribute id=\"kind\" title=\"kind\" type=\"string\"/>\n");
+    out.push_str("    </attributes>\n");
+
+    out.push_str("    <nodes>\n");
+    for node in &graph_data.nodes {
+        let pid = node
+            .parent
+            .as_ref()
+            .map(|parent| format!(" pid=\"{}\"", escape(parent)))
+            .unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "      <node id=\"{id}\" label=\"{id}\"{pid}>",
+            id = escape(&node.id)
+        );
+        let values = [
+            Some(node.node_type.clone()),
+            node.category.clone(),
+            node.lifecycle.clone(),
+            node.import_time_us.map(|us| us.to_string()),
+            node.coverage.map(|percent| percent.to_string()),
+            node.highlighted.map(|highlighted| highlighted.to_string()),
+            Some(node.is_orphan.to_string()),
+        ];
+        out.push_str("        <attvalues>\n");
+        for ((id, _), value) in NODE_ATTRIBUTES.iter().zip(values) {
+            if let Some(value) = value {
+                let _ = writeln!(
+                    out,
+                    "          <attvalue for=\"{id}\" value=\"{}\"/>",
+                    escape(&value)
+                );
+            }
+        }
+        for (i, key) in annotation_keys.iter().enumerate() {
+            if let Some(value) = node.annotations.as_ref().and_then(|a| a.get(*key)) {
+                let _ = writeln!(
+                    out,
+                    "          <attvalue for=\"annotation_{i}\" value=\"{}\"/>",
+                    escape(value)
+                );
+            }
+        }
+        out.push_str("        </attvalues>\n");
+        out.push_str("      </node>\n");
+    }
+    out.push_str("    </nodes>\n");
+
+    out.push_str("    <edges>\n");
+    for (i, edge) in graph_data.edges.iter().enumerate() {
+        let kind = edge.kind.as_deref().unwrap_or("import_time");
+        let weight = edge.count.unwrap_or(1);
+        let _ = writeln!(
+            out,
+            "      <edge id=\"e{i}\" source=\"{}\" target=\"{}\" weight=\"{weight}\">",
+            escape(&edge.source),
+            escape(&edge.target)
+        );
+        let _ = writeln!(
+            out,
+            "        <attvalues>\n          <attvalue for=\"kind\" value=\"{kind}\"/>\n        </attvalues>"
+        );
+        out.push_str("      </edge>\n");
+    }
+    out.push_str("    </edges>\n");
+
+    out.push_str("  </graph>\n</gexf>");
+    out
+}
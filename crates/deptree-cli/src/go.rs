@@ -0,0 +1,281 @@
+//! Go intra-module package dependency analyzer
+//!
+//! Reads the module path from `go.mod`, treats every directory containing `.go` files
+//! as a package, and parses `import` declarations to build a graph of dependencies
+//! between packages of the same module.
+
+use crate::comments::strip_c_style_comments;
+use deptree_graph::{DependencyGraph, GraphId};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use walkdir::WalkDir;
+
+/// Concrete dependency graph for Go packages.
+pub type GoGraph = DependencyGraph<PackagePath>;
+
+/// Errors that can occur during Go dependency analysis
+#[derive(Error, Debug)]
+pub enum GoAnalysisError {
+    #[error("Invalid module root: {0}")]
+    InvalidRoot(PathBuf),
+
+    #[error("Failed to read go.mod {0}: {1}")]
+    GoModReadError(PathBuf, std::io::Error),
+
+    #[error("No module directive found in {0}")]
+    MissingModuleDirective(PathBuf),
+}
+
+/// Represents a Go package by its import path (e.g., "example.com/app/internal/db")
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PackagePath(pub Vec<String>);
+
+impl PackagePath {
+    /// Create a package path from a slash-separated import path
+    pub fn from_import_path(input: &str) -> Option<Self> {
+        let parts: Vec<String> = input
+            .trim()
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect();
+
+        if parts.is_empty() {
+            None
+        } else {
+            Some(PackagePath(parts))
+        }
+    }
+
+    /// Create a package path from a directory inside the module root
+    pub fn from_dir(dir: &Path, module_root: &Path, module_path: &str) -> Option<Self> {
+        let relative = dir.strip_prefix(module_root).ok()?;
+        let mut parts = PackagePath::from_import_path(module_path)?.0;
+        parts.extend(
+            relative
+                .components()
+                .filter_map(|c| c.as_os_str().to_str().map(String::from)),
+        );
+        Some(PackagePath(parts))
+    }
+
+    /// Convert to the slash-separated import path
+    pub fn to_import_path(&self) -> String {
+        self.0.join("/")
+    }
+}
+
+/// Parses the name as it appears in graph data (see `DependencyGraph::from_graph_data`)
+impl std::str::FromStr for PackagePath {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PackagePath::from_import_path(s).ok_or_else(|| format!("invalid package path '{s}'"))
+    }
+}
+
+impl GraphId for PackagePath {
+    fn to_dotted(&self) -> String {
+        self.to_import_path()
+    }
+
+    fn segments(&self) -> Vec<String> {
+        self.0.clone()
+    }
+
+    fn join_segments(segments: &[String]) -> String {
+        segments.join("/")
+    }
+
+    // A Go package is independent of the packages in its subdirectories
+    fn represents_group(&self) -> bool {
+        false
+    }
+}
+
+/// Read the module path declared by the `module` directive in `go.mod`
+pub fn read_module_path(module_root: &Path) -> Result<String, GoAnalysisError> {
+    let go_mod = module_root.join("go.mod");
+    let content = std::fs::read_to_string(&go_mod)
+        .map_err(|e| GoAnalysisError::GoModReadError(go_mod.clone(), e))?;
+
+    strip_c_style_comments(&content)
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("module"))
+        .filter(|rest| rest.starts_with(char::is_whitespace))
+        .map(|rest| {
+            rest.trim()
+                .trim_matches(|c| c == '"' || c == '`')
+                .to_string()
+        })
+        .find(|path| !path.is_empty())
+        .ok_or(GoAnalysisError::MissingModuleDirective(go_mod))
+}
+
+/// Resolve a package input given on the command line.
+///
+/// Accepts either a full import path ("example.com/app/internal/db") or a directory
+/// relative to the module root ("internal/db", "./internal/db", or "." for the root package).
+pub fn resolve_package_input(input: &str, module_path: &str) -> Option<PackagePath> {
+    let input = input.trim().trim_end_matches('/');
+    if input == module_path || input.starts_with(&format!("{module_path}/")) {
+        return PackagePath::from_import_path(input);
+    }
+
+    let relative = input.trim_start_matches("./");
+    if relative.is_empty() || relative == "." {
+        PackagePath::from_import_path(module_path)
+    } else {
+        PackagePath::from_import_path(&format!("{module_path}/{relative}"))
+    }
+}
+
+/// Collect the contents of every string literal in `text`
+fn string_literals(text: &str) -> Vec<String> {
+    let mut literals = Vec::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '"' || c == '`' {
+            let literal: String = chars.by_ref().take_while(|&next| next != c).collect();
+            literals.push(literal);
+        }
+    }
+
+    literals
+}
+
+/// Extract import paths from a Go source file.
+///
+/// Handles single-line imports, parenthesized import blocks, and named/dot/blank imports.
+/// Scanning stops at the first top-level declaration since imports must precede them.
+fn extract_imports(source: &str) -> Vec<String> {
+    let cleaned = strip_c_style_comments(source);
+    let mut imports = Vec::new();
+    let mut in_block = false;
+
+    for line in cleaned.lines() {
+        let trimmed = line.trim();
+
+        if in_block {
+            let (body, closed) = match trimmed.find(')') {
+                Some(end) => (&trimmed[..end], true),
+                None => (trimmed, false),
+            };
+            imports.extend(string_literals(body));
+            in_block = !closed;
+            continue;
+        }
+
+        if let Some(rest) = trimmed.strip_prefix("import") {
+            let rest = rest.trim_start();
+            if let Some(block) = rest.strip_prefix('(') {
+                let (body, closed) = match block.find(')') {
+                    Some(end) => (&block[..end], true),
+                    None => (block, false),
+                };
+                imports.extend(string_literals(body));
+                in_block = !closed;
+            } else {
+                imports.extend(string_literals(rest).into_iter().take(1));
+            }
+        } else if ["func", "type", "var", "const"]
+            .iter()
+            .any(|keyword| trimmed.starts_with(&format!("{keyword} ")))
+        {
+            break;
+        }
+    }
+
+    imports
+}
+
+/// Directories the Go tool ignores when matching packages
+fn should_skip_dir(path: &Path, module_root: &Path) -> bool {
+    if path == module_root {
+        return false;
+    }
+
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    name.starts_with('.')
+        || name.starts_with('_')
+        || name == "vendor"
+        || name == "testdata"
+        // Nested modules are analyzed separately
+        || path.join("go.mod").is_file()
+}
+
+/// Analyze a Go module and return the dependency graph between its packages
+pub fn analyze_module(module_root: &Path, include_tests: bool) -> Result<GoGraph, GoAnalysisError> {
+    if !module_root.is_dir() {
+        return Err(GoAnalysisError::InvalidRoot(module_root.to_path_buf()));
+    }
+
+    let module_path = read_module_path(module_root)?;
+
+    let mut packages: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+    for entry in WalkDir::new(module_root)
+        .into_iter()
+        .filter_entry(|e| !e.file_type().is_dir() || !should_skip_dir(e.path(), module_root))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+    {
+        let path = entry.path();
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        if !name.ends_with(".go") || (!include_tests && name.ends_with("_test.go")) {
+            continue;
+        }
+        if let Some(dir) = path.parent() {
+            packages
+                .entry(dir.to_path_buf())
+                .or_default()
+                .push(path.to_path_buf());
+        }
+    }
+
+    let package_ids: BTreeMap<PathBuf, PackagePath> = packages
+        .keys()
+        .filter_map(|dir| {
+            PackagePath::from_dir(dir, module_root, &module_path).map(|pkg| (dir.clone(), pkg))
+        })
+        .collect();
+
+    let known: HashSet<&PackagePath> = package_ids.values().collect();
+
+    let mut graph = GoGraph::new();
+    for (dir, files) in &packages {
+        let Some(package) = package_ids.get(dir) else {
+            continue;
+        };
+        graph.ensure_node(package.clone());
+
+        for file_path in files {
+            let source = match std::fs::read_to_string(file_path) {
+                Ok(source) => source,
+                Err(e) => {
+                    eprintln!("Warning: Skipping file {}: {}", file_path.display(), e);
+                    continue;
+                }
+            };
+
+            for import in extract_imports(&source) {
+                if let Some(target) = PackagePath::from_import_path(&import)
+                    && target != *package
+                    && known.contains(&target)
+                {
+                    graph.add_dependency(package.clone(), target);
+                }
+            }
+        }
+    }
+
+    Ok(graph)
+}
@@ -0,0 +1,261 @@
+//! Graph health score (`stats`)
+//!
+//! Folds five structural measures into one number from 0 to 100 that a team can track
+//! from sprint to sprint: import cycles, average import depth, the largest fan-in, the
+//! share of imports crossing packages, and the share of orphan modules. Each measure is
+//! scored from 0 (worst) to 100 (best) on its own and the total is their weighted mean,
+//! so the breakdown shows which one moved the number.
+
+use deptree_graph::escape::openmetrics_label;
+use deptree_graph::{DependencyGraph, GraphId};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+use thiserror::Error;
+
+/// Errors that can occur while parsing health weights
+#[derive(Error, Debug)]
+pub enum HealthError {
+    #[error(
+        "Invalid health weights '{0}': expected KEY=WEIGHT pairs with KEY one of cycles, depth, fan_in, cross_package, orphans and non-negative weights"
+    )]
+    InvalidWeights(String),
+
+    #[error("Invalid health weights '{0}': at least one weight must be positive")]
+    ZeroWeights(String),
+}
+
+/// Cycles at which the cycle score drops to 50
+const CYCLES_HALF: f64 = 5.0;
+
+/// Average import depth at which the depth score drops to 50
+const DEPTH_HALF: f64 = 8.0;
+
+/// Weight of each measure in the total (`stats --weights`); all 1 by default
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Weights {
+    pub cycles: f64,
+    pub depth: f64,
+    pub fan_in: f64,
+    pub cross_package: f64,
+    pub orphans: f64,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Weights {
+            cycles: 1.0,
+            depth: 1.0,
+            fan_in: 1.0,
+            cross_package: 1.0,
+            orphans: 1.0,
+        }
+    }
+}
+
+/// Parse `--weights KEY=WEIGHT,...`; measures left out keep a weight of 1
+pub fn parse_weights(value: &str) -> Result<Weights, HealthError> {
+    let invalid = || HealthError::InvalidWeights(value.to_string());
+    let mut weights = Weights::default();
+    for pair in value.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (key, weight) = pair.split_once('=').ok_or_else(invalid)?;
+        let weight = weight
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|w| w.is_finite() && *w >= 0.0)
+            .ok_or_else(invalid)?;
+        let slot = match key.trim() {
+            "cycles" => &mut weights.cycles,
+            "depth" => &mut weights.depth,
+            "fan_in" => &mut weights.fan_in,
+            "cross_package" => &mut weights.cross_package,
+            "orphans" => &mut weights.orphans,
+            _ => return Err(invalid()),
+        };
+        *slot = weight;
+    }
+    let total =
+        weights.cycles + weights.depth + weights.fan_in + weights.cross_package + weights.orphans;
+    if total == 0.0 {
+        return Err(HealthError::ZeroWeights(value.to_string()));
+    }
+    Ok(weights)
+}
+
+/// One measure of the score
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Component {
+    pub name: &'static str,
+    /// The measure itself: a count, an average, or a ratio
+    pub value: f64,
+    /// 0 (worst) to 100 (best)
+    pub score: f64,
+    pub weight: f64,
+    /// What was measured, e.g. "3 of 40 imports cross packages"
+    pub detail: String,
+}
+
+/// Health score of a graph with its breakdown
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Health {
+    /// Weighted mean of the component scores, 0 to 100
+    pub score: f64,
+    pub modules: usize,
+    pub imports: usize,
+    pub components: Vec<Component>,
+}
+
+/// Score the graph. A module's parent package is its name without the last segment, so
+/// `app.api.views` importing `app.db` crosses packages. Empty graphs score 100.
+pub fn health<T: GraphId>(graph: &DependencyGraph<T>, weights: Weights) -> Health {
+    let modules = graph.node_count();
+    let imports: Vec<(&T, &T)> = graph
+        .edges()
+        .map(|(from, to, _)| (from, to))
+        .filter(|(from, to)| from != to)
+        .collect();
+    let ratio = |part: usize, whole: usize| {
+        if whole == 0 {
+            0.0
+        } else {
+            part as f64 / whole as f64
+        }
+    };
+    let halving = |value: f64, half: f64| 100.0 * half / (half + value);
+
+    let cycles = graph.find_cycles().len();
+    let depths = graph.import_depths();
+    let average_depth = ratio(depths.values().sum(), modules);
+
+    let mut importers: HashMap<&T, usize> = HashMap::new();
+    for (_, to) in &imports {
+        *importers.entry(*to).or_default() += 1;
+    }
+    let (most_imported, fan_in) = importers
+        .iter()
+        .map(|(module, count)| (module.to_dotted(), *count))
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+        .unwrap_or_default();
+    let fan_in_ratio = ratio(fan_in, modules.saturating_sub(1));
+
+    let parent = |module: &T| {
+        let mut segments = module.segments();
+        segments.pop();
+        segments
+    };
+    let crossing = imports
+        .iter()
+        .filter(|(from, to)| parent(from) != parent(to))
+        .count();
+    let cross_ratio = ratio(crossing, imports.len());
+    let orphans = graph.orphans().len();
+    let orphan_ratio = ratio(orphans, modules);
+
+    let components = vec![
+        Component {
+            name: "cycles",
+            value: cycles as f64,
+            score: halving(cycles as f64, CYCLES_HALF),
+            weight: weights.cycles,
+            detail: format!("{cycles} import cycle(s)"),
+        },
+        Component {
+            name: "depth",
+            value: average_depth,
+            score: halving(average_depth, DEPTH_HALF),
+            weight: weights.depth,
+            detail: format!("modules are {average_depth:.2} imports deep on average"),
+        },
+        Component {
+            name: "fan_in",
+            value: fan_in as f64,
+            score: 100.0 * (1.0 - fan_in_ratio),
+            weight: weights.fan_in,
+            detail: if fan_in == 0 {
+                "no module is imported".to_string()
+            } else {
+                format!(
+                    "{fan_in} of {} other modules import {most_imported}",
+                    modules - 1
+                )
+            },
+        },
+        Component {
+            name: "cross_package",
+            value: cross_ratio,
+            score: 100.0 * (1.0 - cross_ratio),
+            weight: weights.cross_package,
+            detail: format!("{crossing} of {} imports cross packages", imports.len()),
+        },
+        Component {
+            name: "orphans",
+            value: orphan_ratio,
+            score: 100.0 * (1.0 - orphan_ratio),
+            weight: weights.orphans,
+            detail: format!("{orphans} of {modules} modules are orphans"),
+        },
+    ];
+    let total_weight: f64 = components.iter().map(|c| c.weight).sum();
+    let score = if total_weight > 0.0 {
+        components.iter().map(|c| c.score * c.weight).sum::<f64>() / total_weight
+    } else {
+        100.0
+    };
+
+    // One decimal is enough to track, and keeps the output stable under float noise
+    let round = |value: f64| (value * 10.0).round() / 10.0;
+    Health {
+        score: round(score),
+        modules,
+        imports: imports.len(),
+        components: components
+            .into_iter()
+            .map(|c| Component {
+                score: round(c.score),
+                value: (c.value * 100.0).round() / 100.0,
+                ..c
+            })
+            .collect(),
+    }
+}
+
+/// Render the score with its breakdown
+pub fn render_report(health: &Health) -> String {
+    let mut output = format!(
+        "Health score: {:.1}/100 ({} modules, {} imports)\n\n",
+        health.score, health.modules, health.imports
+    );
+    let _ = writeln!(output, "{:>6}  {:>6}  component", "score", "weight");
+    for component in &health.components {
+        let _ = writeln!(
+            output,
+            "{:>6.1}  {:>6}  {}: {}",
+            component.score, component.weight, component.name, component.detail
+        );
+    }
+    output
+}
+
+/// Render the score and the component scores as OpenMetrics gauges
+pub fn render_openmetrics(health: &Health) -> String {
+    let mut output = String::from(
+        "# HELP deptree_health_score Weighted graph health score from 0 to 100.\n\
+         # TYPE deptree_health_score gauge\n",
+    );
+    let _ = writeln!(output, "deptree_health_score {}", health.score);
+    output.push_str(
+        "# HELP deptree_health_component Score of one health measure from 0 to 100.\n\
+         # TYPE deptree_health_component gauge\n",
+    );
+    for component in &health.components {
+        let _ = writeln!(
+            output,
+            "deptree_health_component{{component=\"{}\"}} {}",
+            openmetrics_label(component.name),
+            component.score
+        );
+    }
+    output.push_str("# EOF\n");
+    output
+}
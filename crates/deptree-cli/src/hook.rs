@@ -0,0 +1,319 @@
+//! Pre-commit hook mode (`hook` subcommand)
+//!
+//! Checks the files about to be committed: which modules they define, how much of the
+//! graph imports those modules, and which `[[contracts]]` violations and exceeded
+//! `[[budgets]]` run through them. Violations elsewhere in the graph are left to `check`,
+//! so a commit is only blocked by what it touches.
+
+use crate::budgets::BudgetResult;
+use crate::config::Config;
+use crate::contracts::ContractResult;
+use crate::polyglot::{PolyglotError, PolyglotGraph, PolyglotNode};
+use crate::proto::{self, ProtoPath};
+use crate::python::{self, ModulePath};
+use deptree_graph::GraphId;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use thiserror::Error;
+
+/// Errors that can occur while collecting staged files
+#[derive(Error, Debug)]
+pub enum HookError {
+    #[error("Failed to run git to list staged files: {0}")]
+    GitUnavailable(std::io::Error),
+
+    #[error("git diff --cached failed in {0}: {1}")]
+    Git(PathBuf, String),
+}
+
+/// Files added, copied, modified, or renamed in the index, relative to `root`
+pub fn staged_files(root: &Path) -> Result<Vec<PathBuf>, HookError> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args([
+            "diff",
+            "--cached",
+            "--name-only",
+            "--diff-filter=ACMR",
+            "--relative",
+        ])
+        .output()
+        .map_err(HookError::GitUnavailable)?;
+    if !output.status.success() {
+        return Err(HookError::Git(
+            root.to_path_buf(),
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Directories the configured analyzers read, so a file is named the way its analyzer
+/// named it rather than by whichever suffix of its path matches a node
+#[derive(Debug, Clone, Default)]
+pub struct SourceRoots {
+    /// Python project directory; scripts outside the source root are named from it
+    python_project: Option<PathBuf>,
+    python_source: Option<PathBuf>,
+    /// `[python.module_map]` directories with the module they map to
+    python_mapped: Vec<(PathBuf, ModulePath)>,
+    /// Proto import roots
+    proto: Vec<PathBuf>,
+}
+
+impl SourceRoots {
+    /// Roots of the analyzers in `config`, whose paths are relative to `base_dir`, with the
+    /// Python source root detected as the analysis does when it isn't configured
+    pub fn from_config(config: &Config, base_dir: &Path) -> Result<Self, PolyglotError> {
+        let mut roots = SourceRoots::default();
+        if let Some(python_config) = &config.python {
+            let project = base_dir.join(&python_config.path);
+            let source = match &python_config.source_root {
+                Some(root) => base_dir.join(root),
+                None => python::detect_source_root(&project)?,
+            };
+            roots.python_mapped = python_config
+                .module_map
+                .iter()
+                .map(|(path, module)| {
+                    ModulePath::from_dotted(module)
+                        .map(|module| (absolute(&project.join(path)), module))
+                        .ok_or_else(|| PolyglotError::InvalidModuleName(module.clone()))
+                })
+                .collect::<Result<_, _>>()?;
+            roots.python_source = Some(absolute(&source));
+            roots.python_project = Some(absolute(&project));
+        }
+        if let Some(proto_config) = &config.proto {
+            roots.proto = proto::resolve_import_roots(
+                &base_dir.join(&proto_config.path),
+                &proto_config.proto_paths,
+            )?;
+        }
+        Ok(roots)
+    }
+}
+
+/// `path` made absolute with symlinks resolved, as the proto import roots are; a deleted
+/// file is resolved through its directory
+fn absolute(path: &Path) -> PathBuf {
+    path.canonicalize()
+        .ok()
+        .or_else(|| {
+            let parent = path.parent()?.canonicalize().ok()?;
+            Some(parent.join(path.file_name()?))
+        })
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Whether `file` is a source file one of the analyzers reads
+pub fn is_source_file(file: &Path) -> bool {
+    file.extension()
+        .is_some_and(|ext| ["py", "pyx", "pxd", "proto"].iter().any(|e| ext == *e))
+}
+
+/// Node of `graph` defined by `file` (relative to the working directory): a Python module
+/// under the source root or a `module_map` directory, a script elsewhere in the Python
+/// project, or a schema under a proto import root. Files outside those roots, or that the
+/// analysis skipped, define no node.
+pub fn node_of_file(
+    file: &Path,
+    roots: &SourceRoots,
+    graph: &PolyglotGraph,
+) -> Option<PolyglotNode> {
+    let path = absolute(file);
+    if path.extension().is_some_and(|ext| ext == "proto") {
+        let node = PolyglotNode::Proto(ProtoPath::from_file_path(&path, &roots.proto)?);
+        return graph.contains(&node).then_some(node);
+    }
+    if !is_source_file(&path) {
+        return None;
+    }
+    let module = python::mapped_module(&path, &roots.python_mapped)
+        .or_else(|| ModulePath::from_file_path(&path, roots.python_source.as_deref()?));
+    if let Some(module) = module {
+        let node = PolyglotNode::Python(module);
+        return graph.contains(&node).then_some(node);
+    }
+    // Outside the source root only scripts are analyzed, named by their path
+    let script = PolyglotNode::Python(ModulePath::from_script_path(
+        &path,
+        roots.python_project.as_deref()?,
+    )?);
+    graph.is_script(&script).then_some(script)
+}
+
+/// A staged module with the number of modules importing it directly or indirectly
+#[derive(Debug, Clone)]
+pub struct StagedModule {
+    pub node: PolyglotNode,
+    pub downstream: usize,
+}
+
+/// Outcome of the hook
+#[derive(Debug, Clone)]
+pub struct HookReport {
+    /// Staged modules, sorted by name
+    pub staged: Vec<StagedModule>,
+    /// Staged source files defining no analyzed node, e.g. outside the source roots
+    pub unmapped: Vec<PathBuf>,
+    /// Modules outside the staged ones that import any of them
+    pub affected: usize,
+    /// Contracts with their violations narrowed to chains through a staged module
+    pub results: Vec<ContractResult>,
+    /// Exceeded budgets counting a module through a staged one
+    pub budgets: Vec<BudgetResult>,
+}
+
+impl HookReport {
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(ContractResult::is_kept) && self.budgets.is_empty()
+    }
+}
+
+/// Build the report for `files` (relative to `repo_root`), keeping only the contract
+/// violations and exceeded budgets with an import chain through a staged module
+pub fn run_hook(
+    graph: &PolyglotGraph,
+    roots: &SourceRoots,
+    repo_root: &Path,
+    files: &[PathBuf],
+    mut results: Vec<ContractResult>,
+    mut budgets: Vec<BudgetResult>,
+) -> HookReport {
+    let (mut nodes, mut unmapped) = (Vec::new(), Vec::new());
+    for file in files {
+        match node_of_file(&repo_root.join(file), roots, graph) {
+            Some(node) => nodes.push(node),
+            None if is_source_file(file) => unmapped.push(file.clone()),
+            None => {}
+        }
+    }
+    unmapped.sort();
+    nodes.sort_by_key(GraphId::to_dotted);
+    nodes.dedup();
+    let staged_set: HashSet<&PolyglotNode> = nodes.iter().collect();
+
+    let staged = nodes
+        .iter()
+        .map(|node| StagedModule {
+            node: node.clone(),
+            // The module itself is part of its downstream set
+            downstream: graph
+                .find_downstream(std::slice::from_ref(node), None)
+                .len()
+                .saturating_sub(1),
+        })
+        .collect();
+    let affected = graph
+        .find_downstream(&nodes, None)
+        .keys()
+        .filter(|node| !staged_set.contains(node))
+        .count();
+
+    for result in &mut results {
+        result
+            .violations
+            .retain(|violation| violation.chain.iter().any(|n| staged_set.contains(n)));
+    }
+    budgets.retain(|budget| {
+        !budget.is_kept()
+            && budget
+                .counted
+                .iter()
+                .any(|counted| counted.chain.iter().any(|n| staged_set.contains(n)))
+    });
+    HookReport {
+        staged,
+        unmapped,
+        affected,
+        results,
+        budgets,
+    }
+}
+
+/// Concise summary: the staged modules with their reach, the violations they take part
+/// in, and a final PASS or FAIL line
+pub fn render_report(report: &HookReport) -> String {
+    let mut out = String::new();
+    if report.staged.is_empty() {
+        out.push_str("No staged file defines an analyzed module\n");
+    } else {
+        let _ = writeln!(
+            out,
+            "{} staged module(s), imported by {} other module(s):",
+            report.staged.len(),
+            report.affected
+        );
+        for module in &report.staged {
+            let _ = writeln!(
+                out,
+                "  {}: {} downstream",
+                module.node.to_dotted(),
+                module.downstream
+            );
+        }
+    }
+
+    if !report.unmapped.is_empty() {
+        let files: Vec<String> = report
+            .unmapped
+            .iter()
+            .map(|file| file.display().to_string())
+            .collect();
+        let _ = writeln!(out, "Not part of the analyzed roots: {}", files.join(", "));
+    }
+
+    let mut violations = 0;
+    for result in report.results.iter().filter(|r| !r.is_kept()) {
+        let _ = writeln!(out, "BROKEN {} [{}]", result.name, result.kind);
+        for violation in &result.violations {
+            let chain: Vec<String> = violation.chain.iter().map(GraphId::to_dotted).collect();
+            let _ = writeln!(
+                out,
+                "    {} must not import {}: {}",
+                violation.importer,
+                violation.imported,
+                chain.join(" -> ")
+            );
+            violations += 1;
+        }
+    }
+    for budget in &report.budgets {
+        let _ = writeln!(
+            out,
+            "EXCEEDED {} [{}] {}: {} of {}",
+            budget.name,
+            budget.metric.as_str(),
+            budget.module,
+            budget.counted.len(),
+            budget.limit
+        );
+        for counted in &budget.counted {
+            let chain: Vec<String> = counted.chain.iter().map(GraphId::to_dotted).collect();
+            let _ = writeln!(
+                out,
+                "    {}: {}",
+                counted.module.to_dotted(),
+                chain.join(" -> ")
+            );
+        }
+    }
+    if report.passed() {
+        out.push_str("PASS: no contract violation or exceeded budget involves the staged files\n");
+    } else {
+        let _ = writeln!(
+            out,
+            "FAIL: {violations} contract violation(s) and {} exceeded budget(s) involve the staged files",
+            report.budgets.len()
+        );
+    }
+    out
+}
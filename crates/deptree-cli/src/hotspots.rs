@@ -0,0 +1,90 @@
+//! Import side-effect hotspots (`--side-effect-hotspots`)
+//!
+//! Importing a module runs the `__init__.py` of every package above it first, so a package
+//! body that does real work (registries, configuration, eager imports wrapped in code) is
+//! paid by every module that touches the package. Packages are ranked by the statements
+//! of their `__init__.py` that are not imports times the number of modules importing the
+//! package or anything in it.
+
+use crate::python::{ModulePath, PythonGraph};
+use std::collections::HashSet;
+
+/// A package whose `__init__.py` does work on import
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hotspot {
+    pub package: ModulePath,
+    /// Statements of the `__init__.py` other than imports, `__all__`, and docstrings
+    pub statements: usize,
+    /// Modules outside the package importing it or one of its modules
+    pub importers: usize,
+    /// Modules the `__init__.py` imports, directly or indirectly
+    pub upstream: usize,
+}
+
+impl Hotspot {
+    pub fn score(&self) -> usize {
+        self.statements * self.importers
+    }
+}
+
+/// Packages with a non-empty body and at least one importer, worst first
+pub fn side_effect_hotspots(graph: &PythonGraph) -> Vec<Hotspot> {
+    let mut hotspots: Vec<Hotspot> = graph
+        .nodes()
+        .filter_map(|file| {
+            let statements = graph.body_statements(file).filter(|count| *count > 0)?;
+            // With --split-package-init the body is recorded on `pkg.__init__`
+            let package = match file.0.split_last() {
+                Some((last, parent)) if last == "__init__" => ModulePath(parent.to_vec()),
+                _ => file.clone(),
+            };
+            let inside = |module: &ModulePath| module.0.starts_with(&package.0);
+            let importers: HashSet<&ModulePath> = graph
+                .nodes()
+                .filter(|module| inside(module))
+                .flat_map(|module| graph.direct_dependents(module))
+                .filter(|importer| !inside(importer))
+                .collect();
+            if importers.is_empty() {
+                return None;
+            }
+            // The file itself is part of its upstream set
+            let upstream = graph
+                .find_upstream(std::slice::from_ref(file), None)
+                .len()
+                .saturating_sub(1);
+            Some(Hotspot {
+                importers: importers.len(),
+                package,
+                statements,
+                upstream,
+            })
+        })
+        .collect();
+    hotspots.sort_by(|a, b| {
+        b.score()
+            .cmp(&a.score())
+            .then(b.statements.cmp(&a.statements))
+            .then_with(|| a.package.to_dotted().cmp(&b.package.to_dotted()))
+    });
+    hotspots
+}
+
+/// Table of the `limit` worst hotspots
+pub fn render_report(hotspots: &[Hotspot], limit: usize) -> String {
+    let mut output = format!(
+        "{:>8}  {:>10}  {:>9}  {:>8}  package\n",
+        "score", "statements", "importers", "upstream"
+    );
+    for hotspot in hotspots.iter().take(limit) {
+        output.push_str(&format!(
+            "{:>8}  {:>10}  {:>9}  {:>8}  {}\n",
+            hotspot.score(),
+            hotspot.statements,
+            hotspot.importers,
+            hotspot.upstream,
+            hotspot.package.to_dotted()
+        ));
+    }
+    output
+}
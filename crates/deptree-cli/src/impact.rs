@@ -0,0 +1,183 @@
+//! Impact scores (`--impact-report`, `--impact-scores`)
+//!
+//! Scores every module by how far a change to it reaches and how often it changes: the
+//! number of modules that (transitively) import it times the number of commits that
+//! touched its file recently. Modules that are both widely imported and frequently
+//! edited come first, which makes the ranking a starting point for hardening work.
+
+use crate::coverage;
+use crate::python::{ModulePath, PythonGraph};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::process::Command;
+use thiserror::Error;
+
+/// Errors that can occur while computing churn
+#[derive(Error, Debug)]
+pub enum ImpactError {
+    #[error("Failed to run git to compute churn: {0}")]
+    GitUnavailable(std::io::Error),
+
+    #[error("git log failed in {0} (churn needs a git checkout): {1}")]
+    Git(String, String),
+
+    #[error("Invalid impact weights '{0}': expected REACH,CHURN with non-negative numbers")]
+    InvalidWeights(String),
+}
+
+/// Exponents applied to the two factors of a score; a weight of 0 ignores the factor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Weights {
+    pub reach: f64,
+    pub churn: f64,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Weights {
+            reach: 1.0,
+            churn: 1.0,
+        }
+    }
+}
+
+/// Parse `--impact-weights REACH,CHURN`
+pub fn parse_weights(value: &str) -> Result<Weights, ImpactError> {
+    let invalid = || ImpactError::InvalidWeights(value.to_string());
+    let (reach, churn) = value.split_once(',').ok_or_else(invalid)?;
+    let parse = |weight: &str| {
+        weight
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|w| w.is_finite() && *w >= 0.0)
+            .ok_or_else(invalid)
+    };
+    Ok(Weights {
+        reach: parse(reach)?,
+        churn: parse(churn)?,
+    })
+}
+
+/// Count how many commits touched each file in `git log --format= --name-only` output
+pub fn parse_name_only(log: &str) -> BTreeMap<String, usize> {
+    let mut commits: BTreeMap<String, usize> = BTreeMap::new();
+    for line in log.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        *commits.entry(line.to_string()).or_default() += 1;
+    }
+    commits
+}
+
+/// Commits per file (relative to `project_root`) since `since`, any date `git log
+/// --since` accepts (e.g. "90 days ago" or "2024-01-01")
+pub fn git_churn(project_root: &Path, since: &str) -> Result<BTreeMap<String, usize>, ImpactError> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(project_root)
+        .args(["log", "--format=", "--name-only", "--relative"])
+        .arg(format!("--since={since}"))
+        .args(["--", "."])
+        .output()
+        .map_err(ImpactError::GitUnavailable)?;
+    if !output.status.success() {
+        return Err(ImpactError::Git(
+            project_root.display().to_string(),
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(parse_name_only(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Score of one module
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleImpact {
+    pub module: ModulePath,
+    /// Modules that import it directly or indirectly
+    pub reach: usize,
+    /// Commits that touched its file
+    pub churn: usize,
+    /// `reach^reach_weight × churn^churn_weight`
+    pub score: f64,
+}
+
+/// Score every module of `graph` from per-file churn, highest score first (ties by
+/// name). File names are matched to modules the way coverage reports are.
+pub fn impact_scores(
+    graph: &PythonGraph,
+    churn: &BTreeMap<String, usize>,
+    weights: Weights,
+) -> Vec<ModuleImpact> {
+    let mut module_churn: HashMap<ModulePath, usize> = HashMap::new();
+    for (file, commits) in churn {
+        if let Some(module) = coverage::match_module(file, graph) {
+            *module_churn.entry(module).or_default() += commits;
+        }
+    }
+
+    let mut scores: Vec<ModuleImpact> = graph
+        .nodes()
+        .map(|module| {
+            // The module itself is part of its downstream set
+            let reach = graph
+                .find_downstream(std::slice::from_ref(module), None)
+                .len()
+                .saturating_sub(1);
+            let churn = module_churn.get(module).copied().unwrap_or(0);
+            let score = (reach as f64).powf(weights.reach) * (churn as f64).powf(weights.churn);
+            ModuleImpact {
+                module: module.clone(),
+                reach,
+                churn,
+                score,
+            }
+        })
+        .collect();
+    scores.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.module.to_dotted().cmp(&b.module.to_dotted()))
+    });
+    scores
+}
+
+/// Attach `impact`, `reach`, and `churn` annotations to every scored module
+pub fn annotate(graph: &mut PythonGraph, scores: &[ModuleImpact]) {
+    for impact in scores {
+        graph.set_annotation(&impact.module, "impact", &format_score(impact.score));
+        graph.set_annotation(&impact.module, "reach", &impact.reach.to_string());
+        graph.set_annotation(&impact.module, "churn", &impact.churn.to_string());
+    }
+}
+
+fn format_score(score: f64) -> String {
+    if score.fract() == 0.0 {
+        format!("{score:.0}")
+    } else {
+        format!("{score:.2}")
+    }
+}
+
+/// Ranked table of the modules with a non-zero score
+pub fn render_report(scores: &[ModuleImpact], since: &str) -> String {
+    let mut output = format!(
+        "Impact = reach x churn (commits since {since})\n{:>10}  {:>6}  {:>6}  module\n",
+        "score", "reach", "churn"
+    );
+    let ranked: Vec<&ModuleImpact> = scores.iter().filter(|s| s.score > 0.0).collect();
+    for impact in &ranked {
+        output.push_str(&format!(
+            "{:>10}  {:>6}  {:>6}  {}\n",
+            format_score(impact.score),
+            impact.reach,
+            impact.churn,
+            impact.module.to_dotted()
+        ));
+    }
+    let unscored = scores.len() - ranked.len();
+    if unscored > 0 {
+        output.push_str(&format!(
+            "({unscored} module(s) with a score of 0 not shown)\n"
+        ));
+    }
+    output
+}
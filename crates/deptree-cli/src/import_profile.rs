@@ -0,0 +1,120 @@
+//! Python import-time profiles
+//!
+//! Reads the log written by `python -X importtime` and attaches the measured
+//! per-module import durations to the nodes of a dependency graph.
+
+use crate::python::{ModulePath, PythonGraph};
+use deptree_graph::{DependencyGraph, GraphId, ImportCost};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Errors that can occur while loading an import profile
+#[derive(Error, Debug)]
+pub enum ImportProfileError {
+    #[error("Failed to read import profile {0}: {1}")]
+    ReadError(PathBuf, std::io::Error),
+
+    #[error("No `-X importtime` entries found in {0}")]
+    NoEntries(PathBuf),
+}
+
+/// Measured import cost of one module
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportTiming {
+    pub module: String,
+    pub cost: ImportCost,
+}
+
+/// Parse a `-X importtime` log.
+///
+/// Lines look like `import time:       123 |        456 |   pkg.module`; the header line
+/// and any unrelated output are ignored. Modules reported several times (e.g. when logs
+/// of multiple runs are concatenated) have their durations summed. Results are sorted
+/// by module name.
+pub fn parse_importtime(content: &str) -> Vec<ImportTiming> {
+    let mut costs: HashMap<String, ImportCost> = HashMap::new();
+
+    for line in content.lines() {
+        let Some(rest) = line.trim_start().strip_prefix("import time:") else {
+            continue;
+        };
+        let mut fields = rest.split('|');
+        let (Some(self_us), Some(cumulative_us), Some(module), None) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let (Ok(self_us), Ok(cumulative_us)) = (
+            self_us.trim().parse::<u64>(),
+            cumulative_us.trim().parse::<u64>(),
+        ) else {
+            continue;
+        };
+        let module = module.trim();
+        if module.is_empty() {
+            continue;
+        }
+
+        let cost = costs.entry(module.to_string()).or_default();
+        cost.self_us += self_us;
+        cost.cumulative_us += cumulative_us;
+    }
+
+    let mut timings: Vec<ImportTiming> = costs
+        .into_iter()
+        .map(|(module, cost)| ImportTiming { module, cost })
+        .collect();
+    timings.sort_by(|a, b| a.module.cmp(&b.module));
+    timings
+}
+
+/// Read and parse a `-X importtime` log file
+pub fn load(path: &Path) -> Result<Vec<ImportTiming>, ImportProfileError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| ImportProfileError::ReadError(path.to_path_buf(), e))?;
+    let timings = parse_importtime(&content);
+    if timings.is_empty() {
+        return Err(ImportProfileError::NoEntries(path.to_path_buf()));
+    }
+    Ok(timings)
+}
+
+/// Attach timings to the matching modules of `graph`; modules outside the project
+/// (stdlib, third-party) are ignored. Returns how many modules were matched.
+pub fn apply_to_graph(graph: &mut PythonGraph, timings: &[ImportTiming]) -> usize {
+    let mut matched = 0;
+    for timing in timings {
+        if let Some(module) = ModulePath::from_dotted(&timing.module)
+            && graph.contains(&module)
+        {
+            graph.set_import_cost(&module, timing.cost);
+            matched += 1;
+        }
+    }
+    matched
+}
+
+/// Render the `limit` modules with the highest self import time, together with the
+/// number of modules that (transitively) depend on them and therefore pay that cost.
+pub fn render_slowest_report<T: GraphId>(graph: &DependencyGraph<T>, limit: usize) -> String {
+    let mut output = format!(
+        "{:>10}  {:>10}  {:>10}  module\n",
+        "self_ms", "cumul_ms", "dependents"
+    );
+    for (module, cost) in graph.slowest_imports(limit) {
+        // The module itself is part of its downstream set
+        let dependents = graph
+            .find_downstream(std::slice::from_ref(&module), None)
+            .len()
+            .saturating_sub(1);
+        output.push_str(&format!(
+            "{:>10.2}  {:>10.2}  {:>10}  {}\n",
+            cost.self_us as f64 / 1000.0,
+            cost.cumulative_us as f64 / 1000.0,
+            dependents,
+            module.to_dotted()
+        ));
+    }
+    output
+}
@@ -0,0 +1,67 @@
+//! Direct importers of a file (`importers` subcommand)
+//!
+//! Lists every statement that imports a module as `path:line:col: message`, the format
+//! read by vim's quickfix list (`:cexpr`/`:cfile`), emacs' compilation mode, and VS Code
+//! problem matchers, so an editor can jump from a file to the code that uses it.
+
+use crate::python::{ModulePath, PythonGraph};
+use deptree_graph::ImportSite;
+use std::fmt::Write;
+use std::path::Path;
+
+/// One import statement of the module
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Importer {
+    /// Module or script the statement is in
+    pub module: ModulePath,
+    pub site: ImportSite,
+}
+
+/// Import statements of `module` in the modules importing it directly, sorted by file,
+/// line, and column, and the importers with no recorded statement
+pub fn importers(graph: &PythonGraph, module: &ModulePath) -> (Vec<Importer>, Vec<ModulePath>) {
+    let mut found = Vec::new();
+    let mut unlocated = Vec::new();
+    for importer in graph.direct_dependents(module) {
+        let sites = graph.import_sites(importer, module);
+        if sites.is_empty() {
+            unlocated.push(importer.clone());
+        }
+        found.extend(sites.iter().map(|site| Importer {
+            module: importer.clone(),
+            site: site.clone(),
+        }));
+    }
+    found.sort_by(|a, b| {
+        (&a.site.file, a.site.line, a.site.column).cmp(&(&b.site.file, b.site.line, b.site.column))
+    });
+    found.dedup();
+    (found, unlocated)
+}
+
+/// One `path:line:col: <importer> imports <module>` line per statement, followed by
+/// `as <alias>` when the statement renames the import; paths are relative to the current
+/// directory when `project_root` is
+pub fn render_quickfix(importers: &[Importer], module: &ModulePath, project_root: &Path) -> String {
+    let mut out = String::new();
+    for importer in importers {
+        let path = if project_root == Path::new(".") {
+            importer.site.file.clone()
+        } else {
+            project_root.join(&importer.site.file).display().to_string()
+        };
+        let _ = write!(
+            out,
+            "{path}:{}:{}: {} imports {}",
+            importer.site.line,
+            importer.site.column.max(1),
+            importer.module.to_dotted(),
+            module.to_dotted()
+        );
+        if let Some(alias) = &importer.site.alias {
+            let _ = write!(out, " as {alias}");
+        }
+        out.push('\n');
+    }
+    out
+}
@@ -0,0 +1,142 @@
+//! Layout hints (`--layout-seed`)
+//!
+//! The viewer lays graphs out in the browser, and its force-directed layouts start from
+//! random positions, so two renderings of the same graph rarely line up. With a seed, a
+//! force-directed pass runs when the graph is generated instead and each node's result is
+//! stored in `GraphNode::position`, which the viewer takes as its initial (`preset`)
+//! layout. Nodes start at a hash of their name mixed with the seed and the pass takes a
+//! fixed number of steps in name order, so the same graph and seed always give the same
+//! arrangement, and runs can be compared side by side.
+
+use crate::sample::seeded_hash;
+use deptree_graph::{GraphData, GraphNode, NodePosition};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Preferred distance between connected nodes, in viewer pixels
+const EDGE_LENGTH: f64 = 120.0;
+
+/// Steps of the pass: each costs time quadratic in the node count, so large graphs get
+/// fewer, down to `MIN_STEPS`
+const MAX_STEPS: usize = 100;
+const MIN_STEPS: usize = 10;
+const STEP_BUDGET: usize = 5_000_000;
+
+/// Set the position of every node drawn on its own (compound nodes are placed around
+/// their children by the viewer)
+pub fn add_layout_hints(data: &mut GraphData, seed: u64) {
+    let mut positions = layout(data, seed);
+    for node in &mut data.nodes {
+        node.position = positions.remove(&node.id);
+    }
+}
+
+fn layout(data: &GraphData, seed: u64) -> HashMap<String, NodePosition> {
+    let parents: HashSet<&str> = data
+        .nodes
+        .iter()
+        .filter_map(|node| node.parent.as_deref())
+        .collect();
+    let mut nodes: Vec<&GraphNode> = data
+        .nodes
+        .iter()
+        .filter(|node| !parents.contains(node.id.as_str()))
+        .collect();
+    nodes.sort_by(|a, b| a.id.cmp(&b.id));
+    let count = nodes.len();
+    if count == 0 {
+        return HashMap::new();
+    }
+
+    let index: HashMap<&str, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| (node.id.as_str(), i))
+        .collect();
+    let mut edges: Vec<(usize, usize)> = data
+        .edges
+        .iter()
+        .filter_map(|edge| {
+            Some((
+                *index.get(edge.source.as_str())?,
+                *index.get(edge.target.as_str())?,
+            ))
+        })
+        .filter(|(source, target)| source != target)
+        .collect();
+    edges.sort_unstable();
+    edges.dedup();
+    let mut groups: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (i, node) in nodes.iter().enumerate() {
+        if let Some(parent) = node.parent.as_deref() {
+            groups.entry(parent).or_default().push(i);
+        }
+    }
+
+    let side = EDGE_LENGTH * (count as f64).sqrt();
+    let unit = |bits: u64| (bits & 0xffff_ffff) as f64 / f64::from(u32::MAX);
+    let mut position: Vec<(f64, f64)> = nodes
+        .iter()
+        .map(|node| {
+            let hash = seeded_hash(&node.id, seed);
+            (unit(hash >> 32) * side, unit(hash) * side)
+        })
+        .collect();
+
+    // Fruchterman-Reingold: every pair repels with k²/d, connected nodes attract with d²/k
+    let steps = (STEP_BUDGET / (count * count)).clamp(MIN_STEPS, MAX_STEPS);
+    let mut shift = vec![(0.0, 0.0); count];
+    for step in 0..steps {
+        shift.fill((0.0, 0.0));
+        for a in 0..count {
+            for b in a + 1..count {
+                let (dx, dy) = (position[a].0 - position[b].0, position[a].1 - position[b].1);
+                let force = EDGE_LENGTH * EDGE_LENGTH / (dx * dx + dy * dy).max(0.01);
+                shift[a] = (shift[a].0 + dx * force, shift[a].1 + dy * force);
+                shift[b] = (shift[b].0 - dx * force, shift[b].1 - dy * force);
+            }
+        }
+        for &(a, b) in &edges {
+            let (dx, dy) = (position[a].0 - position[b].0, position[a].1 - position[b].1);
+            let force = (dx * dx + dy * dy).sqrt() / EDGE_LENGTH;
+            shift[a] = (shift[a].0 - dx * force, shift[a].1 - dy * force);
+            shift[b] = (shift[b].0 + dx * force, shift[b].1 + dy * force);
+        }
+        // Members of a compound node are pulled toward their center, keeping it compact
+        for members in groups.values() {
+            let size = members.len() as f64;
+            let center_x = members.iter().map(|&i| position[i].0).sum::<f64>() / size;
+            let center_y = members.iter().map(|&i| position[i].1).sum::<f64>() / size;
+            for &i in members {
+                let (dx, dy) = (position[i].0 - center_x, position[i].1 - center_y);
+                let force = (dx * dx + dy * dy).sqrt() / EDGE_LENGTH;
+                shift[i] = (shift[i].0 - dx * force, shift[i].1 - dy * force);
+            }
+        }
+
+        // Moves are capped by a temperature cooling to zero over the steps
+        let temperature = side / 10.0 * (1.0 - step as f64 / steps as f64);
+        for (point, &(dx, dy)) in position.iter_mut().zip(&shift) {
+            let length = (dx * dx + dy * dy).sqrt();
+            if length > 0.0 {
+                let moved = length.min(temperature) / length;
+                *point = (point.0 + dx * moved, point.1 + dy * moved);
+            }
+        }
+    }
+
+    // Tenths of a pixel are plenty, and keep the output stable under float noise
+    let round = |value: f64| (value * 10.0).round() / 10.0;
+    nodes
+        .iter()
+        .zip(position)
+        .map(|(node, (x, y))| {
+            (
+                node.id.clone(),
+                NodePosition {
+                    x: round(x),
+                    y: round(y),
+                },
+            )
+        })
+        .collect()
+}
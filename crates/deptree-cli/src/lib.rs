@@ -1,2 +1,47 @@
+pub mod annotations;
+pub mod anonymize;
+pub mod badge;
+pub mod baseline;
+pub mod budgets;
+mod comments;
+pub mod communities;
+pub mod config;
+pub mod conformance;
+pub mod contracts;
+pub mod coupling;
+pub mod coverage;
+pub mod critical;
+pub mod cycles;
+#[cfg(feature = "html")]
 pub mod cytoscape;
+pub mod deadcode;
+pub mod diff;
+pub mod emit;
+pub mod explain;
+pub mod extraction;
+pub mod generated;
+pub mod gexf;
+pub mod go;
+pub mod health;
+pub mod hook;
+pub mod hotspots;
+pub mod impact;
+pub mod import_profile;
+pub mod importers;
+pub mod layout;
+pub mod lifecycle;
+pub mod limits;
+pub mod mcp;
+pub mod polyglot;
+pub mod proto;
+pub mod publish;
 pub mod python;
+pub mod rename;
+pub mod sample;
+pub mod services;
+pub mod source;
+pub mod splits;
+pub mod sqlite;
+pub mod surface;
+pub mod testreach;
+pub mod validate;
@@ -0,0 +1,70 @@
+//! Module lifecycle report (`--lifecycle-report`)
+//!
+//! Modules are declared `experimental`, `stable`, or `sunset` with `--lifecycle`, the
+//! `lifecycle` table of the configuration, or a `# deptree: lifecycle=<state>` header
+//! comment. The report lists the imports that cut across those states: stable code
+//! relying on experimental modules, and anything still importing a sunset module.
+
+use deptree_graph::{DependencyGraph, GraphId, Lifecycle};
+
+/// Imports that cross lifecycle states, each as `(importer, imported)` sorted by name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LifecycleReport<T> {
+    /// Stable modules importing experimental ones. Modules without a declared state
+    /// count as stable.
+    pub stable_on_experimental: Vec<(T, T)>,
+    /// Modules that are not sunset themselves importing sunset ones
+    pub into_sunset: Vec<(T, T)>,
+}
+
+/// Collect the imports of `graph` that cross lifecycle states
+pub fn lifecycle_report<T: GraphId>(graph: &DependencyGraph<T>) -> LifecycleReport<T> {
+    let state = |module: &T| graph.lifecycle(module).unwrap_or(Lifecycle::Stable);
+    let mut report = LifecycleReport {
+        stable_on_experimental: Vec::new(),
+        into_sunset: Vec::new(),
+    };
+
+    for module in graph.nodes() {
+        for target in graph.direct_dependencies(module) {
+            match (state(module), state(target)) {
+                (Lifecycle::Stable, Lifecycle::Experimental) => {
+                    report
+                        .stable_on_experimental
+                        .push((module.clone(), target.clone()));
+                }
+                (from, Lifecycle::Sunset) if from != Lifecycle::Sunset => {
+                    report.into_sunset.push((module.clone(), target.clone()));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for edges in [&mut report.stable_on_experimental, &mut report.into_sunset] {
+        edges.sort_by_key(|(from, to)| (from.to_dotted(), to.to_dotted()));
+    }
+    report
+}
+
+/// Render the report as plain text, one section per kind of crossing
+pub fn render_report<T: GraphId>(report: &LifecycleReport<T>) -> String {
+    let mut output = String::new();
+    let sections = [
+        (
+            "Stable modules importing experimental modules",
+            &report.stable_on_experimental,
+        ),
+        ("Imports of sunset modules", &report.into_sunset),
+    ];
+    for (title, edges) in sections {
+        output.push_str(&format!("{title} ({}):\n", edges.len()));
+        if edges.is_empty() {
+            output.push_str("  (none)\n");
+        }
+        for (from, to) in edges {
+            output.push_str(&format!("  {} -> {}\n", from.to_dotted(), to.to_dotted()));
+        }
+    }
+    output
+}
@@ -0,0 +1,167 @@
+//! Output size limits (`--max-nodes`, `--max-edges`)
+//!
+//! Graphviz and the Cytoscape viewer give up on graphs with tens of thousands of
+//! elements. When what would be drawn exceeds a limit, the graph is reduced before
+//! rendering: either by aggregating modules into their packages at the deepest level
+//! that fits, or by keeping the best-connected modules.
+
+use deptree_graph::{DependencyGraph, GraphId};
+use std::collections::{HashMap, HashSet};
+
+/// How to reduce a graph that exceeds a limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fallback {
+    /// Collapse modules into their packages, sampling if no package depth fits
+    Aggregate,
+    /// Keep the modules with the most imports to and from the rest of the graph
+    Sample,
+}
+
+impl Fallback {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "sample" => Fallback::Sample,
+            _ => Fallback::Aggregate,
+        }
+    }
+}
+
+/// Maximum size of a rendered graph; `None` means unlimited
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    pub max_nodes: Option<usize>,
+    pub max_edges: Option<usize>,
+    pub fallback: Fallback,
+}
+
+impl Limits {
+    fn allows(&self, nodes: usize, edges: usize) -> bool {
+        self.max_nodes.is_none_or(|max| nodes <= max)
+            && self.max_edges.is_none_or(|max| edges <= max)
+    }
+}
+
+/// A graph reduced to fit the limits
+pub struct Reduced<T: GraphId> {
+    pub graph: DependencyGraph<T>,
+    /// Node of the reduced graph each original node ended up in; sampled-out nodes are
+    /// missing
+    pub mapping: HashMap<T, T>,
+    /// What was done, for the warning printed to the user
+    pub description: String,
+}
+
+/// Reduce the `visible` part of `graph` if drawing it would exceed `limits`; `None` when
+/// it already fits. `ancestor(module, depth)` names the package of `module` made of its
+/// first `depth` segments.
+pub fn reduce<T: GraphId>(
+    graph: &DependencyGraph<T>,
+    visible: &HashSet<T>,
+    limits: &Limits,
+    include_orphans: bool,
+    include_namespace_packages: bool,
+    ancestor: impl Fn(&T, usize) -> Option<T>,
+) -> Option<Reduced<T>> {
+    let size = |graph: &DependencyGraph<T>| {
+        let metrics = graph.metrics(None, include_orphans, include_namespace_packages);
+        (metrics.nodes, metrics.edges)
+    };
+    let selected = graph.filter_nodes(|module| visible.contains(module));
+    let (nodes, edges) = size(&selected);
+    if limits.allows(nodes, edges) {
+        return None;
+    }
+    let exceeded = format!("{nodes} nodes and {edges} edges exceed the output limits");
+
+    if limits.fallback == Fallback::Aggregate {
+        let max_depth = visible
+            .iter()
+            .map(|m| m.segments().len())
+            .max()
+            .unwrap_or(0);
+        for depth in (1..max_depth).rev() {
+            let Some(mapping) = visible
+                .iter()
+                .map(|module| {
+                    let package = if module.segments().len() > depth {
+                        ancestor(module, depth)?
+                    } else {
+                        module.clone()
+                    };
+                    Some((module.clone(), package))
+                })
+                .collect::<Option<HashMap<T, T>>>()
+            else {
+                break;
+            };
+            let aggregated = selected
+                .map_ids(|module| mapping[module].clone())
+                .filter_edges(|from, to, _| from != to);
+            let (nodes, edges) = size(&aggregated);
+            if limits.allows(nodes, edges) {
+                // Coarser depths only merge more: when everything sits in one package,
+                // what fits is a lone node or no edge at all, so sample instead
+                if nodes < 2 || edges == 0 {
+                    break;
+                }
+                return Some(Reduced {
+                    graph: aggregated,
+                    mapping,
+                    description: format!(
+                        "{exceeded}; showing packages up to depth {depth} ({nodes} nodes, {edges} edges)"
+                    ),
+                });
+            }
+        }
+    }
+
+    // Rank modules by degree within the selection, then keep as many as fit
+    let mut ranked: Vec<(usize, T)> = visible
+        .iter()
+        .map(|module| {
+            let neighbors = |found: Vec<&T>| {
+                found
+                    .into_iter()
+                    .filter(|other| *other != module && visible.contains(*other))
+                    .count()
+            };
+            let degree = neighbors(selected.direct_dependencies(module))
+                + neighbors(selected.direct_dependents(module));
+            (degree, module.clone())
+        })
+        .collect();
+    ranked.sort_by(|(a_degree, a), (b_degree, b)| {
+        b_degree
+            .cmp(a_degree)
+            .then_with(|| a.to_dotted().cmp(&b.to_dotted()))
+    });
+
+    // Keeping more modules never shrinks the sample, so binary search the largest
+    // prefix of the ranking that fits instead of dropping one module at a time
+    let sample = |keep: usize| {
+        let kept: HashSet<&T> = ranked[..keep].iter().map(|(_, module)| module).collect();
+        let sampled = selected.filter_nodes(|module| kept.contains(module));
+        let (nodes, edges) = size(&sampled);
+        (kept, sampled, nodes, edges)
+    };
+    // An empty sample always fits; one past the largest allowed size never does
+    let most = limits.max_nodes.unwrap_or(ranked.len()).min(ranked.len());
+    let (mut fits, mut exceeds) = (0, most + 1);
+    while fits + 1 < exceeds {
+        let middle = fits + (exceeds - fits) / 2;
+        let (_, _, nodes, edges) = sample(middle);
+        if limits.allows(nodes, edges) {
+            fits = middle;
+        } else {
+            exceeds = middle;
+        }
+    }
+    let (kept, sampled, nodes, edges) = sample(fits);
+    Some(Reduced {
+        mapping: kept.iter().map(|m| ((*m).clone(), (*m).clone())).collect(),
+        graph: sampled,
+        description: format!(
+            "{exceeded}; showing the {fits} best-connected modules ({nodes} nodes, {edges} edges)"
+        ),
+    })
+}
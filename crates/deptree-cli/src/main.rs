@@ -1,6 +1,64 @@
-use clap::{Parser, Subcommand};
-use deptree_utils::{cytoscape, python};
+use clap::{ArgGroup, Parser, Subcommand};
+use deptree_graph::filters::{
+    EdgeDirection, FilterConfig, FilterPreset, import_weight, matches_pattern,
+};
+use deptree_graph::schema::Payload;
+use deptree_graph::{
+    DependencyGraph, DotClustering, EdgeKind, GraphData, GraphId, GraphMetrics, Lifecycle,
+    MermaidEdgeLabels, NamespaceGrouping, OrphanScope, Relation, RenderOptions,
+};
+#[cfg(feature = "html")]
+use deptree_utils::cytoscape;
+use deptree_utils::{
+    annotations, anonymize, badge, baseline, budgets, communities, config, conformance, contracts,
+    coupling, coverage, critical, cycles, deadcode, diff, emit, explain, extraction, gexf, go,
+    health, hook, hotspots, impact, import_profile, importers, layout, lifecycle, limits, mcp,
+    polyglot, proto, publish, python, rename, sample, services, source, splits, sqlite, surface,
+    testreach, validate,
+};
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+/// Exit code when `check` finds broken contracts or exceeded budgets, `hook` finds
+/// violations through staged files, or `--forbid-new-cycles` finds a new cycle
+const EXIT_CONTRACTS_BROKEN: u8 = 1;
+
+/// Exit code for configuration, analysis, and usage errors
+const EXIT_ERROR: u8 = 2;
+
+/// Names accepted by `--format`; `cytoscape` only when the `html` feature is compiled in
+const FORMATS: &[&str] = &[
+    "dot",
+    "mermaid",
+    "list",
+    #[cfg(feature = "html")]
+    "cytoscape",
+    "json",
+    "openmetrics",
+    "sqlite",
+    "gexf",
+    "badge",
+];
+
+/// Names accepted by `diff --format` and `conformance --format`
+const DIFF_FORMATS: &[&str] = &[
+    "dot",
+    #[cfg(feature = "html")]
+    "cytoscape",
+    "list",
+];
+
+/// Names accepted by `--edges` and by the `edges` of a profile; `upstream` and
+/// `downstream` are the former names of `dependencies` and `dependents`
+const EDGES: &[&str] = &[
+    "dependencies",
+    "dependents",
+    "both",
+    "upstream",
+    "downstream",
+];
 
 /// Output formats supported by the CLI
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -8,19 +66,87 @@ enum OutputFormat {
     Dot,
     Mermaid,
     List,
+    // Never parsed without the `html` feature
+    #[cfg_attr(not(feature = "html"), allow(dead_code))]
     Cytoscape,
+    Json,
+    OpenMetrics,
+    Sqlite,
+    Gexf,
+    Badge,
+}
+
+impl OutputFormat {
+    fn parse(format: &str) -> Self {
+        match format {
+            "dot" => OutputFormat::Dot,
+            "mermaid" => OutputFormat::Mermaid,
+            "list" => OutputFormat::List,
+            "cytoscape" => OutputFormat::Cytoscape,
+            "json" => OutputFormat::Json,
+            "openmetrics" => OutputFormat::OpenMetrics,
+            "sqlite" => OutputFormat::Sqlite,
+            "gexf" => OutputFormat::Gexf,
+            "badge" => OutputFormat::Badge,
+            _ => unreachable!("Invalid format validated by clap"),
+        }
+    }
+
+    /// MIME type used when publishing the rendered output
+    fn content_type(self) -> &'static str {
+        match self {
+            OutputFormat::Dot => "text/vnd.graphviz",
+            OutputFormat::Mermaid | OutputFormat::List => "text/plain; charset=utf-8",
+            OutputFormat::Cytoscape => "text/html; charset=utf-8",
+            OutputFormat::Json => "application/json",
+            OutputFormat::OpenMetrics => {
+                "application/openmetrics-text; version=1.0.0; charset=utf-8"
+            }
+            OutputFormat::Sqlite => "application/vnd.sqlite3",
+            OutputFormat::Gexf => "application/gexf+xml",
+            OutputFormat::Badge => "application/json",
+        }
+    }
+}
+
+/// Rendered output: text for most formats, a database file for `--format sqlite`
+enum Rendered {
+    Text(String),
+    Database(Vec<u8>),
+}
+
+impl From<String> for Rendered {
+    fn from(text: String) -> Self {
+        Rendered::Text(text)
+    }
+}
+
+/// Source root of a Python analysis of `root` (detected unless given), and `options`
+/// mapping the other roots of `--source-root auto-all`
+fn resolve_source_root(
+    root: &Path,
+    source_root: Option<PathBuf>,
+    mut options: python::AnalysisOptions,
+) -> Result<(PathBuf, python::AnalysisOptions), python::PythonAnalysisError> {
+    let (source_root, extra) = python::resolve_source_roots(root, source_root.as_deref())?;
+    options.module_map.extend(extra);
+    Ok((source_root, options))
 }
 
 /// Parse a module input, which can be either:
 /// - A dotted module name like "pkg_a.module_a"
-/// - A file path like "scripts/blah.py" or "src/pkg_a/module_a.py"
+/// - A file path like "scripts/blah.py" or "src/pkg_a/module_a.py" (or a Cython `.pyx`/`.pxd` file)
 fn parse_module_input(
     input: &str,
     project_root: &Path,
     source_root: &Path,
 ) -> Result<python::ModulePath, String> {
     // Check if input looks like a file path
-    let is_file_path = input.contains('/') || input.contains('\\') || input.ends_with(".py");
+    let is_file_path = input.contains('/')
+        || input.contains('\\')
+        || [".py", ".pyx", ".pxd"]
+            .iter()
+            .any(|suffix| input.ends_with(suffix));
 
     if is_file_path {
         // Treat as file path
@@ -128,6 +254,27 @@ fn read_module_list_file(
         .collect())
 }
 
+/// Combine module inputs from a comma-separated flag, a repeatable flag, and a list file
+fn collect_module_inputs(
+    csv: Option<String>,
+    repeated: Vec<String>,
+    file_path: Option<PathBuf>,
+    list_flag: &str,
+    module_flag: &str,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    Ok(csv
+        .iter()
+        .flat_map(|csv| {
+            csv.split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+        })
+        .chain(repeated)
+        .chain(read_module_list_file(file_path, list_flag, module_flag)?)
+        .collect())
+}
+
 #[derive(Parser, Debug)]
 #[clap(author = "Simon Zeng", version, about = "Dependency tree utilities")]
 struct Args {
@@ -139,315 +286,2229 @@ struct Args {
     command: Command,
 }
 
+/// Upstream/downstream selection flags shared by all analyzers
+#[derive(clap::Args, Debug)]
+struct SelectionArgs {
+    /// Comma-separated list of modules to find downstream dependencies for
+    #[arg(long)]
+    downstream: Option<String>,
+
+    /// Individual module to find downstream dependencies for (can be repeated)
+    #[arg(long = "downstream-module")]
+    downstream_module: Vec<String>,
+
+    /// File containing newline-separated list of modules to find downstream dependencies for
+    #[arg(long)]
+    downstream_file: Option<PathBuf>,
+
+    /// Comma-separated list of modules to find upstream dependencies for
+    #[arg(long)]
+    upstream: Option<String>,
+
+    /// Individual module to find upstream dependencies for (can be repeated)
+    #[arg(long = "upstream-module")]
+    upstream_module: Vec<String>,
+
+    /// File containing newline-separated list of modules to find upstream dependencies for
+    #[arg(long)]
+    upstream_file: Option<PathBuf>,
+
+    /// Include only nodes within distance N from specified modules
+    #[arg(long)]
+    max_rank: Option<usize>,
+
+    /// Encoded viewer filter state (the `#filter=...` part of a shared link) to render headlessly
+    #[arg(long, conflicts_with_all = [
+        "downstream", "downstream_module", "downstream_file",
+        "upstream", "upstream_module", "upstream_file",
+        "max_rank", "show_all", "communities", "edges",
+    ])]
+    filter_state: Option<String>,
+}
+
+/// Raw selection inputs gathered from every selection flag
+#[derive(Clone)]
+struct Selection {
+    downstream: Vec<String>,
+    upstream: Vec<String>,
+    /// Roots of a neighborhood query: everything they import and everything importing
+    /// them (module arguments of `python`)
+    neighborhood: Vec<String>,
+    max_rank: Option<usize>,
+    /// Viewer filter state the selection came from (`--filter-state`)
+    view: Option<FilterConfig>,
+}
+
+impl SelectionArgs {
+    fn collect(self) -> Result<Selection, Box<dyn std::error::Error>> {
+        if let Some(encoded) = &self.filter_state {
+            let view = FilterConfig::decode(encoded)?;
+            return Ok(Selection {
+                downstream: view.downstream_roots.clone(),
+                upstream: view.upstream_roots.clone(),
+                neighborhood: Vec::new(),
+                max_rank: view.max_distance,
+                view: Some(view),
+            });
+        }
+        Ok(Selection {
+            downstream: collect_module_inputs(
+                self.downstream,
+                self.downstream_module,
+                self.downstream_file,
+                "--downstream-file",
+                "--downstream",
+            )?,
+            upstream: collect_module_inputs(
+                self.upstream,
+                self.upstream_module,
+                self.upstream_file,
+                "--upstream-file",
+                "--upstream",
+            )?,
+            neighborhood: Vec::new(),
+            max_rank: self.max_rank,
+            view: None,
+        })
+    }
+}
+
+/// Output flags shared by all analyzers
+#[derive(clap::Args, Debug, Clone)]
+struct OutputArgs {
+    /// Output format (default: dot; 'cytoscape' needs a build with the `html` feature); repeat it to render several formats from one analysis, each to its own --output
+    #[arg(long, default_value = "dot", value_parser = clap::builder::PossibleValuesParser::new(FORMATS))]
+    format: Vec<String>,
+
+    /// Module described by --format badge
+    #[arg(long, value_name = "MODULE", required_if_eq("format", "badge"))]
+    module: Option<String>,
+
+    /// What --format badge shows: 'dependents' (modules importing the module, directly or indirectly) or 'cycle' (whether it is in an import cycle)
+    #[arg(long, default_value = "dependents", value_parser = ["dependents", "cycle"])]
+    badge_metric: String,
+
+    /// Render --format badge as an SVG image instead of shields.io endpoint JSON
+    #[arg(long)]
+    badge_svg: bool,
+
+    /// Include orphan nodes (nodes with no dependencies) in DOT output
+    #[arg(long)]
+    include_orphans: bool,
+
+    /// Show full graph with highlighted nodes instead of filtering (requires --downstream or --upstream)
+    #[arg(long)]
+    show_all: bool,
+
+    /// Draw a DOT cluster per package (default: on; `--cluster-namespaces=false` draws every module flat)
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    cluster_namespaces: Option<bool>,
+
+    /// Draw one DOT cluster around the modules under the source root and one around the scripts outside it (default: on with --show-all, off otherwise)
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    cluster_roots: Option<bool>,
+
+    /// Draw a package as a cluster only when it has at least N visible children (default: 2)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    group_min_children: Option<u64>,
+
+    /// Draw packages nested deeper than N name segments without a cluster of their own
+    #[arg(long, value_name = "N")]
+    group_max_depth: Option<usize>,
+
+    /// Label DOT and Mermaid edges with how many times the import occurs (e.g. "×4") when more than once
+    #[arg(long)]
+    show_edge_counts: bool,
+
+    /// Label Mermaid edges (comma-separated): 'kind' of import, 'count' when more than once, 'weight=KEY' for the KEY annotation of the imported module (see --annotations)
+    #[arg(long, value_name = "LABELS", value_delimiter = ',', value_parser = parse_mermaid_edge_label)]
+    mermaid_edge_labels: Vec<String>,
+
+    /// Link DOT nodes (URL attribute) and Cytoscape nodes (opened on double click) to their source, e.g. 'https://github.com/org/repo/blob/main/{path}#L{line}'; {path} is the file relative to the project root, {line} the first line, {module} the dotted name
+    #[arg(long, value_name = "TEMPLATE", conflicts_with = "anonymize")]
+    link_template: Option<String>,
+
+    /// Group modules into detected communities instead of packages (DOT clusters, Cytoscape compound nodes); with --format list, report where they disagree with the package structure
+    #[arg(long)]
+    communities: bool,
+
+    /// Keep only edges toward the selection's dependencies or dependents ('upstream' and 'downstream' are accepted as aliases); requires --downstream or --upstream
+    #[arg(long, default_value = "both", value_parser = clap::builder::PossibleValuesParser::new(EDGES))]
+    edges: String,
+
+    /// Module to leave out of the output, bridging its incoming and outgoing edges (can be repeated)
+    #[arg(long = "hide-module", value_name = "MODULE")]
+    hide_module: Vec<String>,
+
+    /// Leave generated modules (protobuf `_pb2`, `@generated`, linguist-generated) out of the output, bridging their edges like --hide-module
+    #[arg(long)]
+    hide_generated: bool,
+
+    /// JSON file of per-module metadata (e.g. team, tier, service) added to the node attributes of every output
+    #[arg(long, value_name = "FILE")]
+    annotations: Option<PathBuf>,
+
+    /// Group modules by the value of an annotation instead of packages (DOT clusters, Cytoscape compound nodes)
+    #[arg(
+        long,
+        value_name = "KEY",
+        requires = "annotations",
+        conflicts_with = "communities"
+    )]
+    group_by: Option<String>,
+
+    /// Reduce DOT, Mermaid, and Cytoscape output drawing more nodes than this (see --limit-fallback)
+    #[arg(long, value_name = "N")]
+    max_nodes: Option<usize>,
+
+    /// Reduce DOT, Mermaid, and Cytoscape output drawing more edges than this (see --limit-fallback)
+    #[arg(long, value_name = "N")]
+    max_edges: Option<usize>,
+
+    /// Replace module names with opaque segments (m1.m2, ...) in every format, keeping the
+    /// package nesting
+    #[arg(long)]
+    anonymize: bool,
+
+    /// JSON file mapping names to their anonymized segments: read if it exists and
+    /// rewritten with the new ones, so segments stay stable across runs
+    #[arg(long, value_name = "FILE", requires = "anonymize")]
+    anonymize_map: Option<PathBuf>,
+
+    /// Render a representative subgraph of about N modules: the best-connected modules with
+    /// their neighbors, plus the shortest import cycle if there is one
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    sample: Option<u64>,
+
+    /// Seed breaking ties between equally connected modules in --sample (default: 0)
+    #[arg(long, value_name = "SEED", requires = "sample")]
+    seed: Option<u64>,
+
+    /// Add node positions from a layout seeded with SEED to Cytoscape and JSON output, so the same graph renders in the same arrangement every time
+    #[arg(long, value_name = "SEED")]
+    layout_seed: Option<u64>,
+
+    /// How to shrink output over --max-nodes/--max-edges: 'aggregate' into packages (sampling if no package level fits) or 'sample' the best-connected modules
+    #[arg(long, default_value = "aggregate", value_parser = ["aggregate", "sample"])]
+    limit_fallback: String,
+
+    /// Also upload the rendered output to an http(s):// endpoint (POST) or s3://bucket/key (PUT); credentials come from DEPTREE_PUBLISH_TOKEN or the AWS_* variables
+    #[arg(long, value_name = "URL")]
+    publish: Option<String>,
+
+    /// Write the output to a file instead of stdout (required for --format sqlite); with a repeated --format, give one per format, in the same order
+    #[arg(long, short = 'o', value_name = "FILE")]
+    output: Vec<PathBuf>,
+}
+
+impl OutputArgs {
+    /// The format to render; [`render_graph`] renders a repeated --format one at a time
+    fn output_format(&self) -> OutputFormat {
+        OutputFormat::parse(&self.format[0])
+    }
+
+    /// The flags for rendering the `index`th --format alone, to its --output file
+    fn nth_format(&self, index: usize) -> OutputArgs {
+        OutputArgs {
+            format: vec![self.format[index].clone()],
+            output: self.output.get(index).cloned().into_iter().collect(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Project flags shared by the subcommands analyzing one Python project
+#[derive(clap::Args, Debug)]
+struct PythonProjectArgs {
+    /// Path to the Python project root
+    #[arg(default_value = ".")]
+    path: PathBuf,
+
+    /// Python source root directory (defaults to auto-detection; `auto-all` analyzes
+    /// every plausible one)
+    #[arg(long, short = 's')]
+    source_root: Option<PathBuf>,
+
+    /// Glob patterns to exclude from script discovery (can be repeated)
+    #[arg(long = "exclude-scripts")]
+    exclude_scripts: Vec<String>,
+}
+
+fn parse_mermaid_edge_label(input: &str) -> Result<String, String> {
+    match input.split_once('=') {
+        Some(("weight", key)) if !key.is_empty() => Ok(input.to_string()),
+        None if matches!(input, "kind" | "count") => Ok(input.to_string()),
+        _ => Err(format!(
+            "expected kind, count, or weight=KEY, got '{input}'"
+        )),
+    }
+}
+
+/// The labels selected by --mermaid-edge-labels (already validated)
+fn mermaid_edge_labels(labels: &[String]) -> MermaidEdgeLabels {
+    let mut selected = MermaidEdgeLabels::default();
+    for label in labels {
+        match label.split_once('=') {
+            Some((_, key)) => selected.weight = Some(key.to_string()),
+            None if label == "kind" => selected.kind = true,
+            None => selected.count = true,
+        }
+    }
+    selected
+}
+
+fn parse_script_category(input: &str) -> Result<(String, String), String> {
+    match input.split_once('=') {
+        Some((directory, category)) if !category.is_empty() => {
+            Ok((directory.to_string(), category.to_string()))
+        }
+        _ => Err(format!("expected DIR=CATEGORY, got '{input}'")),
+    }
+}
+
+fn parse_module_mapping(input: &str) -> Result<(PathBuf, python::ModulePath), String> {
+    match input.split_once('=') {
+        Some((path, module)) if !path.is_empty() => python::ModulePath::from_dotted(module)
+            .map(|module| (PathBuf::from(path), module))
+            .ok_or_else(|| format!("expected PATH=MODULE, got '{input}'")),
+        _ => Err(format!("expected PATH=MODULE, got '{input}'")),
+    }
+}
+
+fn parse_lifecycle_mapping(input: &str) -> Result<(python::ModulePath, Lifecycle), String> {
+    let parsed = input.split_once('=').and_then(|(module, state)| {
+        Some((
+            python::ModulePath::from_dotted(module)?,
+            Lifecycle::parse(state)?,
+        ))
+    });
+    parsed.ok_or_else(|| {
+        format!("expected MODULE=STATE with STATE experimental, stable, or sunset, got '{input}'")
+    })
+}
+
+fn parse_timeout(input: &str) -> Result<Duration, String> {
+    input
+        .parse::<f64>()
+        .ok()
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .ok_or_else(|| format!("expected a non-negative number of seconds, got '{input}'"))
+}
+
+fn parse_impact_weights(input: &str) -> Result<impact::Weights, String> {
+    impact::parse_weights(input).map_err(|e| e.to_string())
+}
+
+fn parse_health_weights(input: &str) -> Result<health::Weights, String> {
+    health::parse_weights(input).map_err(|e| e.to_string())
+}
+
+/// DOT clustering: the mode's default, overridden by --cluster-namespaces/--cluster-roots
+fn dot_clustering(output: &OutputArgs, default: DotClustering) -> DotClustering {
+    DotClustering {
+        namespaces: output.cluster_namespaces.unwrap_or(default.namespaces),
+        roots: output.cluster_roots.unwrap_or(default.roots),
+    }
+}
+
+/// The direction named by one of [`EDGES`]
+/// How the graph formats draw what they are asked for
+fn render_options(output: &OutputArgs) -> RenderOptions {
+    let default = NamespaceGrouping::default();
+    RenderOptions {
+        namespace_grouping: NamespaceGrouping {
+            min_children: output
+                .group_min_children
+                .map_or(default.min_children, |n| n as usize),
+            max_depth: output.group_max_depth,
+        },
+        show_edge_counts: output.show_edge_counts,
+        mermaid_edge_labels: mermaid_edge_labels(&output.mermaid_edge_labels),
+        link_template: output.link_template.clone(),
+        ..RenderOptions::default()
+    }
+}
+
+/// The direction named by one of [`EDGES`]
+fn parse_edge_direction(edges: &str) -> Option<EdgeDirection> {
+    match edges {
+        "dependencies" | "upstream" => Some(EdgeDirection::Dependencies),
+        "dependents" | "downstream" => Some(EdgeDirection::Dependents),
+        "both" => Some(EdgeDirection::Both),
+        _ => None,
+    }
+}
+
+// Parsed once per run, so the size of the `Python` variant is of no concern
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug)]
 enum Command {
     /// Analyze Python project dependencies
+    #[command(group(
+        ArgGroup::new("report").multiple(false).conflicts_with_all([
+            "modules", "downstream", "downstream_module", "downstream_file",
+            "upstream", "upstream_module", "upstream_file", "max_rank", "filter_state",
+        ])
+    ))]
     Python {
-        /// Path to the Python project root
+        /// Path to the Python project root, a .zip/.tar/.tar.gz/.tgz archive of it, or
+        /// git+<url>[@<rev>]
         #[arg()]
         path: PathBuf,
 
-        /// Python source root directory (defaults to auto-detection)
+        /// Modules (dotted names or file paths) to show the neighborhood of: everything they
+        /// import and everything importing them, directly or indirectly (--max-rank limits
+        /// the distance). The --downstream/--upstream flags select one direction instead
+        #[arg(value_name = "MODULE", conflicts_with_all = [
+            "downstream", "downstream_module", "downstream_file",
+            "upstream", "upstream_module", "upstream_file", "filter_state",
+        ])]
+        modules: Vec<String>,
+
+        /// Python source root directory (defaults to auto-detection, which stops when several
+        /// roots are plausible; `auto-all` analyzes all of them; relative to the extracted
+        /// project for archive and git sources)
         #[arg(long, short = 's')]
         source_root: Option<PathBuf>,
 
-        /// Output format: 'dot', 'mermaid', 'list', or 'cytoscape' (default: dot)
-        #[arg(long, default_value = "dot", value_parser = ["dot", "mermaid", "list", "cytoscape"])]
-        format: String,
+        #[command(flatten)]
+        selection: SelectionArgs,
+
+        #[command(flatten)]
+        output: OutputArgs,
+
+        /// Glob patterns to exclude from script discovery (can be repeated)
+        #[arg(long = "exclude-scripts")]
+        exclude_scripts: Vec<String>,
+
+        /// Draw every script inside a cluster for its directory (bin/, tools/, ...)
+        #[arg(long)]
+        cluster_scripts: bool,
+
+        /// Category for the scripts below a directory, as DIR=CATEGORY (e.g. ops=automation; can be repeated)
+        #[arg(long = "script-category", value_name = "DIR=CATEGORY", value_parser = parse_script_category)]
+        script_category: Vec<(String, String)>,
 
-        /// Comma-separated list of modules to find downstream dependencies for
+        /// Include namespace packages in the output (by default they are excluded)
         #[arg(long)]
-        downstream: Option<String>,
+        include_namespace_packages: bool,
 
-        /// Individual module to find downstream dependencies for (can be repeated)
-        #[arg(long = "downstream-module")]
-        downstream_module: Vec<String>,
+        /// Import a file or directory under another module name, as PATH=MODULE with PATH
+        /// relative to the project root (e.g. gen/protos=company.protos; can be repeated)
+        #[arg(long = "module-map", value_name = "PATH=MODULE", value_parser = parse_module_mapping)]
+        module_map: Vec<(PathBuf, python::ModulePath)>,
 
-        /// File containing newline-separated list of modules to find downstream dependencies for
+        /// Leave out local libraries declared as path dependencies in pyproject.toml
+        /// (`name @ file://...`, uv and Poetry `path` sources)
         #[arg(long)]
-        downstream_file: Option<PathBuf>,
+        no_path_dependencies: bool,
 
-        /// Comma-separated list of modules to find upstream dependencies for
+        /// Skip script discovery: only modules below the source root (and mapped paths)
+        /// are analyzed, and the rest of the project is never walked
         #[arg(long)]
-        upstream: Option<String>,
+        no_scripts: bool,
 
-        /// Individual module to find upstream dependencies for (can be repeated)
-        #[arg(long = "upstream-module")]
-        upstream_module: Vec<String>,
+        /// Drop imports that cannot run on this platform (linux, darwin, or win32), judged
+        /// by enclosing `sys.platform`/`os.name` checks
+        #[arg(long, value_parser = python::parse_platform)]
+        platform: Option<python::Platform>,
 
-        /// File containing newline-separated list of modules to find upstream dependencies for
+        /// Fail when a relative import cannot be resolved confidently (above the top-level
+        /// package, to a module outside the project, or from a script)
         #[arg(long)]
-        upstream_file: Option<PathBuf>,
+        strict_resolution: bool,
 
-        /// Include only nodes within distance N from specified modules
+        /// Give each package's `__init__.py` its own node, `pkg.__init__`, with the imports
+        /// made there; `pkg` then depends on it (by default both are the node `pkg`)
         #[arg(long)]
-        max_rank: Option<usize>,
+        split_package_init: bool,
 
-        /// Glob patterns to exclude from script discovery (can be repeated)
-        #[arg(long = "exclude-scripts")]
-        exclude_scripts: Vec<String>,
+        /// Lifecycle state of a module and everything below it, as MODULE=STATE with STATE
+        /// experimental, stable, or sunset (can be repeated)
+        #[arg(long = "lifecycle", value_name = "MODULE=STATE", value_parser = parse_lifecycle_mapping)]
+        lifecycle: Vec<(python::ModulePath, Lifecycle)>,
+
+        /// Print the imports of experimental modules by stable ones and of sunset modules
+        /// instead of the graph
+        #[arg(long, group = "report")]
+        lifecycle_report: bool,
+
+        /// Python version the sources target, e.g. 3.8; syntax newer than it is reported
+        /// (defaults to the newest version the parser supports)
+        #[arg(long, value_name = "VERSION", value_parser = python::parse_python_version)]
+        python_version: Option<python::PythonVersion>,
+
+        /// Keep only deferred dependencies (imports inside function bodies)
+        #[arg(long, conflicts_with = "import_time_only")]
+        runtime_only: bool,
 
-        /// Include orphan nodes (nodes with no dependencies) in DOT output
+        /// Keep only dependencies executed at import time (module-level imports)
         #[arg(long)]
-        include_orphans: bool,
+        import_time_only: bool,
 
-        /// Show full graph with highlighted nodes instead of filtering (requires --downstream or --upstream)
+        /// `python -X importtime` log used to color nodes by their import duration
         #[arg(long)]
-        show_all: bool,
+        import_profile: Option<PathBuf>,
 
-        /// Include namespace packages in the output (by default they are excluded)
+        /// Print the N modules with the highest self import time instead of the graph (requires --import-profile)
+        #[arg(long, requires = "import_profile", group = "report")]
+        slowest: Option<usize>,
+
+        /// Print the N packages whose `__init__.py` does the most work on import (statements
+        /// other than imports, times the modules importing the package) instead of the graph
+        #[arg(long, value_name = "N", group = "report")]
+        side_effect_hotspots: Option<usize>,
+
+        /// Print the modules no entry point loads (scripts, `__main__` and test modules,
+        /// scripts and plugins declared in pyproject.toml), with how sure that is given the
+        /// dynamic imports of computed names, instead of the graph
+        #[arg(long, group = "report")]
+        dead_code: bool,
+
+        /// Further entry point for --dead-code, as a dotted name or file path (can be
+        /// repeated)
+        #[arg(long = "entry-point", value_name = "MODULE", requires = "dead_code")]
+        entry_point: Vec<String>,
+
+        /// Print, for every production module, the test modules importing it directly or
+        /// indirectly, and the production modules no test reaches, instead of the graph
+        #[arg(long, group = "report")]
+        test_reach: bool,
+
+        /// Wildcard pattern of the test modules for --test-reach, replacing the default
+        /// (pytest file names and `tests`/`test` packages) (can be repeated)
+        #[arg(long = "test-module", value_name = "PATTERN", requires = "test_reach")]
+        test_module: Vec<String>,
+
+        /// Print the longest import chain from this entry module (dotted name or file path),
+        /// the chain setting its import depth, instead of the graph
+        #[arg(long, value_name = "ENTRY", group = "report")]
+        critical_path: Option<String>,
+
+        /// Print the imports between the teams owning the modules (from --annotations) as a
+        /// matrix, and the modules whose dependencies are mostly owned by other teams,
+        /// instead of the graph
+        #[arg(long, requires = "annotations", group = "report")]
+        team_coupling: bool,
+
+        /// Annotation naming the team that owns a module, for --team-coupling
+        #[arg(
+            long,
+            value_name = "KEY",
+            default_value = "team",
+            requires = "team_coupling"
+        )]
+        owner_key: String,
+
+        /// Cobertura coverage report (coverage.xml) used to annotate nodes with line coverage
         #[arg(long)]
-        include_namespace_packages: bool,
+        coverage: Option<PathBuf>,
+
+        /// Print modules ranked by impact (transitive dependents x commits touching them)
+        /// instead of the graph (requires a git checkout)
+        #[arg(long, group = "report")]
+        impact_report: bool,
+
+        /// Annotate modules with their `impact`, `reach`, and `churn` in the graph output
+        /// (requires a git checkout)
+        #[arg(long)]
+        impact_scores: bool,
+
+        /// Count commits since this date for impact scores (anything `git log --since`
+        /// accepts)
+        #[arg(long, value_name = "DATE", default_value = "90 days ago")]
+        churn_since: String,
+
+        /// Exponents of reach and churn in the impact score, as REACH,CHURN (0 ignores a
+        /// factor)
+        #[arg(long, value_name = "REACH,CHURN", default_value = "1,1", value_parser = parse_impact_weights)]
+        impact_weights: impact::Weights,
+
+        /// Experimental: print suggested groupings for splitting a package instead of the graph
+        #[arg(long, value_name = "PACKAGE", group = "report")]
+        suggest_splits: Option<String>,
+
+        /// Print which modules of a package are imported from outside it (its de facto
+        /// public API), with the importing modules per consumer package, instead of the graph
+        #[arg(long, value_name = "PACKAGE", group = "report")]
+        api_surface: Option<String>,
+
+        /// Stop parsing after this many seconds and render the partial graph, listing the
+        /// files left unprocessed on stderr
+        #[arg(long, value_name = "SECS", value_parser = parse_timeout)]
+        timeout: Option<Duration>,
+
+        /// Print the resolved source root, exclusions, files found per directory, and
+        /// skipped paths instead of building the graph
+        #[arg(long)]
+        explain_config: bool,
+
+        /// Debugging: print an intermediate representation as JSON instead of the graph:
+        /// 'imports' (extracted per file), 'modules' (module to file), or 'resolution'
+        /// (how each imported name was resolved: resolved, unresolved, external, skipped)
+        #[arg(long, hide = true, value_name = "IR", value_parser = ["imports", "modules", "resolution"])]
+        emit: Option<String>,
+
+        /// Fail when the graph has an import cycle that the --baseline graph does not,
+        /// printing the imports that form it instead of the graph
+        #[arg(long, requires = "baseline", group = "report")]
+        forbid_new_cycles: bool,
+
+        /// Graph exported earlier with `--format json`, compared against by
+        /// --forbid-new-cycles
+        #[arg(long, value_name = "FILE", requires = "forbid_new_cycles")]
+        baseline: Option<PathBuf>,
     },
-}
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args = Args::parse();
+    /// Analyze Go module package dependencies
+    Go {
+        /// Path to the Go module root (the directory containing go.mod)
+        #[arg()]
+        path: PathBuf,
 
-    if args.verbose {
-        eprintln!("DEBUG {args:?}");
-    }
+        #[command(flatten)]
+        selection: SelectionArgs,
 
-    match args.command {
-        Command::Python {
-            path,
-            source_root,
-            format,
-            downstream,
-            downstream_module,
-            downstream_file,
-            upstream,
-            upstream_module,
-            upstream_file,
-            max_rank,
-            exclude_scripts,
-            include_orphans,
-            show_all,
-            include_namespace_packages,
-        } => {
-            // Determine the source root first (needed for parsing module inputs with file paths)
-            let actual_source_root = if let Some(explicit_root) = source_root.as_ref() {
-                explicit_root.clone()
-            } else {
-                python::detect_source_root(&path)?
-            };
+        #[command(flatten)]
+        output: OutputArgs,
 
-            let graph =
-                python::analyze_project(&path, Some(&actual_source_root), &exclude_scripts)?;
+        /// Include `_test.go` files when collecting imports
+        #[arg(long)]
+        include_tests: bool,
+    },
 
-            // Collect downstream module inputs from all three sources
-            let downstream_inputs: Vec<String> = downstream
-                .iter()
-                .flat_map(|csv| {
-                    csv.split(',')
-                        .map(str::trim)
-                        .filter(|s| !s.is_empty())
-                        .map(String::from)
-                })
-                .chain(downstream_module)
-                .chain(read_module_list_file(
-                    downstream_file,
-                    "--downstream-file",
-                    "--downstream",
-                )?)
-                .collect();
+    /// Analyze Protobuf schema dependencies
+    Proto {
+        /// Path to the directory containing the .proto files
+        #[arg()]
+        path: PathBuf,
 
-            // Collect upstream module inputs from all three sources
-            let upstream_inputs: Vec<String> = upstream
-                .iter()
-                .flat_map(|csv| {
-                    csv.split(',')
-                        .map(str::trim)
-                        .filter(|s| !s.is_empty())
-                        .map(String::from)
-                })
-                .chain(upstream_module)
-                .chain(read_module_list_file(
-                    upstream_file,
-                    "--upstream-file",
-                    "--upstream",
-                )?)
-                .collect();
+        /// Directory imports are resolved against, like `protoc -I` (can be repeated; defaults to the project root)
+        #[arg(long = "proto-path", short = 'I')]
+        proto_path: Vec<PathBuf>,
 
-            // Parse output format
-            let output_format = match format.as_str() {
-                "dot" => OutputFormat::Dot,
-                "mermaid" => OutputFormat::Mermaid,
-                "list" => OutputFormat::List,
-                "cytoscape" => OutputFormat::Cytoscape,
-                _ => unreachable!("Invalid format validated by clap"),
-            };
+        #[command(flatten)]
+        selection: SelectionArgs,
 
-            // Determine what kind of analysis to perform
-            let has_downstream = !downstream_inputs.is_empty();
-            let has_upstream = !upstream_inputs.is_empty();
+        #[command(flatten)]
+        output: OutputArgs,
+    },
 
-            // Validate show_all flag usage
-            if show_all && !has_downstream && !has_upstream {
-                return Err(
-                    "--show-all requires --downstream or --upstream to be specified".into(),
-                );
-            }
+    /// Merge the graphs of every analyzer configured in deptree.toml, linked by declared bridges
+    Polyglot {
+        /// Repository root containing deptree.toml
+        #[arg(default_value = ".")]
+        path: PathBuf,
 
-            if has_downstream || has_upstream {
-                // Parse downstream module inputs (can be dotted names or file paths)
-                let downstream_paths: Option<Vec<python::ModulePath>> = if has_downstream {
-                    let paths: Result<Vec<python::ModulePath>, String> = downstream_inputs
-                        .iter()
-                        .map(|input| parse_module_input(input, &path, &actual_source_root))
-                        .collect();
-                    Some(paths?)
-                } else {
-                    None
-                };
+        /// Configuration file to use instead of <path>/deptree.toml
+        #[arg(long)]
+        config: Option<PathBuf>,
 
-                // Parse upstream module inputs (can be dotted names or file paths)
-                let upstream_paths: Option<Vec<python::ModulePath>> = if has_upstream {
-                    let paths: Result<Vec<python::ModulePath>, String> = upstream_inputs
-                        .iter()
-                        .map(|input| parse_module_input(input, &path, &actual_source_root))
-                        .collect();
-                    Some(paths?)
-                } else {
-                    None
-                };
+        /// Take the selection and output options from a `[profiles.<name>]` config entry
+        #[arg(long, conflicts_with_all = [
+            "downstream", "downstream_module", "downstream_file",
+            "upstream", "upstream_module", "upstream_file",
+            "max_rank", "filter_state", "format", "include_orphans",
+            "show_all", "communities", "edges", "hide_module",
+        ])]
+        profile: Option<String>,
 
-                // Compute the filter set based on which flags are provided
-                let filter: std::collections::HashSet<python::ModulePath> =
-                    match (downstream_paths, upstream_paths) {
-                        (Some(down_paths), Some(up_paths)) => {
-                            // Both downstream and upstream specified: compute intersection
-                            let downstream_modules = graph.find_downstream(&down_paths, max_rank);
-                            let upstream_modules = graph.find_upstream(&up_paths, max_rank);
-
-                            let downstream_set: std::collections::HashSet<_> =
-                                downstream_modules.keys().cloned().collect();
-                            let upstream_set: std::collections::HashSet<_> =
-                                upstream_modules.keys().cloned().collect();
-
-                            downstream_set
-                                .intersection(&upstream_set)
-                                .cloned()
-                                .collect()
-                        }
-                        (Some(down_paths), None) => {
-                            // Only downstream specified
-                            let downstream_modules = graph.find_downstream(&down_paths, max_rank);
-                            downstream_modules.keys().cloned().collect()
-                        }
-                        (None, Some(up_paths)) => {
-                            // Only upstream specified
-                            let upstream_modules = graph.find_upstream(&up_paths, max_rank);
-                            upstream_modules.keys().cloned().collect()
-                        }
-                        (None, None) => {
-                            unreachable!("Already checked has_downstream || has_upstream")
-                        }
-                    };
-
-                match output_format {
-                    OutputFormat::Dot => {
-                        if show_all {
-                            println!(
-                                "{}",
-                                graph.to_dot_highlighted(
-                                    &filter,
-                                    include_orphans,
-                                    include_namespace_packages
-                                )
-                            );
-                        } else {
-                            println!(
-                                "{}",
-                                graph.to_dot_filtered(
-                                    &filter,
-                                    include_orphans,
-                                    include_namespace_packages
-                                )
-                            );
-                        }
-                    }
-                    OutputFormat::Mermaid => {
-                        if show_all {
-                            println!(
-                                "{}",
-                                graph.to_mermaid_highlighted(
-                                    &filter,
-                                    include_orphans,
-                                    include_namespace_packages
-                                )
-                            );
-                        } else {
-                            println!(
-                                "{}",
-                                graph.to_mermaid_filtered(
-                                    &filter,
-                                    include_orphans,
-                                    include_namespace_packages
-                                )
-                            );
-                        }
-                    }
-                    OutputFormat::Cytoscape => {
-                        if show_all {
-                            let data = graph.to_cytoscape_graph_data_highlighted(
-                                &filter,
-                                include_orphans,
-                                include_namespace_packages,
-                            );
-                            let html = cytoscape::render_cytoscape_html(&data)?;
-                            println!("{html}");
-                        } else {
-                            let data = graph.to_cytoscape_graph_data_filtered(
-                                &filter,
-                                include_orphans,
-                                include_namespace_packages,
-                            );
-                            let html = cytoscape::render_cytoscape_html(&data)?;
-                            println!("{html}");
-                        }
-                    }
-                    OutputFormat::List => {
-                        if show_all {
-                            return Err("--show-all cannot be used with --format list".into());
-                        }
-                        println!(
-                            "{}",
-                            graph.to_list_filtered(&filter, include_namespace_packages)
-                        );
-                    }
-                }
-            } else {
-                // Default behavior: output full graph in the specified format
-                match output_format {
-                    OutputFormat::Dot => {
-                        println!(
-                            "{}",
-                            graph.to_dot(include_orphans, include_namespace_packages)
-                        );
-                    }
-                    OutputFormat::Mermaid => {
-                        println!(
-                            "{}",
-                            graph.to_mermaid(include_orphans, include_namespace_packages)
-                        );
-                    }
-                    OutputFormat::Cytoscape => {
-                        let data = graph
-                            .to_cytoscape_graph_data(include_orphans, include_namespace_packages);
-                        let html = cytoscape::render_cytoscape_html(&data)?;
-                        println!("{html}");
-                    }
-                    OutputFormat::List => {
-                        return Err(
-                            "List format requires --downstream or --upstream to be specified"
-                                .into(),
-                        );
-                    }
-                }
-            }
-        }
-    }
+        /// Print the `[[services]]` containing the --downstream modules or a module importing
+        /// them, i.e. the services to redeploy and test, instead of the graph; changes may be
+        /// given as changed file paths (`git diff --name-only`); `--format list` prints only
+        /// the service names and `--format json` the whole rollup
+        #[arg(long, conflicts_with_all = [
+            "upstream", "upstream_module", "upstream_file", "profile",
+        ])]
+        impact_services: bool,
 
-    Ok(())
+        #[command(flatten)]
+        selection: SelectionArgs,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Check the import contracts and dependency budgets declared in deptree.toml (exit code 1 if any is broken or exceeded)
+    Check {
+        /// Repository root containing deptree.toml
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Configuration file to use instead of <path>/deptree.toml
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Baseline file of tolerated violations; only violations missing from it fail the check
+        #[arg(long)]
+        baseline: Option<PathBuf>,
+
+        /// Rewrite the baseline file with the current violations instead of failing
+        #[arg(long, requires = "baseline")]
+        update_baseline: bool,
+
+        /// Check only the contracts listed by a `[profiles.<name>]` config entry
+        #[arg(long)]
+        profile: Option<String>,
+    },
+
+    /// Pre-commit entry point: report the downstream reach of the staged files and the
+    /// contract violations and exceeded budgets they take part in (exit code 1 if any)
+    Hook {
+        /// Files to check, relative to the repository root it runs in (defaults to the files
+        /// staged in git); pre-commit passes the staged files
+        #[arg()]
+        files: Vec<PathBuf>,
+
+        /// Configuration file to use instead of ./deptree.toml
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Stop parsing after this many seconds and check the partial graph
+        #[arg(long, value_name = "SECS", default_value = "10", value_parser = parse_timeout)]
+        timeout: Duration,
+    },
+
+    /// List every import of a Python file by other files as `path:line:col` lines for
+    /// editor quickfix lists and problem matchers (direct importers only)
+    Importers {
+        /// File (or dotted module name) whose importers to list
+        #[arg()]
+        file: String,
+
+        #[command(flatten)]
+        project: PythonProjectArgs,
+    },
+
+    /// Summarize what extracting a Python package into its own repository entails: imports
+    /// crossing its boundary both ways, cycles through it, and a suggested dependency
+    /// direction
+    ExtractCheck {
+        /// Package to extract, as a dotted name or the path of its `__init__.py`
+        #[arg()]
+        package: String,
+
+        #[command(flatten)]
+        project: PythonProjectArgs,
+    },
+
+    /// List every line a module rename has to update (imports, from-imports, relative
+    /// imports, dynamic import literals), grouped by package, and the files to move
+    RenameCheck {
+        /// Module or package to rename, as a dotted name or a file path
+        #[arg()]
+        old: String,
+
+        /// New dotted name
+        #[arg()]
+        new: String,
+
+        #[command(flatten)]
+        project: PythonProjectArgs,
+
+        /// Print a shell script moving the files and rewriting the lines with `sed`
+        /// instead of the report
+        #[arg(long)]
+        sed: bool,
+    },
+
+    /// Print a 0-100 health score of a Python project combining import cycles, average
+    /// import depth, the largest fan-in, cross-package imports, and orphan modules, with
+    /// the score of each
+    Stats {
+        #[command(flatten)]
+        project: PythonProjectArgs,
+
+        /// Score namespace packages too (by default they are left out)
+        #[arg(long)]
+        include_namespace_packages: bool,
+
+        /// Weights of the measures, as KEY=WEIGHT pairs (keys cycles, depth, fan_in,
+        /// cross_package, orphans; unlisted ones weigh 1)
+        #[arg(long, value_name = "KEY=WEIGHT,...", value_parser = parse_health_weights)]
+        weights: Option<health::Weights>,
+
+        /// Output format: the breakdown as text, `json` with every measure's value, score,
+        /// and weight, or `openmetrics` gauges for tracking
+        #[arg(long, default_value = "text", value_parser = ["text", "json", "openmetrics"])]
+        format: String,
+    },
+
+    /// Serve the graph queries (upstream, downstream, path, search, metrics) of a Python
+    /// project as Model Context Protocol tools over stdio
+    Mcp {
+        #[command(flatten)]
+        project: PythonProjectArgs,
+    },
+
+    /// Compare two graphs exported with `--format json` and render what changed
+    Diff {
+        /// Graph before the change
+        #[arg()]
+        old: PathBuf,
+
+        /// Graph after the change
+        #[arg()]
+        new: PathBuf,
+
+        /// Output format: 'dot' and 'cytoscape' (with the `html` feature) draw added elements
+        /// green, removed ones red and dashed, and unchanged ones grey; 'list' prints only the
+        /// changes (default: dot)
+        #[arg(long, default_value = "dot", value_parser = clap::builder::PossibleValuesParser::new(DIFF_FORMATS))]
+        format: String,
+    },
+
+    /// Compare the imports of a Python project with the intended architecture declared in a
+    /// TOML file (components of packages and the dependencies allowed between them) and
+    /// report convergent, divergent, and absent dependencies
+    Conformance {
+        /// Architecture file: a `[components]` table of package lists and `[[allow]]`
+        /// entries with `from` and `to` components
+        #[arg()]
+        architecture: PathBuf,
+
+        #[command(flatten)]
+        project: PythonProjectArgs,
+
+        /// Output format: 'list' prints tables of the components and their dependencies;
+        /// 'dot' and 'cytoscape' (with the `html` feature) draw the component graph with
+        /// convergent dependencies green, divergent ones red, and absent ones grey (default: list)
+        #[arg(long, default_value = "list", value_parser = clap::builder::PossibleValuesParser::new(DIFF_FORMATS))]
+        format: String,
+    },
+
+    /// Check GraphData JSON files (`--format json` exports or hand-written viewer fixtures)
+    /// for dangling edges, duplicate ids, unknown node types, wrong orphan flags, and broken
+    /// parent references (exit code 1 if any)
+    Validate {
+        /// Graph files to check
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+
+    /// Print the JSON Schema of a payload: 'graph-data' (--format json output), 'filter-config', 'filter-result', or 'filter-preset'
+    Schema {
+        #[arg(default_value = "graph-data", value_parser = ["graph-data", "filter-config", "filter-result", "filter-preset"])]
+        payload: String,
+    },
+}
+
+/// Replace the selection and output flags with the options saved in a profile
+/// (`--profile` conflicts with those flags, so none of them were given)
+fn apply_profile(
+    profile: &config::Profile,
+    selection: &mut SelectionArgs,
+    output: &mut OutputArgs,
+) -> Result<(), String> {
+    // A badge needs --badge-module, which profiles don't set
+    let formats: Vec<&str> = FORMATS
+        .iter()
+        .copied()
+        .filter(|format| *format != "badge")
+        .collect();
+
+    if let Some(format) = &profile.format {
+        if !formats.contains(&format.as_str()) {
+            return Err(format!(
+                "Invalid profile format '{format}' (expected one of: {})",
+                formats.join(", ")
+            ));
+        }
+        output.format = vec![format.clone()];
+    }
+    if let Some(edges) = &profile.edges {
+        if parse_edge_direction(edges).is_none() {
+            return Err(format!(
+                "Invalid profile edges '{edges}' (expected one of: {})",
+                EDGES.join(", ")
+            ));
+        }
+        output.edges = edges.clone();
+    }
+    output.include_orphans = profile.include_orphans;
+    output.show_all = profile.show_all;
+    output.communities = profile.communities;
+    output.hide_module = profile.hide_modules.clone();
+
+    selection.upstream_module = profile.upstream.clone();
+    selection.downstream_module = profile.downstream.clone();
+    selection.max_rank = profile.max_rank;
+    selection.filter_state = profile.filter_state.clone();
+    Ok(())
+}
+
+/// Load the configuration and build the cross-language graph it describes, with the
+/// directory the configuration's paths are relative to
+fn load_polyglot_graph(
+    path: &Path,
+    config: Option<PathBuf>,
+) -> Result<(config::Config, PathBuf, polyglot::PolyglotGraph), Box<dyn std::error::Error>> {
+    let config_path = config.unwrap_or_else(|| path.join(config::CONFIG_FILE_NAME));
+    let loaded = config::Config::load(&config_path)?;
+    let base_dir = config_path.parent().unwrap_or(Path::new(".")).to_path_buf();
+    let graph = polyglot::analyze_polyglot(&loaded, &base_dir)?;
+    Ok((loaded, base_dir, graph))
+}
+
+/// Print warnings for import cycles and orphan nodes to stderr
+fn report_cycles_and_orphans<T: GraphId>(graph: &DependencyGraph<T>, orphan_label: &str) {
+    for cycle in graph.find_cycles() {
+        let members: Vec<String> = cycle.iter().map(GraphId::to_dotted).collect();
+        eprintln!("Warning: import cycle between {}", members.join(", "));
+    }
+    for orphan in graph.orphans() {
+        eprintln!(
+            "Warning: orphan {orphan_label} (no imports and not imported): {}",
+            orphan.to_dotted()
+        );
+    }
+}
+
+/// Compute the set of nodes selected by the upstream/downstream roots, or by the
+/// neighborhood roots (which the upstream/downstream flags conflict with): both what they
+/// import and what imports them
+fn compute_filter<T: GraphId>(
+    graph: &DependencyGraph<T>,
+    downstream: Option<Vec<T>>,
+    upstream: Option<Vec<T>>,
+    neighborhood: Option<Vec<T>>,
+    max_rank: Option<usize>,
+    weight_key: Option<&str>,
+) -> HashSet<T> {
+    // A viewer filter state with a `weightKey` measures the rank along weighted imports
+    let related = |roots: &[T], relation: Relation| -> HashSet<T> {
+        match weight_key.filter(|_| max_rank.is_some()) {
+            Some(key) => graph
+                .find_related_weighted(roots, relation, max_rank, |importer, imported| {
+                    import_weight(
+                        key,
+                        graph.edge_multiplicity(importer, imported),
+                        graph.annotations(imported),
+                    )
+                })
+                .into_keys()
+                .collect(),
+            None => graph
+                .find_related(roots, relation, max_rank)
+                .into_keys()
+                .collect(),
+        }
+    };
+    if let Some(roots) = neighborhood {
+        let mut selected = related(&roots, Relation::Dependents);
+        selected.extend(related(&roots, Relation::Dependencies));
+        return selected;
+    }
+    match (downstream, upstream) {
+        (Some(down_paths), Some(up_paths)) => {
+            // Both downstream and upstream specified: compute intersection
+            let downstream_set = related(&down_paths, Relation::Dependents);
+            let upstream_set = related(&up_paths, Relation::Dependencies);
+
+            downstream_set
+                .intersection(&upstream_set)
+                .cloned()
+                .collect()
+        }
+        (Some(down_paths), None) => related(&down_paths, Relation::Dependents),
+        (None, Some(up_paths)) => related(&up_paths, Relation::Dependencies),
+        (None, None) => unreachable!("Callers only filter when roots are specified"),
+    }
+}
+
+/// Render a graph according to the selection and output flags. `presets` are embedded in
+/// Cytoscape/JSON output for the viewer.
+fn render_graph<T>(
+    graph: &DependencyGraph<T>,
+    selection: Selection,
+    output: &OutputArgs,
+    include_namespace_packages: bool,
+    presets: &[FilterPreset],
+    parse_input: impl Fn(&str) -> Result<T, String>,
+) -> Result<Option<String>, Box<dyn std::error::Error>>
+where
+    T: GraphId + std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    if output.format.len() > 1 {
+        // One analysis, rendered once per format
+        if output.output.len() != output.format.len() {
+            return Err(format!(
+                "--format is given {} times, so --output needs one file per format in the same order (got {})",
+                output.format.len(),
+                output.output.len()
+            )
+            .into());
+        }
+        if output.publish.is_some() {
+            return Err("--publish takes a single --format".into());
+        }
+        for index in 0..output.format.len() {
+            render_format(
+                graph,
+                selection.clone(),
+                &output.nth_format(index),
+                include_namespace_packages,
+                presets,
+                &parse_input,
+            )?;
+        }
+        return Ok(None);
+    }
+    if output.output.len() > 1 {
+        return Err(
+            "--output can only be repeated along with --format, one file per format".into(),
+        );
+    }
+    render_format(
+        graph,
+        selection,
+        output,
+        include_namespace_packages,
+        presets,
+        parse_input,
+    )
+}
+
+/// [`render_graph`] for a single --format
+fn render_format<T>(
+    graph: &DependencyGraph<T>,
+    selection: Selection,
+    output: &OutputArgs,
+    include_namespace_packages: bool,
+    presets: &[FilterPreset],
+    parse_input: impl Fn(&str) -> Result<T, String>,
+) -> Result<Option<String>, Box<dyn std::error::Error>>
+where
+    T: GraphId + std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    let output_format = output.output_format();
+    if output_format == OutputFormat::Sqlite && output.output.is_empty() {
+        return Err("--format sqlite requires --output <FILE>".into());
+    }
+    let rendered = render_output(
+        graph,
+        selection,
+        output,
+        include_namespace_packages,
+        presets,
+        parse_input,
+    )?;
+    let bytes = match &rendered {
+        Rendered::Text(text) => format!("{text}\n").into_bytes(),
+        Rendered::Database(bytes) => bytes.clone(),
+    };
+    if let Some(url) = &output.publish {
+        let content_type = if output_format == OutputFormat::Badge && output.badge_svg {
+            "image/svg+xml"
+        } else {
+            output_format.content_type()
+        };
+        publish::publish(url, &bytes, content_type)?;
+        eprintln!("Published {} output to {url}", output.format[0]);
+    }
+    match (output.output.first(), rendered) {
+        (None, Rendered::Text(text)) => Ok(Some(text)),
+        (path, _) => {
+            let path = path.ok_or("--format sqlite requires --output <FILE>")?;
+            std::fs::write(path, bytes)
+                .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+            Ok(None)
+        }
+    }
+}
+
+/// Shrink what a DOT, Mermaid, or Cytoscape rendering of `visible` would draw to the
+/// --max-nodes/--max-edges limits, warning about it. `None` when no reduction is needed.
+fn limit_output<T: GraphId>(
+    graph: &DependencyGraph<T>,
+    visible: &HashSet<T>,
+    output: &OutputArgs,
+    include_orphans: bool,
+    include_namespace_packages: bool,
+    parse_input: impl Fn(&str) -> Result<T, String>,
+) -> Option<limits::Reduced<T>> {
+    let drawn = matches!(
+        output.output_format(),
+        OutputFormat::Dot | OutputFormat::Mermaid | OutputFormat::Cytoscape
+    );
+    if !drawn || (output.max_nodes.is_none() && output.max_edges.is_none()) {
+        return None;
+    }
+    let limits = limits::Limits {
+        max_nodes: output.max_nodes,
+        max_edges: output.max_edges,
+        fallback: limits::Fallback::parse(&output.limit_fallback),
+    };
+    // Packages are named the way module arguments are, so they parse back into ids
+    let ancestor =
+        |module: &T, depth: usize| parse_input(&T::join_segments(&module.segments()[..depth])).ok();
+    let reduced = limits::reduce(
+        graph,
+        visible,
+        &limits,
+        include_orphans,
+        include_namespace_packages,
+        ancestor,
+    )?;
+    eprintln!("Warning: {}", reduced.description);
+    Some(reduced)
+}
+
+fn render_output<T>(
+    graph: &DependencyGraph<T>,
+    selection: Selection,
+    output: &OutputArgs,
+    include_namespace_packages: bool,
+    presets: &[FilterPreset],
+    parse_input: impl Fn(&str) -> Result<T, String>,
+) -> Result<Rendered, Box<dyn std::error::Error>>
+where
+    T: GraphId + std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    let output_format = output.output_format();
+    // A viewer filter state can only hide what the output flags include
+    let view = selection.view.as_ref();
+    let include_orphans = output.include_orphans && view.is_none_or(|v| v.show_orphans);
+    let include_namespace_packages =
+        include_namespace_packages && view.is_none_or(|v| v.show_namespaces);
+    // Hidden modules: drop them but keep their neighbors connected
+    let bridged;
+    let graph = if output.hide_module.is_empty() && !output.hide_generated {
+        graph
+    } else {
+        let mut hidden: HashSet<T> = HashSet::new();
+        if output.hide_generated {
+            hidden.extend(graph.generated_modules().cloned());
+        }
+        for input in &output.hide_module {
+            let module = parse_input(input)?;
+            if !graph.contains(&module) {
+                eprintln!("Warning: --hide-module {input} matches no module");
+            }
+            hidden.insert(module);
+        }
+        bridged = graph.hide_nodes(&hidden);
+        &bridged
+    };
+    let sampled;
+    let graph = match output.sample {
+        None => graph,
+        Some(size) => {
+            let seed = output.seed.unwrap_or(0);
+            let kept = sample::sample(graph, size as usize, seed);
+            eprintln!(
+                "Sampled {} of {} modules (seed {seed})",
+                kept.len(),
+                graph.nodes().count()
+            );
+            sampled = graph.filter_nodes(|module| kept.contains(module));
+            &sampled
+        }
+    };
+    let options = render_options(output);
+    let annotated;
+    let graph = match &output.annotations {
+        None => graph,
+        Some(path) => {
+            let mut copy = graph.clone();
+            if annotations::apply_to_graph(&mut copy, &annotations::load(path)?) == 0 {
+                eprintln!(
+                    "Warning: no module of the annotations file {} matched the graph",
+                    path.display()
+                );
+            }
+            annotated = copy;
+            &annotated
+        }
+    };
+
+    // Anonymize last, so --hide-module and --annotations still refer to the real names;
+    // module arguments are translated, and viewer presets (which name modules) dropped
+    let anonymized;
+    let mut mapping: HashMap<T, T> = HashMap::new();
+    let (graph, presets) = if output.anonymize {
+        let mut anonymizer = match &output.anonymize_map {
+            Some(path) => anonymize::Anonymizer::load(path)?,
+            None => anonymize::Anonymizer::default(),
+        };
+        (anonymized, mapping) = anonymizer.anonymize(graph)?;
+        if let Some(path) = &output.anonymize_map {
+            anonymizer.save(path)?;
+        }
+        (&anonymized, &[][..])
+    } else {
+        (graph, presets)
+    };
+    let parse_input = |input: &str| -> Result<T, String> {
+        let module = parse_input(input)?;
+        Ok(mapping.get(&module).cloned().unwrap_or(module))
+    };
+
+    if output_format == OutputFormat::Badge {
+        let input = output
+            .module
+            .as_deref()
+            .ok_or("--format badge requires --module")?;
+        let module = parse_input(input)?;
+        if !graph.contains(&module) {
+            return Err(format!("--module {input} matches no module").into());
+        }
+        let metric = badge::BadgeMetric::parse(&output.badge_metric)
+            .expect("Invalid badge metric validated by clap");
+        let badge = badge::badge(graph, &module, metric);
+        return Ok(if output.badge_svg {
+            badge.to_svg()
+        } else {
+            badge.to_shields_json()
+        }
+        .into());
+    }
+
+    let edge_direction = view.map_or_else(
+        || parse_edge_direction(&output.edges).unwrap_or_default(),
+        |v| v.edge_direction,
+    );
+
+    // Serialize Cytoscape/JSON/GEXF graph data, or store it with its metrics in SQLite
+    let emit = |mut data: GraphData,
+                metrics: &dyn Fn() -> GraphMetrics|
+     -> Result<Rendered, Box<dyn std::error::Error>> {
+        if let Some(config) = data.config.as_mut()
+            && !presets.is_empty()
+        {
+            config.presets = Some(presets.to_vec());
+        }
+        if let Some(seed) = output.layout_seed
+            && matches!(output_format, OutputFormat::Json | OutputFormat::Cytoscape)
+        {
+            layout::add_layout_hints(&mut data, seed);
+        }
+        match output_format {
+            OutputFormat::Badge => unreachable!("Badges are rendered before the graph"),
+            OutputFormat::Json => Ok(serde_json::to_string_pretty(&data)?.into()),
+            OutputFormat::Gexf => Ok(gexf::render_gexf(&data).into()),
+            OutputFormat::Sqlite => {
+                let roots = |inputs: &[String]| {
+                    if !output.anonymize {
+                        return inputs.join(",");
+                    }
+                    let names: Vec<String> = inputs
+                        .iter()
+                        .filter_map(|input| parse_input(input).ok())
+                        .map(|module| module.to_dotted())
+                        .collect();
+                    names.join(",")
+                };
+                let metadata = [
+                    ("downstream", roots(&selection.downstream)),
+                    ("upstream", roots(&selection.upstream)),
+                    ("modules", roots(&selection.neighborhood)),
+                    (
+                        "max_rank",
+                        selection
+                            .max_rank
+                            .map_or_else(String::new, |r| r.to_string()),
+                    ),
+                    ("edges", output.edges.clone()),
+                ];
+                Ok(Rendered::Database(sqlite::export(
+                    &data,
+                    &metrics(),
+                    &metadata,
+                )?))
+            }
+            #[cfg(feature = "html")]
+            _ => Ok(cytoscape::render_cytoscape_html(&data)?.into()),
+            #[cfg(not(feature = "html"))]
+            _ => unreachable!("--format cytoscape is rejected without the html feature"),
+        }
+    };
+
+    // Module arguments select in both directions
+    let has_downstream = !selection.downstream.is_empty() || !selection.neighborhood.is_empty();
+    let has_upstream = !selection.upstream.is_empty() || !selection.neighborhood.is_empty();
+
+    // Validate show_all flag usage
+    if output.show_all && !has_downstream && !has_upstream {
+        return Err("--show-all requires --downstream or --upstream to be specified".into());
+    }
+    if edge_direction != EdgeDirection::Both && !has_downstream && !has_upstream {
+        return Err("--edges requires --downstream or --upstream to be specified".into());
+    }
+
+    if output.communities {
+        if has_downstream || has_upstream {
+            return Err("--communities cannot be combined with --downstream or --upstream".into());
+        }
+        let detected = communities::detect(graph, include_orphans, include_namespace_packages);
+        return match output_format {
+            OutputFormat::Badge => unreachable!("Badges are rendered before the graph"),
+            OutputFormat::Dot => Ok(graph
+                .to_dot_communities(
+                    &detected,
+                    include_orphans,
+                    include_namespace_packages,
+                    &options,
+                )
+                .into()),
+            OutputFormat::Cytoscape
+            | OutputFormat::Json
+            | OutputFormat::Sqlite
+            | OutputFormat::Gexf => emit(
+                graph.to_cytoscape_graph_data_communities(
+                    &detected,
+                    include_orphans,
+                    include_namespace_packages,
+                    &options,
+                ),
+                &|| graph.metrics(None, include_orphans, include_namespace_packages),
+            ),
+            OutputFormat::List => Ok(communities::render_report(graph, &detected).into()),
+            OutputFormat::Mermaid | OutputFormat::OpenMetrics => Err(
+                "--communities supports the dot, cytoscape, json, sqlite, gexf, and list formats"
+                    .into(),
+            ),
+        };
+    }
+
+    if let Some(key) = &output.group_by {
+        if has_downstream || has_upstream {
+            return Err("--group-by cannot be combined with --downstream or --upstream".into());
+        }
+        let groups = annotations::groups(graph, key);
+        if groups.is_empty() {
+            eprintln!("Warning: no module has a '{key}' annotation");
+        }
+        return match output_format {
+            OutputFormat::Badge => unreachable!("Badges are rendered before the graph"),
+            OutputFormat::Dot => Ok(graph
+                .to_dot_grouped(
+                    &groups,
+                    include_orphans,
+                    include_namespace_packages,
+                    &options,
+                )
+                .into()),
+            OutputFormat::Cytoscape
+            | OutputFormat::Json
+            | OutputFormat::Sqlite
+            | OutputFormat::Gexf => emit(
+                graph.to_cytoscape_graph_data_grouped(
+                    &groups,
+                    include_orphans,
+                    include_namespace_packages,
+                    &options,
+                ),
+                &|| graph.metrics(None, include_orphans, include_namespace_packages),
+            ),
+            OutputFormat::Mermaid | OutputFormat::List | OutputFormat::OpenMetrics => {
+                Err("--group-by supports the dot, cytoscape, json, sqlite, and gexf formats".into())
+            }
+        };
+    }
+
+    if !has_downstream && !has_upstream && view.is_none() {
+        // Default behavior: output full graph in the specified format
+        let limited;
+        let graph = match limit_output(
+            graph,
+            &graph.nodes().cloned().collect(),
+            output,
+            include_orphans,
+            include_namespace_packages,
+            parse_input,
+        ) {
+            Some(reduced) => {
+                limited = reduced.graph;
+                &limited
+            }
+            None => graph,
+        };
+        return match output_format {
+            OutputFormat::Badge => unreachable!("Badges are rendered before the graph"),
+            OutputFormat::Dot => Ok(graph
+                .to_dot_clustered(
+                    include_orphans,
+                    include_namespace_packages,
+                    dot_clustering(output, DotClustering::PACKAGES),
+                    &options,
+                )
+                .into()),
+            OutputFormat::Mermaid => Ok(graph
+                .to_mermaid(include_orphans, include_namespace_packages, &options)
+                .into()),
+            OutputFormat::Cytoscape
+            | OutputFormat::Json
+            | OutputFormat::Sqlite
+            | OutputFormat::Gexf => emit(
+                graph.to_cytoscape_graph_data(
+                    include_orphans,
+                    include_namespace_packages,
+                    &options,
+                ),
+                &|| graph.metrics(None, include_orphans, include_namespace_packages),
+            ),
+            OutputFormat::List => {
+                Err("List format requires --downstream or --upstream to be specified".into())
+            }
+            OutputFormat::OpenMetrics => Ok(graph
+                .to_openmetrics(None, include_orphans, include_namespace_packages)
+                .into()),
+        };
+    }
+
+    // Parse module inputs (the accepted syntax depends on the analyzer)
+    let parse_all = |inputs: &[String]| -> Result<Option<Vec<T>>, String> {
+        if inputs.is_empty() {
+            return Ok(None);
+        }
+        inputs
+            .iter()
+            .map(|input| parse_input(input))
+            .collect::<Result<Vec<T>, String>>()
+            .map(Some)
+    };
+    let downstream_paths = parse_all(&selection.downstream)?;
+    let upstream_paths = parse_all(&selection.upstream)?;
+    let neighborhood_paths = parse_all(&selection.neighborhood)?;
+    let roots: Vec<T> = downstream_paths
+        .iter()
+        .chain(&upstream_paths)
+        .chain(&neighborhood_paths)
+        .flatten()
+        .cloned()
+        .collect();
+
+    let mut filter = if has_downstream || has_upstream {
+        compute_filter(
+            graph,
+            downstream_paths,
+            upstream_paths,
+            neighborhood_paths,
+            selection.max_rank,
+            view.and_then(|view| view.weight_key.as_deref()),
+        )
+    } else {
+        graph.nodes().cloned().collect()
+    };
+
+    // Viewer filters: hide excluded scripts, and "show only highlighted" off means --show-all
+    let show_all = match view {
+        Some(view) => {
+            filter.retain(|node| {
+                !graph.is_script(node)
+                    || !view
+                        .exclude_patterns
+                        .iter()
+                        .any(|pattern| matches_pattern(&node.to_dotted(), pattern))
+            });
+            !view.highlighted_only && (has_downstream || has_upstream)
+        }
+        None => output.show_all,
+    };
+
+    // Edge direction: drop edges pointing the wrong way relative to the roots. Orphans are
+    // settled first so nodes that merely lose their edges stay in the output.
+    let directed;
+    let (graph, include_orphans) = if edge_direction == EdgeDirection::Both {
+        (graph, include_orphans)
+    } else {
+        let upstream: HashSet<T> = graph.find_upstream(&roots, None).into_keys().collect();
+        let downstream: HashSet<T> = graph.find_downstream(&roots, None).into_keys().collect();
+        let orphans: HashSet<T> = if include_orphans {
+            HashSet::new()
+        } else {
+            graph.orphans_in(OrphanScope::Graph, include_namespace_packages)
+        };
+        directed = graph
+            .filter_nodes(|node| !orphans.contains(node))
+            .filter_edges(|from, to, _| edge_direction.keeps(from, to, &upstream, &downstream));
+        (&directed, true)
+    };
+
+    // Highlighted output draws every node, filtered output only the selection
+    let visible = if show_all {
+        graph.nodes().cloned().collect()
+    } else {
+        filter.clone()
+    };
+    let limited;
+    let graph = match limit_output(
+        graph,
+        &visible,
+        output,
+        include_orphans,
+        include_namespace_packages,
+        parse_input,
+    ) {
+        Some(reduced) => {
+            filter = filter
+                .iter()
+                .filter_map(|module| reduced.mapping.get(module).cloned())
+                .collect();
+            limited = reduced.graph;
+            &limited
+        }
+        None => graph,
+    };
+
+    match output_format {
+        OutputFormat::Badge => unreachable!("Badges are rendered before the graph"),
+        OutputFormat::Dot if show_all => Ok(graph
+            .to_dot_highlighted_clustered(
+                &filter,
+                include_orphans,
+                include_namespace_packages,
+                dot_clustering(output, DotClustering::ALL),
+                &options,
+            )
+            .into()),
+        OutputFormat::Dot => Ok(graph
+            .to_dot_filtered_clustered(
+                &filter,
+                include_orphans,
+                include_namespace_packages,
+                dot_clustering(output, DotClustering::PACKAGES),
+                &options,
+            )
+            .into()),
+        OutputFormat::Mermaid if show_all => Ok(graph
+            .to_mermaid_highlighted(
+                &filter,
+                include_orphans,
+                include_namespace_packages,
+                &options,
+            )
+            .into()),
+        OutputFormat::Mermaid => Ok(graph
+            .to_mermaid_filtered(
+                &filter,
+                include_orphans,
+                include_namespace_packages,
+                &options,
+            )
+            .into()),
+        OutputFormat::Cytoscape
+        | OutputFormat::Json
+        | OutputFormat::Sqlite
+        | OutputFormat::Gexf => {
+            let data = if show_all {
+                graph.to_cytoscape_graph_data_highlighted(
+                    &filter,
+                    include_orphans,
+                    include_namespace_packages,
+                    &options,
+                )
+            } else {
+                graph.to_cytoscape_graph_data_filtered(
+                    &filter,
+                    include_orphans,
+                    include_namespace_packages,
+                    &options,
+                )
+            };
+            // Highlighted output draws every node, so its metrics cover the whole graph
+            let metric_filter = (!show_all).then_some(&filter);
+            emit(data, &|| {
+                graph.metrics(metric_filter, include_orphans, include_namespace_packages)
+            })
+        }
+        OutputFormat::List => {
+            if output.show_all {
+                return Err("--show-all cannot be used with --format list".into());
+            }
+            Ok(graph
+                .to_list_filtered(&filter, include_namespace_packages)
+                .into())
+        }
+        OutputFormat::OpenMetrics => {
+            if show_all {
+                return Err("--show-all cannot be used with --format openmetrics".into());
+            }
+            Ok(graph
+                .to_openmetrics(Some(&filter), include_orphans, include_namespace_packages)
+                .into())
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
+
+fn run() -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let args = Args::parse();
+
+    if args.verbose {
+        eprintln!("DEBUG {args:?}");
+    }
+
+    let rendered = match args.command {
+        Command::Python {
+            path,
+            modules,
+            source_root,
+            selection,
+            output,
+            exclude_scripts,
+            cluster_scripts,
+            script_category,
+            include_namespace_packages,
+            module_map,
+            no_path_dependencies,
+            no_scripts,
+            platform,
+            strict_resolution,
+            split_package_init,
+            lifecycle,
+            lifecycle_report,
+            python_version,
+            runtime_only,
+            import_time_only,
+            import_profile,
+            slowest,
+            side_effect_hotspots,
+            dead_code,
+            test_reach,
+            test_module,
+            critical_path,
+            team_coupling,
+            owner_key,
+            entry_point,
+            coverage,
+            impact_report,
+            impact_scores,
+            churn_since,
+            impact_weights,
+            suggest_splits,
+            api_surface,
+            timeout,
+            explain_config,
+            emit,
+            forbid_new_cycles,
+            baseline,
+        } => {
+            // The time budget includes fetching archives and git sources
+            let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+            // Archives and git sources are unpacked into a temporary directory that lives
+            // until the graph is rendered
+            let source = source::ProjectSource::parse(&path);
+            let project = source.fetch()?;
+            let fetched = !matches!(source, source::ProjectSource::Local(_));
+            let path = project.root().to_path_buf();
+
+            let source_root = source_root.map(|explicit_root| {
+                if fetched
+                    && explicit_root.is_relative()
+                    && explicit_root != Path::new(python::ALL_SOURCE_ROOTS)
+                {
+                    path.join(explicit_root)
+                } else {
+                    explicit_root
+                }
+            });
+            let options = python::AnalysisOptions {
+                python_version,
+                module_map,
+                skip_path_dependencies: no_path_dependencies,
+                skip_scripts: no_scripts,
+                platform,
+                strict_resolution,
+                lifecycles: lifecycle,
+                deadline,
+                split_package_init,
+            };
+            // `auto-all` is resolved now, so everything below sees a single source root
+            let (source_root, options) = match source_root {
+                Some(all) if all == Path::new(python::ALL_SOURCE_ROOTS) => {
+                    let (root, options) = resolve_source_root(&path, Some(all), options)?;
+                    (Some(root), options)
+                }
+                source_root => (source_root, options),
+            };
+
+            if explain_config {
+                let explanation =
+                    explain::explain(&path, source_root.as_deref(), &exclude_scripts, &options)?;
+                print!("{}", explain::render_report(&explanation));
+                return Ok(
+                    if explanation.source_root.is_some() && explanation.ambiguous_roots.is_empty() {
+                        ExitCode::SUCCESS
+                    } else {
+                        ExitCode::from(EXIT_ERROR)
+                    },
+                );
+            }
+
+            // Determine the source root first (needed for parsing module inputs with file paths)
+            let actual_source_root = match source_root {
+                Some(explicit_root) => explicit_root,
+                None => python::detect_source_root(&path)?,
+            };
+            if let Some(emit) = emit {
+                let emit = emit::Emit::parse(&emit).expect("Invalid --emit validated by clap");
+                let (_, trace) = python::analyze_project_traced(
+                    &path,
+                    Some(&actual_source_root),
+                    &exclude_scripts,
+                    &options,
+                )?;
+                print!("{}", trace.to_json(emit));
+                return Ok(ExitCode::SUCCESS);
+            }
+            // --dead-code reads the dynamic imports the trace records
+            let (mut graph, trace) = if dead_code {
+                let (graph, trace) = python::analyze_project_traced(
+                    &path,
+                    Some(&actual_source_root),
+                    &exclude_scripts,
+                    &options,
+                )?;
+                (graph, Some(trace))
+            } else {
+                let graph = python::analyze_project_with_options(
+                    &path,
+                    Some(&actual_source_root),
+                    &exclude_scripts,
+                    &options,
+                )?;
+                (graph, None)
+            };
+            python::group_scripts(&mut graph, cluster_scripts, &script_category);
+            if let Some(profile_path) = &import_profile {
+                let timings = import_profile::load(profile_path)?;
+                if import_profile::apply_to_graph(&mut graph, &timings) == 0 {
+                    eprintln!(
+                        "Warning: no module in {} matches the analyzed project",
+                        profile_path.display()
+                    );
+                }
+            }
+            if let Some(report_path) = &coverage {
+                let files = coverage::load(report_path)?;
+                if coverage::apply_to_graph(&mut graph, &files) == 0 {
+                    eprintln!(
+                        "Warning: no module in {} matches the analyzed project",
+                        report_path.display()
+                    );
+                }
+            }
+            let mut graph = match (runtime_only, import_time_only) {
+                (true, _) => graph.filter_edges(|_, _, kind| kind == EdgeKind::Deferred),
+                (_, true) => graph.filter_edges(|_, _, kind| kind == EdgeKind::ImportTime),
+                _ => graph,
+            };
+            if impact_report || impact_scores {
+                let churn = impact::git_churn(&path, &churn_since)?;
+                let scores = impact::impact_scores(&graph, &churn, impact_weights);
+                if impact_report {
+                    print!("{}", impact::render_report(&scores, &churn_since));
+                    return Ok(ExitCode::SUCCESS);
+                }
+                impact::annotate(&mut graph, &scores);
+            }
+
+            if let Some(limit) = slowest {
+                print!("{}", import_profile::render_slowest_report(&graph, limit));
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            if let Some(limit) = side_effect_hotspots {
+                let hotspots = hotspots::side_effect_hotspots(&graph);
+                print!("{}", hotspots::render_report(&hotspots, limit));
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            if let Some(trace) = &trace {
+                let mut declared = python::declared_entry_points(&path)?;
+                for input in &entry_point {
+                    declared.push(parse_module_input(input, &path, &actual_source_root)?);
+                }
+                let report = deadcode::dead_code(&graph, trace, &declared);
+                print!("{}", deadcode::render_report(&report));
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            if test_reach {
+                let reach = testreach::test_reach(&graph, &test_module);
+                print!("{}", testreach::render_report(&reach));
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            if let Some(entry) = critical_path {
+                let entry = parse_module_input(&entry, &path, &actual_source_root)?;
+                let report = critical::render_report(&graph, &entry).ok_or_else(|| {
+                    format!("{} is not part of the analyzed project", entry.to_dotted())
+                })?;
+                print!("{report}");
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            if team_coupling && let Some(path) = &output.annotations {
+                let mut owned = graph.clone();
+                annotations::apply_to_graph(&mut owned, &annotations::load(path)?);
+                let coupling = coupling::team_coupling(&owned, &owner_key);
+                print!("{}", coupling::render_report(&coupling));
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            if lifecycle_report {
+                let report = lifecycle::lifecycle_report(&graph);
+                print!("{}", lifecycle::render_report(&report));
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            if forbid_new_cycles && let Some(baseline_path) = baseline {
+                let recorded = cycles::load_baseline::<python::ModulePath>(&baseline_path)?;
+                let found = cycles::new_cycles(&graph, &recorded);
+                let existing = recorded.find_cycles().len();
+                print!("{}", cycles::render_report(&graph, &found, existing));
+                return Ok(if found.is_empty() {
+                    ExitCode::SUCCESS
+                } else {
+                    ExitCode::from(EXIT_CONTRACTS_BROKEN)
+                });
+            }
+
+            if let Some(package) = suggest_splits {
+                let package = parse_module_input(&package, &path, &actual_source_root)?;
+                let suggestion = splits::suggest_splits(&graph, &package)?;
+                print!("{}", splits::render_report(&suggestion));
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            if let Some(package) = api_surface {
+                let package = parse_module_input(&package, &path, &actual_source_root)?;
+                let surface = surface::api_surface(&graph, &package)?;
+                print!("{}", surface::render_report(&surface));
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            // Module inputs can be dotted names or file paths
+            render_graph(
+                &graph,
+                Selection {
+                    neighborhood: modules,
+                    ..selection.collect()?
+                },
+                &output,
+                include_namespace_packages,
+                &[],
+                |input| parse_module_input(input, &path, &actual_source_root),
+            )?
+        }
+        Command::Go {
+            path,
+            selection,
+            output,
+            include_tests,
+        } => {
+            let module_path = go::read_module_path(&path)?;
+            let graph = go::analyze_module(&path, include_tests)?;
+
+            // Package inputs can be full import paths or directories relative to the module root
+            render_graph(&graph, selection.collect()?, &output, false, &[], |input| {
+                go::resolve_package_input(input, &module_path)
+                    .ok_or_else(|| format!("Invalid package: {input}"))
+            })?
+        }
+        Command::Proto {
+            path,
+            proto_path,
+            selection,
+            output,
+        } => {
+            let import_roots = proto::resolve_import_roots(&path, &proto_path)?;
+            let graph = proto::analyze_protos(&path, &proto_path)?;
+            report_cycles_and_orphans(&graph, "schema");
+
+            // Schema inputs can be import paths or paths to .proto files
+            render_graph(&graph, selection.collect()?, &output, false, &[], |input| {
+                proto::resolve_proto_input(input, &import_roots)
+            })?
+        }
+        Command::Polyglot {
+            path,
+            config,
+            profile,
+            impact_services,
+            mut selection,
+            mut output,
+        } => {
+            let (loaded, base_dir, graph) = load_polyglot_graph(&path, config)?;
+            if impact_services {
+                let changes = selection.collect()?.downstream;
+                if changes.is_empty() {
+                    return Err("--impact-services needs the changed modules or files \
+                        (--downstream, --downstream-module, or --downstream-file)"
+                        .into());
+                }
+                let roots = hook::SourceRoots::from_config(&loaded, &base_dir)?;
+                let impact =
+                    services::impact_services(&graph, &roots, &path, &loaded.services, &changes)?;
+                match output.output_format() {
+                    OutputFormat::List => print!("{}", services::render_list(&impact)),
+                    OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&impact)?),
+                    _ => print!("{}", services::render_report(&impact)),
+                }
+                return Ok(ExitCode::SUCCESS);
+            }
+            if let Some(name) = &profile {
+                apply_profile(loaded.profile(name)?, &mut selection, &mut output)
+                    .map_err(|e| format!("Profile '{name}': {e}"))?;
+            }
+            if output.group_min_children.is_none() {
+                output.group_min_children = loaded.grouping.min_children.map(|n| n.get() as u64);
+            }
+            if output.group_max_depth.is_none() {
+                output.group_max_depth = loaded.grouping.max_depth;
+            }
+            let presets: Vec<FilterPreset> = loaded
+                .presets
+                .iter()
+                .map(config::Preset::to_filter_preset)
+                .collect();
+            let node_ids: HashSet<String> = graph.nodes().map(GraphId::to_dotted).collect();
+            for preset in &presets {
+                let roots = preset.config.upstream_roots.iter();
+                for root in roots.chain(&preset.config.downstream_roots) {
+                    if !node_ids.contains(root) {
+                        eprintln!(
+                            "Warning: preset '{}' refers to unknown node {root}",
+                            preset.name
+                        );
+                    }
+                }
+            }
+
+            // Nodes are referenced as <language>:<name>, e.g. proto:svc/v1/user.proto
+            render_graph(
+                &graph,
+                selection.collect()?,
+                &output,
+                false,
+                &presets,
+                |input| polyglot::PolyglotNode::parse(input).map_err(|e| e.to_string()),
+            )?
+        }
+        Command::Check {
+            path,
+            config,
+            baseline,
+            update_baseline,
+            profile,
+        } => {
+            let (loaded, _, graph) = load_polyglot_graph(&path, config)?;
+            let profile = profile
+                .as_deref()
+                .map(|name| loaded.profile(name))
+                .transpose()?;
+            let selected = loaded.contracts_for(profile)?;
+            let budgets = loaded.budgets_for(profile)?;
+            // Budgets alone are enough to check
+            let mut results = if selected.is_empty() && !budgets.is_empty() {
+                Vec::new()
+            } else {
+                contracts::check_contracts(&graph, &selected)?
+            };
+            let budget_results = budgets::check_budgets(&graph, &budgets)?;
+
+            if let Some(baseline_path) = &baseline {
+                let recorded = if update_baseline {
+                    let current = baseline::Baseline::from_results(&results);
+                    current.save(baseline_path)?;
+                    eprintln!(
+                        "Wrote {} violation(s) to {}",
+                        current.violations.len(),
+                        baseline_path.display()
+                    );
+                    current
+                } else {
+                    baseline::Baseline::load(baseline_path)?
+                };
+
+                let stale = recorded.apply(&mut results);
+                if !stale.is_empty() {
+                    eprintln!(
+                        "Note: {} baselined violation(s) no longer occur; run with --update-baseline to prune them",
+                        stale.len()
+                    );
+                }
+            }
+
+            if !results.is_empty() {
+                print!("{}", contracts::render_report(&results));
+            }
+            if !budget_results.is_empty() {
+                if !results.is_empty() {
+                    println!();
+                }
+                print!("{}", budgets::render_report(&budget_results));
+            }
+
+            return Ok(
+                if results.iter().all(contracts::ContractResult::is_kept)
+                    && budget_results.iter().all(budgets::BudgetResult::is_kept)
+                {
+                    ExitCode::SUCCESS
+                } else {
+                    ExitCode::from(EXIT_CONTRACTS_BROKEN)
+                },
+            );
+        }
+        Command::Hook {
+            files,
+            config,
+            timeout,
+        } => {
+            let deadline = Instant::now() + timeout;
+            // pre-commit runs hooks from the repository root
+            let root = Path::new(".");
+            let files = if files.is_empty() {
+                hook::staged_files(root)?
+            } else {
+                files
+            };
+            let config_path = config.unwrap_or_else(|| root.join(config::CONFIG_FILE_NAME));
+            let loaded = config::Config::load(&config_path)?;
+            let base_dir = config_path.parent().unwrap_or(Path::new("."));
+            let graph =
+                polyglot::analyze_polyglot_with_deadline(&loaded, base_dir, Some(deadline))?;
+            let results = if loaded.contracts.is_empty() {
+                Vec::new()
+            } else {
+                contracts::check_contracts(&graph, &loaded.contracts)?
+            };
+            let budgets = budgets::check_budgets(&graph, &loaded.budgets)?;
+            let roots = hook::SourceRoots::from_config(&loaded, base_dir)?;
+
+            let report = hook::run_hook(&graph, &roots, root, &files, results, budgets);
+            print!("{}", hook::render_report(&report));
+            return Ok(if report.passed() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::from(EXIT_CONTRACTS_BROKEN)
+            });
+        }
+        Command::Importers {
+            file,
+            project:
+                PythonProjectArgs {
+                    path,
+                    source_root,
+                    exclude_scripts,
+                },
+        } => {
+            let (source_root, options) =
+                resolve_source_root(&path, source_root, python::AnalysisOptions::default())?;
+            let graph = python::analyze_project_with_options(
+                &path,
+                Some(&source_root),
+                &exclude_scripts,
+                &options,
+            )?;
+            let module = parse_module_input(&file, &path, &source_root)?;
+            if !graph.contains(&module) {
+                return Err(format!("{file} is not part of the analyzed project").into());
+            }
+            let (found, unlocated) = importers::importers(&graph, &module);
+            for importer in &unlocated {
+                eprintln!(
+                    "Warning: {} imports {} at an unrecorded location",
+                    importer.to_dotted(),
+                    module.to_dotted()
+                );
+            }
+            if found.is_empty() && unlocated.is_empty() {
+                eprintln!("No module imports {}", module.to_dotted());
+            }
+            print!("{}", importers::render_quickfix(&found, &module, &path));
+            return Ok(ExitCode::SUCCESS);
+        }
+        Command::ExtractCheck {
+            package,
+            project:
+                PythonProjectArgs {
+                    path,
+                    source_root,
+                    exclude_scripts,
+                },
+        } => {
+            let (source_root, options) =
+                resolve_source_root(&path, source_root, python::AnalysisOptions::default())?;
+            let graph = python::analyze_project_with_options(
+                &path,
+                Some(&source_root),
+                &exclude_scripts,
+                &options,
+            )?;
+            let package = parse_module_input(&package, &path, &source_root)?;
+            let extraction = extraction::extraction(&graph, &package)?;
+            print!("{}", extraction::render_report(&extraction));
+            return Ok(ExitCode::SUCCESS);
+        }
+        Command::RenameCheck {
+            old,
+            new,
+            project:
+                PythonProjectArgs {
+                    path,
+                    source_root,
+                    exclude_scripts,
+                },
+            sed,
+        } => {
+            let (source_root, options) =
+                resolve_source_root(&path, source_root, python::AnalysisOptions::default())?;
+            let (_, trace) = python::analyze_project_traced(
+                &path,
+                Some(&source_root),
+                &exclude_scripts,
+                &options,
+            )?;
+            let old = parse_module_input(&old, &path, &source_root)?;
+            let check = rename::rename_check(&trace, &old.to_dotted(), &new)?;
+            if sed {
+                print!("{}", rename::render_sed_script(&check, &path));
+            } else {
+                print!("{}", rename::render_report(&check, &path));
+            }
+            return Ok(ExitCode::SUCCESS);
+        }
+        Command::Stats {
+            project:
+                PythonProjectArgs {
+                    path,
+                    source_root,
+                    exclude_scripts,
+                },
+            include_namespace_packages,
+            weights,
+            format,
+        } => {
+            let (source_root, options) =
+                resolve_source_root(&path, source_root, python::AnalysisOptions::default())?;
+            let graph = python::analyze_project_with_options(
+                &path,
+                Some(&source_root),
+                &exclude_scripts,
+                &options,
+            )?;
+            let scored = graph.filter_nodes(|module| {
+                include_namespace_packages || !graph.is_namespace_package(module)
+            });
+            let health = health::health(&scored, weights.unwrap_or_default());
+            match format.as_str() {
+                "json" => println!("{}", serde_json::to_string_pretty(&health)?),
+                "openmetrics" => print!("{}", health::render_openmetrics(&health)),
+                _ => print!("{}", health::render_report(&health)),
+            }
+            return Ok(ExitCode::SUCCESS);
+        }
+        Command::Mcp {
+            project:
+                PythonProjectArgs {
+                    path,
+                    source_root,
+                    exclude_scripts,
+                },
+        } => {
+            let (source_root, options) =
+                resolve_source_root(&path, source_root, python::AnalysisOptions::default())?;
+            let load = || {
+                python::analyze_project_with_options(
+                    &path,
+                    Some(&source_root),
+                    &exclude_scripts,
+                    &options,
+                )
+                .map_err(|e| e.to_string())
+            };
+            let parse = |input: &str| parse_module_input(input, &path, &source_root);
+            let mut server = mcp::Server::new(path.clone(), exclude_scripts.clone(), load, parse)?;
+            eprintln!("deptree-utils MCP server ready for {}", path.display());
+            server.serve(std::io::stdin().lock(), std::io::stdout().lock())?;
+            None
+        }
+        Command::Diff { old, new, format } => {
+            let changes = diff::diff(&diff::load_graph(&old)?, &diff::load_graph(&new)?);
+            Some(match OutputFormat::parse(&format) {
+                #[cfg(feature = "html")]
+                OutputFormat::Cytoscape => cytoscape::render_cytoscape_html(&changes)?,
+                OutputFormat::List => diff::render_list(&changes),
+                _ => diff::render_dot(&changes),
+            })
+        }
+        Command::Conformance {
+            architecture,
+            project:
+                PythonProjectArgs {
+                    path,
+                    source_root,
+                    exclude_scripts,
+                },
+            format,
+        } => {
+            let architecture = conformance::Architecture::load(&architecture)?;
+            let (source_root, options) =
+                resolve_source_root(&path, source_root, python::AnalysisOptions::default())?;
+            let graph = python::analyze_project_with_options(
+                &path,
+                Some(&source_root),
+                &exclude_scripts,
+                &options,
+            )?;
+            let report = conformance::conformance(&graph, &architecture);
+            Some(match OutputFormat::parse(&format) {
+                #[cfg(feature = "html")]
+                OutputFormat::Cytoscape => {
+                    cytoscape::render_cytoscape_html(&conformance::graph_data(&report))?
+                }
+                OutputFormat::Dot => conformance::render_dot(&report),
+                _ => conformance::render_report(&report),
+            })
+        }
+        Command::Validate { files } => {
+            let mut valid = true;
+            for path in &files {
+                match validate::load(path)? {
+                    Ok(data) => {
+                        let problems = validate::validate(&data);
+                        valid &= problems.is_empty();
+                        print!("{}", validate::render_report(path, &data, &problems));
+                    }
+                    Err(e) => {
+                        valid = false;
+                        println!("{}: {e}", path.display());
+                    }
+                }
+            }
+            return Ok(if valid {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::from(EXIT_CONTRACTS_BROKEN)
+            });
+        }
+        Command::Schema { payload } => {
+            let payload = Payload::from_name(&payload)
+                .ok_or_else(|| format!("Unknown payload: {payload}"))?;
+            Some(serde_json::to_string_pretty(&payload.json_schema())?)
+        }
+    };
+
+    if let Some(rendered) = rendered {
+        println!("{rendered}");
+    }
+
+    Ok(ExitCode::SUCCESS)
 }
@@ -0,0 +1,435 @@
+//! Model Context Protocol server (`mcp` subcommand)
+//!
+//! Serves the graph queries (upstream, downstream, path, search, metrics) as MCP tools
+//! over stdio, one JSON-RPC message per line, so coding assistants can ask what a change
+//! to a module reaches. The graph is built once at startup and rebuilt before a tool call
+//! whenever a source file under the project was added, removed, renamed, or modified
+//! since.
+
+use crate::python;
+use deptree_graph::filters::matches_pattern;
+use deptree_graph::{DependencyGraph, GraphId};
+use serde_json::{Value, json};
+use std::fmt::Write as _;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use thiserror::Error;
+use walkdir::WalkDir;
+
+/// Errors that end the server
+#[derive(Error, Debug)]
+pub enum McpError {
+    #[error("Failed to read or write an MCP message: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Protocol revision answered to `initialize`
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Most modules `search` lists unless asked for more
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+/// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// What changes when a source file is added, removed, renamed, or modified: a hash of
+/// every source's path, modification time, and length, so edits that leave the newest
+/// time or the file count alone (a rename, `git checkout` restoring older files) count too
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fingerprint(u64);
+
+impl Fingerprint {
+    /// Python and Cython sources below `root`, skipping the directories analysis skips
+    pub fn of_sources(root: &Path, exclude_patterns: &[String]) -> Self {
+        let mut sources: Vec<(PathBuf, Option<SystemTime>, u64)> = WalkDir::new(root)
+            .into_iter()
+            .filter_entry(|e| {
+                e.path().strip_prefix(root).is_ok_and(|relative| {
+                    python::exclusion_reason(relative, e.file_type().is_dir(), exclude_patterns)
+                        .is_none()
+                })
+            })
+            .filter_map(|e| e.ok())
+            .filter(|e| {
+                e.path()
+                    .extension()
+                    .is_some_and(|ext| ext == "py" || ext == "pyx" || ext == "pxd")
+            })
+            .map(|entry| {
+                let metadata = entry.metadata().ok();
+                (
+                    entry.into_path(),
+                    metadata.as_ref().and_then(|m| m.modified().ok()),
+                    metadata.map_or(0, |m| m.len()),
+                )
+            })
+            .collect();
+        sources.sort();
+        let mut hasher = DefaultHasher::new();
+        sources.hash(&mut hasher);
+        Fingerprint(hasher.finish())
+    }
+}
+
+/// Graph served to the client, with what it takes to rebuild it
+pub struct Server<T: GraphId, L, P> {
+    root: PathBuf,
+    exclude_patterns: Vec<String>,
+    load: L,
+    parse: P,
+    graph: DependencyGraph<T>,
+    fingerprint: Fingerprint,
+}
+
+impl<T, L, P> Server<T, L, P>
+where
+    T: GraphId,
+    L: FnMut() -> Result<DependencyGraph<T>, String>,
+    P: Fn(&str) -> Result<T, String>,
+{
+    /// Build the graph of the project at `root` with `load`; `parse` turns the module
+    /// arguments of tool calls into graph nodes
+    pub fn new(
+        root: PathBuf,
+        exclude_patterns: Vec<String>,
+        mut load: L,
+        parse: P,
+    ) -> Result<Self, String> {
+        let fingerprint = Fingerprint::of_sources(&root, &exclude_patterns);
+        let graph = load()?;
+        Ok(Server {
+            root,
+            exclude_patterns,
+            load,
+            parse,
+            graph,
+            fingerprint,
+        })
+    }
+
+    /// Answer messages from `input` until it closes
+    pub fn serve(&mut self, input: impl BufRead, mut output: impl Write) -> Result<(), McpError> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = self.handle_message(&line) {
+                writeln!(output, "{response}")?;
+                output.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Response to one JSON-RPC message (`None` for notifications)
+    pub fn handle_message(&mut self, message: &str) -> Option<Value> {
+        let request: Value = match serde_json::from_str(message) {
+            Ok(request) => request,
+            Err(e) => return Some(error_response(Value::Null, PARSE_ERROR, &e.to_string())),
+        };
+        // Requests carry an id; notifications (e.g. `notifications/initialized`) get no answer
+        let id = request.get("id")?.clone();
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+        let result = match request.get("method").and_then(Value::as_str) {
+            Some("initialize") => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": {} },
+                "serverInfo": {
+                    "name": "deptree-utils",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            })),
+            Some("ping") => Ok(json!({})),
+            Some("tools/list") => Ok(json!({ "tools": tool_definitions() })),
+            Some("tools/call") => self.call_tool(&params),
+            Some(method) => Err((METHOD_NOT_FOUND, format!("Unknown method '{method}'"))),
+            None => Err((METHOD_NOT_FOUND, "Missing method".to_string())),
+        };
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    /// Rebuild the graph if a source file changed since it was built. A failed rebuild
+    /// keeps the previous graph, and is retried on the next call.
+    fn refresh(&mut self) -> Option<String> {
+        let current = Fingerprint::of_sources(&self.root, &self.exclude_patterns);
+        if current == self.fingerprint {
+            return None;
+        }
+        match (self.load)() {
+            Ok(graph) => {
+                self.graph = graph;
+                self.fingerprint = current;
+                None
+            }
+            Err(e) => Some(format!(
+                "Warning: rebuilding the graph failed ({e}); these results are from the previous graph\n"
+            )),
+        }
+    }
+
+    fn call_tool(&mut self, params: &Value) -> Result<Value, (i64, String)> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or((INVALID_PARAMS, "Missing tool name".to_string()))?;
+        if !TOOLS.iter().any(|tool| tool.name == name) {
+            return Err((INVALID_PARAMS, format!("Unknown tool '{name}'")));
+        }
+        let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+        let warning = self.refresh();
+        // Bad arguments are reported to the model as a failed tool call
+        let (text, is_error) = match self.run_tool(name, &arguments) {
+            Ok(text) => (text, false),
+            Err(e) => (e, true),
+        };
+        Ok(json!({
+            "content": [{ "type": "text", "text": warning.unwrap_or_default() + &text }],
+            "isError": is_error,
+        }))
+    }
+
+    fn run_tool(&self, name: &str, arguments: &Value) -> Result<String, String> {
+        let string = |key: &str| -> Result<&str, String> {
+            arguments
+                .get(key)
+                .and_then(Value::as_str)
+                .ok_or_else(|| format!("Missing string argument '{key}'"))
+        };
+        let number = |key: &str| -> Result<Option<usize>, String> {
+            match arguments.get(key) {
+                None | Some(Value::Null) => Ok(None),
+                Some(value) => value
+                    .as_u64()
+                    .map(|n| Some(n as usize))
+                    .ok_or_else(|| format!("Argument '{key}' must be a non-negative integer")),
+            }
+        };
+        match name {
+            "upstream" | "downstream" => {
+                let module = self.module(string("module")?)?;
+                let max_depth = number("max_depth")?;
+                let reached = if name == "upstream" {
+                    self.graph
+                        .find_upstream(std::slice::from_ref(&module), max_depth)
+                } else {
+                    self.graph
+                        .find_downstream(std::slice::from_ref(&module), max_depth)
+                };
+                Ok(render_reached(&module, name, reached.into_iter().collect()))
+            }
+            "path" => {
+                let from = self.module(string("from")?)?;
+                let to = self.module(string("to")?)?;
+                Ok(match self.graph.shortest_chain(&from, |n| *n == to) {
+                    Some(chain) => chain
+                        .iter()
+                        .map(GraphId::to_dotted)
+                        .collect::<Vec<_>>()
+                        .join(" -> "),
+                    None => format!(
+                        "{} does not import {}, directly or indirectly",
+                        from.to_dotted(),
+                        to.to_dotted()
+                    ),
+                })
+            }
+            "search" => {
+                let query = string("query")?;
+                let limit = number("limit")?.unwrap_or(DEFAULT_SEARCH_LIMIT);
+                let mut matches: Vec<String> = self
+                    .graph
+                    .nodes()
+                    .map(GraphId::to_dotted)
+                    .filter(|name| matches_pattern(name, query))
+                    .collect();
+                matches.sort();
+                let total = matches.len();
+                let mut text = format!("{total} module(s) match '{query}'\n");
+                for name in matches.iter().take(limit) {
+                    let _ = writeln!(text, "  {name}");
+                }
+                if total > limit {
+                    let _ = writeln!(text, "  ... and {} more", total - limit);
+                }
+                Ok(text)
+            }
+            "metrics" => match arguments.get("module").and_then(Value::as_str) {
+                None => {
+                    let metrics = self.graph.metrics(None, true, true);
+                    Ok(format!(
+                        "modules: {}\nimports: {}\ncycles: {}\nmax depth: {}\n",
+                        metrics.nodes, metrics.edges, metrics.cycles, metrics.max_depth
+                    ))
+                }
+                Some(input) => {
+                    let module = self.module(input)?;
+                    let roots = std::slice::from_ref(&module);
+                    // The module itself is part of both sets
+                    let upstream = self.graph.find_upstream(roots, None).len() - 1;
+                    let downstream = self.graph.find_downstream(roots, None).len() - 1;
+                    let cycle = self
+                        .graph
+                        .find_cycles()
+                        .into_iter()
+                        .find(|members| members.contains(&module))
+                        .map_or_else(
+                            || "none".to_string(),
+                            |members| {
+                                let names: Vec<String> =
+                                    members.iter().map(GraphId::to_dotted).collect();
+                                names.join(", ")
+                            },
+                        );
+                    Ok(format!(
+                        "module: {}\nimports directly: {}\nimported directly by: {}\nupstream (transitive): {upstream}\ndownstream (transitive): {downstream}\ncycle: {cycle}\n",
+                        module.to_dotted(),
+                        self.graph.direct_dependencies(&module).len(),
+                        self.graph.direct_dependents(&module).len(),
+                    ))
+                }
+            },
+            _ => unreachable!("Tool names are checked by call_tool"),
+        }
+    }
+
+    /// Node named by a tool argument, which must be in the graph
+    fn module(&self, input: &str) -> Result<T, String> {
+        let module = (self.parse)(input)?;
+        if self.graph.contains(&module) {
+            Ok(module)
+        } else {
+            Err(format!(
+                "Module '{input}' is not in the graph (the search tool finds module names)"
+            ))
+        }
+    }
+}
+
+/// Modules reached from `module`, nearest first, without the module itself
+fn render_reached<T: GraphId>(module: &T, direction: &str, mut reached: Vec<(T, usize)>) -> String {
+    reached.retain(|(node, _)| node != module);
+    reached.sort_by_cached_key(|(node, distance)| (*distance, node.to_dotted()));
+    let relation = if direction == "upstream" {
+        "imports"
+    } else {
+        "is imported by"
+    };
+    let mut text = format!(
+        "{} {relation} {} module(s) ({direction}, distance in imports):\n",
+        module.to_dotted(),
+        reached.len()
+    );
+    for (node, distance) in &reached {
+        let _ = writeln!(text, "  {distance} {}", node.to_dotted());
+    }
+    text
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+struct Tool {
+    name: &'static str,
+    description: &'static str,
+    /// `(name, JSON type, description, required)`
+    arguments: &'static [(&'static str, &'static str, &'static str, bool)],
+}
+
+const MODULE_ARGUMENT: &str = "Dotted module name (e.g. pkg.sub.module) or file path";
+
+const TOOLS: [Tool; 5] = [
+    Tool {
+        name: "upstream",
+        description: "Modules that a module imports, directly or indirectly: what it depends on",
+        arguments: &[
+            ("module", "string", MODULE_ARGUMENT, true),
+            (
+                "max_depth",
+                "integer",
+                "Stop after this many imports",
+                false,
+            ),
+        ],
+    },
+    Tool {
+        name: "downstream",
+        description: "Modules that import a module, directly or indirectly: what may break if it changes",
+        arguments: &[
+            ("module", "string", MODULE_ARGUMENT, true),
+            (
+                "max_depth",
+                "integer",
+                "Stop after this many imports",
+                false,
+            ),
+        ],
+    },
+    Tool {
+        name: "path",
+        description: "Shortest import chain through which one module depends on another",
+        arguments: &[
+            ("from", "string", "Importing module", true),
+            ("to", "string", "Imported module", true),
+        ],
+    },
+    Tool {
+        name: "search",
+        description: "Find module names; * matches any text at the start or end (e.g. *.models, api.*)",
+        arguments: &[
+            ("query", "string", "Text or wildcard pattern", true),
+            ("limit", "integer", "Most names to list (default 50)", false),
+        ],
+    },
+    Tool {
+        name: "metrics",
+        description: "Size, cycle, and depth metrics of the whole graph, or the fan-in, fan-out, and reach of one module",
+        arguments: &[(
+            "module",
+            "string",
+            "Module to describe (omit for the whole graph)",
+            false,
+        )],
+    },
+];
+
+fn tool_definitions() -> Vec<Value> {
+    TOOLS
+        .iter()
+        .map(|tool| {
+            let properties: serde_json::Map<String, Value> = tool
+                .arguments
+                .iter()
+                .map(|(name, kind, description, _)| {
+                    (
+                        name.to_string(),
+                        json!({ "type": kind, "description": description }),
+                    )
+                })
+                .collect();
+            let required: Vec<&str> = tool
+                .arguments
+                .iter()
+                .filter(|(.., required)| *required)
+                .map(|(name, ..)| *name)
+                .collect();
+            json!({
+                "name": tool.name,
+                "description": tool.description,
+                "inputSchema": {
+                    "type": "object",
+                    "properties": properties,
+                    "required": required,
+                },
+            })
+        })
+        .collect()
+}
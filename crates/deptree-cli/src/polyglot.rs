@@ -0,0 +1,323 @@
+//! Cross-language dependency graph
+//!
+//! Runs every analyzer configured in `deptree.toml`, merges their graphs, and adds
+//! edges for the declared `[[bridges]]` so impact analysis can cross language borders
+//! (e.g. from a proto change to the Python code generated from it).
+
+use crate::config::{Config, ConfigError};
+use crate::go::{self, GoAnalysisError, PackagePath};
+use crate::proto::{self, ProtoAnalysisError, ProtoPath};
+use crate::python::{self, ModulePath, PythonAnalysisError};
+use deptree_graph::{DependencyGraph, GraphId, Lifecycle};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Instant;
+use thiserror::Error;
+
+/// Dependency graph spanning several languages.
+pub type PolyglotGraph = DependencyGraph<PolyglotNode>;
+
+/// Errors that can occur while building the cross-language graph
+#[derive(Error, Debug)]
+pub enum PolyglotError {
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    #[error(transparent)]
+    Python(#[from] PythonAnalysisError),
+
+    #[error(transparent)]
+    Go(#[from] GoAnalysisError),
+
+    #[error(transparent)]
+    Proto(#[from] ProtoAnalysisError),
+
+    #[error("Invalid node '{0}': expected '<language>:<name>' with language python, go, or proto")]
+    InvalidNode(String),
+
+    #[error("Bridge endpoint '{0}' does not match any analyzed node")]
+    UnknownBridgeEndpoint(String),
+
+    #[error("No analyzer sections ([python], [go], [proto]) configured")]
+    NoAnalyzers,
+
+    #[error("Invalid module name '{0}' in [python.module_map]")]
+    InvalidModuleName(String),
+
+    #[error(
+        "Invalid entry '{0}' = '{1}' in [python.lifecycle]: expected a module name and experimental, stable, or sunset"
+    )]
+    InvalidLifecycle(String, String),
+}
+
+/// Languages that can take part in a cross-language graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    Python,
+    Go,
+    Proto,
+}
+
+impl Language {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Language::Python => "python",
+            Language::Go => "go",
+            Language::Proto => "proto",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "python" => Some(Language::Python),
+            "go" => Some(Language::Go),
+            "proto" => Some(Language::Proto),
+            _ => None,
+        }
+    }
+
+    /// Separator used inside this language's identifiers
+    fn separator(self) -> &'static str {
+        match self {
+            Language::Python => ".",
+            Language::Go | Language::Proto => "/",
+        }
+    }
+}
+
+/// A node of the cross-language graph, tagged with its language
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PolyglotNode {
+    Python(ModulePath),
+    Go(PackagePath),
+    Proto(ProtoPath),
+}
+
+impl PolyglotNode {
+    pub fn language(&self) -> Language {
+        match self {
+            PolyglotNode::Python(_) => Language::Python,
+            PolyglotNode::Go(_) => Language::Go,
+            PolyglotNode::Proto(_) => Language::Proto,
+        }
+    }
+
+    fn inner_segments(&self) -> Vec<String> {
+        match self {
+            PolyglotNode::Python(module) => module.segments(),
+            PolyglotNode::Go(package) => package.segments(),
+            PolyglotNode::Proto(schema) => schema.segments(),
+        }
+    }
+
+    /// Parse a `<language>:<name>` node reference (e.g. `python:pkg.module`)
+    pub fn parse(input: &str) -> Result<Self, PolyglotError> {
+        let invalid = || PolyglotError::InvalidNode(input.to_string());
+        let (language, name) = input.trim().split_once(':').ok_or_else(invalid)?;
+        match Language::parse(language).ok_or_else(invalid)? {
+            Language::Python => ModulePath::from_dotted(name).map(PolyglotNode::Python),
+            Language::Go => PackagePath::from_import_path(name).map(PolyglotNode::Go),
+            Language::Proto => ProtoPath::from_import(name).map(PolyglotNode::Proto),
+        }
+        .ok_or_else(invalid)
+    }
+}
+
+/// Parses the name as it appears in graph data (`<language>:<name>`)
+impl std::str::FromStr for PolyglotNode {
+    type Err = PolyglotError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PolyglotNode::parse(s)
+    }
+}
+
+impl GraphId for PolyglotNode {
+    fn to_dotted(&self) -> String {
+        Self::join_segments(&self.segments())
+    }
+
+    /// The language comes first so every language forms its own namespace group
+    fn segments(&self) -> Vec<String> {
+        std::iter::once(self.language().as_str().to_string())
+            .chain(self.inner_segments())
+            .collect()
+    }
+
+    fn join_segments(segments: &[String]) -> String {
+        match segments.split_first() {
+            Some((language, [])) => language.clone(),
+            Some((language, rest)) => {
+                let separator = Language::parse(language)
+                    .map(Language::separator)
+                    .unwrap_or(".");
+                format!("{language}:{}", rest.join(separator))
+            }
+            None => String::new(),
+        }
+    }
+
+    fn kind_segments() -> usize {
+        1
+    }
+
+    fn represents_group(&self) -> bool {
+        match self {
+            PolyglotNode::Python(module) => module.represents_group(),
+            PolyglotNode::Go(package) => package.represents_group(),
+            PolyglotNode::Proto(schema) => schema.represents_group(),
+        }
+    }
+}
+
+/// Resolve one side of a bridge to the graph nodes it stands for
+fn resolve_bridge_endpoint(
+    endpoint: &str,
+    config: &Config,
+    base_dir: &Path,
+    graph: &PolyglotGraph,
+) -> Result<Vec<PolyglotNode>, PolyglotError> {
+    let node = PolyglotNode::parse(endpoint)?;
+
+    let nodes = match (&node, config.go.as_ref(), config.proto.as_ref()) {
+        // Go endpoints may be written relative to the module root
+        (PolyglotNode::Go(_), Some(go_config), _) => {
+            let name = endpoint.trim().trim_start_matches("go:");
+            let module_path = go::read_module_path(&base_dir.join(&go_config.path))?;
+            go::resolve_package_input(name, &module_path)
+                .map(PolyglotNode::Go)
+                .into_iter()
+                .collect()
+        }
+        // Proto endpoints not naming a .proto file refer to a proto package
+        (PolyglotNode::Proto(schema), _, Some(proto_config))
+            if !schema.to_import_path().ends_with(".proto") =>
+        {
+            let package = endpoint.trim().trim_start_matches("proto:");
+            let mut schemas = proto::collect_packages(
+                &base_dir.join(&proto_config.path),
+                &proto_config.proto_paths,
+            )?
+            .remove(package)
+            .unwrap_or_default();
+            schemas.sort_by_key(ProtoPath::to_import_path);
+            schemas.into_iter().map(PolyglotNode::Proto).collect()
+        }
+        _ => vec![node],
+    };
+
+    let nodes: Vec<PolyglotNode> = nodes
+        .into_iter()
+        .filter(|node| graph.contains(node))
+        .collect();
+    if nodes.is_empty() {
+        Err(PolyglotError::UnknownBridgeEndpoint(endpoint.to_string()))
+    } else {
+        Ok(nodes)
+    }
+}
+
+/// Run every configured analyzer and merge the results, adding bridge edges.
+///
+/// Paths in the configuration are resolved relative to `base_dir` (the directory
+/// holding the config file).
+pub fn analyze_polyglot(config: &Config, base_dir: &Path) -> Result<PolyglotGraph, PolyglotError> {
+    analyze_polyglot_with_deadline(config, base_dir, None)
+}
+
+/// [`analyze_polyglot`] that stops parsing Python files once `deadline` has passed (see
+/// [`python::AnalysisOptions::deadline`])
+pub fn analyze_polyglot_with_deadline(
+    config: &Config,
+    base_dir: &Path,
+    deadline: Option<Instant>,
+) -> Result<PolyglotGraph, PolyglotError> {
+    if config.python.is_none() && config.go.is_none() && config.proto.is_none() {
+        return Err(PolyglotError::NoAnalyzers);
+    }
+
+    let mut graph = PolyglotGraph::new();
+
+    if let Some(python_config) = &config.python {
+        let source_root = python_config
+            .source_root
+            .as_ref()
+            .map(|root| base_dir.join(root));
+        let options = python::AnalysisOptions {
+            python_version: python_config
+                .python_version
+                .as_deref()
+                .map(python::parse_python_version)
+                .transpose()?,
+            module_map: python_config
+                .module_map
+                .iter()
+                .map(|(path, module)| {
+                    python::ModulePath::from_dotted(module)
+                        .map(|module| (path.clone(), module))
+                        .ok_or_else(|| PolyglotError::InvalidModuleName(module.clone()))
+                })
+                .collect::<Result<_, _>>()?,
+            skip_path_dependencies: false,
+            skip_scripts: python_config.no_scripts,
+            platform: None,
+            strict_resolution: false,
+            split_package_init: false,
+            lifecycles: python_config
+                .lifecycle
+                .iter()
+                .map(|(module, state)| {
+                    python::ModulePath::from_dotted(module)
+                        .zip(Lifecycle::parse(state))
+                        .ok_or_else(|| {
+                            PolyglotError::InvalidLifecycle(module.clone(), state.clone())
+                        })
+                })
+                .collect::<Result<_, _>>()?,
+            deadline,
+        };
+        let mut python_graph = python::analyze_project_with_options(
+            &base_dir.join(&python_config.path),
+            source_root.as_deref(),
+            &python_config.exclude_scripts,
+            &options,
+        )?;
+        let categories: Vec<(String, String)> = python_config
+            .script_categories
+            .iter()
+            .map(|(directory, category)| (directory.clone(), category.clone()))
+            .collect();
+        python::group_scripts(
+            &mut python_graph,
+            python_config.cluster_scripts,
+            &categories,
+        );
+        graph.extend(&python_graph.map_ids(|m| PolyglotNode::Python(m.clone())));
+    }
+
+    if let Some(go_config) = &config.go {
+        let go_graph =
+            go::analyze_module(&base_dir.join(&go_config.path), go_config.include_tests)?;
+        graph.extend(&go_graph.map_ids(|p| PolyglotNode::Go(p.clone())));
+    }
+
+    if let Some(proto_config) = &config.proto {
+        let proto_graph = proto::analyze_protos(
+            &base_dir.join(&proto_config.path),
+            &proto_config.proto_paths,
+        )?;
+        graph.extend(&proto_graph.map_ids(|s| PolyglotNode::Proto(s.clone())));
+    }
+
+    for bridge in &config.bridges {
+        let from_nodes = resolve_bridge_endpoint(&bridge.from, config, base_dir, &graph)?;
+        let to_nodes = resolve_bridge_endpoint(&bridge.to, config, base_dir, &graph)?;
+        for from in &from_nodes {
+            for to in &to_nodes {
+                graph.add_dependency(from.clone(), to.clone());
+            }
+        }
+    }
+
+    Ok(graph)
+}
@@ -0,0 +1,268 @@
+//! Protobuf schema dependency analyzer
+//!
+//! Parses `.proto` files to extract `import` statements and builds a dependency graph
+//! between the schema files of a project.
+
+use crate::comments::strip_c_style_comments;
+use deptree_graph::{DependencyGraph, GraphId};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use walkdir::WalkDir;
+
+/// Concrete dependency graph for Protobuf schema files.
+pub type ProtoGraph = DependencyGraph<ProtoPath>;
+
+/// Errors that can occur during Protobuf dependency analysis
+#[derive(Error, Debug)]
+pub enum ProtoAnalysisError {
+    #[error("Invalid project root: {0}")]
+    InvalidRoot(PathBuf),
+
+    #[error("Invalid import path {0}: {1}")]
+    InvalidImportPath(PathBuf, std::io::Error),
+}
+
+/// Represents a schema file by its import path (e.g., "shop/v1/order.proto")
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ProtoPath(pub Vec<String>);
+
+impl ProtoPath {
+    /// Create a schema path from the string used in an `import` statement
+    pub fn from_import(input: &str) -> Option<Self> {
+        let parts: Vec<String> = input
+            .trim()
+            .trim_start_matches("./")
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect();
+
+        if parts.is_empty() {
+            None
+        } else {
+            Some(ProtoPath(parts))
+        }
+    }
+
+    /// Create a schema path from a file path under one of the import roots.
+    /// The first root containing the file wins, mirroring `protoc -I` semantics.
+    pub fn from_file_path(path: &Path, import_roots: &[PathBuf]) -> Option<Self> {
+        let relative = import_roots
+            .iter()
+            .find_map(|root| path.strip_prefix(root).ok())?;
+        let parts: Vec<String> = relative
+            .components()
+            .filter_map(|c| c.as_os_str().to_str().map(String::from))
+            .collect();
+
+        if parts.is_empty() {
+            None
+        } else {
+            Some(ProtoPath(parts))
+        }
+    }
+
+    /// Convert to the slash-separated import path
+    pub fn to_import_path(&self) -> String {
+        self.0.join("/")
+    }
+}
+
+/// Parses the name as it appears in graph data (see `DependencyGraph::from_graph_data`)
+impl std::str::FromStr for ProtoPath {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ProtoPath::from_import(s).ok_or_else(|| format!("invalid schema path '{s}'"))
+    }
+}
+
+impl GraphId for ProtoPath {
+    fn to_dotted(&self) -> String {
+        self.to_import_path()
+    }
+
+    fn segments(&self) -> Vec<String> {
+        self.0.clone()
+    }
+
+    fn join_segments(segments: &[String]) -> String {
+        segments.join("/")
+    }
+}
+
+/// Extract the imported file paths from a `.proto` source, including `public` and `weak` imports
+fn extract_imports(source: &str) -> Vec<String> {
+    strip_c_style_comments(source)
+        .split(';')
+        .filter_map(|statement| {
+            let rest = statement.trim().strip_prefix("import")?;
+            if !rest.starts_with(char::is_whitespace) && !rest.starts_with(['"', '\'']) {
+                return None;
+            }
+            let rest = rest.trim_start();
+            let rest = rest
+                .strip_prefix("public")
+                .or_else(|| rest.strip_prefix("weak"))
+                .unwrap_or(rest)
+                .trim();
+            let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+            let literal = &rest[1..];
+            literal.find(quote).map(|end| literal[..end].to_string())
+        })
+        .collect()
+}
+
+fn should_skip_dir(path: &Path, project_root: &Path) -> bool {
+    path != project_root
+        && path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(|name| name.starts_with('.') || name == "node_modules")
+            .unwrap_or(false)
+}
+
+/// Extract the `package` declared by a `.proto` source, if any
+fn extract_package(source: &str) -> Option<String> {
+    strip_c_style_comments(source)
+        .split(';')
+        .find_map(|statement| {
+            let rest = statement.trim().strip_prefix("package")?;
+            rest.starts_with(char::is_whitespace)
+                .then(|| rest.trim().to_string())
+        })
+        .filter(|package| !package.is_empty())
+}
+
+/// Find every `.proto` file under `project_root` that lies inside one of the import roots
+fn discover_schemas(project_root: &Path, import_roots: &[PathBuf]) -> Vec<(ProtoPath, PathBuf)> {
+    WalkDir::new(project_root)
+        .into_iter()
+        .filter_entry(|e| !e.file_type().is_dir() || !should_skip_dir(e.path(), project_root))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| {
+            e.path()
+                .extension()
+                .map(|ext| ext == "proto")
+                .unwrap_or(false)
+        })
+        .filter_map(|e| {
+            let path = e.path().canonicalize().ok()?;
+            ProtoPath::from_file_path(&path, import_roots).map(|schema| (schema, path))
+        })
+        .collect()
+}
+
+fn read_schema(file_path: &Path) -> Option<String> {
+    match std::fs::read_to_string(file_path) {
+        Ok(source) => Some(source),
+        Err(e) => {
+            eprintln!("Warning: Skipping file {}: {}", file_path.display(), e);
+            None
+        }
+    }
+}
+
+/// Analyze the `.proto` files under `project_root` and return their import graph.
+///
+/// `import_paths` are the roots imports are resolved against (like `protoc -I`);
+/// when empty, the project root itself is used.
+pub fn analyze_protos(
+    project_root: &Path,
+    import_paths: &[PathBuf],
+) -> Result<ProtoGraph, ProtoAnalysisError> {
+    if !project_root.is_dir() {
+        return Err(ProtoAnalysisError::InvalidRoot(project_root.to_path_buf()));
+    }
+
+    let import_roots = resolve_import_roots(project_root, import_paths)?;
+    let sources = discover_schemas(project_root, &import_roots);
+    let known: HashSet<&ProtoPath> = sources.iter().map(|(schema, _)| schema).collect();
+
+    let mut graph = ProtoGraph::new();
+    for (schema, file_path) in &sources {
+        graph.ensure_node(schema.clone());
+
+        let Some(source) = read_schema(file_path) else {
+            continue;
+        };
+
+        for import in extract_imports(&source) {
+            // Imports that don't resolve to a project file (e.g. google/protobuf/*) are external
+            if let Some(target) = ProtoPath::from_import(&import)
+                && known.contains(&target)
+            {
+                graph.add_dependency(schema.clone(), target);
+            }
+        }
+    }
+
+    Ok(graph)
+}
+
+/// Map each declared `package` to the schema files that declare it
+pub fn collect_packages(
+    project_root: &Path,
+    import_paths: &[PathBuf],
+) -> Result<HashMap<String, Vec<ProtoPath>>, ProtoAnalysisError> {
+    if !project_root.is_dir() {
+        return Err(ProtoAnalysisError::InvalidRoot(project_root.to_path_buf()));
+    }
+
+    let import_roots = resolve_import_roots(project_root, import_paths)?;
+    let mut packages: HashMap<String, Vec<ProtoPath>> = HashMap::new();
+    for (schema, file_path) in discover_schemas(project_root, &import_roots) {
+        if let Some(package) = read_schema(&file_path).and_then(|source| extract_package(&source)) {
+            packages.entry(package).or_default().push(schema);
+        }
+    }
+
+    Ok(packages)
+}
+
+/// Canonicalize the import roots, defaulting to the project root
+pub fn resolve_import_roots(
+    project_root: &Path,
+    import_paths: &[PathBuf],
+) -> Result<Vec<PathBuf>, ProtoAnalysisError> {
+    let roots: Vec<PathBuf> = if import_paths.is_empty() {
+        vec![project_root.to_path_buf()]
+    } else {
+        import_paths
+            .iter()
+            .map(|p| {
+                if p.is_absolute() {
+                    p.clone()
+                } else {
+                    project_root.join(p)
+                }
+            })
+            .collect()
+    };
+
+    roots
+        .into_iter()
+        .map(|root| {
+            root.canonicalize()
+                .map_err(|e| ProtoAnalysisError::InvalidImportPath(root, e))
+        })
+        .collect()
+}
+
+/// Resolve a schema given on the command line: either an import path
+/// ("shop/v1/order.proto") or a path to an existing file under one of the import roots.
+pub fn resolve_proto_input(input: &str, import_roots: &[PathBuf]) -> Result<ProtoPath, String> {
+    let as_file = Path::new(input);
+    if as_file.is_file() {
+        let canonical = as_file
+            .canonicalize()
+            .map_err(|e| format!("Failed to canonicalize path {input}: {e}"))?;
+        return ProtoPath::from_file_path(&canonical, import_roots)
+            .ok_or_else(|| format!("File {} is outside the import paths", canonical.display()));
+    }
+
+    ProtoPath::from_import(input).ok_or_else(|| format!("Invalid schema path: {input}"))
+}
@@ -0,0 +1,152 @@
+//! Uploading rendered graphs (`--publish`)
+//!
+//! `http(s)://` destinations receive a `POST` of the artifact; `s3://bucket/key`
+//! destinations a signed `PUT` to AWS or any S3-compatible store. Requests are made with
+//! the `curl` binary, and credentials come from environment variables so they never show
+//! up on the command line:
+//!
+//! - HTTP: `DEPTREE_PUBLISH_TOKEN` is sent as a bearer token
+//! - S3: `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`, optional `AWS_SESSION_TOKEN`,
+//!   `AWS_REGION` (or `AWS_DEFAULT_REGION`, default `us-east-1`), and `AWS_ENDPOINT_URL`
+//!   for S3-compatible stores (path-style `<endpoint>/<bucket>/<key>`)
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+use thiserror::Error;
+
+/// Errors that can occur while publishing
+#[derive(Error, Debug)]
+pub enum PublishError {
+    #[error(
+        "Unsupported publish destination '{0}' (expected http://, https://, or s3://bucket/key)"
+    )]
+    InvalidUrl(String),
+
+    #[error("Publishing to S3 requires the {0} environment variable")]
+    MissingCredentials(&'static str),
+
+    #[error("Failed to stage the upload: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to run curl: {0}")]
+    CurlUnavailable(std::io::Error),
+
+    #[error("Upload to {0} failed: {1}")]
+    Upload(String, String),
+}
+
+/// A resolved upload request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Upload {
+    method: &'static str,
+    url: String,
+    headers: Vec<String>,
+    /// `user:password` for curl, kept out of argv
+    user: Option<String>,
+    /// `--aws-sigv4` provider string
+    aws_sigv4: Option<String>,
+}
+
+impl Upload {
+    /// Resolve a destination, reading credentials through `env`
+    pub fn for_url(url: &str, env: impl Fn(&str) -> Option<String>) -> Result<Self, PublishError> {
+        if url.starts_with("http://") || url.starts_with("https://") {
+            return Ok(Upload {
+                method: "POST",
+                url: url.to_string(),
+                headers: env("DEPTREE_PUBLISH_TOKEN")
+                    .map(|token| format!("Authorization: Bearer {token}"))
+                    .into_iter()
+                    .collect(),
+                user: None,
+                aws_sigv4: None,
+            });
+        }
+
+        let (bucket, key) = url
+            .strip_prefix("s3://")
+            .and_then(|rest| rest.split_once('/'))
+            .filter(|(bucket, key)| !bucket.is_empty() && !key.is_empty())
+            .ok_or_else(|| PublishError::InvalidUrl(url.to_string()))?;
+        let access_key = env("AWS_ACCESS_KEY_ID")
+            .ok_or(PublishError::MissingCredentials("AWS_ACCESS_KEY_ID"))?;
+        let secret_key = env("AWS_SECRET_ACCESS_KEY")
+            .ok_or(PublishError::MissingCredentials("AWS_SECRET_ACCESS_KEY"))?;
+        let region = env("AWS_REGION")
+            .or_else(|| env("AWS_DEFAULT_REGION"))
+            .unwrap_or_else(|| "us-east-1".to_string());
+        let url = match env("AWS_ENDPOINT_URL") {
+            Some(endpoint) => format!("{}/{bucket}/{key}", endpoint.trim_end_matches('/')),
+            None => format!("https://{bucket}.s3.{region}.amazonaws.com/{key}"),
+        };
+
+        Ok(Upload {
+            method: "PUT",
+            url,
+            headers: env("AWS_SESSION_TOKEN")
+                .map(|token| format!("x-amz-security-token: {token}"))
+                .into_iter()
+                .collect(),
+            user: Some(format!("{access_key}:{secret_key}")),
+            aws_sigv4: Some(format!("aws:amz:{region}:s3")),
+        })
+    }
+
+    /// Send `body` with the given content type
+    pub fn send(&self, body: &[u8], content_type: &str) -> Result<(), PublishError> {
+        let mut staged = tempfile::NamedTempFile::new()?;
+        staged.write_all(body)?;
+        staged.flush()?;
+
+        let mut child = Command::new("curl")
+            .args(["--silent", "--show-error", "--fail", "--config", "-"])
+            .args(["--request", self.method])
+            .args(["--header", &format!("Content-Type: {content_type}")])
+            .arg("--data-binary")
+            .arg(format!("@{}", staged.path().display()))
+            .arg(&self.url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(PublishError::CurlUnavailable)?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(self.curl_config().as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(PublishError::Upload(
+                self.url.clone(),
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ))
+        }
+    }
+
+    /// Secret-bearing options, passed to curl on stdin
+    pub fn curl_config(&self) -> String {
+        let quote =
+            |value: &str| format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut config = String::new();
+        for header in &self.headers {
+            config.push_str(&format!("header = {}\n", quote(header)));
+        }
+        if let Some(user) = &self.user {
+            config.push_str(&format!("user = {}\n", quote(user)));
+        }
+        if let Some(provider) = &self.aws_sigv4 {
+            config.push_str(&format!("aws-sigv4 = {}\n", quote(provider)));
+        }
+        config
+    }
+}
+
+/// Upload `body` to `url` using credentials from the environment
+pub fn publish(url: &str, body: &[u8], content_type: &str) -> Result<(), PublishError> {
+    let upload = Upload::for_url(url, |name| {
+        std::env::var(name).ok().filter(|value| !value.is_empty())
+    })?;
+    upload.send(body, content_type)
+}
@@ -3,12 +3,21 @@
 //! Parses Python files to extract import statements and builds a dependency graph
 //! of internal module dependencies.
 
-use deptree_graph::{DependencyGraph, GraphId, filters};
-use ruff_python_parser::parse_module;
-use std::collections::HashMap;
+use crate::emit::{
+    AnalysisTrace, DynamicImport, DynamicName, FileTrace, Outcome, Resolution, TracedImport,
+};
+use crate::generated::{self, GitAttributes};
+use deptree_graph::{DependencyGraph, EdgeKind, GraphId, ImportSite, Lifecycle, filters};
+pub use ruff_python_ast::PythonVersion;
+use ruff_python_parser::{Mode, ParseOptions, parse};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
+use std::time::Instant;
 use thiserror::Error;
-use walkdir::WalkDir;
+use walkdir::{DirEntry, WalkDir};
 
 /// Concrete dependency graph for Python modules.
 pub type PythonGraph = DependencyGraph<ModulePath>;
@@ -27,10 +36,74 @@ pub enum PythonAnalysisError {
 
     #[error("No Python source root found in {0}")]
     NoSourceRootFound(PathBuf),
+
+    #[error("Several plausible Python source roots in {}: {}; pass --source-root with one of them, or --source-root {ALL_SOURCE_ROOTS} to analyze all of them", .0.display(), .1.join(", "))]
+    AmbiguousSourceRoot(PathBuf, Vec<String>),
+
+    #[error("Invalid Python version '{0}': expected 3.<minor> between 3.7 and {1}")]
+    InvalidPythonVersion(String, PythonVersion),
+
+    #[error("Invalid platform '{0}': expected linux, darwin, or win32")]
+    InvalidPlatform(String),
+
+    #[error("{} relative import(s) could not be resolved confidently:\n  {}", .0.len(), .0.join("\n  "))]
+    UnresolvedRelativeImports(Vec<String>),
+}
+
+/// Settings that change how source files are parsed and named
+#[derive(Debug, Clone, Default)]
+pub struct AnalysisOptions {
+    /// Python version the sources target (`--python-version`). Syntax newer than this is
+    /// reported as a warning; `None` accepts everything the parser knows.
+    pub python_version: Option<PythonVersion>,
+    /// Files or directories (relative to the project root) importable under another name
+    /// (`--module-map`), e.g. `gen/protos` as `company.protos`. The most specific entry
+    /// wins; mapped paths outside the source root are modules, not scripts.
+    pub module_map: Vec<(PathBuf, ModulePath)>,
+    /// Leave out the local libraries declared as path dependencies in pyproject.toml
+    /// (`--no-path-dependencies`). By default their modules are part of the graph,
+    /// with the dependency's name as their category.
+    pub skip_path_dependencies: bool,
+    /// Skip script discovery (`--no-scripts`): Python files outside the source root are
+    /// not looked for, so the project root is never walked.
+    pub skip_scripts: bool,
+    /// Only keep imports that can run on this platform (`--platform`). Without it, imports
+    /// guarded by platform checks are kept and their edges tagged `platform:<name>`.
+    pub platform: Option<Platform>,
+    /// Fail on relative imports that cannot be resolved confidently (`--strict-resolution`):
+    /// ones reaching above the top-level package, naming a module the project does not
+    /// have, or made from a script outside the source root.
+    pub strict_resolution: bool,
+    /// Lifecycle state of the modules below each module name (`--lifecycle`); the most
+    /// specific entry wins, and a `# deptree: lifecycle=<state>` header comment in the
+    /// file (or its package's `__init__.py`) overrides them.
+    pub lifecycles: Vec<(ModulePath, Lifecycle)>,
+    /// Stop parsing files once this instant has passed (`--timeout`). The graph then only
+    /// has the imports of the files parsed so far, and the remaining files are listed in
+    /// a warning.
+    pub deadline: Option<Instant>,
+    /// Give every package's `__init__.py` a node of its own, `pkg.__init__`, holding the
+    /// imports made there (`--split-package-init`). `pkg` then stands for importing the
+    /// package and depends on `pkg.__init__`, which running the import executes; by
+    /// default both are the single node `pkg`.
+    pub split_package_init: bool,
+}
+
+/// Parse a `--python-version` value such as `3.8` or `3.12`
+pub fn parse_python_version(value: &str) -> Result<PythonVersion, PythonAnalysisError> {
+    let latest = PythonVersion::latest();
+    let invalid = || PythonAnalysisError::InvalidPythonVersion(value.to_string(), latest);
+    let (major, minor) = value.trim().split_once('.').ok_or_else(invalid)?;
+    let major: u8 = major.parse().map_err(|_| invalid())?;
+    let minor: u8 = minor.parse().map_err(|_| invalid())?;
+    if major != 3 || minor < 7 || minor > latest.minor {
+        return Err(invalid());
+    }
+    Ok(PythonVersion { major, minor })
 }
 
 /// Represents a Python module within the project
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ModulePath(pub Vec<String>);
 
 impl ModulePath {
@@ -50,10 +123,12 @@ impl ModulePath {
             .filter_map(|c| c.as_os_str().to_str().map(String::from))
             .collect();
 
-        if let Some(last) = parts.last_mut() {
-            if last.ends_with(".py") {
-                *last = last.strip_suffix(".py")?.to_string();
-            }
+        if let Some(last) = parts.last_mut()
+            && let Some(stem) = [".py", ".pyx", ".pxd"]
+                .iter()
+                .find_map(|suffix| last.strip_suffix(suffix))
+        {
+            *last = stem.to_string();
         }
 
         if parts.last().map(|s| s.as_str()) == Some("__init__") {
@@ -76,10 +151,10 @@ impl ModulePath {
             .filter_map(|c| c.as_os_str().to_str().map(String::from))
             .collect();
 
-        if let Some(last) = parts.last_mut() {
-            if last.ends_with(".py") {
-                *last = last.strip_suffix(".py")?.to_string();
-            }
+        if let Some(last) = parts.last_mut()
+            && last.ends_with(".py")
+        {
+            *last = last.strip_suffix(".py")?.to_string();
         }
 
         if parts.is_empty() {
@@ -94,21 +169,44 @@ impl ModulePath {
         self.0.join(".")
     }
 
+    /// Node of this package's `__init__.py` with `--split-package-init`
+    pub fn package_init(&self) -> ModulePath {
+        let mut parts = self.0.clone();
+        parts.push("__init__".to_string());
+        ModulePath(parts)
+    }
+
+    /// Number of segments of the package a relative import starts from
+    fn package_depth(&self, is_package: bool) -> usize {
+        if is_package {
+            self.0.len()
+        } else {
+            self.0.len().saturating_sub(1)
+        }
+    }
+
     /// Resolve a relative import from this module's location
     pub fn resolve_relative(&self, level: u32, module: Option<&str>) -> Option<ModulePath> {
+        self.resolve_relative_from(false, level, module)
+    }
+
+    /// Resolve a relative import made by this module. `is_package` says whether the
+    /// importing file is a package's `__init__.py`: there `.` is the package itself, while
+    /// in a plain module it is the module's parent package.
+    pub fn resolve_relative_from(
+        &self,
+        is_package: bool,
+        level: u32,
+        module: Option<&str>,
+    ) -> Option<ModulePath> {
         if level == 0 {
             return module.map(|m| ModulePath(m.split('.').map(String::from).collect()));
         }
 
-        let up_count = level as usize;
-        if up_count > self.0.len() {
-            return None;
-        }
-
-        let mut base: Vec<String> = self.0[..self.0.len() - up_count + 1].to_vec();
-        if level >= 1 && !base.is_empty() {
-            base.pop();
-        }
+        let depth = self
+            .package_depth(is_package)
+            .checked_sub(level as usize - 1)?;
+        let mut base: Vec<String> = self.0[..depth].to_vec();
 
         if let Some(m) = module {
             base.extend(m.split('.').map(String::from));
@@ -122,6 +220,15 @@ impl ModulePath {
     }
 }
 
+/// Parses the name as it appears in graph data (see `DependencyGraph::from_graph_data`)
+impl std::str::FromStr for ModulePath {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ModulePath::from_dotted(s).ok_or_else(|| format!("invalid module name '{s}'"))
+    }
+}
+
 impl GraphId for ModulePath {
     fn to_dotted(&self) -> String {
         self.0.join(".")
@@ -145,33 +252,678 @@ pub enum Import {
     },
 }
 
-/// Extract imports from a Python source file
-fn extract_imports(source: &str) -> Result<Vec<Import>, String> {
-    let parsed = parse_module(source).map_err(|e| e.to_string())?;
+impl std::fmt::Display for Import {
+    /// The statement in normalized Python syntax, e.g. `from ..core import models, *`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Import::Absolute { module } => write!(f, "import {}", module.join(".")),
+            Import::From {
+                module,
+                names,
+                level,
+            } => write!(
+                f,
+                "from {}{} import {}",
+                ".".repeat(*level as usize),
+                module.as_deref().unwrap_or_default().join("."),
+                if names.is_empty() {
+                    "*".to_string()
+                } else {
+                    names.join(", ")
+                }
+            ),
+        }
+    }
+}
+
+/// An import together with when it executes
+#[derive(Debug, Clone)]
+pub struct ImportStatement {
+    pub import: Import,
+    /// `Deferred` for imports inside function bodies, `ImportTime` otherwise
+    /// (module level, including class bodies and top-level `if`/`try` blocks)
+    pub kind: EdgeKind,
+    /// Platforms on which the import can run, narrowed by enclosing `sys.platform` /
+    /// `os.name` checks
+    pub platforms: Platforms,
+    /// 1-based line where the statement starts
+    pub line: usize,
+    /// 1-based column (in bytes) where the statement starts
+    pub column: usize,
+    pub aliases: Aliases,
+}
+
+/// Imports bound under another name (`import pkg.db as database`, `from pkg import db as
+/// database`), as (imported name, alias); the imported name of an `import` is the dotted
+/// module
+pub type Aliases = Vec<(String, String)>;
+
+/// Target platform selected with `--platform`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    Linux,
+    Darwin,
+    Win32,
+}
+
+impl Platform {
+    pub const ALL: [Platform; 3] = [Platform::Linux, Platform::Darwin, Platform::Win32];
+
+    /// The platform's `sys.platform` value
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Platform::Linux => "linux",
+            Platform::Darwin => "darwin",
+            Platform::Win32 => "win32",
+        }
+    }
+
+    fn os_name(self) -> &'static str {
+        match self {
+            Platform::Win32 => "nt",
+            Platform::Linux | Platform::Darwin => "posix",
+        }
+    }
+
+    /// The platform's `platform.system()` value
+    fn system(self) -> &'static str {
+        match self {
+            Platform::Linux => "Linux",
+            Platform::Darwin => "Darwin",
+            Platform::Win32 => "Windows",
+        }
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// Parse a `--platform` value (`linux`, `darwin`, or `win32`)
+pub fn parse_platform(value: &str) -> Result<Platform, PythonAnalysisError> {
+    Platform::ALL
+        .into_iter()
+        .find(|platform| platform.as_str() == value)
+        .ok_or_else(|| PythonAnalysisError::InvalidPlatform(value.to_string()))
+}
+
+/// A set of [`Platform`]s
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Platforms(u8);
+
+impl Platforms {
+    pub const ALL: Platforms = Platforms(0b111);
+    pub const NONE: Platforms = Platforms(0);
+
+    fn matching(predicate: impl Fn(Platform) -> bool) -> Self {
+        Platforms(
+            Platform::ALL
+                .into_iter()
+                .filter(|&platform| predicate(platform))
+                .map(Platform::bit)
+                .sum(),
+        )
+    }
+
+    pub fn contains(self, platform: Platform) -> bool {
+        self.0 & platform.bit() != 0
+    }
+
+    pub fn union(self, other: Platforms) -> Platforms {
+        Platforms(self.0 | other.0)
+    }
+
+    pub fn intersection(self, other: Platforms) -> Platforms {
+        Platforms(self.0 & other.0)
+    }
+
+    fn complement(self) -> Platforms {
+        Platforms(!self.0 & Self::ALL.0)
+    }
+
+    /// Edge tags for imports limited to these platforms: `platform:<name>` for each of
+    /// them, or `platform:other` when none of the known platforms apply
+    fn tags(self) -> Vec<String> {
+        let tags: Vec<String> = Platform::ALL
+            .into_iter()
+            .filter(|&platform| self.contains(platform))
+            .map(|platform| format!("platform:{}", platform.as_str()))
+            .collect();
+        if tags.is_empty() {
+            vec!["platform:other".to_string()]
+        } else {
+            tags
+        }
+    }
+}
+
+/// Platforms on which `test` may be true and on which it may be false. Comparisons of
+/// `sys.platform`, `os.name`, and `platform.system()` with string literals (`==`, `!=`,
+/// `in`, `not in`), `sys.platform.startswith(...)`, `not`, `and`, and `or` are
+/// understood; any other expression may go either way.
+fn platform_condition(test: &ruff_python_ast::Expr) -> (Platforms, Platforms) {
+    use ruff_python_ast::{BoolOp, CmpOp, Expr, UnaryOp};
+
+    #[derive(Clone, Copy)]
+    enum Subject {
+        SysPlatform,
+        OsName,
+        System,
+    }
+
+    let is_attribute = |expr: &Expr, module: &str, name: &str| {
+        matches!(expr, Expr::Attribute(attribute)
+            if attribute.attr.as_str() == name
+                && matches!(attribute.value.as_ref(), Expr::Name(value) if value.id.as_str() == module))
+    };
+    let subject = |expr: &Expr| match expr {
+        _ if is_attribute(expr, "sys", "platform") => Some(Subject::SysPlatform),
+        _ if is_attribute(expr, "os", "name") => Some(Subject::OsName),
+        Expr::Call(call)
+            if call.arguments.args.is_empty() && is_attribute(&call.func, "platform", "system") =>
+        {
+            Some(Subject::System)
+        }
+        _ => None,
+    };
+    // String literals of `"x"` or a tuple/list of them
+    let strings = |expr: &Expr| -> Option<Vec<String>> {
+        match expr {
+            Expr::StringLiteral(literal) => Some(vec![literal.value.to_str().to_string()]),
+            Expr::Tuple(ruff_python_ast::ExprTuple { elts, .. })
+            | Expr::List(ruff_python_ast::ExprList { elts, .. }) => elts
+                .iter()
+                .map(|elt| match elt {
+                    Expr::StringLiteral(literal) => Some(literal.value.to_str().to_string()),
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        }
+    };
+    let is_value = |subject: Subject, platform: Platform, value: &str| match subject {
+        Subject::SysPlatform => {
+            value == platform.as_str() || (platform == Platform::Linux && value == "linux2")
+        }
+        Subject::OsName => value == platform.os_name(),
+        Subject::System => value == platform.system(),
+    };
+    let unknown = (Platforms::ALL, Platforms::ALL);
+    let from_true = |platforms: Platforms| (platforms, platforms.complement());
+
+    match test {
+        Expr::UnaryOp(unary) if unary.op == UnaryOp::Not => {
+            let (may_true, may_false) = platform_condition(&unary.operand);
+            (may_false, may_true)
+        }
+        Expr::BoolOp(bool_op) => {
+            let parts = bool_op.values.iter().map(platform_condition);
+            match bool_op.op {
+                // `and` is true only where every operand may be, false where any may be
+                BoolOp::And => parts.fold(
+                    (Platforms::ALL, Platforms::NONE),
+                    |(may_true, may_false), (part_true, part_false)| {
+                        (
+                            may_true.intersection(part_true),
+                            may_false.union(part_false),
+                        )
+                    },
+                ),
+                BoolOp::Or => parts.fold(
+                    (Platforms::NONE, Platforms::ALL),
+                    |(may_true, may_false), (part_true, part_false)| {
+                        (
+                            may_true.union(part_true),
+                            may_false.intersection(part_false),
+                        )
+                    },
+                ),
+            }
+        }
+        Expr::Compare(compare) if compare.ops.len() == 1 => {
+            let (left, right) = (compare.left.as_ref(), &compare.comparators[0]);
+            let (subject, values) = match (subject(left), subject(right)) {
+                (Some(subject), _) => (subject, strings(right)),
+                (None, Some(subject)) => (subject, strings(left)),
+                (None, None) => return unknown,
+            };
+            let Some(values) = values else {
+                return unknown;
+            };
+            let matches = Platforms::matching(|platform| {
+                values
+                    .iter()
+                    .any(|value| is_value(subject, platform, value))
+            });
+            match compare.ops[0] {
+                CmpOp::Eq | CmpOp::In => from_true(matches),
+                CmpOp::NotEq | CmpOp::NotIn => from_true(matches.complement()),
+                _ => unknown,
+            }
+        }
+        Expr::Call(call) => match call.func.as_ref() {
+            Expr::Attribute(method)
+                if method.attr.as_str() == "startswith"
+                    && matches!(subject(&method.value), Some(Subject::SysPlatform))
+                    && call.arguments.args.len() == 1 =>
+            {
+                match strings(&call.arguments.args[0]) {
+                    Some(prefixes) => from_true(Platforms::matching(|platform| {
+                        prefixes
+                            .iter()
+                            .any(|prefix| platform.as_str().starts_with(prefix.as_str()))
+                    })),
+                    None => unknown,
+                }
+            }
+            _ => unknown,
+        },
+        _ => unknown,
+    }
+}
+
+/// Extensions of Cython sources: implementation (`.pyx`) and declaration (`.pxd`) files
+const CYTHON_EXTENSIONS: [&str; 2] = ["pyx", "pxd"];
+
+pub(crate) fn is_cython_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| CYTHON_EXTENSIONS.iter().any(|cython| ext == *cython))
+}
+
+/// Comment on an import statement that leaves its edges out of the graph
+const IGNORE_MARKER: &str = "deptree: ignore";
+
+/// Comment among a file's leading comments that leaves the whole module out of the graph
+const IGNORE_FILE_MARKER: &str = "deptree: ignore-file";
+
+/// Prefix of the header comment declaring a module's lifecycle state
+const LIFECYCLE_MARKER: &str = "deptree: lifecycle=";
+
+/// Whether a line carries `marker` as a comment (`# deptree: ignore`)
+fn has_marker_comment(line: &str, marker: &str) -> bool {
+    line.split('#')
+        .skip(1)
+        .any(|comment| comment.trim() == marker)
+}
+
+/// Line layout of a source file: where each line starts, and the byte ranges of the
+/// lines carrying `# deptree: ignore`
+struct SourceLines {
+    starts: Vec<usize>,
+    ignored: Vec<Range<usize>>,
+}
+
+impl SourceLines {
+    fn new(source: &str) -> Self {
+        let mut starts = Vec::new();
+        let mut ignored = Vec::new();
+        let mut start = 0;
+        for line in source.split_inclusive('\n') {
+            starts.push(start);
+            if has_marker_comment(line, IGNORE_MARKER) {
+                ignored.push(start..start + line.len());
+            }
+            start += line.len();
+        }
+        SourceLines { starts, ignored }
+    }
+
+    /// Whether the bytes from `start` to `end` touch an ignored line
+    fn is_ignored(&self, start: usize, end: usize) -> bool {
+        self.ignored
+            .iter()
+            .any(|line| line.start <= end && start < line.end)
+    }
+
+    /// 1-based line of a byte offset
+    fn line_of(&self, offset: usize) -> usize {
+        self.starts.partition_point(|&start| start <= offset)
+    }
+
+    /// 1-based column (in bytes) of a byte offset
+    fn column_of(&self, offset: usize) -> usize {
+        offset - self.starts[self.line_of(offset) - 1] + 1
+    }
+}
+
+/// Whether the file opts out of the graph with `# deptree: ignore-file` before its first
+/// line of code
+pub(crate) fn has_ignore_file_marker(path: &Path) -> bool {
+    let Ok(file) = std::fs::File::open(path) else {
+        return false;
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .take_while(|line| line.trim().is_empty() || line.trim_start().starts_with('#'))
+        .any(|line| has_marker_comment(&line, IGNORE_FILE_MARKER))
+}
+
+/// Value of a `# deptree: lifecycle=<state>` comment before the file's first line of code
+fn header_lifecycle(source: &str) -> Option<&str> {
+    source
+        .lines()
+        .take_while(|line| line.trim().is_empty() || line.trim_start().starts_with('#'))
+        .flat_map(|line| line.split('#').skip(1))
+        .find_map(|comment| comment.trim().strip_prefix(LIFECYCLE_MARKER))
+        .map(str::trim)
+}
+
+/// Imports of a parsed file
+pub struct FileImports {
+    pub imports: Vec<ImportStatement>,
+    /// `(line, message)` for each construct the targeted Python version does not support
+    pub unsupported: Vec<(usize, String)>,
+    /// Names resolved lazily by a module-level `__getattr__`, as (attribute, module spec)
+    pub lazy_exports: Vec<(String, String)>,
+    /// Statements run on import other than imports, `__all__` assignments, docstrings,
+    /// and `pass`
+    pub body_statements: usize,
+    /// Modules imported by name at run time, with names as written
+    pub dynamic_imports: Vec<DynamicImport>,
+}
+
+/// Extract imports from a Python source file, skipping those marked `# deptree: ignore`.
+/// Syntax newer than `target_version` still parses but is listed in `unsupported`.
+pub fn extract_imports(source: &str, target_version: PythonVersion) -> Result<FileImports, String> {
+    let options = ParseOptions::from(Mode::Module).with_target_version(target_version);
+    let parsed = parse(source, options)
+        .map_err(|e| e.to_string())?
+        .try_into_module()
+        .ok_or("not a module")?;
 
     let mut imports = Vec::new();
-    visit_stmts(parsed.suite(), &mut imports);
+    let lines = SourceLines::new(source);
+    visit_stmts(
+        parsed.suite(),
+        EdgeKind::ImportTime,
+        Platforms::ALL,
+        &lines,
+        &mut imports,
+    );
+    let mut dynamic_imports = DynamicImports {
+        source,
+        lines: &lines,
+        found: Vec::new(),
+    };
+    ruff_python_ast::visitor::walk_body(&mut dynamic_imports, parsed.suite());
 
-    Ok(imports)
+    let unsupported = parsed
+        .unsupported_syntax_errors()
+        .iter()
+        .map(|error| {
+            let line = lines.line_of(usize::from(error.range.start()));
+            (line, error.to_string())
+        })
+        .collect();
+
+    Ok(FileImports {
+        imports,
+        unsupported,
+        lazy_exports: lazy_exports(parsed.suite(), source),
+        body_statements: body_statements(parsed.suite()),
+        dynamic_imports: dynamic_imports.found,
+    })
+}
+
+/// Finds calls importing a module by name: `importlib.import_module(...)`, a bare
+/// `import_module(...)`, and `__import__(...)`. Calls on an ignored line are skipped.
+struct DynamicImports<'s> {
+    source: &'s str,
+    lines: &'s SourceLines,
+    found: Vec<DynamicImport>,
+}
+
+impl<'a> ruff_python_ast::visitor::Visitor<'a> for DynamicImports<'_> {
+    fn visit_expr(&mut self, expr: &'a ruff_python_ast::Expr) {
+        use ruff_python_ast::Expr;
+
+        if let Expr::Call(call) = expr
+            && is_import_function(&call.func)
+            && let Some(argument) = call.arguments.args.first()
+        {
+            let (start, end) = (
+                usize::from(call.range.start()),
+                usize::from(call.range.end()),
+            );
+            if !self.lines.is_ignored(start, end) {
+                let name = match argument {
+                    Expr::StringLiteral(literal) => DynamicName::Literal {
+                        module: literal.value.to_str().to_string(),
+                    },
+                    _ => DynamicName::Computed {
+                        prefix: constant_prefix(&self.source[start..end]),
+                    },
+                };
+                self.found.push(DynamicImport {
+                    line: self.lines.line_of(start),
+                    name,
+                });
+            }
+        }
+        ruff_python_ast::visitor::walk_expr(self, expr);
+    }
+}
+
+fn is_import_function(func: &ruff_python_ast::Expr) -> bool {
+    use ruff_python_ast::Expr;
+
+    match func {
+        Expr::Name(name) => matches!(name.id.as_str(), "import_module" | "__import__"),
+        Expr::Attribute(attribute) => {
+            attribute.attr.as_str() == "import_module"
+                && matches!(attribute.value.as_ref(), Expr::Name(value) if value.id.as_str() == "importlib")
+        }
+        _ => false,
+    }
+}
+
+/// Constant start of the first argument of a call, given the call's text: `plugins.` for
+/// `import_module(f"plugins.{name}")`, `import_module("plugins." + name)`, or
+/// `import_module("plugins.%s" % name)`, and nothing for `import_module(name)`
+fn constant_prefix(call: &str) -> String {
+    let argument = call
+        .split_once('(')
+        .map_or("", |(_, rest)| rest)
+        .trim_start();
+    let literal = argument.trim_start_matches(['f', 'F', 'r', 'R']);
+    match literal.chars().next() {
+        Some(quote @ ('"' | '\'')) => literal[1..]
+            .chars()
+            .take_while(|c| !matches!(c, '{' | '%') && *c != quote)
+            .collect(),
+        _ => String::new(),
+    }
+}
+
+/// `name` with leading dots resolved against `package` (one dot is the package itself),
+/// or unchanged when they climb above the top-level package
+fn absolute_name(package: &[String], name: &str) -> String {
+    let relative = name.trim_start_matches('.');
+    let level = name.len() - relative.len();
+    if level == 0 {
+        return name.to_string();
+    }
+    match package.len().checked_sub(level - 1) {
+        Some(depth) if depth > 0 => format!("{}.{relative}", package[..depth].join(".")),
+        _ => name.to_string(),
+    }
+}
+
+/// Attributes a module provides through a module-level `__getattr__` (PEP 562), as
+/// `(attribute, module)` pairs. Only literal dicts assigned at module level are read, and
+/// only those `__getattr__` uses, directly or through other module-level assignments
+/// (`_attr_to_module = {... for module, attrs in submodules.items() ...}`). A dict maps
+/// attributes to modules (`{"Client": ".client"}`, optionally `"module:attribute"`) or
+/// modules to the attributes they provide (`{"client": ["Client", "connect"]}`).
+fn lazy_exports(suite: &[ruff_python_ast::Stmt], source: &str) -> Vec<(String, String)> {
+    use ruff_python_ast::{Expr, Stmt};
+
+    let text = |start: usize, end: usize| &source[start..end];
+    let Some(getattr_body) = suite.iter().find_map(|stmt| match stmt {
+        Stmt::FunctionDef(func) if func.name.as_str() == "__getattr__" => {
+            Some(text(func.range.start().into(), func.range.end().into()))
+        }
+        _ => None,
+    }) else {
+        return Vec::new();
+    };
+
+    // Module-level `name = value` assignments with their source text
+    let assignments: Vec<(&str, &Expr, &str)> = suite
+        .iter()
+        .filter_map(|stmt| {
+            let (target, value, range) = match stmt {
+                Stmt::Assign(assign) if assign.targets.len() == 1 => {
+                    (&assign.targets[0], assign.value.as_ref(), assign.range)
+                }
+                Stmt::AnnAssign(assign) => (
+                    assign.target.as_ref(),
+                    assign.value.as_deref()?,
+                    assign.range,
+                ),
+                _ => return None,
+            };
+            match target {
+                Expr::Name(name) => Some((
+                    name.id.as_str(),
+                    value,
+                    text(range.start().into(), range.end().into()),
+                )),
+                _ => None,
+            }
+        })
+        .collect();
+    let mentions = |code: &str, name: &str| {
+        let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+        code.match_indices(name).any(|(start, _)| {
+            !code[..start].ends_with(is_ident) && !code[start + name.len()..].starts_with(is_ident)
+        })
+    };
+    let mut used = vec![false; assignments.len()];
+    let mut pending = vec![getattr_body];
+    while let Some(code) = pending.pop() {
+        for (i, (name, _, assignment)) in assignments.iter().enumerate() {
+            if !used[i] && mentions(code, name) {
+                used[i] = true;
+                pending.push(assignment);
+            }
+        }
+    }
+
+    let string = |expr: &Expr| match expr {
+        Expr::StringLiteral(literal) => Some(literal.value.to_str().to_string()),
+        _ => None,
+    };
+    let mut exports = Vec::new();
+    for (_, value, _) in assignments
+        .iter()
+        .zip(used)
+        .filter_map(|(a, used)| used.then_some(a))
+    {
+        let Expr::Dict(dict) = value else {
+            continue;
+        };
+        for item in &dict.items {
+            let Some(key) = item.key.as_ref().and_then(string) else {
+                continue;
+            };
+            match &item.value {
+                Expr::StringLiteral(_) => exports.extend(string(&item.value).map(|m| (key, m))),
+                Expr::List(ruff_python_ast::ExprList { elts, .. })
+                | Expr::Tuple(ruff_python_ast::ExprTuple { elts, .. }) => exports.extend(
+                    elts.iter()
+                        .filter_map(string)
+                        .map(|attribute| (attribute, key.clone())),
+                ),
+                _ => {}
+            }
+        }
+    }
+    exports
 }
 
-/// Recursively visit all statements in the AST to extract imports
-fn visit_stmts(stmts: &[ruff_python_ast::Stmt], imports: &mut Vec<Import>) {
+/// Resolve the module of a lazy export defined in `package` (the package of the module
+/// with the `__getattr__`). Leading dots are relative to `package`; a plain name is looked
+/// up as a submodule of `package` first, then as an absolute module.
+fn resolve_lazy_export(
+    package: &[String],
+    spec: &str,
+    modules: &HashMap<ModulePath, PathBuf>,
+) -> Option<ModulePath> {
+    let spec = spec.split_once(':').map_or(spec, |(module, _)| module);
+    let relative = spec.trim_start_matches('.');
+    let level = spec.len() - relative.len();
+    let rest = relative.split('.').filter(|part| !part.is_empty());
+
+    let candidates = if level > 0 {
+        let depth = package.len().checked_sub(level - 1)?;
+        vec![
+            package[..depth]
+                .iter()
+                .cloned()
+                .chain(rest.map(String::from))
+                .collect(),
+        ]
+    } else {
+        vec![
+            package
+                .iter()
+                .cloned()
+                .chain(rest.clone().map(String::from))
+                .collect(),
+            rest.map(String::from).collect(),
+        ]
+    };
+    candidates
+        .into_iter()
+        .map(ModulePath)
+        .find(|module| !module.0.is_empty() && modules.contains_key(module))
+}
+
+/// Recursively visit all statements in the AST to extract imports.
+/// `kind` records whether the statements run at import time or are deferred, and
+/// `platforms` where they can run at all; import statements touching an ignored line
+/// are skipped.
+fn visit_stmts(
+    stmts: &[ruff_python_ast::Stmt],
+    kind: EdgeKind,
+    platforms: Platforms,
+    lines: &SourceLines,
+    imports: &mut Vec<ImportStatement>,
+) {
     use ruff_python_ast::{Stmt, StmtImport, StmtImportFrom};
 
     for stmt in stmts {
         match stmt {
-            Stmt::Import(StmtImport { names, .. }) => {
+            Stmt::Import(StmtImport { range, .. })
+            | Stmt::ImportFrom(StmtImportFrom { range, .. })
+                if lines.is_ignored(usize::from(range.start()), usize::from(range.end())) => {}
+            Stmt::Import(StmtImport { names, range, .. }) => {
+                let line = lines.line_of(usize::from(range.start()));
+                let column = lines.column_of(usize::from(range.start()));
                 for alias in names {
                     let module: Vec<String> =
                         alias.name.as_str().split('.').map(String::from).collect();
-                    imports.push(Import::Absolute { module });
+                    imports.push(ImportStatement {
+                        import: Import::Absolute { module },
+                        kind,
+                        platforms,
+                        line,
+                        column,
+                        aliases: alias
+                            .asname
+                            .iter()
+                            .map(|asname| (alias.name.to_string(), asname.to_string()))
+                            .collect(),
+                    });
                 }
             }
             Stmt::ImportFrom(StmtImportFrom {
                 module,
                 names,
                 level,
+                range,
                 ..
             }) => {
                 let module_parts = module
@@ -189,10 +941,25 @@ fn visit_stmts(stmts: &[ruff_python_ast::Stmt], imports: &mut Vec<Import>) {
                     })
                     .collect();
 
-                imports.push(Import::From {
-                    module: module_parts,
-                    names: imported_names,
-                    level: *level,
+                let aliases = names
+                    .iter()
+                    .filter_map(|alias| {
+                        let asname = alias.asname.as_ref()?;
+                        Some((alias.name.to_string(), asname.to_string()))
+                    })
+                    .collect();
+
+                imports.push(ImportStatement {
+                    import: Import::From {
+                        module: module_parts,
+                        names: imported_names,
+                        level: *level,
+                    },
+                    kind,
+                    platforms,
+                    line: lines.line_of(usize::from(range.start())),
+                    column: lines.column_of(usize::from(range.start())),
+                    aliases,
                 });
             }
             _ => {}
@@ -200,45 +967,65 @@ fn visit_stmts(stmts: &[ruff_python_ast::Stmt], imports: &mut Vec<Import>) {
 
         match stmt {
             Stmt::FunctionDef(func) => {
-                visit_stmts(&func.body, imports);
+                // Function bodies only run when called
+                visit_stmts(&func.body, EdgeKind::Deferred, platforms, lines, imports);
             }
             Stmt::ClassDef(class) => {
-                visit_stmts(&class.body, imports);
+                visit_stmts(&class.body, kind, platforms, lines, imports);
             }
             Stmt::If(if_stmt) => {
-                visit_stmts(&if_stmt.body, imports);
+                let (may_true, mut may_false) = platform_condition(&if_stmt.test);
+                visit_stmts(
+                    &if_stmt.body,
+                    kind,
+                    platforms.intersection(may_true),
+                    lines,
+                    imports,
+                );
+                // Each `elif`/`else` only runs where the tests before it were false
                 for clause in &if_stmt.elif_else_clauses {
-                    visit_stmts(&clause.body, imports);
+                    let (clause_true, clause_false) = clause
+                        .test
+                        .as_ref()
+                        .map_or((Platforms::ALL, Platforms::NONE), platform_condition);
+                    visit_stmts(
+                        &clause.body,
+                        kind,
+                        platforms.intersection(may_false).intersection(clause_true),
+                        lines,
+                        imports,
+                    );
+                    may_false = may_false.intersection(clause_false);
                 }
             }
             Stmt::While(while_stmt) => {
-                visit_stmts(&while_stmt.body, imports);
-                visit_stmts(&while_stmt.orelse, imports);
+                visit_stmts(&while_stmt.body, kind, platforms, lines, imports);
+                visit_stmts(&while_stmt.orelse, kind, platforms, lines, imports);
             }
             Stmt::For(for_stmt) => {
-                visit_stmts(&for_stmt.body, imports);
-                visit_stmts(&for_stmt.orelse, imports);
+                visit_stmts(&for_stmt.body, kind, platforms, lines, imports);
+                visit_stmts(&for_stmt.orelse, kind, platforms, lines, imports);
             }
             Stmt::With(with_stmt) => {
-                visit_stmts(&with_stmt.body, imports);
+                visit_stmts(&with_stmt.body, kind, platforms, lines, imports);
             }
             Stmt::Try(try_stmt) => {
                 use ruff_python_ast::ExceptHandler;
 
-                visit_stmts(&try_stmt.body, imports);
+                visit_stmts(&try_stmt.body, kind, platforms, lines, imports);
                 for handler in &try_stmt.handlers {
                     match handler {
                         ExceptHandler::ExceptHandler(except) => {
-                            visit_stmts(&except.body, imports);
+                            visit_stmts(&except.body, kind, platforms, lines, imports);
                         }
                     }
                 }
-                visit_stmts(&try_stmt.orelse, imports);
-                visit_stmts(&try_stmt.finalbody, imports);
+                visit_stmts(&try_stmt.orelse, kind, platforms, lines, imports);
+                visit_stmts(&try_stmt.finalbody, kind, platforms, lines, imports);
             }
             Stmt::Match(match_stmt) => {
                 for case in &match_stmt.cases {
-                    visit_stmts(&case.body, imports);
+                    visit_stmts(&case.body, kind, platforms, lines, imports);
                 }
             }
             _ => {}
@@ -246,6 +1033,227 @@ fn visit_stmts(stmts: &[ruff_python_ast::Stmt], imports: &mut Vec<Import>) {
     }
 }
 
+/// Number of statements that run when a module is imported, other than imports, `__all__`
+/// assignments, docstrings, and `pass`. The bodies of top-level `if`/`try` blocks are
+/// counted instead of the blocks themselves, so guarded imports stay free; a `def` or
+/// `class` counts once.
+fn body_statements(stmts: &[ruff_python_ast::Stmt]) -> usize {
+    use ruff_python_ast::{ExceptHandler, Expr, Stmt};
+
+    let is_all =
+        |target: &Expr| matches!(target, Expr::Name(name) if name.id.as_str() == "__all__");
+    stmts
+        .iter()
+        .map(|stmt| match stmt {
+            Stmt::Import(_) | Stmt::ImportFrom(_) | Stmt::Pass(_) => 0,
+            Stmt::Expr(expr) if matches!(*expr.value, Expr::StringLiteral(_)) => 0,
+            Stmt::Assign(assign) if assign.targets.iter().all(is_all) => 0,
+            Stmt::AugAssign(assign) if is_all(&assign.target) => 0,
+            Stmt::AnnAssign(assign) if is_all(&assign.target) => 0,
+            Stmt::If(if_stmt) => {
+                body_statements(&if_stmt.body)
+                    + if_stmt
+                        .elif_else_clauses
+                        .iter()
+                        .map(|clause| body_statements(&clause.body))
+                        .sum::<usize>()
+            }
+            Stmt::Try(try_stmt) => {
+                body_statements(&try_stmt.body)
+                    + try_stmt
+                        .handlers
+                        .iter()
+                        .map(|handler| match handler {
+                            ExceptHandler::ExceptHandler(except) => body_statements(&except.body),
+                        })
+                        .sum::<usize>()
+                    + body_statements(&try_stmt.orelse)
+                    + body_statements(&try_stmt.finalbody)
+            }
+            _ => 1,
+        })
+        .sum()
+}
+
+/// Best-effort import extraction for files ruff cannot parse: Python files with a syntax
+/// error (so it does not drop all of their edges) and Cython sources. Recognizes `import`,
+/// `from ... import`, and Cython `cimport` statements line by line (following parenthesized
+/// and backslash continuations) outside triple-quoted strings; imports indented below a
+/// `def` (or a Cython `cdef`/`cpdef` function) are deferred.
+fn scan_imports(source: &str) -> Vec<ImportStatement> {
+    let mut imports = Vec::new();
+    // Indentation of the enclosing `def` lines
+    let mut def_indents: Vec<usize> = Vec::new();
+    let mut open_string: Option<&str> = None;
+    let mut lines = source.lines().enumerate();
+
+    while let Some((index, line)) = lines.next() {
+        if let Some(quote) = open_string {
+            if line.matches(quote).count() % 2 == 1 {
+                open_string = None;
+            }
+            continue;
+        }
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        open_string = ["\"\"\"", "'''"]
+            .into_iter()
+            .find(|quote| line.matches(quote).count() % 2 == 1);
+
+        let indent = line.len() - trimmed.len();
+        while def_indents.last().is_some_and(|&def| indent <= def) {
+            def_indents.pop();
+        }
+        if starts_function(trimmed) {
+            def_indents.push(indent);
+            continue;
+        }
+        if !["import ", "cimport ", "from "]
+            .iter()
+            .any(|keyword| trimmed.starts_with(keyword))
+        {
+            continue;
+        }
+
+        let mut ignored = has_marker_comment(line, IGNORE_MARKER);
+        let mut statement = strip_comment(trimmed).to_string();
+        while statement.ends_with('\\')
+            || statement.matches('(').count() > statement.matches(')').count()
+        {
+            let Some((_, next)) = lines.next() else {
+                break;
+            };
+            ignored |= has_marker_comment(next, IGNORE_MARKER);
+            statement = format!(
+                "{} {}",
+                statement.trim_end_matches('\\'),
+                strip_comment(next).trim()
+            );
+        }
+        if ignored {
+            continue;
+        }
+
+        let kind = if def_indents.is_empty() {
+            EdgeKind::ImportTime
+        } else {
+            EdgeKind::Deferred
+        };
+        imports.extend(
+            statement
+                .split(';')
+                .filter_map(|part| scan_import_statement(part.trim()))
+                .flatten()
+                .map(|(import, aliases)| ImportStatement {
+                    import,
+                    kind,
+                    platforms: Platforms::ALL,
+                    line: index + 1,
+                    column: indent + 1,
+                    aliases,
+                }),
+        );
+    }
+
+    imports
+}
+
+/// Whether a (left-trimmed) line opens a function body: `def`, `async def`, `cpdef`, or a
+/// `cdef` function (as opposed to `cdef class` or a variable declaration)
+fn starts_function(line: &str) -> bool {
+    let code = strip_comment(line);
+    line.starts_with("def ")
+        || line.starts_with("async def ")
+        || line.starts_with("cpdef ")
+        || (line.starts_with("cdef ")
+            && !line.starts_with("cdef class ")
+            && code.contains('(')
+            && code.ends_with(':'))
+}
+
+fn strip_comment(line: &str) -> &str {
+    line.split_once('#')
+        .map_or(line, |(code, _)| code)
+        .trim_end()
+}
+
+/// Parse one `import ...` or `from ... import ...` statement (or their `cimport` forms),
+/// each import with its aliases; `None` when it is malformed
+fn scan_import_statement(statement: &str) -> Option<Vec<(Import, Aliases)>> {
+    let is_dotted_name = |name: &str| {
+        name.split('.')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_alphanumeric() || c == '_'))
+    };
+    // `name` or `name as alias`
+    let imported_name = |item: &str| item.split_whitespace().next().map(str::to_string);
+    let alias = |item: &str| match item.split_whitespace().collect::<Vec<_>>()[..] {
+        [name, "as", alias] => Some((name.to_string(), alias.to_string())),
+        _ => None,
+    };
+
+    if let Some(names) = statement
+        .strip_prefix("import ")
+        .or_else(|| statement.strip_prefix("cimport "))
+    {
+        let items: Vec<&str> = names.split(',').collect();
+        let modules: Vec<String> = items
+            .iter()
+            .filter_map(|item| imported_name(item))
+            .collect();
+        if modules.is_empty() || !modules.iter().all(|m| is_dotted_name(m)) {
+            return None;
+        }
+        return Some(
+            modules
+                .into_iter()
+                .zip(items)
+                .map(|(m, item)| {
+                    let import = Import::Absolute {
+                        module: m.split('.').map(String::from).collect(),
+                    };
+                    (import, alias(item).into_iter().collect())
+                })
+                .collect(),
+        );
+    }
+
+    let from = statement.strip_prefix("from ")?;
+    let (module, names) = from
+        .split_once(" import ")
+        .or_else(|| from.split_once(" cimport "))?;
+    let module = module.trim();
+    let dotted = module.trim_start_matches('.');
+    let level = (module.len() - dotted.len()) as u32;
+    if (level == 0 || !dotted.is_empty()) && !is_dotted_name(dotted) {
+        return None;
+    }
+    let items: Vec<&str> = names
+        .trim()
+        .trim_start_matches('(')
+        .trim_end_matches(')')
+        .split(',')
+        .collect();
+    let names: Vec<String> = items
+        .iter()
+        .filter_map(|item| imported_name(item))
+        .filter(|name| name != "*")
+        .collect();
+    if !names.iter().all(|name| is_dotted_name(name)) {
+        return None;
+    }
+    let import = Import::From {
+        module: (!dotted.is_empty()).then(|| dotted.split('.').map(String::from).collect()),
+        names,
+        level,
+    };
+    Some(vec![(
+        import,
+        items.into_iter().filter_map(alias).collect(),
+    )])
+}
+
 /// Check if a given Python package directory is a namespace package
 ///
 /// Detects two types:
@@ -261,37 +1269,180 @@ fn is_namespace_package(package_path: &Path) -> bool {
     if !init_path.exists() {
         if let Ok(entries) = std::fs::read_dir(package_path) {
             for entry in entries.filter_map(|e| e.ok()) {
-                if let Some(ext) = entry.path().extension() {
-                    if ext == "py" {
-                        return true;
-                    }
+                if let Some(ext) = entry.path().extension()
+                    && ext == "py"
+                {
+                    return true;
                 }
             }
         }
         return false;
     }
 
-    if let Ok(content) = std::fs::read_to_string(&init_path) {
-        let has_pkgutil = content.contains("pkgutil.extend_path");
-        let has_pkg_resources = content.contains("pkg_resources.declare_namespace");
+    declares_namespace(&init_path)
+}
+
+/// Whether an `__init__.py` makes its package a legacy namespace package, extending the
+/// package path with `pkgutil.extend_path()` or `pkg_resources.declare_namespace()`
+fn declares_namespace(init_path: &Path) -> bool {
+    std::fs::read_to_string(init_path).is_ok_and(|content| {
+        content.contains("pkgutil.extend_path")
+            || content.contains("pkg_resources.declare_namespace")
+    })
+}
 
-        if has_pkgutil || has_pkg_resources {
-            return true;
+/// Portions of the source root's namespace packages elsewhere in the project: directories
+/// whose `__init__.py` extends the package path (see [`declares_namespace`]) and whose
+/// trailing names match a namespace package of the source root, as `(directory, root)`
+/// with the directory their modules are named from. `plugins/src/company/` extending
+/// `src/company/` gives `(plugins/src/company, plugins/src)`, so its `plugin.py` is
+/// `company.plugin` rather than a script. `pruned` directories are not searched.
+pub(crate) fn namespace_portions(
+    project_root: &Path,
+    source_root: &Path,
+    pruned: &[&Path],
+    exclude_patterns: &[String],
+) -> Vec<(PathBuf, PathBuf)> {
+    let mut portions = Vec::new();
+    let mut walker = WalkDir::new(project_root).into_iter();
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if !entry.file_type().is_dir() {
+            continue;
+        }
+        let path = entry.path();
+        if path == source_root
+            || pruned.contains(&path)
+            || should_exclude_entry(&entry, project_root, exclude_patterns)
+        {
+            walker.skip_current_dir();
+            continue;
+        }
+        if !declares_namespace(&path.join("__init__.py")) {
+            continue;
+        }
+        let Ok(relative) = path.strip_prefix(project_root) else {
+            continue;
+        };
+        let names: Vec<&std::ffi::OsStr> = relative.iter().collect();
+        // The most specific namespace package the trailing names match
+        let depth = (1..=names.len()).rev().find(|&depth| {
+            let package: PathBuf = names[names.len() - depth..].iter().collect();
+            is_namespace_package(&source_root.join(package))
+        });
+        if let Some(depth) = depth
+            && let Some(root) = path.ancestors().nth(depth)
+        {
+            portions.push((path.to_path_buf(), root.to_path_buf()));
+            walker.skip_current_dir();
         }
     }
-
-    false
+    portions
 }
 
-/// Analyze a Python project and return its internal dependency graph
-pub fn analyze_project(
+/// Path dependencies of the project and, transitively, of the libraries it depends on.
+/// Missing directories are reported and skipped.
+pub(crate) fn collect_path_dependencies(
     project_root: &Path,
-    source_root: Option<&Path>,
-    exclude_patterns: &[String],
+) -> Result<Vec<(String, PathBuf)>, PythonAnalysisError> {
+    let mut seen: HashSet<PathBuf> = project_root.canonicalize().into_iter().collect();
+    let mut found = Vec::new();
+    let mut pending = path_dependencies(project_root)?;
+    while let Some((name, directory)) = pending.pop() {
+        let Ok(canonical) = directory.canonicalize() else {
+            eprintln!(
+                "Warning: path dependency {name} not found at {}",
+                directory.display()
+            );
+            continue;
+        };
+        if !seen.insert(canonical) {
+            continue;
+        }
+        pending.extend(path_dependencies(&directory)?);
+        found.push((name, directory));
+    }
+    found.sort();
+    Ok(found)
+}
+
+/// Module name of `path` under the most specific `module_map` entry containing it, where
+/// the entries' paths have been joined to the project root
+pub(crate) fn mapped_module(
+    path: &Path,
+    module_map: &[(PathBuf, ModulePath)],
+) -> Option<ModulePath> {
+    let (root, prefix) = module_map
+        .iter()
+        .filter(|(root, _)| path.starts_with(root))
+        .max_by_key(|(root, _)| root.components().count())?;
+    let mut module = prefix.clone();
+    if let Some(relative) = ModulePath::from_file_path(path, root) {
+        module.0.extend(relative.0);
+    }
+    Some(module)
+}
+
+/// Analyze a Python project and return its internal dependency graph
+pub fn analyze_project(
+    project_root: &Path,
+    source_root: Option<&Path>,
+    exclude_patterns: &[String],
+) -> Result<PythonGraph, PythonAnalysisError> {
+    analyze_project_with_options(
+        project_root,
+        source_root,
+        exclude_patterns,
+        &AnalysisOptions::default(),
+    )
+}
+
+/// [`analyze_project`] with explicit parser settings
+pub fn analyze_project_with_options(
+    project_root: &Path,
+    source_root: Option<&Path>,
+    exclude_patterns: &[String],
+    options: &AnalysisOptions,
+) -> Result<PythonGraph, PythonAnalysisError> {
+    analyze(project_root, source_root, exclude_patterns, options, None)
+}
+
+/// [`analyze_project_with_options`] that also records the extracted imports, the modules
+/// found, and how each import was resolved (`--emit`)
+pub fn analyze_project_traced(
+    project_root: &Path,
+    source_root: Option<&Path>,
+    exclude_patterns: &[String],
+    options: &AnalysisOptions,
+) -> Result<(PythonGraph, AnalysisTrace), PythonAnalysisError> {
+    let mut trace = AnalysisTrace::default();
+    let graph = analyze(
+        project_root,
+        source_root,
+        exclude_patterns,
+        options,
+        Some(&mut trace),
+    )?;
+    // Files are walked in directory order, which differs between file systems
+    trace.files.sort_by(|a, b| a.file.cmp(&b.file));
+    trace.resolution.sort_by(|a, b| a.file.cmp(&b.file));
+    Ok((graph, trace))
+}
+
+fn analyze(
+    project_root: &Path,
+    source_root: Option<&Path>,
+    exclude_patterns: &[String],
+    options: &AnalysisOptions,
+    mut trace: Option<&mut AnalysisTrace>,
 ) -> Result<PythonGraph, PythonAnalysisError> {
     #[derive(Clone, Copy)]
     enum SourceKind {
         Internal,
+        /// Cython module inside the source root
+        Extension,
         Script,
     }
 
@@ -311,83 +1462,324 @@ pub fn analyze_project(
         detect_source_root(project_root)?
     };
 
+    let module_map: Vec<(PathBuf, ModulePath)> = options
+        .module_map
+        .iter()
+        .map(|(path, module)| (project_root.join(path), module.clone()))
+        .collect();
+    let module_of = |path: &Path| {
+        mapped_module(path, &module_map)
+            .or_else(|| ModulePath::from_file_path(path, &actual_source_root))
+    };
+    let is_source_file = |path: &Path| {
+        path.extension().map(|ext| ext == "py").unwrap_or(false) || is_cython_file(path)
+    };
+
     let mut graph = PythonGraph::new();
 
     let mut sources: Vec<SourceFile> = Vec::new();
-
-    for entry in WalkDir::new(&actual_source_root)
-        .into_iter()
-        .filter_entry(|e| !should_exclude_path(e.path(), &actual_source_root, exclude_patterns))
-        .filter_map(|e| e.ok())
-        .filter(|e| e.path().extension().map(|ext| ext == "py").unwrap_or(false))
-    {
+    // Modules whose file starts with `# deptree: ignore-file`
+    let mut ignored: HashSet<ModulePath> = HashSet::new();
+
+    // Mapped paths outside the source root are walked like it
+    let walked_roots = std::iter::once((actual_source_root.as_path(), exclude_patterns)).chain(
+        module_map
+            .iter()
+            .filter(|(root, _)| !root.starts_with(&actual_source_root))
+            .map(|(root, _)| (root.as_path(), &[][..])),
+    );
+    for entry in walked_roots.flat_map(|(walk_root, excludes)| {
+        WalkDir::new(walk_root)
+            .into_iter()
+            .filter_entry(move |e| !should_exclude_entry(e, walk_root, excludes))
+            .filter_map(|e| e.ok())
+            .filter(|e| is_source_file(e.path()))
+    }) {
         let path = entry.path();
-        if let Some(module_path) = ModulePath::from_file_path(path, &actual_source_root) {
+        if let Some(module_path) = module_of(path) {
+            if has_ignore_file_marker(path) {
+                ignored.insert(module_path);
+                continue;
+            }
             sources.push(SourceFile {
                 module: module_path,
                 path: path.to_path_buf(),
-                kind: SourceKind::Internal,
+                kind: if is_cython_file(path) {
+                    SourceKind::Extension
+                } else {
+                    SourceKind::Internal
+                },
             });
         }
     }
 
+    // A package shadows the module file of the same name next to it (`json/` over
+    // `json.py`), as on import; the module keeps a node of its own, named by its path
+    let is_init = |path: &Path| path.file_stem().is_some_and(|stem| stem == "__init__");
+    let packages: HashSet<ModulePath> = sources
+        .iter()
+        .filter(|source| is_init(&source.path))
+        .map(|source| source.module.clone())
+        .collect();
+    let mut shadowed: HashSet<PathBuf> = HashSet::new();
+    for source in &mut sources {
+        if packages.contains(&source.module)
+            && !is_init(&source.path)
+            && source.path.extension().is_some_and(|ext| ext == "py")
+            && let Some(renamed) = ModulePath::from_script_path(&source.path, project_root)
+        {
+            eprintln!(
+                "Warning: {} is shadowed by the package {}; it is shown as {}",
+                source
+                    .path
+                    .strip_prefix(project_root)
+                    .unwrap_or(&source.path)
+                    .display(),
+                source.module.to_dotted(),
+                renamed.to_dotted()
+            );
+            graph.mark_as_script(&renamed);
+            graph.ensure_node(renamed.clone());
+            shadowed.insert(source.path.clone());
+            source.module = renamed;
+            source.kind = SourceKind::Script;
+        }
+    }
+
     for entry in WalkDir::new(&actual_source_root)
         .into_iter()
-        .filter_entry(|e| !should_exclude_path(e.path(), &actual_source_root, exclude_patterns))
+        .filter_entry(|e| !should_exclude_entry(e, &actual_source_root, exclude_patterns))
         .filter_map(|e| e.ok())
         .filter(|e| e.path().is_dir() && e.path() != actual_source_root)
     {
         let dir_path = entry.path();
-        if is_namespace_package(dir_path) {
-            if let Some(module_path) =
-                ModulePath::from_file_path(&dir_path.join("__dummy__.py"), &actual_source_root)
-            {
-                let mut package_parts = module_path.0;
-                if !package_parts.is_empty()
-                    && package_parts.last() == Some(&"__dummy__".to_string())
-                {
-                    package_parts.pop();
-                    if !package_parts.is_empty() {
-                        let package_module_path = ModulePath(package_parts);
-                        graph.mark_as_namespace_package(&package_module_path);
-                        graph.ensure_node(package_module_path);
-                    }
+        if is_namespace_package(dir_path)
+            && let Some(module_path) = module_of(&dir_path.join("__dummy__.py"))
+        {
+            let mut package_parts = module_path.0;
+            if !package_parts.is_empty() && package_parts.last() == Some(&"__dummy__".to_string()) {
+                package_parts.pop();
+                if !package_parts.is_empty() {
+                    let package_module_path = ModulePath(package_parts);
+                    graph.mark_as_namespace_package(&package_module_path);
+                    graph.ensure_node(package_module_path);
                 }
             }
         }
     }
 
+    // Modules of namespace packages extended from outside the source root
+    let pruned: Vec<&Path> = module_map.iter().map(|(root, _)| root.as_path()).collect();
+    let portions = if actual_source_root == project_root {
+        Vec::new()
+    } else {
+        namespace_portions(project_root, &actual_source_root, &pruned, exclude_patterns)
+    };
+    let mut known: HashSet<ModulePath> =
+        sources.iter().map(|source| source.module.clone()).collect();
+    for (portion, root) in &portions {
+        for entry in WalkDir::new(portion)
+            .into_iter()
+            .filter_entry(|e| !should_exclude_entry(e, portion, &[]))
+            .filter_map(|e| e.ok())
+            .filter(|e| is_source_file(e.path()))
+        {
+            let path = entry.path();
+            // The portion's own `__init__.py` only extends the package path
+            if path == portion.join("__init__.py") {
+                continue;
+            }
+            let Some(module) = ModulePath::from_file_path(path, root) else {
+                continue;
+            };
+            if has_ignore_file_marker(path) {
+                ignored.insert(module);
+                continue;
+            }
+            // The source root's modules win, then the first portion's
+            if !known.insert(module.clone()) {
+                continue;
+            }
+            sources.push(SourceFile {
+                module,
+                path: path.to_path_buf(),
+                kind: if is_cython_file(path) {
+                    SourceKind::Extension
+                } else {
+                    SourceKind::Internal
+                },
+            });
+        }
+    }
+
     for entry in WalkDir::new(project_root)
         .into_iter()
         .filter_entry(|e| {
-            if e.path() == actual_source_root {
+            // `--no-scripts` rejects the project root, so nothing below it is read
+            if options.skip_scripts
+                || e.path() == actual_source_root
+                || module_map.iter().any(|(root, _)| e.path() == root)
+                || portions.iter().any(|(portion, _)| e.path() == portion)
+            {
                 return false;
             }
-            !should_exclude_path(e.path(), project_root, exclude_patterns)
+            !should_exclude_entry(e, project_root, exclude_patterns)
         })
         .filter_map(|e| e.ok())
         .filter(|e| e.path().extension().map(|ext| ext == "py").unwrap_or(false))
     {
         let path = entry.path();
-        if !path.starts_with(&actual_source_root) {
-            if let Some(script_path) = ModulePath::from_script_path(path, project_root) {
-                graph.mark_as_script(&script_path);
-                graph.ensure_node(script_path.clone());
+        if !path.starts_with(&actual_source_root)
+            && let Some(script_path) = ModulePath::from_script_path(path, project_root)
+        {
+            if has_ignore_file_marker(path) {
+                ignored.insert(script_path);
+                continue;
+            }
+            graph.mark_as_script(&script_path);
+            graph.ensure_node(script_path.clone());
+            sources.push(SourceFile {
+                module: script_path,
+                path: path.to_path_buf(),
+                kind: SourceKind::Script,
+            });
+        }
+    }
+
+    // Modules of local libraries, with the name of the dependency providing them
+    let mut library_modules: HashMap<ModulePath, String> = HashMap::new();
+    if !options.skip_path_dependencies {
+        let own_modules: HashSet<ModulePath> =
+            sources.iter().map(|source| source.module.clone()).collect();
+        for (name, library_root) in collect_path_dependencies(project_root)? {
+            let library_source_root = match detect_source_root(&library_root) {
+                Ok(root) => root,
+                Err(e) => {
+                    eprintln!("Warning: Skipping path dependency {name}: {e}");
+                    continue;
+                }
+            };
+            for entry in WalkDir::new(&library_source_root)
+                .into_iter()
+                .filter_entry(|e| !should_exclude_entry(e, &library_source_root, &[]))
+                .filter_map(|e| e.ok())
+                .filter(|e| is_source_file(e.path()))
+            {
+                let path = entry.path();
+                let Some(module) = ModulePath::from_file_path(path, &library_source_root) else {
+                    continue;
+                };
+                // The project's own modules win over a library's
+                if own_modules.contains(&module)
+                    || library_modules.contains_key(&module)
+                    || has_ignore_file_marker(path)
+                {
+                    continue;
+                }
+                library_modules.insert(module.clone(), name.clone());
                 sources.push(SourceFile {
-                    module: script_path,
+                    module,
                     path: path.to_path_buf(),
-                    kind: SourceKind::Script,
+                    kind: if is_cython_file(path) {
+                        SourceKind::Extension
+                    } else {
+                        SourceKind::Internal
+                    },
                 });
             }
         }
     }
 
+    // Platforms on which each edge's imports can run, over all of its import statements
+    let mut edge_platforms: HashMap<(ModulePath, ModulePath), Platforms> = HashMap::new();
+
     let all_files: HashMap<ModulePath, PathBuf> = sources
         .iter()
         .map(|source| (source.module.clone(), source.path.clone()))
         .collect();
+    let relative = |path: &Path| {
+        path.strip_prefix(project_root)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    };
+    if let Some(trace) = trace.as_deref_mut() {
+        trace.modules = all_files
+            .iter()
+            .map(|(module, path)| (module.to_dotted(), relative(path)))
+            .collect();
+    }
+
+    // Scripts by directory and file stem. A script's directory comes first on `sys.path`
+    // when it runs, so its absolute imports find the scripts next to it before anything else.
+    let siblings: HashMap<(PathBuf, String), ModulePath> = sources
+        .iter()
+        .filter(|source| {
+            matches!(source.kind, SourceKind::Script) && !shadowed.contains(&source.path)
+        })
+        .filter_map(|source| {
+            let dir = source.path.parent()?.to_path_buf();
+            let stem = source.path.file_stem()?.to_str()?.to_string();
+            Some(((dir, stem), source.module.clone()))
+        })
+        .collect();
+    let mut shadowing: Vec<String> = siblings
+        .iter()
+        .filter(|((_, stem), _)| {
+            let internal = ModulePath(vec![stem.clone()]);
+            all_files.contains_key(&internal) || is_package_import(&internal, &all_files)
+        })
+        .map(|((dir, stem), _)| {
+            format!(
+                "Warning: {} shadows the project's {stem} module for the scripts next to it",
+                relative(&dir.join(format!("{stem}.py")))
+            )
+        })
+        .collect();
+    shadowing.sort();
+    for warning in shadowing {
+        eprintln!("{warning}");
+    }
+
+    // `pkg.__init__` for the `__init__.py` of a package with `--split-package-init`
+    let split_init = |source: &SourceFile| {
+        let is_init = source
+            .path
+            .file_stem()
+            .is_some_and(|stem| stem == "__init__");
+        (options.split_package_init && is_init && !matches!(source.kind, SourceKind::Script))
+            .then(|| source.module.package_init())
+    };
 
-    for source_file in &sources {
+    // Parse everything first: resolving `from pkg import name` needs the lazy exports of `pkg`
+    let mut parsed_files: Vec<(&SourceFile, Vec<ImportStatement>)> = Vec::new();
+    let mut lazy_modules: HashMap<ModulePath, HashMap<String, ModulePath>> = HashMap::new();
+    // Configured lifecycle states, followed by those declared in header comments so that
+    // the comments win over configuration for the same module
+    let mut lifecycles = options.lifecycles.clone();
+    let attributes = GitAttributes::load(project_root);
+    for (parsed_count, source_file) in sources.iter().enumerate() {
+        if options
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            let unprocessed: Vec<String> = sources[parsed_count..]
+                .iter()
+                .map(|source| {
+                    let path = source
+                        .path
+                        .strip_prefix(project_root)
+                        .unwrap_or(&source.path);
+                    path.display().to_string()
+                })
+                .collect();
+            eprintln!(
+                "Warning: time budget exhausted after parsing {parsed_count} of {} files; the graph is partial. Unprocessed files:\n  {}",
+                sources.len(),
+                unprocessed.join("\n  ")
+            );
+            break;
+        }
         let SourceFile {
             module: module_path,
             path: file_path,
@@ -402,30 +1794,256 @@ pub fn analyze_project(
             }
         };
 
-        let imports = match extract_imports(&source) {
-            Ok(imports) => imports,
+        if let Some(value) = header_lifecycle(&source) {
+            match Lifecycle::parse(value) {
+                Some(lifecycle) => lifecycles.push((module_path.clone(), lifecycle)),
+                None => eprintln!(
+                    "Warning: {}: unknown lifecycle '{value}' (expected experimental, stable, or sunset)",
+                    file_path.display()
+                ),
+            }
+        }
+
+        let target_version = options.python_version.unwrap_or_else(PythonVersion::latest);
+        let parsed = if matches!(kind, SourceKind::Extension) {
+            // Cython is not Python syntax; the line scan understands `cimport` too
+            Ok(FileImports {
+                imports: scan_imports(&source),
+                unsupported: Vec::new(),
+                lazy_exports: Vec::new(),
+                body_statements: 0,
+                dynamic_imports: Vec::new(),
+            })
+        } else {
+            extract_imports(&source, target_version)
+        };
+        let recovered = parsed.is_err();
+        let (imports, body, dynamic_imports) = match parsed {
+            Ok(FileImports {
+                imports,
+                unsupported,
+                lazy_exports,
+                body_statements,
+                dynamic_imports,
+            }) => {
+                for (line, message) in unsupported {
+                    eprintln!("Warning: {}:{}: {}", file_path.display(), line, message);
+                }
+                let package = if file_path.file_stem().is_some_and(|stem| stem == "__init__") {
+                    &module_path.0[..]
+                } else {
+                    &module_path.0[..module_path.0.len() - 1]
+                };
+                let resolved: HashMap<String, ModulePath> = lazy_exports
+                    .into_iter()
+                    .filter_map(|(attribute, spec)| {
+                        resolve_lazy_export(package, &spec, &all_files)
+                            .map(|module| (attribute, module))
+                    })
+                    .collect();
+                if !resolved.is_empty() {
+                    lazy_modules.insert(module_path.clone(), resolved);
+                }
+                let body = matches!(kind, SourceKind::Internal).then_some(body_statements);
+                let dynamic_imports = dynamic_imports
+                    .into_iter()
+                    .map(|import| DynamicImport {
+                        name: match import.name {
+                            DynamicName::Literal { module } => DynamicName::Literal {
+                                module: absolute_name(package, &module),
+                            },
+                            DynamicName::Computed { prefix } => DynamicName::Computed {
+                                prefix: absolute_name(package, &prefix),
+                            },
+                        },
+                        ..import
+                    })
+                    .collect();
+                (imports, body, dynamic_imports)
+            }
             Err(message) => {
+                let recovered = scan_imports(&source);
                 eprintln!(
-                    "Warning: Skipping unparseable file {}: {}",
+                    "Warning: partial imports for unparseable file {}: {}; recovered {} import(s) by scanning its lines",
                     file_path.display(),
-                    message
+                    message,
+                    recovered.len()
                 );
-                continue;
+                (recovered, None, Vec::new())
             }
         };
 
         graph.ensure_node(module_path.clone());
-        if matches!(kind, SourceKind::Script) {
-            graph.mark_as_script(module_path);
+        // The node standing for the file itself
+        let file_node = if let Some(init) = split_init(source_file) {
+            graph.add_dependency(module_path.clone(), init.clone());
+            init
+        } else {
+            module_path.clone()
+        };
+        let relative_path = file_path.strip_prefix(project_root).unwrap_or(file_path);
+        // For `--link-template`, written the same way on every platform
+        let source_path: Vec<String> = relative_path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        graph.set_source_file(&file_node, &source_path.join("/"));
+        if generated::is_generated(relative_path, &source, &attributes) {
+            graph.mark_as_generated(&file_node);
+        }
+        // Only package bodies are recorded, for `--side-effect-hotspots`
+        if let Some(count) = body
+            && file_path.file_stem().is_some_and(|stem| stem == "__init__")
+        {
+            graph.set_body_statements(&file_node, count);
         }
+        match kind {
+            SourceKind::Script => graph.mark_as_script(&file_node),
+            SourceKind::Extension => graph.mark_as_extension(&file_node),
+            SourceKind::Internal => {}
+        }
+        if let Some(trace) = trace.as_deref_mut() {
+            trace.files.push(FileTrace {
+                file: relative(file_path),
+                module: file_node.to_dotted(),
+                kind: match kind {
+                    SourceKind::Internal => "module",
+                    SourceKind::Extension => "extension",
+                    SourceKind::Script => "script",
+                },
+                recovered,
+                imports: imports
+                    .iter()
+                    .map(|statement| TracedImport {
+                        line: statement.line,
+                        column: statement.column,
+                        statement: statement.import.to_string(),
+                        kind: statement.kind.as_str(),
+                        platforms: (statement.platforms != Platforms::ALL).then(|| {
+                            Platform::ALL
+                                .into_iter()
+                                .filter(|&platform| statement.platforms.contains(platform))
+                                .map(Platform::as_str)
+                                .collect()
+                        }),
+                    })
+                    .collect(),
+                dynamic_imports,
+            });
+        }
+        parsed_files.push((source_file, imports));
+    }
 
-        for import in imports {
+    let mut unresolved: Vec<String> = Vec::new();
+    for (source_file, imports) in parsed_files {
+        let module_path = &source_file.module;
+        let is_package = source_file
+            .path
+            .file_stem()
+            .is_some_and(|stem| stem == "__init__");
+        let file = relative(&source_file.path);
+        // Imports are resolved from the package, but made by its `__init__.py`
+        let importer = split_init(source_file).unwrap_or_else(|| module_path.clone());
+        for ImportStatement {
+            import,
+            kind,
+            platforms,
+            line,
+            column,
+            aliases,
+        } in imports
+        {
+            // Outcomes for `--emit resolution`, built only when tracing
+            let statement = trace.is_some().then(|| import.to_string());
+            let mut record = |name: Option<&String>, outcome: &dyn Fn() -> Outcome| {
+                if let (Some(trace), Some(statement)) = (trace.as_deref_mut(), &statement) {
+                    trace.resolution.push(Resolution {
+                        file: file.clone(),
+                        line,
+                        statement: statement.clone(),
+                        name: name.cloned(),
+                        outcome: outcome(),
+                    });
+                }
+            };
+            if let Some(platform) = options
+                .platform
+                .filter(|&platform| !platforms.contains(platform))
+            {
+                record(None, &|| Outcome::Skipped {
+                    reason: format!("cannot run on {}", platform.as_str()),
+                });
+                continue;
+            }
+            // Name the file's code uses for an imported name the statement renames
+            let alias = |name: &str| {
+                aliases
+                    .iter()
+                    .find(|(imported, _)| imported == name)
+                    .map(|(_, alias)| alias.clone())
+            };
+            let mut add_dependency = |target: ModulePath, alias: Option<String>| {
+                let seen = edge_platforms
+                    .entry((importer.clone(), target.clone()))
+                    .or_insert(Platforms::NONE);
+                *seen = seen.union(platforms);
+                let site = ImportSite {
+                    file: file.clone(),
+                    line,
+                    column,
+                    alias,
+                };
+                graph.record_import_site(&importer, &target, site);
+                graph.add_dependency_with_kind(importer.clone(), target, kind);
+            };
+            let first = match &import {
+                Import::Absolute { module } => module.first(),
+                Import::From {
+                    module: Some(module),
+                    level: 0,
+                    ..
+                } => module.first(),
+                Import::From { .. } => None,
+            };
+            if matches!(source_file.kind, SourceKind::Script)
+                && let (Some(dir), Some(first)) = (source_file.path.parent(), first)
+                && let Some(sibling) = siblings.get(&(dir.to_path_buf(), first.clone()))
+                && sibling != module_path
+            {
+                let sibling = sibling.clone();
+                record(None, &|| Outcome::Resolved {
+                    target: sibling.to_dotted(),
+                    via: "sibling script",
+                });
+                let renamed = match &import {
+                    Import::Absolute { module } => alias(&module.join(".")),
+                    Import::From { .. } => None,
+                };
+                add_dependency(sibling, renamed);
+                continue;
+            }
             match import {
                 Import::Absolute { module } => {
+                    let renamed = alias(&module.join("."));
                     let resolved = ModulePath(module);
-                    if all_files.contains_key(&resolved) || is_package_import(&resolved, &all_files)
-                    {
-                        graph.add_dependency(module_path.clone(), resolved);
+                    let via = if all_files.contains_key(&resolved) {
+                        Some("module")
+                    } else if is_package_import(&resolved, &all_files) {
+                        Some("package")
+                    } else {
+                        None
+                    };
+                    match via {
+                        Some(via) => {
+                            record(None, &|| Outcome::Resolved {
+                                target: resolved.to_dotted(),
+                                via,
+                            });
+                            add_dependency(resolved, renamed);
+                        }
+                        None => record(None, &|| Outcome::External {
+                            module: resolved.to_dotted(),
+                        }),
                     }
                 }
                 Import::From {
@@ -434,28 +2052,103 @@ pub fn analyze_project(
                     level,
                 } => {
                     let module_str = module.as_ref().map(|v| v.join("."));
-                    if let Some(base_path) =
-                        module_path.resolve_relative(level, module_str.as_deref())
-                    {
+                    let base_path =
+                        module_path.resolve_relative_from(is_package, level, module_str.as_deref());
+                    let mut problem = None;
+                    if (options.strict_resolution || statement.is_some()) && level > 0 {
+                        problem = match &base_path {
+                            _ if matches!(source_file.kind, SourceKind::Script) => {
+                                Some("made from a script outside the source root".to_string())
+                            }
+                            _ if level as usize > module_path.package_depth(is_package) => {
+                                Some("reaches above the top-level package".to_string())
+                            }
+                            Some(base)
+                                if !all_files.contains_key(base)
+                                    && !is_package_import(base, &all_files) =>
+                            {
+                                Some(format!("{} is not part of the project", base.to_dotted()))
+                            }
+                            Some(_) => None,
+                            None => Some("names no module".to_string()),
+                        };
+                        if let Some(problem) =
+                            problem.as_ref().filter(|_| options.strict_resolution)
+                        {
+                            unresolved.push(format!(
+                                "{}: from {}{} import {}: {}",
+                                source_file.path.display(),
+                                ".".repeat(level as usize),
+                                module_str.as_deref().unwrap_or(""),
+                                if names.is_empty() {
+                                    "*".to_string()
+                                } else {
+                                    names.join(", ")
+                                },
+                                problem
+                            ));
+                        }
+                    }
+                    // Names that match nothing in the project
+                    let missing = |base: Option<&ModulePath>| {
+                        if level == 0
+                            && let Some(base) = base
+                        {
+                            Outcome::External {
+                                module: base.to_dotted(),
+                            }
+                        } else {
+                            Outcome::Unresolved {
+                                reason: problem
+                                    .clone()
+                                    .unwrap_or_else(|| "names no module".to_string()),
+                            }
+                        }
+                    };
+                    if let Some(base_path) = base_path {
+                        let base_found = all_files.contains_key(&base_path)
+                            || is_package_import(&base_path, &all_files);
                         for name in &names {
                             let mut submodule_path = base_path.0.clone();
                             submodule_path.push(name.clone());
                             let submodule = ModulePath(submodule_path);
 
-                            if all_files.contains_key(&submodule) {
-                                graph.add_dependency(module_path.clone(), submodule);
-                            } else if all_files.contains_key(&base_path)
-                                || is_package_import(&base_path, &all_files)
-                            {
-                                graph.add_dependency(module_path.clone(), base_path.clone());
-                            }
+                            let lazy_export = lazy_modules
+                                .get(&base_path)
+                                .and_then(|exports| exports.get(name));
+                            let (target, via) = if all_files.contains_key(&submodule) {
+                                (submodule, "submodule")
+                            } else if let Some(target) = lazy_export {
+                                (target.clone(), "lazy export")
+                            } else if base_found {
+                                (base_path.clone(), "module")
+                            } else {
+                                record(Some(name), &|| missing(Some(&base_path)));
+                                continue;
+                            };
+                            record(Some(name), &|| Outcome::Resolved {
+                                target: target.to_dotted(),
+                                via,
+                            });
+                            add_dependency(target, alias(name));
                         }
 
-                        if names.is_empty()
-                            && (all_files.contains_key(&base_path)
-                                || is_package_import(&base_path, &all_files))
-                        {
-                            graph.add_dependency(module_path.clone(), base_path);
+                        if names.is_empty() {
+                            if base_found {
+                                record(None, &|| Outcome::Resolved {
+                                    target: base_path.to_dotted(),
+                                    via: "module",
+                                });
+                                add_dependency(base_path, None);
+                            } else {
+                                record(None, &|| missing(Some(&base_path)));
+                            }
+                        }
+                    } else if names.is_empty() {
+                        record(None, &|| missing(None));
+                    } else {
+                        for name in &names {
+                            record(Some(name), &|| missing(None));
                         }
                     }
                 }
@@ -463,60 +2156,160 @@ pub fn analyze_project(
         }
     }
 
+    if !unresolved.is_empty() {
+        return Err(PythonAnalysisError::UnresolvedRelativeImports(unresolved));
+    }
+
+    for (module, name) in &library_modules {
+        graph.set_category(module, name);
+    }
+    if !lifecycles.is_empty() {
+        let modules: Vec<ModulePath> = graph.nodes().cloned().collect();
+        for module in modules {
+            let declared = lifecycles
+                .iter()
+                .filter(|(prefix, _)| module.0.starts_with(&prefix.0))
+                .max_by_key(|(prefix, _)| prefix.0.len());
+            if let Some((_, lifecycle)) = declared {
+                graph.set_lifecycle(&module, *lifecycle);
+            }
+        }
+    }
+    for ((from, to), platforms) in &edge_platforms {
+        if *platforms != Platforms::ALL {
+            for tag in platforms.tags() {
+                graph.tag_edge(from, to, &tag);
+            }
+        }
+    }
+
+    // A package whose `__init__.py` is ignored can still be reached through its submodules
+    if !ignored.is_empty() {
+        graph = graph.filter_nodes(|module| !ignored.contains(module));
+    }
+
     Ok(graph)
 }
 
+/// Lay out scripts by directory. With `cluster`, every script outside the project root is
+/// drawn inside its directory's cluster, even when alone there. `categories` maps
+/// directories (relative to the project root, `/`-separated) to a category given to the
+/// scripts below them; the most specific directory wins.
+pub fn group_scripts(graph: &mut PythonGraph, cluster: bool, categories: &[(String, String)]) {
+    let categories: Vec<(Vec<&str>, &str)> = categories
+        .iter()
+        .map(|(directory, category)| {
+            let segments = directory
+                .split('/')
+                .filter(|segment| !segment.is_empty() && *segment != ".")
+                .collect();
+            (segments, category.as_str())
+        })
+        .collect();
+
+    let scripts: Vec<ModulePath> = graph
+        .nodes()
+        .filter(|module| graph.is_script(module))
+        .cloned()
+        .collect();
+    for script in scripts {
+        let directory = &script.0[..script.0.len() - 1];
+        if cluster && !directory.is_empty() {
+            graph.cluster_by_directory(&script);
+        }
+        let category = categories
+            .iter()
+            .filter(|(prefix, _)| {
+                prefix.len() <= directory.len()
+                    && prefix.iter().zip(directory).all(|(a, b)| *a == b)
+            })
+            .max_by_key(|(prefix, _)| prefix.len());
+        if let Some((_, category)) = category {
+            graph.set_category(&script, category);
+        }
+    }
+}
+
 fn is_package_import(module: &ModulePath, modules: &HashMap<ModulePath, PathBuf>) -> bool {
     modules
         .keys()
         .any(|m| m.0.len() > module.0.len() && m.0.starts_with(&module.0))
 }
 
-fn should_exclude_path(path: &Path, project_root: &Path, exclude_patterns: &[String]) -> bool {
-    let relative_path = match path.strip_prefix(project_root) {
-        Ok(rel) => rel,
-        Err(_) => return true,
-    };
-
-    let path_str = relative_path.to_string_lossy();
-
-    let default_excludes = [
-        "venv",
-        ".venv",
-        "__pycache__",
-        ".git",
-        ".pytest_cache",
-        ".egg-info",
-        "build",
-        "dist",
-        ".tox",
-        ".mypy_cache",
-        "node_modules",
-        ".egg",
-        "eggs",
-    ];
+/// Directory and file names never walked, besides anything starting with `venv` or ending
+/// with `.egg-info`
+pub const DEFAULT_EXCLUDES: [&str; 13] = [
+    "venv",
+    ".venv",
+    "__pycache__",
+    ".git",
+    ".pytest_cache",
+    ".egg-info",
+    "build",
+    "dist",
+    ".tox",
+    ".mypy_cache",
+    "node_modules",
+    ".egg",
+    "eggs",
+];
+
+/// Why a path is left out of the walk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Exclusion {
+    /// A path component is one of the [`DEFAULT_EXCLUDES`]
+    Default(String),
+    /// The path matches an `--exclude-scripts` pattern
+    Pattern(String),
+}
 
+/// Why `relative_path` (relative to the walked root) is excluded, if it is
+pub fn exclusion_reason(
+    relative_path: &Path,
+    is_dir: bool,
+    exclude_patterns: &[String],
+) -> Option<Exclusion> {
     for component in relative_path.components() {
-        if let Some(component_str) = component.as_os_str().to_str() {
-            for pattern in &default_excludes {
-                if component_str == *pattern
-                    || (pattern.ends_with('*')
-                        && component_str.starts_with(pattern.trim_end_matches('*')))
-                    || component_str.starts_with("venv")
-                    || component_str.ends_with(".egg-info")
-                {
-                    return true;
-                }
-            }
+        if let Some(component_str) = component.as_os_str().to_str()
+            && (DEFAULT_EXCLUDES.contains(&component_str)
+                || component_str.starts_with("venv")
+                || component_str.ends_with(".egg-info"))
+        {
+            return Some(Exclusion::Default(component_str.to_string()));
         }
     }
 
+    // The walk root itself is never excluded
+    if relative_path.as_os_str().is_empty() {
+        return None;
+    }
+    let path_str = relative_path.to_string_lossy();
+    // A directory is pruned when a pattern matches every path below it, so that e.g.
+    // `tools/*` skips `tools/` instead of walking it to exclude each file. `*suffix`
+    // patterns never cover a directory: its files end with their own names.
+    let dir_str = is_dir.then(|| format!("{path_str}/"));
+    let covers_directory = |pattern: &str| {
+        dir_str.as_ref().is_some_and(|dir| {
+            (!pattern.starts_with('*') || pattern.ends_with('*'))
+                && filters::matches_pattern(dir, pattern)
+        })
+    };
     exclude_patterns
         .iter()
-        .any(|pattern| filters::matches_pattern(&path_str, pattern))
+        .find(|pattern| filters::matches_pattern(&path_str, pattern) || covers_directory(pattern))
+        .map(|pattern| Exclusion::Pattern(pattern.clone()))
 }
 
-fn parse_pyproject_toml(project_root: &Path) -> Result<Option<PathBuf>, PythonAnalysisError> {
+fn should_exclude_entry(entry: &DirEntry, walk_root: &Path, exclude_patterns: &[String]) -> bool {
+    match entry.path().strip_prefix(walk_root) {
+        Ok(relative_path) => {
+            exclusion_reason(relative_path, entry.file_type().is_dir(), exclude_patterns).is_some()
+        }
+        Err(_) => true,
+    }
+}
+
+fn read_pyproject_toml(project_root: &Path) -> Result<Option<toml::Value>, PythonAnalysisError> {
     let toml_path = project_root.join("pyproject.toml");
 
     if !toml_path.exists() {
@@ -526,22 +2319,288 @@ fn parse_pyproject_toml(project_root: &Path) -> Result<Option<PathBuf>, PythonAn
     let content = std::fs::read_to_string(&toml_path)
         .map_err(|e| PythonAnalysisError::ConfigReadError(toml_path.clone(), e))?;
 
-    let config: toml::Value = content
+    content
         .parse()
-        .map_err(|e| PythonAnalysisError::ConfigParseError(toml_path.clone(), e))?;
+        .map(Some)
+        .map_err(|e| PythonAnalysisError::ConfigParseError(toml_path, e))
+}
 
-    let source_root = config
-        .get("tool")
+fn parse_pyproject_toml(project_root: &Path) -> Result<Option<PathBuf>, PythonAnalysisError> {
+    let Some(config) = read_pyproject_toml(project_root)? else {
+        return Ok(None);
+    };
+
+    let tool = config.get("tool");
+    let setuptools_root = tool
         .and_then(|t| t.get("setuptools"))
         .and_then(|s| s.get("packages"))
         .and_then(|p| p.get("find"))
         .and_then(|f| f.get("where"))
         .and_then(|w| w.as_array())
         .and_then(|a| a.first())
+        .and_then(|v| v.as_str());
+    // Poetry: `packages = [{ include = "app", from = "src" }]`; without `from` the
+    // packages sit in the project root
+    let poetry_root = tool
+        .and_then(|t| t.get("poetry"))
+        .and_then(|p| p.get("packages"))
+        .and_then(|p| p.as_array())
+        .and_then(|a| a.first())
+        .map(|package| package.get("from").and_then(|f| f.as_str()).unwrap_or("."));
+
+    // Hatch: `[tool.hatch.build.targets.wheel] packages = ["src/app"]` lists package
+    // directories, whose parent is the source root
+    let hatch_root = tool
+        .and_then(|t| t.get("hatch"))
+        .and_then(|h| h.get("build"))
+        .and_then(|b| b.get("targets"))
+        .and_then(|t| t.get("wheel"))
+        .and_then(|w| w.get("packages"))
+        .and_then(|p| p.as_array())
+        .and_then(|a| a.first())
         .and_then(|v| v.as_str())
-        .map(|s| project_root.join(s));
+        .map(
+            |package| match package.trim_end_matches('/').rsplit_once('/') {
+                Some((parent, _)) => parent,
+                None => ".",
+            },
+        );
+    // PDM: `[tool.pdm.build] package-dir = "src"` (older releases: `[tool.pdm]`)
+    let pdm_root = tool.and_then(|t| t.get("pdm")).and_then(|pdm| {
+        pdm.get("build")
+            .and_then(|b| b.get("package-dir"))
+            .or_else(|| pdm.get("package-dir"))
+            .and_then(|d| d.as_str())
+    });
+    // Flit: `[tool.flit.module] name = "app"` is looked up in the project root, then in src/
+    let flit_root = tool
+        .and_then(|t| t.get("flit"))
+        .and_then(|f| f.get("module"))
+        .and_then(|m| m.get("name"))
+        .and_then(|n| n.as_str())
+        .map(|module| {
+            let in_src = project_root.join("src");
+            if in_src.join(module).is_dir() || in_src.join(format!("{module}.py")).is_file() {
+                "src"
+            } else {
+                "."
+            }
+        });
+
+    Ok(setuptools_root
+        .or(poetry_root)
+        .or(hatch_root)
+        .or(pdm_root)
+        .or(flit_root)
+        .map(|s| project_root.join(s)))
+}
+
+/// Local libraries the project depends on by path, as `(name, directory)`:
+///
+/// - PEP 508 requirements `name @ file://...` in `[project]` dependencies and optional
+///   dependencies (relative paths and `${PROJECT_ROOT}` are resolved against the project)
+/// - `[tool.uv.sources]` entries with a `path`
+/// - Poetry dependencies (including dependency groups) with a `path`
+fn path_dependencies(project_root: &Path) -> Result<Vec<(String, PathBuf)>, PythonAnalysisError> {
+    let Some(config) = read_pyproject_toml(project_root)? else {
+        return Ok(Vec::new());
+    };
+    let mut dependencies = Vec::new();
+
+    let project = config.get("project");
+    let requirements = project
+        .and_then(|p| p.get("dependencies"))
+        .and_then(|d| d.as_array())
+        .into_iter()
+        .flatten()
+        .chain(
+            project
+                .and_then(|p| p.get("optional-dependencies"))
+                .and_then(|o| o.as_table())
+                .into_iter()
+                .flat_map(|groups| groups.values())
+                .filter_map(|group| group.as_array())
+                .flatten(),
+        )
+        .filter_map(|requirement| requirement.as_str());
+    for requirement in requirements {
+        let requirement = requirement.split(';').next().unwrap_or_default();
+        if let Some((name, url)) = requirement.split_once('@')
+            && let Some(path) = url.trim().strip_prefix("file:")
+        {
+            let name = name.split('[').next().unwrap_or_default().trim();
+            let path = path.trim_start_matches("//");
+            let path = path.replace("${PROJECT_ROOT}", ".");
+            dependencies.push((name.to_string(), project_root.join(path)));
+        }
+    }
+
+    let tool = config.get("tool");
+    let uv_sources = tool
+        .and_then(|t| t.get("uv"))
+        .and_then(|u| u.get("sources"))
+        .and_then(|s| s.as_table());
+    let poetry = tool.and_then(|t| t.get("poetry"));
+    let poetry_groups = poetry
+        .and_then(|p| p.get("group"))
+        .and_then(|g| g.as_table())
+        .into_iter()
+        .flat_map(|groups| groups.values())
+        .filter_map(|group| group.get("dependencies"));
+    let tables = uv_sources.into_iter().chain(
+        poetry
+            .and_then(|p| p.get("dependencies"))
+            .into_iter()
+            .chain(poetry_groups)
+            .filter_map(|d| d.as_table()),
+    );
+    for table in tables {
+        for (name, source) in table {
+            if let Some(path) = source.get("path").and_then(|p| p.as_str()) {
+                dependencies.push((name.clone(), project_root.join(path)));
+            }
+        }
+    }
+
+    Ok(dependencies)
+}
+
+/// Modules providing the scripts and plugins declared in pyproject.toml, i.e. the `MODULE`
+/// of each `MODULE:ATTRIBUTE` reference in `[project.scripts]`, `[project.gui-scripts]`,
+/// `[project.entry-points.*]`, `[tool.poetry.scripts]`, and `[tool.poetry.plugins.*]`
+pub fn declared_entry_points(project_root: &Path) -> Result<Vec<ModulePath>, PythonAnalysisError> {
+    let Some(config) = read_pyproject_toml(project_root)? else {
+        return Ok(Vec::new());
+    };
+    let project = config.get("project");
+    let poetry = config.get("tool").and_then(|t| t.get("poetry"));
+    // Tables of entry point groups, e.g. `[project.entry-points."app.plugins"]`
+    fn groups(table: Option<&toml::Value>) -> impl Iterator<Item = &toml::Value> {
+        table
+            .and_then(|t| t.as_table())
+            .into_iter()
+            .flat_map(|groups| groups.values())
+    }
+    let tables = ["scripts", "gui-scripts"]
+        .into_iter()
+        .filter_map(|key| project.and_then(|p| p.get(key)))
+        .chain(groups(project.and_then(|p| p.get("entry-points"))))
+        .chain(poetry.and_then(|p| p.get("scripts")))
+        .chain(groups(poetry.and_then(|p| p.get("plugins"))))
+        .filter_map(|table| table.as_table());
+
+    let mut modules = Vec::new();
+    for table in tables {
+        for reference in table.values() {
+            // Poetry also takes `{ reference = "...", type = "console" }` or `{ callable = "..." }`
+            let reference = reference
+                .as_str()
+                .or_else(|| reference.get("reference").and_then(|r| r.as_str()))
+                .or_else(|| reference.get("callable").and_then(|c| c.as_str()));
+            if let Some(reference) = reference {
+                let module = reference
+                    .split([':', '['])
+                    .next()
+                    .unwrap_or_default()
+                    .trim();
+                if !module.is_empty() {
+                    modules.push(ModulePath(module.split('.').map(String::from).collect()));
+                }
+            }
+        }
+    }
+    Ok(modules)
+}
+
+/// Root entry of `package_dir` in the `[options]` section of setup.cfg:
+///
+/// ```ini
+/// [options]
+/// package_dir =
+///     = src
+/// ```
+fn parse_setup_cfg(project_root: &Path) -> Result<Option<PathBuf>, PythonAnalysisError> {
+    let cfg_path = project_root.join("setup.cfg");
+    if !cfg_path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(&cfg_path)
+        .map_err(|e| PythonAnalysisError::ConfigReadError(cfg_path.clone(), e))?;
+
+    let mut section = "";
+    let mut entries: Vec<&str> = Vec::new();
+    let mut in_package_dir = false;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = trimmed.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+            section = name.trim();
+            in_package_dir = false;
+            continue;
+        }
+        if in_package_dir && line.starts_with(char::is_whitespace) {
+            entries.push(trimmed);
+            continue;
+        }
+        in_package_dir = false;
+        if section == "options"
+            && let Some((key, value)) = trimmed.split_once('=')
+            && key.trim() == "package_dir"
+        {
+            in_package_dir = true;
+            if !value.trim().is_empty() {
+                entries.push(value.trim());
+            }
+        }
+    }
+
+    Ok(entries
+        .iter()
+        .filter_map(|entry| entry.split_once('='))
+        .find(|(package, _)| package.trim().is_empty())
+        .map(|(_, dir)| project_root.join(dir.trim())))
+}
 
-    Ok(source_root)
+/// Root entry of a literal `package_dir={"": "src"}` argument in setup.py. The file is
+/// scanned, not executed, so computed values are not seen.
+fn parse_setup_py(project_root: &Path) -> Result<Option<PathBuf>, PythonAnalysisError> {
+    let setup_path = project_root.join("setup.py");
+    if !setup_path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(&setup_path)
+        .map_err(|e| PythonAnalysisError::ConfigReadError(setup_path.clone(), e))?;
+    Ok(setup_py_package_dir(&content).map(|dir| project_root.join(dir)))
+}
+
+fn setup_py_package_dir(source: &str) -> Option<String> {
+    let (_, rest) = source.split_once("package_dir")?;
+    let dict = rest
+        .trim_start()
+        .strip_prefix('=')?
+        .trim_start()
+        .strip_prefix('{')?;
+    let (dict, _) = dict.split_once('}')?;
+
+    // The dict's string literals, alternating between package names and directories
+    let mut strings = Vec::new();
+    let mut chars = dict.chars();
+    while let Some(c) = chars.next() {
+        if c == '"' || c == '\'' {
+            strings.push(
+                chars
+                    .by_ref()
+                    .take_while(|&next| next != c)
+                    .collect::<String>(),
+            );
+        }
+    }
+    strings
+        .chunks(2)
+        .find(|pair| pair.len() == 2 && pair[0].is_empty())
+        .map(|pair| pair[1].clone())
 }
 
 fn has_python_packages(path: &Path) -> bool {
@@ -560,27 +2619,240 @@ fn has_python_packages(path: &Path) -> bool {
         })
 }
 
-pub fn detect_source_root(project_root: &Path) -> Result<PathBuf, PythonAnalysisError> {
-    if let Some(root) = parse_pyproject_toml(project_root)? {
-        if root.is_dir() && has_python_packages(&root) {
-            return Ok(root);
+/// How a source root was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceLayout {
+    /// Given with `--source-root`
+    Explicit,
+    /// Declared in packaging metadata (`pyproject.toml`, `setup.cfg`, or `setup.py`)
+    Declared(&'static str),
+    /// `src/` directory
+    Src,
+    /// `lib/python/` directory
+    LibPython,
+    /// Packages directly in the project root
+    Flat,
+}
+
+impl SourceLayout {
+    pub fn describe(self) -> String {
+        match self {
+            SourceLayout::Explicit => "set with --source-root".to_string(),
+            SourceLayout::Declared(file) => format!("declared in {file}"),
+            SourceLayout::Src => "src layout".to_string(),
+            SourceLayout::LibPython => "lib/python layout".to_string(),
+            SourceLayout::Flat => "flat layout".to_string(),
+        }
+    }
+}
+
+/// Reads the source root declared in one packaging file
+type RootParser = fn(&Path) -> Result<Option<PathBuf>, PythonAnalysisError>;
+
+/// Outcome of source root detection
+#[derive(Debug, Clone)]
+pub(crate) struct SourceRootSearch {
+    /// The source root with the layout it was found by; `None` if nothing qualifies
+    pub found: Option<(PathBuf, SourceLayout)>,
+    /// Declared or existing candidates checked before it that have no Python packages
+    pub rejected: Vec<(PathBuf, SourceLayout)>,
+    /// Every layout with packages, with its number of modules, when several have some and
+    /// no packaging file declares the root; `found` is then only the first of them
+    pub ambiguous: Vec<(PathBuf, SourceLayout, usize)>,
+}
+
+/// Try the declared source roots, then the `src/` and `lib/python/` layouts, then the
+/// project root itself
+pub(crate) fn find_source_root(
+    project_root: &Path,
+) -> Result<SourceRootSearch, PythonAnalysisError> {
+    let mut rejected = Vec::new();
+    let found = |root: PathBuf, layout: SourceLayout, rejected: Vec<_>| {
+        Ok(SourceRootSearch {
+            found: Some((root, layout)),
+            rejected,
+            ambiguous: Vec::new(),
+        })
+    };
+    let declared: [(RootParser, &'static str); 3] = [
+        (parse_pyproject_toml, "pyproject.toml"),
+        (parse_setup_cfg, "setup.cfg"),
+        (parse_setup_py, "setup.py"),
+    ];
+    for (parse, file) in declared {
+        if let Some(root) = parse(project_root)? {
+            if root.is_dir() && has_python_packages(&root) {
+                return found(root, SourceLayout::Declared(file), rejected);
+            }
+            rejected.push((root, SourceLayout::Declared(file)));
         }
     }
 
-    for candidate in ["src", "lib/python"] {
+    let mut candidates = Vec::new();
+    for (candidate, layout) in [
+        ("src", SourceLayout::Src),
+        ("lib/python", SourceLayout::LibPython),
+    ] {
         let path = project_root.join(candidate);
-        if path.is_dir() && has_python_packages(&path) {
-            return Ok(path);
+        if path.is_dir() {
+            if has_python_packages(&path) {
+                candidates.push((path, layout));
+            } else {
+                rejected.push((path, layout));
+            }
         }
     }
+    // Next to `src/`, packages in the project root (other than tests) are a second guess
+    let flat_packages = if candidates.is_empty() {
+        Vec::new()
+    } else {
+        top_level_packages(project_root)
+    };
+    if !flat_packages.is_empty() {
+        candidates.push((project_root.to_path_buf(), SourceLayout::Flat));
+    }
 
-    if has_python_packages(project_root) {
-        return Ok(project_root.to_path_buf());
+    match candidates.len() {
+        0 if has_python_packages(project_root) => {
+            found(project_root.to_path_buf(), SourceLayout::Flat, rejected)
+        }
+        0 => Ok(SourceRootSearch {
+            found: None,
+            rejected,
+            ambiguous: Vec::new(),
+        }),
+        1 => {
+            let (root, layout) = candidates.remove(0);
+            found(root, layout, rejected)
+        }
+        _ => {
+            let ambiguous = candidates
+                .iter()
+                .map(|(root, layout)| {
+                    let modules = if *layout == SourceLayout::Flat {
+                        flat_packages
+                            .iter()
+                            .map(|package| count_modules(package))
+                            .sum()
+                    } else {
+                        count_modules(root)
+                    };
+                    (root.clone(), *layout, modules)
+                })
+                .collect();
+            Ok(SourceRootSearch {
+                found: candidates.into_iter().next(),
+                rejected,
+                ambiguous,
+            })
+        }
     }
+}
 
-    Err(PythonAnalysisError::NoSourceRootFound(
-        project_root.to_path_buf(),
-    ))
+/// Directories directly in the project root holding a regular package, leaving out test
+/// packages and the directories never walked
+fn top_level_packages(project_root: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(project_root) else {
+        return Vec::new();
+    };
+    let mut packages: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.join("__init__.py").is_file())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| {
+                    !matches!(name, "tests" | "test" | "src" | "lib")
+                        && exclusion_reason(Path::new(name), true, &[]).is_none()
+                })
+        })
+        .collect();
+    packages.sort();
+    packages
+}
+
+/// Python and Cython files below `path`, skipping the directories never walked
+fn count_modules(path: &Path) -> usize {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_entry(|e| !should_exclude_entry(e, path, &[]))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "py") || is_cython_file(e.path()))
+        .count()
+}
+
+/// Value of `--source-root` analyzing every plausible source root
+pub const ALL_SOURCE_ROOTS: &str = "auto-all";
+
+/// Source root for an analysis of `project_root`: `explicit` unless it is
+/// [`ALL_SOURCE_ROOTS`], or the detected one. With [`ALL_SOURCE_ROOTS`] the other
+/// plausible roots come back too, as `AnalysisOptions::module_map` entries (relative to the
+/// project root) mapping them to top-level modules.
+pub fn resolve_source_roots(
+    project_root: &Path,
+    explicit: Option<&Path>,
+) -> Result<(PathBuf, Vec<(PathBuf, ModulePath)>), PythonAnalysisError> {
+    match explicit {
+        Some(root) if root != Path::new(ALL_SOURCE_ROOTS) => Ok((root.to_path_buf(), Vec::new())),
+        Some(_) => {
+            let search = find_source_root(project_root)?;
+            if search.ambiguous.is_empty() {
+                return match search.found {
+                    Some((root, _)) => Ok((root, Vec::new())),
+                    None => Err(PythonAnalysisError::NoSourceRootFound(
+                        project_root.to_path_buf(),
+                    )),
+                };
+            }
+            // The project root contains the others, so it is walked as the source root
+            let mut roots: Vec<PathBuf> = search
+                .ambiguous
+                .into_iter()
+                .map(|(root, _, _)| root)
+                .collect();
+            roots.sort_by_key(|root| root.components().count());
+            let main = roots.remove(0);
+            let extra = roots
+                .into_iter()
+                .map(|root| {
+                    let relative = root.strip_prefix(project_root).unwrap_or(&root);
+                    (relative.to_path_buf(), ModulePath(Vec::new()))
+                })
+                .collect();
+            Ok((main, extra))
+        }
+        None => Ok((detect_source_root(project_root)?, Vec::new())),
+    }
+}
+
+pub fn detect_source_root(project_root: &Path) -> Result<PathBuf, PythonAnalysisError> {
+    let search = find_source_root(project_root)?;
+    if !search.ambiguous.is_empty() {
+        let candidates = search
+            .ambiguous
+            .iter()
+            .map(|(root, _, modules)| {
+                let relative = root.strip_prefix(project_root).unwrap_or(root);
+                let name = if relative.as_os_str().is_empty() {
+                    ".".to_string()
+                } else {
+                    relative.display().to_string()
+                };
+                format!("{name} ({modules} modules)")
+            })
+            .collect();
+        return Err(PythonAnalysisError::AmbiguousSourceRoot(
+            project_root.to_path_buf(),
+            candidates,
+        ));
+    }
+    match search.found {
+        Some((root, _)) => Ok(root),
+        None => Err(PythonAnalysisError::NoSourceRootFound(
+            project_root.to_path_buf(),
+        )),
+    }
 }
 
 #[cfg(test)]
@@ -593,6 +2865,131 @@ mod tests {
         assert_eq!(mp.to_dotted(), "pkg_a.module_a");
     }
 
+    #[test]
+    fn test_setup_py_package_dir() {
+        let source = "from setuptools import setup\n\nsetup(\n    name='legacy',\n    package_dir={'': 'lib'},\n)\n";
+        assert_eq!(setup_py_package_dir(source).as_deref(), Some("lib"));
+        let source = r#"setup(package_dir = {"legacy.vendored": "third_party", "": "python/src"})"#;
+        assert_eq!(setup_py_package_dir(source).as_deref(), Some("python/src"));
+        assert_eq!(setup_py_package_dir("setup(package_dir=DIRS)"), None);
+        assert_eq!(setup_py_package_dir("setup(packages=['a'])"), None);
+    }
+
+    #[test]
+    fn test_header_lifecycle() {
+        let source = "#!/usr/bin/env python\n# deptree: lifecycle=sunset\n\nimport a\n";
+        assert_eq!(header_lifecycle(source), Some("sunset"));
+        // Only comments before the first line of code count
+        let source = "\"\"\"Docs\"\"\"\n# deptree: lifecycle=sunset\n";
+        assert_eq!(header_lifecycle(source), None);
+        assert_eq!(
+            header_lifecycle("import a  # deptree: lifecycle=stable\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_lazy_exports() {
+        let source = r#"import importlib
+
+_LAZY = {"Client": ".client", "connect": "sdk.net:connect"}
+_submodules = {"csv_export": ["CsvExporter"], "pdf_export": ("render_pdf",)}
+_by_attr = {a: m for m, attrs in _submodules.items() for a in attrs}
+_UNUSED = {"Other": ".other"}
+
+def __getattr__(name):
+    if name in _LAZY:
+        return importlib.import_module(_LAZY[name], __name__)
+    return importlib.import_module("." + _by_attr[name], __name__)
+"#;
+        let parsed = parse(source, ParseOptions::from(Mode::Module))
+            .unwrap()
+            .try_into_module()
+            .unwrap();
+        let exports = lazy_exports(parsed.suite(), source);
+        let pairs: Vec<(&str, &str)> = exports
+            .iter()
+            .map(|(attribute, module)| (attribute.as_str(), module.as_str()))
+            .collect();
+        assert_eq!(
+            pairs,
+            [
+                ("Client", ".client"),
+                ("connect", "sdk.net:connect"),
+                ("CsvExporter", "csv_export"),
+                ("render_pdf", "pdf_export"),
+            ]
+        );
+
+        // Without `__getattr__` the dicts are ordinary data
+        let source = "_LAZY = {\"Client\": \".client\"}\n";
+        let parsed = parse(source, ParseOptions::from(Mode::Module))
+            .unwrap()
+            .try_into_module()
+            .unwrap();
+        assert!(lazy_exports(parsed.suite(), source).is_empty());
+    }
+
+    #[test]
+    fn test_scan_imports_recovers_from_syntax_errors() {
+        let source = r#""""Docstring mentioning
+import not_an_import
+"""
+import a.b as ab, c
+from .pkg import (
+    d,  # comment
+    e as f,
+)
+from g import h  # deptree: ignore
+if True:
+    from i import j
+
+async def load():
+    import k; import l
+
+def broken(:
+    return from_nowhere
+"#;
+        assert!(extract_imports(source, PythonVersion::latest()).is_err());
+        let imports: Vec<String> = scan_imports(source)
+            .iter()
+            .map(|statement| {
+                let import = match &statement.import {
+                    Import::Absolute { module } => module.join("."),
+                    Import::From {
+                        module,
+                        names,
+                        level,
+                    } => format!(
+                        "{}{} -> {}",
+                        ".".repeat(*level as usize),
+                        module.as_ref().map(|m| m.join(".")).unwrap_or_default(),
+                        names.join(",")
+                    ),
+                };
+                format!("{import} ({})", statement.kind.as_str())
+            })
+            .collect();
+        assert_eq!(
+            imports,
+            [
+                "a.b (import_time)",
+                "c (import_time)",
+                ".pkg -> d,e (import_time)",
+                "i -> j (import_time)",
+                "k (deferred)",
+                "l (deferred)",
+            ]
+        );
+        let aliases: Vec<Aliases> = scan_imports(source)
+            .into_iter()
+            .map(|statement| statement.aliases)
+            .filter(|aliases| !aliases.is_empty())
+            .collect();
+        let pair = |name: &str, alias: &str| (name.to_string(), alias.to_string());
+        assert_eq!(aliases, [vec![pair("a.b", "ab")], vec![pair("e", "f")]]);
+    }
+
     #[test]
     fn test_resolve_relative_level_1() {
         let mp = ModulePath(vec!["pkg_a".to_string(), "module_a".to_string()]);
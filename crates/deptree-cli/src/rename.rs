@@ -0,0 +1,425 @@
+//! Module rename impact (`rename-check` subcommand)
+//!
+//! Renaming a module (or a package, with everything nested below it) means moving its
+//! files and updating every statement naming it: absolute imports, `from parent import
+//! leaf` imports, relative imports crossing the renamed package's boundary, and string
+//! literals passed to `importlib.import_module`. The check collects those lines from the
+//! analysis trace, grouped by the package of the file they are in, and can print them as
+//! a shell script of `sed` edits for the statements that can be rewritten mechanically.
+
+use crate::emit::{AnalysisTrace, DynamicName, Outcome};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::path::Path;
+use thiserror::Error;
+
+/// Errors that can occur while checking a rename
+#[derive(Error, Debug)]
+pub enum RenameError {
+    #[error("Module '{0}' does not match any analyzed module")]
+    UnknownModule(String),
+
+    #[error("'{0}' is not a dotted module name")]
+    InvalidName(String),
+
+    #[error("Module '{0}' already exists")]
+    AlreadyExists(String),
+}
+
+/// Why a line has to change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EditKind {
+    /// An absolute import naming the module (`import app.db`, `from app.db import x`)
+    Import,
+    /// `from parent import leaf`, where the module's name is split over the statement
+    FromParent,
+    /// A relative import with one side inside the renamed module and the other outside
+    Relative,
+    /// A string literal naming the module in a dynamic import
+    Dynamic,
+}
+
+/// A line to update
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Edit {
+    /// Path relative to the project root
+    pub file: String,
+    pub line: usize,
+    pub kind: EditKind,
+    /// The import statement (normalized), or the name passed to a dynamic import
+    pub text: String,
+}
+
+impl Edit {
+    fn describe(&self) -> String {
+        match self.kind {
+            EditKind::Dynamic => format!("dynamic import of \"{}\"", self.text),
+            _ => self.text.clone(),
+        }
+    }
+}
+
+/// What renaming one module entails
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameCheck {
+    pub old: String,
+    pub new: String,
+    /// Files of the module and the modules below it, with the path each moves to
+    pub moves: Vec<(String, String)>,
+    /// Lines to update by the package of the file they are in ("" for top-level files)
+    pub edits: BTreeMap<String, Vec<Edit>>,
+    /// Dynamic imports of computed names that may name the module, as `(file, line,
+    /// constant prefix)`
+    pub computed: Vec<(String, usize, String)>,
+}
+
+impl RenameCheck {
+    pub fn files(&self) -> usize {
+        self.edits
+            .values()
+            .flatten()
+            .map(|edit| edit.file.as_str())
+            .collect::<BTreeSet<_>>()
+            .len()
+    }
+
+    pub fn lines(&self) -> usize {
+        self.edits.values().map(Vec::len).sum()
+    }
+}
+
+/// Whether `module` is `package` or nested below it (both dotted)
+fn is_within(module: &str, package: &str) -> bool {
+    module == package
+        || module
+            .strip_prefix(package)
+            .is_some_and(|rest| rest.starts_with('.'))
+}
+
+fn parent(module: &str) -> &str {
+    module.rsplit_once('.').map_or("", |(parent, _)| parent)
+}
+
+fn is_dotted_name(name: &str) -> bool {
+    name.split('.').all(|part| {
+        part.chars().next().is_some_and(|c| !c.is_ascii_digit())
+            && part.chars().all(|c| c.is_alphanumeric() || c == '_')
+    })
+}
+
+/// Path of `file` (the file of `module`) once `old` is renamed to `new`: the directories
+/// standing for the module's packages are replaced, the rest of the path is kept
+fn moved_path(file: &str, module: &str, old: &str, new: &str) -> String {
+    let components: Vec<&str> = file.split('/').collect();
+    let depth = module.split('.').count();
+    let is_init = components
+        .last()
+        .is_some_and(|name| name.starts_with("__init__."));
+    // Components naming the module: its directories, and the file unless it is `__init__`
+    let end = components.len() - usize::from(is_init);
+    let start = end.saturating_sub(depth);
+    let suffix = components[end - 1]
+        .split_once('.')
+        .map_or("", |(_, extension)| extension);
+    let renamed = format!("{new}{}", &module[old.len()..]);
+    let mut path: Vec<String> = components[..start].iter().map(|c| c.to_string()).collect();
+    path.extend(renamed.split('.').map(String::from));
+    if is_init {
+        path.push(components[end].to_string());
+    } else if let Some(last) = path.last_mut() {
+        *last = format!("{last}.{suffix}");
+    }
+    path.join("/")
+}
+
+/// Collect what renaming `old` to `new` (dotted names) touches, from the trace of an
+/// analysis of the whole project
+pub fn rename_check(
+    trace: &AnalysisTrace,
+    old: &str,
+    new: &str,
+) -> Result<RenameCheck, RenameError> {
+    if !is_dotted_name(new) {
+        return Err(RenameError::InvalidName(new.to_string()));
+    }
+    if !trace.modules.keys().any(|module| is_within(module, old)) {
+        return Err(RenameError::UnknownModule(old.to_string()));
+    }
+    if trace.modules.keys().any(|module| is_within(module, new)) {
+        return Err(RenameError::AlreadyExists(new.to_string()));
+    }
+
+    let moves = trace
+        .modules
+        .iter()
+        .filter(|(module, _)| is_within(module, old))
+        .map(|(module, file)| (file.clone(), moved_path(file, module, old, new)))
+        .collect();
+
+    let module_of: BTreeMap<&str, &str> = trace
+        .files
+        .iter()
+        .map(|file| (file.file.as_str(), file.module.as_str()))
+        .collect();
+    // A relative import from inside the module to outside it only breaks when the
+    // module changes packages
+    let same_parent = parent(old) == parent(new);
+    let mut edits: BTreeSet<(&str, Edit)> = BTreeSet::new();
+    for resolution in &trace.resolution {
+        let Outcome::Resolved { target, .. } = &resolution.outcome else {
+            continue;
+        };
+        let Some(&importer) = module_of.get(resolution.file.as_str()) else {
+            continue;
+        };
+        let statement = &resolution.statement;
+        let (inside, targets) = (is_within(importer, old), is_within(target, old));
+        let kind = if statement.starts_with("from .") {
+            if inside == targets || (inside && same_parent) {
+                continue;
+            }
+            EditKind::Relative
+        } else if !targets {
+            continue;
+        } else {
+            let named = statement
+                .strip_prefix("import ")
+                .or_else(|| statement.strip_prefix("from ")?.split(" import ").next());
+            if named.is_some_and(|module| is_within(module, old)) {
+                EditKind::Import
+            } else {
+                EditKind::FromParent
+            }
+        };
+        edits.insert((
+            importer,
+            Edit {
+                file: resolution.file.clone(),
+                line: resolution.line,
+                kind,
+                text: statement.clone(),
+            },
+        ));
+    }
+
+    let mut computed = Vec::new();
+    for file in &trace.files {
+        for import in &file.dynamic_imports {
+            match &import.name {
+                DynamicName::Literal { module } if is_within(module, old) => {
+                    edits.insert((
+                        &file.module,
+                        Edit {
+                            file: file.file.clone(),
+                            line: import.line,
+                            kind: EditKind::Dynamic,
+                            text: module.clone(),
+                        },
+                    ));
+                }
+                DynamicName::Computed { prefix }
+                    if old.starts_with(prefix.as_str()) || prefix.starts_with(old) =>
+                {
+                    computed.push((file.file.clone(), import.line, prefix.clone()));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut by_package: BTreeMap<String, Vec<Edit>> = BTreeMap::new();
+    for (importer, edit) in edits {
+        // An `__init__` file is in the package it defines
+        let package = if edit
+            .file
+            .rsplit('/')
+            .next()
+            .is_some_and(|name| name.starts_with("__init__."))
+        {
+            importer
+        } else {
+            parent(importer)
+        };
+        by_package
+            .entry(package.to_string())
+            .or_default()
+            .push(edit);
+    }
+    for package in by_package.values_mut() {
+        package.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+        package.dedup_by(|a, b| a.file == b.file && a.line == b.line);
+    }
+
+    Ok(RenameCheck {
+        old: old.to_string(),
+        new: new.to_string(),
+        moves,
+        edits: by_package,
+        computed,
+    })
+}
+
+/// Paths relative to the current directory when `project_root` is
+fn display_path(file: &str, project_root: &Path) -> String {
+    if project_root == Path::new(".") {
+        file.to_string()
+    } else {
+        project_root.join(file).display().to_string()
+    }
+}
+
+/// Files to move, then one `path:line: statement` line per edit under its package
+pub fn render_report(check: &RenameCheck, project_root: &Path) -> String {
+    let mut out = format!(
+        "Renaming {} to {} touches {} line(s) in {} file(s)\n",
+        check.old,
+        check.new,
+        check.lines(),
+        check.files()
+    );
+    if !check.moves.is_empty() {
+        out.push_str("\nFiles to move:\n");
+        for (from, to) in &check.moves {
+            let _ = writeln!(
+                out,
+                "    {} -> {}",
+                display_path(from, project_root),
+                display_path(to, project_root)
+            );
+        }
+    }
+    for (package, edits) in &check.edits {
+        let _ = writeln!(
+            out,
+            "\n{}:",
+            if package.is_empty() {
+                "(top level)"
+            } else {
+                package
+            }
+        );
+        for edit in edits {
+            let note = match edit.kind {
+                EditKind::FromParent => " (binds the module's old name)",
+                EditKind::Relative => " (relative)",
+                EditKind::Import | EditKind::Dynamic => "",
+            };
+            let _ = writeln!(
+                out,
+                "    {}:{}: {}{note}",
+                display_path(&edit.file, project_root),
+                edit.line,
+                edit.describe()
+            );
+        }
+    }
+    if !check.computed.is_empty() {
+        out.push_str("\nDynamic imports of computed names that may name it:\n");
+        for (file, line, prefix) in &check.computed {
+            let _ = writeln!(
+                out,
+                "    {}:{line}: prefix \"{prefix}\"",
+                display_path(file, project_root)
+            );
+        }
+    }
+    out
+}
+
+/// Quote `value` for a POSIX shell
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// `sed` address and command rewriting one line, or `None` when it needs a person
+fn sed_command(edit: &Edit, old: &str, new: &str) -> Option<String> {
+    let word = |name: &str| format!(r"\b{}\b", name.replace('.', r"\."));
+    match edit.kind {
+        EditKind::Import | EditKind::Dynamic => {
+            Some(format!("{}s/{}/{new}/g", edit.line, word(old)))
+        }
+        // `from app import db` keeps working as `from core import db` as long as the
+        // statement imports nothing else and the module keeps its last name
+        EditKind::FromParent => {
+            let (old_parent, old_leaf) = old.rsplit_once('.')?;
+            let (new_parent, new_leaf) = new.rsplit_once('.')?;
+            let names = edit.text.split_once(" import ")?.1;
+            (old_leaf == new_leaf && names == old_leaf).then(|| {
+                format!(
+                    r"{}s/\bfrom\s\+{}\s\+import\b/from {new_parent} import/",
+                    edit.line,
+                    old_parent.replace('.', r"\.")
+                )
+            })
+        }
+        EditKind::Relative => None,
+    }
+}
+
+/// Shell script moving the files and applying the edits `sed` can make (GNU `sed -i`);
+/// the other edits are listed as comments
+pub fn render_sed_script(check: &RenameCheck, project_root: &Path) -> String {
+    let mut out = format!(
+        "#!/bin/sh\n# Rename {} to {}\nset -e\n",
+        check.old, check.new
+    );
+    let mut directories = BTreeSet::new();
+    for (from, to) in &check.moves {
+        let to = display_path(to, project_root);
+        if let Some((directory, _)) = to.rsplit_once('/')
+            && directories.insert(directory.to_string())
+        {
+            let _ = writeln!(out, "mkdir -p {}", shell_quote(directory));
+        }
+        let _ = writeln!(
+            out,
+            "mv {} {}",
+            shell_quote(&display_path(from, project_root)),
+            shell_quote(&to)
+        );
+    }
+
+    let mut by_file: BTreeMap<&str, Vec<&Edit>> = BTreeMap::new();
+    for edit in check.edits.values().flatten() {
+        by_file.entry(&edit.file).or_default().push(edit);
+    }
+    // Files of the module itself are edited where they were moved to
+    let moved: BTreeMap<&str, &str> = check
+        .moves
+        .iter()
+        .map(|(from, to)| (from.as_str(), to.as_str()))
+        .collect();
+    for (file, edits) in by_file {
+        let path = display_path(moved.get(file).copied().unwrap_or(file), project_root);
+        let mut commands = Vec::new();
+        for edit in edits {
+            match sed_command(edit, &check.old, &check.new) {
+                Some(command) => commands.push(format!("-e {}", shell_quote(&command))),
+                None => {
+                    let _ = writeln!(
+                        out,
+                        "# {path}:{}: {}: update by hand",
+                        edit.line,
+                        edit.describe()
+                    );
+                }
+            }
+        }
+        if !commands.is_empty() {
+            let _ = writeln!(
+                out,
+                "sed -i {} -- {}",
+                commands.join(" "),
+                shell_quote(&path)
+            );
+        }
+    }
+    for (file, line, prefix) in &check.computed {
+        let _ = writeln!(
+            out,
+            "# {}:{line}: dynamic import of a computed name starting with \"{prefix}\": check by hand",
+            display_path(file, project_root)
+        );
+    }
+    out
+}
@@ -0,0 +1,93 @@
+//! Graph sampling (`--sample`, `--seed`)
+//!
+//! Cuts a graph down to a small subgraph that still looks like it, for documentation
+//! examples and bug reports: the best-connected modules with their neighborhoods, plus
+//! a short import cycle when the graph has any. Ties are broken by a hash of the
+//! module name mixed with the seed, so a sample is reproducible and another seed gives
+//! another sample of the same shape.
+
+use deptree_graph::{DependencyGraph, GraphId};
+use std::collections::HashSet;
+
+/// Order of a module among equally connected ones
+fn tie_break<T: GraphId>(module: &T, seed: u64) -> u64 {
+    seeded_hash(&module.to_dotted(), seed)
+}
+
+/// Hash of `name` mixed with `seed`, the same across runs and platforms
+pub(crate) fn seeded_hash(name: &str, seed: u64) -> u64 {
+    // FNV-1a: stable across runs and platforms, unlike the std hashers
+    let hash = name.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    // SplitMix64 finalizer, so nearby seeds give unrelated orders
+    let mut mixed = hash ^ seed.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    mixed = (mixed ^ (mixed >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    mixed = (mixed ^ (mixed >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    mixed ^ (mixed >> 31)
+}
+
+/// Shortest closed import chain through `member`, without the repeated module: one
+/// breadth-first search for the nearest module importing `member` back
+fn closed_chain<T: GraphId>(graph: &DependencyGraph<T>, member: &T) -> Vec<T> {
+    if graph.edge_multiplicity(member, member) > 0 {
+        return vec![member.clone()];
+    }
+    graph
+        .shortest_chain(member, |module| graph.edge_multiplicity(module, member) > 0)
+        .unwrap_or_default()
+}
+
+/// Modules of a sample of about `size` modules: a shortest cycle through one member of the
+/// smallest import cycle (even if it alone exceeds `size`), then the highest-degree modules, each followed
+/// by its best-connected neighbors, until `size` are kept
+pub fn sample<T: GraphId>(graph: &DependencyGraph<T>, size: usize, seed: u64) -> HashSet<T> {
+    let degree = |module: &T| {
+        let others = |found: Vec<&T>| found.into_iter().filter(|n| *n != module).count();
+        others(graph.direct_dependencies(module)) + others(graph.direct_dependents(module))
+    };
+    // Best first: highest degree, then the seeded order
+    let rank = |module: &T| (std::cmp::Reverse(degree(module)), tie_break(module, seed));
+
+    let mut kept: Vec<T> = Vec::new();
+    let smallest_cycle = graph
+        .find_cycles()
+        .into_iter()
+        .min_by_key(|cycle| (cycle.len(), cycle.iter().map(|m| tie_break(m, seed)).min()));
+    // A chain through one member is enough: searching from every member of a large
+    // cycle would traverse the component once per member
+    if let Some(member) = smallest_cycle.and_then(|cycle| {
+        cycle
+            .into_iter()
+            .min_by_key(|member| tie_break(member, seed))
+    }) {
+        kept.extend(closed_chain(graph, &member));
+    }
+    let mut seen: HashSet<T> = kept.iter().cloned().collect();
+
+    let mut ranked: Vec<&T> = graph.nodes().collect();
+    ranked.sort_by_cached_key(|module| rank(module));
+    for hub in ranked {
+        if kept.len() >= size {
+            break;
+        }
+        if seen.insert(hub.clone()) {
+            kept.push(hub.clone());
+        }
+        let mut neighbors: Vec<&T> = graph
+            .direct_dependencies(hub)
+            .into_iter()
+            .chain(graph.direct_dependents(hub))
+            .collect();
+        neighbors.sort_by_cached_key(|module| rank(module));
+        for neighbor in neighbors {
+            if kept.len() >= size {
+                break;
+            }
+            if seen.insert(neighbor.clone()) {
+                kept.push(neighbor.clone());
+            }
+        }
+    }
+    seen
+}
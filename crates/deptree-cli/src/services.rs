@@ -0,0 +1,190 @@
+//! Service-level deployment impact (`polyglot --impact-services`)
+//!
+//! Rolls the downstream closure of a set of changed modules up to the `[[services]]`
+//! declared in `deptree.toml`: a service has to be redeployed (and its tests run) when it
+//! contains a changed module or a module importing one, directly or indirectly. Changes
+//! are given as node names or as the changed files themselves, so the output of
+//! `git diff --name-only` can be passed as is.
+
+use crate::config::Service;
+use crate::contracts::ModuleSpec;
+use crate::hook::{SourceRoots, is_source_file, node_of_file};
+use crate::polyglot::{PolyglotError, PolyglotGraph, PolyglotNode};
+use deptree_graph::GraphId;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::Write;
+use std::path::Path;
+use thiserror::Error;
+
+/// Errors that can occur while rolling changes up to services
+#[derive(Error, Debug)]
+pub enum ServiceError {
+    #[error("Service '{0}': {1}")]
+    InvalidModule(String, Box<PolyglotError>),
+
+    #[error("Service '{0}' refers to '{1}', which matches no analyzed module")]
+    UnknownModule(String, String),
+
+    #[error("Changed module '{0}' is not part of the analyzed graph")]
+    UnknownChange(String),
+
+    #[error("No [[services]] configured")]
+    NoServices,
+}
+
+/// A service reached by the changes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AffectedService {
+    pub name: String,
+    /// Changed modules of the service, sorted
+    pub changed: Vec<String>,
+    /// Modules of the service importing a changed module, sorted
+    pub affected: Vec<String>,
+}
+
+/// Services to redeploy for a change
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ServiceImpact {
+    /// Changed modules, sorted
+    pub changed: Vec<String>,
+    /// Services with a changed or affected module, in configuration order
+    pub services: Vec<AffectedService>,
+    /// Services left untouched, in configuration order
+    pub unaffected: Vec<String>,
+    /// Changed or affected modules belonging to no service, sorted
+    pub unowned: Vec<String>,
+}
+
+/// Node changed by `input`: a file path relative to `project` (see [`node_of_file`]) or a
+/// `<language>:<name>` node. Files defining no node, like documentation, deleted modules,
+/// or files outside the analyzed roots, give `Ok(None)`.
+fn changed_node(
+    input: &str,
+    graph: &PolyglotGraph,
+    roots: &SourceRoots,
+    project: &Path,
+) -> Result<Option<PolyglotNode>, ServiceError> {
+    let input = input.trim();
+    if is_source_file(Path::new(input)) || !input.contains(':') {
+        return Ok(node_of_file(&project.join(input), roots, graph));
+    }
+    match PolyglotNode::parse(input) {
+        Ok(node) if graph.contains(&node) => Ok(Some(node)),
+        _ => Err(ServiceError::UnknownChange(input.to_string())),
+    }
+}
+
+/// The services containing a module of the downstream closure of `changes`, whose file
+/// paths are relative to `project`
+pub fn impact_services(
+    graph: &PolyglotGraph,
+    roots: &SourceRoots,
+    project: &Path,
+    services: &[Service],
+    changes: &[String],
+) -> Result<ServiceImpact, ServiceError> {
+    if services.is_empty() {
+        return Err(ServiceError::NoServices);
+    }
+    let mut specs: Vec<(&str, Vec<ModuleSpec>)> = Vec::new();
+    for service in services {
+        let mut modules = Vec::new();
+        for module in &service.modules {
+            let spec = ModuleSpec::resolve(module, graph)
+                .map_err(|e| ServiceError::InvalidModule(service.name.clone(), Box::new(e)))?
+                .ok_or_else(|| ServiceError::UnknownModule(service.name.clone(), module.clone()))?;
+            modules.push(spec);
+        }
+        specs.push((&service.name, modules));
+    }
+
+    let mut changed: Vec<PolyglotNode> = Vec::new();
+    for input in changes {
+        changed.extend(changed_node(input, graph, roots, project)?);
+    }
+    changed.sort_by_key(GraphId::to_dotted);
+    changed.dedup();
+    let changed_set: HashSet<&PolyglotNode> = changed.iter().collect();
+    let reached = graph.find_downstream(&changed, None);
+
+    let mut impact = ServiceImpact {
+        changed: changed.iter().map(GraphId::to_dotted).collect(),
+        services: Vec::new(),
+        unaffected: Vec::new(),
+        unowned: Vec::new(),
+    };
+    let mut owned: HashSet<&PolyglotNode> = HashSet::new();
+    for (name, modules) in &specs {
+        let mut service = AffectedService {
+            name: name.to_string(),
+            changed: Vec::new(),
+            affected: Vec::new(),
+        };
+        for node in reached.keys() {
+            if !modules.iter().any(|spec| spec.covers(node)) {
+                continue;
+            }
+            owned.insert(node);
+            if changed_set.contains(node) {
+                service.changed.push(node.to_dotted());
+            } else {
+                service.affected.push(node.to_dotted());
+            }
+        }
+        if service.changed.is_empty() && service.affected.is_empty() {
+            impact.unaffected.push(service.name);
+        } else {
+            service.changed.sort();
+            service.affected.sort();
+            impact.services.push(service);
+        }
+    }
+    impact.unowned = reached
+        .keys()
+        .filter(|node| !owned.contains(node))
+        .map(GraphId::to_dotted)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    Ok(impact)
+}
+
+/// Render the services to redeploy with the modules bringing each in, then the untouched
+/// services and the reached modules no service contains
+pub fn render_report(impact: &ServiceImpact) -> String {
+    let total = impact.services.len() + impact.unaffected.len();
+    let mut out = format!(
+        "{} of {total} service(s) affected by {} changed module(s)\n",
+        impact.services.len(),
+        impact.changed.len()
+    );
+    if !impact.services.is_empty() {
+        out.push_str("\nRedeploy and test:\n");
+        for service in &impact.services {
+            let mut modules: Vec<String> = service
+                .changed
+                .iter()
+                .map(|module| format!("{module} (changed)"))
+                .collect();
+            modules.extend(service.affected.iter().cloned());
+            let _ = writeln!(out, "    {}: {}", service.name, modules.join(", "));
+        }
+    }
+    if !impact.unaffected.is_empty() {
+        let _ = writeln!(out, "\nUnaffected: {}", impact.unaffected.join(", "));
+    }
+    if !impact.unowned.is_empty() {
+        let _ = writeln!(out, "\nIn no service: {}", impact.unowned.join(", "));
+    }
+    out
+}
+
+/// The names of the services to redeploy, one per line
+pub fn render_list(impact: &ServiceImpact) -> String {
+    impact
+        .services
+        .iter()
+        .map(|service| format!("{}\n", service.name))
+        .collect()
+}
@@ -0,0 +1,195 @@
+//! Remote and archived project sources
+//!
+//! Lets an analyzer run on a release artifact (`.zip`, `.tar`, `.tar.gz`/`.tgz`) or a
+//! `git+<url>[@<rev>]` repository instead of a local checkout. The project is unpacked or
+//! cloned into a temporary directory that lives as long as the returned [`Project`].
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+use thiserror::Error;
+
+/// Errors that can occur while fetching a project
+#[derive(Error, Debug)]
+pub enum SourceError {
+    #[error("Failed to create a temporary directory: {0}")]
+    TempDir(std::io::Error),
+
+    #[error("Failed to extract {0}: {1}")]
+    Extract(PathBuf, std::io::Error),
+
+    #[error("Failed to extract {0}: {1}")]
+    Zip(PathBuf, zip::result::ZipError),
+
+    #[error("Failed to run git: {0}")]
+    GitUnavailable(std::io::Error),
+
+    #[error("git {0} failed: {1}")]
+    Git(String, String),
+
+    #[error("Invalid git revision '{0}': revisions cannot start with '-'")]
+    InvalidRevision(String),
+}
+
+/// Where a project comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProjectSource {
+    /// A directory on disk
+    Local(PathBuf),
+    /// A `.zip`, `.tar`, `.tar.gz`, or `.tgz` file
+    Archive(PathBuf),
+    /// `git+<url>[@<rev>]`
+    Git { url: String, rev: Option<String> },
+}
+
+impl ProjectSource {
+    /// Classify a project argument: `git+` URLs, archive files by extension, and local
+    /// directories otherwise
+    pub fn parse(input: &Path) -> Self {
+        let text = input.to_string_lossy();
+        if let Some(spec) = text.strip_prefix("git+") {
+            // A revision follows the last `@` of the path (not the one in `git@host`)
+            let path_start = spec.find("://").map_or(0, |i| i + 3);
+            return match spec.rfind('@') {
+                Some(at) if at > path_start && spec[path_start..at].contains('/') => {
+                    ProjectSource::Git {
+                        url: spec[..at].to_string(),
+                        rev: Some(spec[at + 1..].to_string()),
+                    }
+                }
+                _ => ProjectSource::Git {
+                    url: spec.to_string(),
+                    rev: None,
+                },
+            };
+        }
+        if archive_kind(input).is_some() && input.is_file() {
+            ProjectSource::Archive(input.to_path_buf())
+        } else {
+            ProjectSource::Local(input.to_path_buf())
+        }
+    }
+
+    /// Make the project available on disk, extracting or cloning it when needed
+    pub fn fetch(&self) -> Result<Project, SourceError> {
+        match self {
+            ProjectSource::Local(path) => Ok(Project {
+                root: path.clone(),
+                _temp: None,
+            }),
+            ProjectSource::Archive(path) => {
+                let temp = TempDir::new().map_err(SourceError::TempDir)?;
+                extract(path, temp.path())?;
+                Ok(Project {
+                    root: single_top_level_dir(temp.path()),
+                    _temp: Some(temp),
+                })
+            }
+            ProjectSource::Git { url, rev } => {
+                let temp = TempDir::new().map_err(SourceError::TempDir)?;
+                let root = temp.path().join("checkout");
+                clone(url, rev.as_deref(), &root)?;
+                Ok(Project {
+                    root,
+                    _temp: Some(temp),
+                })
+            }
+        }
+    }
+}
+
+/// A project available on disk; a temporary copy is deleted on drop
+pub struct Project {
+    root: PathBuf,
+    _temp: Option<TempDir>,
+}
+
+impl Project {
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+fn archive_kind(path: &Path) -> Option<ArchiveKind> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    if name.ends_with(".zip") {
+        Some(ArchiveKind::Zip)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(ArchiveKind::TarGz)
+    } else if name.ends_with(".tar") {
+        Some(ArchiveKind::Tar)
+    } else {
+        None
+    }
+}
+
+/// Unpack an archive into `dest`. Both readers refuse entries that would land outside
+/// `dest` (absolute paths or `..` components).
+fn extract(archive: &Path, dest: &Path) -> Result<(), SourceError> {
+    let io_error = |e| SourceError::Extract(archive.to_path_buf(), e);
+    let file = File::open(archive).map_err(io_error)?;
+    match archive_kind(archive) {
+        Some(ArchiveKind::Zip) => zip::ZipArchive::new(file)
+            .and_then(|mut zip| zip.extract(dest))
+            .map_err(|e| SourceError::Zip(archive.to_path_buf(), e)),
+        Some(ArchiveKind::TarGz) => tar::Archive::new(flate2::read::GzDecoder::new(file))
+            .unpack(dest)
+            .map_err(io_error),
+        Some(ArchiveKind::Tar) | None => tar::Archive::new(file).unpack(dest).map_err(io_error),
+    }
+}
+
+/// Release archives usually wrap everything in one `name-version/` directory; analyze
+/// that directory rather than its parent
+fn single_top_level_dir(dir: &Path) -> PathBuf {
+    let entries: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| entries.filter_map(|e| e.ok().map(|e| e.path())).collect())
+        .unwrap_or_default();
+    match entries.as_slice() {
+        [only] if only.is_dir() => only.clone(),
+        _ => dir.to_path_buf(),
+    }
+}
+
+/// `--` keeps a url starting with `-` from being read as an option (`--upload-pack=...`
+/// runs a command); a revision can't be put after one, so those are rejected instead
+fn clone(url: &str, rev: Option<&str>, dest: &Path) -> Result<(), SourceError> {
+    let dest = dest.to_string_lossy();
+    match rev {
+        Some(rev) if rev.starts_with('-') => Err(SourceError::InvalidRevision(rev.to_string())),
+        Some(rev) => {
+            run_git(
+                "clone",
+                &["clone", "--quiet", "--no-checkout", "--", url, &dest],
+            )?;
+            run_git("checkout", &["-C", &dest, "checkout", "--quiet", rev, "--"])
+        }
+        None => run_git(
+            "clone",
+            &["clone", "--quiet", "--depth", "1", "--", url, &dest],
+        ),
+    }
+}
+
+fn run_git(step: &str, args: &[&str]) -> Result<(), SourceError> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(SourceError::GitUnavailable)?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(SourceError::Git(
+            step.to_string(),
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
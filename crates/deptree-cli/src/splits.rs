@@ -0,0 +1,198 @@
+//! Module split suggestions (experimental)
+//!
+//! Partitions the modules of a package by dependency cohesion (community detection over
+//! the imports between them) and reports, for every suggested group, the edges that
+//! would cross the new boundaries.
+
+use deptree_graph::{DependencyGraph, GraphId};
+use std::collections::HashSet;
+use thiserror::Error;
+
+/// Errors that can occur while suggesting splits
+#[derive(Error, Debug)]
+pub enum SplitError {
+    #[error("Package '{0}' does not match any analyzed module")]
+    UnknownPackage(String),
+
+    #[error("'{0}' has no submodules to split; pass a package")]
+    NotAPackage(String),
+}
+
+/// One suggested group of modules and the dependencies crossing its boundary
+#[derive(Debug, Clone)]
+pub struct SplitGroup<T> {
+    pub members: Vec<T>,
+    /// Imports from this group into other groups of the same package
+    pub cross_group_imports: Vec<(T, T)>,
+    /// Modules outside the package imported by this group
+    pub external_imports: Vec<T>,
+    /// Modules outside the package importing this group
+    pub external_importers: Vec<T>,
+}
+
+/// Suggested partition of a package
+#[derive(Debug, Clone)]
+pub struct SplitSuggestion<T> {
+    pub package: T,
+    pub groups: Vec<SplitGroup<T>>,
+    /// Modules without any import to or from the rest of the package
+    pub unconnected: Vec<T>,
+    /// Modularity of the partition over the package's internal imports
+    pub modularity: f64,
+}
+
+/// Direct neighbors of `module`: its dependencies (`upstream`) or dependents
+fn direct_neighbors<T: GraphId>(graph: &DependencyGraph<T>, module: &T, upstream: bool) -> Vec<T> {
+    let neighbors = if upstream {
+        graph.direct_dependencies(module)
+    } else {
+        graph.direct_dependents(module)
+    };
+    neighbors
+        .into_iter()
+        .filter(|neighbor| *neighbor != module)
+        .cloned()
+        .collect()
+}
+
+fn sorted_unique<T: GraphId>(mut nodes: Vec<T>) -> Vec<T> {
+    nodes.sort_by_key(GraphId::to_dotted);
+    nodes.dedup();
+    nodes
+}
+
+/// Suggest how the modules of `package` (the package itself and everything nested below
+/// it) could be split into cohesive groups
+pub fn suggest_splits<T: GraphId>(
+    graph: &DependencyGraph<T>,
+    package: &T,
+) -> Result<SplitSuggestion<T>, SplitError> {
+    let prefix = package.segments();
+    let members: HashSet<T> = graph
+        .nodes()
+        .filter(|n| n.segments().starts_with(&prefix))
+        .cloned()
+        .collect();
+
+    if members.is_empty() {
+        return Err(SplitError::UnknownPackage(package.to_dotted()));
+    }
+    if members.iter().all(|m| m == package) {
+        return Err(SplitError::NotAPackage(package.to_dotted()));
+    }
+
+    let communities = graph.detect_communities(Some(&members));
+    let modularity = graph.modularity(&communities);
+
+    let is_connected = |module: &T| {
+        direct_neighbors(graph, module, true)
+            .into_iter()
+            .chain(direct_neighbors(graph, module, false))
+            .any(|neighbor| members.contains(&neighbor) && neighbor != *module)
+    };
+    let (communities, isolated): (Vec<Vec<T>>, Vec<Vec<T>>) = communities
+        .into_iter()
+        .partition(|community| community.len() > 1 || community.iter().any(is_connected));
+    let unconnected = isolated.into_iter().flatten().collect();
+
+    let groups = communities
+        .iter()
+        .map(|community| {
+            let in_group: HashSet<&T> = community.iter().collect();
+            let mut cross_group_imports = Vec::new();
+            let mut external_imports = Vec::new();
+            let mut external_importers = Vec::new();
+
+            for module in community {
+                for dependency in direct_neighbors(graph, module, true) {
+                    if !members.contains(&dependency) {
+                        external_imports.push(dependency);
+                    } else if !in_group.contains(&dependency) {
+                        cross_group_imports.push((module.clone(), dependency));
+                    }
+                }
+                for dependent in direct_neighbors(graph, module, false) {
+                    if !members.contains(&dependent) {
+                        external_importers.push(dependent);
+                    }
+                }
+            }
+
+            SplitGroup {
+                members: community.clone(),
+                cross_group_imports,
+                external_imports: sorted_unique(external_imports),
+                external_importers: sorted_unique(external_importers),
+            }
+        })
+        .collect();
+
+    Ok(SplitSuggestion {
+        package: package.clone(),
+        groups,
+        unconnected,
+        modularity,
+    })
+}
+
+fn join_names<T: GraphId>(nodes: &[T]) -> String {
+    nodes
+        .iter()
+        .map(GraphId::to_dotted)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Render a split suggestion as a human-readable report
+pub fn render_report<T: GraphId>(suggestion: &SplitSuggestion<T>) -> String {
+    let package = suggestion.package.to_dotted();
+    let mut output = if suggestion.groups.len() > 1 {
+        format!(
+            "Suggested split of {package}: {} groups (modularity {:.2})\n",
+            suggestion.groups.len(),
+            suggestion.modularity
+        )
+    } else {
+        format!("No split suggested: the modules of {package} form a single cohesive group\n")
+    };
+
+    for (index, group) in suggestion.groups.iter().enumerate() {
+        let count = match group.members.len() {
+            1 => "1 module".to_string(),
+            n => format!("{n} modules"),
+        };
+        output.push_str(&format!(
+            "\nGroup {} ({count}): {}\n",
+            index + 1,
+            join_names(&group.members)
+        ));
+        for (from, to) in &group.cross_group_imports {
+            output.push_str(&format!(
+                "    imports from other groups: {} -> {}\n",
+                from.to_dotted(),
+                to.to_dotted()
+            ));
+        }
+        if !group.external_imports.is_empty() {
+            output.push_str(&format!(
+                "    imports outside {package}: {}\n",
+                join_names(&group.external_imports)
+            ));
+        }
+        if !group.external_importers.is_empty() {
+            output.push_str(&format!(
+                "    imported from outside {package} by: {}\n",
+                join_names(&group.external_importers)
+            ));
+        }
+    }
+
+    if !suggestion.unconnected.is_empty() {
+        output.push_str(&format!(
+            "\nNot connected to the rest of {package}: {}\n",
+            join_names(&suggestion.unconnected)
+        ));
+    }
+
+    output
+}
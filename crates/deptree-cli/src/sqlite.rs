@@ -0,0 +1,154 @@
+//! SQLite export (`--format sqlite`)
+//!
+//! Stores a rendered graph in a small relational schema so it can be explored with SQL:
+//!
+//! - `metadata(key, value)`: generator, schema version, and the options of the run
+//! - `nodes(id, type, parent, is_orphan, highlighted, category, lifecycle, import_time_us,
+//!   coverage)`
+//! - `annotations(node, key, value)`: metadata from `--annotations`, one row per key
+//! - `edges(source, target, kind, count)`, with `kind` either `import_time` or `deferred`
+//!   and `count` the number of imports the edge stands for
+//! - `metrics(name, package, value)`: the `--format openmetrics` gauges, `package` set
+//!   only for `package_fan_in`
+
+use deptree_graph::{GraphData, GraphMetrics};
+use rusqlite::{Connection, DatabaseName, params};
+use thiserror::Error;
+
+/// Version of the table layout, recorded as `schema_version` in `metadata`
+pub const SCHEMA_VERSION: u32 = 4;
+
+const SCHEMA: &str = "
+CREATE TABLE metadata (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE nodes (
+    id TEXT PRIMARY KEY,
+    type TEXT NOT NULL,
+    parent TEXT,
+    is_orphan INTEGER NOT NULL,
+    highlighted INTEGER,
+    category TEXT,
+    lifecycle TEXT,
+    import_time_us INTEGER,
+    coverage REAL
+);
+CREATE TABLE annotations (
+    node TEXT NOT NULL,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (node, key)
+);
+CREATE TABLE edges (
+    source TEXT NOT NULL,
+    target TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    count INTEGER NOT NULL,
+    PRIMARY KEY (source, target)
+);
+CREATE INDEX edges_by_target ON edges (target);
+CREATE TABLE metrics (
+    name TEXT NOT NULL,
+    package TEXT,
+    value INTEGER NOT NULL
+);
+";
+
+/// Errors that can occur while building the database
+#[derive(Error, Debug)]
+pub enum SqliteError {
+    #[error("Failed to build SQLite database: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+}
+
+/// Build the database in memory and return its file contents
+pub fn export(
+    data: &GraphData,
+    metrics: &GraphMetrics,
+    metadata: &[(&str, String)],
+) -> Result<Vec<u8>, SqliteError> {
+    let mut connection = Connection::open_in_memory()?;
+    connection.execute_batch(SCHEMA)?;
+
+    let tx = connection.transaction()?;
+    {
+        let mut insert = tx.prepare("INSERT INTO metadata (key, value) VALUES (?1, ?2)")?;
+        insert.execute(params!["generator", generator()])?;
+        insert.execute(params!["schema_version", SCHEMA_VERSION.to_string()])?;
+        if let Some(config) = &data.config {
+            insert.execute(params![
+                "include_orphans",
+                config.include_orphans.to_string()
+            ])?;
+            insert.execute(params![
+                "include_namespace_packages",
+                config.include_namespaces.to_string()
+            ])?;
+        }
+        for (key, value) in metadata {
+            insert.execute(params![key, value])?;
+        }
+
+        let mut insert = tx.prepare(
+            "INSERT INTO nodes (id, type, parent, is_orphan, highlighted, category, \
+             lifecycle, import_time_us, coverage) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?;
+        for node in &data.nodes {
+            insert.execute(params![
+                node.id,
+                node.node_type,
+                node.parent,
+                node.is_orphan,
+                node.highlighted,
+                node.category,
+                node.lifecycle,
+                node.import_time_us,
+                node.coverage,
+            ])?;
+        }
+
+        let mut insert =
+            tx.prepare("INSERT INTO annotations (node, key, value) VALUES (?1, ?2, ?3)")?;
+        for node in &data.nodes {
+            for (key, value) in node.annotations.iter().flatten() {
+                insert.execute(params![node.id, key, value])?;
+            }
+        }
+
+        // Parallel edges were already collapsed for rendering
+        let mut insert = tx.prepare(
+            "INSERT OR IGNORE INTO edges (source, target, kind, count) VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        for edge in &data.edges {
+            let kind = edge.kind.as_deref().unwrap_or("import_time");
+            insert.execute(params![
+                edge.source,
+                edge.target,
+                kind,
+                edge.count.unwrap_or(1)
+            ])?;
+        }
+
+        let mut insert =
+            tx.prepare("INSERT INTO metrics (name, package, value) VALUES (?1, ?2, ?3)")?;
+        for (name, value) in [
+            ("nodes", metrics.nodes),
+            ("edges", metrics.edges),
+            ("cycles", metrics.cycles),
+            ("max_depth", metrics.max_depth),
+        ] {
+            insert.execute(params![name, None::<String>, value])?;
+        }
+        for (package, value) in &metrics.package_fan_in {
+            insert.execute(params!["package_fan_in", package, value])?;
+        }
+    }
+    tx.commit()?;
+
+    Ok(connection.serialize(DatabaseName::Main)?.to_vec())
+}
+
+fn generator() -> String {
+    format!("deptree-utils {}", env!("CARGO_PKG_VERSION"))
+}
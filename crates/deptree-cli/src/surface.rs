@@ -0,0 +1,146 @@
+//! Package API surface (`--api-surface`)
+//!
+//! Whatever the code outside a package imports from it is its de facto public API, and has
+//! to stay stable (or be rewritten at every consumer) when the package moves to its own
+//! repository. The report splits the modules of a package into those imported from outside,
+//! with the consumer packages importing each, and those only used internally.
+
+use deptree_graph::{DependencyGraph, GraphId};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Errors that can occur while computing an API surface
+#[derive(Error, Debug)]
+pub enum SurfaceError {
+    #[error("Package '{0}' does not match any analyzed module")]
+    UnknownPackage(String),
+}
+
+/// A module of the package imported from outside it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicModule<T> {
+    pub module: T,
+    /// Consumer packages (dotted) with the number of their modules importing this one,
+    /// most first
+    pub consumers: Vec<(String, usize)>,
+}
+
+impl<T> PublicModule<T> {
+    /// Modules outside the package importing this one
+    pub fn importers(&self) -> usize {
+        self.consumers.iter().map(|(_, count)| count).sum()
+    }
+}
+
+/// Modules of a package (the package and everything nested below it) by who imports them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiSurface<T> {
+    pub package: T,
+    /// Most importers first
+    pub public: Vec<PublicModule<T>>,
+    /// Imported only by modules of the package
+    pub internal: Vec<T>,
+    /// Not imported at all
+    pub unused: Vec<T>,
+}
+
+/// API surface of `package`. An importer's consumer package is its name cut to as many
+/// segments as `package` has, so consumers are reported at the level of the package itself
+/// (e.g. `app.api` for `app.api.views` importing `shop.core.models`).
+pub fn api_surface<T: GraphId>(
+    graph: &DependencyGraph<T>,
+    package: &T,
+) -> Result<ApiSurface<T>, SurfaceError> {
+    let prefix = package.segments();
+    let inside = |module: &T| module.segments().starts_with(&prefix);
+    let mut members: Vec<&T> = graph.nodes().filter(|module| inside(module)).collect();
+    if members.is_empty() {
+        return Err(SurfaceError::UnknownPackage(package.to_dotted()));
+    }
+    members.sort_by_key(|module| module.to_dotted());
+
+    let mut public = Vec::new();
+    let mut internal = Vec::new();
+    let mut unused = Vec::new();
+    for module in members {
+        let importers: Vec<&T> = graph
+            .direct_dependents(module)
+            .into_iter()
+            .filter(|importer| *importer != module)
+            .collect();
+        let mut consumers: BTreeMap<String, usize> = BTreeMap::new();
+        for importer in importers.iter().filter(|importer| !inside(importer)) {
+            let segments = importer.segments();
+            let consumer = T::join_segments(&segments[..segments.len().min(prefix.len())]);
+            *consumers.entry(consumer).or_default() += 1;
+        }
+        if !consumers.is_empty() {
+            let mut consumers: Vec<(String, usize)> = consumers.into_iter().collect();
+            // Stable, so equal counts keep name order
+            consumers.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+            public.push(PublicModule {
+                module: module.clone(),
+                consumers,
+            });
+        } else if importers.is_empty() {
+            unused.push(module.clone());
+        } else {
+            internal.push(module.clone());
+        }
+    }
+    public.sort_by_key(|public: &PublicModule<T>| std::cmp::Reverse(public.importers()));
+
+    Ok(ApiSurface {
+        package: package.clone(),
+        public,
+        internal,
+        unused,
+    })
+}
+
+fn join_names<T: GraphId>(nodes: &[T]) -> String {
+    nodes
+        .iter()
+        .map(GraphId::to_dotted)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Render an API surface as a human-readable report
+pub fn render_report<T: GraphId>(surface: &ApiSurface<T>) -> String {
+    let package = surface.package.to_dotted();
+    let total = surface.public.len() + surface.internal.len() + surface.unused.len();
+    let mut output = format!(
+        "API surface of {package}: {} of {total} modules imported from outside\n",
+        surface.public.len()
+    );
+
+    if !surface.public.is_empty() {
+        output.push_str("\nPublic (importing modules per consumer package):\n");
+        for public in &surface.public {
+            let consumers: Vec<String> = public
+                .consumers
+                .iter()
+                .map(|(consumer, count)| format!("{consumer} {count}"))
+                .collect();
+            output.push_str(&format!(
+                "    {}: {}\n",
+                public.module.to_dotted(),
+                consumers.join(", ")
+            ));
+        }
+    }
+    if !surface.internal.is_empty() {
+        output.push_str(&format!(
+            "\nInternal only: {}\n",
+            join_names(&surface.internal)
+        ));
+    }
+    if !surface.unused.is_empty() {
+        output.push_str(&format!(
+            "\nNot imported: {}\n",
+            join_names(&surface.unused)
+        ));
+    }
+    output
+}
@@ -0,0 +1,133 @@
+//! Test reach (`--test-reach`)
+//!
+//! Splits the modules into tests and production code and reports, for every production
+//! module, the test modules importing it directly or indirectly. A module no test reaches
+//! is not exercised by the test suite at all (short of dynamic imports), which makes the
+//! report a cheap stand-in for coverage that catches untested new modules in review.
+//! Importing a module runs the packages above it, so those count as reached too.
+
+use deptree_graph::filters::matches_pattern;
+use deptree_graph::{DependencyGraph, GraphId};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Write;
+
+/// Whether a module is a test by default: pytest collects it (`test_*`, `*_test`,
+/// `conftest`), or it sits in a `tests`/`test` package
+pub fn is_test_module<T: GraphId>(module: &T) -> bool {
+    let segments = module.segments();
+    let Some((name, packages)) = segments.split_last() else {
+        return false;
+    };
+    name.starts_with("test_")
+        || name.ends_with("_test")
+        || name == "conftest"
+        || packages
+            .iter()
+            .any(|package| package == "tests" || package == "test")
+}
+
+/// Production modules by the tests reaching them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestReach {
+    /// Test modules, dotted and sorted
+    pub tests: Vec<String>,
+    /// Production modules reached by a test, with those tests (dotted and sorted)
+    pub reached: BTreeMap<String, Vec<String>>,
+    /// Production modules no test reaches, dotted and sorted
+    pub unreached: Vec<String>,
+}
+
+/// Test reach of the graph's production modules. Tests are the modules matching one of
+/// `patterns` (wildcards on dotted names, see `matches_pattern`), or the
+/// [`is_test_module`] ones without patterns. Scripts and namespace packages outside the
+/// tests are not production modules.
+pub fn test_reach<T: GraphId>(graph: &DependencyGraph<T>, patterns: &[String]) -> TestReach {
+    let is_test = |module: &T| {
+        if patterns.is_empty() {
+            is_test_module(module)
+        } else {
+            let name = module.to_dotted();
+            patterns
+                .iter()
+                .any(|pattern| matches_pattern(&name, pattern))
+        }
+    };
+    let mut tests: Vec<&T> = graph.nodes().filter(|module| is_test(module)).collect();
+    tests.sort_by_key(|module| module.to_dotted());
+
+    let is_production = |module: &T| {
+        !is_test(module) && !graph.is_script(module) && !graph.is_namespace_package(module)
+    };
+    let by_name: HashMap<String, &T> = graph
+        .nodes()
+        .map(|module| (module.to_dotted(), module))
+        .collect();
+
+    let mut reached: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for test in &tests {
+        let mut loaded: HashSet<&T> = HashSet::new();
+        for module in graph
+            .find_upstream(std::slice::from_ref(*test), None)
+            .keys()
+        {
+            let segments = module.segments();
+            loaded.extend(
+                (1..=segments.len())
+                    .filter_map(|len| by_name.get(&T::join_segments(&segments[..len])))
+                    .copied(),
+            );
+        }
+        for module in loaded.into_iter().filter(|module| is_production(module)) {
+            reached
+                .entry(module.to_dotted())
+                .or_default()
+                .insert(test.to_dotted());
+        }
+    }
+
+    let mut unreached: Vec<String> = graph
+        .nodes()
+        .filter(|module| is_production(module))
+        .map(GraphId::to_dotted)
+        .filter(|name| !reached.contains_key(name))
+        .collect();
+    unreached.sort();
+
+    TestReach {
+        tests: tests.into_iter().map(GraphId::to_dotted).collect(),
+        reached: reached
+            .into_iter()
+            .map(|(module, tests)| (module, tests.into_iter().collect()))
+            .collect(),
+        unreached,
+    }
+}
+
+/// Render the test reach as a human-readable report, the unreached modules first
+pub fn render_report(reach: &TestReach) -> String {
+    let production = reach.reached.len() + reach.unreached.len();
+    let mut output = format!(
+        "{} of {production} production modules reached by {} test module{}\n",
+        reach.reached.len(),
+        reach.tests.len(),
+        if reach.tests.len() == 1 { "" } else { "s" }
+    );
+    if !reach.unreached.is_empty() {
+        output.push_str("\nNot reached by any test:\n");
+        for module in &reach.unreached {
+            let _ = writeln!(output, "    {module}");
+        }
+    }
+    if !reach.reached.is_empty() {
+        output.push_str("\nReached:\n");
+        for (module, tests) in &reach.reached {
+            let _ = writeln!(
+                output,
+                "    {module} ({}): {}",
+                tests.len(),
+                tests.join(", ")
+            );
+        }
+    }
+    output
+}
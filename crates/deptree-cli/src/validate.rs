@@ -0,0 +1,199 @@
+//! Graph data linting (`validate` subcommand)
+//!
+//! Checks a GraphData JSON file (a `--format json` export, or a hand-written viewer
+//! fixture) for the mistakes the viewer does not reject but silently misrenders: edges
+//! to missing nodes, reused node ids, node types it does not know, `is_orphan` flags
+//! contradicting the edges, and `parent` references that lead nowhere or in a circle.
+
+use deptree_graph::{GraphData, GraphDataError};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Errors that can occur while reading a graph to validate
+#[derive(Error, Debug)]
+pub enum ValidateError {
+    #[error("Failed to read graph {0}: {1}")]
+    ReadError(PathBuf, std::io::Error),
+}
+
+/// Node types the viewer knows how to draw
+const NODE_TYPES: [&str; 7] = [
+    "module",
+    "script",
+    "namespace",
+    "extension",
+    "namespace_group",
+    "community",
+    "group",
+];
+
+/// Node types that only group other nodes and are never orphans
+const GROUP_TYPES: [&str; 3] = ["namespace_group", "community", "group"];
+
+/// Index of the element a problem concerns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Location {
+    Node(usize),
+    Edge(usize),
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Location::Node(index) => write!(f, "nodes[{index}]"),
+            Location::Edge(index) => write!(f, "edges[{index}]"),
+        }
+    }
+}
+
+/// One mistake in a graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub location: Location,
+    pub message: String,
+}
+
+impl Problem {
+    fn node(index: usize, message: String) -> Self {
+        Problem {
+            location: Location::Node(index),
+            message,
+        }
+    }
+
+    fn edge(index: usize, message: String) -> Self {
+        Problem {
+            location: Location::Edge(index),
+            message,
+        }
+    }
+}
+
+/// Read the graph at `path`; the inner error is for a file that is not graph data at all,
+/// which is reported like a problem, so one bad fixture does not hide the others
+pub fn load(path: &Path) -> Result<Result<GraphData, GraphDataError>, ValidateError> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| ValidateError::ReadError(path.to_path_buf(), e))?;
+    Ok(GraphData::from_json(&json))
+}
+
+/// Problems of `data`, nodes first, in the order of the elements they concern
+pub fn validate(data: &GraphData) -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    let mut first_index: HashMap<&str, usize> = HashMap::new();
+    for (index, node) in data.nodes.iter().enumerate() {
+        if let Some(first) = first_index.get(node.id.as_str()) {
+            problems.push(Problem::node(
+                index,
+                format!("duplicate id '{}' (first used by nodes[{first}])", node.id),
+            ));
+        } else {
+            first_index.insert(&node.id, index);
+        }
+    }
+    let by_id: HashMap<&str, &deptree_graph::GraphNode> = first_index
+        .iter()
+        .map(|(id, &index)| (*id, &data.nodes[index]))
+        .collect();
+
+    let mut connected: HashSet<&str> = HashSet::new();
+    for (index, edge) in data.edges.iter().enumerate() {
+        for (end, id) in [("source", &edge.source), ("target", &edge.target)] {
+            if !by_id.contains_key(id.as_str()) {
+                problems.push(Problem::edge(index, format!("{end} '{id}' is not a node")));
+            }
+        }
+        connected.insert(&edge.source);
+        connected.insert(&edge.target);
+    }
+
+    for (index, node) in data.nodes.iter().enumerate() {
+        let node_type = node.node_type.as_str();
+        if !NODE_TYPES.contains(&node_type) {
+            problems.push(Problem::node(
+                index,
+                format!(
+                    "'{}' has unknown type '{node_type}' (expected one of: {})",
+                    node.id,
+                    NODE_TYPES.join(", ")
+                ),
+            ));
+        }
+        // Graph data flags the orphans of the whole graph, so an unflagged node without
+        // edges may just have lost them to a filter; a flagged one with edges is wrong
+        if node.is_orphan && GROUP_TYPES.contains(&node_type) {
+            problems.push(Problem::node(
+                index,
+                format!(
+                    "'{}' is a {node_type} node but is flagged is_orphan",
+                    node.id
+                ),
+            ));
+        } else if node.is_orphan && connected.contains(node.id.as_str()) {
+            problems.push(Problem::node(
+                index,
+                format!("'{}' is flagged is_orphan but has edges", node.id),
+            ));
+        }
+        if let Some(parent) = &node.parent {
+            if !by_id.contains_key(parent.as_str()) {
+                problems.push(Problem::node(
+                    index,
+                    format!("'{}' has parent '{parent}', which is not a node", node.id),
+                ));
+            } else if is_own_ancestor(&by_id, &node.id) {
+                problems.push(Problem::node(
+                    index,
+                    format!(
+                        "'{}' is its own ancestor through parent references",
+                        node.id
+                    ),
+                ));
+            }
+        }
+    }
+
+    // Stable, so the problems of one element keep the order of the checks
+    problems.sort_by_key(|problem| problem.location);
+    problems
+}
+
+/// Whether following `parent` references from `id` leads back to it
+fn is_own_ancestor(by_id: &HashMap<&str, &deptree_graph::GraphNode>, id: &str) -> bool {
+    let mut seen = HashSet::new();
+    let mut current = id;
+    while let Some(parent) = by_id.get(current).and_then(|node| node.parent.as_deref()) {
+        if parent == id {
+            return true;
+        }
+        if !seen.insert(parent) {
+            // A loop above `id` that does not include it
+            return false;
+        }
+        current = parent;
+    }
+    false
+}
+
+/// One `<path>: <location>: <message>` line per problem, or a one-line summary of a
+/// valid graph
+pub fn render_report(path: &Path, data: &GraphData, problems: &[Problem]) -> String {
+    let mut output = String::new();
+    let path = path.display();
+    for problem in problems {
+        let _ = writeln!(output, "{path}: {}: {}", problem.location, problem.message);
+    }
+    if problems.is_empty() {
+        let count = |n: usize, noun: &str| format!("{n} {noun}{}", if n == 1 { "" } else { "s" });
+        let _ = writeln!(
+            output,
+            "{path}: valid ({}, {})",
+            count(data.nodes.len(), "node"),
+            count(data.edges.len(), "edge")
+        );
+    }
+    output
+}
@@ -0,0 +1,186 @@
+//! Integration tests for module annotations (`--annotations`, `--group-by`)
+
+use deptree_graph::{DependencyGraph, GraphId};
+use deptree_utils::annotations::{self, Annotations};
+use deptree_utils::go::{GoGraph, PackagePath};
+use serde_json::json;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+mod common;
+use common::{get_binary_path, python_graph};
+
+/// `team` annotation of each module after applying `{key: {"team": "core"}}`, sorted
+fn teams_covered_by<T: GraphId>(mut graph: DependencyGraph<T>, key: &str) -> Vec<String> {
+    let annotations: Annotations = [(
+        key.to_string(),
+        [("team".to_string(), "core".to_string())].into(),
+    )]
+    .into();
+    annotations::apply_to_graph(&mut graph, &annotations);
+    let mut teams: Vec<String> = graph
+        .nodes()
+        .map(|module| {
+            format!(
+                "{}: {:?}",
+                module.to_dotted(),
+                graph.annotation(module, "team")
+            )
+        })
+        .collect();
+    teams.sort();
+    teams
+}
+
+fn fixture_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("split_project")
+}
+
+fn annotations_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("annotations")
+        .join(name)
+}
+
+fn run(annotations: &str, args: &[&str]) -> Output {
+    Command::new(get_binary_path())
+        .arg("python")
+        .arg(fixture_path())
+        .arg("--annotations")
+        .arg(annotations_path(annotations))
+        .args(args)
+        .output()
+        .expect("Failed to execute command")
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+fn test_cli_annotations_json() {
+    let output = run("split_project.json", &["--format", "json"]);
+    let data: serde_json::Value = serde_json::from_str(&stdout(&output)).expect("Invalid JSON");
+    let nodes = data["nodes"].as_array().expect("nodes array");
+    let annotations = |id: &str| {
+        nodes
+            .iter()
+            .find(|n| n["id"] == id)
+            .unwrap_or_else(|| panic!("{id} node"))["annotations"]
+            .clone()
+    };
+
+    // The most specific entry wins per key; other keys are inherited
+    assert_eq!(
+        annotations("shop.core.auth"),
+        serde_json::json!({"team": "identity", "tier": "1", "service": "login"})
+    );
+    assert_eq!(
+        annotations("shop.core.cart"),
+        serde_json::json!({"team": "commerce", "tier": "2"})
+    );
+    assert_eq!(
+        annotations("shop.util"),
+        serde_json::json!({"team": "storefront", "tier": "2"})
+    );
+}
+
+#[test]
+fn test_cli_group_by_dot() {
+    let output = run("split_project.json", &["--group-by", "team"]);
+    insta::assert_snapshot!(stdout(&output));
+}
+
+#[test]
+fn test_cli_group_by_json() {
+    let output = run(
+        "split_project.json",
+        &["--group-by", "tier", "--format", "json"],
+    );
+    let data: serde_json::Value = serde_json::from_str(&stdout(&output)).expect("Invalid JSON");
+    let nodes = data["nodes"].as_array().expect("nodes array");
+
+    let groups: Vec<&str> = nodes
+        .iter()
+        .filter(|n| n["type"] == "group")
+        .filter_map(|n| n["id"].as_str())
+        .collect();
+    assert_eq!(groups, vec!["tier=1", "tier=2"]);
+
+    let auth = nodes
+        .iter()
+        .find(|n| n["id"] == "shop.core.auth")
+        .expect("shop.core.auth node");
+    assert_eq!(auth["parent"], "tier=1");
+}
+
+#[test]
+fn test_cli_group_by_requires_annotations() {
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(fixture_path())
+        .args(["--group-by", "team"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--annotations"), "stderr: {stderr}");
+}
+
+#[test]
+fn test_cli_annotations_rejects_nested_values() {
+    let output = run("invalid.json", &[]);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("annotation 'owners' of 'shop' must be a string, number, or boolean"),
+        "stderr: {stderr}"
+    );
+}
+
+#[test]
+fn test_parse_stringifies_scalars() {
+    let annotations =
+        annotations::from_json(json!({"app": {"team": "core", "tier": 1, "public": true}}));
+
+    insta::assert_debug_snapshot!(annotations);
+}
+
+#[test]
+fn test_parse_rejects_lists_and_non_objects() {
+    let errors = [
+        annotations::from_json(json!({"app": {"owners": ["a", "b"]}})),
+        annotations::from_json(json!(["app"])),
+    ];
+
+    insta::assert_debug_snapshot!(errors);
+}
+
+#[test]
+fn test_annotations_cover_dotted_modules_at_a_component_boundary() {
+    let graph = python_graph(&[("app.billing", "app"), ("application", "app")]);
+
+    insta::assert_debug_snapshot!(teams_covered_by(graph, "app"));
+}
+
+#[test]
+fn test_annotations_cover_path_like_packages() {
+    let package = |path: &str| PackagePath::from_import_path(path).unwrap();
+    let mut graph = GoGraph::new();
+    graph.add_dependency(package("example.com/svc/api"), package("example.com/svc"));
+    graph.add_dependency(package("example.com/svcx"), package("example.com/svc"));
+
+    insta::assert_debug_snapshot!(teams_covered_by(graph, "example.com/svc"));
+}
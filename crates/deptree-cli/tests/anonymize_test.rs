@@ -0,0 +1,42 @@
+//! Tests for the graph anonymizer (`anonymize`)
+
+use deptree_utils::anonymize::Anonymizer;
+use deptree_utils::python::{ModulePath, PythonGraph};
+use std::collections::{BTreeMap, HashMap};
+
+mod common;
+use common::{module, python_graph};
+
+/// Mapping and anonymized imports, by dotted name
+fn render(anonymized: &PythonGraph, mapping: &HashMap<ModulePath, ModulePath>) -> String {
+    let mapping: BTreeMap<String, String> = mapping
+        .iter()
+        .map(|(from, to)| (from.to_dotted(), to.to_dotted()))
+        .collect();
+    let mut edges: Vec<String> = anonymized
+        .edges()
+        .map(|(from, to, _)| format!("{} -> {}", from.to_dotted(), to.to_dotted()))
+        .collect();
+    edges.sort();
+    format!("{mapping:#?}\n{edges:#?}")
+}
+
+#[test]
+fn test_anonymize_keeps_nesting() {
+    let graph = python_graph(&[("app.api", "app.core")]);
+
+    let (anonymized, mapping) = Anonymizer::default().anonymize(&graph).unwrap();
+    insta::assert_snapshot!(render(&anonymized, &mapping));
+}
+
+#[test]
+fn test_anonymize_reuses_known_segments() {
+    let mut graph = python_graph(&[("app.api", "app.core")]);
+    let mut anonymizer = Anonymizer::default();
+    anonymizer.anonymize(&graph).unwrap();
+    graph.add_dependency(module("app.api"), module("lib"));
+
+    // A later run keeps the segments it knows and numbers new names after them
+    let (anonymized, mapping) = anonymizer.anonymize(&graph).unwrap();
+    insta::assert_snapshot!(render(&anonymized, &mapping));
+}
@@ -0,0 +1,38 @@
+//! Tests for the module badges (`badge`)
+
+use deptree_utils::badge::{BadgeMetric, badge};
+use deptree_utils::python::PythonGraph;
+
+mod common;
+use common::{module, python_graph};
+
+/// `lib` and `util` import each other, below `app` and `cli`
+fn badge_graph() -> PythonGraph {
+    python_graph(&[
+        ("app", "lib"),
+        ("cli", "app"),
+        ("lib", "util"),
+        ("util", "lib"),
+    ])
+}
+
+#[test]
+fn test_badge_counts_transitive_dependents() {
+    let badge = badge(&badge_graph(), &module("lib"), BadgeMetric::Dependents);
+
+    insta::assert_snapshot!(badge.to_shields_json());
+}
+
+#[test]
+fn test_badge_reports_the_cycle_of_a_module() {
+    let badge = badge(&badge_graph(), &module("util"), BadgeMetric::Cycle);
+
+    insta::assert_snapshot!(badge.to_shields_json());
+}
+
+#[test]
+fn test_badge_of_a_module_outside_cycles() {
+    let badge = badge(&badge_graph(), &module("cli"), BadgeMetric::Cycle);
+
+    insta::assert_snapshot!(badge.to_shields_json());
+}
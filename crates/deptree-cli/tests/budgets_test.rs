@@ -0,0 +1,68 @@
+//! Integration tests for dependency budgets declared in deptree.toml
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use deptree_utils::{budgets, config, polyglot};
+
+mod common;
+use common::get_binary_path;
+
+fn fixture_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("contracts_project")
+}
+
+fn load_fixture() -> (config::Config, polyglot::PolyglotGraph) {
+    let root = fixture_path();
+    let loaded = config::Config::load(&root.join("budgets.toml")).expect("Failed to load config");
+    let graph = polyglot::analyze_polyglot(&loaded, &root).expect("Failed to analyze project");
+    (loaded, graph)
+}
+
+#[test]
+fn test_budgets_report() {
+    let (loaded, graph) = load_fixture();
+    let results = budgets::check_budgets(&graph, &loaded.budgets).expect("Failed to check budgets");
+
+    insta::assert_snapshot!(budgets::render_report(&results));
+}
+
+#[test]
+fn test_budget_without_limit() {
+    let (_, graph) = load_fixture();
+    let budget = config::Budget {
+        name: "Unbounded".to_string(),
+        module: "python:app.api".to_string(),
+        max_upstream: None,
+        max_downstream: None,
+        max_fan_in: None,
+        max_fan_out: None,
+    };
+    let error =
+        budgets::check_budget(&graph, &budget).expect_err("Budgets need at least one limit");
+
+    insta::assert_snapshot!(error.to_string());
+}
+
+#[test]
+fn test_cli_check_exceeded_budget_exit_code() {
+    let output = Command::new(get_binary_path())
+        .arg("check")
+        .arg("--config")
+        .arg(fixture_path().join("budgets.toml"))
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(
+        output.status.code(),
+        Some(1),
+        "Exceeded budgets should exit with code 1: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    insta::assert_snapshot!(stdout);
+}
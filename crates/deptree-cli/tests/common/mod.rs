@@ -0,0 +1,25 @@
+//! Helpers shared by the integration tests
+// Each test crate compiles this module and uses only some of it
+#![allow(dead_code)]
+
+use deptree_utils::python::{ModulePath, PythonGraph};
+use std::path::PathBuf;
+
+/// The `deptree-utils` binary built for this test run
+pub fn get_binary_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_BIN_EXE_deptree-utils"))
+}
+
+/// A Python module from its dotted name
+pub fn module(name: &str) -> ModulePath {
+    ModulePath::from_dotted(name).expect("Invalid module name")
+}
+
+/// Python graph with one import per `(importer, imported)` pair
+pub fn python_graph(imports: &[(&str, &str)]) -> PythonGraph {
+    let mut graph = PythonGraph::new();
+    for (from, to) in imports {
+        graph.add_dependency(module(from), module(to));
+    }
+    graph
+}
@@ -0,0 +1,97 @@
+//! Integration tests for community detection output
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use deptree_utils::{communities, python};
+
+mod common;
+use common::get_binary_path;
+
+fn fixture_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("split_project")
+}
+
+fn run_communities(format: &str) -> String {
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(fixture_path())
+        .arg("--communities")
+        .arg("--format")
+        .arg(format)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+fn test_communities_report() {
+    let graph =
+        python::analyze_project(&fixture_path(), None, &[]).expect("Failed to analyze project");
+    let detected = communities::detect(&graph, false, false);
+
+    insta::assert_snapshot!(communities::render_report(&graph, &detected));
+}
+
+#[test]
+fn test_communities_mismatches() {
+    let graph =
+        python::analyze_project(&fixture_path(), None, &[]).expect("Failed to analyze project");
+    let detected = communities::detect(&graph, false, false);
+    let mismatches = communities::find_mismatches(&graph, &detected);
+
+    assert!(mismatches.contains(&communities::Mismatch::SplitPackage {
+        package: "shop.core".to_string(),
+        communities: vec![1, 2],
+    }));
+}
+
+#[test]
+fn test_cli_communities_dot() {
+    insta::assert_snapshot!(run_communities("dot"));
+}
+
+#[test]
+fn test_cli_communities_json() {
+    let stdout = run_communities("json");
+    let data: serde_json::Value = serde_json::from_str(&stdout).expect("Invalid JSON");
+    let nodes = data["nodes"].as_array().expect("nodes array");
+
+    let compounds: Vec<&str> = nodes
+        .iter()
+        .filter(|n| n["type"] == "community")
+        .filter_map(|n| n["id"].as_str())
+        .collect();
+    assert_eq!(compounds, vec!["community 1", "community 2"]);
+
+    let cart = nodes
+        .iter()
+        .find(|n| n["id"] == "shop.core.cart")
+        .expect("shop.core.cart node");
+    assert_eq!(cart["parent"], "community 2");
+}
+
+#[test]
+fn test_cli_communities_rejects_selection() {
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(fixture_path())
+        .arg("--communities")
+        .arg("--downstream")
+        .arg("shop.util")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--communities cannot be combined"));
+}
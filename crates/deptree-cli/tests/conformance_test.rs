@@ -0,0 +1,112 @@
+//! Integration tests for the architecture conformance report
+
+use deptree_utils::conformance::{self, Architecture, ConformanceError};
+use std::path::PathBuf;
+use std::process::Command;
+
+mod common;
+use common::{get_binary_path, python_graph};
+
+/// Architecture loaded from `toml` written to a scratch file
+fn architecture(toml: &str) -> Result<Architecture, ConformanceError> {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("architecture.toml");
+    std::fs::write(&path, toml).unwrap();
+    Architecture::load(&path)
+}
+
+#[test]
+fn test_cli_conformance() {
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("split_project");
+    let dir = tempfile::tempdir().unwrap();
+    let architecture = dir.path().join("architecture.toml");
+    std::fs::write(
+        &architecture,
+        r#"
+[components]
+api = ["shop.api"]
+core = ["shop.core"]
+util = ["shop.util"]
+
+[[allow]]
+from = "api"
+to = ["core", "util"]
+"#,
+    )
+    .unwrap();
+    let conformance = |format: &str| {
+        let output = Command::new(get_binary_path())
+            .arg("conformance")
+            .arg(&architecture)
+            .arg(&fixture)
+            .args(["--format", format])
+            .output()
+            .expect("Failed to execute command");
+        assert!(
+            output.status.success(),
+            "Command failed: {:?}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    insta::assert_snapshot!("conformance_list", conformance("list"));
+    insta::assert_snapshot!("conformance_dot", conformance("dot"));
+
+    std::fs::write(
+        &architecture,
+        "[components]\napi = [\"shop.api\"]\n[[allow]]\nfrom = \"api\"\nto = [\"db\"]\n",
+    )
+    .unwrap();
+    let output = Command::new(get_binary_path())
+        .arg("conformance")
+        .arg(&architecture)
+        .arg(&fixture)
+        .output()
+        .expect("Failed to execute command");
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("Allowed dependency refers to 'db', which is not a declared component")
+    );
+}
+
+#[test]
+fn test_conformance_classifies_component_dependencies() {
+    let architecture = architecture(
+        r#"
+        [components]
+        ui = ["app.web"]
+        core = ["app.core"]
+        storage = ["app.core.db", "app.cache"]
+
+        [[allow]]
+        from = "ui"
+        to = ["core", "storage"]
+        "#,
+    )
+    .unwrap();
+    let graph = python_graph(&[
+        ("app.web.views", "app.core.models"),
+        ("app.core.models", "app.core.db.session"),
+        ("app.core.models", "app.web.forms"),
+        ("app.web.views", "app.web.forms"),
+        ("app.web.views", "vendor.json"),
+    ]);
+
+    // The longest package wins: app.core.db belongs to storage, not core
+    let conformance = conformance::conformance(&graph, &architecture);
+    insta::assert_snapshot!(conformance::render_report(&conformance));
+}
+
+#[test]
+fn test_conformance_rejects_unknown_allowed_components() {
+    let error =
+        architecture("[components]\nui = [\"app\"]\n[[allow]]\nfrom = \"ui\"\nto = [\"db\"]")
+            .unwrap_err();
+
+    insta::assert_snapshot!(error.to_string());
+}
@@ -0,0 +1,286 @@
+//! Integration tests for import contracts declared in deptree.toml
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use deptree_utils::{baseline, config, contracts, polyglot};
+
+mod common;
+use common::get_binary_path;
+
+fn fixture_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("contracts_project")
+}
+
+fn load_fixture() -> (config::Config, polyglot::PolyglotGraph) {
+    let root = fixture_path();
+    let loaded =
+        config::Config::load(&root.join(config::CONFIG_FILE_NAME)).expect("Failed to load config");
+    let graph = polyglot::analyze_polyglot(&loaded, &root).expect("Failed to analyze project");
+    (loaded, graph)
+}
+
+#[test]
+fn test_contracts_report() {
+    let (loaded, graph) = load_fixture();
+    let results =
+        contracts::check_contracts(&graph, &loaded.contracts).expect("Failed to check contracts");
+
+    insta::assert_snapshot!(contracts::render_report(&results));
+}
+
+#[test]
+fn test_contract_unknown_module() {
+    let (_, graph) = load_fixture();
+    let contract = config::Contract::Independence {
+        name: "Typo".to_string(),
+        modules: vec![
+            "python:app.billing".to_string(),
+            "python:app.shiping".to_string(),
+        ],
+    };
+    let error = contracts::check_contract(&graph, &contract)
+        .expect_err("Unknown modules should be rejected");
+
+    insta::assert_snapshot!(error.to_string());
+}
+
+#[test]
+fn test_cli_check_broken_contracts_exit_code() {
+    let output = Command::new(get_binary_path())
+        .arg("check")
+        .arg(fixture_path())
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(
+        output.status.code(),
+        Some(1),
+        "Broken contracts should exit with code 1: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    insta::assert_snapshot!(stdout);
+}
+
+#[test]
+fn test_cli_check_kept_contracts() {
+    let output = Command::new(get_binary_path())
+        .arg("check")
+        .arg("--config")
+        .arg(fixture_path().join("kept.toml"))
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    insta::assert_snapshot!(stdout);
+}
+
+#[test]
+fn test_baseline_from_results() {
+    let (loaded, graph) = load_fixture();
+    let results =
+        contracts::check_contracts(&graph, &loaded.contracts).expect("Failed to check contracts");
+    let recorded = baseline::Baseline::from_results(&results);
+
+    insta::assert_snapshot!(
+        serde_json::to_string_pretty(&recorded).expect("Failed to serialize baseline")
+    );
+}
+
+#[test]
+fn test_cli_check_with_baseline_reports_only_new_violations() {
+    let output = Command::new(get_binary_path())
+        .arg("check")
+        .arg(fixture_path())
+        .arg("--baseline")
+        .arg(fixture_path().join("baseline.json"))
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(
+        output.status.code(),
+        Some(1),
+        "New violations should still fail: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let combined = format!(
+        "stdout:\n{}\nstderr:\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    insta::assert_snapshot!(combined);
+}
+
+#[test]
+fn test_cli_update_baseline_tolerates_existing_violations() {
+    let baseline_path =
+        std::env::temp_dir().join(format!("deptree-baseline-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&baseline_path);
+
+    let update = Command::new(get_binary_path())
+        .arg("check")
+        .arg(fixture_path())
+        .arg("--baseline")
+        .arg(&baseline_path)
+        .arg("--update-baseline")
+        .output()
+        .expect("Failed to execute command");
+    assert!(
+        update.status.success(),
+        "Updating the baseline failed: {:?}",
+        String::from_utf8_lossy(&update.stderr)
+    );
+
+    let check = Command::new(get_binary_path())
+        .arg("check")
+        .arg(fixture_path())
+        .arg("--baseline")
+        .arg(&baseline_path)
+        .output()
+        .expect("Failed to execute command");
+    let _ = std::fs::remove_file(&baseline_path);
+
+    assert!(
+        check.status.success(),
+        "Baselined violations should pass: {:?}",
+        String::from_utf8_lossy(&check.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&check.stdout);
+    insta::assert_snapshot!(stdout);
+}
+
+#[test]
+fn test_cli_hook_reports_violations_through_staged_files() {
+    let run = |files: &[&str]| {
+        let output = Command::new(get_binary_path())
+            .current_dir(fixture_path())
+            .arg("hook")
+            .args(files)
+            .output()
+            .expect("Failed to execute command");
+        (
+            output.status.code(),
+            String::from_utf8_lossy(&output.stdout).to_string(),
+        )
+    };
+
+    let (status, report) = run(&["app/services/pricing.py", "README.md"]);
+    assert_eq!(status, Some(1));
+    insta::assert_snapshot!(report);
+
+    // Violations elsewhere in the graph don't block the commit
+    let (status, report) = run(&["app/services/orders.py"]);
+    assert_eq!(status, Some(0), "{report}");
+    assert!(
+        report.ends_with(
+            "PASS: no contract violation or exceeded budget involves the staged files\n"
+        )
+    );
+}
+
+#[test]
+fn test_cli_hook_reports_exceeded_budgets_through_staged_files() {
+    let run = |files: &[&str]| {
+        Command::new(get_binary_path())
+            .current_dir(fixture_path())
+            .arg("hook")
+            .arg("--config")
+            .arg(fixture_path().join("budgets.toml"))
+            .args(files)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let output = run(&["app/services/pricing.py"]);
+    assert_eq!(output.status.code(), Some(1));
+    insta::assert_snapshot!(String::from_utf8_lossy(&output.stdout));
+
+    // The exceeded budget counts no chain through the shipping rates
+    let output = run(&["app/shipping/rates.py"]);
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn test_cli_hook_maps_files_through_the_source_roots() {
+    let project = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("project_with_scripts");
+    let config_dir = tempfile::tempdir().unwrap();
+    let config_path = config_dir.path().join("deptree.toml");
+    std::fs::write(
+        &config_path,
+        format!("[python]\npath = {:?}\n", project.display().to_string()),
+    )
+    .unwrap();
+
+    // Copies of source-root modules elsewhere in the repository are not those modules
+    let output = Command::new(get_binary_path())
+        .current_dir(&project)
+        .arg("hook")
+        .arg("--config")
+        .arg(&config_path)
+        .args([
+            "src/foo/bar.py",
+            "scripts/blah.py",
+            "tests/foo/bar.py",
+            "foo/bar.py",
+            "README.md",
+        ])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    insta::assert_snapshot!(String::from_utf8_lossy(&output.stdout));
+}
+
+#[test]
+fn test_cli_hook_reads_staged_files_from_git() {
+    let repo = tempfile::tempdir().unwrap();
+    for entry in walkdir::WalkDir::new(fixture_path()) {
+        let entry = entry.unwrap();
+        let relative = entry.path().strip_prefix(fixture_path()).unwrap();
+        let target = repo.path().join(relative);
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&target).unwrap();
+        } else {
+            std::fs::copy(entry.path(), &target).unwrap();
+        }
+    }
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(repo.path())
+            .args(args)
+            .status()
+            .expect("Failed to run git");
+        assert!(status.success(), "git {args:?} failed");
+    };
+    git(&["init", "--quiet"]);
+    git(&["add", "app/billing/charge.py"]);
+
+    let output = Command::new(get_binary_path())
+        .current_dir(repo.path())
+        .arg("hook")
+        .output()
+        .expect("Failed to execute command");
+    let report = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(1), "{report}");
+    assert!(
+        report.contains("BROKEN Features are independent [independence]"),
+        "{report}"
+    );
+    assert!(!report.contains("Layered architecture"), "{report}");
+}
@@ -0,0 +1,36 @@
+//! Tests for the team coupling report (`--team-coupling`)
+
+use deptree_graph::GraphId;
+use deptree_utils::coupling;
+
+mod common;
+use common::python_graph;
+
+#[test]
+fn test_team_coupling_counts_pairs_and_hotspots() {
+    let mut graph = python_graph(&[
+        ("billing.api", "billing.models"),
+        ("billing.api", "search.index"),
+        ("billing.api", "platform.db"),
+        ("billing.models", "platform.db"),
+        ("search.index", "platform.db"),
+        ("search.index", "search.tokens"),
+        ("search.index", "vendor.lib"),
+    ]);
+    for (prefix, team) in [
+        ("billing", "payments"),
+        ("search", "search"),
+        ("platform", "platform"),
+    ] {
+        for node in graph.nodes().cloned().collect::<Vec<_>>() {
+            if node.segments()[0] == prefix {
+                graph.set_annotation(&node, "team", team);
+            }
+        }
+    }
+
+    // billing.models only imports platform; billing.api has 2 of 3 foreign dependencies;
+    // search.index is split evenly between teams, so not a hotspot
+    let coupling = coupling::team_coupling(&graph, "team");
+    insta::assert_snapshot!(coupling::render_report(&coupling));
+}
@@ -0,0 +1,26 @@
+//! Tests for the critical import path report (`--critical-path`)
+
+use deptree_utils::critical;
+
+mod common;
+use common::{module, python_graph};
+
+#[test]
+fn test_critical_path_report_counts_cycles_once() {
+    let graph = python_graph(&[
+        ("app.main", "app.cli"),
+        ("app.cli", "app.a"),
+        ("app.a", "app.b"),
+        ("app.b", "app.a"),
+        ("app.b", "app.db"),
+    ]);
+
+    insta::assert_snapshot!(critical::render_report(&graph, &module("app.main")).unwrap());
+}
+
+#[test]
+fn test_critical_path_of_unknown_entry() {
+    let graph = python_graph(&[("app.main", "app.cli")]);
+
+    assert_eq!(critical::render_report(&graph, &module("app.gone")), None);
+}
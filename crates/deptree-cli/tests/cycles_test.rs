@@ -0,0 +1,31 @@
+//! Tests for the cycle baseline check (`--forbid-new-cycles`)
+
+use deptree_utils::cycles;
+
+mod common;
+use common::python_graph;
+
+#[test]
+fn test_new_cycles_ignore_baselined_and_shrunk_cycles() {
+    let baseline = python_graph(&[("a", "b"), ("b", "c"), ("c", "a"), ("x", "y")]);
+    // a-b-c shrinks to a-b: still tolerated
+    let current = python_graph(&[("a", "b"), ("b", "a"), ("x", "y")]);
+
+    assert!(cycles::new_cycles(&current, &baseline).is_empty());
+}
+
+#[test]
+fn test_new_cycles_report_introduced_and_grown_cycles() {
+    let baseline = python_graph(&[("a", "b"), ("b", "a"), ("x", "y")]);
+    let current = python_graph(&[
+        ("a", "b"),
+        ("b", "a"),
+        ("b", "c"),
+        ("c", "a"),
+        ("x", "y"),
+        ("y", "x"),
+    ]);
+
+    let new = cycles::new_cycles(&current, &baseline);
+    insta::assert_snapshot!(cycles::render_report(&current, &new, 1));
+}
@@ -0,0 +1,76 @@
+//! Tests for the dead-code candidates (`--dead-code`)
+
+use deptree_utils::deadcode;
+use deptree_utils::emit::{AnalysisTrace, DynamicImport, DynamicName, FileTrace};
+use deptree_utils::python::PythonGraph;
+
+mod common;
+use common::{module, python_graph};
+
+fn file(module: &str, line: usize, name: DynamicName) -> FileTrace {
+    FileTrace {
+        file: format!("{}.py", module.replace('.', "/")),
+        module: module.to_string(),
+        kind: "module",
+        recovered: false,
+        imports: Vec::new(),
+        dynamic_imports: vec![DynamicImport { line, name }],
+    }
+}
+
+fn project() -> (PythonGraph, AnalysisTrace) {
+    let mut graph = python_graph(&[
+        ("app.__main__", "app.cli"),
+        ("app", "app.registry"),
+        ("legacy", "app.cli"),
+    ]);
+    for name in ["app.loader", "plugins", "plugins.csv", "old"] {
+        graph.ensure_node(module(name));
+    }
+    let trace = AnalysisTrace {
+        files: vec![
+            // Loaded through the package of `app.__main__`
+            file(
+                "app.registry",
+                3,
+                DynamicName::Literal {
+                    module: "app.loader".to_string(),
+                },
+            ),
+            file(
+                "app.loader",
+                7,
+                DynamicName::Computed {
+                    prefix: "plugins.".to_string(),
+                },
+            ),
+            // Never loaded, so its unbounded import does not count
+            file(
+                "legacy",
+                1,
+                DynamicName::Computed {
+                    prefix: String::new(),
+                },
+            ),
+        ],
+        ..AnalysisTrace::default()
+    };
+    (graph, trace)
+}
+
+#[test]
+fn test_dead_code_follows_packages_and_dynamic_imports() {
+    let (graph, trace) = project();
+
+    let report = deadcode::dead_code(&graph, &trace, &[]);
+    insta::assert_snapshot!(deadcode::render_report(&report));
+}
+
+#[test]
+fn test_dead_code_loads_declared_entry_points() {
+    let (graph, trace) = project();
+
+    // legacy.main names an attribute of legacy, whose unbounded import then counts
+    let report = deadcode::dead_code(&graph, &trace, &[module("legacy.main")]);
+    insta::assert_snapshot!(deadcode::render_report(&report));
+}
@@ -0,0 +1,116 @@
+//! Integration tests for graph diffs
+
+use deptree_graph::{GRAPH_DATA_VERSION, GraphData, GraphEdge, GraphNode};
+use deptree_utils::diff;
+use std::path::PathBuf;
+use std::process::Command;
+
+mod common;
+use common::get_binary_path;
+
+fn fixture(name: &str) -> String {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("diff")
+        .join(name)
+        .display()
+        .to_string()
+}
+
+fn run_diff(args: &[&str]) -> String {
+    let output = Command::new(get_binary_path())
+        .arg("diff")
+        .arg(fixture("old.json"))
+        .arg(fixture("new.json"))
+        .args(args)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+fn test_diff_dot() {
+    insta::assert_snapshot!(run_diff(&[]));
+}
+
+#[test]
+fn test_diff_list() {
+    insta::assert_snapshot!(run_diff(&["--format", "list"]));
+}
+
+#[test]
+#[cfg(feature = "html")]
+fn test_diff_cytoscape_marks_changes() {
+    let html = run_diff(&["--format", "cytoscape"]);
+    assert!(html.contains(r#""id":"shop.cache""#), "{html}");
+    assert!(html.contains(r#""change":"added""#));
+    assert!(html.contains(r#""change":"removed""#));
+    // Package groups are kept for nesting, without a change of their own
+    assert!(html.contains(r#"{"id":"shop","type":"namespace_group","is_orphan":false}"#));
+}
+
+#[test]
+fn test_diff_rejects_missing_graph() {
+    let output = Command::new(get_binary_path())
+        .args(["diff", "does-not-exist.json"])
+        .arg(fixture("new.json"))
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Failed to read graph"));
+}
+
+fn graph(nodes: &[&str], edges: &[(&str, &str)]) -> GraphData {
+    GraphData {
+        version: GRAPH_DATA_VERSION,
+        nodes: nodes
+            .iter()
+            .map(|id| GraphNode {
+                id: id.to_string(),
+                node_type: "module".to_string(),
+                is_orphan: false,
+                highlighted: None,
+                parent: None,
+                import_time_us: None,
+                coverage: None,
+                category: None,
+                lifecycle: None,
+                generated: None,
+                change: None,
+                annotations: None,
+                position: None,
+                style: None,
+                href: None,
+            })
+            .collect(),
+        edges: edges
+            .iter()
+            .map(|(source, target)| GraphEdge {
+                source: source.to_string(),
+                target: target.to_string(),
+                kind: None,
+                tags: None,
+                count: None,
+                change: None,
+                style: None,
+            })
+            .collect(),
+        config: None,
+    }
+}
+
+#[test]
+fn test_diff_classifies_nodes_and_edges() {
+    let old = graph(&["a", "b", "c"], &[("a", "b"), ("b", "c")]);
+    let new = graph(&["a", "b", "d"], &[("a", "b"), ("a", "d")]);
+
+    insta::assert_snapshot!(diff::render_dot(&diff::diff(&old, &new)));
+}
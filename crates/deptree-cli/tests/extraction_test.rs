@@ -0,0 +1,64 @@
+//! Integration tests for the extraction feasibility check
+
+use deptree_utils::extraction;
+use deptree_utils::python::PythonGraph;
+use std::path::PathBuf;
+use std::process::Command;
+
+mod common;
+use common::{get_binary_path, module, python_graph};
+
+/// `lib` is imported by `app` and imports back only through a cycle
+fn lib_graph() -> PythonGraph {
+    python_graph(&[
+        ("app.api", "lib.models"),
+        ("app.web", "lib.models"),
+        ("lib.models", "lib.db"),
+        ("lib.db", "app.settings"),
+        ("app.settings", "lib.db"),
+    ])
+}
+
+#[test]
+fn test_cli_extract_check() {
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("split_project");
+    let extract_check = |package: &str| {
+        Command::new(get_binary_path())
+            .args(["extract-check", package])
+            .arg(&fixture)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let output = extract_check("shop.core");
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    insta::assert_snapshot!(String::from_utf8_lossy(&output.stdout));
+
+    let output = extract_check("billing");
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("Package 'billing' does not match any analyzed module")
+    );
+}
+
+#[test]
+fn test_extraction_suggests_the_direction_with_fewer_imports_against_it() {
+    let extraction = extraction::extraction(&lib_graph(), &module("lib")).unwrap();
+
+    insta::assert_snapshot!(extraction::render_report(&extraction));
+}
+
+#[test]
+fn test_extraction_of_an_importing_module_is_an_application() {
+    let extraction = extraction::extraction(&lib_graph(), &module("app.api")).unwrap();
+
+    insta::assert_snapshot!(extraction::render_report(&extraction));
+}
@@ -0,0 +1,157 @@
+//! Integration tests for rendering a shared viewer filter state (`--filter-state`)
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use deptree_graph::filters::{EdgeDirection, FilterConfig};
+
+mod common;
+use common::get_binary_path;
+
+fn fixture_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("project_with_scripts")
+}
+
+/// Viewer defaults: everything shown, nothing selected
+fn default_view() -> FilterConfig {
+    FilterConfig {
+        show_orphans: true,
+        show_namespaces: true,
+        exclude_patterns: vec![],
+        upstream_roots: vec![],
+        downstream_roots: vec![],
+        max_distance: None,
+        highlighted_only: true,
+        highlight_neighbors: 0,
+        fade_filtered: false,
+        edge_direction: EdgeDirection::Both,
+        weight_key: None,
+    }
+}
+
+fn run(args: &[&str]) -> Output {
+    Command::new(get_binary_path())
+        .arg("python")
+        .arg(fixture_path())
+        .args(args)
+        .output()
+        .expect("Failed to execute command")
+}
+
+fn run_ok(args: &[&str]) -> String {
+    let output = run(args);
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn test_filter_state_matches_equivalent_flags() {
+    let view = FilterConfig {
+        downstream_roots: vec!["foo.bar".to_string()],
+        max_distance: Some(1),
+        ..default_view()
+    };
+
+    assert_eq!(
+        run_ok(&["--filter-state", &view.encode(), "--format", "list"]),
+        run_ok(&[
+            "--downstream",
+            "foo.bar",
+            "--max-rank",
+            "1",
+            "--format",
+            "list"
+        ])
+    );
+}
+
+#[test]
+fn test_filter_state_excludes_scripts() {
+    let view = FilterConfig {
+        exclude_patterns: vec!["*runner*".to_string()],
+        ..default_view()
+    };
+
+    insta::assert_snapshot!(run_ok(&[
+        "--filter-state",
+        &view.encode(),
+        "--format",
+        "mermaid"
+    ]));
+}
+
+#[test]
+fn test_filter_state_shows_all_when_not_highlighted_only() {
+    let view = FilterConfig {
+        upstream_roots: vec!["scripts.blah".to_string()],
+        highlighted_only: false,
+        ..default_view()
+    };
+
+    assert_eq!(
+        run_ok(&["--filter-state", &view.encode(), "--format", "mermaid"]),
+        run_ok(&[
+            "--upstream",
+            "scripts.blah",
+            "--show-all",
+            "--format",
+            "mermaid"
+        ])
+    );
+}
+
+#[test]
+fn test_filter_state_conflicts_with_selection_flags() {
+    let output = run(&[
+        "--filter-state",
+        &default_view().encode(),
+        "--upstream",
+        "foo.bar",
+    ]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used with"));
+}
+
+#[test]
+fn test_filter_state_rejects_garbage() {
+    let output = run(&["--filter-state", "not-a-filter-state"]);
+
+    assert!(!output.status.success());
+    insta::assert_snapshot!(String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn test_filter_state_weight_key_measures_weighted_distance() {
+    let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures");
+    let view = FilterConfig {
+        upstream_roots: vec!["shop.api".to_string()],
+        max_distance: Some(3),
+        weight_key: Some("loc".to_string()),
+        ..default_view()
+    };
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(fixtures.join("split_project"))
+        .arg("--annotations")
+        .arg(fixtures.join("annotations").join("loc.json"))
+        .args(["--filter-state", &view.encode(), "--format", "list"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    // shop.core.users is 4 lines of imports away through shop.core.auth
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "shop.api\nshop.core.auth\n"
+    );
+}
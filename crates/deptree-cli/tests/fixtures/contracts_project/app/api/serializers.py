@@ -0,0 +1,2 @@
+def serialize(value):
+    return dict(value)
@@ -0,0 +1,5 @@
+from app.services import orders
+
+
+def list_orders():
+    return orders.all_orders()
@@ -0,0 +1,5 @@
+from app.shipping.rates import shipping_rate
+
+
+def charge(order):
+    return shipping_rate(order)
@@ -0,0 +1,5 @@
+from app.services import pricing
+
+
+class Invoice:
+    total = pricing.price
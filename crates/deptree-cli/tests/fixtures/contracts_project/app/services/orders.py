@@ -0,0 +1,5 @@
+from app.models.order import Order
+
+
+def all_orders():
+    return [Order()]
@@ -0,0 +1,5 @@
+from app.api.serializers import serialize
+
+
+def price(order):
+    return serialize(order)
@@ -0,0 +1,2 @@
+def shipping_rate(order):
+    return 0
@@ -0,0 +1,3 @@
+from numeric.core import Vector
+
+__all__ = ["Vector"]
@@ -0,0 +1 @@
+SCALE = 1.0
@@ -0,0 +1,20 @@
+# cython: language_level=3
+"""Vector math; `import numeric.unused` in this docstring is not an import."""
+
+from libc.math cimport sqrt
+cimport numpy as cnp
+from numeric.kernels cimport dot
+import numeric.utils
+
+
+cdef double norm(double[:] v):
+    from numeric import config
+
+    return sqrt(dot(v, v)) * config.SCALE
+
+
+cdef class Vector:
+    cdef double[:] data
+
+    def length(self):
+        return norm(self.data)
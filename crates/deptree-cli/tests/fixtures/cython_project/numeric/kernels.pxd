@@ -0,0 +1 @@
+cdef double dot(double[:] a, double[:] b)
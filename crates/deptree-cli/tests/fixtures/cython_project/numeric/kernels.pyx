@@ -0,0 +1,8 @@
+from .utils import clamp
+
+
+cpdef double dot(double[:] a, double[:] b):
+    cdef double total = 0
+    for i in range(a.shape[0]):
+        total += a[i] * b[i]
+    return clamp(total)
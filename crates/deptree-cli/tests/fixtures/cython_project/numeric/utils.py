@@ -0,0 +1,2 @@
+def clamp(value):
+    return max(value, 0.0)
@@ -0,0 +1,5 @@
+module example.com/shop
+
+go 1.22
+
+require github.com/acme/lib v1.0.0
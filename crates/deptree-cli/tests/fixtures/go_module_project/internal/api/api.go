@@ -0,0 +1,15 @@
+// Package api exposes the HTTP handlers.
+package api
+
+import (
+	"net/http"
+
+	"example.com/shop/internal/store"
+	money "example.com/shop/pkg/money" // formatting helpers
+)
+
+var _ http.Handler
+
+func Handler() string {
+	return money.Format(store.Total())
+}
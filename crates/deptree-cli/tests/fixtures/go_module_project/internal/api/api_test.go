@@ -0,0 +1,11 @@
+package api
+
+import (
+	"testing"
+
+	"example.com/shop/internal/testutil"
+)
+
+func TestHandler(t *testing.T) {
+	testutil.Reset()
+}
@@ -0,0 +1,12 @@
+package db
+
+import (
+	"database/sql"
+	_ "github.com/acme/lib"
+)
+
+var _ *sql.DB
+
+func Count() int {
+	return 0
+}
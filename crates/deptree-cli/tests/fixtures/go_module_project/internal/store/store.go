@@ -0,0 +1,8 @@
+package store
+
+import "example.com/shop/internal/store/db"
+import . "example.com/shop/pkg/money"
+
+func Total() Cents {
+	return Cents(db.Count())
+}
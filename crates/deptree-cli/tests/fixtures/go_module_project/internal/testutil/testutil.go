@@ -0,0 +1,7 @@
+package testutil
+
+import "example.com/shop/internal/store/db"
+
+func Reset() {
+	_ = db.Count()
+}
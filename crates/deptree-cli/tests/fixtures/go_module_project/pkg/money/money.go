@@ -0,0 +1,9 @@
+package money
+
+import "fmt"
+
+type Cents int
+
+func Format(c Cents) string {
+	return fmt.Sprintf("$%d.%02d", c/100, c%100)
+}
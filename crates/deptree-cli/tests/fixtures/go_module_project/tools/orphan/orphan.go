@@ -0,0 +1,7 @@
+package orphan
+
+/*
+import "example.com/shop/pkg/money"
+*/
+
+func Unused() {}
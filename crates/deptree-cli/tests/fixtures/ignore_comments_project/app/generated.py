@@ -0,0 +1,5 @@
+#!/usr/bin/env python
+# Generated by protoc; do not edit.
+# deptree: ignore-file
+
+from app import core, utils
@@ -0,0 +1,12 @@
+import app.core
+import app.generated
+import app.legacy  # deptree: ignore
+from app import (
+    utils,  # deptree: ignore
+)
+
+
+def run():
+    from app import plugins  # deptree: ignore
+
+    return plugins
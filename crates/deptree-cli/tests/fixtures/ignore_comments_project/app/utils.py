@@ -0,0 +1 @@
+MESSAGE = "# deptree: ignore"
@@ -0,0 +1,2 @@
+from plugins import render_pdf
+from sdk import Client, Session, __version__
@@ -0,0 +1,17 @@
+"""Exporters, loaded on first use"""
+
+import importlib
+
+submod_attrs: dict[str, list[str]] = {
+    "csv_export": ["CsvExporter"],
+    "pdf_export": ["PdfExporter", "render_pdf"],
+}
+
+_attr_to_module = {attr: module for module, attrs in submod_attrs.items() for attr in attrs}
+
+
+def __getattr__(name):
+    module = _attr_to_module.get(name)
+    if module is None:
+        raise AttributeError(name)
+    return getattr(importlib.import_module(f".{module}", __name__), name)
@@ -0,0 +1,4 @@
+class PdfExporter: ...
+
+
+def render_pdf(): ...
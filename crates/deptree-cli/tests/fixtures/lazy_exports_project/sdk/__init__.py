@@ -0,0 +1,17 @@
+"""SDK with lazily imported submodules (PEP 562)"""
+
+import importlib
+
+__version__ = "1.0"
+
+_LAZY_IMPORTS = {
+    "Client": ".client",
+    "Session": "sdk.session:Session",
+}
+
+
+def __getattr__(name):
+    if name in _LAZY_IMPORTS:
+        module, _, attribute = _LAZY_IMPORTS[name].partition(":")
+        return getattr(importlib.import_module(module, __name__), attribute or name)
+    raise AttributeError(name)
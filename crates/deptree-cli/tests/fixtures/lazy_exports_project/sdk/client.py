@@ -0,0 +1,5 @@
+from sdk.session import Session
+
+
+class Client:
+    session = Session
@@ -0,0 +1,3 @@
+from app import core
+from app.labs import vectors
+from app.legacy import auth
@@ -0,0 +1 @@
+from app.legacy.auth import check_token
@@ -0,0 +1,2 @@
+# deptree: lifecycle=experimental
+"""Playground for features that may change without notice"""
@@ -0,0 +1,2 @@
+from app import core
+from app.legacy.auth import check_token
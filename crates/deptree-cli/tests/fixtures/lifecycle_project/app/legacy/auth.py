@@ -0,0 +1 @@
+def check_token(token): ...
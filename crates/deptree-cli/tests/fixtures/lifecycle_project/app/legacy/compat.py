@@ -0,0 +1,3 @@
+# Kept for old clients.
+# deptree: lifecycle=stable
+from app.legacy import auth
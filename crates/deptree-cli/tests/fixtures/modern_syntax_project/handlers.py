@@ -0,0 +1,5 @@
+Callback = object
+
+
+def default(request):
+    return request
@@ -0,0 +1,15 @@
+"""Dispatches requests with syntax that needs Python 3.12."""
+
+import handlers
+
+type Handler = handlers.Callback
+
+
+def route(request):
+    match request.kind:
+        case "report":
+            from reports import render
+
+            return render(request)
+        case _:
+            return handlers.default(request)
@@ -0,0 +1,4 @@
+# Generated code, importable as company.protos.user_pb2
+from company.protos import common_pb2
+
+User = common_pb2.Message
@@ -0,0 +1,3 @@
+from company_app import service
+
+service.lookup(1)
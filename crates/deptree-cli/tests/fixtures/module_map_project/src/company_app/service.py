@@ -0,0 +1,6 @@
+import vendored.client
+from company.protos import user_pb2
+
+
+def lookup(user_id):
+    return vendored.client.get(user_pb2.User, user_id)
@@ -0,0 +1,2 @@
+def get(kind, key):
+    return kind, key
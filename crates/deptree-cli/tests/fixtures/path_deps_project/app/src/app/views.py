@@ -0,0 +1,9 @@
+import requests
+
+from metrics import counters
+from shared.text import slugify
+
+
+def show(title):
+    counters.increment("views")
+    return slugify(title)
@@ -0,0 +1,7 @@
+from shared import text
+
+COUNTS = {}
+
+
+def increment(name):
+    COUNTS[text.slugify(name)] = COUNTS.get(name, 0) + 1
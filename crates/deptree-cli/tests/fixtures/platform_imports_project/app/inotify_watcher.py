@@ -0,0 +1 @@
+"""inotify watcher helpers"""
@@ -0,0 +1,14 @@
+import os
+import sys
+
+from app import config
+
+if sys.platform == "win32":
+    from app import win_console as console
+else:
+    from app import posix_console as console
+
+if os.name == "nt":
+    from app import registry
+elif sys.platform.startswith("linux"):
+    from app import inotify_watcher
@@ -0,0 +1 @@
+"""posix console helpers"""
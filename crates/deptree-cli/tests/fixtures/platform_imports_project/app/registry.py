@@ -0,0 +1 @@
+"""registry helpers"""
@@ -0,0 +1 @@
+"""win console helpers"""
@@ -0,0 +1,5 @@
+from svc.client_gen import OrdersClient
+
+
+def list_orders():
+    return OrdersClient()
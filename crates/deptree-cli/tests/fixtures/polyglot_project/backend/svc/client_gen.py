@@ -0,0 +1,5 @@
+"""Generated from protos/svc/v1."""
+
+
+class OrdersClient:
+    pass
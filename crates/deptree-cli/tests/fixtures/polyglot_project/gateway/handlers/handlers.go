@@ -0,0 +1,5 @@
+package handlers
+
+import "example.com/gateway/rpc"
+
+var Client rpc.InvoiceClient
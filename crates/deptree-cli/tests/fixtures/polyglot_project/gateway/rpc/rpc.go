@@ -0,0 +1,3 @@
+package rpc
+
+type InvoiceClient struct{}
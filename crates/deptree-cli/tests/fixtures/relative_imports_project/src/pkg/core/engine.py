@@ -0,0 +1 @@
+from ..settings import DEBUG
@@ -0,0 +1,3 @@
+from . import settings
+from .missing import thing
+from ... import toplevel
@@ -0,0 +1 @@
+DEBUG = False
@@ -0,0 +1,4 @@
+import app.db.models
+from app import db
+from . import db as database
+import app.util
@@ -0,0 +1,6 @@
+import importlib
+
+
+def load(name):
+    importlib.import_module("app.db.models")
+    return importlib.import_module(f"app.{name}")
@@ -0,0 +1 @@
+import company.core
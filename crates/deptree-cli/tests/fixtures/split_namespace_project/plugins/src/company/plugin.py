@@ -0,0 +1,2 @@
+from company import core
+from company.ext import hooks
@@ -0,0 +1 @@
+from company import plugin
@@ -0,0 +1 @@
+from shop.core import auth, checkout
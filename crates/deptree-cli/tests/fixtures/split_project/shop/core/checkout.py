@@ -0,0 +1 @@
+from shop.core import cart, pricing, users
@@ -0,0 +1 @@
+from shop import util
@@ -0,0 +1 @@
+USERS = {}
@@ -0,0 +1,13 @@
+"""Valid imports followed by a syntax error further down."""
+
+import valid_module
+
+
+def helper():
+    from another_valid import also_works
+
+    return also_works()
+
+
+def broken(:
+    return "this won't parse"
@@ -0,0 +1,86 @@
+//! Tests for generated code detection
+
+use deptree_utils::generated::{GitAttributes, is_generated};
+use std::path::Path;
+
+/// Whether the anchored gitattributes `pattern` matches `path`
+fn glob_matches(pattern: &str, path: &str) -> bool {
+    GitAttributes::parse(&format!("/{pattern} linguist-generated"))
+        .generated(Path::new(path))
+        .is_some()
+}
+
+#[test]
+fn test_glob_match() {
+    let matched: Vec<String> = [
+        ("*.py", "api_pb2.py"),
+        ("*.py", "gen/api_pb2.py"),
+        ("gen/**", "gen/protos/api.py"),
+        ("**/gen/*.py", "gen/api.py"),
+        ("**/gen/*.py", "src/app/gen/api.py"),
+        ("**/gen/*.py", "src/app/gen/v1/api.py"),
+        ("api_v?.py", "api_v2.py"),
+    ]
+    .into_iter()
+    .map(|(pattern, path)| format!("{pattern} {path}: {}", glob_matches(pattern, path)))
+    .collect();
+
+    insta::assert_debug_snapshot!(matched);
+}
+
+#[test]
+fn test_gitattributes_last_matching_rule_wins() {
+    let attributes = GitAttributes::parse(
+        "# generated clients\n\
+         src/clients/** linguist-generated=true\n\
+         src/clients/handwritten.py -linguist-generated\n\
+         *.py text eol=lf\n\
+         models.py linguist-generated\n",
+    );
+
+    let generated: Vec<String> = [
+        "src/clients/api.py",
+        "src/clients/handwritten.py",
+        "src/app/models.py",
+        "src/app/views.py",
+    ]
+    .into_iter()
+    .map(|path| format!("{path}: {:?}", attributes.generated(Path::new(path))))
+    .collect();
+    insta::assert_debug_snapshot!(generated);
+}
+
+#[test]
+fn test_is_generated() {
+    let none = GitAttributes::default();
+    let generated: Vec<String> = [
+        ("protos/api_pb2.py", ""),
+        ("protos/api_pb2_grpc.py", ""),
+        ("app/schema_generated.py", ""),
+        (
+            "app/schema.py",
+            "#!/usr/bin/env python\n# @generated by codegen\nimport a\n",
+        ),
+        // Only the header counts
+        ("app/schema.py", "import a\n# @generated\n"),
+    ]
+    .into_iter()
+    .map(|(path, source)| {
+        let generated = is_generated(Path::new(path), source, &none);
+        format!("{path} {source:?}: {generated}")
+    })
+    .collect();
+
+    insta::assert_debug_snapshot!(generated);
+}
+
+#[test]
+fn test_gitattributes_override_generated_names() {
+    let attributes = GitAttributes::parse("api_pb2.py -linguist-generated\n");
+
+    assert!(!is_generated(
+        Path::new("protos/api_pb2.py"),
+        "",
+        &attributes
+    ));
+}
@@ -0,0 +1,114 @@
+//! Integration tests for Go dependency analysis
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use deptree_graph::RenderOptions;
+use deptree_utils::go;
+
+mod common;
+use common::get_binary_path;
+
+fn fixture_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("go_module_project")
+}
+
+#[test]
+fn test_read_module_path() {
+    let module_path = go::read_module_path(&fixture_path()).expect("Failed to read go.mod");
+    insta::assert_snapshot!(module_path);
+}
+
+#[test]
+fn test_go_module_dot_output() {
+    let graph = go::analyze_module(&fixture_path(), false).expect("Failed to analyze module");
+    let dot_output = graph.to_dot(true, false, &RenderOptions::default());
+
+    insta::assert_snapshot!(dot_output);
+}
+
+#[test]
+fn test_go_module_mermaid_output() {
+    let graph = go::analyze_module(&fixture_path(), false).expect("Failed to analyze module");
+    let mermaid_output = graph.to_mermaid(false, false, &RenderOptions::default());
+
+    insta::assert_snapshot!(mermaid_output);
+}
+
+#[test]
+fn test_go_module_include_tests() {
+    // api_test.go pulls in internal/testutil only when test files are included
+    let graph = go::analyze_module(&fixture_path(), true).expect("Failed to analyze module");
+    let dot_output = graph.to_dot(false, false, &RenderOptions::default());
+
+    insta::assert_snapshot!(dot_output);
+}
+
+#[test]
+fn test_go_module_cytoscape_data() {
+    let graph = go::analyze_module(&fixture_path(), false).expect("Failed to analyze module");
+    let data = graph.to_cytoscape_graph_data(false, false, &RenderOptions::default());
+    let json = serde_json::to_string_pretty(&data).expect("Failed to serialize graph data");
+
+    insta::assert_snapshot!(json);
+}
+
+#[test]
+fn test_go_module_downstream() {
+    let graph = go::analyze_module(&fixture_path(), false).expect("Failed to analyze module");
+    let money = go::PackagePath::from_import_path("example.com/shop/pkg/money")
+        .expect("Invalid import path");
+
+    let downstream = graph.find_downstream(&[money], None);
+    let filter = downstream.keys().cloned().collect();
+
+    insta::assert_snapshot!(graph.to_list_filtered(&filter, false));
+}
+
+#[test]
+fn test_go_cli_upstream_relative_package() {
+    let output = Command::new(get_binary_path())
+        .arg("go")
+        .arg(fixture_path())
+        .arg("--upstream")
+        .arg("internal/store")
+        .arg("--format")
+        .arg("list")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    insta::assert_snapshot!(stdout);
+}
+
+#[test]
+fn test_go_cli_downstream_mermaid_show_all() {
+    let output = Command::new(get_binary_path())
+        .arg("go")
+        .arg(fixture_path())
+        .arg("--downstream")
+        .arg("example.com/shop/internal/store/db")
+        .arg("--format")
+        .arg("mermaid")
+        .arg("--show-all")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    insta::assert_snapshot!(stdout);
+}
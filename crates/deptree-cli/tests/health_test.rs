@@ -0,0 +1,46 @@
+//! Tests for the graph health score (`stats`)
+
+use deptree_utils::health::{self, Weights};
+use deptree_utils::python::PythonGraph;
+
+mod common;
+use common::{module, python_graph};
+
+/// A cycle, app.db imported by three of the four other modules, and one orphan
+fn scored_graph() -> PythonGraph {
+    let mut graph = python_graph(&[
+        ("app.api", "app.db"),
+        ("app.db", "app.api"),
+        ("app.cli", "app.db"),
+        ("tools.report", "app.db"),
+    ]);
+    graph.ensure_node(module("app.legacy"));
+    graph
+}
+
+#[test]
+fn test_health_score_combines_weighted_components() {
+    let health = health::health(&scored_graph(), Weights::default());
+
+    insta::assert_snapshot!(health::render_report(&health));
+}
+
+#[test]
+fn test_health_weights_select_components() {
+    let only_orphans = health::parse_weights("cycles=0,depth=0,fan_in=0,cross_package=0").unwrap();
+
+    assert_eq!(health::health(&scored_graph(), only_orphans).score, 80.0);
+}
+
+#[test]
+fn test_health_weights_are_validated() {
+    let errors: Vec<String> = [
+        "cycles=0,depth=0,fan_in=0,cross_package=0,orphans=0",
+        "coupling=2",
+    ]
+    .into_iter()
+    .map(|weights| health::parse_weights(weights).unwrap_err().to_string())
+    .collect();
+
+    insta::assert_debug_snapshot!(errors);
+}
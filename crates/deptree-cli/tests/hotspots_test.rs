@@ -0,0 +1,25 @@
+//! Tests for the side-effect hotspots (`--side-effect-hotspots`)
+
+use deptree_utils::hotspots;
+
+mod common;
+use common::{module, python_graph};
+
+#[test]
+fn test_hotspots_rank_body_size_by_importers() {
+    let mut graph = python_graph(&[
+        ("app.api", "plugins.registry"),
+        ("app.cli", "plugins"),
+        ("plugins", "plugins.registry"),
+        ("plugins.registry", "config"),
+        ("app.api", "config"),
+        ("app", "app.api"),
+    ]);
+    graph.set_body_statements(&module("plugins"), 3);
+    graph.set_body_statements(&module("config"), 4);
+    graph.set_body_statements(&module("app"), 10);
+
+    // `app` has no importers outside itself
+    let hotspots = hotspots::side_effect_hotspots(&graph);
+    insta::assert_snapshot!(hotspots::render_report(&hotspots, 10));
+}
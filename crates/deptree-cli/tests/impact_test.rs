@@ -0,0 +1,53 @@
+//! Tests for the change impact report (`--impact-report`)
+
+use deptree_utils::impact::{self, Weights};
+use deptree_utils::python::PythonGraph;
+use std::collections::BTreeMap;
+
+mod common;
+use common::python_graph;
+
+/// app.api -> app.core -> app.db, app.cli -> app.core
+fn layered_graph() -> PythonGraph {
+    python_graph(&[
+        ("app.api", "app.core"),
+        ("app.core", "app.db"),
+        ("app.cli", "app.core"),
+    ])
+}
+
+/// Two commits touched core.py, one db.py
+fn churn() -> BTreeMap<String, usize> {
+    impact::parse_name_only("src/app/core.py\n\nsrc/app/core.py\nsrc/app/db.py\nREADME.md\n")
+}
+
+#[test]
+fn test_parse_weights() {
+    let parsed: Vec<String> = ["1,1", "0.5, 2", "1", "-1,1"]
+        .into_iter()
+        .map(|value| match impact::parse_weights(value) {
+            Ok(weights) => format!("{value}: {weights:?}"),
+            Err(error) => format!("{value}: {error}"),
+        })
+        .collect();
+
+    insta::assert_debug_snapshot!(parsed);
+}
+
+#[test]
+fn test_impact_scores_rank_reach_times_churn() {
+    let scores = impact::impact_scores(&layered_graph(), &churn(), Weights::default());
+
+    insta::assert_snapshot!(impact::render_report(&scores, "1 month ago"));
+}
+
+#[test]
+fn test_impact_scores_without_churn_rank_by_reach() {
+    let reach_only = Weights {
+        reach: 1.0,
+        churn: 0.0,
+    };
+
+    let scores = impact::impact_scores(&layered_graph(), &churn(), reach_only);
+    insta::assert_snapshot!(impact::render_report(&scores, "1 month ago"));
+}
@@ -0,0 +1,39 @@
+//! Tests for the import locations of a module (`importers`)
+
+use deptree_graph::ImportSite;
+use deptree_utils::importers;
+use std::path::Path;
+
+mod common;
+use common::{module, python_graph};
+
+#[test]
+fn test_importers_sorted_by_location() {
+    let target = module("app.db");
+    let mut graph = python_graph(&[("app.cli", "app.db")]);
+    for (importer, file, line, column, alias) in [
+        ("app.models", "src/app/models.py", 3, 1, None),
+        ("app.api", "src/app/api.py", 12, 5, Some("database")),
+        ("app.api", "src/app/api.py", 1, 1, None),
+    ] {
+        graph.add_dependency(module(importer), target.clone());
+        let site = ImportSite {
+            file: file.to_string(),
+            line,
+            column,
+            alias: alias.map(String::from),
+        };
+        graph.record_import_site(&module(importer), &target, site);
+    }
+
+    let (found, _) = importers::importers(&graph, &target);
+    insta::assert_snapshot!(importers::render_quickfix(&found, &target, Path::new(".")));
+}
+
+#[test]
+fn test_importers_without_a_recorded_site_are_unlocated() {
+    let graph = python_graph(&[("app.cli", "app.db")]);
+
+    let (_, unlocated) = importers::importers(&graph, &module("app.db"));
+    assert_eq!(unlocated, vec![module("app.cli")]);
+}
@@ -0,0 +1,76 @@
+//! Tests for the layout hints of the graph data (`--layout-seed`)
+
+use deptree_graph::{GraphData, GraphEdge, GraphNode, NodePosition};
+use deptree_utils::layout::add_layout_hints;
+
+/// `app` groups `app.api` and `app.db`; `cli` imports `app.api`
+fn graph() -> GraphData {
+    let node = |id: &str, parent: Option<&str>| GraphNode {
+        id: id.to_string(),
+        node_type: "module".to_string(),
+        is_orphan: false,
+        highlighted: None,
+        parent: parent.map(String::from),
+        import_time_us: None,
+        coverage: None,
+        category: None,
+        lifecycle: None,
+        generated: None,
+        change: None,
+        annotations: None,
+        position: None,
+        style: None,
+        href: None,
+    };
+    let edge = |source: &str, target: &str| GraphEdge {
+        source: source.to_string(),
+        target: target.to_string(),
+        kind: None,
+        tags: None,
+        count: None,
+        change: None,
+        style: None,
+    };
+    GraphData {
+        version: deptree_graph::GRAPH_DATA_VERSION,
+        nodes: vec![
+            node("app", None),
+            node("app.api", Some("app")),
+            node("app.db", Some("app")),
+            node("cli", None),
+        ],
+        edges: vec![edge("app.api", "app.db"), edge("cli", "app.api")],
+        config: None,
+    }
+}
+
+/// Node positions laid out with `seed`, in node order; `reversed` hands the nodes to the
+/// layout in reverse
+fn positions(seed: u64, reversed: bool) -> Vec<Option<NodePosition>> {
+    let mut data = graph();
+    if reversed {
+        data.nodes.reverse();
+    }
+    add_layout_hints(&mut data, seed);
+    if reversed {
+        data.nodes.reverse();
+    }
+    data.nodes.iter().map(|node| node.position).collect()
+}
+
+#[test]
+fn test_layout_hints_place_modules_but_not_compound_nodes() {
+    let placed: Vec<bool> = positions(7, false).iter().map(Option::is_some).collect();
+
+    assert_eq!(placed, [false, true, true, true]);
+}
+
+#[test]
+fn test_layout_hints_ignore_node_order() {
+    assert_eq!(positions(7, true), positions(7, false));
+}
+
+#[test]
+fn test_layout_hints_depend_on_the_seed() {
+    assert_ne!(positions(8, false), positions(7, false));
+}
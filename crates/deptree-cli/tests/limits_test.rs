@@ -0,0 +1,85 @@
+//! Tests for the rendered graph limits (`--max-nodes`, `--max-edges`)
+
+use deptree_graph::GraphId;
+use deptree_utils::limits::{Fallback, Limits, reduce};
+use deptree_utils::python::{ModulePath, PythonGraph};
+use std::collections::HashSet;
+
+mod common;
+use common::python_graph;
+
+fn ancestor(module: &ModulePath, depth: usize) -> Option<ModulePath> {
+    Some(ModulePath(module.0[..depth].to_vec()))
+}
+
+fn sample_graph() -> PythonGraph {
+    python_graph(&[
+        ("app.api.views", "app.core.models"),
+        ("app.api.views", "app.core.db"),
+        ("app.api.auth", "app.core.models"),
+        ("app.core.models", "app.core.db"),
+        ("app.core.db", "lib.sql"),
+    ])
+}
+
+fn max_nodes_limit(max_nodes: usize, fallback: Fallback) -> Limits {
+    Limits {
+        max_nodes: Some(max_nodes),
+        max_edges: None,
+        fallback,
+    }
+}
+
+/// Mapping of `sample_graph` reduced to at most `max_nodes` modules, by dotted name and
+/// sorted; sampled-out modules map to nothing
+fn reduced_mapping(max_nodes: usize, fallback: Fallback) -> Option<Vec<(String, String)>> {
+    let graph = sample_graph();
+    let visible: HashSet<ModulePath> = graph.nodes().cloned().collect();
+    let reduced = reduce(
+        &graph,
+        &visible,
+        &max_nodes_limit(max_nodes, fallback),
+        false,
+        false,
+        ancestor,
+    )?;
+    let mut mapping: Vec<(String, String)> = graph
+        .nodes()
+        .map(|node| {
+            let target = reduced.mapping.get(node).map(GraphId::to_dotted);
+            (node.to_dotted(), target.unwrap_or_default())
+        })
+        .collect();
+    mapping.sort();
+    Some(mapping)
+}
+
+#[test]
+fn test_reduce_keeps_graphs_within_limits() {
+    assert!(reduced_mapping(5, Fallback::Aggregate).is_none());
+}
+
+#[test]
+fn test_reduce_aggregates_at_deepest_fitting_depth() {
+    insta::assert_debug_snapshot!(reduced_mapping(3, Fallback::Aggregate));
+}
+
+#[test]
+fn test_reduce_samples_best_connected_modules() {
+    insta::assert_debug_snapshot!(reduced_mapping(2, Fallback::Sample));
+}
+
+#[test]
+fn test_reduce_samples_when_only_a_single_package_fits() {
+    // At depth 1 every module collapses into `app`, leaving nothing to draw
+    let graph = python_graph(&[
+        ("app.api.views", "app.core.db"),
+        ("app.api.auth", "app.core.db"),
+        ("app.cli", "app.api.views"),
+    ]);
+    let visible: HashSet<ModulePath> = graph.nodes().cloned().collect();
+    let limits = max_nodes_limit(2, Fallback::Aggregate);
+
+    let reduced = reduce(&graph, &visible, &limits, false, false, ancestor).unwrap();
+    insta::assert_snapshot!(reduced.description);
+}
@@ -0,0 +1,221 @@
+//! Integration tests for the MCP server
+
+use deptree_graph::DependencyGraph;
+use deptree_utils::mcp::Server;
+use deptree_utils::python::ModulePath;
+use serde_json::{Value, json};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+mod common;
+use common::get_binary_path;
+
+fn get_fixture_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("sample_python_project")
+}
+
+fn spawn_server(project: &Path) -> Child {
+    Command::new(get_binary_path())
+        .arg("mcp")
+        .arg(project)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to execute command")
+}
+
+fn tool_call(id: u32, tool: &str, arguments: &str) -> String {
+    format!(
+        r#"{{"jsonrpc":"2.0","id":{id},"method":"tools/call","params":{{"name":"{tool}","arguments":{arguments}}}}}"#
+    )
+}
+
+/// Send one request and read its response line
+fn request(stdin: &mut ChildStdin, stdout: &mut BufReader<ChildStdout>, message: &str) -> String {
+    writeln!(stdin, "{message}").unwrap();
+    let mut line = String::new();
+    stdout.read_line(&mut line).unwrap();
+    line
+}
+
+#[test]
+fn test_mcp_session() {
+    let mut child = spawn_server(&get_fixture_path());
+    let messages = [
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2024-11-05","capabilities":{},"clientInfo":{"name":"test","version":"0"}}}"#.to_string(),
+        r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#.to_string(),
+        r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#.to_string(),
+        tool_call(3, "downstream", r#"{"module":"pkg_b.module_b"}"#),
+        tool_call(4, "upstream", r#"{"module":"main.py","max_depth":1}"#),
+        tool_call(5, "path", r#"{"from":"main","to":"pkg_b.module_b"}"#),
+        tool_call(6, "search", r#"{"query":"pkg_a*"}"#),
+        tool_call(7, "metrics", "{}"),
+        tool_call(8, "metrics", r#"{"module":"pkg_a.module_a"}"#),
+        tool_call(9, "downstream", r#"{"module":"pkg_c"}"#),
+        r#"{"jsonrpc":"2.0","id":10,"method":"resources/list"}"#.to_string(),
+    ];
+    let mut stdin = child.stdin.take().unwrap();
+    for message in &messages {
+        writeln!(stdin, "{message}").unwrap();
+    }
+    drop(stdin);
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    // One pretty-printed response per request (the notification gets none)
+    let transcript: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| {
+            let mut response: serde_json::Value = serde_json::from_str(line).unwrap();
+            if let Some(info) = response.pointer_mut("/result/serverInfo") {
+                info["version"] = "<version>".into();
+            }
+            serde_json::to_string_pretty(&response).unwrap()
+        })
+        .collect();
+    insta::assert_snapshot!(transcript.join("\n"));
+}
+
+#[test]
+fn test_mcp_rebuilds_graph_after_source_change() {
+    let temp = tempfile::tempdir().unwrap();
+    for file in ["main.py", "pkg_b/__init__.py", "pkg_b/module_b.py"] {
+        let target = temp.path().join(file);
+        std::fs::create_dir_all(target.parent().unwrap()).unwrap();
+        std::fs::copy(get_fixture_path().join(file), target).unwrap();
+    }
+    let mut child = spawn_server(temp.path());
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+
+    let downstream = tool_call(1, "downstream", r#"{"module":"pkg_b.module_b"}"#);
+    let before = request(&mut stdin, &mut stdout, &downstream);
+    assert!(!before.contains("pkg_b.extra"), "{before}");
+
+    std::fs::write(
+        temp.path().join("pkg_b/extra.py"),
+        "from pkg_b import module_b\n",
+    )
+    .unwrap();
+    let after = request(&mut stdin, &mut stdout, &downstream);
+    assert!(after.contains("1 pkg_b.extra"), "{after}");
+
+    drop(stdin);
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn test_mcp_fingerprint_sees_renames_and_older_files() {
+    use deptree_utils::mcp::Fingerprint;
+    use std::time::{Duration, SystemTime};
+
+    let project = tempfile::tempdir().unwrap();
+    let write = |name: &str, body: &str, age: u64| {
+        let path = project.path().join(name);
+        std::fs::write(&path, body).unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(age))
+            .unwrap();
+    };
+    write("a.py", "import b\n", 60);
+    write("b.py", "", 30);
+    let fingerprint = || Fingerprint::of_sources(project.path(), &[]);
+    let initial = fingerprint();
+
+    // Same file count and newest modification time
+    std::fs::rename(project.path().join("b.py"), project.path().join("c.py")).unwrap();
+    let renamed = fingerprint();
+    assert_ne!(renamed, initial);
+
+    // An edit restoring an older modification time, as a checkout can
+    write("a.py", "import c\n", 90);
+    assert_ne!(fingerprint(), renamed);
+}
+
+type LibraryServer = Server<
+    ModulePath,
+    Box<dyn FnMut() -> Result<DependencyGraph<ModulePath>, String>>,
+    fn(&str) -> Result<ModulePath, String>,
+>;
+
+/// Server over a fixed graph
+fn server(edges: &'static [(&'static str, &'static str)]) -> LibraryServer {
+    let load: Box<dyn FnMut() -> Result<DependencyGraph<ModulePath>, String>> =
+        Box::new(move || {
+            let mut graph = DependencyGraph::new();
+            for (from, to) in edges {
+                graph.add_dependency(from.parse()?, to.parse()?);
+            }
+            Ok(graph)
+        });
+    let parse: fn(&str) -> Result<ModulePath, String> = |s| s.parse();
+    // No sources below the root, so the graph is never rebuilt
+    let root = std::env::temp_dir().join("deptree-mcp-test-no-sources");
+    Server::new(root, Vec::new(), load, parse).unwrap()
+}
+
+/// Text of a tool call result, prefixed with `error: ` when flagged as an error
+fn call_tool(tool: &str, arguments: Value) -> String {
+    let mut server = server(&[("app.api", "app.core"), ("app.core", "app.db")]);
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "tools/call",
+        "params": { "name": tool, "arguments": arguments },
+    });
+    let response = server.handle_message(&request.to_string()).unwrap();
+    let result = &response["result"];
+    let text = result["content"][0]["text"].as_str().unwrap();
+    if result["isError"].as_bool().unwrap() {
+        format!("error: {text}")
+    } else {
+        text.to_string()
+    }
+}
+
+#[test]
+fn test_path_tool_answers_from_the_graph() {
+    insta::assert_snapshot!(call_tool(
+        "path",
+        json!({ "from": "app.api", "to": "app.db" })
+    ));
+}
+
+#[test]
+fn test_downstream_tool_stops_at_max_depth() {
+    insta::assert_snapshot!(call_tool(
+        "downstream",
+        json!({ "module": "app.db", "max_depth": 1 })
+    ));
+}
+
+#[test]
+fn test_tools_flag_unknown_modules_as_errors() {
+    insta::assert_snapshot!(call_tool("upstream", json!({ "module": "app.missing" })));
+}
+
+#[test]
+fn test_notifications_get_no_response() {
+    let mut server = server(&[("a", "b")]);
+
+    assert!(
+        server
+            .handle_message(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#)
+            .is_none()
+    );
+}
+
+#[test]
+fn test_unknown_methods_are_rejected() {
+    let mut server = server(&[("a", "b")]);
+
+    let response = server
+        .handle_message(r#"{"jsonrpc":"2.0","id":7,"method":"resources/list"}"#)
+        .unwrap();
+    insta::assert_snapshot!(serde_json::to_string_pretty(&response).unwrap());
+}
@@ -0,0 +1,146 @@
+//! Integration tests for cross-language graphs linked by declared bridges
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use deptree_graph::RenderOptions;
+use deptree_utils::{config, polyglot};
+
+mod common;
+use common::get_binary_path;
+
+fn fixture_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("polyglot_project")
+}
+
+fn load_graph() -> polyglot::PolyglotGraph {
+    let root = fixture_path();
+    let loaded =
+        config::Config::load(&root.join(config::CONFIG_FILE_NAME)).expect("Failed to load config");
+    polyglot::analyze_polyglot(&loaded, &root).expect("Failed to analyze polyglot project")
+}
+
+#[test]
+fn test_polyglot_dot_output() {
+    let graph = load_graph();
+    insta::assert_snapshot!(graph.to_dot(false, false, &RenderOptions::default()));
+}
+
+#[test]
+fn test_polyglot_proto_change_reaches_python_consumers() {
+    let graph = load_graph();
+    let common = polyglot::PolyglotNode::parse("proto:svc/v1/common.proto").expect("Invalid node");
+
+    let downstream = graph.find_downstream(&[common], None);
+    let filter = downstream.keys().cloned().collect();
+
+    insta::assert_snapshot!(graph.to_list_filtered(&filter, false));
+}
+
+#[test]
+fn test_polyglot_invalid_node_reference() {
+    let error = polyglot::PolyglotNode::parse("rust:crate::module")
+        .expect_err("Unknown language should be rejected");
+
+    insta::assert_snapshot!(error.to_string());
+}
+
+#[test]
+fn test_polyglot_cli_upstream_mermaid() {
+    let output = Command::new(get_binary_path())
+        .arg("polyglot")
+        .arg(fixture_path())
+        .arg("--upstream")
+        .arg("go:example.com/gateway/handlers")
+        .arg("--format")
+        .arg("mermaid")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    insta::assert_snapshot!(stdout);
+}
+
+#[test]
+fn test_polyglot_presets_embedded_in_json() {
+    let output = Command::new(get_binary_path())
+        .arg("polyglot")
+        .arg("--config")
+        .arg(fixture_path().join("presets.toml"))
+        .arg("--format")
+        .arg("json")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("preset 'stale' refers to unknown node go:example.com/gateway/removed"),
+        "Expected a warning for the stale preset: {stderr}"
+    );
+
+    let data: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Output is not valid JSON");
+    insta::assert_snapshot!(
+        serde_json::to_string_pretty(&data["config"]["presets"]).expect("Failed to serialize")
+    );
+}
+
+#[test]
+fn test_polyglot_impact_services() {
+    let run = |args: &[&str]| {
+        Command::new(get_binary_path())
+            .arg("polyglot")
+            .arg(fixture_path())
+            .arg("--config")
+            .arg(fixture_path().join("services.toml"))
+            .arg("--impact-services")
+            .args(args)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    // Changed files as listed by `git diff --name-only`, docs included
+    let output = run(&[
+        "--downstream-module",
+        "protos/svc/v1/common.proto",
+        "--downstream-module",
+        "README.md",
+    ]);
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    insta::assert_snapshot!(String::from_utf8_lossy(&output.stdout));
+
+    let output = run(&[
+        "--downstream",
+        "go:example.com/gateway/rpc",
+        "--format",
+        "list",
+    ]);
+    assert!(output.status.success());
+    // The schema rpc is generated from is upstream of the change
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "gateway\n");
+
+    let output = run(&["--downstream", "python:app.gone"]);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("Changed module 'python:app.gone' is not part of the analyzed graph")
+    );
+}
@@ -0,0 +1,100 @@
+//! Integration tests for named option profiles (`--profile`) declared in the config file
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+mod common;
+use common::get_binary_path;
+
+fn fixture_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("contracts_project")
+}
+
+fn run(subcommand: &str, args: &[&str]) -> Output {
+    Command::new(get_binary_path())
+        .arg(subcommand)
+        .arg(fixture_path())
+        .arg("--config")
+        .arg(fixture_path().join("profiles.toml"))
+        .args(args)
+        .output()
+        .expect("Failed to execute command")
+}
+
+fn stdout_of(output: &Output) -> String {
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn test_profile_matches_equivalent_flags() {
+    let with_profile = stdout_of(&run("polyglot", &["--profile", "impact"]));
+    let with_flags = stdout_of(&run(
+        "polyglot",
+        &[
+            "--downstream",
+            "python:app.models.order",
+            "--format",
+            "list",
+        ],
+    ));
+
+    assert_eq!(with_profile, with_flags);
+}
+
+#[test]
+fn test_profile_edges_match_equivalent_flags() {
+    let with_profile = stdout_of(&run("polyglot", &["--profile", "order-importers"]));
+    let with_flags = stdout_of(&run(
+        "polyglot",
+        &[
+            "--downstream",
+            "python:app.models.order",
+            "--edges",
+            "dependents",
+            "--format",
+            "list",
+        ],
+    ));
+
+    assert_eq!(with_profile, with_flags);
+}
+
+#[test]
+fn test_profile_output_options() {
+    let output = run("polyglot", &["--profile", "docs"]);
+
+    insta::assert_snapshot!(stdout_of(&output));
+}
+
+#[test]
+fn test_profile_selects_contracts() {
+    let output = run("check", &["--profile", "ci"]);
+
+    // Only the two selected contracts are checked; the broken layers contract is skipped
+    insta::assert_snapshot!(String::from_utf8_lossy(&output.stdout));
+}
+
+#[test]
+fn test_profile_conflicts_with_flags() {
+    let output = run("polyglot", &["--profile", "impact", "--format", "dot"]);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("cannot be used with"), "stderr: {stderr}");
+}
+
+#[test]
+fn test_unknown_profile() {
+    let output = run("polyglot", &["--profile", "nightly"]);
+
+    assert!(!output.status.success());
+    insta::assert_snapshot!(String::from_utf8_lossy(&output.stderr));
+}
@@ -0,0 +1,104 @@
+//! Integration tests for Protobuf schema dependency analysis
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use deptree_graph::{GraphId, RenderOptions};
+use deptree_utils::proto;
+
+mod common;
+use common::get_binary_path;
+
+fn fixture_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("proto_project")
+}
+
+#[test]
+fn test_proto_project_dot_output() {
+    let graph = proto::analyze_protos(&fixture_path(), &[]).expect("Failed to analyze protos");
+    let dot_output = graph.to_dot(true, false, &RenderOptions::default());
+
+    insta::assert_snapshot!(dot_output);
+}
+
+#[test]
+fn test_proto_project_mermaid_output() {
+    let graph = proto::analyze_protos(&fixture_path(), &[]).expect("Failed to analyze protos");
+    let mermaid_output = graph.to_mermaid(false, false, &RenderOptions::default());
+
+    insta::assert_snapshot!(mermaid_output);
+}
+
+#[test]
+fn test_proto_cycles() {
+    let graph = proto::analyze_protos(&fixture_path(), &[]).expect("Failed to analyze protos");
+    let cycles: Vec<String> = graph
+        .find_cycles()
+        .iter()
+        .map(|cycle| {
+            cycle
+                .iter()
+                .map(GraphId::to_dotted)
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .collect();
+
+    insta::assert_snapshot!(cycles.join("\n"));
+}
+
+#[test]
+fn test_proto_orphans() {
+    let graph = proto::analyze_protos(&fixture_path(), &[]).expect("Failed to analyze protos");
+    let orphans: Vec<String> = graph.orphans().iter().map(GraphId::to_dotted).collect();
+
+    insta::assert_snapshot!(orphans.join("\n"));
+}
+
+#[test]
+fn test_proto_import_path_root() {
+    // With `-I shop`, files are identified relative to that directory and
+    // imports spelled from the project root no longer resolve
+    let graph = proto::analyze_protos(&fixture_path(), &[PathBuf::from("shop")])
+        .expect("Failed to analyze protos");
+    let dot_output = graph.to_dot(true, false, &RenderOptions::default());
+
+    insta::assert_snapshot!(dot_output);
+}
+
+#[test]
+fn test_proto_cli_downstream_reports_cycles() {
+    let output = Command::new(get_binary_path())
+        .arg("proto")
+        .arg(fixture_path())
+        .arg("--downstream")
+        .arg("common/money.proto")
+        .arg("--format")
+        .arg("list")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let combined = format!(
+        "stdout:\n{}\nstderr:\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    insta::assert_snapshot!(combined);
+}
+
+#[test]
+fn test_proto_openmetrics_counts_cycles() {
+    let graph = proto::analyze_protos(&fixture_path(), &[]).expect("Failed to analyze protos");
+    let metrics = graph.to_openmetrics(None, true, false);
+
+    insta::assert_snapshot!(metrics);
+}
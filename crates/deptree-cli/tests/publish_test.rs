@@ -0,0 +1,217 @@
+//! Integration tests for uploading rendered graphs (`--publish`)
+
+use deptree_utils::publish::Upload;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::thread::JoinHandle;
+
+mod common;
+use common::get_binary_path;
+
+fn fixture_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("sample_python_project")
+}
+
+/// A single request seen by the test server
+struct Request {
+    request_line: String,
+    headers: Vec<String>,
+    body: String,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.eq_ignore_ascii_case(name).then(|| value.trim())
+        })
+    }
+}
+
+/// Accept one request and answer it with `status`
+fn serve_once(status: &'static str) -> (String, JoinHandle<Request>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = format!("http://{}", listener.local_addr().unwrap());
+    let handle = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut headers = Vec::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line.trim().is_empty() {
+                break;
+            }
+            headers.push(line.trim().to_string());
+        }
+        let mut request = Request {
+            request_line: request_line.trim().to_string(),
+            headers,
+            body: String::new(),
+        };
+        let length: usize = request
+            .header("content-length")
+            .map_or(0, |value| value.parse().unwrap());
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        request.body = String::from_utf8(body).unwrap();
+        write!(stream, "HTTP/1.1 {status}\r\nContent-Length: 0\r\n\r\n").unwrap();
+        request
+    });
+    (address, handle)
+}
+
+fn publish(url: &str, format: &str, env: &[(&str, &str)]) -> Output {
+    Command::new(get_binary_path())
+        .arg("python")
+        .arg(fixture_path())
+        .arg("--format")
+        .arg(format)
+        .arg("--publish")
+        .arg(url)
+        .env("no_proxy", "*")
+        .envs(env.iter().copied())
+        .output()
+        .expect("Failed to execute command")
+}
+
+#[test]
+fn test_publish_posts_json_with_bearer_token() {
+    let (address, server) = serve_once("200 OK");
+    let output = publish(
+        &format!("{address}/graphs/main"),
+        "json",
+        &[("DEPTREE_PUBLISH_TOKEN", "s3cret")],
+    );
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let request = server.join().unwrap();
+
+    assert_eq!(request.request_line, "POST /graphs/main HTTP/1.1");
+    assert_eq!(request.header("authorization"), Some("Bearer s3cret"));
+    assert_eq!(request.header("content-type"), Some("application/json"));
+    // The uploaded artifact is exactly what is printed
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(request.body, stdout);
+}
+
+#[test]
+#[cfg(feature = "html")]
+fn test_publish_signs_s3_compatible_put() {
+    let (address, server) = serve_once("200 OK");
+    let output = publish(
+        "s3://graphs/ci/main.html",
+        "cytoscape",
+        &[
+            ("AWS_ACCESS_KEY_ID", "AKIDEXAMPLE"),
+            ("AWS_SECRET_ACCESS_KEY", "secret"),
+            ("AWS_REGION", "eu-west-1"),
+            ("AWS_ENDPOINT_URL", &address),
+        ],
+    );
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let request = server.join().unwrap();
+
+    assert_eq!(request.request_line, "PUT /graphs/ci/main.html HTTP/1.1");
+    assert_eq!(
+        request.header("content-type"),
+        Some("text/html; charset=utf-8")
+    );
+    let authorization = request.header("authorization").unwrap();
+    assert!(
+        authorization.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/"),
+        "{authorization}"
+    );
+    assert!(authorization.contains("/eu-west-1/s3/aws4_request"));
+}
+
+#[test]
+fn test_publish_failure_is_an_error() {
+    let (address, server) = serve_once("403 Forbidden");
+    let output = publish(&format!("{address}/graphs"), "json", &[]);
+    server.join().unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Upload to"), "{stderr}");
+    assert!(stderr.contains("403"), "{stderr}");
+}
+
+#[test]
+fn test_publish_s3_requires_credentials() {
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(fixture_path())
+        .arg("--publish")
+        .arg("s3://graphs/main.dot")
+        .env_remove("AWS_ACCESS_KEY_ID")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("Publishing to S3 requires the AWS_ACCESS_KEY_ID environment variable")
+    );
+}
+
+fn env_of(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars: HashMap<String, String> = vars
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    move |name| vars.get(name).cloned()
+}
+
+#[test]
+fn test_s3_destination_signs_for_the_region() {
+    let env = env_of(&[
+        ("AWS_ACCESS_KEY_ID", "AKID"),
+        ("AWS_SECRET_ACCESS_KEY", "se\"cret"),
+        ("AWS_REGION", "eu-west-1"),
+    ]);
+
+    let upload = Upload::for_url("s3://graphs/ci/main.json", env).unwrap();
+    insta::assert_snapshot!(format!("{upload:#?}\n{}", upload.curl_config()));
+}
+
+#[test]
+fn test_s3_destination_with_a_custom_endpoint() {
+    let env = env_of(&[
+        ("AWS_ACCESS_KEY_ID", "AKID"),
+        ("AWS_SECRET_ACCESS_KEY", "secret"),
+        ("AWS_ENDPOINT_URL", "http://minio:9000/"),
+    ]);
+
+    let upload = Upload::for_url("s3://graphs/main.json", env).unwrap();
+    insta::assert_debug_snapshot!(upload);
+}
+
+#[test]
+fn test_invalid_destinations() {
+    let errors: Vec<String> = [
+        "s3://graphs/main.json",
+        "s3://graphs",
+        "ftp://example.com/graph.json",
+    ]
+    .into_iter()
+    .map(|url| Upload::for_url(url, env_of(&[])).unwrap_err().to_string())
+    .collect();
+
+    insta::assert_debug_snapshot!(errors);
+}
@@ -1,9 +1,15 @@
 //! Integration tests for Python dependency analysis
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use deptree_utils::{cytoscape, python};
+use deptree_graph::RenderOptions;
+#[cfg(feature = "html")]
+use deptree_utils::cytoscape;
+use deptree_utils::{coverage, import_profile, python};
+
+mod common;
+use common::get_binary_path;
 
 fn fixture_path() -> PathBuf {
     PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -23,7 +29,7 @@ fn namespace_grouping_fixture() -> PathBuf {
 fn test_sample_python_project_dot_output() {
     let root = fixture_path();
     let graph = python::analyze_project(&root, None, &[]).expect("Failed to analyze project");
-    let dot_output = graph.to_dot(false, false);
+    let dot_output = graph.to_dot(false, false, &RenderOptions::default());
 
     insta::assert_snapshot!(dot_output);
 }
@@ -32,7 +38,7 @@ fn test_sample_python_project_dot_output() {
 fn test_sample_python_project_mermaid_output() {
     let root = fixture_path();
     let graph = python::analyze_project(&root, None, &[]).expect("Failed to analyze project");
-    let mermaid_output = graph.to_mermaid(false, false);
+    let mermaid_output = graph.to_mermaid(false, false, &RenderOptions::default());
 
     insta::assert_snapshot!(mermaid_output);
 }
@@ -65,12 +71,12 @@ fn test_skip_unparseable_files() {
         .join("fixtures")
         .join("unparseable_python_project");
 
-    // Should succeed despite malformed.py containing invalid syntax
+    // Should succeed despite malformed.py and partially_broken.py containing invalid syntax
     let graph = python::analyze_project(&root, None, &[])
         .expect("Failed to analyze project with unparseable files");
-    let dot_output = graph.to_dot(false, false);
+    let dot_output = graph.to_dot(false, false, &RenderOptions::default());
 
-    // Snapshot should only contain valid_module and another_valid, not malformed
+    // malformed.py yields nothing, partially_broken.py keeps the imports a line scan recovers
     insta::assert_snapshot!(dot_output);
 }
 
@@ -147,7 +153,7 @@ fn test_src_layout_auto_detection() {
     // Auto-detection should find src/ directory from pyproject.toml
     let graph =
         python::analyze_project(&root, None, &[]).expect("Failed to analyze src layout project");
-    let dot_output = graph.to_dot(false, false);
+    let dot_output = graph.to_dot(false, false, &RenderOptions::default());
 
     // Should have same modules as flat layout (pkg_a, pkg_b, main)
     // Module names should be relative to src/ not project root
@@ -164,7 +170,7 @@ fn test_lib_python_layout_auto_detection() {
     // Auto-detection should find lib/python/ directory via heuristics
     let graph =
         python::analyze_project(&root, None, &[]).expect("Failed to analyze lib/python layout");
-    let dot_output = graph.to_dot(false, false);
+    let dot_output = graph.to_dot(false, false, &RenderOptions::default());
 
     // Should have same modules as flat layout (pkg_a, pkg_b, main)
     // Module names should be relative to lib/python/ not project root
@@ -182,7 +188,7 @@ fn test_explicit_source_root_override() {
     // Explicitly specify source root instead of relying on auto-detection
     let graph = python::analyze_project(&project_root, Some(&source_root), &[])
         .expect("Failed to analyze with explicit source root");
-    let dot_output = graph.to_dot(false, false);
+    let dot_output = graph.to_dot(false, false, &RenderOptions::default());
 
     // Should produce same output as auto-detection
     insta::assert_snapshot!(dot_output);
@@ -198,7 +204,7 @@ fn test_project_with_scripts() {
     // Should discover scripts outside source root
     let graph =
         python::analyze_project(&root, None, &[]).expect("Failed to analyze project with scripts");
-    let dot_output = graph.to_dot(false, false);
+    let dot_output = graph.to_dot(false, false, &RenderOptions::default());
 
     // Should include:
     // - foo.bar (internal module)
@@ -219,7 +225,7 @@ fn test_project_with_scripts_mermaid() {
     // Should discover scripts outside source root
     let graph =
         python::analyze_project(&root, None, &[]).expect("Failed to analyze project with scripts");
-    let mermaid_output = graph.to_mermaid(false, false);
+    let mermaid_output = graph.to_mermaid(false, false, &RenderOptions::default());
 
     // Should include:
     // - foo.bar (internal module) - rounded rectangle shape
@@ -243,7 +249,7 @@ fn test_script_imports_internal_module() {
     let foo_bar = python::ModulePath(vec!["foo".to_string(), "bar".to_string()]);
 
     // Find downstream dependencies of foo.bar - should include scripts.blah
-    let downstream = graph.find_downstream(&[foo_bar.clone()], None);
+    let downstream = graph.find_downstream(std::slice::from_ref(&foo_bar), None);
     let filter: std::collections::HashSet<_> = downstream.keys().cloned().collect();
     let output = graph.to_list_filtered(&filter, false);
 
@@ -287,12 +293,53 @@ fn test_exclude_scripts_pattern() {
     // Exclude the scripts directory entirely
     let graph = python::analyze_project(&root, None, &["scripts".to_string()])
         .expect("Failed to analyze project with exclusions");
-    let dot_output = graph.to_dot(false, false);
+    let dot_output = graph.to_dot(false, false, &RenderOptions::default());
 
     // Should only include foo.bar, no scripts
     insta::assert_snapshot!(dot_output);
 }
 
+#[test]
+fn test_no_scripts_skips_script_discovery() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("project_with_scripts");
+
+    let options = python::AnalysisOptions {
+        skip_scripts: true,
+        ..Default::default()
+    };
+    let graph = python::analyze_project_with_options(&root, None, &[], &options)
+        .expect("Failed to analyze project without scripts");
+    let excluded = python::analyze_project(&root, None, &["scripts".to_string()])
+        .expect("Failed to analyze project with exclusions");
+
+    assert_eq!(
+        graph.to_dot(false, false, &RenderOptions::default()),
+        excluded.to_dot(false, false, &RenderOptions::default())
+    );
+}
+
+#[test]
+fn test_exclude_pattern_prunes_covered_directories() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("project_with_scripts");
+
+    // `scripts/*` covers the directory, so it is skipped like `scripts`
+    let graph = python::analyze_project(&root, None, &["scripts/*".to_string()])
+        .expect("Failed to analyze project with exclusions");
+    let excluded = python::analyze_project(&root, None, &["scripts".to_string()])
+        .expect("Failed to analyze project with exclusions");
+
+    assert_eq!(
+        graph.to_dot(false, false, &RenderOptions::default()),
+        excluded.to_dot(false, false, &RenderOptions::default())
+    );
+}
+
 #[test]
 fn test_upstream_single_module_no_deps() {
     let root = fixture_path();
@@ -305,7 +352,7 @@ fn test_upstream_single_module_no_deps() {
     ])];
     let upstream = graph.find_upstream(&roots, None);
     let filter: std::collections::HashSet<_> = upstream.keys().cloned().collect();
-    let output = graph.to_dot_filtered(&filter, false, false);
+    let output = graph.to_dot_filtered(&filter, false, false, &RenderOptions::default());
 
     insta::assert_snapshot!(output);
 }
@@ -323,7 +370,7 @@ fn test_upstream_single_module_with_deps() {
     ])];
     let upstream = graph.find_upstream(&roots, None);
     let filter: std::collections::HashSet<_> = upstream.keys().cloned().collect();
-    let output = graph.to_dot_filtered(&filter, false, false);
+    let output = graph.to_dot_filtered(&filter, false, false, &RenderOptions::default());
 
     insta::assert_snapshot!(output);
 }
@@ -338,7 +385,7 @@ fn test_upstream_transitive_deps() {
     let roots = vec![python::ModulePath(vec!["main".to_string()])];
     let upstream = graph.find_upstream(&roots, None);
     let filter: std::collections::HashSet<_> = upstream.keys().cloned().collect();
-    let output = graph.to_dot_filtered(&filter, false, false);
+    let output = graph.to_dot_filtered(&filter, false, false, &RenderOptions::default());
 
     insta::assert_snapshot!(output);
 }
@@ -353,7 +400,7 @@ fn test_upstream_transitive_deps_mermaid() {
     let roots = vec![python::ModulePath(vec!["main".to_string()])];
     let upstream = graph.find_upstream(&roots, None);
     let filter: std::collections::HashSet<_> = upstream.keys().cloned().collect();
-    let output = graph.to_mermaid_filtered(&filter, false, false);
+    let output = graph.to_mermaid_filtered(&filter, false, false, &RenderOptions::default());
 
     insta::assert_snapshot!(output);
 }
@@ -371,7 +418,7 @@ fn test_upstream_multiple_modules() {
     ];
     let upstream = graph.find_upstream(&roots, None);
     let filter: std::collections::HashSet<_> = upstream.keys().cloned().collect();
-    let output = graph.to_dot_filtered(&filter, false, false);
+    let output = graph.to_dot_filtered(&filter, false, false, &RenderOptions::default());
 
     insta::assert_snapshot!(output);
 }
@@ -385,7 +432,7 @@ fn test_upstream_nonexistent_module() {
     let roots = vec![python::ModulePath(vec!["nonexistent".to_string()])];
     let upstream = graph.find_upstream(&roots, None);
     let filter: std::collections::HashSet<_> = upstream.keys().cloned().collect();
-    let output = graph.to_dot_filtered(&filter, false, false);
+    let output = graph.to_dot_filtered(&filter, false, false, &RenderOptions::default());
 
     // Should be empty graph
     insta::assert_snapshot!(output);
@@ -409,7 +456,7 @@ fn test_upstream_with_scripts() {
     ])];
     let upstream = graph.find_upstream(&roots, None);
     let filter: std::collections::HashSet<_> = upstream.keys().cloned().collect();
-    let output = graph.to_dot_filtered(&filter, false, false);
+    let output = graph.to_dot_filtered(&filter, false, false, &RenderOptions::default());
 
     // Should show box shape for scripts.blah
     insta::assert_snapshot!(output);
@@ -433,7 +480,7 @@ fn test_upstream_with_scripts_mermaid() {
     ])];
     let upstream = graph.find_upstream(&roots, None);
     let filter: std::collections::HashSet<_> = upstream.keys().cloned().collect();
-    let output = graph.to_mermaid_filtered(&filter, false, false);
+    let output = graph.to_mermaid_filtered(&filter, false, false, &RenderOptions::default());
 
     // Should show rectangle shape for scripts.blah, rounded for foo.bar
     insta::assert_snapshot!(output);
@@ -457,7 +504,7 @@ fn test_upstream_script_with_relative_imports() {
     ])];
     let upstream = graph.find_upstream(&roots, None);
     let filter: std::collections::HashSet<_> = upstream.keys().cloned().collect();
-    let output = graph.to_dot_filtered(&filter, false, false);
+    let output = graph.to_dot_filtered(&filter, false, false, &RenderOptions::default());
 
     // Should show dependencies between scripts and to internal modules
     insta::assert_snapshot!(output);
@@ -479,7 +526,7 @@ fn test_function_level_imports() {
     let roots = vec![python::ModulePath(vec!["function_imports".to_string()])];
     let upstream = graph.find_upstream(&roots, None);
     let filter: std::collections::HashSet<_> = upstream.keys().cloned().collect();
-    let output = graph.to_dot_filtered(&filter, false, false);
+    let output = graph.to_dot_filtered(&filter, false, false, &RenderOptions::default());
 
     // Should include function_imports, base_module, and another_module
     insta::assert_snapshot!(output);
@@ -499,7 +546,7 @@ fn test_class_method_imports() {
     let roots = vec![python::ModulePath(vec!["class_imports".to_string()])];
     let upstream = graph.find_upstream(&roots, None);
     let filter: std::collections::HashSet<_> = upstream.keys().cloned().collect();
-    let output = graph.to_dot_filtered(&filter, false, false);
+    let output = graph.to_dot_filtered(&filter, false, false, &RenderOptions::default());
 
     // Should include class_imports and base_module
     insta::assert_snapshot!(output);
@@ -519,7 +566,7 @@ fn test_conditional_imports() {
     let roots = vec![python::ModulePath(vec!["conditional_imports".to_string()])];
     let upstream = graph.find_upstream(&roots, None);
     let filter: std::collections::HashSet<_> = upstream.keys().cloned().collect();
-    let output = graph.to_dot_filtered(&filter, false, false);
+    let output = graph.to_dot_filtered(&filter, false, false, &RenderOptions::default());
 
     // Should include conditional_imports, base_module, and another_module
     insta::assert_snapshot!(output);
@@ -534,25 +581,141 @@ fn test_full_graph_with_nested_imports() {
 
     let graph = python::analyze_project(&root, None, &[])
         .expect("Failed to analyze nested imports project");
-    let dot_output = graph.to_dot(false, false);
+    let dot_output = graph.to_dot(false, false, &RenderOptions::default());
 
     // Should show all dependencies including those from nested imports
     insta::assert_snapshot!(dot_output);
 }
 
-// CLI integration tests for file path support
+#[test]
+fn test_deferred_imports_mermaid_output() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("nested_imports_project");
+
+    let graph = python::analyze_project(&root, None, &[])
+        .expect("Failed to analyze nested imports project");
+
+    // Function-level imports render as dotted arrows
+    insta::assert_snapshot!(graph.to_mermaid(false, false, &RenderOptions::default()));
+}
+
+#[test]
+fn test_deferred_imports_cytoscape_data() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("nested_imports_project");
+
+    let graph = python::analyze_project(&root, None, &[])
+        .expect("Failed to analyze nested imports project");
+    let graph_data = graph.to_cytoscape_graph_data(false, false, &RenderOptions::default());
+    let serialized =
+        serde_json::to_string_pretty(&graph_data).expect("Failed to serialize graph data");
+
+    insta::assert_snapshot!(serialized);
+}
+
+fn import_profile_fixture() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("import_profiles")
+        .join("sample_python_project.log")
+}
+
+fn sample_graph_with_import_profile() -> python::PythonGraph {
+    let mut graph =
+        python::analyze_project(&fixture_path(), None, &[]).expect("Failed to analyze project");
+    let timings = import_profile::load(&import_profile_fixture()).expect("Failed to load profile");
+    import_profile::apply_to_graph(&mut graph, &timings);
+    graph
+}
+
+#[test]
+fn test_parse_importtime_log() {
+    let content =
+        std::fs::read_to_string(import_profile_fixture()).expect("Failed to read profile");
+    let timings: Vec<String> = import_profile::parse_importtime(&content)
+        .iter()
+        .map(|t| format!("{} {} {}", t.module, t.cost.self_us, t.cost.cumulative_us))
+        .collect();
+
+    insta::assert_snapshot!(timings.join("\n"));
+}
+
+#[test]
+fn test_import_profile_dot_output() {
+    let graph = sample_graph_with_import_profile();
+
+    insta::assert_snapshot!(graph.to_dot(false, false, &RenderOptions::default()));
+}
+
+#[test]
+fn test_import_profile_mermaid_output() {
+    let graph = sample_graph_with_import_profile();
+
+    insta::assert_snapshot!(graph.to_mermaid(false, false, &RenderOptions::default()));
+}
+
+fn coverage_report_fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("coverage_reports")
+        .join(name)
+}
+
+#[test]
+fn test_parse_cobertura_report() {
+    let files = coverage::load(&coverage_report_fixture("sample_python_project.xml"))
+        .expect("Failed to load coverage report");
+    let lines: Vec<String> = files
+        .iter()
+        .map(|f| {
+            format!(
+                "{} {}/{} {}",
+                f.filename,
+                f.covered_lines,
+                f.total_lines,
+                f.percent()
+            )
+        })
+        .collect();
+
+    insta::assert_snapshot!(lines.join("\n"));
+}
+
+#[test]
+fn test_coverage_rejects_raw_data_file() {
+    let error = coverage::load(&coverage_report_fixture("raw.coverage"))
+        .expect_err("Raw .coverage files should be rejected");
+
+    insta::assert_snapshot!(
+        error
+            .to_string()
+            .replace(env!("CARGO_MANIFEST_DIR"), "<manifest>")
+    );
+}
+
+#[test]
+fn test_coverage_cytoscape_data() {
+    let mut graph =
+        python::analyze_project(&fixture_path(), None, &[]).expect("Failed to analyze project");
+    let files = coverage::load(&coverage_report_fixture("sample_python_project.xml"))
+        .expect("Failed to load coverage report");
+    coverage::apply_to_graph(&mut graph, &files);
 
-fn get_binary_path() -> PathBuf {
-    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    // In a workspace, target is at the workspace root (two levels up)
-    path.push("..");
-    path.push("..");
-    path.push("target");
-    path.push("debug");
-    path.push("deptree-utils");
-    path
+    let graph_data = graph.to_cytoscape_graph_data(false, false, &RenderOptions::default());
+    let serialized =
+        serde_json::to_string_pretty(&graph_data).expect("Failed to serialize graph data");
+
+    insta::assert_snapshot!(serialized);
 }
 
+// CLI integration tests for file path support
+
 #[test]
 fn test_upstream_cli_with_script_file_path() {
     let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -705,7 +868,7 @@ fn test_downstream_graph_dot_format() {
     ])];
     let downstream = graph.find_downstream(&roots, None);
     let filter: std::collections::HashSet<_> = downstream.keys().cloned().collect();
-    let output = graph.to_dot_filtered(&filter, false, false);
+    let output = graph.to_dot_filtered(&filter, false, false, &RenderOptions::default());
 
     insta::assert_snapshot!(output);
 }
@@ -722,7 +885,7 @@ fn test_downstream_graph_mermaid_format() {
     ])];
     let downstream = graph.find_downstream(&roots, None);
     let filter: std::collections::HashSet<_> = downstream.keys().cloned().collect();
-    let output = graph.to_mermaid_filtered(&filter, false, false);
+    let output = graph.to_mermaid_filtered(&filter, false, false, &RenderOptions::default());
 
     insta::assert_snapshot!(output);
 }
@@ -807,7 +970,7 @@ fn test_downstream_max_rank_with_mermaid_format() {
     ])];
     let downstream = graph.find_downstream(&roots, Some(1));
     let filter: std::collections::HashSet<_> = downstream.keys().cloned().collect();
-    let output = graph.to_mermaid_filtered(&filter, false, false);
+    let output = graph.to_mermaid_filtered(&filter, false, false, &RenderOptions::default());
 
     insta::assert_snapshot!(output);
 }
@@ -877,7 +1040,7 @@ fn test_upstream_max_rank_with_dot_format() {
     let roots = vec![python::ModulePath(vec!["main".to_string()])];
     let upstream = graph.find_upstream(&roots, Some(1));
     let filter: std::collections::HashSet<_> = upstream.keys().cloned().collect();
-    let output = graph.to_dot_filtered(&filter, false, false);
+    let output = graph.to_dot_filtered(&filter, false, false, &RenderOptions::default());
 
     insta::assert_snapshot!(output);
 }
@@ -914,7 +1077,7 @@ fn test_orphan_filtering_with_max_rank() {
     ])];
     let downstream = graph.find_downstream(&roots, Some(0));
     let filter: std::collections::HashSet<_> = downstream.keys().cloned().collect();
-    let output = graph.to_dot_filtered(&filter, false, false);
+    let output = graph.to_dot_filtered(&filter, false, false, &RenderOptions::default());
 
     // Should have nodes but pkg_b.module_b itself might be considered an orphan
     // in the filtered graph (no edges within the filter)
@@ -930,7 +1093,7 @@ fn test_downstream_highlighted_dot() {
     let roots = vec![python::ModulePath(vec!["pkg_b".to_string()])];
     let downstream = graph.find_downstream(&roots, None);
     let highlight_set: std::collections::HashSet<_> = downstream.keys().cloned().collect();
-    let output = graph.to_dot_highlighted(&highlight_set, false, false);
+    let output = graph.to_dot_highlighted(&highlight_set, false, false, &RenderOptions::default());
 
     // Should show full graph with pkg_b and pkg_a.module_a highlighted
     insta::assert_snapshot!(output);
@@ -948,7 +1111,8 @@ fn test_downstream_highlighted_mermaid() {
     ])];
     let downstream = graph.find_downstream(&roots, None);
     let highlight_set: std::collections::HashSet<_> = downstream.keys().cloned().collect();
-    let output = graph.to_mermaid_highlighted(&highlight_set, false, false);
+    let output =
+        graph.to_mermaid_highlighted(&highlight_set, false, false, &RenderOptions::default());
 
     // Should show full graph with pkg_b.module_b and main highlighted in blue
     insta::assert_snapshot!(output);
@@ -963,7 +1127,7 @@ fn test_upstream_highlighted_dot() {
     let roots = vec![python::ModulePath(vec!["main".to_string()])];
     let upstream = graph.find_upstream(&roots, None);
     let highlight_set: std::collections::HashSet<_> = upstream.keys().cloned().collect();
-    let output = graph.to_dot_highlighted(&highlight_set, false, false);
+    let output = graph.to_dot_highlighted(&highlight_set, false, false, &RenderOptions::default());
 
     // Should show full graph with main, pkg_b.module_b, pkg_a.module_a highlighted
     insta::assert_snapshot!(output);
@@ -978,7 +1142,8 @@ fn test_upstream_highlighted_mermaid() {
     let roots = vec![python::ModulePath(vec!["main".to_string()])];
     let upstream = graph.find_upstream(&roots, None);
     let highlight_set: std::collections::HashSet<_> = upstream.keys().cloned().collect();
-    let output = graph.to_mermaid_highlighted(&highlight_set, false, false);
+    let output =
+        graph.to_mermaid_highlighted(&highlight_set, false, false, &RenderOptions::default());
 
     // Should show full graph with main, pkg_b.module_b, pkg_a.module_a highlighted in blue
     insta::assert_snapshot!(output);
@@ -1001,7 +1166,7 @@ fn test_highlighted_with_scripts() {
     ])];
     let upstream = graph.find_upstream(&roots, None);
     let highlight_set: std::collections::HashSet<_> = upstream.keys().cloned().collect();
-    let output = graph.to_dot_highlighted(&highlight_set, false, false);
+    let output = graph.to_dot_highlighted(&highlight_set, false, false, &RenderOptions::default());
 
     // Should show full graph with scripts.blah (box shape) and foo.bar highlighted
     insta::assert_snapshot!(output);
@@ -1019,7 +1184,7 @@ fn test_highlighted_orphans_included() {
     ])];
     let downstream = graph.find_downstream(&roots, None);
     let highlight_set: std::collections::HashSet<_> = downstream.keys().cloned().collect();
-    let output = graph.to_dot_highlighted(&highlight_set, true, false);
+    let output = graph.to_dot_highlighted(&highlight_set, true, false, &RenderOptions::default());
 
     // Should show full graph including orphans with highlighting
     insta::assert_snapshot!(output);
@@ -1034,7 +1199,7 @@ fn test_namespace_grouping_dot_output() {
     let root = namespace_grouping_fixture();
     let graph =
         python::analyze_project(&root, None, &[]).expect("Failed to analyze namespace grouping");
-    let dot_output = graph.to_dot(false, false);
+    let dot_output = graph.to_dot(false, false, &RenderOptions::default());
 
     // Should cluster foo, foo.bar, and foo.bar.quux namespaces into groups
     insta::assert_snapshot!(dot_output);
@@ -1045,7 +1210,7 @@ fn test_namespace_grouping_mermaid_output() {
     let root = namespace_grouping_fixture();
     let graph =
         python::analyze_project(&root, None, &[]).expect("Failed to analyze namespace grouping");
-    let mermaid_output = graph.to_mermaid(false, false);
+    let mermaid_output = graph.to_mermaid(false, false, &RenderOptions::default());
 
     // Should render namespace clusters as Mermaid subgraphs
     insta::assert_snapshot!(mermaid_output);
@@ -1064,7 +1229,8 @@ fn test_namespace_grouping_mermaid_downstream_filtered() {
     ])];
     let downstream = graph.find_downstream(&roots, None);
     let filter: std::collections::HashSet<_> = downstream.keys().cloned().collect();
-    let mermaid_output = graph.to_mermaid_filtered(&filter, false, false);
+    let mermaid_output =
+        graph.to_mermaid_filtered(&filter, false, false, &RenderOptions::default());
 
     // Downstream view should preserve namespace subgraphs
     insta::assert_snapshot!(mermaid_output);
@@ -1082,7 +1248,8 @@ fn test_namespace_grouping_mermaid_upstream_filtered() {
     ])];
     let upstream = graph.find_upstream(&roots, None);
     let filter: std::collections::HashSet<_> = upstream.keys().cloned().collect();
-    let mermaid_output = graph.to_mermaid_filtered(&filter, false, false);
+    let mermaid_output =
+        graph.to_mermaid_filtered(&filter, false, false, &RenderOptions::default());
 
     // Upstream view should preserve namespace subgraphs
     insta::assert_snapshot!(mermaid_output);
@@ -1101,7 +1268,8 @@ fn test_namespace_grouping_mermaid_highlighted_mode() {
     ])];
     let downstream = graph.find_downstream(&roots, None);
     let highlight_set: std::collections::HashSet<_> = downstream.keys().cloned().collect();
-    let mermaid_output = graph.to_mermaid_highlighted(&highlight_set, false, false);
+    let mermaid_output =
+        graph.to_mermaid_highlighted(&highlight_set, false, false, &RenderOptions::default());
 
     // Highlighted (show-all) view should preserve namespace subgraphs
     insta::assert_snapshot!(mermaid_output);
@@ -1113,7 +1281,7 @@ fn test_namespace_grouping_cytoscape_graph_data() {
     let graph =
         python::analyze_project(&root, None, &[]).expect("Failed to analyze namespace grouping");
 
-    let graph_data = graph.to_cytoscape_graph_data(false, false);
+    let graph_data = graph.to_cytoscape_graph_data(false, false, &RenderOptions::default());
     let serialized = serde_json::to_string_pretty(&graph_data)
         .expect("Cytoscape graph data should serialize to JSON");
 
@@ -1141,7 +1309,7 @@ fn test_namespace_package_excluded_by_default_dot() {
     let graph = python::analyze_project(&root, None, &[])
         .expect("Failed to analyze namespace packages project");
 
-    let dot_output = graph.to_dot(false, false);
+    let dot_output = graph.to_dot(false, false, &RenderOptions::default());
 
     // Should not contain namespace package nodes (pep420_namespace, legacy_namespace)
     // but should have edges between actual modules
@@ -1154,7 +1322,7 @@ fn test_namespace_package_excluded_by_default_mermaid() {
     let graph = python::analyze_project(&root, None, &[])
         .expect("Failed to analyze namespace packages project");
 
-    let mermaid_output = graph.to_mermaid(false, false);
+    let mermaid_output = graph.to_mermaid(false, false, &RenderOptions::default());
 
     // Should not contain namespace package nodes in Mermaid format
     insta::assert_snapshot!(mermaid_output);
@@ -1166,7 +1334,7 @@ fn test_namespace_package_with_orphans() {
     let graph = python::analyze_project(&root, None, &[])
         .expect("Failed to analyze namespace packages project");
 
-    let dot_output = graph.to_dot(true, false);
+    let dot_output = graph.to_dot(true, false, &RenderOptions::default());
 
     // Should include orphans but still exclude namespace packages
     insta::assert_snapshot!(dot_output);
@@ -1178,7 +1346,7 @@ fn test_namespace_package_normal_pkg_included() {
     let graph = python::analyze_project(&root, None, &[])
         .expect("Failed to analyze namespace packages project");
 
-    let dot_output = graph.to_dot(false, false);
+    let dot_output = graph.to_dot(false, false, &RenderOptions::default());
 
     // Verify normal_pkg is included (it's not a namespace package)
     assert!(dot_output.contains("normal_pkg"));
@@ -1191,7 +1359,7 @@ fn test_namespace_package_pep420_detected() {
         .expect("Failed to analyze namespace packages project");
 
     // PEP 420 namespace should be detected but excluded from default output
-    let dot_output = graph.to_dot(false, false);
+    let dot_output = graph.to_dot(false, false, &RenderOptions::default());
     assert!(!dot_output.contains("\"pep420_namespace.sub_a\""));
     assert!(!dot_output.contains("\"pep420_namespace.sub_b\""));
 }
@@ -1203,7 +1371,7 @@ fn test_namespace_package_legacy_detected() {
         .expect("Failed to analyze namespace packages project");
 
     // Legacy namespace should be detected but excluded from default output
-    let dot_output = graph.to_dot(false, false);
+    let dot_output = graph.to_dot(false, false, &RenderOptions::default());
     assert!(!dot_output.contains("\"legacy_namespace\""));
 }
 
@@ -1217,7 +1385,7 @@ fn test_namespace_package_included_with_flag_dot() {
     let graph = python::analyze_project(&root, None, &[])
         .expect("Failed to analyze namespace packages project");
 
-    let dot_output = graph.to_dot(false, true);
+    let dot_output = graph.to_dot(false, true, &RenderOptions::default());
 
     // With include_namespace_packages=true, should show namespace packages
     // However, they may not appear as nodes if they have no __init__.py modules
@@ -1231,7 +1399,7 @@ fn test_namespace_package_included_with_flag_mermaid() {
     let graph = python::analyze_project(&root, None, &[])
         .expect("Failed to analyze namespace packages project");
 
-    let mermaid_output = graph.to_mermaid(false, true);
+    let mermaid_output = graph.to_mermaid(false, true, &RenderOptions::default());
 
     // Mermaid output with include flag
     insta::assert_snapshot!(mermaid_output);
@@ -1247,7 +1415,7 @@ fn test_namespace_package_edge_traversal_dot() {
     let graph = python::analyze_project(&root, None, &[])
         .expect("Failed to analyze namespace packages project");
 
-    let dot_output = graph.to_dot(false, false);
+    let dot_output = graph.to_dot(false, false, &RenderOptions::default());
 
     // Should have direct edges that skip namespace packages:
     // normal_pkg.consumer -> pep420_namespace.sub_b.module_b
@@ -1263,7 +1431,7 @@ fn test_namespace_package_edge_traversal_mermaid() {
     let graph = python::analyze_project(&root, None, &[])
         .expect("Failed to analyze namespace packages project");
 
-    let mermaid_output = graph.to_mermaid(false, false);
+    let mermaid_output = graph.to_mermaid(false, false, &RenderOptions::default());
 
     // Same edge traversal test for Mermaid format
     insta::assert_snapshot!(mermaid_output);
@@ -1275,7 +1443,7 @@ fn test_namespace_package_transitive_dependencies() {
     let graph = python::analyze_project(&root, None, &[])
         .expect("Failed to analyze namespace packages project");
 
-    let dot_output = graph.to_dot(false, false);
+    let dot_output = graph.to_dot(false, false, &RenderOptions::default());
 
     // Verify transitive edges are created:
     // consumer -> module_b -> module_a -> normal_pkg
@@ -1302,7 +1470,7 @@ fn test_downstream_with_namespace_packages() {
     ])];
     let downstream = graph.find_downstream(&roots, None);
     let filter: std::collections::HashSet<_> = downstream.keys().cloned().collect();
-    let output = graph.to_dot_filtered(&filter, false, false);
+    let output = graph.to_dot_filtered(&filter, false, false, &RenderOptions::default());
 
     // Should include module_b and consumer (which depend on module_a)
     insta::assert_snapshot!(output);
@@ -1346,7 +1514,7 @@ fn test_upstream_with_namespace_packages() {
     ])];
     let upstream = graph.find_upstream(&roots, None);
     let filter: std::collections::HashSet<_> = upstream.keys().cloned().collect();
-    let output = graph.to_dot_filtered(&filter, false, false);
+    let output = graph.to_dot_filtered(&filter, false, false, &RenderOptions::default());
 
     // Should include all dependencies (module_b, module_a, etc.)
     insta::assert_snapshot!(output);
@@ -1388,7 +1556,7 @@ fn test_namespace_package_max_rank_filtering() {
     ])];
     let downstream = graph.find_downstream(&roots, Some(1));
     let filter: std::collections::HashSet<_> = downstream.keys().cloned().collect();
-    let output = graph.to_dot_filtered(&filter, false, false);
+    let output = graph.to_dot_filtered(&filter, false, false, &RenderOptions::default());
 
     // Should only include direct dependents
     insta::assert_snapshot!(output);
@@ -1427,7 +1595,7 @@ fn test_namespace_highlighted_mode() {
     ])];
     let downstream = graph.find_downstream(&roots, None);
     let highlight_set: std::collections::HashSet<_> = downstream.keys().cloned().collect();
-    let output = graph.to_dot_highlighted(&highlight_set, false, false);
+    let output = graph.to_dot_highlighted(&highlight_set, false, false, &RenderOptions::default());
 
     // Should highlight downstream modules but not namespace packages
     insta::assert_snapshot!(output);
@@ -1445,17 +1613,18 @@ fn test_namespace_with_orphans_and_highlighting() {
     ])];
     let upstream = graph.find_upstream(&roots, None);
     let highlight_set: std::collections::HashSet<_> = upstream.keys().cloned().collect();
-    let output = graph.to_dot_highlighted(&highlight_set, true, false);
+    let output = graph.to_dot_highlighted(&highlight_set, true, false, &RenderOptions::default());
 
     // Include orphans and highlight upstream dependencies
     insta::assert_snapshot!(output);
 }
 
 #[test]
+#[cfg(feature = "html")]
 fn test_sample_python_project_cytoscape_output() {
     let root = fixture_path();
     let graph = python::analyze_project(&root, None, &[]).expect("Failed to analyze project");
-    let graph_data = graph.to_cytoscape_graph_data(false, false);
+    let graph_data = graph.to_cytoscape_graph_data(false, false, &RenderOptions::default());
     let cytoscape_output = cytoscape::render_cytoscape_html(&graph_data)
         .expect("Cytoscape HTML should render with embedded graph data");
     let serialized = serde_json::to_string_pretty(&graph_data)
@@ -1471,6 +1640,24 @@ fn test_sample_python_project_cytoscape_output() {
     insta::assert_snapshot!(serialized);
 }
 
+#[test]
+fn test_cli_cytoscape_format_follows_html_feature() {
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(fixture_path())
+        .args(["--format", "cytoscape"])
+        .output()
+        .expect("Failed to execute command");
+    assert_eq!(output.status.success(), cfg!(feature = "html"));
+    if !cfg!(feature = "html") {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("invalid value 'cytoscape' for '--format"),
+            "{stderr}"
+        );
+    }
+}
+
 #[test]
 fn test_cytoscape_filtered_downstream() {
     let root = fixture_path();
@@ -1482,7 +1669,8 @@ fn test_cytoscape_filtered_downstream() {
     ])];
     let downstream = graph.find_downstream(&roots, None);
     let filter: std::collections::HashSet<_> = downstream.keys().cloned().collect();
-    let graph_data = graph.to_cytoscape_graph_data_filtered(&filter, false, false);
+    let graph_data =
+        graph.to_cytoscape_graph_data_filtered(&filter, false, false, &RenderOptions::default());
     let serialized = serde_json::to_string_pretty(&graph_data)
         .expect("Cytoscape graph data should serialize to JSON");
 
@@ -1504,7 +1692,12 @@ fn test_cytoscape_highlighted_mode() {
     ])];
     let downstream = graph.find_downstream(&roots, None);
     let highlight_set: std::collections::HashSet<_> = downstream.keys().cloned().collect();
-    let graph_data = graph.to_cytoscape_graph_data_highlighted(&highlight_set, false, false);
+    let graph_data = graph.to_cytoscape_graph_data_highlighted(
+        &highlight_set,
+        false,
+        false,
+        &RenderOptions::default(),
+    );
     let serialized = serde_json::to_string_pretty(&graph_data)
         .expect("Cytoscape graph data should serialize to JSON");
 
@@ -1523,7 +1716,7 @@ fn test_cytoscape_with_scripts() {
         .join("project_with_scripts");
 
     let graph = python::analyze_project(&root, None, &[]).expect("Failed to analyze project");
-    let graph_data = graph.to_cytoscape_graph_data(false, false);
+    let graph_data = graph.to_cytoscape_graph_data(false, false, &RenderOptions::default());
     let serialized = serde_json::to_string_pretty(&graph_data)
         .expect("Cytoscape graph data should serialize to JSON");
 
@@ -1543,13 +1736,13 @@ fn test_cytoscape_with_namespace_packages() {
     let graph = python::analyze_project(&root, None, &[]).expect("Failed to analyze project");
 
     // Test with namespace packages included
-    let data_with_ns = graph.to_cytoscape_graph_data(false, true);
+    let data_with_ns = graph.to_cytoscape_graph_data(false, true, &RenderOptions::default());
     let serialized_with_ns = serde_json::to_string_pretty(&data_with_ns)
         .expect("Cytoscape graph data should serialize to JSON");
     insta::assert_snapshot!("cytoscape_with_namespace_packages", serialized_with_ns);
 
     // Test with namespace packages excluded (default)
-    let data_without_ns = graph.to_cytoscape_graph_data(false, false);
+    let data_without_ns = graph.to_cytoscape_graph_data(false, false, &RenderOptions::default());
     let serialized_without_ns = serde_json::to_string_pretty(&data_without_ns)
         .expect("Cytoscape graph data should serialize to JSON");
     insta::assert_snapshot!(
@@ -1558,19 +1751,42 @@ fn test_cytoscape_with_namespace_packages() {
     );
 }
 
+#[test]
+fn test_namespace_package_portions_merge_across_roots() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("split_namespace_project");
+    let graph = python::analyze_project(&root, None, &[]).expect("Failed to analyze project");
+
+    // plugins/src/company extends the company package of src/ with pkgutil, so its
+    // files are modules of the package rather than scripts named after their path
+    let plugin = python::ModulePath(vec!["company".into(), "plugin".into()]);
+    let hooks = python::ModulePath(vec!["company".into(), "ext".into(), "hooks".into()]);
+    assert!(graph.contains(&plugin) && !graph.is_script(&plugin));
+    assert!(graph.contains(&hooks));
+    assert!(graph.is_namespace_package(&python::ModulePath(vec!["company".into()])));
+    assert!(
+        !graph
+            .nodes()
+            .any(|module| module.to_dotted().starts_with("plugins"))
+    );
+    insta::assert_snapshot!(graph.to_dot(false, false, &RenderOptions::default()));
+}
+
 #[test]
 fn test_cytoscape_orphan_filtering() {
     let root = fixture_path();
     let graph = python::analyze_project(&root, None, &[]).expect("Failed to analyze project");
 
     // With orphans excluded (default)
-    let data_no_orphans = graph.to_cytoscape_graph_data(false, false);
+    let data_no_orphans = graph.to_cytoscape_graph_data(false, false, &RenderOptions::default());
     let serialized_no_orphans = serde_json::to_string_pretty(&data_no_orphans)
         .expect("Cytoscape graph data should serialize to JSON");
     insta::assert_snapshot!("cytoscape_no_orphans", serialized_no_orphans);
 
     // With orphans included
-    let data_with_orphans = graph.to_cytoscape_graph_data(true, false);
+    let data_with_orphans = graph.to_cytoscape_graph_data(true, false, &RenderOptions::default());
     let serialized_with_orphans = serde_json::to_string_pretty(&data_with_orphans)
         .expect("Cytoscape graph data should serialize to JSON");
     insta::assert_snapshot!("cytoscape_with_orphans", serialized_with_orphans);
@@ -1582,7 +1798,7 @@ fn test_cytoscape_json_escaping() {
     // This verifies that JSON escaping works correctly
     let root = fixture_path();
     let graph = python::analyze_project(&root, None, &[]).expect("Failed to analyze project");
-    let graph_data = graph.to_cytoscape_graph_data(false, false);
+    let graph_data = graph.to_cytoscape_graph_data(false, false, &RenderOptions::default());
     let serialized =
         serde_json::to_string(&graph_data).expect("Cytoscape graph data should serialize to JSON");
 
@@ -1608,7 +1824,7 @@ fn test_venv_excluded_from_module_discovery() {
     let root = venv_exclusion_fixture();
     let graph = python::analyze_project(&root, None, &[])
         .expect("Failed to analyze venv exclusion project");
-    let dot_output = graph.to_dot(true, false); // include orphans to see all discovered modules
+    let dot_output = graph.to_dot(true, false, &RenderOptions::default()); // include orphans to see all discovered modules
 
     // Should contain real_pkg modules but NOT .venv contents
     assert!(dot_output.contains("real_pkg"));
@@ -1624,3 +1840,2157 @@ fn test_venv_excluded_from_module_discovery() {
 
     insta::assert_snapshot!(dot_output);
 }
+
+#[test]
+fn test_cli_import_time_only() {
+    let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("nested_imports_project");
+
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(&project_root)
+        .arg("--import-time-only")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    insta::assert_snapshot!(stdout);
+}
+
+#[test]
+fn test_cli_runtime_only() {
+    let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("nested_imports_project");
+
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(&project_root)
+        .arg("--runtime-only")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    insta::assert_snapshot!(stdout);
+}
+
+#[test]
+fn test_cli_slowest_imports_report() {
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(fixture_path())
+        .arg("--import-profile")
+        .arg(import_profile_fixture())
+        .arg("--slowest")
+        .arg("3")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    insta::assert_snapshot!(stdout);
+}
+
+#[test]
+fn test_cli_json_output_with_coverage() {
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(fixture_path())
+        .arg("--coverage")
+        .arg(coverage_report_fixture("sample_python_project.xml"))
+        .arg("--downstream")
+        .arg("pkg_b.module_b")
+        .arg("--format")
+        .arg("json")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    insta::assert_snapshot!(stdout);
+}
+
+#[test]
+fn test_cli_edges_upstream_with_show_all() {
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(fixture_path())
+        .arg("--upstream")
+        .arg("pkg_a.module_a")
+        .arg("--show-all")
+        .arg("--edges")
+        .arg("upstream")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // main stays in the full view but loses its edges into the selection
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    insta::assert_snapshot!(stdout);
+}
+
+#[test]
+fn test_cli_edges_downstream_keeps_dependents_only() {
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(fixture_path())
+        .arg("--downstream")
+        .arg("pkg_a.module_a")
+        .arg("--show-all")
+        .arg("--edges")
+        .arg("downstream")
+        .arg("--format")
+        .arg("mermaid")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // Only main -> pkg_a.module_a points into the selection's dependents
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    insta::assert_snapshot!(stdout);
+}
+
+#[test]
+fn test_cli_edges_requires_selection() {
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(fixture_path())
+        .arg("--edges")
+        .arg("upstream")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--edges requires --downstream or --upstream"));
+}
+
+#[test]
+fn test_cli_hide_module_bridges_edges() {
+    let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("split_project");
+
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(&project_root)
+        .arg("--hide-module")
+        .arg("shop.core.checkout")
+        .arg("--hide-module")
+        .arg("shop.missing")
+        .arg("--format")
+        .arg("mermaid")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Warning: --hide-module shop.missing matches no module"));
+
+    // shop.api reaches cart, pricing, and users through the hidden checkout module
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    insta::assert_snapshot!(stdout);
+}
+
+#[test]
+fn test_ignore_comments_exclude_edges_and_files() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("ignore_comments_project");
+    let graph = python::analyze_project(&root, None, &[]).expect("Failed to analyze project");
+
+    // app.generated is left out entirely; main keeps only its unmarked import of app.core
+    insta::assert_snapshot!(graph.to_dot(true, false, &RenderOptions::default()));
+}
+
+#[test]
+fn test_cluster_scripts_by_directory_dot_output() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("project_with_scripts");
+    let mut graph = python::analyze_project(&root, None, &[]).expect("Failed to analyze project");
+    python::group_scripts(&mut graph, true, &[]);
+
+    // scripts/utils gets its own cluster even though helper.py is alone there
+    insta::assert_snapshot!(graph.to_dot(false, false, &RenderOptions::default()));
+}
+
+#[test]
+fn test_cli_script_categories_in_json() {
+    let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("project_with_scripts");
+
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(&project_root)
+        .arg("--script-category")
+        .arg("scripts=tooling")
+        .arg("--script-category")
+        .arg("scripts/utils/=automation")
+        .arg("--format")
+        .arg("json")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    insta::assert_snapshot!(stdout);
+}
+
+#[test]
+fn test_cli_openmetrics_output() {
+    let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("split_project");
+
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(&project_root)
+        .arg("--format")
+        .arg("openmetrics")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    insta::assert_snapshot!(stdout);
+}
+
+#[test]
+fn test_cli_openmetrics_downstream_selection() {
+    let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("sample_python_project");
+
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(&project_root)
+        .arg("--downstream")
+        .arg("pkg_a.module_a")
+        .arg("--format")
+        .arg("openmetrics")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    insta::assert_snapshot!(stdout);
+}
+
+#[test]
+fn test_cli_gexf_output() {
+    let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("split_project");
+
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(&project_root)
+        .arg("--format")
+        .arg("gexf")
+        .arg("--upstream")
+        .arg("shop.core.checkout")
+        .arg("--show-all")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    insta::assert_snapshot!(stdout);
+}
+
+#[test]
+fn test_cli_python_version_reports_newer_syntax() {
+    let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("modern_syntax_project");
+    let run = |args: &[&str]| {
+        Command::new(get_binary_path())
+            .arg("python")
+            .arg(&project_root)
+            .args(args)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let old = run(&["--python-version", "3.8"]);
+    assert!(
+        old.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&old.stderr)
+    );
+    let stderr = String::from_utf8_lossy(&old.stderr);
+    assert!(stderr.contains("router.py:5: "), "{stderr}");
+    assert!(stderr.contains("router.py:9: "), "{stderr}");
+    assert!(
+        stderr.contains("`match` statement on Python 3.8"),
+        "{stderr}"
+    );
+
+    // The imports are still read, including those inside the `match`
+    let current = run(&[]);
+    assert!(current.stderr.is_empty());
+    assert_eq!(old.stdout, current.stdout);
+    insta::assert_snapshot!(String::from_utf8_lossy(&current.stdout));
+
+    let invalid = run(&["--python-version", "2.7"]);
+    assert!(!invalid.status.success());
+    assert!(String::from_utf8_lossy(&invalid.stderr).contains("Invalid Python version '2.7'"));
+}
+
+#[test]
+fn test_cli_platform_prunes_guarded_imports() {
+    let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("platform_imports_project");
+    let run = |args: &[&str]| {
+        Command::new(get_binary_path())
+            .arg("python")
+            .arg(&project_root)
+            .args(args)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    // By default every edge is kept, with platform-specific ones tagged
+    let all = run(&["--format", "json"]);
+    assert!(
+        all.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&all.stderr)
+    );
+    let json: serde_json::Value = serde_json::from_slice(&all.stdout).unwrap();
+    let edges: Vec<String> = json["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|edge| format!("{} -> {} {}", edge["source"], edge["target"], edge["tags"]))
+        .collect();
+    insta::assert_snapshot!(edges.join("\n"));
+
+    let linux = run(&["--platform", "linux"]);
+    assert!(linux.status.success());
+    insta::assert_snapshot!(String::from_utf8_lossy(&linux.stdout));
+
+    let invalid = run(&["--platform", "freebsd"]);
+    assert!(!invalid.status.success());
+    assert!(String::from_utf8_lossy(&invalid.stderr).contains("Invalid platform 'freebsd'"));
+}
+
+#[test]
+fn test_cython_modules() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("cython_project");
+    let graph = python::analyze_project(&root, None, &[]).expect("Failed to analyze project");
+
+    let module = |name: &str| python::ModulePath::from_dotted(name).unwrap();
+    assert!(graph.is_extension(&module("numeric.core")));
+    // `kernels.pyx` and its `kernels.pxd` declarations are one module
+    assert!(graph.is_extension(&module("numeric.kernels")));
+    assert!(!graph.is_extension(&module("numeric.api")));
+
+    insta::assert_snapshot!(graph.to_dot(false, false, &RenderOptions::default()));
+}
+
+#[test]
+fn test_lazy_getattr_exports() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("lazy_exports_project");
+    let graph = python::analyze_project(&root, None, &[]).expect("Failed to analyze project");
+
+    let targets: Vec<String> = graph
+        .to_cytoscape_graph_data(true, false, &RenderOptions::default())
+        .edges
+        .into_iter()
+        .filter(|edge| edge.source == "app")
+        .map(|edge| edge.target)
+        .collect();
+    // `Client`, `Session`, and `render_pdf` come from the submodules `__getattr__` loads;
+    // `__version__` is an ordinary attribute of the package
+    insta::assert_snapshot!(targets.join("\n"), @r"
+    plugins.pdf_export
+    sdk
+    sdk.client
+    sdk.session
+    ");
+}
+
+#[test]
+fn test_relative_imports_in_package_init() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("relative_imports_project");
+    let graph = python::analyze_project(&root, None, &[]).expect("Failed to analyze project");
+
+    // `.` in an `__init__.py` is the package itself, not its parent
+    let edges: Vec<String> = graph
+        .to_cytoscape_graph_data(true, false, &RenderOptions::default())
+        .edges
+        .into_iter()
+        .map(|edge| format!("{} -> {}", edge.source, edge.target))
+        .collect();
+    insta::assert_snapshot!(edges.join("\n"), @r"
+    pkg -> pkg.core.engine
+    pkg.core -> pkg.core.engine
+    pkg.core -> pkg.settings
+    pkg.core.engine -> pkg.settings
+    pkg.legacy -> pkg.settings
+    ");
+}
+
+#[test]
+fn test_cli_strict_resolution() {
+    let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("relative_imports_project");
+    let run = |args: &[&str]| {
+        Command::new(get_binary_path())
+            .arg("python")
+            .arg(&project_root)
+            .args(args)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    assert!(run(&[]).status.success());
+
+    let strict = run(&["--strict-resolution"]);
+    assert!(!strict.status.success());
+    let stderr = String::from_utf8_lossy(&strict.stderr);
+    assert!(
+        stderr.contains("3 relative import(s) could not be resolved confidently"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains(
+            "legacy.py: from .missing import thing: pkg.missing is not part of the project"
+        ),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("legacy.py: from ... import toplevel: reaches above the top-level package"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains(
+            "run.py: from .helpers import setup: made from a script outside the source root"
+        ),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn test_cli_lifecycle_report() {
+    let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("lifecycle_project");
+    let run = |args: &[&str]| {
+        Command::new(get_binary_path())
+            .arg("python")
+            .arg(&project_root)
+            .args(["--lifecycle", "app.legacy=sunset"])
+            .args(args)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let report = run(&["--lifecycle-report"]);
+    assert!(
+        report.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&report.stderr)
+    );
+    insta::assert_snapshot!(String::from_utf8_lossy(&report.stdout));
+
+    // Header comments win over --lifecycle for their module
+    let json = run(&["--format", "json"]);
+    let data: serde_json::Value = serde_json::from_slice(&json.stdout).unwrap();
+    let lifecycles: Vec<String> = data["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|node| node.get("lifecycle").is_some())
+        .map(|node| format!("{} {}", node["id"], node["lifecycle"]))
+        .collect();
+    assert_eq!(
+        lifecycles,
+        [
+            r#""app.labs.vectors" "experimental""#,
+            r#""app.legacy.auth" "sunset""#,
+            r#""app.legacy.compat" "stable""#,
+        ]
+    );
+
+    let invalid = run(&["--lifecycle", "app.core=deprecated"]);
+    assert!(!invalid.status.success());
+    assert!(String::from_utf8_lossy(&invalid.stderr).contains("expected MODULE=STATE"));
+}
+
+#[test]
+fn test_source_root_from_packaging_metadata() {
+    let cases = [
+        (
+            "setup.cfg",
+            "[metadata]\nname = legacy\n\n[options]\npackage_dir =\n    = code\npackages = find:\n",
+        ),
+        ("setup.cfg", "[options]\npackage_dir = =code\n"),
+        (
+            "setup.py",
+            "from setuptools import setup\n\nsetup(name=\"legacy\", package_dir={\"\": \"code\"})\n",
+        ),
+        (
+            "pyproject.toml",
+            "[tool.poetry]\nname = \"legacy\"\npackages = [{ include = \"app\", from = \"code\" }]\n",
+        ),
+        (
+            "pyproject.toml",
+            "[tool.hatch.build.targets.wheel]\npackages = [\"code/app\"]\n",
+        ),
+        (
+            "pyproject.toml",
+            "[tool.pdm.build]\npackage-dir = \"code\"\n",
+        ),
+        ("pyproject.toml", "[tool.pdm]\npackage-dir = \"code\"\n"),
+    ];
+
+    for (file, content) in cases {
+        let project = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(project.path().join("code/app")).unwrap();
+        std::fs::write(project.path().join("code/app/__init__.py"), "").unwrap();
+        std::fs::write(project.path().join(file), content).unwrap();
+
+        let root = python::detect_source_root(project.path()).unwrap();
+        assert_eq!(root, project.path().join("code"), "{file}:\n{content}");
+    }
+}
+
+#[test]
+fn test_source_root_from_flit_module() {
+    let project = tempfile::tempdir().unwrap();
+    std::fs::write(
+        project.path().join("pyproject.toml"),
+        "[tool.flit.module]\nname = \"app\"\n",
+    )
+    .unwrap();
+    // The module is looked up in src/ first
+    std::fs::create_dir_all(project.path().join("src/app")).unwrap();
+    std::fs::write(project.path().join("src/app/__init__.py"), "").unwrap();
+    assert_eq!(
+        python::detect_source_root(project.path()).unwrap(),
+        project.path().join("src")
+    );
+
+    // src/ holding other packages does not matter once the module is in the project root
+    std::fs::rename(project.path().join("src/app"), project.path().join("app")).unwrap();
+    std::fs::create_dir_all(project.path().join("src/unrelated")).unwrap();
+    std::fs::write(project.path().join("src/unrelated/__init__.py"), "").unwrap();
+    assert_eq!(
+        python::detect_source_root(project.path()).unwrap(),
+        project.path()
+    );
+}
+
+#[test]
+fn test_ambiguous_source_root_requires_choice() {
+    let project = tempfile::tempdir().unwrap();
+    for dir in ["src/app", "legacy", "tests"] {
+        std::fs::create_dir_all(project.path().join(dir)).unwrap();
+        std::fs::write(project.path().join(dir).join("__init__.py"), "").unwrap();
+    }
+    std::fs::write(
+        project.path().join("src/app/main.py"),
+        "import legacy.old\n",
+    )
+    .unwrap();
+    std::fs::write(project.path().join("legacy/old.py"), "import app.main\n").unwrap();
+    let run = |args: &[&str]| {
+        Command::new(get_binary_path())
+            .arg("python")
+            .arg(project.path())
+            .args(args)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let output = run(&[]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Several plausible Python source roots")
+            && stderr.contains("src (2 modules), . (2 modules)")
+            && stderr.contains("--source-root auto-all"),
+        "{stderr}"
+    );
+
+    // Either root can be chosen, or both analyzed as one project
+    let output = run(&[
+        "--source-root",
+        project.path().join("src").to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    let output = run(&["--source-root", "auto-all"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let modules = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        modules.contains("app.main") && modules.contains("legacy.old"),
+        "{modules}"
+    );
+
+    // Test packages next to src/ are not a second root
+    std::fs::remove_dir_all(project.path().join("legacy")).unwrap();
+    assert_eq!(
+        python::detect_source_root(project.path()).unwrap(),
+        project.path().join("src")
+    );
+}
+
+#[test]
+fn test_cli_module_map() {
+    let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("module_map_project");
+
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(&project_root)
+        .arg("--module-map")
+        .arg("gen/protos=company.protos")
+        .arg("--module-map")
+        .arg("third_party/client.py=vendored.client")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // The generated and vendored files are modules under their import names, not scripts
+    assert!(!stdout.contains("gen.protos"), "{stdout}");
+    insta::assert_snapshot!(stdout);
+}
+
+#[test]
+fn test_cli_path_dependencies() {
+    let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("path_deps_project")
+        .join("app");
+    let run = |args: &[&str]| {
+        let output = Command::new(get_binary_path())
+            .arg("python")
+            .arg(&project_root)
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+        assert!(
+            output.status.success(),
+            "Command failed: {:?}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        output
+    };
+
+    let output = run(&["--format", "json"]);
+    // metrics' own dependency on shared is the same library; only the missing one warns
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Warning: path dependency fixtures not found at"),
+        "{stderr}"
+    );
+    assert_eq!(stderr.lines().count(), 1, "{stderr}");
+    let data: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let mut nodes: Vec<String> = data["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|node| node["type"] != "namespace_group")
+        .map(|node| format!("{} {}", node["id"], node["category"]))
+        .collect();
+    nodes.sort();
+    insta::assert_snapshot!(nodes.join("\n"));
+
+    let without = run(&["--no-path-dependencies"]);
+    let stdout = String::from_utf8_lossy(&without.stdout);
+    assert!(!stdout.contains("shared.text"), "{stdout}");
+}
+
+#[test]
+fn test_cli_max_nodes_falls_back() {
+    let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("split_project");
+    let run = |args: &[&str]| {
+        let output = Command::new(get_binary_path())
+            .arg("python")
+            .arg(&project_root)
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+        assert!(
+            output.status.success(),
+            "Command failed: {:?}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        (
+            String::from_utf8_lossy(&output.stdout).to_string(),
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        )
+    };
+
+    let (aggregated, warning) = run(&["--max-nodes", "4"]);
+    assert!(
+        warning.contains("showing packages up to depth 2"),
+        "{warning}"
+    );
+    insta::assert_snapshot!("max_nodes_aggregate", aggregated);
+
+    let (sampled, warning) = run(&["--max-edges", "3", "--limit-fallback", "sample"]);
+    assert!(warning.contains("best-connected modules"), "{warning}");
+    insta::assert_snapshot!("max_edges_sample", sampled);
+
+    // Data formats are never reduced
+    let (json, warning) = run(&["--max-nodes", "4", "--format", "json"]);
+    assert!(warning.is_empty(), "{warning}");
+    let data: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(data["edges"].as_array().unwrap().len(), 10);
+}
+
+#[test]
+fn test_cli_dot_clustering_flags() {
+    let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("project_with_scripts");
+    let run = |args: &[&str]| {
+        let output = Command::new(get_binary_path())
+            .arg("python")
+            .arg(&project_root)
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+        assert!(
+            output.status.success(),
+            "Command failed: {:?}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    // Plain output: modules and scripts apart, no package clusters
+    let plain = run(&["--cluster-roots", "--cluster-namespaces=false"]);
+    insta::assert_snapshot!("dot_cluster_roots_flat", plain);
+
+    // Filtered output gets the root clusters too; every script sits in the `scripts`
+    // package cluster, so there is nothing left for a scripts root cluster
+    let filtered = run(&["--upstream", "scripts.runner", "--cluster-roots"]);
+    assert!(filtered.contains("subgraph cluster_root {"), "{filtered}");
+    assert!(!filtered.contains("cluster_scripts_root"), "{filtered}");
+
+    // Highlighted output can drop them
+    let highlighted = run(&["--upstream", "scripts.runner", "--show-all"]);
+    assert!(
+        highlighted.contains("subgraph cluster_root {"),
+        "{highlighted}"
+    );
+    let highlighted = run(&[
+        "--upstream",
+        "scripts.runner",
+        "--show-all",
+        "--cluster-roots=false",
+    ]);
+    assert!(!highlighted.contains("cluster_root"), "{highlighted}");
+    assert!(
+        highlighted.contains("subgraph cluster_scripts {"),
+        "{highlighted}"
+    );
+}
+
+#[test]
+fn test_cli_namespace_grouping_options() {
+    let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("test_namespace_grouping");
+    let run = |args: &[&str]| {
+        let output = Command::new(get_binary_path())
+            .arg("python")
+            .arg(&project_root)
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+        (
+            output.status.success(),
+            String::from_utf8_lossy(&output.stdout).to_string(),
+        )
+    };
+
+    let (success, shallow) = run(&["--group-max-depth", "1"]);
+    assert!(success);
+    insta::assert_snapshot!("namespace_grouping_max_depth_1", shallow);
+
+    let (success, mermaid) = run(&["--group-min-children", "3", "--format", "mermaid"]);
+    assert!(success);
+    insta::assert_snapshot!("namespace_grouping_min_children_3_mermaid", mermaid);
+
+    let (success, _) = run(&["--group-min-children", "0"]);
+    assert!(!success, "a threshold of 0 would cluster single modules");
+}
+
+#[test]
+fn test_cli_show_edge_counts() {
+    let project = tempfile::tempdir().unwrap();
+    let app = project.path().join("app");
+    std::fs::create_dir_all(&app).unwrap();
+    std::fs::write(app.join("__init__.py"), "").unwrap();
+    std::fs::write(app.join("db.py"), "").unwrap();
+    std::fs::write(app.join("util.py"), "").unwrap();
+    std::fs::write(
+        app.join("main.py"),
+        "import app.db\nfrom app import db\nfrom app.db import connect\nimport app.util\n",
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        let output = Command::new(get_binary_path())
+            .arg("python")
+            .arg(project.path())
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+        assert!(
+            output.status.success(),
+            "Command failed: {:?}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    let dot = run(&["--show-edge-counts"]);
+    assert!(
+        dot.contains("\"app.main\" -> \"app.db\" [label=\"×3\"];"),
+        "{dot}"
+    );
+    assert!(dot.contains("\"app.main\" -> \"app.util\";"), "{dot}");
+    assert!(!run(&[]).contains("×3"));
+
+    let mermaid = run(&["--format", "mermaid", "--show-edge-counts"]);
+    assert!(mermaid.contains("-->|\"×3\"|"), "{mermaid}");
+
+    // JSON carries the count whether or not labels are drawn
+    let json: serde_json::Value = serde_json::from_str(&run(&["--format", "json"])).unwrap();
+    let counts: Vec<_> = json["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|edge| (edge["target"].as_str().unwrap(), edge.get("count").cloned()))
+        .collect();
+    assert!(
+        counts.contains(&("app.db", Some(serde_json::json!(3)))),
+        "{counts:?}"
+    );
+    assert!(counts.contains(&("app.util", None)), "{counts:?}");
+}
+
+#[test]
+fn test_cli_mermaid_edge_labels() {
+    let project = tempfile::tempdir().unwrap();
+    let app = project.path().join("app");
+    std::fs::create_dir_all(&app).unwrap();
+    std::fs::write(app.join("__init__.py"), "").unwrap();
+    for module in ["db", "util", "lazy"] {
+        std::fs::write(app.join(format!("{module}.py")), "").unwrap();
+    }
+    std::fs::write(
+        app.join("main.py"),
+        "import app.db\nfrom app import db\nfrom app.db import connect\nimport app.util\n\
+         def run():\n    import app.lazy\n",
+    )
+    .unwrap();
+    let annotations = project.path().join("annotations.json");
+    std::fs::write(&annotations, r#"{"app.db": {"loc": "120"}}"#).unwrap();
+    let run = |args: &[&str]| {
+        Command::new(get_binary_path())
+            .arg("python")
+            .arg(project.path())
+            .args(["--format", "mermaid"])
+            .args(args)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let output = run(&[
+        "--mermaid-edge-labels",
+        "kind,count,weight=loc",
+        "--annotations",
+        annotations.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    let mermaid = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        mermaid.contains(" -- \"import_time, ×3, loc=120\" --> "),
+        "{mermaid}"
+    );
+    assert!(mermaid.contains(" -- \"import_time\" --> "), "{mermaid}");
+    assert!(mermaid.contains(" -. \"deferred\" .-> "), "{mermaid}");
+
+    // Without the option the edges stay unlabeled
+    let plain = String::from_utf8_lossy(&run(&[]).stdout).into_owned();
+    assert!(!plain.contains(" -- \""), "{plain}");
+
+    let invalid = run(&["--mermaid-edge-labels", "weight"]);
+    assert!(!invalid.status.success());
+    assert!(
+        String::from_utf8_lossy(&invalid.stderr).contains("expected kind, count, or weight=KEY")
+    );
+}
+
+#[test]
+fn test_cli_link_template() {
+    let project = tempfile::tempdir().unwrap();
+    let app = project.path().join("app");
+    std::fs::create_dir_all(&app).unwrap();
+    std::fs::write(app.join("__init__.py"), "").unwrap();
+    std::fs::write(app.join("db.py"), "import app\n").unwrap();
+    std::fs::write(app.join("main.py"), "import app.db\n").unwrap();
+    let run = |format: &str| {
+        let output = Command::new(get_binary_path())
+            .arg("python")
+            .arg(project.path())
+            .args(["--format", format])
+            .args([
+                "--link-template",
+                "https://example.com/repo/blob/main/{path}#L{line}",
+            ])
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    let dot = run("dot");
+    assert!(
+        dot.contains(r#""app.main" [URL="https://example.com/repo/blob/main/app/main.py#L1"]"#),
+        "{dot}"
+    );
+    // The package cluster links to its __init__.py
+    assert!(
+        dot.contains(r#"URL = "https://example.com/repo/blob/main/app/__init__.py#L1";"#),
+        "{dot}"
+    );
+
+    let json: serde_json::Value = serde_json::from_str(&run("json")).unwrap();
+    let db = json["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|node| node["id"] == "app.db")
+        .unwrap();
+    assert_eq!(
+        db["href"],
+        "https://example.com/repo/blob/main/app/db.py#L1"
+    );
+}
+
+#[test]
+fn test_cli_test_reach() {
+    let project = tempfile::tempdir().unwrap();
+    let app = project.path().join("app");
+    let tests = project.path().join("tests");
+    std::fs::create_dir_all(&app).unwrap();
+    std::fs::create_dir_all(&tests).unwrap();
+    std::fs::write(app.join("__init__.py"), "").unwrap();
+    std::fs::write(app.join("models.py"), "").unwrap();
+    std::fs::write(app.join("core.py"), "from app import models\n").unwrap();
+    std::fs::write(app.join("cli.py"), "from app import core\n").unwrap();
+    std::fs::write(tests.join("test_core.py"), "from app import core\n").unwrap();
+    std::fs::write(tests.join("test_models.py"), "import app.models\n").unwrap();
+
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(project.path())
+        .arg("--test-reach")
+        .output()
+        .expect("Failed to execute command");
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    insta::assert_snapshot!(String::from_utf8_lossy(&output.stdout));
+}
+
+#[test]
+fn test_cli_explain_config() {
+    let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures");
+    let explain = |project: &str, args: &[&str]| {
+        let project_root = fixtures.join(project);
+        let output = Command::new(get_binary_path())
+            .arg("python")
+            .arg(&project_root)
+            .arg("--explain-config")
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+        let stdout = String::from_utf8_lossy(&output.stdout)
+            .replace(&project_root.display().to_string(), "<project>");
+        (output.status.code(), stdout)
+    };
+
+    let (status, scripts) = explain("project_with_scripts", &[]);
+    assert_eq!(status, Some(0));
+    insta::assert_snapshot!("explain_config_scripts", scripts);
+
+    // Default and custom exclusions and ignored files are reported with their reason
+    let (_, venv) = explain(
+        "venv_exclusion_project",
+        &["--exclude-scripts", "pkg/*_test.py"],
+    );
+    assert!(venv.contains(".venv/: default exclusion '.venv'"), "{venv}");
+    assert!(venv.contains("--exclude-scripts: pkg/*_test.py"), "{venv}");
+    let (_, ignored) = explain("ignore_comments_project", &[]);
+    assert!(
+        ignored.contains("app/generated.py: # deptree: ignore-file"),
+        "{ignored}"
+    );
+
+    // Without a source root the candidates are still explained, and the exit code says so
+    let empty = tempfile::tempdir().unwrap();
+    std::fs::create_dir(empty.path().join("src")).unwrap();
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(empty.path())
+        .arg("--explain-config")
+        .output()
+        .expect("Failed to execute command");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stdout.contains("Source root: none found"), "{stdout}");
+    assert!(
+        stdout.contains("Passed over src/ (src layout): no Python packages"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn test_cli_generated_modules() {
+    let project = tempfile::tempdir().unwrap();
+    let app = project.path().join("app");
+    std::fs::create_dir_all(app.join("protos")).unwrap();
+    std::fs::write(app.join("__init__.py"), "").unwrap();
+    std::fs::write(app.join("protos").join("__init__.py"), "").unwrap();
+    std::fs::write(app.join("models.py"), "").unwrap();
+    std::fs::write(
+        app.join("protos").join("user_pb2.py"),
+        "from app.protos import common_pb2\nimport app.models\n",
+    )
+    .unwrap();
+    std::fs::write(app.join("protos").join("common_pb2.py"), "").unwrap();
+    std::fs::write(app.join("client.py"), "# @generated\nimport app.models\n").unwrap();
+    std::fs::write(app.join("schema.py"), "import app.models\n").unwrap();
+    std::fs::write(
+        app.join("service.py"),
+        "import app.protos.user_pb2\nimport app.client\nimport app.schema\n",
+    )
+    .unwrap();
+    std::fs::write(
+        project.path().join(".gitattributes"),
+        "app/schema.py linguist-generated=true\n",
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        let output = Command::new(get_binary_path())
+            .arg("python")
+            .arg(project.path())
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+        assert!(
+            output.status.success(),
+            "Command failed: {:?}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    let json: serde_json::Value = serde_json::from_str(&run(&["--format", "json"])).unwrap();
+    let mut generated: Vec<&str> = json["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|node| node["generated"] == true)
+        .map(|node| node["id"].as_str().unwrap())
+        .collect();
+    generated.sort();
+    assert_eq!(
+        generated,
+        vec![
+            "app.client",
+            "app.protos.common_pb2",
+            "app.protos.user_pb2",
+            "app.schema"
+        ]
+    );
+
+    // Hidden generated modules keep their dependents connected to what they import
+    let dot = run(&["--hide-generated"]);
+    assert!(!dot.contains("_pb2"), "{dot}");
+    assert!(!dot.contains("app.client"), "{dot}");
+    assert!(dot.contains("\"app.service\" -> \"app.models\""), "{dot}");
+}
+
+#[test]
+fn test_cli_forbid_new_cycles() {
+    let project = tempfile::tempdir().unwrap();
+    let app = project.path().join("app");
+    std::fs::create_dir_all(&app).unwrap();
+    std::fs::write(app.join("__init__.py"), "").unwrap();
+    std::fs::write(app.join("a.py"), "import app.b\n").unwrap();
+    std::fs::write(app.join("b.py"), "import app.a\n").unwrap();
+    std::fs::write(app.join("c.py"), "").unwrap();
+    std::fs::write(app.join("d.py"), "import app.c\n").unwrap();
+    let run = |args: &[&str]| {
+        let output = Command::new(get_binary_path())
+            .arg("python")
+            .arg(project.path())
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        (output.status.code(), stdout)
+    };
+
+    let (_, json) = run(&["--format", "json"]);
+    let baseline = project.path().join("baseline.json");
+    std::fs::write(&baseline, json).unwrap();
+    let baseline = baseline.to_str().unwrap();
+
+    // The existing a <-> b cycle is tolerated
+    let (status, report) = run(&["--forbid-new-cycles", "--baseline", baseline]);
+    assert_eq!(status, Some(0), "{report}");
+    assert!(
+        report.contains("No new import cycles (1 existing cycle(s) are in the baseline)"),
+        "{report}"
+    );
+
+    std::fs::write(
+        app.join("c.py"),
+        "\"\"\"Helpers.\"\"\"\n\nfrom app import d\n",
+    )
+    .unwrap();
+    let (status, report) = run(&["--forbid-new-cycles", "--baseline", baseline]);
+    assert_eq!(status, Some(1), "{report}");
+    insta::assert_snapshot!("forbid_new_cycles", report);
+}
+
+#[test]
+fn test_cli_impact_report() {
+    let project = tempfile::tempdir().unwrap();
+    let app = project.path().join("app");
+    std::fs::create_dir_all(&app).unwrap();
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(project.path())
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()
+            .expect("Failed to run git");
+        assert!(status.success(), "git {args:?} failed");
+    };
+    let commit = |files: &[(&str, &str)]| {
+        for (file, content) in files {
+            std::fs::write(app.join(file), content).unwrap();
+        }
+        git(&["add", "-A"]);
+        git(&["commit", "--quiet", "-m", "change"]);
+    };
+    git(&["init", "--quiet"]);
+    commit(&[
+        ("__init__.py", ""),
+        ("db.py", ""),
+        ("core.py", "import app.db\n"),
+        ("api.py", "import app.core\n"),
+        ("cli.py", "import app.core\n"),
+    ]);
+    commit(&[("core.py", "import app.db\n\n\nVERSION = 2\n")]);
+    commit(&[("core.py", "import app.db\n\n\nVERSION = 3\n")]);
+    commit(&[("api.py", "import app.core\n\n\nVERSION = 2\n")]);
+
+    let run = |args: &[&str]| {
+        let output = Command::new(get_binary_path())
+            .arg("python")
+            .arg(project.path())
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+        assert!(
+            output.status.success(),
+            "Command failed: {:?}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    insta::assert_snapshot!("impact_report", run(&["--impact-report"]));
+    let reach_weighted = run(&["--impact-report", "--impact-weights", "2,1"]);
+    assert!(
+        reach_weighted
+            .contains("        12       2       3  app.core\n         9       3       1  app.db"),
+        "{reach_weighted}"
+    );
+
+    let json: serde_json::Value =
+        serde_json::from_str(&run(&["--impact-scores", "--format", "json"])).unwrap();
+    let core = json["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|node| node["id"] == "app.core")
+        .unwrap();
+    assert_eq!(
+        core["annotations"],
+        serde_json::json!({"churn": "3", "impact": "6", "reach": "2"})
+    );
+}
+
+#[test]
+fn test_cli_timeout_renders_partial_graph() {
+    let run = |timeout: &str| {
+        Command::new(get_binary_path())
+            .arg("python")
+            .arg(fixture_path())
+            .args(["--timeout", timeout])
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    // An exhausted budget still succeeds, with every file reported as unprocessed
+    let output = run("0");
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("pkg_a"));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("time budget exhausted after parsing 0 of"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("pkg_a/module_a.py"), "{stderr}");
+
+    // A generous budget changes nothing
+    let output = run("600");
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("time budget"));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\"pkg_a.module_a\""));
+
+    assert!(!run("-1").status.success());
+}
+
+#[test]
+fn test_cli_importers_quickfix_output() {
+    let project = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("nested_imports_project");
+    // Run from the project so the printed paths are relative, as an editor expects
+    let output = Command::new(get_binary_path())
+        .current_dir(&project)
+        .args(["importers", "base_module.py"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    insta::assert_snapshot!(String::from_utf8_lossy(&output.stdout));
+
+    let output = Command::new(get_binary_path())
+        .current_dir(&project)
+        .args(["importers", "missing.py"])
+        .output()
+        .expect("Failed to execute command");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_cli_badge_output() {
+    let badge = |args: &[&str]| {
+        let output = Command::new(get_binary_path())
+            .arg("python")
+            .arg(fixture_path())
+            .args(["--format", "badge"])
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    insta::assert_snapshot!(
+        "badge_dependents_json",
+        badge(&["--module", "pkg_b.module_b"])
+    );
+    insta::assert_snapshot!(
+        "badge_cycle_svg",
+        badge(&["--module", "main", "--badge-metric", "cycle", "--badge-svg"])
+    );
+
+    // The module is required
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(fixture_path())
+        .args(["--format", "badge"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_cli_sample_keeps_hub_and_cycle() {
+    let project = tempfile::tempdir().unwrap();
+    let app = project.path().join("app");
+    std::fs::create_dir_all(&app).unwrap();
+    std::fs::write(app.join("__init__.py"), "").unwrap();
+    std::fs::write(app.join("core.py"), "import app.db\n").unwrap();
+    std::fs::write(app.join("db.py"), "").unwrap();
+    for client in ["api", "cli", "jobs", "admin"] {
+        std::fs::write(app.join(format!("{client}.py")), "import app.core\n").unwrap();
+    }
+    std::fs::write(app.join("left.py"), "import app.right\n").unwrap();
+    std::fs::write(app.join("right.py"), "import app.left\n").unwrap();
+    let run = |args: &[&str]| {
+        let output = Command::new(get_binary_path())
+            .arg("python")
+            .arg(project.path())
+            .args(["--format", "json"])
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success());
+        let data: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let mut modules: Vec<String> = data["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|node| node["type"] != "namespace_group")
+            .map(|node| node["id"].as_str().unwrap().to_string())
+            .collect();
+        modules.sort();
+        (
+            modules,
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        )
+    };
+
+    let (modules, stderr) = run(&["--sample", "4"]);
+    // The cycle, the hub, and one of the hub's neighbors
+    assert_eq!(modules.len(), 4, "{modules:?}");
+    for module in ["app.core", "app.left", "app.right"] {
+        assert!(modules.contains(&module.to_string()), "{modules:?}");
+    }
+    assert!(
+        stderr.contains("Sampled 4 of 9 modules (seed 0)"),
+        "{stderr}"
+    );
+
+    // Deterministic for a seed
+    assert_eq!(
+        run(&["--sample", "4", "--seed", "7"]),
+        run(&["--sample", "4", "--seed", "7"])
+    );
+}
+
+#[test]
+fn test_cli_layout_seed_adds_reproducible_positions() {
+    let fixture = fixture_path();
+    let run = |args: &[&str]| {
+        let output = Command::new(get_binary_path())
+            .arg("python")
+            .arg(&fixture)
+            .args(["--format", "json"])
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success());
+        let data: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        data["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|node| (node["id"].to_string(), node.get("position").cloned()))
+            .collect::<Vec<_>>()
+    };
+
+    assert!(run(&[]).iter().all(|(_, position)| position.is_none()));
+    let positions = run(&["--layout-seed", "3"]);
+    assert!(
+        positions
+            .iter()
+            .any(|(_, position)| position.as_ref().is_some_and(|p| p["x"].is_f64()))
+    );
+    assert_eq!(run(&["--layout-seed", "3"]), positions);
+    assert_ne!(run(&["--layout-seed", "4"]), positions);
+}
+
+#[test]
+fn test_cli_anonymize_replaces_names_and_persists_map() {
+    let fixture = fixture_path();
+    let temp = tempfile::tempdir().unwrap();
+    let map = temp.path().join("names.json");
+    let run = |args: &[&str]| {
+        let output = Command::new(get_binary_path())
+            .arg("python")
+            .arg(&fixture)
+            .arg("--anonymize")
+            .arg("--anonymize-map")
+            .arg(&map)
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    // Module arguments take the real names
+    let mermaid = run(&["--format", "mermaid", "--downstream", "pkg_b.module_b"]);
+    assert!(!mermaid.contains("pkg_"), "{mermaid}");
+    insta::assert_snapshot!("anonymize_mermaid", mermaid);
+
+    // The mapping file keeps the segments stable across runs
+    let mapping: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&map).unwrap()).unwrap();
+    assert_eq!(mapping["pkg_b.module_b"], "m5");
+    assert_eq!(
+        run(&["--format", "list", "--upstream", "main"]),
+        "m1\nm2.m3\nm4.m5\n"
+    );
+}
+
+#[test]
+fn test_cli_split_package_init() {
+    let run = |args: &[&str]| {
+        let output = Command::new(get_binary_path())
+            .arg("python")
+            .arg(fixture_path())
+            .args(["--format", "list", "--upstream", "main,pkg_a"])
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    // Merged by default
+    assert!(!run(&[]).contains("__init__"));
+
+    // The import in pkg_a/__init__.py is made by its own node, which pkg_a depends on
+    insta::assert_snapshot!("split_package_init", run(&["--split-package-init"]));
+}
+
+#[test]
+fn test_cli_side_effect_hotspots() {
+    let project = tempfile::tempdir().unwrap();
+    let files = [
+        (
+            "app/__init__.py",
+            "\"\"\"App.\"\"\"\nfrom app import settings\n__all__ = [\"settings\"]\n",
+        ),
+        ("app/settings.py", ""),
+        (
+            "plugins/__init__.py",
+            "import app.settings\nREGISTRY = {}\nfor name in [\"a\", \"b\"]:\n    REGISTRY[name] = name\ntry:\n    import ujson\nexcept ImportError:\n    ujson = None\n",
+        ),
+        ("plugins/base.py", ""),
+        ("api.py", "import plugins.base\nimport app\n"),
+        ("cli.py", "from plugins import REGISTRY\n"),
+    ];
+    for (file, source) in files {
+        let path = project.path().join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, source).unwrap();
+    }
+
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(project.path())
+        .args(["--side-effect-hotspots", "10"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    // The app package only imports, so it is no hotspot
+    insta::assert_snapshot!(
+        "side_effect_hotspots",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
+
+#[test]
+fn test_cli_dead_code_report() {
+    let project = tempfile::tempdir().unwrap();
+    let files = [
+        (
+            "pyproject.toml",
+            "[project]\nname = \"shop\"\n\n[project.scripts]\nshop = \"shop.cli:main\"\n",
+        ),
+        ("shop/__init__.py", ""),
+        (
+            "shop/cli.py",
+            "import importlib\nfrom shop import orders\n\ndef load(name):\n    return importlib.import_module(f\"shop.plugins.{name}\")\n",
+        ),
+        (
+            "shop/orders.py",
+            "import importlib\nexporter = importlib.import_module(\".exporters.csv\", __package__)\n",
+        ),
+        ("shop/exporters/__init__.py", ""),
+        ("shop/exporters/csv.py", ""),
+        ("shop/exporters/xml.py", ""),
+        ("shop/plugins/__init__.py", ""),
+        ("shop/plugins/stripe.py", ""),
+        ("shop/admin.py", "from shop.exporters import xml\n"),
+        // Never loaded, so its import of any module does not count
+        (
+            "shop/legacy.py",
+            "def run(name):\n    return __import__(name)\n",
+        ),
+    ];
+    for (file, source) in files {
+        let path = project.path().join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, source).unwrap();
+    }
+
+    let report = |entry_points: &[&str]| {
+        let mut command = Command::new(get_binary_path());
+        command.arg("python").arg(project.path()).arg("--dead-code");
+        for entry_point in entry_points {
+            command.args(["--entry-point", entry_point]);
+        }
+        let output = command.output().expect("Failed to execute command");
+        assert!(
+            output.status.success(),
+            "Command failed: {:?}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    insta::assert_snapshot!("dead_code", report(&[]));
+    // An entry point given as a file path loads what it imports
+    let with_admin = report(&["shop/admin.py"]);
+    assert!(with_admin.starts_with("3 of 10 modules unreachable from 2 entry points\n"));
+    assert!(!with_admin.contains("shop.exporters.xml"));
+}
+
+#[test]
+fn test_cli_emit_intermediate_representations() {
+    let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("relative_imports_project");
+    let emit = |representation: &str| {
+        let output = Command::new(get_binary_path())
+            .arg("python")
+            .arg(&project_root)
+            .args(["--emit", representation])
+            .output()
+            .expect("Failed to execute command");
+        assert!(
+            output.status.success(),
+            "Command failed: {:?}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    let parse =
+        |json: String| -> serde_json::Value { serde_json::from_str(&json).expect("Invalid JSON") };
+
+    let modules = parse(emit("modules"));
+    assert_eq!(modules["pkg.core"], "src/pkg/core/__init__.py");
+    assert_eq!(modules["pkg.legacy"], "src/pkg/legacy.py");
+
+    let imports = parse(emit("imports"));
+    let legacy = imports
+        .as_array()
+        .expect("files array")
+        .iter()
+        .find(|file| file["module"] == "pkg.legacy")
+        .expect("pkg.legacy was parsed");
+    let statements: Vec<&str> = legacy["imports"]
+        .as_array()
+        .expect("imports array")
+        .iter()
+        .map(|import| import["statement"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        statements,
+        vec![
+            "from . import settings",
+            "from .missing import thing",
+            "from ... import toplevel"
+        ]
+    );
+
+    insta::assert_snapshot!("emit_resolution", emit("resolution"));
+}
+
+#[test]
+fn test_cli_multiple_formats_in_one_run() {
+    let run = |args: &[&str]| {
+        Command::new(get_binary_path())
+            .arg("python")
+            .arg(fixture_path())
+            .args(args)
+            .output()
+            .expect("Failed to execute command")
+    };
+    let out = tempfile::tempdir().unwrap();
+    let dot = out.path().join("graph.dot");
+    let json = out.path().join("graph.json");
+
+    let output = run(&[
+        "--format",
+        "dot",
+        "--format",
+        "json",
+        "--output",
+        dot.to_str().unwrap(),
+        "--output",
+        json.to_str().unwrap(),
+    ]);
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(output.stdout.is_empty());
+    // Each file matches what a run with that format alone prints
+    for (format, path) in [("dot", &dot), ("json", &json)] {
+        let single = run(&["--format", format]);
+        assert_eq!(
+            std::fs::read_to_string(path).unwrap(),
+            String::from_utf8_lossy(&single.stdout)
+        );
+    }
+
+    let missing = run(&["--format", "dot", "--format", "json"]);
+    assert!(!missing.status.success());
+    assert!(
+        String::from_utf8_lossy(&missing.stderr)
+            .contains("--output needs one file per format in the same order (got 0)")
+    );
+}
+
+#[test]
+fn test_shadowed_module_names_are_reported_and_kept_apart() {
+    let project = tempfile::tempdir().unwrap();
+    let src = project.path().join("src");
+    let scripts = project.path().join("scripts");
+    for dir in [src.join("app"), src.join("json"), scripts.clone()] {
+        std::fs::create_dir_all(dir).unwrap();
+    }
+    std::fs::write(src.join("app/__init__.py"), "").unwrap();
+    std::fs::write(src.join("app/main.py"), "import json\n").unwrap();
+    std::fs::write(src.join("json/__init__.py"), "").unwrap();
+    std::fs::write(src.join("json.py"), "import app.main\n").unwrap();
+    std::fs::write(scripts.join("json.py"), "def loads(text):\n    pass\n").unwrap();
+    std::fs::write(
+        scripts.join("run.py"),
+        "import json\nfrom json import loads\nimport app.main\n",
+    )
+    .unwrap();
+
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(project.path())
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(
+            "Warning: src/json.py is shadowed by the package json; it is shown as src.json"
+        ),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains(
+            "Warning: scripts/json.py shadows the project's json module for the scripts next to it"
+        ),
+        "{stderr}"
+    );
+
+    let edges = String::from_utf8_lossy(&output.stdout);
+    // The package wins inside the project, the sibling script next to a script
+    assert!(edges.contains(r#""app.main" -> "json";"#), "{edges}");
+    assert!(
+        edges.contains(r#""scripts.run" -> "scripts.json";"#),
+        "{edges}"
+    );
+    assert!(edges.contains(r#""scripts.run" -> "app.main";"#), "{edges}");
+    assert!(!edges.contains(r#""scripts.run" -> "json";"#), "{edges}");
+    // The shadowed module's imports stay its own
+    assert!(edges.contains(r#""src.json" -> "app.main";"#), "{edges}");
+    assert!(!edges.contains(r#"    "json" -> "app.main";"#), "{edges}");
+}
+
+#[test]
+fn test_cli_importers_show_import_aliases() {
+    let project = tempfile::tempdir().unwrap();
+    let app = project.path().join("app");
+    std::fs::create_dir_all(&app).unwrap();
+    std::fs::write(app.join("__init__.py"), "").unwrap();
+    std::fs::write(app.join("db.py"), "").unwrap();
+    std::fs::write(
+        app.join("api.py"),
+        "import app.db as database\nfrom app import db as store\nfrom app import db\n",
+    )
+    .unwrap();
+
+    let output = Command::new(get_binary_path())
+        .current_dir(project.path())
+        .args(["importers", "app/db.py"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "app/api.py:1:1: app.api imports app.db as database\n\
+         app/api.py:2:1: app.api imports app.db as store\n\
+         app/api.py:3:1: app.api imports app.db\n"
+    );
+}
+
+#[test]
+fn test_cli_team_coupling() {
+    let project = tempfile::tempdir().unwrap();
+    for (file, source) in [
+        ("billing/__init__.py", ""),
+        (
+            "billing/api.py",
+            "import billing.models\nimport search.index\nimport platform_db.session\n",
+        ),
+        ("billing/models.py", "import platform_db.session\n"),
+        ("search/__init__.py", ""),
+        (
+            "search/index.py",
+            "import platform_db.session\nimport search.tokens\n",
+        ),
+        ("search/tokens.py", ""),
+        ("platform_db/__init__.py", ""),
+        ("platform_db/session.py", ""),
+    ] {
+        let path = project.path().join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, source).unwrap();
+    }
+    let annotations = project.path().join("owners.json");
+    std::fs::write(
+        &annotations,
+        r#"{"billing": {"team": "payments", "owner": "ana"}, "search": {"team": "search"},
+            "platform_db": {"team": "platform"}}"#,
+    )
+    .unwrap();
+
+    let run = |args: &[&str]| {
+        Command::new(get_binary_path())
+            .arg("python")
+            .arg(project.path())
+            .arg("--annotations")
+            .arg(&annotations)
+            .arg("--team-coupling")
+            .args(args)
+            .output()
+            .expect("Failed to execute command")
+    };
+    let output = run(&[]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    insta::assert_snapshot!(String::from_utf8_lossy(&output.stdout));
+
+    // Any annotation can name the owner
+    let output = run(&["--owner-key", "owner"]);
+    assert!(output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stdout).contains("No module depends mostly on other teams")
+    );
+}
+
+#[test]
+fn test_cli_critical_path() {
+    let project = tempfile::tempdir().unwrap();
+    for (file, source) in [
+        ("app/__init__.py", ""),
+        ("app/main.py", "import app.util\nimport app.cli\n"),
+        ("app/util.py", ""),
+        ("app/cli.py", "from app import orders\n"),
+        ("app/orders.py", "from app import billing\n"),
+        (
+            "app/billing.py",
+            "from app import orders\nfrom app import db\n",
+        ),
+        ("app/db.py", ""),
+    ] {
+        let path = project.path().join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, source).unwrap();
+    }
+
+    let run = |entry: &str| {
+        Command::new(get_binary_path())
+            .arg("python")
+            .arg(project.path())
+            .arg("--critical-path")
+            .arg(entry)
+            .output()
+            .expect("Failed to execute command")
+    };
+    let output = run("app/main.py");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    insta::assert_snapshot!(String::from_utf8_lossy(&output.stdout));
+
+    let output = run("app.missing");
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("app.missing is not part of the analyzed project")
+    );
+}
+
+#[test]
+fn test_cli_stats_health_score() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("src_layout_project");
+    let run = |args: &[&str]| {
+        Command::new(get_binary_path())
+            .arg("stats")
+            .arg(&root)
+            .args(args)
+            .output()
+            .expect("Failed to execute command")
+    };
+    let output = run(&[]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    insta::assert_snapshot!(String::from_utf8_lossy(&output.stdout));
+
+    // Weighting cross-package imports out raises the score
+    let output = run(&["--weights", "cross_package=0", "--format", "json"]);
+    assert!(output.status.success());
+    let health: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(health["score"], 75.8);
+    assert_eq!(health["components"][3]["weight"], 0.0);
+
+    let output = run(&["--weights", "coupling=2"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_cli_report_flags_conflict_with_each_other_and_the_selection() {
+    let run = |args: &[&str]| {
+        Command::new(get_binary_path())
+            .arg("python")
+            .arg(fixture_path())
+            .args(args)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    for args in [
+        &["--dead-code", "--test-reach"][..],
+        &["--critical-path", "main", "--api-surface", "pkg_a"],
+        &[
+            "--suggest-splits",
+            "pkg_a",
+            "--downstream",
+            "pkg_a.module_a",
+        ],
+        &["pkg_a.module_a", "--side-effect-hotspots", "3"],
+    ] {
+        let output = run(args);
+        assert!(!output.status.success(), "{args:?} was accepted");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("cannot be used with"), "{args:?}: {stderr}");
+    }
+}
+
+#[test]
+fn test_cli_module_arguments_select_neighborhood() {
+    let run = |args: &[&str]| {
+        Command::new(get_binary_path())
+            .arg("python")
+            .arg(fixture_path())
+            .args(args)
+            .output()
+            .expect("Failed to execute command")
+    };
+    let lines = |output: &std::process::Output| -> Vec<String> {
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(String::from)
+            .collect()
+    };
+
+    let neighborhood = lines(&run(&["pkg_a/module_a.py", "--format", "list"]));
+    insta::assert_snapshot!(neighborhood.join("\n"));
+
+    // The union of both directions, where the flags together intersect them
+    let mut both = lines(&run(&["--upstream", "pkg_a.module_a", "--format", "list"]));
+    both.extend(lines(&run(&[
+        "--downstream",
+        "pkg_a.module_a",
+        "--format",
+        "list",
+    ])));
+    both.sort();
+    both.dedup();
+    assert_eq!(neighborhood, both);
+
+    let output = run(&["pkg_a.module_a", "--downstream", "pkg_b"]);
+    assert!(!output.status.success());
+}
+
+/// Each statement of `source` the analysis keeps, in normalized syntax
+fn extracted_imports(source: &str) -> Vec<String> {
+    python::extract_imports(source, python::PythonVersion::latest())
+        .unwrap()
+        .imports
+        .iter()
+        .map(|statement| statement.import.to_string())
+        .collect()
+}
+
+#[test]
+fn test_extract_imports_skips_ignore_comments() {
+    let source = "import a  # deptree: ignore\nimport b\nfrom c import (\n    d,  # deptree: ignore\n)\nfrom e import f  # noqa # deptree: ignore\nx = '# deptree: ignore-file'\n";
+
+    insta::assert_debug_snapshot!(extracted_imports(source));
+}
+
+#[test]
+fn test_extract_imports_reports_syntax_newer_than_target() {
+    let source = "import a\n\nmatch a.mode:\n    case 1:\n        import b\n";
+
+    let current = python::extract_imports(source, python::PythonVersion::PY310).unwrap();
+    assert!(current.unsupported.is_empty());
+    let old = python::extract_imports(source, python::PythonVersion::PY38).unwrap();
+    assert_eq!(old.imports.len(), 2);
+    // The message wording is the parser's; which statements are flagged is ours
+    assert!(
+        old.unsupported
+            .iter()
+            .all(|(_, message)| message.contains("match"))
+    );
+    let lines: Vec<usize> = old.unsupported.iter().map(|(line, _)| *line).collect();
+    insta::assert_debug_snapshot!(lines);
+}
+
+#[test]
+fn test_parse_python_version() {
+    let parsed: Vec<String> = ["3.8", "3.12", "3", "2.7", "3.x", "4.0", "3.99"]
+        .into_iter()
+        .map(|value| match python::parse_python_version(value) {
+            Ok(version) => format!("{value}: {}.{}", version.major, version.minor),
+            Err(_) => format!("{value}: invalid"),
+        })
+        .collect();
+    insta::assert_debug_snapshot!(parsed);
+}
+
+#[test]
+fn test_extract_imports_records_platform_guards() {
+    let source = r#"import sys, os
+import common
+if sys.platform == "win32":
+    import win
+elif sys.platform.startswith("linux"):
+    import linux
+else:
+    import other
+if os.name != "nt" and sys.platform in ("darwin",):
+    import mac
+if not sys.platform == "darwin":
+    import not_mac
+if feature_enabled() or sys.platform == "win32":
+    import maybe
+"#;
+    let imports = python::extract_imports(source, python::PythonVersion::latest()).unwrap();
+
+    let platforms: Vec<String> = imports
+        .imports
+        .iter()
+        .map(|statement| {
+            let platforms: Vec<&str> = python::Platform::ALL
+                .into_iter()
+                .filter(|&platform| statement.platforms.contains(platform))
+                .map(python::Platform::as_str)
+                .collect();
+            format!("{}: {}", statement.import, platforms.join(", "))
+        })
+        .collect();
+    insta::assert_debug_snapshot!(platforms);
+}
+
+#[test]
+fn test_parse_platform_rejects_unknown_platforms() {
+    let Err(error) = python::parse_platform("freebsd") else {
+        panic!("freebsd accepted");
+    };
+
+    insta::assert_snapshot!(error.to_string());
+}
+
+#[test]
+fn test_resolve_relative_from_package_init() {
+    let package = python::ModulePath::from_dotted("pkg_a.sub").unwrap();
+
+    // In pkg_a/sub/__init__.py `.` is pkg_a.sub itself, in pkg_a/sub.py it is pkg_a
+    let resolved: Vec<String> = [
+        (true, 1, Some("leaf")),
+        (true, 1, None),
+        (true, 2, Some("other")),
+        (true, 4, Some("x")),
+        (false, 1, Some("leaf")),
+        (false, 1, None),
+        (false, 2, None),
+    ]
+    .into_iter()
+    .map(|(is_package, level, module)| {
+        let import = format!(
+            "{}{}",
+            ".".repeat(level as usize),
+            module.unwrap_or_default()
+        );
+        let file = if is_package { "__init__" } else { "module" };
+        let target = package
+            .resolve_relative_from(is_package, level, module)
+            .map_or("unresolved".to_string(), |module| module.to_dotted());
+        format!("{import} in {file}: {target}")
+    })
+    .collect();
+    insta::assert_debug_snapshot!(resolved);
+}
+
+#[test]
+fn test_exclusion_reason() {
+    let patterns = vec!["tools/*".to_string(), "*_test.py".to_string()];
+
+    // Directories are pruned when a pattern covers everything below them
+    let reasons: Vec<String> = [
+        ("app/__pycache__/x.py", false),
+        ("venv3/lib", false),
+        ("pkg.egg-info", false),
+        ("tools/run.py", false),
+        ("app/builder.py", false),
+        ("tools", true),
+        ("app", true),
+    ]
+    .into_iter()
+    .map(|(path, is_dir)| {
+        let reason = python::exclusion_reason(Path::new(path), is_dir, &patterns);
+        format!("{path}: {reason:?}")
+    })
+    .collect();
+    assert_eq!(
+        python::exclusion_reason(Path::new(""), true, &["*".to_string()]),
+        None
+    );
+    insta::assert_debug_snapshot!(reasons);
+}
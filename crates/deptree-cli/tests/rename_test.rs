@@ -0,0 +1,176 @@
+//! Integration tests for the module rename check
+
+use deptree_utils::emit::{
+    AnalysisTrace, DynamicImport, DynamicName, FileTrace, Outcome, Resolution,
+};
+use deptree_utils::rename;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+mod common;
+use common::get_binary_path;
+
+fn rename_check(args: &[&str]) -> std::process::Output {
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("rename_project");
+    // Run from the project so the printed paths are relative to it
+    Command::new(get_binary_path())
+        .current_dir(fixture)
+        .arg("rename-check")
+        .args(args)
+        .output()
+        .expect("Failed to execute command")
+}
+
+#[test]
+fn test_cli_rename_check_report() {
+    let output = rename_check(&["app.db", "core.storage"]);
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    insta::assert_snapshot!(String::from_utf8_lossy(&output.stdout));
+
+    // A file path names the module too
+    let output = rename_check(&["src/app/db/__init__.py", "core.storage"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("Renaming app.db to core.storage"));
+
+    let output = rename_check(&["app.db", "app.util"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Module 'app.util' already exists"));
+}
+
+#[test]
+fn test_cli_rename_check_sed_script() {
+    let output = rename_check(&["app.db", "core.db", "--sed"]);
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    insta::assert_snapshot!(String::from_utf8_lossy(&output.stdout));
+}
+
+fn file(file: &str, module: &str, dynamic_imports: Vec<DynamicImport>) -> FileTrace {
+    FileTrace {
+        file: file.to_string(),
+        module: module.to_string(),
+        kind: "module",
+        recovered: false,
+        imports: Vec::new(),
+        dynamic_imports,
+    }
+}
+
+fn resolved(file: &str, line: usize, statement: &str, target: &str) -> Resolution {
+    Resolution {
+        file: file.to_string(),
+        line,
+        statement: statement.to_string(),
+        name: None,
+        outcome: Outcome::Resolved {
+            target: target.to_string(),
+            via: "module",
+        },
+    }
+}
+
+/// Trace of an `app` package whose `app.db` package is imported in every way
+fn trace() -> AnalysisTrace {
+    AnalysisTrace {
+        files: vec![
+            file("src/app/api.py", "app.api", Vec::new()),
+            file("src/app/db/__init__.py", "app.db", Vec::new()),
+            file("src/app/db/models.py", "app.db.models", Vec::new()),
+            file(
+                "src/app/plugins.py",
+                "app.plugins",
+                vec![
+                    DynamicImport {
+                        line: 4,
+                        name: DynamicName::Literal {
+                            module: "app.db.models".to_string(),
+                        },
+                    },
+                    DynamicImport {
+                        line: 5,
+                        name: DynamicName::Computed {
+                            prefix: "app.".to_string(),
+                        },
+                    },
+                ],
+            ),
+            file("src/app/util.py", "app.util", Vec::new()),
+        ],
+        modules: [
+            ("app.api", "src/app/api.py"),
+            ("app.db", "src/app/db/__init__.py"),
+            ("app.db.models", "src/app/db/models.py"),
+            ("app.plugins", "src/app/plugins.py"),
+            ("app.util", "src/app/util.py"),
+        ]
+        .into_iter()
+        .map(|(module, file)| (module.to_string(), file.to_string()))
+        .collect(),
+        resolution: vec![
+            resolved("src/app/api.py", 1, "import app.db.models", "app.db.models"),
+            resolved("src/app/api.py", 2, "from app import db", "app.db"),
+            resolved("src/app/api.py", 3, "from . import db", "app.db"),
+            resolved("src/app/api.py", 4, "import app.util", "app.util"),
+            // Inside the package, relative imports move along
+            resolved(
+                "src/app/db/__init__.py",
+                1,
+                "from . import models",
+                "app.db.models",
+            ),
+            resolved(
+                "src/app/db/models.py",
+                1,
+                "from ..util import x",
+                "app.util",
+            ),
+        ],
+    }
+}
+
+#[test]
+fn test_rename_check_collects_lines_to_update() {
+    // Leaving the app package breaks `..util`
+    let check = rename::rename_check(&trace(), "app.db", "core.storage").unwrap();
+
+    insta::assert_snapshot!(rename::render_report(&check, Path::new(".")));
+}
+
+#[test]
+fn test_rename_check_keeps_parent_imports_within_the_package() {
+    // Within the package, `from app import db` binds the old name: left to update by hand
+    let check = rename::rename_check(&trace(), "app.db", "app.storage").unwrap();
+
+    insta::assert_snapshot!(rename::render_sed_script(&check, Path::new(".")));
+}
+
+#[test]
+fn test_rename_check_rewrites_parent_imports_across_packages() {
+    let check = rename::rename_check(&trace(), "app.db", "core.db").unwrap();
+
+    insta::assert_snapshot!(rename::render_sed_script(&check, Path::new(".")));
+}
+
+#[test]
+fn test_rename_check_rejects_unknown_and_taken_names() {
+    let errors: Vec<String> = [("app.db", "app.util"), ("app.cache", "app.store")]
+        .into_iter()
+        .map(|(old, new)| {
+            rename::rename_check(&trace(), old, new)
+                .unwrap_err()
+                .to_string()
+        })
+        .collect();
+
+    insta::assert_debug_snapshot!(errors);
+}
@@ -0,0 +1,49 @@
+//! Tests for graph sampling (`--sample`)
+
+use deptree_graph::GraphId;
+use deptree_utils::python::PythonGraph;
+use deptree_utils::sample::sample;
+
+mod common;
+use common::python_graph;
+
+/// A hub with three importers and a dependency, and a cycle imported by a leaf
+fn hub_and_cycle() -> PythonGraph {
+    python_graph(&[
+        ("api", "core"),
+        ("cli", "core"),
+        ("jobs", "core"),
+        ("core", "db"),
+        ("x", "y"),
+        ("y", "x"),
+        ("leaf", "y"),
+    ])
+}
+
+fn sampled_names(graph: &PythonGraph, size: usize, seed: u64) -> Vec<String> {
+    let mut names: Vec<String> = sample(graph, size, seed)
+        .iter()
+        .map(GraphId::to_dotted)
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_sample_keeps_a_cycle_and_the_hub_neighborhood() {
+    insta::assert_debug_snapshot!(sampled_names(&hub_and_cycle(), 4, 0));
+}
+
+#[test]
+fn test_sample_is_the_same_for_the_same_seed() {
+    let graph = hub_and_cycle();
+
+    assert_eq!(sampled_names(&graph, 4, 0), sampled_names(&graph, 4, 0));
+}
+
+#[test]
+fn test_sample_larger_than_the_graph_keeps_every_module() {
+    let graph = hub_and_cycle();
+
+    assert_eq!(sample(&graph, 100, 0).len(), graph.node_count());
+}
@@ -0,0 +1,92 @@
+//! Integration tests for the published payload schemas
+
+use std::path::PathBuf;
+use std::process::Command;
+
+mod common;
+use common::get_binary_path;
+
+fn run(args: &[&str]) -> String {
+    let output = Command::new(get_binary_path())
+        .args(args)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+fn schema(payload: &str) -> serde_json::Value {
+    serde_json::from_str(&run(&["schema", payload])).expect("Schema is not valid JSON")
+}
+
+#[test]
+fn test_schema_graph_data() {
+    insta::assert_snapshot!(run(&["schema"]));
+}
+
+#[test]
+fn test_schema_filter_config() {
+    insta::assert_snapshot!(run(&["schema", "filter-config"]));
+}
+
+#[test]
+fn test_schema_filter_result() {
+    insta::assert_snapshot!(run(&["schema", "filter-result"]));
+}
+
+#[test]
+fn test_schema_filter_preset() {
+    insta::assert_snapshot!(run(&["schema", "filter-preset"]));
+}
+
+#[test]
+fn test_json_output_fields_are_described_by_schema() {
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("sample_python_project");
+    let coverage = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("coverage_reports")
+        .join("sample_python_project.xml");
+    let output: serde_json::Value = serde_json::from_str(&run(&[
+        "python",
+        fixture.to_str().expect("Fixture path is not UTF-8"),
+        "--format",
+        "json",
+        "--coverage",
+        coverage.to_str().expect("Fixture path is not UTF-8"),
+    ]))
+    .expect("Output is not valid JSON");
+    let schema = schema("graph-data");
+
+    for (collection, definition) in [("nodes", "GraphNode"), ("edges", "GraphEdge")] {
+        let properties = &schema["$defs"][definition]["properties"];
+        for item in output[collection].as_array().expect("Expected an array") {
+            for key in item.as_object().expect("Expected an object").keys() {
+                assert!(
+                    properties.get(key).is_some(),
+                    "{definition} schema does not describe field '{key}'"
+                );
+            }
+        }
+    }
+    for key in output["config"]
+        .as_object()
+        .expect("Expected config")
+        .keys()
+    {
+        assert!(
+            schema["$defs"]["GraphConfig"]["properties"]
+                .get(key)
+                .is_some(),
+            "GraphConfig schema does not describe field '{key}'"
+        );
+    }
+}
@@ -0,0 +1,58 @@
+//! Tests for the service-level deployment impact (`polyglot --impact-services`)
+
+use deptree_utils::config::Service;
+use deptree_utils::hook::SourceRoots;
+use deptree_utils::polyglot::{PolyglotGraph, PolyglotNode};
+use deptree_utils::services::{self, ServiceError, ServiceImpact};
+use std::path::Path;
+
+mod common;
+use common::module;
+
+/// Services `billing` (with `shared`) and `search` over a graph where `tools.report`
+/// belongs to neither, impacted by `changes`
+fn impact(changes: &[&str]) -> Result<ServiceImpact, ServiceError> {
+    let node = |name: &str| PolyglotNode::Python(module(name));
+    let mut graph = PolyglotGraph::new();
+    graph.add_dependency(node("billing.api"), node("shared.money"));
+    graph.add_dependency(node("search.index"), node("search.tokens"));
+    graph.add_dependency(node("tools.report"), node("billing.api"));
+    let service = |name: &str, modules: &[&str]| Service {
+        name: name.to_string(),
+        modules: modules.iter().map(|m| m.to_string()).collect(),
+    };
+    let services = [
+        service("billing", &["python:billing", "python:shared"]),
+        service("search", &["python:search"]),
+    ];
+
+    let changes: Vec<String> = changes.iter().map(|c| c.to_string()).collect();
+    services::impact_services(
+        &graph,
+        &SourceRoots::default(),
+        Path::new("."),
+        &services,
+        &changes,
+    )
+}
+
+#[test]
+fn test_impact_services_rolls_up_downstream_modules() {
+    let impact = impact(&["python:shared.money"]).unwrap();
+
+    insta::assert_snapshot!(services::render_report(&impact));
+}
+
+#[test]
+fn test_impact_services_skips_files_defining_no_module() {
+    let impact = impact(&["README.md"]).unwrap();
+
+    insta::assert_snapshot!(services::render_report(&impact));
+}
+
+#[test]
+fn test_impact_services_rejects_unknown_nodes() {
+    let error = impact(&["python:gone"]).unwrap_err();
+
+    insta::assert_snapshot!(error.to_string());
+}
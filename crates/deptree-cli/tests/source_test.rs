@@ -0,0 +1,192 @@
+//! Integration tests for analyzing archives and git sources with the `python` subcommand
+
+use deptree_utils::source::ProjectSource;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+mod common;
+use common::get_binary_path;
+
+fn fixture_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("sample_python_project")
+}
+
+fn mermaid_of(project: &str) -> String {
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(project)
+        .arg("--format")
+        .arg("mermaid")
+        .output()
+        .expect("Failed to execute command");
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .status()
+        .expect("Failed to run git");
+    assert!(status.success(), "git {args:?} failed");
+}
+
+#[test]
+fn test_tar_gz_archive_matches_directory() {
+    let temp = tempfile::tempdir().unwrap();
+    let archive_path = temp.path().join("sample-1.0.tar.gz");
+    let encoder = flate2::write::GzEncoder::new(
+        File::create(&archive_path).unwrap(),
+        flate2::Compression::default(),
+    );
+    let mut builder = tar::Builder::new(encoder);
+    builder
+        .append_dir_all("sample-1.0", fixture_path())
+        .unwrap();
+    builder.into_inner().unwrap().finish().unwrap();
+
+    // The single `sample-1.0/` directory is analyzed as the project root
+    assert_eq!(
+        mermaid_of(archive_path.to_str().unwrap()),
+        mermaid_of(fixture_path().to_str().unwrap())
+    );
+}
+
+#[test]
+fn test_zip_archive_matches_directory() {
+    let temp = tempfile::tempdir().unwrap();
+    let archive_path = temp.path().join("sample.zip");
+    let mut writer = zip::ZipWriter::new(File::create(&archive_path).unwrap());
+    let options = zip::write::SimpleFileOptions::default();
+    for entry in walkdir::WalkDir::new(fixture_path()) {
+        let entry = entry.unwrap();
+        let relative = entry.path().strip_prefix(fixture_path()).unwrap();
+        let name = relative.to_string_lossy().replace('\\', "/");
+        if entry.file_type().is_dir() {
+            if !name.is_empty() {
+                writer.add_directory(name, options).unwrap();
+            }
+        } else {
+            writer.start_file(name, options).unwrap();
+            std::io::copy(&mut File::open(entry.path()).unwrap(), &mut writer).unwrap();
+        }
+    }
+    writer.finish().unwrap();
+
+    assert_eq!(
+        mermaid_of(archive_path.to_str().unwrap()),
+        mermaid_of(fixture_path().to_str().unwrap())
+    );
+}
+
+#[test]
+fn test_git_source_checks_out_revision() {
+    let temp = tempfile::tempdir().unwrap();
+    let repo = temp.path().join("repo");
+    let status = Command::new("cp")
+        .arg("-r")
+        .arg(fixture_path())
+        .arg(&repo)
+        .status()
+        .expect("Failed to copy fixture");
+    assert!(status.success());
+    git(&repo, &["init", "--quiet"]);
+    git(&repo, &["add", "-A"]);
+    git(&repo, &["commit", "--quiet", "-m", "initial"]);
+    git(&repo, &["tag", "v1"]);
+
+    // Changes after the tag are not part of the analyzed revision
+    std::fs::write(
+        repo.join("pkg_b").join("later.py"),
+        "from pkg_b import module_b\n",
+    )
+    .unwrap();
+    git(&repo, &["add", "-A"]);
+    git(&repo, &["commit", "--quiet", "-m", "later"]);
+
+    let source = format!("git+file://{}@v1", repo.display());
+    assert_eq!(
+        mermaid_of(&source),
+        mermaid_of(fixture_path().to_str().unwrap())
+    );
+    assert!(mermaid_of(&format!("git+file://{}", repo.display())).contains("pkg_b.later"));
+}
+
+#[test]
+fn test_git_source_unknown_revision_fails() {
+    let temp = tempfile::tempdir().unwrap();
+    let repo = temp.path().join("repo");
+    std::fs::create_dir(&repo).unwrap();
+    std::fs::write(repo.join("main.py"), "").unwrap();
+    git(&repo, &["init", "--quiet"]);
+    git(&repo, &["add", "-A"]);
+    git(&repo, &["commit", "--quiet", "-m", "initial"]);
+
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(format!("git+file://{}@no-such-rev", repo.display()))
+        .output()
+        .expect("Failed to execute command");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("git checkout failed"));
+}
+
+#[test]
+fn test_git_source_options_are_not_passed_to_git() {
+    let temp = tempfile::tempdir().unwrap();
+    let marker = temp.path().join("pwned");
+    let payload = format!("--upload-pack=touch {}", marker.display());
+
+    for source in [
+        format!("git+{payload}"),
+        format!("git+file://{}/repo@{payload}", temp.path().display()),
+    ] {
+        let output = Command::new(get_binary_path())
+            .arg("python")
+            .arg(&source)
+            .output()
+            .expect("Failed to execute command");
+        assert!(!output.status.success(), "{source} was analyzed");
+    }
+    assert!(!marker.exists(), "git ran the injected command");
+
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(format!(
+            "git+file://{}/repo@--orphan",
+            temp.path().display()
+        ))
+        .output()
+        .expect("Failed to execute command");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid git revision '--orphan'"));
+}
+
+#[test]
+fn test_parse_git_sources() {
+    let sources = [
+        "git+https://example.com/org/repo.git@v1.2",
+        "git+ssh://git@example.com/org/repo.git",
+    ]
+    .map(|input| ProjectSource::parse(Path::new(input)));
+
+    insta::assert_debug_snapshot!(sources);
+}
+
+#[test]
+fn test_parse_missing_archive_is_local() {
+    assert_eq!(
+        ProjectSource::parse(Path::new("missing.tar.gz")),
+        ProjectSource::Local(PathBuf::from("missing.tar.gz"))
+    );
+}
@@ -0,0 +1,56 @@
+//! Integration tests for package split suggestions
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use deptree_utils::{python, splits};
+
+mod common;
+use common::get_binary_path;
+
+fn fixture_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("split_project")
+}
+
+#[test]
+fn test_suggest_splits_report() {
+    let graph =
+        python::analyze_project(&fixture_path(), None, &[]).expect("Failed to analyze project");
+    let package = python::ModulePath::from_dotted("shop.core").expect("Invalid module");
+    let suggestion = splits::suggest_splits(&graph, &package).expect("Failed to suggest splits");
+
+    insta::assert_snapshot!(splits::render_report(&suggestion));
+}
+
+#[test]
+fn test_suggest_splits_rejects_single_module() {
+    let graph =
+        python::analyze_project(&fixture_path(), None, &[]).expect("Failed to analyze project");
+    let module = python::ModulePath::from_dotted("shop.core.cart").expect("Invalid module");
+    let error = splits::suggest_splits(&graph, &module).expect_err("A module can't be split");
+
+    insta::assert_snapshot!(error.to_string());
+}
+
+#[test]
+fn test_cli_suggest_splits() {
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(fixture_path())
+        .arg("--suggest-splits")
+        .arg("shop.core")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    insta::assert_snapshot!(stdout);
+}
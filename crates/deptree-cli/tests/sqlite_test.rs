@@ -0,0 +1,125 @@
+//! Integration tests for the SQLite export (`--format sqlite`) and `--output`
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use rusqlite::Connection;
+
+mod common;
+use common::get_binary_path;
+
+fn fixture_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("split_project")
+}
+
+fn run(args: &[&str], output_path: Option<&Path>) -> Output {
+    let mut command = Command::new(get_binary_path());
+    command.arg("python").arg(fixture_path()).args(args);
+    if let Some(path) = output_path {
+        command.arg("--output").arg(path);
+    }
+    command.output().expect("Failed to execute command")
+}
+
+fn assert_success(output: &Output) {
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Render each row of `sql` as tab-separated values
+fn query(db: &Connection, sql: &str) -> String {
+    let mut statement = db.prepare(sql).unwrap();
+    let columns = statement.column_count();
+    let rows = statement
+        .query_map([], |row| {
+            let values: Vec<String> = (0..columns)
+                .map(|i| match row.get_ref(i).unwrap() {
+                    rusqlite::types::ValueRef::Null => "NULL".to_string(),
+                    rusqlite::types::ValueRef::Integer(v) => v.to_string(),
+                    rusqlite::types::ValueRef::Real(v) => v.to_string(),
+                    rusqlite::types::ValueRef::Text(v) => String::from_utf8_lossy(v).to_string(),
+                    rusqlite::types::ValueRef::Blob(_) => "<blob>".to_string(),
+                })
+                .collect();
+            Ok(values.join("\t"))
+        })
+        .unwrap();
+    rows.map(Result::unwrap).collect::<Vec<_>>().join("\n")
+}
+
+#[test]
+fn test_sqlite_export_tables() {
+    let temp = tempfile::tempdir().unwrap();
+    let db_path = temp.path().join("graph.db");
+    let output = run(
+        &["--format", "sqlite", "--downstream", "shop.core.users"],
+        Some(&db_path),
+    );
+    assert_success(&output);
+    assert!(output.stdout.is_empty());
+
+    let db = Connection::open(&db_path).unwrap();
+    let dump = format!(
+        "metadata:\n{}\n\nnodes:\n{}\n\nedges:\n{}\n\nmetrics:\n{}\n",
+        query(
+            &db,
+            "SELECT key, value FROM metadata WHERE key != 'generator' ORDER BY key"
+        ),
+        query(
+            &db,
+            "SELECT id, type, parent, is_orphan, highlighted FROM nodes ORDER BY id"
+        ),
+        query(
+            &db,
+            "SELECT source, target, kind, count FROM edges ORDER BY source, target"
+        ),
+        query(
+            &db,
+            "SELECT name, package, value FROM metrics ORDER BY name, package"
+        ),
+    );
+    insta::assert_snapshot!(dump);
+}
+
+#[test]
+fn test_sqlite_fan_in_query() {
+    let temp = tempfile::tempdir().unwrap();
+    let db_path = temp.path().join("graph.db");
+    assert_success(&run(&["--format", "sqlite"], Some(&db_path)));
+
+    // Analysts can ask "who imports this module" directly
+    let db = Connection::open(&db_path).unwrap();
+    assert_eq!(
+        query(
+            &db,
+            "SELECT source FROM edges WHERE target = 'shop.core.users' ORDER BY source"
+        ),
+        "shop.core.auth\nshop.core.checkout\nshop.core.sessions"
+    );
+}
+
+#[test]
+fn test_sqlite_requires_output() {
+    let output = run(&["--format", "sqlite"], None);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--format sqlite requires --output"));
+}
+
+#[test]
+fn test_output_writes_text_formats_to_file() {
+    let temp = tempfile::tempdir().unwrap();
+    let path = temp.path().join("graph.mmd");
+    let to_file = run(&["--format", "mermaid"], Some(&path));
+    assert_success(&to_file);
+    assert!(to_file.stdout.is_empty());
+
+    let to_stdout = run(&["--format", "mermaid"], None);
+    assert_success(&to_stdout);
+    assert_eq!(std::fs::read(&path).unwrap(), to_stdout.stdout);
+}
@@ -0,0 +1,71 @@
+//! Integration tests for the package API surface report
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use deptree_utils::{python, surface};
+
+mod common;
+use common::get_binary_path;
+
+fn module(name: &str) -> python::ModulePath {
+    python::ModulePath::from_dotted(name).expect("Invalid module")
+}
+
+#[test]
+fn test_api_surface_counts_consumer_packages() {
+    let mut graph = python::PythonGraph::new();
+    for (from, to) in [
+        ("app.api.views", "shop.core.models"),
+        ("app.api.forms", "shop.core.models"),
+        ("app.web", "shop.core.models"),
+        ("cli", "shop.core"),
+        ("shop.web.views", "shop.core.models"),
+        ("shop.core", "shop.core.models"),
+        ("shop.core.models", "shop.core.db"),
+    ] {
+        graph.add_dependency(module(from), module(to));
+    }
+    graph.ensure_node(module("shop.core.legacy"));
+
+    let surface = surface::api_surface(&graph, &module("shop.core")).unwrap();
+    assert_eq!(surface.public.len(), 2);
+    assert_eq!(surface.public[0].module, module("shop.core.models"));
+    assert_eq!(
+        surface.public[0].consumers,
+        vec![
+            ("app.api".to_string(), 2),
+            ("app.web".to_string(), 1),
+            ("shop.web".to_string(), 1),
+        ]
+    );
+    assert_eq!(surface.internal, vec![module("shop.core.db")]);
+    assert_eq!(surface.unused, vec![module("shop.core.legacy")]);
+
+    let error = surface::api_surface(&graph, &module("billing")).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Package 'billing' does not match any analyzed module"
+    );
+}
+
+#[test]
+fn test_cli_api_surface() {
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("split_project");
+    let output = Command::new(get_binary_path())
+        .arg("python")
+        .arg(fixture)
+        .args(["--api-surface", "shop.core"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    insta::assert_snapshot!(String::from_utf8_lossy(&output.stdout));
+}
@@ -0,0 +1,34 @@
+//! Tests for the test reach report (`--test-reach`)
+
+use deptree_utils::python::PythonGraph;
+use deptree_utils::testreach;
+
+mod common;
+use common::{module, python_graph};
+
+fn reach_graph() -> PythonGraph {
+    let mut graph = python_graph(&[
+        ("tests.test_api", "app.api.views"),
+        ("app.api.views", "app.db"),
+        ("tests.helpers", "app.db"),
+        ("app.cli", "app.api.views"),
+    ]);
+    graph.ensure_node(module("app.api"));
+    graph.ensure_node(module("app.legacy_test"));
+    graph
+}
+
+#[test]
+fn test_test_reach_follows_imports_and_packages() {
+    // Importing app.api.views runs the app.api package
+    let reach = testreach::test_reach(&reach_graph(), &[]);
+
+    insta::assert_snapshot!(testreach::render_report(&reach));
+}
+
+#[test]
+fn test_test_reach_patterns_replace_the_default_rule() {
+    let reach = testreach::test_reach(&reach_graph(), &["tests.test_*".to_string()]);
+
+    insta::assert_snapshot!(testreach::render_report(&reach));
+}
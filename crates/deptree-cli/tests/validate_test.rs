@@ -0,0 +1,77 @@
+//! Integration tests for graph validation
+
+use deptree_graph::GraphData;
+use deptree_utils::validate;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+mod common;
+use common::get_binary_path;
+
+fn fixtures_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("validate")
+}
+
+fn run_validate(files: &[&str]) -> Output {
+    Command::new(get_binary_path())
+        .arg("validate")
+        .args(files)
+        .current_dir(fixtures_dir())
+        .output()
+        .expect("Failed to execute command")
+}
+
+#[test]
+fn test_validate_accepts_valid_graph() {
+    let output = run_validate(&["valid.json"]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "valid.json: valid (4 nodes, 1 edge)\n"
+    );
+}
+
+#[test]
+fn test_validate_reports_problems() {
+    let output = run_validate(&["valid.json", "broken.json", "not_graph.json"]);
+    assert_eq!(output.status.code(), Some(1));
+    insta::assert_snapshot!(String::from_utf8_lossy(&output.stdout));
+}
+
+#[test]
+fn test_validate_rejects_missing_file() {
+    let output = run_validate(&["does-not-exist.json"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Failed to read graph"));
+}
+
+#[test]
+fn test_validate_reports_each_kind_of_problem() {
+    let data = GraphData::from_json(
+        r#"{
+            "nodes": [
+                {"id": "app", "type": "module", "is_orphan": true},
+                {"id": "lib", "type": "module", "is_orphan": false, "parent": "pkg"},
+                {"id": "app", "type": "modul", "is_orphan": false},
+                {"id": "grp", "type": "group", "is_orphan": true},
+                {"id": "a", "type": "namespace_group", "is_orphan": false, "parent": "b"},
+                {"id": "b", "type": "namespace_group", "is_orphan": false, "parent": "a"}
+            ],
+            "edges": [
+                {"source": "app", "target": "lib"},
+                {"source": "lib", "target": "gone"}
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let problems = validate::validate(&data);
+    insta::assert_snapshot!(validate::render_report(
+        Path::new("graph.json"),
+        &data,
+        &problems
+    ));
+}
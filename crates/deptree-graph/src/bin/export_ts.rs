@@ -3,6 +3,7 @@ use std::fs;
 use std::path::PathBuf;
 
 use deptree_graph::GraphData;
+use deptree_graph::filters::{FilterConfig, FilterPreset, FilterResult};
 use ts_rs::TS;
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -16,6 +17,9 @@ fn main() -> Result<(), Box<dyn Error>> {
     fs::create_dir_all(&out_dir)?;
 
     GraphData::export_all_to(&out_dir)
+        .and_then(|()| FilterConfig::export_all_to(&out_dir))
+        .and_then(|()| FilterResult::export_all_to(&out_dir))
+        .and_then(|()| FilterPreset::export_all_to(&out_dir))
         .map_err(|err| format!("failed to export TypeScript bindings: {err}"))?;
 
     println!("Generated TypeScript bindings in {}", out_dir.display());
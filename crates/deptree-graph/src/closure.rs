@@ -0,0 +1,123 @@
+//! Precomputed transitive closure for repeated reachability queries.
+//!
+//! Stores, for every strongly connected component, the set of nodes reachable from it
+//! in each relation as a bitset. Building costs one pass over the condensation in
+//! topological order (O(C·E/64) word operations for C components); afterwards an
+//! unbounded upstream/downstream query is the union of one bitset per root, O(V/64).
+//! Memory grows quadratically, so callers build it only within a byte budget.
+
+use crate::Relation;
+use petgraph::algo::tarjan_scc;
+
+use crate::csr::CompactGraph;
+
+/// Fixed-size set of node indices
+#[derive(Debug, Clone)]
+struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    fn new(len: usize) -> Self {
+        BitSet {
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    fn insert(&mut self, index: usize) {
+        self.words[index / 64] |= 1 << (index % 64);
+    }
+
+    fn union_with(&mut self, other: &BitSet) {
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word |= other;
+        }
+    }
+
+    fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            (0..64)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| i * 64 + bit)
+        })
+    }
+}
+
+/// Transitive closure of a graph in both directions, one bitset per component
+#[derive(Debug, Clone)]
+pub struct ReachabilityClosure {
+    node_count: usize,
+    component: Vec<usize>,
+    /// Nodes reachable along outgoing edges (dependencies), components included
+    outgoing: Vec<BitSet>,
+    /// Nodes reachable along incoming edges (dependents), components included
+    incoming: Vec<BitSet>,
+}
+
+impl ReachabilityClosure {
+    /// Bytes the closure of a graph with `components` components over `nodes` nodes takes
+    pub fn estimated_bytes(components: usize, nodes: usize) -> usize {
+        2 * components * nodes.div_ceil(64) * 8
+    }
+
+    /// Build the closure of `graph`, or `None` if it would take more than `max_bytes`
+    pub fn build_within(graph: &CompactGraph, max_bytes: usize) -> Option<Self> {
+        let node_count = graph.node_count();
+        // Reverse topological order: every component comes after the ones it points to
+        let components = tarjan_scc(graph.adjacency(Relation::Dependencies));
+        if Self::estimated_bytes(components.len(), node_count) > max_bytes {
+            return None;
+        }
+
+        let mut component = vec![0; node_count];
+        for (i, members) in components.iter().enumerate() {
+            for &node in members {
+                component[node as usize] = i;
+            }
+        }
+
+        // Settle components so that every neighbor in `relation` is done first
+        let closure_in = |relation: Relation, order: &[usize]| {
+            let mut sets: Vec<Option<BitSet>> = vec![None; components.len()];
+            for &i in order {
+                let mut set = BitSet::new(node_count);
+                for &node in &components[i] {
+                    set.insert(node as usize);
+                    for &neighbor in graph.neighbors(node, relation) {
+                        let j = component[neighbor as usize];
+                        if j != i {
+                            let reached = sets[j].as_ref().expect("component settled first");
+                            set.union_with(reached);
+                        }
+                    }
+                }
+                sets[i] = Some(set);
+            }
+            sets.into_iter().map(Option::unwrap).collect::<Vec<_>>()
+        };
+        let mut order: Vec<usize> = (0..components.len()).collect();
+        let outgoing = closure_in(Relation::Dependencies, &order);
+        order.reverse();
+        let incoming = closure_in(Relation::Dependents, &order);
+
+        Some(ReachabilityClosure {
+            node_count,
+            component,
+            outgoing,
+            incoming,
+        })
+    }
+
+    /// Every node reachable from any of `roots` in `relation`, roots included
+    pub fn reachable(&self, roots: &[u32], relation: Relation) -> Vec<u32> {
+        let sets = match relation {
+            Relation::Dependencies => &self.outgoing,
+            Relation::Dependents => &self.incoming,
+        };
+        let mut result = BitSet::new(self.node_count);
+        for root in roots {
+            result.union_with(&sets[self.component[*root as usize]]);
+        }
+        result.ones().map(|index| index as u32).collect()
+    }
+}
@@ -0,0 +1,178 @@
+//! Community detection over undirected weighted graphs.
+//!
+//! Implements the Louvain method (local moving followed by aggregation, repeated until
+//! modularity stops improving). Nodes are visited in index order and ties keep the
+//! current assignment, so results are deterministic for a given input.
+
+/// Minimum modularity gain considered an improvement (guards against float noise)
+const MIN_GAIN: f64 = 1e-12;
+
+/// Symmetric weighted adjacency lists; a self-loop stores the full `A_ii` entry
+struct WeightedGraph {
+    adjacency: Vec<Vec<(usize, f64)>>,
+}
+
+impl WeightedGraph {
+    fn from_edges(node_count: usize, edges: &[(usize, usize, f64)]) -> Self {
+        let mut adjacency = vec![Vec::new(); node_count];
+        for &(from, to, weight) in edges {
+            if from == to {
+                adjacency[from].push((from, 2.0 * weight));
+            } else {
+                adjacency[from].push((to, weight));
+                adjacency[to].push((from, weight));
+            }
+        }
+        WeightedGraph { adjacency }
+    }
+
+    fn len(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    fn degree(&self, node: usize) -> f64 {
+        self.adjacency[node].iter().map(|(_, w)| w).sum()
+    }
+
+    /// One pass of local moving; returns the community of every node and whether any node moved
+    fn local_moving(&self) -> (Vec<usize>, bool) {
+        let n = self.len();
+        let degrees: Vec<f64> = (0..n).map(|i| self.degree(i)).collect();
+        let total: f64 = degrees.iter().sum();
+        let mut community: Vec<usize> = (0..n).collect();
+        let mut community_degree = degrees.clone();
+        let mut moved_any = false;
+
+        if total == 0.0 {
+            return (community, false);
+        }
+
+        loop {
+            let mut moved = false;
+            for node in 0..n {
+                let current = community[node];
+                community_degree[current] -= degrees[node];
+
+                // Weight from `node` to each neighboring community
+                let mut links: Vec<(usize, f64)> = Vec::new();
+                for &(neighbor, weight) in &self.adjacency[node] {
+                    if neighbor == node {
+                        continue;
+                    }
+                    let target = community[neighbor];
+                    match links.iter_mut().find(|(c, _)| *c == target) {
+                        Some((_, w)) => *w += weight,
+                        None => links.push((target, weight)),
+                    }
+                }
+
+                let gain =
+                    |c: usize, weight: f64| weight - community_degree[c] * degrees[node] / total;
+                let stay_weight = links
+                    .iter()
+                    .find(|(c, _)| *c == current)
+                    .map(|(_, w)| *w)
+                    .unwrap_or(0.0);
+                let mut best = current;
+                let mut best_gain = gain(current, stay_weight);
+                for &(candidate, weight) in &links {
+                    let candidate_gain = gain(candidate, weight);
+                    if candidate_gain > best_gain + MIN_GAIN {
+                        best = candidate;
+                        best_gain = candidate_gain;
+                    }
+                }
+
+                community[node] = best;
+                community_degree[best] += degrees[node];
+                if best != current {
+                    moved = true;
+                    moved_any = true;
+                }
+            }
+            if !moved {
+                break;
+            }
+        }
+
+        (community, moved_any)
+    }
+
+    /// Collapse every community into a single node
+    fn aggregate(&self, community: &[usize]) -> (WeightedGraph, Vec<usize>) {
+        let mut renumber: Vec<Option<usize>> = vec![None; self.len()];
+        let mut next = 0;
+        let mapping: Vec<usize> = community
+            .iter()
+            .map(|&c| {
+                *renumber[c].get_or_insert_with(|| {
+                    next += 1;
+                    next - 1
+                })
+            })
+            .collect();
+
+        let mut adjacency: Vec<Vec<(usize, f64)>> = vec![Vec::new(); next];
+        for (node, neighbors) in self.adjacency.iter().enumerate() {
+            for &(neighbor, weight) in neighbors {
+                let (from, to) = (mapping[node], mapping[neighbor]);
+                match adjacency[from].iter_mut().find(|(n, _)| *n == to) {
+                    Some((_, w)) => *w += weight,
+                    None => adjacency[from].push((to, weight)),
+                }
+            }
+        }
+
+        (WeightedGraph { adjacency }, mapping)
+    }
+}
+
+/// Assign every node (`0..node_count`) to a community by maximizing modularity.
+///
+/// `edges` are undirected `(from, to, weight)` triples. Community ids are dense and
+/// numbered in order of their lowest node index.
+pub fn louvain(node_count: usize, edges: &[(usize, usize, f64)]) -> Vec<usize> {
+    let mut graph = WeightedGraph::from_edges(node_count, edges);
+    let mut assignment: Vec<usize> = (0..node_count).collect();
+
+    loop {
+        let (community, moved) = graph.local_moving();
+        if !moved {
+            break;
+        }
+        let (aggregated, mapping) = graph.aggregate(&community);
+        for slot in assignment.iter_mut() {
+            *slot = mapping[*slot];
+        }
+        graph = aggregated;
+    }
+
+    assignment
+}
+
+/// Modularity of a community assignment (between -0.5 and 1; 0 for an edgeless graph)
+pub fn modularity(node_count: usize, edges: &[(usize, usize, f64)], assignment: &[usize]) -> f64 {
+    let graph = WeightedGraph::from_edges(node_count, edges);
+    let total: f64 = (0..node_count).map(|i| graph.degree(i)).sum();
+    if total == 0.0 {
+        return 0.0;
+    }
+
+    let community_count = assignment.iter().max().map(|m| m + 1).unwrap_or(0);
+    let mut internal = vec![0.0; community_count];
+    let mut degree = vec![0.0; community_count];
+    for (node, neighbors) in graph.adjacency.iter().enumerate() {
+        degree[assignment[node]] += graph.degree(node);
+        for &(neighbor, weight) in neighbors {
+            if assignment[node] == assignment[neighbor] {
+                internal[assignment[node]] += weight;
+            }
+        }
+    }
+
+    internal
+        .iter()
+        .zip(&degree)
+        .map(|(inner, deg)| inner / total - (deg / total).powi(2))
+        .sum()
+}
@@ -0,0 +1,215 @@
+//! Compact adjacency for string-keyed graphs.
+//!
+//! Nodes are interned once into dense `u32` indices, and the edges of each relation are
+//! stored in compressed sparse row (CSR) form: one offsets array plus one flat array of
+//! neighbor indices. Traversals then walk contiguous slices instead of hashing strings,
+//! which is both smaller and faster than a petgraph `Graph<String, ()>` in WASM.
+
+use std::collections::HashMap;
+
+use crate::Relation;
+use petgraph::visit::{GraphBase, IntoNeighbors, IntoNodeIdentifiers, NodeIndexable};
+
+/// Neighbors of every node in one relation: those of node `i` are
+/// `targets[offsets[i]..offsets[i + 1]]`, sorted and without duplicates
+#[derive(Debug, Clone, Default)]
+pub struct CsrAdjacency {
+    offsets: Vec<u32>,
+    targets: Vec<u32>,
+}
+
+impl CsrAdjacency {
+    /// Build from `(from, to)` pairs over `node_count` nodes
+    pub fn from_edges(node_count: usize, edges: &[(u32, u32)]) -> Self {
+        let mut edges = edges.to_vec();
+        edges.sort_unstable();
+        edges.dedup();
+
+        let mut offsets = vec![0u32; node_count + 1];
+        for &(from, _) in &edges {
+            offsets[from as usize + 1] += 1;
+        }
+        for i in 0..node_count {
+            offsets[i + 1] += offsets[i];
+        }
+        let targets = edges.into_iter().map(|(_, to)| to).collect();
+        CsrAdjacency { offsets, targets }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.offsets.len().saturating_sub(1)
+    }
+
+    pub fn neighbors(&self, node: u32) -> &[u32] {
+        let node = node as usize;
+        &self.targets[self.offsets[node] as usize..self.offsets[node + 1] as usize]
+    }
+}
+
+// Lets petgraph's algorithms (e.g. `tarjan_scc`) run directly on the compact form
+
+impl GraphBase for CsrAdjacency {
+    type EdgeId = (u32, u32);
+    type NodeId = u32;
+}
+
+impl<'a> IntoNeighbors for &'a CsrAdjacency {
+    type Neighbors = std::iter::Copied<std::slice::Iter<'a, u32>>;
+
+    fn neighbors(self, node: u32) -> Self::Neighbors {
+        CsrAdjacency::neighbors(self, node).iter().copied()
+    }
+}
+
+impl IntoNodeIdentifiers for &CsrAdjacency {
+    type NodeIdentifiers = std::ops::Range<u32>;
+
+    fn node_identifiers(self) -> Self::NodeIdentifiers {
+        0..self.node_count() as u32
+    }
+}
+
+impl NodeIndexable for CsrAdjacency {
+    fn node_bound(&self) -> usize {
+        self.node_count()
+    }
+
+    fn to_index(&self, node: u32) -> usize {
+        node as usize
+    }
+
+    fn from_index(&self, index: usize) -> u32 {
+        index as u32
+    }
+}
+
+/// String-keyed directed graph stored as CSR adjacency in both directions
+#[derive(Debug, Clone, Default)]
+pub struct CompactGraph {
+    ids: Vec<String>,
+    index: HashMap<String, u32>,
+    outgoing: CsrAdjacency,
+    incoming: CsrAdjacency,
+}
+
+impl CompactGraph {
+    /// Build from node ids (interned in order, duplicates ignored) and `(source, target)`
+    /// edges; endpoints missing from `ids` are added after them
+    pub fn new<'a>(
+        ids: impl IntoIterator<Item = &'a str>,
+        edges: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Self {
+        let mut graph = CompactGraph::default();
+        for id in ids {
+            graph.intern(id);
+        }
+        let pairs: Vec<(u32, u32)> = edges
+            .into_iter()
+            .map(|(source, target)| (graph.intern(source), graph.intern(target)))
+            .collect();
+        let reversed: Vec<(u32, u32)> = pairs.iter().map(|&(from, to)| (to, from)).collect();
+        graph.outgoing = CsrAdjacency::from_edges(graph.ids.len(), &pairs);
+        graph.incoming = CsrAdjacency::from_edges(graph.ids.len(), &reversed);
+        graph
+    }
+
+    fn intern(&mut self, id: &str) -> u32 {
+        if let Some(&index) = self.index.get(id) {
+            return index;
+        }
+        let index = self.ids.len() as u32;
+        self.ids.push(id.to_string());
+        self.index.insert(id.to_string(), index);
+        index
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn index_of(&self, id: &str) -> Option<u32> {
+        self.index.get(id).copied()
+    }
+
+    pub fn id(&self, index: u32) -> &str {
+        &self.ids[index as usize]
+    }
+
+    /// Adjacency of the dependencies (outgoing edges) or dependents (incoming edges)
+    pub fn adjacency(&self, relation: Relation) -> &CsrAdjacency {
+        match relation {
+            Relation::Dependencies => &self.outgoing,
+            Relation::Dependents => &self.incoming,
+        }
+    }
+
+    pub fn neighbors(&self, index: u32, relation: Relation) -> &[u32] {
+        self.adjacency(relation).neighbors(index)
+    }
+
+    /// Breadth-first search from all of `starts` at once: `(node, distance)` for every
+    /// reachable node in visiting order, starts first at 0. Each node is reached first
+    /// from its nearest start, so distances are minimal over all of them.
+    pub fn bfs(&self, starts: &[u32], relation: Relation) -> Vec<(u32, u32)> {
+        let mut seen = vec![false; self.node_count()];
+        let mut reached: Vec<(u32, u32)> = Vec::new();
+        for &start in starts {
+            if !std::mem::replace(&mut seen[start as usize], true) {
+                reached.push((start, 0));
+            }
+        }
+        // `reached` doubles as the queue
+        let mut next = 0;
+        while let Some(&(node, distance)) = reached.get(next) {
+            next += 1;
+            for &neighbor in self.neighbors(node, relation) {
+                if !std::mem::replace(&mut seen[neighbor as usize], true) {
+                    reached.push((neighbor, distance + 1));
+                }
+            }
+        }
+        reached
+    }
+
+    /// [`Self::bfs`] with `cost(source, target)` as the length of each edge, taken in its
+    /// own orientation whichever way it is walked: `(node, distance)` for every reachable
+    /// node in order of distance, starts first at 0
+    pub fn dijkstra(
+        &self,
+        starts: &[u32],
+        relation: Relation,
+        cost: impl Fn(u32, u32) -> usize,
+    ) -> Vec<(u32, usize)> {
+        use std::cmp::Reverse;
+        use std::collections::BinaryHeap;
+
+        let mut best: Vec<Option<usize>> = vec![None; self.node_count()];
+        let mut settled = vec![false; self.node_count()];
+        let mut queue = BinaryHeap::new();
+        for &start in starts {
+            if best[start as usize].is_none() {
+                best[start as usize] = Some(0);
+                queue.push(Reverse((0_usize, start)));
+            }
+        }
+        let mut reached = Vec::new();
+        while let Some(Reverse((distance, node))) = queue.pop() {
+            if std::mem::replace(&mut settled[node as usize], true) {
+                continue;
+            }
+            reached.push((node, distance));
+            for &neighbor in self.neighbors(node, relation) {
+                let length = match relation {
+                    Relation::Dependencies => cost(node, neighbor),
+                    Relation::Dependents => cost(neighbor, node),
+                };
+                let candidate = distance.saturating_add(length);
+                if best[neighbor as usize].is_none_or(|known| candidate < known) {
+                    best[neighbor as usize] = Some(candidate);
+                    queue.push(Reverse((candidate, neighbor)));
+                }
+            }
+        }
+        reached
+    }
+}
@@ -1,7 +1,13 @@
-use crate::{GraphConfig, GraphData, GraphEdge, GraphNode};
+use crate::style::{EdgeInfo, NodeInfo, RenderStyle, Renderer, Style};
+use crate::{
+    GRAPH_DATA_VERSION, GraphConfig, GraphData, GraphDataError, GraphEdge, GraphNode, Relation,
+};
+use crate::{community, escape};
 use petgraph::Direction;
 use petgraph::graph::{DiGraph, NodeIndex};
-use std::collections::{HashMap, HashSet, VecDeque};
+use petgraph::visit::EdgeRef;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 
 /// Identifier trait for nodes stored in the dependency graph.
 /// Implementations should provide a dotted string representation and path segments
@@ -9,10 +15,401 @@ use std::collections::{HashMap, HashSet, VecDeque};
 pub trait GraphId: Eq + std::hash::Hash + Clone {
     fn to_dotted(&self) -> String;
     fn segments(&self) -> Vec<String>;
+
+    /// Whether this node, when it also has namespace children, stands for its whole group
+    /// as a Python package `__init__` does. DOT and Mermaid output then render the cluster in
+    /// its place, and Cytoscape output makes the node itself the compound parent.
+    fn represents_group(&self) -> bool {
+        true
+    }
+
+    /// Join namespace segments into a display name consistent with `to_dotted`.
+    /// Languages that don't use `.` as a separator (e.g. Go import paths) override this.
+    fn join_segments(segments: &[String]) -> String {
+        segments.join(".")
+    }
+
+    /// Leading segments that say what kind of node this is rather than naming it (the
+    /// language of a cross-language node); anonymized names keep them
+    fn kind_segments() -> usize {
+        0
+    }
+}
+
+/// When a dependency is exercised.
+///
+/// Variant order matters: when parallel edges are collapsed for rendering, the smallest
+/// kind wins, so a single import-time edge makes the rendered edge import-time.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeKind {
+    /// Executed while the importing module itself is being imported
+    #[default]
+    ImportTime,
+    /// Executed later, e.g. an import inside a function body
+    Deferred,
+}
+
+impl EdgeKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EdgeKind::ImportTime => "import_time",
+            EdgeKind::Deferred => "deferred",
+        }
+    }
+}
+
+/// Lifecycle state of a module, declared by its owners
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Lifecycle {
+    /// Not ready to be depended on yet
+    Experimental,
+    Stable,
+    /// Slated for removal
+    Sunset,
+}
+
+impl Lifecycle {
+    pub const ALL: [Lifecycle; 3] = [
+        Lifecycle::Experimental,
+        Lifecycle::Stable,
+        Lifecycle::Sunset,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Lifecycle::Experimental => "experimental",
+            Lifecycle::Stable => "stable",
+            Lifecycle::Sunset => "sunset",
+        }
+    }
+
+    /// Parse `experimental`, `stable`, or `sunset`
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|lifecycle| lifecycle.as_str() == value)
+    }
+}
+
+/// Graph health metrics computed by [`DependencyGraph::metrics`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphMetrics {
+    pub nodes: usize,
+    pub edges: usize,
+    /// Import cycles (strongly connected components, plus self-imports)
+    pub cycles: usize,
+    /// Longest dependency chain in edges, with each cycle counted as one step
+    pub max_depth: usize,
+    /// Distinct modules outside each package that import into it, sorted by package.
+    /// Every proper prefix of a node name is a package.
+    pub package_fan_in: Vec<(String, usize)>,
+}
+
+impl GraphMetrics {
+    /// Render as OpenMetrics gauges (`deptree_nodes`, ..., `deptree_package_fan_in`)
+    pub fn to_openmetrics(&self) -> String {
+        let mut output = String::new();
+        let mut gauge = |name: &str, help: &str, samples: &[(Option<&str>, usize)]| {
+            output.push_str(&format!("# HELP deptree_{name} {help}\n"));
+            output.push_str(&format!("# TYPE deptree_{name} gauge\n"));
+            for (package, value) in samples {
+                match package {
+                    Some(package) => output.push_str(&format!(
+                        "deptree_{name}{{package=\"{}\"}} {value}\n",
+                        escape::openmetrics_label(package)
+                    )),
+                    None => output.push_str(&format!("deptree_{name} {value}\n")),
+                }
+            }
+        };
+        gauge(
+            "nodes",
+            "Number of nodes in the graph.",
+            &[(None, self.nodes)],
+        );
+        gauge(
+            "edges",
+            "Number of dependency edges.",
+            &[(None, self.edges)],
+        );
+        gauge("cycles", "Number of import cycles.", &[(None, self.cycles)]);
+        gauge(
+            "max_depth",
+            "Length of the longest dependency chain, counting each cycle as one step.",
+            &[(None, self.max_depth)],
+        );
+        let fan_in: Vec<(Option<&str>, usize)> = self
+            .package_fan_in
+            .iter()
+            .map(|(package, count)| (Some(package.as_str()), *count))
+            .collect();
+        gauge(
+            "package_fan_in",
+            "Number of modules outside a package that import it.",
+            &fan_in,
+        );
+        output.push_str("# EOF\n");
+        output
+    }
+}
+
+/// Strongly connected components of a graph with the depth of each: the longest chain of
+/// components below it, so an import cycle counts as one step
+struct ComponentDepths {
+    components: Vec<Vec<NodeIndex>>,
+    component_of: HashMap<NodeIndex, usize>,
+    depth: Vec<usize>,
+}
+
+impl ComponentDepths {
+    fn new<N, E>(graph: &DiGraph<N, E>) -> Self {
+        let components = petgraph::algo::tarjan_scc(graph);
+        // tarjan_scc yields components in reverse topological order, so every dependency's
+        // component has been settled before its dependents
+        let mut component_of = HashMap::new();
+        for (i, component) in components.iter().enumerate() {
+            for &idx in component {
+                component_of.insert(idx, i);
+            }
+        }
+        let mut depth = vec![0usize; components.len()];
+        for (i, component) in components.iter().enumerate() {
+            for &idx in component {
+                for dependency in graph.neighbors(idx) {
+                    let j = component_of[&dependency];
+                    if j != i {
+                        depth[i] = depth[i].max(depth[j] + 1);
+                    }
+                }
+            }
+        }
+        ComponentDepths {
+            components,
+            component_of,
+            depth,
+        }
+    }
+}
+
+/// How a node was reached by [`DependencyGraph::find_downstream_with_parents`] or
+/// [`DependencyGraph::find_upstream_with_parents`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reached<T> {
+    /// Distance from the nearest root
+    pub distance: usize,
+    /// Previous node on a shortest chain from that root; `None` for the roots
+    pub parent: Option<T>,
+}
+
+/// Shortest chain from the nearest root to `node`, root first, following the parents
+/// recorded in `reached`. `None` if `node` wasn't reached.
+pub fn chain_to<T: GraphId>(reached: &HashMap<T, Reached<T>>, node: &T) -> Option<Vec<T>> {
+    let mut chain = vec![node.clone()];
+    let mut current = reached.get(node)?;
+    while let Some(parent) = &current.parent {
+        chain.push(parent.clone());
+        current = &reached[parent];
+    }
+    chain.reverse();
+    Some(chain)
+}
+
+/// Measured cost of importing a module, in microseconds (as reported by `python -X importtime`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportCost {
+    /// Time spent executing the module body itself
+    pub self_us: u64,
+    /// Time including every module imported while loading it
+    pub cumulative_us: u64,
+}
+
+/// Where an import is written: a file (relative to the analyzed project) and the 1-based
+/// line and column of the statement
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ImportSite {
+    pub file: String,
+    pub line: usize,
+    /// 0 when unknown (graphs serialized before columns were recorded)
+    #[serde(default)]
+    pub column: usize,
+    /// Name the statement binds the import to when it renames it (`import pkg.db as
+    /// database`, `from pkg import db as database`), i.e. the name the file's code uses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+}
+
+impl std::fmt::Display for ImportSite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)?;
+        if let Some(alias) = &self.alias {
+            write!(f, " as {alias}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Edge weight: how often one module imports another with a given kind. Repeated imports
+/// of the same kind are counted on one edge rather than stored as parallel edges.
+#[derive(Debug, Clone, Copy)]
+struct Imports {
+    kind: EdgeKind,
+    count: usize,
+}
+
+/// Label of an edge imported `count` times, when edge counts are shown and it is more than once
+fn count_label(count: usize) -> Option<String> {
+    (count > 1).then(|| format!("\u{d7}{count}"))
+}
+
+/// Labels drawn on Mermaid edges, see [`RenderOptions::mermaid_edge_labels`].
+/// The default draws none beyond the `--show-edge-counts` count, so existing output stays
+/// the same.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MermaidEdgeLabels {
+    /// The import kind ("import_time" or "deferred")
+    pub kind: bool,
+    /// How many times the import occurs, when more than once
+    pub count: bool,
+    /// Annotation of the imported module weighing the import (e.g. `loc`), shown as
+    /// `key=value` when the module has it
+    pub weight: Option<String>,
+}
+
+impl MermaidEdgeLabels {
+    pub fn is_empty(&self) -> bool {
+        !self.kind && !self.count && self.weight.is_none()
+    }
+}
+
+/// How the DOT, Mermaid, and Cytoscape renderers draw the modules they are asked for. The
+/// default draws the plain output.
+#[derive(Debug, Clone, Default)]
+pub struct RenderOptions {
+    /// When packages are drawn as clusters
+    pub namespace_grouping: NamespaceGrouping,
+    /// Label DOT and Mermaid edges imported more than once with their
+    /// [`DependencyGraph::edge_multiplicity`] (e.g. "×4")
+    pub show_edge_counts: bool,
+    /// Labels Mermaid edges carry
+    pub mermaid_edge_labels: MermaidEdgeLabels,
+    /// Hooks adding style attributes to nodes and edges
+    pub style: RenderStyle,
+    /// Template of the source links on DOT nodes (`URL`) and Cytoscape nodes (`href`),
+    /// e.g. `https://github.com/org/repo/blob/main/{path}#L{line}`. `{path}` is replaced by
+    /// the module's [`DependencyGraph::source_file`], `{line}` by 1, and `{module}` by its
+    /// dotted name.
+    pub link_template: Option<String>,
+}
+
+/// Mermaid edge between two rendered endpoints. `labels` are drawn as edge text
+/// (`-- "deferred, ×3" -->`); without them, `count` keeps the older `-->|"×3"|` form.
+fn render_mermaid_edge(
+    from: &str,
+    to: &str,
+    kind: EdgeKind,
+    count: Option<String>,
+    labels: &[String],
+) -> String {
+    if !labels.is_empty() {
+        let text = escape::mermaid_label(&labels.join(", "));
+        return match kind {
+            EdgeKind::ImportTime => format!("    {from} -- \"{text}\" --> {to}\n"),
+            EdgeKind::Deferred => format!("    {from} -. \"{text}\" .-> {to}\n"),
+        };
+    }
+    let arrow = match kind {
+        EdgeKind::ImportTime => "-->",
+        EdgeKind::Deferred => "-.->",
+    };
+    let label = count
+        .map(|label| format!("|\"{}\"|", escape::mermaid_label(&label)))
+        .unwrap_or_default();
+    format!("    {from} {arrow}{label} {to}\n")
+}
+
+/// DOT attributes (`key="value"`) of a [`RenderStyle`] result
+fn dot_style_attrs(style: &Style) -> impl Iterator<Item = String> + '_ {
+    style
+        .iter()
+        .map(|(key, value)| format!("{key}=\"{}\"", escape::dot_string(value)))
+}
+
+fn render_dot_edge<T: GraphId>(
+    from: &T,
+    to: &T,
+    kind: EdgeKind,
+    label: Option<String>,
+    style: &Style,
+) -> String {
+    let mut attrs = Vec::new();
+    if kind == EdgeKind::Deferred {
+        attrs.push("style=dashed".to_string());
+    }
+    if let Some(label) = label {
+        attrs.push(format!("label=\"{}\"", escape::dot_string(&label)));
+    }
+    attrs.extend(dot_style_attrs(style));
+    let attrs = if attrs.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", attrs.join(", "))
+    };
+    format!(
+        "    \"{}\" -> \"{}\"{attrs};\n",
+        escape::dot_string(&from.to_dotted()),
+        escape::dot_string(&to.to_dotted())
+    )
+}
+
+/// Display name of the community at `index` in a [`DependencyGraph::detect_communities`] result
+fn community_label(index: usize) -> String {
+    format!("community {}", index + 1)
+}
+
+/// Detected communities as named clusters
+fn labeled_communities<T: GraphId>(communities: &[Vec<T>]) -> Vec<(String, Vec<T>)> {
+    communities
+        .iter()
+        .enumerate()
+        .map(|(index, members)| (community_label(index), members.clone()))
+        .collect()
+}
+
+/// Index of the cluster each node belongs to
+fn cluster_membership<T: GraphId>(clusters: &[(String, Vec<T>)]) -> HashMap<T, usize> {
+    clusters
+        .iter()
+        .enumerate()
+        .flat_map(|(index, (_, members))| members.iter().map(move |m| (m.clone(), index)))
+        .collect()
 }
 
-fn sanitize_mermaid_id(name: &str) -> String {
-    name.replace('.', "_")
+/// Which clusters DOT output draws around modules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DotClustering {
+    /// One cluster per package with several visible children
+    pub namespaces: bool,
+    /// One top-level cluster for the modules under the source root and one for the
+    /// scripts outside it
+    pub roots: bool,
+}
+
+impl DotClustering {
+    /// Default of [`DependencyGraph::to_dot`] and [`DependencyGraph::to_dot_filtered`]
+    pub const PACKAGES: Self = Self {
+        namespaces: true,
+        roots: false,
+    };
+
+    /// Default of [`DependencyGraph::to_dot_highlighted`]
+    pub const ALL: Self = Self {
+        namespaces: true,
+        roots: true,
+    };
 }
 
 struct DotNodeSpec {
@@ -27,7 +424,10 @@ impl DotNodeSpec {
         } else {
             format!(" {}", self.attrs)
         };
-        format!("{indent}    \"{}\"{attrs};\n", self.name)
+        format!(
+            "{indent}    \"{}\"{attrs};\n",
+            escape::dot_string(&self.name)
+        )
     }
 }
 
@@ -36,6 +436,7 @@ enum MermaidShape {
     Module,
     Script,
     Namespace,
+    Extension,
 }
 
 #[derive(Clone)]
@@ -43,39 +444,87 @@ struct MermaidNodeSpec {
     id: String,
     label: String,
     shape: MermaidShape,
+    fill: Option<String>,
+    /// Attributes from the [`RenderStyle`] hooks
+    style: Style,
 }
 
 struct MermaidRenderArgs<'a, T> {
     highlight_set: Option<&'a HashSet<T>>,
     specs: &'a HashMap<String, MermaidNodeSpec>,
+    /// Node ids are taken already; subgraphs get theirs as they are rendered
+    ids: escape::IdentifierAssigner,
+    /// Ids of the rendered subgraphs by cluster index
+    subgraph_ids: HashMap<usize, String>,
+}
+
+impl<T: GraphId> MermaidRenderArgs<'_, T> {
+    fn render_definition(&self, module: &T, indent: &str, output: &mut String) {
+        if let Some(spec) = self.specs.get(&module.to_dotted()) {
+            let highlighted = self.highlight_set.is_some_and(|set| set.contains(module));
+            output.push_str(&spec.render_definition(indent, highlighted));
+        }
+    }
 }
 
 impl MermaidNodeSpec {
     fn render_definition(&self, indent: &str, highlighted: bool) -> String {
+        let label = escape::mermaid_label(&self.label);
         let base = match self.shape {
-            MermaidShape::Script => format!("{indent}    {}[\"{}\"]\n", self.id, self.label),
+            MermaidShape::Script => format!("{indent}    {}[\"{}\"]\n", self.id, label),
             MermaidShape::Namespace => {
-                format!("{indent}    {}{{{{\"{}\"}}}} \n", self.id, self.label)
+                format!("{indent}    {}{{{{\"{}\"}}}} \n", self.id, label)
+            }
+            MermaidShape::Extension => {
+                format!("{indent}    {}[[\"{}\"]]\n", self.id, label)
             }
-            MermaidShape::Module => format!("{indent}    {}(\"{}\")\n", self.id, self.label),
+            MermaidShape::Module => format!("{indent}    {}(\"{}\")\n", self.id, label),
         };
 
+        let mut style: Vec<String> = self
+            .style
+            .iter()
+            .map(|(key, value)| format!("{key}:{value}"))
+            .collect();
         if highlighted {
-            format!("{base}{indent}    class {} highlighted\n", self.id)
-        } else {
+            let base = format!("{base}{indent}    class {} highlighted\n", self.id);
+            if style.is_empty() {
+                return base;
+            }
+            return format!("{base}{indent}    style {} {}\n", self.id, style.join(","));
+        }
+        if let Some(fill) = &self.fill
+            && !self.style.contains_key("fill")
+        {
+            style.insert(0, format!("fill:{fill}"));
+        }
+        if style.is_empty() {
             base
+        } else {
+            format!("{base}{indent}    style {} {}\n", self.id, style.join(","))
         }
     }
 
     fn render_inline(&self) -> String {
+        let label = escape::mermaid_label(&self.label);
         match self.shape {
-            MermaidShape::Script => format!("{}[\"{}\"]", self.id, self.label),
-            MermaidShape::Namespace => format!("{}{{{{\"{}\"}}}}", self.id, self.label),
-            MermaidShape::Module => format!("{}(\"{}\")", self.id, self.label),
+            MermaidShape::Script => format!("{}[\"{}\"]", self.id, label),
+            MermaidShape::Namespace => format!("{}{{{{\"{}\"}}}}", self.id, label),
+            MermaidShape::Extension => format!("{}[[\"{}\"]]", self.id, label),
+            MermaidShape::Module => format!("{}(\"{}\")", self.id, label),
         }
     }
 }
 
+/// Which edges decide whether a node is an orphan (see [`DependencyGraph::orphans_in`])
+#[derive(Debug, Clone, Copy)]
+pub enum OrphanScope<'a, T> {
+    /// Every edge of the graph
+    Graph,
+    /// Only edges between the given nodes, e.g. those of a filtered view
+    Subgraph(&'a HashSet<T>),
+}
+
 /// Rendering mode for Cytoscape data generation
 enum CytoscapeMode<'a, T> {
     Full,
@@ -96,6 +545,8 @@ struct NamespaceTree<T> {
     id: Option<T>,
     children: Vec<NamespaceTree<T>>,
     grouped: bool,
+    /// The module here is always clustered with its siblings (per-directory script clusters)
+    pinned: bool,
 }
 
 impl<T: GraphId> NamespaceTree<T> {
@@ -105,16 +556,18 @@ impl<T: GraphId> NamespaceTree<T> {
             id: None,
             children: Vec::new(),
             grouped: false,
+            pinned: false,
         }
     }
 
-    fn insert(&mut self, module: &T) {
-        self.insert_parts(&module.segments(), module);
+    fn insert(&mut self, module: &T, pinned: bool) {
+        self.insert_parts(&module.segments(), module, pinned);
     }
 
-    fn insert_parts(&mut self, parts: &[String], module: &T) {
+    fn insert_parts(&mut self, parts: &[String], module: &T, pinned: bool) {
         if parts.is_empty() {
             self.id = Some(module.clone());
+            self.pinned = pinned;
             return;
         }
 
@@ -128,45 +581,36 @@ impl<T: GraphId> NamespaceTree<T> {
             .find(|c| c.path.last() == Some(child_name));
 
         if let Some(existing) = child {
-            existing.insert_parts(&parts[1..], module);
+            existing.insert_parts(&parts[1..], module, pinned);
         } else {
             let mut new_child = NamespaceTree::new(child_path);
-            new_child.insert_parts(&parts[1..], module);
+            new_child.insert_parts(&parts[1..], module, pinned);
             self.children.push(new_child);
         }
     }
 
-    fn finalize(&mut self) {
+    fn finalize(&mut self, grouping: NamespaceGrouping) {
         for child in &mut self.children {
-            child.finalize();
+            child.finalize(grouping);
         }
         self.children.sort_by(|a, b| a.path.cmp(&b.path));
-        self.grouped = !self.path.is_empty() && self.children.len() >= 2;
+        let within_depth = grouping
+            .max_depth
+            .is_none_or(|max_depth| self.path.len() <= max_depth);
+        self.grouped = !self.path.is_empty()
+            && ((within_depth && self.children.len() >= grouping.min_children.max(1))
+                || self
+                    .children
+                    .iter()
+                    .any(|c| c.pinned && c.children.is_empty()));
     }
 
-    fn find(&self, path: &[String]) -> Option<&NamespaceTree<T>> {
-        if path.is_empty() {
-            return Some(self);
+    /// Drop every namespace cluster, leaving all modules ungrouped
+    fn ungroup(&mut self) {
+        self.grouped = false;
+        for child in &mut self.children {
+            child.ungroup();
         }
-
-        self.children
-            .iter()
-            .find(|c| c.path.last() == path.first())
-            .and_then(|child| child.find(&path[1..]))
-    }
-
-    fn is_group_only(&self, path: &[String]) -> bool {
-        self.find(path)
-            .map(|node| node.grouped && node.id.is_some())
-            .unwrap_or(false)
-    }
-
-    fn direct_concrete_children(&self) -> Vec<T> {
-        self.children.iter().filter_map(|c| c.id.clone()).collect()
-    }
-
-    fn child_groups(&self) -> impl Iterator<Item = &NamespaceTree<T>> {
-        self.children.iter()
     }
 
     fn collect_leaf_descendants(&self, acc: &mut Vec<T>) {
@@ -181,20 +625,23 @@ impl<T: GraphId> NamespaceTree<T> {
             child.collect_leaf_descendants(acc);
         }
     }
+}
 
-    fn collect_ungrouped_modules(&self, acc: &mut Vec<T>) {
-        if self.grouped {
-            for child in &self.children {
-                child.collect_ungrouped_modules(acc);
-            }
-            return;
-        }
+/// When a package is drawn as a cluster (DOT cluster, Mermaid subgraph, Cytoscape
+/// compound node) around its modules
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamespaceGrouping {
+    /// Visible children a package needs to be grouped
+    pub min_children: usize,
+    /// Deepest package, in name segments, that is grouped (`None`: no limit)
+    pub max_depth: Option<usize>,
+}
 
-        for child in &self.children {
-            if let Some(id) = &child.id {
-                acc.push(id.clone());
-            }
-            child.collect_ungrouped_modules(acc);
+impl Default for NamespaceGrouping {
+    fn default() -> Self {
+        Self {
+            min_children: 2,
+            max_depth: None,
         }
     }
 }
@@ -205,11 +652,187 @@ struct NamespaceForest<T> {
     scripts: NamespaceTree<T>,
 }
 
+/// A module of the [`Hierarchy`] drawn as its package cluster
+#[derive(Debug)]
+struct Folded<T> {
+    group: usize,
+    /// Leaf modules of the cluster, which DOT draws the module's edges to
+    leaves: Vec<T>,
+}
+
+/// A package cluster of the [`Hierarchy`]
+#[derive(Debug)]
+struct Group<T> {
+    path: Vec<String>,
+    parent: Option<usize>,
+    /// The module named like the package, if visible
+    module: Option<T>,
+    /// Modules drawn directly inside, sorted
+    members: Vec<T>,
+    children: Vec<usize>,
+    is_script: bool,
+}
+
+/// The package clusters of the visible modules. DOT clusters, Mermaid subgraphs and
+/// Cytoscape compound nodes are all drawn from it, so every format and every mode
+/// (full, filtered, highlighted) groups the same modules the same way.
+#[derive(Debug)]
+struct Hierarchy<T> {
+    /// Clusters in pre-order, modules before scripts
+    groups: Vec<Group<T>>,
+    /// Cluster each drawn module sits in, by dotted name
+    parents: HashMap<String, usize>,
+    /// Modules shown as their cluster instead of a node (see [`GraphId::represents_group`]),
+    /// by dotted name
+    folded: HashMap<String, Folded<T>>,
+    /// Modules outside every cluster, sorted; internal modules first, then scripts
+    ungrouped: [Vec<T>; 2],
+}
+
+impl<T: GraphId> Hierarchy<T> {
+    fn new(forest: &NamespaceForest<T>) -> Self {
+        let mut hierarchy = Hierarchy {
+            groups: Vec::new(),
+            parents: HashMap::new(),
+            folded: HashMap::new(),
+            ungrouped: [Vec::new(), Vec::new()],
+        };
+        for (tree, is_script) in [(&forest.internal, false), (&forest.scripts, true)] {
+            let mut ungrouped = Vec::new();
+            hierarchy.add_children(tree, None, is_script, &mut ungrouped);
+            ungrouped.sort_by_key(GraphId::to_dotted);
+            hierarchy.ungrouped[usize::from(is_script)] = ungrouped;
+        }
+        for group in &mut hierarchy.groups {
+            group.members.sort_by_key(GraphId::to_dotted);
+        }
+        hierarchy
+    }
+
+    fn add_children(
+        &mut self,
+        node: &NamespaceTree<T>,
+        parent: Option<usize>,
+        is_script: bool,
+        ungrouped: &mut Vec<T>,
+    ) {
+        for child in &node.children {
+            if !child.grouped {
+                if let Some(module) = &child.id {
+                    self.place(module, parent, ungrouped);
+                }
+                self.add_children(child, parent, is_script, ungrouped);
+                continue;
+            }
+
+            let index = self.groups.len();
+            self.groups.push(Group {
+                path: child.path.clone(),
+                parent,
+                module: child.id.clone(),
+                members: Vec::new(),
+                children: Vec::new(),
+                is_script,
+            });
+            if let Some(parent) = parent {
+                self.groups[parent].children.push(index);
+            }
+            if let Some(module) = &child.id {
+                if module.represents_group() {
+                    let mut leaves = Vec::new();
+                    child.collect_leaf_descendants(&mut leaves);
+                    self.folded.insert(
+                        module.to_dotted(),
+                        Folded {
+                            group: index,
+                            leaves,
+                        },
+                    );
+                } else {
+                    self.place(module, Some(index), ungrouped);
+                }
+            }
+            self.add_children(child, Some(index), is_script, ungrouped);
+        }
+    }
+
+    fn place(&mut self, module: &T, group: Option<usize>, ungrouped: &mut Vec<T>) {
+        match group {
+            Some(index) => {
+                self.parents.insert(module.to_dotted(), index);
+                self.groups[index].members.push(module.clone());
+            }
+            None => ungrouped.push(module.clone()),
+        }
+    }
+
+    fn group_id(&self, index: usize) -> String {
+        T::join_segments(&self.groups[index].path)
+    }
+
+    /// Outermost clusters of the internal modules or of the scripts
+    fn top_groups(&self, is_script: bool) -> impl Iterator<Item = usize> + '_ {
+        self.groups
+            .iter()
+            .enumerate()
+            .filter(move |(_, g)| g.parent.is_none() && g.is_script == is_script)
+            .map(|(index, _)| index)
+    }
+
+    /// `edges` with folded endpoints replaced by the leaf modules of their cluster, sorted
+    /// and deduplicated. Redirected edges keep the kind of the original edge; a module
+    /// importing its own package gets no self-loop out of it.
+    fn redirect_edges(
+        &self,
+        edges: Vec<(T, T)>,
+        edge_kind: impl Fn(&T, &T) -> EdgeKind,
+    ) -> Vec<(T, T, EdgeKind)> {
+        let endpoints = |module: &T| match self.folded.get(&module.to_dotted()) {
+            Some(folded) => folded.leaves.clone(),
+            None => vec![module.clone()],
+        };
+        let mut redirected = Vec::new();
+        for (from, to) in edges {
+            let kind = edge_kind(&from, &to);
+            let targets = endpoints(&to);
+            for redirected_from in endpoints(&from) {
+                for redirected_to in &targets {
+                    if redirected_from != *redirected_to || from == to {
+                        redirected.push((redirected_from.clone(), redirected_to.clone(), kind));
+                    }
+                }
+            }
+        }
+
+        redirected.sort_by(|a, b| {
+            a.0.to_dotted()
+                .cmp(&b.0.to_dotted())
+                .then_with(|| a.1.to_dotted().cmp(&b.1.to_dotted()))
+                .then_with(|| a.2.cmp(&b.2))
+        });
+        redirected.dedup_by(|later, earlier| later.0 == earlier.0 && later.1 == earlier.1);
+        redirected
+    }
+}
+
+#[derive(Clone)]
 pub struct DependencyGraph<T: GraphId> {
-    graph: DiGraph<T, ()>,
+    graph: DiGraph<T, Imports>,
     node_indices: HashMap<T, NodeIndex>,
     scripts: HashSet<T>,
     namespace_packages: HashSet<T>,
+    extensions: HashSet<T>,
+    generated: HashSet<T>,
+    edge_tags: HashMap<(T, T), Vec<String>>,
+    import_sites: HashMap<(T, T), Vec<ImportSite>>,
+    import_costs: HashMap<T, ImportCost>,
+    coverage: HashMap<T, f64>,
+    body_statements: HashMap<T, usize>,
+    directory_clustered: HashSet<T>,
+    categories: HashMap<T, String>,
+    lifecycles: HashMap<T, Lifecycle>,
+    annotations: HashMap<T, BTreeMap<String, String>>,
+    source_files: HashMap<T, String>,
 }
 
 impl<T: GraphId> DependencyGraph<T> {
@@ -219,6 +842,18 @@ impl<T: GraphId> DependencyGraph<T> {
             node_indices: HashMap::new(),
             scripts: HashSet::new(),
             namespace_packages: HashSet::new(),
+            extensions: HashSet::new(),
+            generated: HashSet::new(),
+            edge_tags: HashMap::new(),
+            import_sites: HashMap::new(),
+            import_costs: HashMap::new(),
+            coverage: HashMap::new(),
+            body_statements: HashMap::new(),
+            directory_clustered: HashSet::new(),
+            categories: HashMap::new(),
+            lifecycles: HashMap::new(),
+            annotations: HashMap::new(),
+            source_files: HashMap::new(),
         }
     }
 
@@ -238,104 +873,594 @@ impl<T: GraphId> DependencyGraph<T> {
         self.namespace_packages.contains(module)
     }
 
-    pub fn ensure_node(&mut self, module: T) {
-        let _ = self.get_or_create_node(module);
+    /// Mark a module as a compiled extension (e.g. a Cython `.pyx` module)
+    pub fn mark_as_extension(&mut self, module: &T) {
+        self.extensions.insert(module.clone());
     }
 
-    fn get_or_create_node(&mut self, module: T) -> NodeIndex {
-        if let Some(&idx) = self.node_indices.get(&module) {
-            idx
-        } else {
-            let idx = self.graph.add_node(module.clone());
-            self.node_indices.insert(module, idx);
-            idx
-        }
+    pub fn is_extension(&self, module: &T) -> bool {
+        self.extensions.contains(module)
     }
 
-    pub fn add_dependency(&mut self, from: T, to: T) {
-        let from_idx = self.get_or_create_node(from);
-        let to_idx = self.get_or_create_node(to);
-        self.graph.add_edge(from_idx, to_idx, ());
+    /// Mark a module as generated code (e.g. a protobuf `_pb2` module)
+    pub fn mark_as_generated(&mut self, module: &T) {
+        self.generated.insert(module.clone());
     }
 
-    fn select_visible_nodes(
-        &self,
-        selection: NodeSelection<'_, T>,
-        include_orphans: bool,
-        include_namespace_packages: bool,
-    ) -> Vec<NodeIndex> {
-        let mut nodes: Vec<_> = self.graph.node_indices().collect();
-        nodes.sort_by_key(|idx| self.graph[*idx].to_dotted());
+    pub fn is_generated(&self, module: &T) -> bool {
+        self.generated.contains(module)
+    }
 
-        nodes
-            .into_iter()
-            .filter(|idx| match selection {
-                NodeSelection::Full | NodeSelection::Highlighted => true,
-                NodeSelection::Filtered(set) => set.contains(&self.graph[*idx]),
-            })
-            .filter(|idx| {
-                include_namespace_packages || !self.is_namespace_package(&self.graph[*idx])
-            })
-            .filter(|idx| {
-                include_orphans
-                    || self
-                        .graph
-                        .neighbors_directed(*idx, Direction::Incoming)
-                        .count()
-                        > 0
-                    || self
-                        .graph
-                        .neighbors_directed(*idx, Direction::Outgoing)
-                        .count()
-                        > 0
-            })
-            .collect()
+    /// Generated modules, see [`Self::mark_as_generated`]
+    pub fn generated_modules(&self) -> impl Iterator<Item = &T> {
+        self.generated.iter()
     }
 
-    fn collect_edges(
-        &self,
-        node_set: &HashSet<NodeIndex>,
-        include_namespace_packages: bool,
-    ) -> Vec<(T, T)> {
-        let mut edges = Vec::new();
+    /// Attach a measured import cost to a module
+    pub fn set_import_cost(&mut self, module: &T, cost: ImportCost) {
+        self.import_costs.insert(module.clone(), cost);
+    }
 
-        if !include_namespace_packages {
-            for from_idx in self.graph.node_indices() {
-                if !node_set.contains(&from_idx) {
-                    continue;
-                }
-                let from_module = &self.graph[from_idx];
+    pub fn import_cost(&self, module: &T) -> Option<ImportCost> {
+        self.import_costs.get(module).copied()
+    }
 
-                for to_idx in self.graph.neighbors(from_idx) {
-                    let to_module = &self.graph[to_idx];
+    /// Attach a test coverage percentage (0-100) to a module
+    pub fn set_coverage(&mut self, module: &T, percent: f64) {
+        self.coverage.insert(module.clone(), percent);
+    }
 
-                    if self.is_namespace_package(to_module) {
-                        let mut visited = HashSet::new();
-                        self.find_transitive_non_namespace_targets(
-                            to_idx,
-                            &mut visited,
-                            node_set,
-                            &mut |target_idx| {
-                                let target_module = &self.graph[target_idx];
-                                edges.push((from_module.clone(), target_module.clone()));
-                            },
-                        );
-                    } else if node_set.contains(&to_idx) {
-                        edges.push((from_module.clone(), to_module.clone()));
-                    }
-                }
-            }
-        } else {
-            edges = self
-                .graph
-                .edge_indices()
-                .filter_map(|e| self.graph.edge_endpoints(e))
-                .filter(|(from, to)| node_set.contains(from) && node_set.contains(to))
-                .map(|(from, to)| (self.graph[from].clone(), self.graph[to].clone()))
-                .collect();
-        }
+    pub fn coverage(&self, module: &T) -> Option<f64> {
+        self.coverage.get(module).copied()
+    }
 
-        edges.sort_by(|a, b| {
+    /// Record how many top-level statements of a module do work on import besides
+    /// importing (e.g. the body of a package's `__init__.py`)
+    pub fn set_body_statements(&mut self, module: &T, count: usize) {
+        self.body_statements.insert(module.clone(), count);
+    }
+
+    pub fn body_statements(&self, module: &T) -> Option<usize> {
+        self.body_statements.get(module).copied()
+    }
+
+    /// Source link of a module from the link template; none for modules without a
+    /// source file when the template uses `{path}`
+    fn source_link(&self, module: &T, options: &RenderOptions) -> Option<String> {
+        let template = options.link_template.as_deref()?;
+        let link = if template.contains("{path}") {
+            template.replace("{path}", self.source_file(module)?)
+        } else {
+            template.to_string()
+        };
+        Some(
+            link.replace("{line}", "1")
+                .replace("{module}", &module.to_dotted()),
+        )
+    }
+
+    /// "module", "script", "namespace", or "extension"
+    fn node_type(&self, module: &T) -> &'static str {
+        if self.is_script(module) {
+            "script"
+        } else if self.is_namespace_package(module) {
+            "namespace"
+        } else if self.is_extension(module) {
+            "extension"
+        } else {
+            "module"
+        }
+    }
+
+    /// Attributes the style hooks give a rendered module
+    fn node_style(
+        &self,
+        renderer: Renderer,
+        module: &T,
+        highlighted: bool,
+        options: &RenderOptions,
+    ) -> Style {
+        if options.style.is_empty() {
+            return Style::new();
+        }
+        let id = module.to_dotted();
+        options.style.node_style(
+            renderer,
+            &NodeInfo {
+                id: &id,
+                node_type: self.node_type(module),
+                highlighted,
+                category: self.category(module),
+                lifecycle: self.lifecycle(module),
+                generated: self.is_generated(module),
+                annotations: self.annotations(module),
+            },
+        )
+    }
+
+    /// Attributes the style hooks give a rendered edge
+    fn edge_style(
+        &self,
+        renderer: Renderer,
+        (from, to): (&T, &T),
+        kind: EdgeKind,
+        options: &RenderOptions,
+    ) -> Style {
+        if options.style.is_empty() {
+            return Style::new();
+        }
+        let (source, target) = (from.to_dotted(), to.to_dotted());
+        options.style.edge_style(
+            renderer,
+            &EdgeInfo {
+                source: &source,
+                target: &target,
+                kind,
+                count: self.edge_multiplicity(from, to),
+            },
+        )
+    }
+
+    /// Labels of the Mermaid edge between two nodes, as selected by
+    /// [`RenderOptions::mermaid_edge_labels`] (the count also when edge counts are shown)
+    fn mermaid_label_parts(&self, from: &T, to: &T, options: &RenderOptions) -> Vec<String> {
+        let labels = &options.mermaid_edge_labels;
+        if labels.is_empty() {
+            return Vec::new();
+        }
+        let mut parts = Vec::new();
+        if labels.kind {
+            parts.push(self.edge_kind(from, to).as_str().to_string());
+        }
+        if (labels.count || options.show_edge_counts)
+            && let Some(count) = count_label(self.edge_multiplicity(from, to))
+        {
+            parts.push(count);
+        }
+        if let Some(key) = &labels.weight
+            && let Some(value) = self.annotation(to, key)
+        {
+            parts.push(format!("{key}={value}"));
+        }
+        parts
+    }
+
+    /// Label of the rendered edge between two nodes, if edge counts are shown
+    fn edge_label(&self, from: &T, to: &T, options: &RenderOptions) -> Option<String> {
+        options
+            .show_edge_counts
+            .then(|| count_label(self.edge_multiplicity(from, to)))
+            .flatten()
+    }
+
+    /// Always draw a module inside the cluster of its parent (e.g. a script's directory),
+    /// even when it is the only member
+    pub fn cluster_by_directory(&mut self, module: &T) {
+        self.directory_clustered.insert(module.clone());
+    }
+
+    pub fn is_directory_clustered(&self, module: &T) -> bool {
+        self.directory_clustered.contains(module)
+    }
+
+    /// Attach a user-defined category (e.g. "automation") to a module
+    pub fn set_category(&mut self, module: &T, category: &str) {
+        self.categories.insert(module.clone(), category.to_string());
+    }
+
+    pub fn category(&self, module: &T) -> Option<&str> {
+        self.categories.get(module).map(String::as_str)
+    }
+
+    /// Record the lifecycle state of a module
+    pub fn set_lifecycle(&mut self, module: &T, lifecycle: Lifecycle) {
+        self.lifecycles.insert(module.clone(), lifecycle);
+    }
+
+    pub fn lifecycle(&self, module: &T) -> Option<Lifecycle> {
+        self.lifecycles.get(module).copied()
+    }
+
+    /// Attach a free-form `key`/`value` annotation (e.g. `team` = `payments`) to a module
+    pub fn set_annotation(&mut self, module: &T, key: &str, value: &str) {
+        self.annotations
+            .entry(module.clone())
+            .or_default()
+            .insert(key.to_string(), value.to_string());
+    }
+
+    /// Record the file a module was read from, relative to the project root and with
+    /// forward slashes (e.g. `src/app/__init__.py`)
+    pub fn set_source_file(&mut self, module: &T, path: &str) {
+        self.source_files.insert(module.clone(), path.to_string());
+    }
+
+    pub fn source_file(&self, module: &T) -> Option<&str> {
+        self.source_files.get(module).map(String::as_str)
+    }
+
+    pub fn annotations(&self, module: &T) -> Option<&BTreeMap<String, String>> {
+        self.annotations.get(module)
+    }
+
+    pub fn annotation(&self, module: &T, key: &str) -> Option<&str> {
+        self.annotations(module)?.get(key).map(String::as_str)
+    }
+
+    /// Modules with a measured import cost, most expensive (by self time) first
+    pub fn slowest_imports(&self, limit: usize) -> Vec<(T, ImportCost)> {
+        let mut costs: Vec<(T, ImportCost)> = self
+            .import_costs
+            .iter()
+            .map(|(module, cost)| (module.clone(), *cost))
+            .collect();
+        costs.sort_by(|a, b| {
+            b.1.self_us
+                .cmp(&a.1.self_us)
+                .then_with(|| a.0.to_dotted().cmp(&b.0.to_dotted()))
+        });
+        costs.truncate(limit);
+        costs
+    }
+
+    /// Fill color for a module with a measured import cost: white for the cheapest,
+    /// saturated red for the module with the highest self time in the graph
+    fn import_heat_color(&self, module: &T) -> Option<String> {
+        let cost = self.import_cost(module)?;
+        let max_self_us = self
+            .import_costs
+            .values()
+            .map(|c| c.self_us)
+            .max()
+            .filter(|max| *max > 0)?;
+        let intensity = cost.self_us as f64 / max_self_us as f64;
+        let channel = 255 - (intensity * 191.0).round() as u8;
+        Some(format!("#ff{channel:02x}{channel:02x}"))
+    }
+
+    pub fn ensure_node(&mut self, module: T) {
+        let _ = self.get_or_create_node(module);
+    }
+
+    fn get_or_create_node(&mut self, module: T) -> NodeIndex {
+        if let Some(&idx) = self.node_indices.get(&module) {
+            idx
+        } else {
+            let idx = self.graph.add_node(module.clone());
+            self.node_indices.insert(module, idx);
+            idx
+        }
+    }
+
+    pub fn add_dependency(&mut self, from: T, to: T) {
+        self.add_dependency_with_kind(from, to, EdgeKind::ImportTime);
+    }
+
+    /// Record one import of `to` by `from`; repeated imports raise the edge's
+    /// [`Self::edge_multiplicity`]
+    pub fn add_dependency_with_kind(&mut self, from: T, to: T, kind: EdgeKind) {
+        self.add_imports(from, to, Imports { kind, count: 1 });
+    }
+
+    fn add_imports(&mut self, from: T, to: T, imports: Imports) {
+        let from_idx = self.get_or_create_node(from);
+        let to_idx = self.get_or_create_node(to);
+        let existing = self
+            .graph
+            .edges_connecting(from_idx, to_idx)
+            .find(|edge| edge.weight().kind == imports.kind)
+            .map(|edge| edge.id());
+        match existing {
+            Some(edge) => self.graph[edge].count += imports.count,
+            None => {
+                self.graph.add_edge(from_idx, to_idx, imports);
+            }
+        }
+    }
+
+    /// How many times `from` imports `to` directly, over both edge kinds; 0 without an edge
+    pub fn edge_multiplicity(&self, from: &T, to: &T) -> usize {
+        match (self.node_indices.get(from), self.node_indices.get(to)) {
+            (Some(&from_idx), Some(&to_idx)) => self
+                .graph
+                .edges_connecting(from_idx, to_idx)
+                .map(|edge| edge.weight().count)
+                .sum(),
+            _ => 0,
+        }
+    }
+
+    /// Attach a free-form tag (e.g. `platform:win32`) to the edge between two nodes
+    pub fn tag_edge(&mut self, from: &T, to: &T, tag: &str) {
+        let tags = self
+            .edge_tags
+            .entry((from.clone(), to.clone()))
+            .or_default();
+        if !tags.iter().any(|existing| existing == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+
+    pub fn edge_tags(&self, from: &T, to: &T) -> &[String] {
+        self.edge_tags
+            .get(&(from.clone(), to.clone()))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Record where the edge between two nodes is written (repeated sites are kept once)
+    pub fn record_import_site(&mut self, from: &T, to: &T, site: ImportSite) {
+        let sites = self
+            .import_sites
+            .entry((from.clone(), to.clone()))
+            .or_default();
+        if !sites.contains(&site) {
+            sites.push(site);
+        }
+    }
+
+    /// Sites recorded for the edge between two nodes, in recording order
+    pub fn import_sites(&self, from: &T, to: &T) -> &[ImportSite] {
+        self.import_sites
+            .get(&(from.clone(), to.clone()))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Copy the tags and import sites of the edge `from -> to` onto the edge between
+    /// `new_from` and `new_to` in `target`
+    fn copy_edge_tags_into<U: GraphId>(
+        &self,
+        (from, to): (&T, &T),
+        (new_from, new_to): (&U, &U),
+        target: &mut DependencyGraph<U>,
+    ) {
+        for tag in self.edge_tags(from, to) {
+            target.tag_edge(new_from, new_to, tag);
+        }
+        for site in self.import_sites(from, to) {
+            target.record_import_site(new_from, new_to, site.clone());
+        }
+    }
+
+    /// Kind of the rendered edge between two nodes: import-time if any parallel edge is,
+    /// deferred if all of them are. Edges without a direct counterpart (e.g. bridged
+    /// through a hidden namespace package) count as import-time.
+    pub fn edge_kind(&self, from: &T, to: &T) -> EdgeKind {
+        match (self.node_indices.get(from), self.node_indices.get(to)) {
+            (Some(&from_idx), Some(&to_idx)) => self
+                .graph
+                .edges_connecting(from_idx, to_idx)
+                .map(|edge| edge.weight().kind)
+                .min()
+                .unwrap_or_default(),
+            _ => EdgeKind::ImportTime,
+        }
+    }
+
+    /// Copy of this graph keeping every node but only the edges accepted by `keep`
+    pub fn filter_edges(&self, keep: impl Fn(&T, &T, EdgeKind) -> bool) -> Self {
+        let mut filtered = self.map_ids(T::clone);
+        filtered.graph.clear_edges();
+        for edge in self.graph.raw_edges() {
+            let from = &self.graph[edge.source()];
+            let to = &self.graph[edge.target()];
+            if keep(from, to, edge.weight.kind) {
+                filtered.add_imports(from.clone(), to.clone(), edge.weight);
+                self.copy_edge_tags_into((from, to), (from, to), &mut filtered);
+            }
+        }
+        filtered
+    }
+
+    /// Copy of this graph keeping only the nodes accepted by `keep` (with their markers)
+    /// and the edges between them
+    pub fn filter_nodes(&self, keep: impl Fn(&T) -> bool) -> Self {
+        let mut filtered = DependencyGraph::new();
+        for module in self.graph.node_weights().filter(|module| keep(module)) {
+            self.copy_node_into(module, module.clone(), &mut filtered);
+        }
+        for edge in self.graph.raw_edges() {
+            let from = &self.graph[edge.source()];
+            let to = &self.graph[edge.target()];
+            if keep(from) && keep(to) {
+                filtered.add_imports(from.clone(), to.clone(), edge.weight);
+                self.copy_edge_tags_into((from, to), (from, to), &mut filtered);
+            }
+        }
+        filtered
+    }
+
+    /// Add `module` to `target` as `id`, with its script/namespace/extension/generated markers, import cost,
+    /// coverage, body statements, directory clustering, category, lifecycle, annotations, and source file
+    fn copy_node_into<U: GraphId>(&self, module: &T, id: U, target: &mut DependencyGraph<U>) {
+        if self.is_script(module) {
+            target.mark_as_script(&id);
+        }
+        if self.is_namespace_package(module) {
+            target.mark_as_namespace_package(&id);
+        }
+        if self.is_extension(module) {
+            target.mark_as_extension(&id);
+        }
+        if self.is_generated(module) {
+            target.mark_as_generated(&id);
+        }
+        if let Some(cost) = self.import_cost(module) {
+            target.set_import_cost(&id, cost);
+        }
+        if let Some(percent) = self.coverage(module) {
+            target.set_coverage(&id, percent);
+        }
+        if let Some(count) = self.body_statements(module) {
+            target.set_body_statements(&id, count);
+        }
+        if self.is_directory_clustered(module) {
+            target.cluster_by_directory(&id);
+        }
+        if let Some(category) = self.category(module) {
+            target.set_category(&id, category);
+        }
+        if let Some(lifecycle) = self.lifecycle(module) {
+            target.set_lifecycle(&id, lifecycle);
+        }
+        for (key, value) in self.annotations(module).into_iter().flatten() {
+            target.set_annotation(&id, key, value);
+        }
+        if let Some(path) = self.source_file(module) {
+            target.set_source_file(&id, path);
+        }
+        target.ensure_node(id);
+    }
+
+    /// Convert every node identifier with `f`, keeping edges and per-node attributes
+    pub fn map_ids<U: GraphId>(&self, f: impl Fn(&T) -> U) -> DependencyGraph<U> {
+        let mut mapped = DependencyGraph::new();
+        for module in self.graph.node_weights() {
+            self.copy_node_into(module, f(module), &mut mapped);
+        }
+        for edge in self.graph.raw_edges() {
+            let (from, to) = (&self.graph[edge.source()], &self.graph[edge.target()]);
+            let (new_from, new_to) = (f(from), f(to));
+            self.copy_edge_tags_into((from, to), (&new_from, &new_to), &mut mapped);
+            mapped.add_imports(new_from, new_to, edge.weight);
+        }
+        mapped
+    }
+
+    /// Add all nodes, edges, and markers of `other` to this graph
+    pub fn extend(&mut self, other: &DependencyGraph<T>) {
+        for module in other.graph.node_weights() {
+            other.copy_node_into(module, module.clone(), self);
+        }
+        for edge in other.graph.raw_edges() {
+            let (from, to) = (&other.graph[edge.source()], &other.graph[edge.target()]);
+            other.copy_edge_tags_into((from, to), (from, to), self);
+            self.add_imports(from.clone(), to.clone(), edge.weight);
+        }
+    }
+
+    /// Copy of this graph without the `hidden` nodes, bridging every path through them
+    /// into a direct edge (as hidden namespace packages are) so hiding a node doesn't
+    /// disconnect its neighbors. A bridged edge is deferred if any edge on its path is.
+    pub fn hide_nodes(&self, hidden: &HashSet<T>) -> Self {
+        let mut result = self.filter_nodes(|module| !hidden.contains(module));
+        let mut bridged: HashSet<(NodeIndex, NodeIndex, EdgeKind)> = HashSet::new();
+
+        for edge in self.graph.raw_edges() {
+            let from_idx = edge.source();
+            if hidden.contains(&self.graph[from_idx])
+                || !hidden.contains(&self.graph[edge.target()])
+            {
+                continue;
+            }
+            // Walk through hidden nodes until reaching visible ones
+            let mut visited = HashSet::from([(edge.target(), edge.weight.kind)]);
+            let mut stack = vec![(edge.target(), edge.weight.kind)];
+            while let Some((idx, kind)) = stack.pop() {
+                for next in self.graph.edges(idx) {
+                    let target = next.target();
+                    let kind = kind.max(next.weight().kind);
+                    if hidden.contains(&self.graph[target]) {
+                        if visited.insert((target, kind)) {
+                            stack.push((target, kind));
+                        }
+                    } else if target != from_idx {
+                        bridged.insert((from_idx, target, kind));
+                    }
+                }
+            }
+        }
+
+        let mut bridged: Vec<_> = bridged.into_iter().collect();
+        bridged.sort();
+        for (from_idx, to_idx, kind) in bridged {
+            let from = &self.graph[from_idx];
+            let to = &self.graph[to_idx];
+            let exists = result
+                .graph
+                .edges_connecting(result.node_indices[from], result.node_indices[to])
+                .any(|edge| edge.weight().kind == kind);
+            if !exists {
+                result.add_dependency_with_kind(from.clone(), to.clone(), kind);
+            }
+        }
+        result
+    }
+
+    pub fn contains(&self, module: &T) -> bool {
+        self.node_indices.contains_key(module)
+    }
+
+    fn select_visible_nodes(
+        &self,
+        selection: NodeSelection<'_, T>,
+        include_orphans: bool,
+        include_namespace_packages: bool,
+    ) -> Vec<NodeIndex> {
+        let mut nodes: Vec<_> = self.graph.node_indices().collect();
+        nodes.sort_by_key(|idx| self.graph[*idx].to_dotted());
+        let orphans = if include_orphans {
+            HashSet::new()
+        } else {
+            self.orphans_in(OrphanScope::Graph, include_namespace_packages)
+        };
+
+        nodes
+            .into_iter()
+            .filter(|idx| match selection {
+                NodeSelection::Full | NodeSelection::Highlighted => true,
+                NodeSelection::Filtered(set) => set.contains(&self.graph[*idx]),
+            })
+            .filter(|idx| {
+                include_namespace_packages || !self.is_namespace_package(&self.graph[*idx])
+            })
+            .filter(|idx| !orphans.contains(&self.graph[*idx]))
+            .collect()
+    }
+
+    fn collect_edges(
+        &self,
+        node_set: &HashSet<NodeIndex>,
+        include_namespace_packages: bool,
+    ) -> Vec<(T, T)> {
+        let mut edges = Vec::new();
+
+        if !include_namespace_packages {
+            for from_idx in self.graph.node_indices() {
+                if !node_set.contains(&from_idx) {
+                    continue;
+                }
+                let from_module = &self.graph[from_idx];
+
+                for to_idx in self.graph.neighbors(from_idx) {
+                    let to_module = &self.graph[to_idx];
+
+                    if self.is_namespace_package(to_module) {
+                        let mut visited = HashSet::new();
+                        self.find_transitive_non_namespace_targets(
+                            to_idx,
+                            &mut visited,
+                            node_set,
+                            &mut |target_idx| {
+                                let target_module = &self.graph[target_idx];
+                                edges.push((from_module.clone(), target_module.clone()));
+                            },
+                        );
+                    } else if node_set.contains(&to_idx) {
+                        edges.push((from_module.clone(), to_module.clone()));
+                    }
+                }
+            }
+        } else {
+            edges = self
+                .graph
+                .edge_indices()
+                .filter_map(|e| self.graph.edge_endpoints(e))
+                .filter(|(from, to)| node_set.contains(from) && node_set.contains(to))
+                .map(|(from, to)| (self.graph[from].clone(), self.graph[to].clone()))
+                .collect();
+        }
+
+        edges.sort_by(|a, b| {
             a.0.to_dotted()
                 .cmp(&b.0.to_dotted())
                 .then_with(|| a.1.to_dotted().cmp(&b.1.to_dotted()))
@@ -344,7 +1469,11 @@ impl<T: GraphId> DependencyGraph<T> {
         edges
     }
 
-    fn build_namespace_forest(&self, visible_nodes: &[NodeIndex]) -> NamespaceForest<T> {
+    fn build_namespace_forest(
+        &self,
+        visible_nodes: &[NodeIndex],
+        grouping: NamespaceGrouping,
+    ) -> NamespaceForest<T> {
         let mut internal = NamespaceTree::new(vec![]);
         let mut scripts = NamespaceTree::new(vec![]);
 
@@ -355,129 +1484,73 @@ impl<T: GraphId> DependencyGraph<T> {
             } else {
                 &mut internal
             };
-            target.insert(module_path);
+            target.insert(module_path, self.is_directory_clustered(module_path));
         }
 
-        internal.finalize();
-        scripts.finalize();
+        internal.finalize(grouping);
+        scripts.finalize(grouping);
 
         NamespaceForest { internal, scripts }
     }
 
-    fn tree_for<'a>(&self, forest: &'a NamespaceForest<T>, module: &T) -> &'a NamespaceTree<T> {
-        if self.is_script(module) {
-            &forest.scripts
-        } else {
-            &forest.internal
+    /// The package clusters of `visible_nodes`; none at all unless `namespaces`
+    fn namespace_hierarchy(
+        &self,
+        visible_nodes: &[NodeIndex],
+        namespaces: bool,
+        grouping: NamespaceGrouping,
+    ) -> Hierarchy<T> {
+        let mut forest = self.build_namespace_forest(visible_nodes, grouping);
+        if !namespaces {
+            forest.internal.ungroup();
+            forest.scripts.ungroup();
         }
+        Hierarchy::new(&forest)
     }
 
-    fn is_group_only_namespace(&self, forest: &NamespaceForest<T>, module: &T) -> bool {
-        self.tree_for(forest, module)
-            .is_group_only(&module.segments())
-    }
-
+    /// Cytoscape compound nodes of `hierarchy`: the parent of every drawn module by dotted
+    /// name, and a `namespace_group` node for each cluster without a module of its own. A
+    /// module named like its cluster is the compound node itself.
     fn generate_compound_nodes(
         &self,
-        forest: &NamespaceForest<T>,
-        include_namespace_packages: bool,
+        hierarchy: &Hierarchy<T>,
     ) -> (HashMap<String, String>, Vec<GraphNode>) {
-        let mut leaf_parent_map = HashMap::new();
+        let mut leaf_parent_map: HashMap<String, String> = hierarchy
+            .parents
+            .iter()
+            .map(|(module, &index)| (module.clone(), hierarchy.group_id(index)))
+            .collect();
         let mut parent_nodes = Vec::new();
 
-        self.collect_compound_nodes_recursive(
-            &forest.internal,
-            None,
-            include_namespace_packages,
-            &mut leaf_parent_map,
-            &mut parent_nodes,
-        );
-
-        self.collect_compound_nodes_recursive(
-            &forest.scripts,
-            None,
-            include_namespace_packages,
-            &mut leaf_parent_map,
-            &mut parent_nodes,
-        );
-
-        (leaf_parent_map, parent_nodes)
-    }
-
-    #[allow(clippy::only_used_in_recursion)]
-    fn collect_compound_nodes_recursive(
-        &self,
-        node: &NamespaceTree<T>,
-        parent_id: Option<String>,
-        include_namespace_packages: bool,
-        leaf_parent_map: &mut HashMap<String, String>,
-        parent_nodes: &mut Vec<GraphNode>,
-    ) {
-        if node.path.is_empty() {
-            for child in node.child_groups() {
-                self.collect_compound_nodes_recursive(
-                    child,
-                    None,
-                    include_namespace_packages,
-                    leaf_parent_map,
-                    parent_nodes,
-                );
-            }
-            return;
-        }
-
-        let current_id = node.path.join(".");
-
-        if node.grouped {
-            if node.id.is_none() {
+        for (index, group) in hierarchy.groups.iter().enumerate() {
+            let parent_id = group.parent.map(|parent| hierarchy.group_id(parent));
+            let Some(module) = &group.module else {
                 parent_nodes.push(GraphNode {
-                    id: current_id.clone(),
+                    id: hierarchy.group_id(index),
                     node_type: "namespace_group".to_string(),
                     is_orphan: false,
                     highlighted: None,
-                    parent: parent_id.clone(),
+                    parent: parent_id,
+                    import_time_us: None,
+                    coverage: None,
+                    category: None,
+                    lifecycle: None,
+                    generated: None,
+                    change: None,
+                    annotations: None,
+                    position: None,
+                    style: None,
+                    href: None,
                 });
-            } else if let Some(pid) = &parent_id {
-                leaf_parent_map.insert(current_id.clone(), pid.clone());
-            }
-
-            for child in node.child_groups() {
-                self.collect_compound_nodes_recursive(
-                    child,
-                    Some(current_id.clone()),
-                    include_namespace_packages,
-                    leaf_parent_map,
-                    parent_nodes,
-                );
-            }
-        } else {
-            if let Some(id) = &node.id {
-                if let Some(pid) = parent_id.clone() {
-                    leaf_parent_map.insert(id.to_dotted(), pid);
-                }
-            }
-
-            for child in node.child_groups() {
-                self.collect_compound_nodes_recursive(
-                    child,
-                    parent_id.clone(),
-                    include_namespace_packages,
-                    leaf_parent_map,
-                    parent_nodes,
-                );
+                continue;
+            };
+            leaf_parent_map.remove(&module.to_dotted());
+            if let Some(parent_id) = parent_id {
+                leaf_parent_map.insert(hierarchy.group_id(index), parent_id);
             }
         }
-    }
 
-    fn get_visible_leaf_descendants(&self, forest: &NamespaceForest<T>, module: &T) -> Vec<T> {
-        self.tree_for(forest, module)
-            .find(&module.segments())
-            .map(|node| {
-                let mut descendants = Vec::new();
-                node.collect_leaf_descendants(&mut descendants);
-                descendants
-            })
-            .unwrap_or_default()
+        (leaf_parent_map, parent_nodes)
     }
 
     fn find_transitive_non_namespace_targets<F>(
@@ -517,32 +1590,67 @@ impl<T: GraphId> DependencyGraph<T> {
         module: &T,
         include_namespace_packages: bool,
         is_highlighted: bool,
+        options: &RenderOptions,
     ) -> Option<DotNodeSpec> {
         if self.is_namespace_package(module) && !include_namespace_packages {
             return None;
         }
 
+        let heat = if is_highlighted || self.is_namespace_package(module) {
+            None
+        } else {
+            self.import_heat_color(module)
+        };
+
         let attrs = if self.is_script(module) {
             if is_highlighted {
-                "[shape=box, fillcolor=lightblue, style=filled]"
+                "[shape=box, fillcolor=lightblue, style=filled]".to_string()
+            } else if let Some(color) = heat {
+                format!("[shape=box, fillcolor=\"{color}\", style=filled]")
             } else {
-                "[shape=box]"
+                "[shape=box]".to_string()
             }
         } else if self.is_namespace_package(module) {
             if is_highlighted {
-                "[shape=hexagon, fillcolor=lightblue, style=filled]"
+                "[shape=hexagon, fillcolor=lightblue, style=filled]".to_string()
             } else {
-                "[shape=hexagon, style=dashed]"
+                "[shape=hexagon, style=dashed]".to_string()
+            }
+        } else if self.is_extension(module) {
+            if is_highlighted {
+                "[shape=component, fillcolor=lightblue, style=filled]".to_string()
+            } else if let Some(color) = heat {
+                format!("[shape=component, fillcolor=\"{color}\", style=filled]")
+            } else {
+                "[shape=component]".to_string()
             }
         } else if is_highlighted {
-            "[fillcolor=lightblue, style=filled]"
+            "[fillcolor=lightblue, style=filled]".to_string()
+        } else if let Some(color) = heat {
+            format!("[fillcolor=\"{color}\", style=filled]")
+        } else {
+            String::new()
+        };
+        // Hook attributes come last, so DOT lets them override the ones above
+        let style = self.node_style(Renderer::Dot, module, is_highlighted, options);
+        let extra: Vec<String> = self
+            .source_link(module, options)
+            .map(|link| format!("URL=\"{}\"", escape::dot_string(&link)))
+            .into_iter()
+            .chain(dot_style_attrs(&style))
+            .collect();
+        let attrs = if extra.is_empty() {
+            attrs
         } else {
-            ""
+            match attrs.strip_suffix(']') {
+                Some(open) => format!("{open}, {}]", extra.join(", ")),
+                None => format!("[{}]", extra.join(", ")),
+            }
         };
 
         Some(DotNodeSpec {
             name: module.to_dotted(),
-            attrs: attrs.to_string(),
+            attrs,
         })
     }
 
@@ -551,6 +1659,7 @@ impl<T: GraphId> DependencyGraph<T> {
         nodes: &[NodeIndex],
         include_namespace_packages: bool,
         highlight_set: Option<&HashSet<T>>,
+        options: &RenderOptions,
     ) -> HashMap<String, DotNodeSpec> {
         nodes
             .iter()
@@ -560,273 +1669,319 @@ impl<T: GraphId> DependencyGraph<T> {
                     .map(|set| set.contains(module))
                     .unwrap_or(false);
 
-                self.dot_spec_for_module(module, include_namespace_packages, is_highlighted)
-                    .map(|spec| (spec.name.clone(), spec))
+                self.dot_spec_for_module(
+                    module,
+                    include_namespace_packages,
+                    is_highlighted,
+                    options,
+                )
+                .map(|spec| (spec.name.clone(), spec))
             })
             .collect()
     }
 
-    #[allow(clippy::only_used_in_recursion, clippy::too_many_arguments)]
-    fn render_dot_subgraph_generic(
+    fn render_dot_group(
         &self,
-        node: &NamespaceTree<T>,
-        forest: &NamespaceForest<T>,
-        highlight_set: Option<&HashSet<T>>,
-        include_namespace_packages: bool,
+        hierarchy: &Hierarchy<T>,
+        index: usize,
         specs: &HashMap<String, DotNodeSpec>,
-        cluster_root: bool,
         indent_level: usize,
-        is_script_root: bool,
+        options: &RenderOptions,
         output: &mut String,
     ) {
         let indent = "    ".repeat(indent_level);
-
-        if node.children.is_empty() && node.id.is_none() {
-            return;
+        let group = &hierarchy.groups[index];
+
+        output.push_str(&format!(
+            "{indent}subgraph cluster_{} {{\n",
+            escape::identifier(&group.path.join("_"))
+        ));
+        output.push_str(&format!(
+            "{indent}    label = \"{}\";\n",
+            escape::dot_string(&hierarchy.group_id(index))
+        ));
+        // The package's own source (its `__init__.py`) links from the cluster
+        if let Some(link) = group
+            .module
+            .as_ref()
+            .and_then(|module| self.source_link(module, options))
+        {
+            output.push_str(&format!(
+                "{indent}    URL = \"{}\";\n",
+                escape::dot_string(&link)
+            ));
         }
+        for module in &group.members {
+            if let Some(spec) = specs.get(&module.to_dotted()) {
+                output.push_str(&spec.render(&indent));
+            }
+        }
+        for &child in &group.children {
+            self.render_dot_group(hierarchy, child, specs, indent_level + 1, options, output);
+        }
+        output.push_str(&format!("{indent}}}\n"));
+    }
 
-        let has_root_content = !node.path.is_empty()
-            || !node.direct_concrete_children().is_empty()
-            || (!is_script_root && node.children.iter().any(|c| c.grouped));
+    /// Node statements of the visible modules: package clusters, the root clusters of
+    /// modules and scripts, and the modules outside any cluster, as `clustering` asks
+    fn render_dot_nodes(
+        &self,
+        hierarchy: &Hierarchy<T>,
+        specs: &HashMap<String, DotNodeSpec>,
+        clustering: DotClustering,
+        options: &RenderOptions,
+        output: &mut String,
+    ) {
+        let mut ungrouped: Vec<&T> = Vec::new();
+        for is_script in [false, true] {
+            let tree_ungrouped = &hierarchy.ungrouped[usize::from(is_script)];
+            let top_groups: Vec<usize> = hierarchy.top_groups(is_script).collect();
+            // A root cluster holds every module of its tree outside a package cluster.
+            // Script directory clusters are not wrapped on their own.
+            let root_cluster = clustering.roots
+                && (!tree_ungrouped.is_empty() || (!is_script && !top_groups.is_empty()));
+            if !root_cluster {
+                for &index in &top_groups {
+                    self.render_dot_group(hierarchy, index, specs, 1, options, output);
+                }
+                ungrouped.extend(tree_ungrouped);
+                continue;
+            }
 
-        if (node.grouped || (cluster_root && node.path.is_empty() && has_root_content))
-            && (cluster_root || !node.path.is_empty())
-        {
-            let cluster_name = if node.path.is_empty() {
-                "root".to_string()
-            } else {
-                node.path.join("_")
-            };
-            let label = if node.path.is_empty() {
-                "root".to_string()
+            let (name, label) = if is_script {
+                ("scripts_root", "scripts")
             } else {
-                node.path.join(".")
+                ("root", "root")
             };
-
-            output.push_str(&format!("{indent}subgraph cluster_{cluster_name} {{\n"));
-            output.push_str(&format!("{indent}    label = \"{label}\";\n"));
-
-            for module in node.direct_concrete_children() {
-                if self.is_group_only_namespace(forest, &module) {
-                    continue;
-                }
+            output.push_str(&format!("    subgraph cluster_{name} {{\n"));
+            output.push_str(&format!("        label = \"{label}\";\n"));
+            for module in tree_ungrouped {
                 if let Some(spec) = specs.get(&module.to_dotted()) {
-                    output.push_str(&spec.render(&indent));
+                    output.push_str(&spec.render("    "));
                 }
             }
-
-            for child in node.child_groups() {
-                self.render_dot_subgraph_generic(
-                    child,
-                    forest,
-                    highlight_set,
-                    include_namespace_packages,
-                    specs,
-                    cluster_root,
-                    indent_level + 1,
-                    is_script_root,
-                    output,
-                );
+            for &index in &top_groups {
+                self.render_dot_group(hierarchy, index, specs, 2, options, output);
             }
+            output.push_str("    }\n");
+        }
 
-            output.push_str(&format!("{indent}}}\n"));
-        } else {
-            for child in node.child_groups() {
-                self.render_dot_subgraph_generic(
-                    child,
-                    forest,
-                    highlight_set,
-                    include_namespace_packages,
-                    specs,
-                    cluster_root,
-                    indent_level,
-                    is_script_root,
-                    output,
-                );
+        ungrouped.sort_by_key(|module| module.to_dotted());
+        for module in ungrouped {
+            if let Some(spec) = specs.get(&module.to_dotted()) {
+                output.push_str(&spec.render(""));
             }
         }
     }
 
-    fn collect_ungrouped_modules(&self, node: &NamespaceTree<T>, ungrouped: &mut Vec<T>) {
-        node.collect_ungrouped_modules(ungrouped);
-    }
-
-    pub fn to_dot(&self, include_orphans: bool, include_namespace_packages: bool) -> String {
+    /// DOT output of the nodes `selection` picks, clustered by package. Edges of modules
+    /// folded into their cluster are drawn to the modules inside it.
+    fn render_dot(
+        &self,
+        selection: NodeSelection<T>,
+        highlight_set: Option<&HashSet<T>>,
+        include_orphans: bool,
+        include_namespace_packages: bool,
+        clustering: DotClustering,
+        options: &RenderOptions,
+    ) -> String {
         let mut output = String::from("digraph dependencies {\n");
         output.push_str("    rankdir=LR;\n");
         output.push_str(
             "    // Note: Scripts (files outside source root) are shown with box shape\n",
         );
-        let nodes = self.select_visible_nodes(
-            NodeSelection::Full,
-            include_orphans,
-            include_namespace_packages,
-        );
-        let forest = self.build_namespace_forest(&nodes);
-        let specs = self.dot_spec_map(&nodes, include_namespace_packages, None);
-
-        self.render_dot_subgraph_generic(
-            &forest.internal,
-            &forest,
-            None,
-            include_namespace_packages,
-            &specs,
-            false,
-            1,
-            false,
-            &mut output,
-        );
-
-        self.render_dot_subgraph_generic(
-            &forest.scripts,
-            &forest,
-            None,
-            include_namespace_packages,
-            &specs,
-            false,
-            1,
-            true,
-            &mut output,
-        );
-
-        let mut ungrouped: Vec<T> = Vec::new();
-        self.collect_ungrouped_modules(&forest.internal, &mut ungrouped);
-        self.collect_ungrouped_modules(&forest.scripts, &mut ungrouped);
-
-        ungrouped.sort_by_key(GraphId::to_dotted);
-
-        for module in &ungrouped {
-            if !self.is_group_only_namespace(&forest, module) {
-                if let Some(spec) = specs.get(&module.to_dotted()) {
-                    output.push_str(&spec.render(""));
-                }
-            }
-        }
-
-        let node_set: HashSet<NodeIndex> = nodes.iter().copied().collect();
-        let mut edges = self.collect_edges(&node_set, include_namespace_packages);
-
-        let mut transformed_edges = Vec::new();
-        for (from_name, to_name) in edges.drain(..) {
-            let from_is_group_only = self.is_group_only_namespace(&forest, &from_name);
-            let to_is_group_only = self.is_group_only_namespace(&forest, &to_name);
-
-            match (from_is_group_only, to_is_group_only) {
-                (false, false) => {
-                    transformed_edges.push((from_name, to_name));
-                }
-                (true, false) => {
-                    let descendants = self.get_visible_leaf_descendants(&forest, &from_name);
-                    for descendant in descendants {
-                        transformed_edges.push((descendant, to_name.clone()));
-                    }
-                }
-                (false, true) => {
-                    let descendants = self.get_visible_leaf_descendants(&forest, &to_name);
-                    for descendant in descendants {
-                        transformed_edges.push((from_name.clone(), descendant));
-                    }
-                }
-                (true, true) => {
-                    let from_descendants = self.get_visible_leaf_descendants(&forest, &from_name);
-                    let to_descendants = self.get_visible_leaf_descendants(&forest, &to_name);
-                    for from_desc in &from_descendants {
-                        for to_desc in &to_descendants {
-                            transformed_edges.push((from_desc.clone(), to_desc.clone()));
-                        }
-                    }
-                }
-            }
+        if highlight_set.is_some() {
+            output
+                .push_str("    // Note: Highlighted nodes are shown with light blue background\n");
         }
+        let nodes =
+            self.select_visible_nodes(selection, include_orphans, include_namespace_packages);
+        let hierarchy =
+            self.namespace_hierarchy(&nodes, clustering.namespaces, options.namespace_grouping);
+        let specs = self.dot_spec_map(&nodes, include_namespace_packages, highlight_set, options);
 
-        edges = transformed_edges;
-
-        edges.sort_by(|a, b| {
-            a.0.to_dotted()
-                .cmp(&b.0.to_dotted())
-                .then_with(|| a.1.to_dotted().cmp(&b.1.to_dotted()))
-        });
-        edges.dedup();
+        self.render_dot_nodes(&hierarchy, &specs, clustering, options, &mut output);
 
-        for (from_name, to_name) in edges {
-            output.push_str(&format!(
-                "    \"{}\" -> \"{}\";\n",
-                from_name.to_dotted(),
-                to_name.to_dotted()
-            ));
+        let node_set: HashSet<NodeIndex> = nodes.iter().copied().collect();
+        let edges = self.collect_edges(&node_set, include_namespace_packages);
+        for (from_name, to_name, kind) in
+            hierarchy.redirect_edges(edges, |from, to| self.edge_kind(from, to))
+        {
+            let label = self.edge_label(&from_name, &to_name, options);
+            let style = self.edge_style(Renderer::Dot, (&from_name, &to_name), kind, options);
+            output.push_str(&render_dot_edge(&from_name, &to_name, kind, label, &style));
         }
 
         output.push_str("}\n");
         output
     }
 
+    pub fn to_dot(
+        &self,
+        include_orphans: bool,
+        include_namespace_packages: bool,
+        options: &RenderOptions,
+    ) -> String {
+        self.to_dot_clustered(
+            include_orphans,
+            include_namespace_packages,
+            DotClustering::PACKAGES,
+            options,
+        )
+    }
+
+    /// [`Self::to_dot`] with explicit clustering
+    pub fn to_dot_clustered(
+        &self,
+        include_orphans: bool,
+        include_namespace_packages: bool,
+        clustering: DotClustering,
+        options: &RenderOptions,
+    ) -> String {
+        self.render_dot(
+            NodeSelection::Full,
+            None,
+            include_orphans,
+            include_namespace_packages,
+            clustering,
+            options,
+        )
+    }
+
     pub fn to_dot_highlighted(
         &self,
         highlight_set: &HashSet<T>,
         include_orphans: bool,
         include_namespace_packages: bool,
+        options: &RenderOptions,
     ) -> String {
-        let mut output = String::from("digraph dependencies {\n");
-        output.push_str("    rankdir=LR;\n");
-        output.push_str(
-            "    // Note: Scripts (files outside source root) are shown with box shape\n",
-        );
-        output.push_str("    // Note: Highlighted nodes are shown with light blue background\n");
-        let nodes = self.select_visible_nodes(
-            NodeSelection::Highlighted,
+        self.to_dot_highlighted_clustered(
+            highlight_set,
             include_orphans,
             include_namespace_packages,
-        );
-        let forest = self.build_namespace_forest(&nodes);
-        let specs = self.dot_spec_map(&nodes, include_namespace_packages, Some(highlight_set));
+            DotClustering::ALL,
+            options,
+        )
+    }
 
-        self.render_dot_subgraph_generic(
-            &forest.internal,
-            &forest,
+    /// [`Self::to_dot_highlighted`] with explicit clustering
+    pub fn to_dot_highlighted_clustered(
+        &self,
+        highlight_set: &HashSet<T>,
+        include_orphans: bool,
+        include_namespace_packages: bool,
+        clustering: DotClustering,
+        options: &RenderOptions,
+    ) -> String {
+        self.render_dot(
+            NodeSelection::Highlighted,
             Some(highlight_set),
+            include_orphans,
             include_namespace_packages,
-            &specs,
-            true,
-            1,
-            false,
-            &mut output,
+            clustering,
+            options,
+        )
+    }
+
+    /// DOT output with one cluster per detected community (see
+    /// [`Self::detect_communities`]) instead of the package hierarchy
+    pub fn to_dot_communities(
+        &self,
+        communities: &[Vec<T>],
+        include_orphans: bool,
+        include_namespace_packages: bool,
+        options: &RenderOptions,
+    ) -> String {
+        self.to_dot_clusters(
+            &labeled_communities(communities),
+            "community",
+            "detected communities",
+            include_orphans,
+            include_namespace_packages,
+            options,
+        )
+    }
+
+    /// DOT output with one cluster per named group of modules (e.g. modules sharing an
+    /// annotation value) instead of the package hierarchy; modules in no group are drawn
+    /// outside the clusters
+    pub fn to_dot_grouped(
+        &self,
+        groups: &[(String, Vec<T>)],
+        include_orphans: bool,
+        include_namespace_packages: bool,
+        options: &RenderOptions,
+    ) -> String {
+        self.to_dot_clusters(
+            groups,
+            "group",
+            "groups",
+            include_orphans,
+            include_namespace_packages,
+            options,
+        )
+    }
+
+    fn to_dot_clusters(
+        &self,
+        clusters: &[(String, Vec<T>)],
+        kind: &str,
+        description: &str,
+        include_orphans: bool,
+        include_namespace_packages: bool,
+        options: &RenderOptions,
+    ) -> String {
+        let mut output = String::from("digraph dependencies {\n");
+        output.push_str("    rankdir=LR;\n");
+        output.push_str(
+            "    // Note: Scripts (files outside source root) are shown with box shape\n",
         );
-
-        self.render_dot_subgraph_generic(
-            &forest.scripts,
-            &forest,
-            Some(highlight_set),
+        output.push_str(&format!(
+            "    // Note: Clusters are {description}, not packages\n"
+        ));
+        let nodes = self.select_visible_nodes(
+            NodeSelection::Full,
+            include_orphans,
             include_namespace_packages,
-            &specs,
-            true,
-            1,
-            true,
-            &mut output,
         );
+        let specs = self.dot_spec_map(&nodes, include_namespace_packages, None, options);
+        let node_set: HashSet<NodeIndex> = nodes.iter().copied().collect();
 
-        let mut ungrouped: Vec<T> = Vec::new();
-        self.collect_ungrouped_modules(&forest.internal, &mut ungrouped);
-        self.collect_ungrouped_modules(&forest.scripts, &mut ungrouped);
-
-        ungrouped.sort_by_key(GraphId::to_dotted);
-
-        for module in &ungrouped {
-            if !self.is_group_only_namespace(&forest, module) {
-                if let Some(spec) = specs.get(&module.to_dotted()) {
-                    output.push_str(&spec.render(""));
-                }
+        let mut clustered: HashSet<&T> = HashSet::new();
+        for (index, (label, members)) in clusters.iter().enumerate() {
+            let visible: Vec<&DotNodeSpec> = members
+                .iter()
+                .filter_map(|m| specs.get(&m.to_dotted()))
+                .collect();
+            if visible.is_empty() {
+                continue;
+            }
+            output.push_str(&format!("    subgraph cluster_{kind}_{} {{\n", index + 1));
+            output.push_str(&format!(
+                "        label = \"{}\";\n",
+                escape::dot_string(label)
+            ));
+            for spec in visible {
+                output.push_str(&spec.render("    "));
             }
+            output.push_str("    }\n");
+            clustered.extend(members);
         }
 
-        let node_set: HashSet<NodeIndex> = nodes.iter().copied().collect();
-        let edges = self.collect_edges(&node_set, include_namespace_packages);
+        for idx in &nodes {
+            let module = &self.graph[*idx];
+            if !clustered.contains(module)
+                && let Some(spec) = specs.get(&module.to_dotted())
+            {
+                output.push_str(&spec.render(""));
+            }
+        }
 
-        for (from_name, to_name) in edges {
-            output.push_str(&format!(
-                "    \"{}\" -> \"{}\";\n",
-                from_name.to_dotted(),
-                to_name.to_dotted()
-            ));
+        for (from_name, to_name) in self.collect_edges(&node_set, include_namespace_packages) {
+            let kind = self.edge_kind(&from_name, &to_name);
+            let label = self.edge_label(&from_name, &to_name, options);
+            let style = self.edge_style(Renderer::Dot, (&from_name, &to_name), kind, options);
+            output.push_str(&render_dot_edge(&from_name, &to_name, kind, label, &style));
         }
 
         output.push_str("}\n");
@@ -837,6 +1992,9 @@ impl<T: GraphId> DependencyGraph<T> {
         &self,
         module: &T,
         include_namespace_packages: bool,
+        highlighted: bool,
+        ids: &mut escape::IdentifierAssigner,
+        options: &RenderOptions,
     ) -> Option<MermaidNodeSpec> {
         if self.is_namespace_package(module) && !include_namespace_packages {
             return None;
@@ -846,240 +2004,194 @@ impl<T: GraphId> DependencyGraph<T> {
             MermaidShape::Script
         } else if self.is_namespace_package(module) {
             MermaidShape::Namespace
+        } else if self.is_extension(module) {
+            MermaidShape::Extension
         } else {
             MermaidShape::Module
         };
 
+        let fill = if self.is_namespace_package(module) {
+            None
+        } else {
+            self.import_heat_color(module)
+        };
+
         let label = module.to_dotted();
         Some(MermaidNodeSpec {
-            id: sanitize_mermaid_id(&label),
+            id: ids.assign(&label),
             label,
             shape,
+            fill,
+            style: self.node_style(Renderer::Mermaid, module, highlighted, options),
         })
     }
 
+    /// Node specs keyed by dotted name. Ids are assigned in name order so that
+    /// disambiguation suffixes don't depend on node insertion order.
     fn mermaid_spec_map(
         &self,
         nodes: &[NodeIndex],
         include_namespace_packages: bool,
+        highlight_set: Option<&HashSet<T>>,
+        ids: &mut escape::IdentifierAssigner,
+        options: &RenderOptions,
     ) -> HashMap<String, MermaidNodeSpec> {
-        nodes
-            .iter()
-            .filter_map(|idx| {
-                let module = &self.graph[*idx];
-                self.mermaid_spec_for_module(module, include_namespace_packages)
-                    .map(|spec| (spec.label.clone(), spec))
+        let mut modules: Vec<&T> = nodes.iter().map(|idx| &self.graph[*idx]).collect();
+        modules.sort_by_cached_key(|module| module.to_dotted());
+        modules
+            .into_iter()
+            .filter_map(|module| {
+                let highlighted = highlight_set.is_some_and(|set| set.contains(module));
+                self.mermaid_spec_for_module(
+                    module,
+                    include_namespace_packages,
+                    highlighted,
+                    ids,
+                    options,
+                )
+                .map(|spec| (spec.label.clone(), spec))
             })
             .collect()
     }
 
-    fn render_mermaid_edge(
+    fn render_mermaid_group(
         &self,
-        from_name: &str,
-        to_name: &str,
-        specs: &HashMap<String, MermaidNodeSpec>,
-    ) -> Option<String> {
-        let from_spec = specs.get(from_name)?;
-        let to_spec = specs.get(to_name)?;
-        Some(format!(
-            "    {} --> {}\n",
-            from_spec.render_inline(),
-            to_spec.render_inline()
-        ))
-    }
-
-    #[allow(clippy::only_used_in_recursion)]
-    fn render_mermaid_subgraph(
-        &self,
-        node: &NamespaceTree<T>,
+        hierarchy: &Hierarchy<T>,
+        index: usize,
         indent_level: usize,
-        args: &MermaidRenderArgs<'_, T>,
-        highlighted_nodes: &mut HashSet<String>,
+        args: &mut MermaidRenderArgs<'_, T>,
         output: &mut String,
     ) {
         let indent = "    ".repeat(indent_level);
-
-        if node.path.is_empty() {
-            for child in node.child_groups() {
-                self.render_mermaid_subgraph(child, indent_level, args, highlighted_nodes, output);
-            }
-            return;
+        let group = &hierarchy.groups[index];
+        let label = hierarchy.group_id(index);
+        let subgraph_id = args.ids.assign(&label);
+        args.subgraph_ids.insert(index, subgraph_id.clone());
+
+        output.push_str(&format!(
+            "{indent}subgraph {subgraph_id}[\"{}\"]\n",
+            escape::mermaid_label(&label)
+        ));
+        for module in &group.members {
+            args.render_definition(module, &indent, output);
         }
-
-        if node.grouped {
-            let subgraph_id = sanitize_mermaid_id(&node.path.join("."));
-            let label = node.path.join(".");
-
-            output.push_str(&format!("{indent}subgraph {subgraph_id}[\"{label}\"]\n"));
-
-            for module in node.direct_concrete_children() {
-                if let Some(spec) = args.specs.get(&module.to_dotted()) {
-                    let is_highlighted = args
-                        .highlight_set
-                        .map(|set| set.contains(&module))
-                        .unwrap_or(false);
-                    if is_highlighted {
-                        highlighted_nodes.insert(spec.id.clone());
-                    }
-                    output.push_str(&spec.render_definition(&indent, is_highlighted));
-                }
-            }
-
-            for child in node.child_groups() {
-                self.render_mermaid_subgraph(
-                    child,
-                    indent_level + 1,
-                    args,
-                    highlighted_nodes,
-                    output,
-                );
-            }
-
-            output.push_str(&format!("{indent}end\n"));
-        } else {
-            for child in node.child_groups() {
-                self.render_mermaid_subgraph(child, indent_level, args, highlighted_nodes, output);
-            }
+        for &child in &group.children {
+            self.render_mermaid_group(hierarchy, child, indent_level + 1, args, output);
         }
+        output.push_str(&format!("{indent}end\n"));
     }
 
-    pub fn to_mermaid(&self, include_orphans: bool, include_namespace_packages: bool) -> String {
+    /// Mermaid output of the nodes `selection` picks, with the same package subgraphs as
+    /// [`Self::render_dot`]. Edges of modules folded into their cluster go to the subgraph.
+    fn render_mermaid(
+        &self,
+        selection: NodeSelection<T>,
+        highlight_set: Option<&HashSet<T>>,
+        include_orphans: bool,
+        include_namespace_packages: bool,
+        options: &RenderOptions,
+    ) -> String {
         let mut output = String::from("flowchart TD\n");
-        let nodes = self.select_visible_nodes(
-            NodeSelection::Full,
-            include_orphans,
+        let nodes =
+            self.select_visible_nodes(selection, include_orphans, include_namespace_packages);
+        let hierarchy = self.namespace_hierarchy(&nodes, true, options.namespace_grouping);
+        let mut ids = escape::IdentifierAssigner::new();
+        let specs = self.mermaid_spec_map(
+            &nodes,
             include_namespace_packages,
+            highlight_set,
+            &mut ids,
+            options,
         );
-        let forest = self.build_namespace_forest(&nodes);
-        let specs = self.mermaid_spec_map(&nodes, include_namespace_packages);
-        let mut highlighted_nodes = HashSet::new();
-        let args = MermaidRenderArgs {
-            highlight_set: None,
+        let mut args = MermaidRenderArgs {
+            highlight_set,
             specs: &specs,
+            ids,
+            subgraph_ids: HashMap::new(),
         };
 
-        self.render_mermaid_subgraph(
-            &forest.internal,
-            1,
-            &args,
-            &mut highlighted_nodes,
-            &mut output,
-        );
-        self.render_mermaid_subgraph(
-            &forest.scripts,
-            1,
-            &args,
-            &mut highlighted_nodes,
-            &mut output,
-        );
-
-        let mut ungrouped = Vec::new();
-        self.collect_ungrouped_modules(&forest.internal, &mut ungrouped);
-        self.collect_ungrouped_modules(&forest.scripts, &mut ungrouped);
-
-        ungrouped.sort_by_key(GraphId::to_dotted);
-
-        for module in &ungrouped {
-            if let Some(spec) = specs.get(&module.to_dotted()) {
-                output.push_str(&spec.render_definition("", false));
+        for is_script in [false, true] {
+            for index in hierarchy.top_groups(is_script) {
+                self.render_mermaid_group(&hierarchy, index, 1, &mut args, &mut output);
             }
         }
 
+        let mut ungrouped: Vec<&T> = hierarchy.ungrouped.iter().flatten().collect();
+        ungrouped.sort_by_key(|module| module.to_dotted());
+        for module in ungrouped {
+            args.render_definition(module, "", &mut output);
+        }
+
         let node_set: HashSet<NodeIndex> = nodes.iter().copied().collect();
         let edges = self.collect_edges(&node_set, include_namespace_packages);
-
-        for (from_name, to_name) in edges {
-            if let Some(line) =
-                self.render_mermaid_edge(&from_name.to_dotted(), &to_name.to_dotted(), &specs)
-            {
-                output.push_str(&line);
+        let endpoint = |module: &T| match hierarchy.folded.get(&module.to_dotted()) {
+            Some(folded) => args.subgraph_ids.get(&folded.group).cloned(),
+            None => specs
+                .get(&module.to_dotted())
+                .map(MermaidNodeSpec::render_inline),
+        };
+        // `linkStyle` refers to edges by their position in the output
+        let mut link_styles = String::new();
+        let drawn = edges.iter().filter_map(|(from_name, to_name)| {
+            Some((from_name, to_name, endpoint(from_name)?, endpoint(to_name)?))
+        });
+        for (position, (from_name, to_name, from, to)) in drawn.enumerate() {
+            let kind = self.edge_kind(from_name, to_name);
+            output.push_str(&render_mermaid_edge(
+                &from,
+                &to,
+                kind,
+                self.edge_label(from_name, to_name, options),
+                &self.mermaid_label_parts(from_name, to_name, options),
+            ));
+            let style = self.edge_style(Renderer::Mermaid, (from_name, to_name), kind, options);
+            if !style.is_empty() {
+                let style: Vec<String> = style
+                    .iter()
+                    .map(|(key, value)| format!("{key}:{value}"))
+                    .collect();
+                link_styles.push_str(&format!("    linkStyle {position} {}\n", style.join(",")));
             }
         }
+        output.push_str(&link_styles);
 
+        if highlight_set.is_some() {
+            output.push_str(
+                "    classDef highlighted fill:#bbdefb,stroke:#1976d2,stroke-width:2px\n",
+            );
+        }
         output
     }
 
+    pub fn to_mermaid(
+        &self,
+        include_orphans: bool,
+        include_namespace_packages: bool,
+        options: &RenderOptions,
+    ) -> String {
+        self.render_mermaid(
+            NodeSelection::Full,
+            None,
+            include_orphans,
+            include_namespace_packages,
+            options,
+        )
+    }
+
     pub fn to_mermaid_highlighted(
         &self,
         highlight_set: &HashSet<T>,
         include_orphans: bool,
         include_namespace_packages: bool,
+        options: &RenderOptions,
     ) -> String {
-        let mut output = String::from("flowchart TD\n");
-        let nodes = self.select_visible_nodes(
+        self.render_mermaid(
             NodeSelection::Highlighted,
+            Some(highlight_set),
             include_orphans,
             include_namespace_packages,
-        );
-        let specs = self.mermaid_spec_map(&nodes, include_namespace_packages);
-        let node_set: HashSet<NodeIndex> = nodes.iter().copied().collect();
-        let edges = self.collect_edges(&node_set, include_namespace_packages);
-        let forest = self.build_namespace_forest(&nodes);
-        let mut highlighted_nodes: HashSet<String> = HashSet::new();
-        let args = MermaidRenderArgs {
-            highlight_set: Some(highlight_set),
-            specs: &specs,
-        };
-
-        self.render_mermaid_subgraph(
-            &forest.internal,
-            1,
-            &args,
-            &mut highlighted_nodes,
-            &mut output,
-        );
-        self.render_mermaid_subgraph(
-            &forest.scripts,
-            1,
-            &args,
-            &mut highlighted_nodes,
-            &mut output,
-        );
-
-        let mut ungrouped = Vec::new();
-        self.collect_ungrouped_modules(&forest.internal, &mut ungrouped);
-        self.collect_ungrouped_modules(&forest.scripts, &mut ungrouped);
-        ungrouped.sort_by_key(GraphId::to_dotted);
-
-        for module in &ungrouped {
-            let is_highlighted = highlight_set.contains(module);
-            if let Some(spec) = specs.get(&module.to_dotted()) {
-                if is_highlighted {
-                    highlighted_nodes.insert(spec.id.clone());
-                }
-                output.push_str(&spec.render_definition("", is_highlighted));
-            }
-        }
-
-        let highlighted_names: HashSet<String> =
-            highlight_set.iter().map(GraphId::to_dotted).collect();
-
-        for (from_name, to_name) in edges {
-            if let Some(line) =
-                self.render_mermaid_edge(&from_name.to_dotted(), &to_name.to_dotted(), &specs)
-            {
-                output.push_str(&line);
-            }
-
-            if highlighted_names.contains(&from_name.to_dotted()) {
-                if let Some(spec) = specs.get(&from_name.to_dotted()) {
-                    if highlighted_nodes.insert(spec.id.clone()) {
-                        output.push_str(&format!("    class {} highlighted\n", spec.id));
-                    }
-                }
-            }
-            if highlighted_names.contains(&to_name.to_dotted()) {
-                if let Some(spec) = specs.get(&to_name.to_dotted()) {
-                    if highlighted_nodes.insert(spec.id.clone()) {
-                        output.push_str(&format!("    class {} highlighted\n", spec.id));
-                    }
-                }
-            }
-        }
-
-        output.push_str("    classDef highlighted fill:#bbdefb,stroke:#1976d2,stroke-width:2px\n");
-
-        output
+            options,
+        )
     }
 
     pub fn to_dot_filtered(
@@ -1087,207 +2199,535 @@ impl<T: GraphId> DependencyGraph<T> {
         filter: &HashSet<T>,
         include_orphans: bool,
         include_namespace_packages: bool,
+        options: &RenderOptions,
     ) -> String {
-        let mut output = String::from("digraph dependencies {\n");
-        output.push_str("    rankdir=LR;\n");
-        output.push_str(
-            "    // Note: Scripts (files outside source root) are shown with box shape\n",
-        );
-        let nodes = self.select_visible_nodes(
-            NodeSelection::Filtered(filter),
+        self.to_dot_filtered_clustered(
+            filter,
             include_orphans,
             include_namespace_packages,
-        );
-        let forest = self.build_namespace_forest(&nodes);
-        let specs = self.dot_spec_map(&nodes, include_namespace_packages, None);
+            DotClustering::PACKAGES,
+            options,
+        )
+    }
 
-        self.render_dot_subgraph_generic(
-            &forest.internal,
-            &forest,
+    /// [`Self::to_dot_filtered`] with explicit clustering
+    pub fn to_dot_filtered_clustered(
+        &self,
+        filter: &HashSet<T>,
+        include_orphans: bool,
+        include_namespace_packages: bool,
+        clustering: DotClustering,
+        options: &RenderOptions,
+    ) -> String {
+        self.render_dot(
+            NodeSelection::Filtered(filter),
             None,
+            include_orphans,
             include_namespace_packages,
-            &specs,
-            false,
-            1,
-            false,
-            &mut output,
-        );
+            clustering,
+            options,
+        )
+    }
 
-        self.render_dot_subgraph_generic(
-            &forest.scripts,
-            &forest,
+    pub fn to_mermaid_filtered(
+        &self,
+        filter: &HashSet<T>,
+        include_orphans: bool,
+        include_namespace_packages: bool,
+        options: &RenderOptions,
+    ) -> String {
+        self.render_mermaid(
+            NodeSelection::Filtered(filter),
             None,
+            include_orphans,
             include_namespace_packages,
-            &specs,
-            false,
-            1,
-            true,
-            &mut output,
-        );
+            options,
+        )
+    }
+
+    /// Distance from the nearest root to every module related to `roots` within
+    /// `max_rank`, roots included at 0
+    pub fn find_related(
+        &self,
+        roots: &[T],
+        relation: Relation,
+        max_rank: Option<usize>,
+    ) -> HashMap<T, usize> {
+        self.collect_reachable(roots, relation, max_rank)
+            .into_iter()
+            .map(|(module, reached)| (module, reached.distance))
+            .collect()
+    }
 
-        let mut ungrouped: Vec<T> = Vec::new();
-        self.collect_ungrouped_modules(&forest.internal, &mut ungrouped);
-        self.collect_ungrouped_modules(&forest.scripts, &mut ungrouped);
+    /// [`Self::find_related`] that also records each node's predecessor, so one shortest
+    /// chain per node can be rebuilt with [`chain_to`] without searching again
+    pub fn find_related_with_parents(
+        &self,
+        roots: &[T],
+        relation: Relation,
+        max_rank: Option<usize>,
+    ) -> HashMap<T, Reached<T>> {
+        self.collect_reachable(roots, relation, max_rank)
+    }
+
+    /// [`Self::find_related_with_parents`] with `cost(importer, imported)` as the length of
+    /// each import instead of 1 (e.g. its [`Self::edge_multiplicity`] or an annotation of
+    /// the imported module), so `max_rank` bounds the weighted distance
+    pub fn find_related_weighted(
+        &self,
+        roots: &[T],
+        relation: Relation,
+        max_rank: Option<usize>,
+        cost: impl Fn(&T, &T) -> usize,
+    ) -> HashMap<T, Reached<T>> {
+        self.collect_reachable_weighted(roots, relation, max_rank, cost)
+    }
 
-        ungrouped.sort_by_key(GraphId::to_dotted);
+    pub fn find_downstream(&self, roots: &[T], max_rank: Option<usize>) -> HashMap<T, usize> {
+        self.find_related(roots, Relation::Dependents, max_rank)
+    }
 
-        for module in &ungrouped {
-            if !self.is_group_only_namespace(&forest, module) {
-                if let Some(spec) = specs.get(&module.to_dotted()) {
-                    output.push_str(&spec.render(""));
+    pub fn find_upstream(&self, roots: &[T], max_rank: Option<usize>) -> HashMap<T, usize> {
+        self.find_related(roots, Relation::Dependencies, max_rank)
+    }
+
+    /// [`Self::find_related_with_parents`] over dependents
+    pub fn find_downstream_with_parents(
+        &self,
+        roots: &[T],
+        max_rank: Option<usize>,
+    ) -> HashMap<T, Reached<T>> {
+        self.collect_reachable(roots, Relation::Dependents, max_rank)
+    }
+
+    /// [`Self::find_related_with_parents`] over dependencies
+    pub fn find_upstream_with_parents(
+        &self,
+        roots: &[T],
+        max_rank: Option<usize>,
+    ) -> HashMap<T, Reached<T>> {
+        self.collect_reachable(roots, Relation::Dependencies, max_rank)
+    }
+
+    /// [`Self::find_related_weighted`] over dependents
+    pub fn find_downstream_weighted(
+        &self,
+        roots: &[T],
+        max_rank: Option<usize>,
+        cost: impl Fn(&T, &T) -> usize,
+    ) -> HashMap<T, Reached<T>> {
+        self.collect_reachable_weighted(roots, Relation::Dependents, max_rank, cost)
+    }
+
+    /// [`Self::find_related_weighted`] over dependencies
+    pub fn find_upstream_weighted(
+        &self,
+        roots: &[T],
+        max_rank: Option<usize>,
+        cost: impl Fn(&T, &T) -> usize,
+    ) -> HashMap<T, Reached<T>> {
+        self.collect_reachable_weighted(roots, Relation::Dependencies, max_rank, cost)
+    }
+
+    /// [`Self::collect_reachable`] for imports of any non-negative length: Dijkstra's
+    /// algorithm seeded with all roots at 0. Each import is measured the same whichever
+    /// way it is walked.
+    fn collect_reachable_weighted(
+        &self,
+        roots: &[T],
+        relation: Relation,
+        max_rank: Option<usize>,
+        cost: impl Fn(&T, &T) -> usize,
+    ) -> HashMap<T, Reached<T>> {
+        use std::cmp::Reverse;
+
+        let direction = relation.direction();
+        use std::collections::BinaryHeap;
+
+        let mut best: Vec<Option<usize>> = vec![None; self.graph.node_count()];
+        let mut parents: Vec<Option<NodeIndex>> = vec![None; self.graph.node_count()];
+        let mut queue = BinaryHeap::new();
+        for root in roots {
+            if let Some(&idx) = self.node_indices.get(root)
+                && best[idx.index()].is_none()
+            {
+                best[idx.index()] = Some(0);
+                queue.push(Reverse((0, idx)));
+            }
+        }
+
+        let mut reached = HashMap::new();
+        while let Some(Reverse((distance, idx))) = queue.pop() {
+            if reached.contains_key(&self.graph[idx]) {
+                continue;
+            }
+            reached.insert(
+                self.graph[idx].clone(),
+                Reached {
+                    distance,
+                    parent: parents[idx.index()].map(|parent| self.graph[parent].clone()),
+                },
+            );
+            for neighbor in self.graph.neighbors_directed(idx, direction) {
+                let length = match direction {
+                    Direction::Outgoing => cost(&self.graph[idx], &self.graph[neighbor]),
+                    Direction::Incoming => cost(&self.graph[neighbor], &self.graph[idx]),
+                };
+                let candidate = distance.saturating_add(length);
+                if max_rank.is_some_and(|limit| candidate > limit) {
+                    continue;
+                }
+                if best[neighbor.index()].is_none_or(|known| candidate < known) {
+                    best[neighbor.index()] = Some(candidate);
+                    parents[neighbor.index()] = Some(idx);
+                    queue.push(Reverse((candidate, neighbor)));
                 }
             }
         }
+        reached
+    }
 
-        let node_set: HashSet<NodeIndex> = nodes.iter().copied().collect();
-        let edges = self.collect_edges(&node_set, include_namespace_packages);
+    /// Distance from the nearest root to every node reachable from `roots`, and the
+    /// node it was reached from.
+    ///
+    /// A single breadth-first search seeded with all roots at distance 0, so the cost
+    /// is one traversal of the reachable subgraph however many roots there are (e.g.
+    /// every file changed in a large PR). Nodes are settled the first time they are
+    /// reached, which is at their minimal distance.
+    fn collect_reachable(
+        &self,
+        roots: &[T],
+        relation: Relation,
+        max_rank: Option<usize>,
+    ) -> HashMap<T, Reached<T>> {
+        let direction = relation.direction();
+        let mut distances: Vec<Option<usize>> = vec![None; self.graph.node_count()];
+        let mut parents: Vec<Option<NodeIndex>> = vec![None; self.graph.node_count()];
+        let mut frontier: Vec<NodeIndex> = Vec::new();
+        for root in roots {
+            if let Some(&idx) = self.node_indices.get(root)
+                && distances[idx.index()].is_none()
+            {
+                distances[idx.index()] = Some(0);
+                frontier.push(idx);
+            }
+        }
 
-        for (from_name, to_name) in edges {
-            output.push_str(&format!(
-                "    \"{}\" -> \"{}\";\n",
-                from_name.to_dotted(),
-                to_name.to_dotted()
-            ));
+        let mut reached = frontier.clone();
+        let mut dist = 0;
+        while !frontier.is_empty() && max_rank.is_none_or(|limit| dist < limit) {
+            dist += 1;
+            let mut next = Vec::new();
+            for idx in frontier {
+                for neighbor in self.graph.neighbors_directed(idx, direction) {
+                    let slot = &mut distances[neighbor.index()];
+                    if slot.is_none() {
+                        *slot = Some(dist);
+                        parents[neighbor.index()] = Some(idx);
+                        next.push(neighbor);
+                    }
+                }
+            }
+            reached.extend_from_slice(&next);
+            frontier = next;
         }
 
-        output.push_str("}\n");
-        output
+        reached
+            .into_iter()
+            .filter_map(|idx| {
+                let reached = Reached {
+                    distance: distances[idx.index()]?,
+                    parent: parents[idx.index()].map(|parent| self.graph[parent].clone()),
+                };
+                Some((self.graph[idx].clone(), reached))
+            })
+            .collect()
     }
 
-    pub fn to_mermaid_filtered(
-        &self,
-        filter: &HashSet<T>,
-        include_orphans: bool,
-        include_namespace_packages: bool,
-    ) -> String {
-        let mut output = String::from("flowchart TD\n");
-        let nodes = self.select_visible_nodes(
-            NodeSelection::Filtered(filter),
-            include_orphans,
-            include_namespace_packages,
-        );
-        let forest = self.build_namespace_forest(&nodes);
-        let specs = self.mermaid_spec_map(&nodes, include_namespace_packages);
-        let node_set: HashSet<NodeIndex> = nodes.iter().copied().collect();
-        let edges = self.collect_edges(&node_set, include_namespace_packages);
-        let mut highlighted_nodes = HashSet::new();
-        let args = MermaidRenderArgs {
-            highlight_set: None,
-            specs: &specs,
-        };
+    /// All node identifiers, in insertion order
+    pub fn nodes(&self) -> impl Iterator<Item = &T> {
+        self.graph.node_weights()
+    }
+
+    /// Every distinct import as `(importer, imported, kind)`, in insertion order; parallel
+    /// edges are reported once with their combined [`Self::edge_kind`]
+    pub fn edges(&self) -> impl Iterator<Item = (&T, &T, EdgeKind)> {
+        let mut seen = HashSet::new();
+        self.graph.raw_edges().iter().filter_map(move |edge| {
+            if !seen.insert((edge.source(), edge.target())) {
+                return None;
+            }
+            let from = &self.graph[edge.source()];
+            let to = &self.graph[edge.target()];
+            Some((from, to, self.edge_kind(from, to)))
+        })
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
 
-        self.render_mermaid_subgraph(
-            &forest.internal,
-            1,
-            &args,
-            &mut highlighted_nodes,
-            &mut output,
-        );
-        self.render_mermaid_subgraph(
-            &forest.scripts,
-            1,
-            &args,
-            &mut highlighted_nodes,
-            &mut output,
-        );
+    /// Number of distinct imports, as yielded by [`Self::edges`]
+    pub fn edge_count(&self) -> usize {
+        self.edges().count()
+    }
 
-        let nodes_in_edges: HashSet<String> = edges
-            .iter()
-            .flat_map(|(from, to)| vec![from.to_dotted(), to.to_dotted()])
-            .collect();
+    /// Modules `module` imports directly, sorted by name (empty if it isn't in the graph)
+    pub fn direct_dependencies(&self, module: &T) -> Vec<&T> {
+        self.direct_neighbors(module, Relation::Dependencies)
+    }
 
-        for idx in &nodes {
-            let module = &self.graph[*idx];
-            let module_name = module.to_dotted();
+    /// Modules importing `module` directly, sorted by name (empty if it isn't in the graph)
+    pub fn direct_dependents(&self, module: &T) -> Vec<&T> {
+        self.direct_neighbors(module, Relation::Dependents)
+    }
+
+    fn direct_neighbors(&self, module: &T, relation: Relation) -> Vec<&T> {
+        let Some(&idx) = self.node_indices.get(module) else {
+            return Vec::new();
+        };
+        let mut neighbors: Vec<NodeIndex> = self
+            .graph
+            .neighbors_directed(idx, relation.direction())
+            .collect();
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        let mut neighbors: Vec<&T> = neighbors.into_iter().map(|n| &self.graph[n]).collect();
+        neighbors.sort_by_cached_key(|n| n.to_dotted());
+        neighbors
+    }
 
-            if !nodes_in_edges.contains(&module_name) {
-                if let Some(spec) = specs.get(&module_name) {
-                    output.push_str(&spec.render_definition("", false));
+    /// Shortest dependency chain from `from` to any node accepted by `is_target`,
+    /// following imports (outgoing edges). The chain starts with `from` and ends with
+    /// the target; ties are broken by the dotted names of the nodes along the way.
+    pub fn shortest_chain(&self, from: &T, is_target: impl Fn(&T) -> bool) -> Option<Vec<T>> {
+        let &start = self.node_indices.get(from)?;
+        let mut previous: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+        let mut visited: HashSet<NodeIndex> = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(idx) = queue.pop_front() {
+            if idx != start && is_target(&self.graph[idx]) {
+                let mut chain = vec![self.graph[idx].clone()];
+                let mut current = idx;
+                while let Some(&prev) = previous.get(&current) {
+                    chain.push(self.graph[prev].clone());
+                    current = prev;
                 }
+                chain.reverse();
+                return Some(chain);
             }
-        }
 
-        for (from_name, to_name) in edges {
-            if let Some(line) =
-                self.render_mermaid_edge(&from_name.to_dotted(), &to_name.to_dotted(), &specs)
-            {
-                output.push_str(&line);
+            let mut neighbors: Vec<NodeIndex> = self
+                .graph
+                .neighbors_directed(idx, Direction::Outgoing)
+                .filter(|n| !visited.contains(n))
+                .collect();
+            neighbors.sort_by_key(|n| self.graph[*n].to_dotted());
+            neighbors.dedup();
+            for neighbor in neighbors {
+                visited.insert(neighbor);
+                previous.insert(neighbor, idx);
+                queue.push_back(neighbor);
             }
         }
 
-        output
-    }
-
-    pub fn find_downstream(&self, roots: &[T], max_rank: Option<usize>) -> HashMap<T, usize> {
-        self.collect_reachable(roots, Direction::Incoming, max_rank)
+        None
     }
 
-    pub fn find_upstream(&self, roots: &[T], max_rank: Option<usize>) -> HashMap<T, usize> {
-        self.collect_reachable(roots, Direction::Outgoing, max_rank)
+    /// Import depth of every module: the length in edges of the longest dependency chain
+    /// starting at it, counting each import cycle as one step (see
+    /// [`GraphMetrics::max_depth`])
+    pub fn import_depths(&self) -> HashMap<T, usize> {
+        let depths = ComponentDepths::new(&self.graph);
+        self.graph
+            .node_indices()
+            .map(|idx| {
+                (
+                    self.graph[idx].clone(),
+                    depths.depth[depths.component_of[&idx]],
+                )
+            })
+            .collect()
     }
 
-    fn collect_reachable(
-        &self,
-        roots: &[T],
-        direction: Direction,
-        max_rank: Option<usize>,
-    ) -> HashMap<T, usize> {
-        let mut result = HashMap::new();
-        let mut queue = VecDeque::new();
-        let mut visited: HashMap<NodeIndex, usize> = HashMap::new();
-
-        for root in roots {
-            if let Some(&idx) = self.node_indices.get(root) {
-                result.insert(root.clone(), 0);
-                queue.push_back((idx, 0usize));
-                visited.insert(idx, 0);
+    /// Longest dependency chain starting at `from`, following imports: the chain behind
+    /// its import depth, measured like [`GraphMetrics::max_depth`] with each import cycle
+    /// counted as one step. A cycle on the way is walked from where the chain enters it
+    /// to the member importing the next step, so every link is an actual import. Ties
+    /// are broken by the dotted names of the nodes.
+    pub fn longest_chain(&self, from: &T) -> Option<Vec<T>> {
+        let &start = self.node_indices.get(from)?;
+        let ComponentDepths {
+            components,
+            component_of,
+            depth,
+        } = ComponentDepths::new(&self.graph);
+
+        let mut chain = vec![self.graph[start].clone()];
+        let mut current = start;
+        loop {
+            let component = component_of[&current];
+            if depth[component] == 0 {
+                break;
+            }
+            // Leave the component along an import one step less deep, from the current
+            // node when possible
+            let Some((exit, next)) = components[component]
+                .iter()
+                .flat_map(|&member| self.graph.neighbors(member).map(move |n| (member, n)))
+                .filter(|(_, n)| {
+                    let other = component_of[n];
+                    other != component && depth[other] + 1 == depth[component]
+                })
+                .min_by_key(|&(member, n)| {
+                    (
+                        member != current,
+                        self.graph[member].to_dotted(),
+                        self.graph[n].to_dotted(),
+                    )
+                })
+            else {
+                break;
+            };
+            if exit != current {
+                // Paths between members of a cycle never leave it
+                let inside =
+                    self.shortest_chain(&self.graph[current], |n| n == &self.graph[exit])?;
+                chain.extend(inside.into_iter().skip(1));
             }
+            chain.push(self.graph[next].clone());
+            current = next;
         }
+        Some(chain)
+    }
 
-        while let Some((idx, dist)) = queue.pop_front() {
-            let next_dist = dist + 1;
-            if max_rank.map(|limit| next_dist > limit).unwrap_or(false) {
-                continue;
-            }
+    /// Undirected weighted edge list over `nodes` (indices into the slice): each distinct
+    /// import between two nodes adds weight 1, so mutual imports weigh 2
+    fn undirected_edges(&self, nodes: &[T]) -> Vec<(usize, usize, f64)> {
+        let positions: HashMap<&T, usize> = nodes.iter().enumerate().map(|(i, n)| (n, i)).collect();
+        let mut pairs: Vec<(usize, usize)> = self
+            .graph
+            .raw_edges()
+            .iter()
+            .filter_map(|edge| {
+                let from = positions.get(&self.graph[edge.source()])?;
+                let to = positions.get(&self.graph[edge.target()])?;
+                (from != to).then_some((*from, *to))
+            })
+            .collect();
+        pairs.sort_unstable();
+        pairs.dedup();
+        pairs
+            .into_iter()
+            .map(|(from, to)| (from, to, 1.0))
+            .collect()
+    }
 
-            for neighbor in self.graph.neighbors_directed(idx, direction) {
-                let should_visit = match visited.get(&neighbor) {
-                    Some(&existing) => next_dist < existing,
-                    None => true,
-                };
+    /// Group nodes into communities of densely connected modules (Louvain method,
+    /// treating imports as undirected edges). When `members` is given only those nodes
+    /// and the edges between them are considered. Each community is sorted and
+    /// communities are ordered by their first member.
+    pub fn detect_communities(&self, members: Option<&HashSet<T>>) -> Vec<Vec<T>> {
+        let mut nodes: Vec<T> = self
+            .graph
+            .node_weights()
+            .filter(|n| members.map(|set| set.contains(*n)).unwrap_or(true))
+            .cloned()
+            .collect();
+        nodes.sort_by_key(GraphId::to_dotted);
 
-                if !should_visit {
-                    continue;
-                }
+        let edges = self.undirected_edges(&nodes);
+        let assignment = community::louvain(nodes.len(), &edges);
 
-                visited.insert(neighbor, next_dist);
+        let mut communities: Vec<Vec<T>> =
+            vec![Vec::new(); assignment.iter().max().map(|m| m + 1).unwrap_or(0)];
+        for (node, community) in nodes.into_iter().zip(assignment) {
+            communities[community].push(node);
+        }
+        communities
+    }
 
-                if let Some(node) = self.graph.node_weight(neighbor) {
-                    let entry = result.entry(node.clone()).or_insert(next_dist);
-                    if next_dist < *entry {
-                        *entry = next_dist;
-                    }
-                }
+    /// Modularity of a partition of (a subset of) the graph, computed over the nodes it
+    /// contains and the edges between them
+    pub fn modularity(&self, communities: &[Vec<T>]) -> f64 {
+        let nodes: Vec<T> = communities.iter().flatten().cloned().collect();
+        let assignment: Vec<usize> = communities
+            .iter()
+            .enumerate()
+            .flat_map(|(i, members)| std::iter::repeat_n(i, members.len()))
+            .collect();
+        let edges = self.undirected_edges(&nodes);
+        community::modularity(nodes.len(), &edges, &assignment)
+    }
 
-                queue.push_back((neighbor, next_dist));
-            }
-        }
+    /// Find import cycles: strongly connected components with more than one node,
+    /// plus nodes that depend on themselves. Members of each cycle are sorted, and
+    /// cycles are ordered by their first member.
+    pub fn find_cycles(&self) -> Vec<Vec<T>> {
+        let mut cycles: Vec<Vec<T>> = petgraph::algo::tarjan_scc(&self.graph)
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1
+                    || component
+                        .first()
+                        .is_some_and(|&idx| self.graph.contains_edge(idx, idx))
+            })
+            .map(|component| {
+                let mut members: Vec<T> = component
+                    .into_iter()
+                    .map(|idx| self.graph[idx].clone())
+                    .collect();
+                members.sort_by_key(GraphId::to_dotted);
+                members
+            })
+            .collect();
+        cycles.sort_by_key(|cycle| cycle.first().map(GraphId::to_dotted));
+        cycles
+    }
 
-        result
+    /// Nodes with no dependencies and no dependents, sorted by name
+    pub fn orphans(&self) -> Vec<T> {
+        let mut orphans: Vec<T> = self
+            .orphans_in(OrphanScope::Graph, true)
+            .into_iter()
+            .collect();
+        orphans.sort_by_key(GraphId::to_dotted);
+        orphans
     }
 
-    fn is_orphan(&self, idx: NodeIndex) -> bool {
-        let has_incoming = self
-            .graph
-            .neighbors_directed(idx, Direction::Incoming)
-            .count()
-            > 0;
-        let has_outgoing = self
+    /// Nodes of `scope` without any edge as it is rendered: with namespace packages
+    /// hidden, a module whose imports only reach a namespace package that leads nowhere is
+    /// an orphan, and one bridged through it to a module is not. Hidden namespace packages
+    /// are never reported. Every renderer, the `is_orphan` field of graph data, and the
+    /// `include_orphans` flags go through this.
+    pub fn orphans_in(
+        &self,
+        scope: OrphanScope<'_, T>,
+        include_namespace_packages: bool,
+    ) -> HashSet<T> {
+        let node_set: HashSet<NodeIndex> = self
             .graph
-            .neighbors_directed(idx, Direction::Outgoing)
-            .count()
-            > 0;
-        !has_incoming && !has_outgoing
+            .node_indices()
+            .filter(|idx| match scope {
+                OrphanScope::Graph => true,
+                OrphanScope::Subgraph(set) => set.contains(&self.graph[*idx]),
+            })
+            .filter(|idx| {
+                include_namespace_packages || !self.is_namespace_package(&self.graph[*idx])
+            })
+            .collect();
+        let connected: HashSet<T> = self
+            .collect_edges(&node_set, include_namespace_packages)
+            .into_iter()
+            .flat_map(|(from, to)| [from, to])
+            .collect();
+        node_set
+            .into_iter()
+            .map(|idx| &self.graph[idx])
+            .filter(|module| !connected.contains(*module))
+            .cloned()
+            .collect()
     }
 
     pub fn to_list_filtered(
@@ -1304,15 +2744,135 @@ impl<T: GraphId> DependencyGraph<T> {
         sorted_modules.join("\n")
     }
 
+    /// Graph health metrics of what would be drawn: node, edge, and cycle counts, the
+    /// longest dependency chain, and per-package fan-in. `filter` restricts them to a
+    /// selection.
+    pub fn metrics(
+        &self,
+        filter: Option<&HashSet<T>>,
+        include_orphans: bool,
+        include_namespace_packages: bool,
+    ) -> GraphMetrics {
+        let selection = filter.map_or(NodeSelection::Full, NodeSelection::Filtered);
+        let nodes =
+            self.select_visible_nodes(selection, include_orphans, include_namespace_packages);
+        let node_set: HashSet<NodeIndex> = nodes.iter().copied().collect();
+        let edges = self.collect_edges(&node_set, include_namespace_packages);
+
+        let mut visible: DiGraph<T, ()> = DiGraph::new();
+        let mut indices = HashMap::new();
+        for idx in &nodes {
+            let module = self.graph[*idx].clone();
+            indices.insert(module.clone(), visible.add_node(module));
+        }
+        for (from, to) in &edges {
+            visible.add_edge(indices[from], indices[to], ());
+        }
+
+        let depths = ComponentDepths::new(&visible);
+        let cycles = depths
+            .components
+            .iter()
+            .filter(|c| c.len() > 1 || visible.contains_edge(c[0], c[0]))
+            .count();
+        let max_depth = depths.depth.iter().copied().max().unwrap_or(0);
+
+        // Every proper prefix of a node is a package containing it
+        let package_of = |module: &T| -> Vec<String> {
+            let segments = module.segments();
+            (1..segments.len())
+                .map(|len| T::join_segments(&segments[..len]))
+                .chain(std::iter::once(module.to_dotted()))
+                .collect()
+        };
+        let mut packages: HashSet<String> = HashSet::new();
+        for module in indices.keys() {
+            let segments = module.segments();
+            packages.extend((1..segments.len()).map(|len| T::join_segments(&segments[..len])));
+        }
+        let mut importers: HashMap<String, HashSet<String>> = packages
+            .iter()
+            .map(|package| (package.clone(), HashSet::new()))
+            .collect();
+        for (from, to) in &edges {
+            let from_packages: HashSet<String> = package_of(from).into_iter().collect();
+            for package in package_of(to) {
+                if let Some(set) = importers.get_mut(&package)
+                    && !from_packages.contains(&package)
+                {
+                    set.insert(from.to_dotted());
+                }
+            }
+        }
+        let mut fan_in: Vec<(String, usize)> = importers
+            .into_iter()
+            .map(|(package, set)| (package, set.len()))
+            .collect();
+        fan_in.sort();
+
+        GraphMetrics {
+            nodes: nodes.len(),
+            edges: edges.len(),
+            cycles,
+            max_depth,
+            package_fan_in: fan_in,
+        }
+    }
+
+    /// [`DependencyGraph::metrics`] in the OpenMetrics text format
+    pub fn to_openmetrics(
+        &self,
+        filter: Option<&HashSet<T>>,
+        include_orphans: bool,
+        include_namespace_packages: bool,
+    ) -> String {
+        self.metrics(filter, include_orphans, include_namespace_packages)
+            .to_openmetrics()
+    }
+
+    /// Graph data of the whole graph as `config` describes it: orphans and namespace
+    /// packages as it includes them, the `highlighted_modules` (matched by dotted name)
+    /// highlighted, and its presets carried along. Equivalent to
+    /// [`Self::to_cytoscape_graph_data`] or [`Self::to_cytoscape_graph_data_highlighted`].
+    pub fn to_graph_data(&self, config: &GraphConfig, options: &RenderOptions) -> GraphData {
+        let mut data = match &config.highlighted_modules {
+            None => self.to_cytoscape_graph_data(
+                config.include_orphans,
+                config.include_namespaces,
+                options,
+            ),
+            Some(names) => {
+                let names: HashSet<&str> = names.iter().map(String::as_str).collect();
+                let highlighted: HashSet<T> = self
+                    .nodes()
+                    .filter(|module| names.contains(module.to_dotted().as_str()))
+                    .cloned()
+                    .collect();
+                self.to_cytoscape_graph_data_highlighted(
+                    &highlighted,
+                    config.include_orphans,
+                    config.include_namespaces,
+                    options,
+                )
+            }
+        };
+        if let Some(data_config) = data.config.as_mut() {
+            data_config.presets = config.presets.clone();
+        }
+        data
+    }
+
     pub fn to_cytoscape_graph_data(
         &self,
         include_orphans: bool,
         include_namespace_packages: bool,
+        options: &RenderOptions,
     ) -> GraphData {
         self.cytoscape_graph_data_internal(
             CytoscapeMode::Full,
             include_orphans,
             include_namespace_packages,
+            options,
         )
     }
 
@@ -1321,11 +2881,13 @@ impl<T: GraphId> DependencyGraph<T> {
         filter: &HashSet<T>,
         include_orphans: bool,
         include_namespace_packages: bool,
+        options: &RenderOptions,
     ) -> GraphData {
         self.cytoscape_graph_data_internal(
             CytoscapeMode::Filtered(filter),
             include_orphans,
             include_namespace_packages,
+            options,
         )
     }
 
@@ -1334,19 +2896,182 @@ impl<T: GraphId> DependencyGraph<T> {
         highlight_set: &HashSet<T>,
         include_orphans: bool,
         include_namespace_packages: bool,
+        options: &RenderOptions,
     ) -> GraphData {
         self.cytoscape_graph_data_internal(
             CytoscapeMode::Highlighted(highlight_set),
             include_orphans,
             include_namespace_packages,
+            options,
+        )
+    }
+
+    /// Graph data where detected `communities` (see [`Self::detect_communities`]) replace
+    /// the package hierarchy as compound nodes, named `community 1`, `community 2`, ...
+    pub fn to_cytoscape_graph_data_communities(
+        &self,
+        communities: &[Vec<T>],
+        include_orphans: bool,
+        include_namespace_packages: bool,
+        options: &RenderOptions,
+    ) -> GraphData {
+        self.cytoscape_graph_data_clusters(
+            &labeled_communities(communities),
+            "community",
+            include_orphans,
+            include_namespace_packages,
+            options,
+        )
+    }
+
+    /// Graph data with one compound node (of type `group`) per named group of modules
+    pub fn to_cytoscape_graph_data_grouped(
+        &self,
+        groups: &[(String, Vec<T>)],
+        include_orphans: bool,
+        include_namespace_packages: bool,
+        options: &RenderOptions,
+    ) -> GraphData {
+        self.cytoscape_graph_data_clusters(
+            groups,
+            "group",
+            include_orphans,
+            include_namespace_packages,
+            options,
         )
     }
 
+    fn cytoscape_graph_data_clusters(
+        &self,
+        clusters: &[(String, Vec<T>)],
+        kind: &str,
+        include_orphans: bool,
+        include_namespace_packages: bool,
+        options: &RenderOptions,
+    ) -> GraphData {
+        let nodes = self.select_visible_nodes(
+            NodeSelection::Full,
+            include_orphans,
+            include_namespace_packages,
+        );
+        let membership = cluster_membership(clusters);
+        let node_set: HashSet<NodeIndex> = nodes.iter().copied().collect();
+
+        let mut graph_nodes: Vec<GraphNode> = clusters
+            .iter()
+            .filter(|(_, members)| {
+                members.iter().any(|m| {
+                    self.node_indices
+                        .get(m)
+                        .is_some_and(|idx| node_set.contains(idx))
+                })
+            })
+            .map(|(label, _)| GraphNode {
+                id: label.clone(),
+                node_type: kind.to_string(),
+                is_orphan: false,
+                highlighted: None,
+                parent: None,
+                import_time_us: None,
+                coverage: None,
+                category: None,
+                lifecycle: None,
+                generated: None,
+                change: None,
+                annotations: None,
+                position: None,
+                style: None,
+                href: None,
+            })
+            .collect();
+
+        let orphans = self.orphans_in(OrphanScope::Graph, include_namespace_packages);
+        for idx in &nodes {
+            let parent = membership
+                .get(&self.graph[*idx])
+                .map(|&index| clusters[index].0.clone());
+            graph_nodes.push(self.cytoscape_node(*idx, false, parent, &orphans, options));
+        }
+
+        GraphData {
+            version: GRAPH_DATA_VERSION,
+            nodes: graph_nodes,
+            edges: self.cytoscape_edges(&node_set, include_namespace_packages, options),
+            config: Some(GraphConfig {
+                include_orphans,
+                include_namespaces: include_namespace_packages,
+                highlighted_modules: None,
+                presets: None,
+            }),
+        }
+    }
+
+    fn cytoscape_node(
+        &self,
+        idx: NodeIndex,
+        highlighted: bool,
+        parent: Option<String>,
+        orphans: &HashSet<T>,
+        options: &RenderOptions,
+    ) -> GraphNode {
+        let module = &self.graph[idx];
+        let node_type = self.node_type(module);
+        let style = self.node_style(Renderer::Cytoscape, module, highlighted, options);
+
+        GraphNode {
+            id: module.to_dotted(),
+            node_type: node_type.to_string(),
+            is_orphan: orphans.contains(module),
+            highlighted: if highlighted { Some(true) } else { None },
+            parent,
+            import_time_us: self.import_cost(module).map(|cost| cost.self_us),
+            coverage: self.coverage(module),
+            category: self.category(module).map(String::from),
+            lifecycle: self
+                .lifecycle(module)
+                .map(|lifecycle| lifecycle.as_str().to_string()),
+            generated: self.is_generated(module).then_some(true),
+            change: None,
+            annotations: self.annotations(module).cloned(),
+            position: None,
+            style: Some(style).filter(|style| !style.is_empty()),
+            href: self.source_link(module, options),
+        }
+    }
+
+    fn cytoscape_edges(
+        &self,
+        node_set: &HashSet<NodeIndex>,
+        include_namespace_packages: bool,
+        options: &RenderOptions,
+    ) -> Vec<GraphEdge> {
+        self.collect_edges(node_set, include_namespace_packages)
+            .iter()
+            .map(|(from, to)| {
+                let kind = self.edge_kind(from, to);
+                let style = self.edge_style(Renderer::Cytoscape, (from, to), kind, options);
+                GraphEdge {
+                    source: from.to_dotted(),
+                    target: to.to_dotted(),
+                    kind: match kind {
+                        EdgeKind::ImportTime => None,
+                        kind => Some(kind.as_str().to_string()),
+                    },
+                    tags: Some(self.edge_tags(from, to).to_vec()).filter(|tags| !tags.is_empty()),
+                    count: Some(self.edge_multiplicity(from, to)).filter(|count| *count > 1),
+                    change: None,
+                    style: Some(style).filter(|style| !style.is_empty()),
+                }
+            })
+            .collect()
+    }
+
     fn cytoscape_graph_data_internal(
         &self,
         mode: CytoscapeMode<T>,
         include_orphans: bool,
         include_namespace_packages: bool,
+        options: &RenderOptions,
     ) -> GraphData {
         let filter_set = match mode {
             CytoscapeMode::Full => None,
@@ -1362,54 +3087,26 @@ impl<T: GraphId> DependencyGraph<T> {
         let nodes =
             self.select_visible_nodes(selection, include_orphans, include_namespace_packages);
 
-        let forest = self.build_namespace_forest(&nodes);
-
-        let (leaf_parent_map, parent_nodes) =
-            self.generate_compound_nodes(&forest, include_namespace_packages);
+        let hierarchy = self.namespace_hierarchy(&nodes, true, options.namespace_grouping);
+        let (leaf_parent_map, parent_nodes) = self.generate_compound_nodes(&hierarchy);
 
         let node_set: HashSet<NodeIndex> = nodes.iter().copied().collect();
         let mut graph_nodes = Vec::new();
 
         graph_nodes.extend(parent_nodes);
 
+        let orphans = self.orphans_in(OrphanScope::Graph, include_namespace_packages);
         for idx in &nodes {
             let module = &self.graph[*idx];
-            let module_name = module.to_dotted();
-            let is_script = self.is_script(module);
-            let is_namespace = self.is_namespace_package(module);
             let is_highlighted = filter_set
                 .map(|f| is_highlighting_mode && f.contains(module))
                 .unwrap_or(false);
-            let is_orphan = self.is_orphan(*idx);
+            let parent = leaf_parent_map.get(&module.to_dotted()).cloned();
 
-            let node_type = if is_script {
-                "script"
-            } else if is_namespace {
-                "namespace"
-            } else {
-                "module"
-            };
-
-            let parent = leaf_parent_map.get(&module_name).cloned();
-
-            graph_nodes.push(GraphNode {
-                id: module_name,
-                node_type: node_type.to_string(),
-                is_orphan,
-                highlighted: if is_highlighted { Some(true) } else { None },
-                parent,
-            });
+            graph_nodes.push(self.cytoscape_node(*idx, is_highlighted, parent, &orphans, options));
         }
 
-        let edges = self.collect_edges(&node_set, include_namespace_packages);
-
-        let graph_edges: Vec<GraphEdge> = edges
-            .iter()
-            .map(|(from, to)| GraphEdge {
-                source: from.to_dotted(),
-                target: to.to_dotted(),
-            })
-            .collect();
+        let graph_edges = self.cytoscape_edges(&node_set, include_namespace_packages, options);
 
         let highlighted_modules = if is_highlighting_mode {
             filter_set.map(|set| {
@@ -1422,12 +3119,14 @@ impl<T: GraphId> DependencyGraph<T> {
         };
 
         GraphData {
+            version: GRAPH_DATA_VERSION,
             nodes: graph_nodes,
             edges: graph_edges,
             config: Some(GraphConfig {
                 include_orphans,
                 include_namespaces: include_namespace_packages,
                 highlighted_modules,
+                presets: None,
             }),
         }
     }
@@ -1438,3 +3137,276 @@ impl<T: GraphId> Default for DependencyGraph<T> {
         Self::new()
     }
 }
+
+impl<T: GraphId + std::str::FromStr> DependencyGraph<T> {
+    /// Rebuild a graph from graph data: its module, script, namespace, and extension
+    /// nodes with their attributes, and its edges with their kinds and tags. Cluster nodes
+    /// (`namespace_group`, `community`, `group`) are skipped since renderers derive them,
+    /// and so are `parent`, `is_orphan`, and `highlighted`. Only the self import time is
+    /// in graph data, so it stands in for the cumulative one too.
+    pub fn from_graph_data(data: &GraphData) -> Result<Self, GraphDataError> {
+        let parse = |id: &str| {
+            id.parse::<T>()
+                .map_err(|_| GraphDataError::InvalidNodeId(id.to_string()))
+        };
+        let mut graph = DependencyGraph::new();
+        for node in &data.nodes {
+            if !matches!(
+                node.node_type.as_str(),
+                "module" | "script" | "namespace" | "extension"
+            ) {
+                continue;
+            }
+            let module = parse(&node.id)?;
+            graph.ensure_node(module.clone());
+            match node.node_type.as_str() {
+                "script" => graph.mark_as_script(&module),
+                "namespace" => graph.mark_as_namespace_package(&module),
+                "extension" => graph.mark_as_extension(&module),
+                _ => {}
+            }
+            if node.generated == Some(true) {
+                graph.mark_as_generated(&module);
+            }
+            if let Some(self_us) = node.import_time_us {
+                graph.set_import_cost(
+                    &module,
+                    ImportCost {
+                        self_us,
+                        cumulative_us: self_us,
+                    },
+                );
+            }
+            if let Some(percent) = node.coverage {
+                graph.set_coverage(&module, percent);
+            }
+            if let Some(category) = &node.category {
+                graph.set_category(&module, category);
+            }
+            if let Some(lifecycle) = node.lifecycle.as_deref().and_then(Lifecycle::parse) {
+                graph.set_lifecycle(&module, lifecycle);
+            }
+            for (key, value) in node.annotations.iter().flatten() {
+                graph.set_annotation(&module, key, value);
+            }
+        }
+
+        for edge in &data.edges {
+            let from = parse(&edge.source)?;
+            let to = parse(&edge.target)?;
+            let kind = match edge.kind.as_deref() {
+                Some("deferred") => EdgeKind::Deferred,
+                _ => EdgeKind::ImportTime,
+            };
+            let count = edge.count.unwrap_or(1).max(1);
+            graph.add_imports(from.clone(), to.clone(), Imports { kind, count });
+            for tag in edge.tags.iter().flatten() {
+                graph.tag_edge(&from, &to, tag);
+            }
+        }
+        Ok(graph)
+    }
+}
+
+/// Version of the serialized [`DependencyGraph`] format written by this build
+pub const DEPENDENCY_GRAPH_VERSION: u32 = 1;
+
+/// Serialized form of a [`DependencyGraph`]: nodes with their attributes and edges, both in
+/// insertion order so that a round trip renders identically
+#[derive(Serialize, Deserialize)]
+struct SerializedGraph<T> {
+    version: u32,
+    nodes: Vec<SerializedNode<T>>,
+    edges: Vec<SerializedEdge<T>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedNode<T> {
+    id: T,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    script: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    namespace_package: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    extension: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    generated: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    directory_clustered: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    import_cost: Option<ImportCost>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    coverage: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    category: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lifecycle: Option<Lifecycle>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    annotations: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source_file: Option<String>,
+}
+
+/// One edge; parallel edges are kept, and the tags and import sites of a node pair sit on
+/// its first edge
+#[derive(Serialize, Deserialize)]
+struct SerializedEdge<T> {
+    from: T,
+    to: T,
+    #[serde(default)]
+    kind: EdgeKind,
+    /// Number of imports the edge stands for
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    count: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sites: Vec<ImportSite>,
+}
+
+fn one() -> usize {
+    1
+}
+
+fn is_one(count: &usize) -> bool {
+    *count == 1
+}
+
+impl<T: GraphId + Serialize> Serialize for DependencyGraph<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let nodes = self
+            .nodes()
+            .map(|module| SerializedNode {
+                id: module.clone(),
+                script: self.is_script(module),
+                namespace_package: self.is_namespace_package(module),
+                extension: self.is_extension(module),
+                generated: self.is_generated(module),
+                directory_clustered: self.is_directory_clustered(module),
+                import_cost: self.import_cost(module),
+                coverage: self.coverage(module),
+                category: self.category(module).map(String::from),
+                lifecycle: self.lifecycle(module),
+                annotations: self.annotations(module).cloned().unwrap_or_default(),
+                source_file: self.source_file(module).map(String::from),
+            })
+            .collect();
+
+        let mut tagged = HashSet::new();
+        let edges = self
+            .graph
+            .raw_edges()
+            .iter()
+            .map(|edge| {
+                let from = &self.graph[edge.source()];
+                let to = &self.graph[edge.target()];
+                let (tags, sites) = if tagged.insert((edge.source(), edge.target())) {
+                    (
+                        self.edge_tags(from, to).to_vec(),
+                        self.import_sites(from, to).to_vec(),
+                    )
+                } else {
+                    (Vec::new(), Vec::new())
+                };
+                SerializedEdge {
+                    from: from.clone(),
+                    to: to.clone(),
+                    kind: edge.weight.kind,
+                    count: edge.weight.count,
+                    tags,
+                    sites,
+                }
+            })
+            .collect();
+
+        SerializedGraph {
+            version: DEPENDENCY_GRAPH_VERSION,
+            nodes,
+            edges,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, T: GraphId + Deserialize<'de>> Deserialize<'de> for DependencyGraph<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let serialized = SerializedGraph::<T>::deserialize(deserializer)?;
+        if serialized.version > DEPENDENCY_GRAPH_VERSION {
+            return Err(D::Error::custom(format!(
+                "dependency graph version {} is newer than the supported version \
+                 {DEPENDENCY_GRAPH_VERSION}",
+                serialized.version
+            )));
+        }
+
+        let mut graph = DependencyGraph::new();
+        for node in serialized.nodes {
+            let module = node.id;
+            if graph.contains(&module) {
+                return Err(D::Error::custom(format!(
+                    "duplicate node '{}'",
+                    module.to_dotted()
+                )));
+            }
+            graph.ensure_node(module.clone());
+            if node.script {
+                graph.mark_as_script(&module);
+            }
+            if node.namespace_package {
+                graph.mark_as_namespace_package(&module);
+            }
+            if node.extension {
+                graph.mark_as_extension(&module);
+            }
+            if node.generated {
+                graph.mark_as_generated(&module);
+            }
+            if node.directory_clustered {
+                graph.cluster_by_directory(&module);
+            }
+            if let Some(cost) = node.import_cost {
+                graph.set_import_cost(&module, cost);
+            }
+            if let Some(percent) = node.coverage {
+                graph.set_coverage(&module, percent);
+            }
+            if let Some(category) = &node.category {
+                graph.set_category(&module, category);
+            }
+            if let Some(lifecycle) = node.lifecycle {
+                graph.set_lifecycle(&module, lifecycle);
+            }
+            for (key, value) in &node.annotations {
+                graph.set_annotation(&module, key, value);
+            }
+            if let Some(path) = &node.source_file {
+                graph.set_source_file(&module, path);
+            }
+        }
+
+        for edge in serialized.edges {
+            for endpoint in [&edge.from, &edge.to] {
+                if !graph.contains(endpoint) {
+                    return Err(D::Error::custom(format!(
+                        "edge endpoint '{}' is not a node",
+                        endpoint.to_dotted()
+                    )));
+                }
+            }
+            let imports = Imports {
+                kind: edge.kind,
+                count: edge.count.max(1),
+            };
+            graph.add_imports(edge.from.clone(), edge.to.clone(), imports);
+            for tag in &edge.tags {
+                graph.tag_edge(&edge.from, &edge.to, tag);
+            }
+            for site in edge.sites {
+                graph.record_import_site(&edge.from, &edge.to, site);
+            }
+        }
+        Ok(graph)
+    }
+}
@@ -0,0 +1,164 @@
+//! Escaping of node names for every output format.
+//!
+//! Module names come from file names and import paths, so they can contain anything a
+//! file system allows: quotes, backslashes, newlines, non-ASCII text, or characters
+//! a format reserves. Every renderer writes names through these helpers so that any
+//! name survives a round trip through the target format's own parser. Identifiers
+//! that cannot be quoted (Mermaid node ids, DOT cluster names) are sanitized instead.
+
+use std::collections::HashSet;
+
+/// Mermaid keywords that cannot be used as a node id
+const MERMAID_RESERVED: &[&str] = &[
+    "end",
+    "graph",
+    "flowchart",
+    "subgraph",
+    "direction",
+    "style",
+    "class",
+    "classdef",
+    "click",
+    "linkstyle",
+    "default",
+    "call",
+    "href",
+];
+
+/// Contents of a double-quoted DOT string (node name, edge endpoint, or label).
+///
+/// Quotes and backslashes are escaped so the string cannot end early, and line breaks
+/// become the `\n` escape so each statement stays on one line. Graphviz renders the
+/// escaped backslash as a single one in labels.
+pub fn dot_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c if c.is_control() => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Contents of a double-quoted Mermaid label.
+///
+/// Mermaid has no backslash escapes; characters that would end the label or be read as
+/// markup are written as its `#code;` entity codes instead, and control characters
+/// become spaces.
+pub fn mermaid_label(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("#quot;"),
+            '#' => escaped.push_str("#35;"),
+            '<' => escaped.push_str("#lt;"),
+            '>' => escaped.push_str("#gt;"),
+            '&' => escaped.push_str("#amp;"),
+            c if c.is_control() => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A bare identifier (Mermaid node or subgraph id, DOT cluster name): ASCII letters,
+/// digits, and underscores only, every other character replaced by `_`. Mermaid
+/// keywords get a trailing `_` so they aren't parsed as statements.
+///
+/// Distinct names can map to the same identifier; use [`IdentifierAssigner`] where ids
+/// must be unique.
+pub fn identifier(value: &str) -> String {
+    let mut id: String = value
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if id.is_empty() || MERMAID_RESERVED.contains(&id.to_ascii_lowercase().as_str()) {
+        id.push('_');
+    }
+    id
+}
+
+/// Hands out unique [`identifier`]s within one document. When the sanitized form of a
+/// name is already taken (`a.b_c` and `a_b.c` both sanitize to `a_b_c`), the first
+/// free `_2`, `_3`, ... suffix is appended, so ids depend only on the order names are
+/// assigned in.
+#[derive(Debug, Default)]
+pub struct IdentifierAssigner {
+    taken: HashSet<String>,
+}
+
+impl IdentifierAssigner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A fresh identifier for `name`; assigning the same name twice yields two ids
+    pub fn assign(&mut self, name: &str) -> String {
+        let base = identifier(name);
+        let mut id = base.clone();
+        let mut suffix = 2;
+        while self.taken.contains(&id) {
+            id = format!("{base}_{suffix}");
+            suffix += 1;
+        }
+        self.taken.insert(id.clone());
+        id
+    }
+}
+
+/// An OpenMetrics label value (backslash, quote, and line feed escaped)
+pub fn openmetrics_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// A double-quoted XML attribute value. Line breaks and tabs are written as character
+/// references (attribute normalization would turn them into spaces), and characters
+/// XML 1.0 does not allow at all are dropped.
+pub fn xml_attribute(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\n' => escaped.push_str("&#10;"),
+            '\r' => escaped.push_str("&#13;"),
+            '\t' => escaped.push_str("&#9;"),
+            c if is_xml_char(c) => escaped.push(c),
+            _ => {}
+        }
+    }
+    escaped
+}
+
+/// Characters allowed in an XML 1.0 document
+fn is_xml_char(c: char) -> bool {
+    matches!(c, '\t' | '\n' | '\r' | '\u{20}'..='\u{D7FF}' | '\u{E000}'..='\u{FFFD}' | '\u{10000}'..)
+}
+
+/// JSON text made safe to embed in an HTML `<script>` element: `<`, `>`, and `&` (which
+/// could close the element or start a comment) and the JavaScript line terminators
+/// U+2028/U+2029 become `\u` escapes, which only occur inside JSON strings.
+pub fn json_for_html(json: &str) -> String {
+    let mut escaped = String::with_capacity(json.len());
+    for c in json.chars() {
+        match c {
+            '<' => escaped.push_str("\\u003c"),
+            '>' => escaped.push_str("\\u003e"),
+            '&' => escaped.push_str("\\u0026"),
+            '\u{2028}' => escaped.push_str("\\u2028"),
+            '\u{2029}' => escaped.push_str("\\u2029"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -1,13 +1,192 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 use crate::GraphNode;
 
+/// Filter configuration sent by the frontend (field names are camelCase on the wire)
+#[cfg_attr(feature = "ts-bindings", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilterConfig {
+    #[serde(rename = "showOrphans")]
+    pub show_orphans: bool,
+    #[serde(rename = "showNamespaces")]
+    pub show_namespaces: bool,
+    #[serde(rename = "excludePatterns")]
+    pub exclude_patterns: Vec<String>,
+    #[serde(rename = "upstreamRoots")]
+    pub upstream_roots: Vec<String>,
+    #[serde(rename = "downstreamRoots")]
+    pub downstream_roots: Vec<String>,
+    #[serde(rename = "maxDistance")]
+    pub max_distance: Option<usize>,
+    #[serde(rename = "highlightedOnly")]
+    pub highlighted_only: bool,
+    /// Also highlight visible nodes within this many hops (either direction) of a
+    /// highlighted node; 0 highlights the matches only
+    #[serde(rename = "highlightNeighbors", default)]
+    pub highlight_neighbors: usize,
+    /// Report filtered-out nodes in `FilterResult::faded` (to be shown dimmed) instead of
+    /// leaving them out
+    #[serde(rename = "fadeFiltered", default)]
+    pub fade_filtered: bool,
+    /// Which edges to keep relative to the upstream/downstream roots
+    #[serde(rename = "edgeDirection", default)]
+    pub edge_direction: EdgeDirection,
+    /// Measure `maxDistance` along weighted imports instead of counting them (see
+    /// [`import_weight`]): `count` or the name of a numeric node annotation
+    #[serde(rename = "weightKey", default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts-bindings", ts(optional))]
+    pub weight_key: Option<String>,
+}
+
+/// Length of one import under [`FilterConfig::weight_key`]: for `count`, how many times
+/// it is made; for any other key, that annotation of the imported module as a whole
+/// number (e.g. `loc`). Imports without a usable value weigh 1.
+pub fn import_weight(
+    key: &str,
+    count: usize,
+    imported_annotations: Option<&BTreeMap<String, String>>,
+) -> usize {
+    if key == "count" {
+        return count.max(1);
+    }
+    imported_annotations
+        .and_then(|annotations| annotations.get(key))
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(1)
+}
+
+/// Edges to show relative to the selected roots
+#[cfg_attr(feature = "ts-bindings", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EdgeDirection {
+    /// Every edge between shown nodes
+    #[default]
+    Both,
+    /// Only dependency edges leaving the roots' dependencies (what the roots import).
+    /// Older filter states and presets spell it `upstream`.
+    #[serde(alias = "upstream")]
+    Dependencies,
+    /// Only edges into the roots' dependents (what imports the roots). Older filter
+    /// states and presets spell it `downstream`.
+    #[serde(alias = "downstream")]
+    Dependents,
+}
+
+impl EdgeDirection {
+    /// Whether the edge `from -> to` is kept, given the nodes related to the roots as
+    /// `dependencies` and as `dependents`
+    pub fn keeps<T: Eq + std::hash::Hash>(
+        self,
+        from: &T,
+        to: &T,
+        dependencies: &HashSet<T>,
+        dependents: &HashSet<T>,
+    ) -> bool {
+        match self {
+            EdgeDirection::Both => true,
+            EdgeDirection::Dependencies => dependencies.contains(from),
+            EdgeDirection::Dependents => dependents.contains(to),
+        }
+    }
+}
+
+/// Version byte leading every encoded filter state (see [`FilterConfig::encode`])
+pub const FILTER_STATE_VERSION: u8 = 1;
+
+/// Upper bound on the decompressed size of an encoded filter state
+const MAX_FILTER_STATE_BYTES: usize = 1 << 20;
+
+/// Errors that can occur while decoding a filter state
+#[derive(Error, Debug)]
+pub enum FilterStateError {
+    #[error("Filter state is not valid URL-safe base64: {0}")]
+    Base64(base64::DecodeError),
+
+    #[error("Filter state is empty")]
+    Empty,
+
+    #[error("Filter state version {0} is not supported (expected {FILTER_STATE_VERSION})")]
+    UnsupportedVersion(u8),
+
+    #[error("Filter state is corrupted: decompression failed")]
+    Decompress,
+
+    #[error("Filter state does not describe a filter configuration: {0}")]
+    Parse(serde_json::Error),
+}
+
+impl FilterConfig {
+    /// Compact, URL-safe encoding of the configuration, for sharing a view as a link
+    /// (`#filter=...` in the viewer) or passing it to `--filter-state`: a version byte
+    /// followed by the deflated JSON, in unpadded URL-safe base64.
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).expect("FilterConfig always serializes");
+        let mut bytes = vec![FILTER_STATE_VERSION];
+        bytes.extend(miniz_oxide::deflate::compress_to_vec(&json, 9));
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    /// Decode a configuration produced by [`FilterConfig::encode`]
+    pub fn decode(encoded: &str) -> Result<Self, FilterStateError> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(encoded.trim())
+            .map_err(FilterStateError::Base64)?;
+        let (&version, compressed) = bytes.split_first().ok_or(FilterStateError::Empty)?;
+        if version != FILTER_STATE_VERSION {
+            return Err(FilterStateError::UnsupportedVersion(version));
+        }
+        let json =
+            miniz_oxide::inflate::decompress_to_vec_with_limit(compressed, MAX_FILTER_STATE_BYTES)
+                .map_err(|_| FilterStateError::Decompress)?;
+        serde_json::from_slice(&json).map_err(FilterStateError::Parse)
+    }
+}
+
+/// A named filter configuration: a saved "view" of the graph that can be shared and
+/// re-applied (declared as `[[presets]]` in `deptree.toml`, or exported from the viewer)
+#[cfg_attr(feature = "ts-bindings", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilterPreset {
+    pub name: String,
+    pub config: FilterConfig,
+}
+
+/// Result of filter operation containing both visibility and highlighting information
+#[cfg_attr(feature = "ts-bindings", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilterResult {
+    /// Node IDs that should be visible
+    pub visible: Vec<String>,
+    /// Node IDs that should be highlighted
+    pub highlighted: Vec<String>,
+    /// Node IDs filtered out but kept on screen at low opacity (only with `fadeFiltered`;
+    /// every other node is hidden)
+    #[serde(default)]
+    pub faded: Vec<String>,
+    /// `[source, target]` edges between shown nodes that `edgeDirection` hides
+    #[serde(rename = "hiddenEdges", default)]
+    pub hidden_edges: Vec<(String, String)>,
+}
+
 /// Match a string against a wildcard pattern.
-/// Supports: *prefix, suffix*, *substring*.
+/// Supports: *prefix, suffix*, *substring*, and `*` alone matching everything.
 pub fn matches_pattern(text: &str, pattern: &str) -> bool {
     if pattern.is_empty() {
         return text.is_empty();
     }
+    if pattern == "*" {
+        return true;
+    }
 
     let starts_with_wildcard = pattern.starts_with('*');
     let ends_with_wildcard = pattern.ends_with('*');
@@ -75,6 +254,9 @@ mod tests {
 
         assert!(!matches_pattern("test_script.py", "*foo*"));
         assert!(!matches_pattern("test_script.py", "foo*"));
+
+        assert!(matches_pattern("test_script.py", "*"));
+        assert!(matches_pattern("", "*"));
     }
 
     #[test]
@@ -86,6 +268,16 @@ mod tests {
                 is_orphan: false,
                 highlighted: None,
                 parent: None,
+                import_time_us: None,
+                coverage: None,
+                category: None,
+                lifecycle: None,
+                generated: None,
+                change: None,
+                annotations: None,
+                position: None,
+                style: None,
+                href: None,
             },
             GraphNode {
                 id: "orphan".to_string(),
@@ -93,6 +285,16 @@ mod tests {
                 is_orphan: true,
                 highlighted: None,
                 parent: None,
+                import_time_us: None,
+                coverage: None,
+                category: None,
+                lifecycle: None,
+                generated: None,
+                change: None,
+                annotations: None,
+                position: None,
+                style: None,
+                href: None,
             },
         ];
 
@@ -114,6 +316,16 @@ mod tests {
                 is_orphan: false,
                 highlighted: None,
                 parent: None,
+                import_time_us: None,
+                coverage: None,
+                category: None,
+                lifecycle: None,
+                generated: None,
+                change: None,
+                annotations: None,
+                position: None,
+                style: None,
+                href: None,
             },
             GraphNode {
                 id: "namespace_pkg".to_string(),
@@ -121,6 +333,16 @@ mod tests {
                 is_orphan: false,
                 highlighted: None,
                 parent: None,
+                import_time_us: None,
+                coverage: None,
+                category: None,
+                lifecycle: None,
+                generated: None,
+                change: None,
+                annotations: None,
+                position: None,
+                style: None,
+                href: None,
             },
         ];
 
@@ -142,6 +364,16 @@ mod tests {
                 is_orphan: false,
                 highlighted: None,
                 parent: None,
+                import_time_us: None,
+                coverage: None,
+                category: None,
+                lifecycle: None,
+                generated: None,
+                change: None,
+                annotations: None,
+                position: None,
+                style: None,
+                href: None,
             },
             GraphNode {
                 id: "scripts.old_runner".to_string(),
@@ -149,6 +381,16 @@ mod tests {
                 is_orphan: false,
                 highlighted: None,
                 parent: None,
+                import_time_us: None,
+                coverage: None,
+                category: None,
+                lifecycle: None,
+                generated: None,
+                change: None,
+                annotations: None,
+                position: None,
+                style: None,
+                href: None,
             },
         ];
 
@@ -158,4 +400,13 @@ mod tests {
         assert!(visible.contains("scripts.main"));
         assert!(!visible.contains("scripts.old_runner"));
     }
+
+    #[test]
+    fn test_import_weight() {
+        let annotations = BTreeMap::from([("loc".to_string(), "120".to_string())]);
+        assert_eq!(import_weight("count", 3, Some(&annotations)), 3);
+        assert_eq!(import_weight("loc", 3, Some(&annotations)), 120);
+        assert_eq!(import_weight("team", 3, Some(&annotations)), 1);
+        assert_eq!(import_weight("loc", 1, None), 1);
+    }
 }
@@ -0,0 +1,293 @@
+//! Reusable reachability index over a node/edge list.
+//!
+//! Interns the node ids once into a [`CompactGraph`] (CSR adjacency in both directions)
+//! and memoizes the breadth-first search from every root set it is asked about, so
+//! repeated upstream/downstream queries (e.g. while a distance slider is dragged) only
+//! pay for the lookup. Several roots are searched together in one multi-source
+//! traversal rather than one search per root.
+//!
+//! When memory permits, the index also precomputes the transitive closure
+//! ([`ReachabilityClosure`]) so unbounded queries for new root sets skip the search.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use crate::Relation;
+
+use crate::closure::ReachabilityClosure;
+use crate::csr::CompactGraph;
+use crate::{DistanceComputation, GraphEdge, GraphNode};
+
+/// Largest transitive closure [`GraphIndex::new`] precomputes (about 11k nodes without
+/// cycles)
+pub const CLOSURE_MEMORY_BUDGET: usize = 32 * 1024 * 1024;
+
+/// `(node, distance)` for every node reachable from a set of roots, in breadth-first
+/// order (roots first, at 0)
+type Reachable = Rc<Vec<(u32, u32)>>;
+
+pub struct GraphIndex {
+    graph: Rc<CompactGraph>,
+    parents: HashMap<String, String>,
+    /// `is_orphan` as computed by the graph that produced the nodes
+    orphans: HashMap<String, bool>,
+    reachable: RefCell<HashMap<(Vec<u32>, Relation), Reachable>>,
+    closure: Option<ReachabilityClosure>,
+}
+
+impl GraphIndex {
+    /// Index `nodes` plus any node only mentioned by `edges`, with the transitive closure
+    /// if it fits in [`CLOSURE_MEMORY_BUDGET`]
+    pub fn new(nodes: &[GraphNode], edges: &[GraphEdge]) -> Self {
+        Self::with_closure_budget(nodes, edges, CLOSURE_MEMORY_BUDGET)
+    }
+
+    /// Like [`GraphIndex::new`], precomputing the transitive closure only if it takes at
+    /// most `max_bytes` (0 disables it)
+    pub fn with_closure_budget(nodes: &[GraphNode], edges: &[GraphEdge], max_bytes: usize) -> Self {
+        let graph = CompactGraph::new(
+            nodes.iter().map(|n| n.id.as_str()),
+            edges.iter().map(|e| (e.source.as_str(), e.target.as_str())),
+        );
+        let parents = nodes
+            .iter()
+            .filter_map(|n| n.parent.clone().map(|parent| (n.id.clone(), parent)))
+            .collect();
+        let orphans = nodes.iter().map(|n| (n.id.clone(), n.is_orphan)).collect();
+        let closure = ReachabilityClosure::build_within(&graph, max_bytes);
+
+        GraphIndex {
+            graph: Rc::new(graph),
+            parents,
+            orphans,
+            reachable: RefCell::new(HashMap::new()),
+            closure,
+        }
+    }
+
+    /// Whether unbounded queries are answered from the precomputed transitive closure
+    pub fn has_closure(&self) -> bool {
+        self.closure.is_some()
+    }
+
+    /// Searches memoized so far, one per distinct root set and direction
+    pub fn memoized_searches(&self) -> usize {
+        self.reachable.borrow().len()
+    }
+
+    /// All-pairs distances over the indexed graph, sharing its adjacency instead of
+    /// rebuilding it. Nodes only mentioned by edges are sources too.
+    pub fn distance_computation(&self) -> DistanceComputation {
+        DistanceComputation::from_graph(Rc::clone(&self.graph))
+    }
+
+    pub fn contains(&self, node_id: &str) -> bool {
+        self.graph.index_of(node_id).is_some()
+    }
+
+    /// Parent (compound) node of `node_id`, if any
+    pub fn parent(&self, node_id: &str) -> Option<&str> {
+        self.parents.get(node_id).map(String::as_str)
+    }
+
+    /// The `is_orphan` flag of `node_id`, which accounts for edges through hidden
+    /// namespace packages; nodes only mentioned by edges fall back to whether they have
+    /// any edge
+    pub fn is_orphan(&self, node_id: &str) -> bool {
+        if let Some(&orphan) = self.orphans.get(node_id) {
+            return orphan;
+        }
+        self.graph.index_of(node_id).is_none_or(|index| {
+            self.graph
+                .neighbors(index, Relation::Dependencies)
+                .is_empty()
+                && self.graph.neighbors(index, Relation::Dependents).is_empty()
+        })
+    }
+
+    /// Nodes related to `roots` with their distance (roots at 0)
+    pub fn related_with_distance(
+        &self,
+        roots: &[String],
+        relation: Relation,
+        max_distance: Option<usize>,
+    ) -> HashMap<String, usize> {
+        self.reachable_from(roots, max_distance, relation)
+    }
+
+    /// Nodes related to `roots`, roots included
+    pub fn related(
+        &self,
+        roots: &[String],
+        relation: Relation,
+        max_distance: Option<usize>,
+    ) -> HashSet<String> {
+        self.reachable_set(roots, max_distance, relation)
+    }
+
+    /// [`Self::related_with_distance`] with `cost(importer, imported)` as the length of
+    /// each import instead of 1, so `max_distance` bounds the weighted distance. Not
+    /// memoized.
+    pub fn related_weighted(
+        &self,
+        roots: &[String],
+        relation: Relation,
+        max_distance: Option<usize>,
+        cost: impl Fn(&str, &str) -> usize,
+    ) -> HashMap<String, usize> {
+        self.weighted_from(roots, max_distance, relation, cost)
+    }
+
+    /// Upstream dependencies of `roots` with their distance (roots at 0)
+    pub fn upstream_with_distance(
+        &self,
+        roots: &[String],
+        max_distance: Option<usize>,
+    ) -> HashMap<String, usize> {
+        self.reachable_from(roots, max_distance, Relation::Dependencies)
+    }
+
+    /// Downstream dependents of `roots` with their distance (roots at 0)
+    pub fn downstream_with_distance(
+        &self,
+        roots: &[String],
+        max_distance: Option<usize>,
+    ) -> HashMap<String, usize> {
+        self.reachable_from(roots, max_distance, Relation::Dependents)
+    }
+
+    pub fn upstream(&self, roots: &[String], max_distance: Option<usize>) -> HashSet<String> {
+        self.reachable_set(roots, max_distance, Relation::Dependencies)
+    }
+
+    pub fn downstream(&self, roots: &[String], max_distance: Option<usize>) -> HashSet<String> {
+        self.reachable_set(roots, max_distance, Relation::Dependents)
+    }
+
+    /// [`Self::related_weighted`] over dependencies
+    pub fn upstream_weighted(
+        &self,
+        roots: &[String],
+        max_distance: Option<usize>,
+        cost: impl Fn(&str, &str) -> usize,
+    ) -> HashMap<String, usize> {
+        self.weighted_from(roots, max_distance, Relation::Dependencies, cost)
+    }
+
+    /// [`Self::related_weighted`] over dependents
+    pub fn downstream_weighted(
+        &self,
+        roots: &[String],
+        max_distance: Option<usize>,
+        cost: impl Fn(&str, &str) -> usize,
+    ) -> HashMap<String, usize> {
+        self.weighted_from(roots, max_distance, Relation::Dependents, cost)
+    }
+
+    /// Distances from `root` to every node it depends on; empty for unknown nodes
+    pub fn distances_from(&self, root: &str) -> HashMap<String, usize> {
+        self.graph
+            .index_of(root)
+            .map(|index| self.named(&self.bfs(vec![index], Relation::Dependencies), None))
+            .unwrap_or_default()
+    }
+
+    /// Split `roots` into the indices of known nodes and the unknown ids
+    fn resolve<'a>(&self, roots: &'a [String]) -> (Vec<u32>, Vec<&'a String>) {
+        let mut known = Vec::new();
+        let mut unknown = Vec::new();
+        for root in roots {
+            match self.graph.index_of(root) {
+                Some(index) => known.push(index),
+                None => unknown.push(root),
+            }
+        }
+        (known, unknown)
+    }
+
+    /// Node ids of a search result within `max_distance`
+    fn named(&self, reached: &[(u32, u32)], max_distance: Option<usize>) -> HashMap<String, usize> {
+        reached
+            .iter()
+            .map(|&(node, distance)| (node, distance as usize))
+            .filter(|&(_, distance)| max_distance.is_none_or(|limit| distance <= limit))
+            .map(|(node, distance)| (self.graph.id(node).to_string(), distance))
+            .collect()
+    }
+
+    /// Nodes reachable from `roots`; unbounded queries use the closure when there is one
+    fn reachable_set(
+        &self,
+        roots: &[String],
+        max_distance: Option<usize>,
+        relation: Relation,
+    ) -> HashSet<String> {
+        let Some(closure) = self.closure.as_ref().filter(|_| max_distance.is_none()) else {
+            return self
+                .reachable_from(roots, max_distance, relation)
+                .into_keys()
+                .collect();
+        };
+        let (known, unknown) = self.resolve(roots);
+        closure
+            .reachable(&known, relation)
+            .into_iter()
+            .map(|index| self.graph.id(index).to_string())
+            // Unknown roots still count as reached from themselves
+            .chain(unknown.into_iter().cloned())
+            .collect()
+    }
+
+    fn reachable_from(
+        &self,
+        roots: &[String],
+        max_distance: Option<usize>,
+        relation: Relation,
+    ) -> HashMap<String, usize> {
+        let (known, unknown) = self.resolve(roots);
+        // Unknown roots still count as reached from themselves
+        let mut result: HashMap<String, usize> =
+            unknown.into_iter().map(|root| (root.clone(), 0)).collect();
+        if !known.is_empty() {
+            result.extend(self.named(&self.bfs(known, relation), max_distance));
+        }
+        result
+    }
+
+    fn weighted_from(
+        &self,
+        roots: &[String],
+        max_distance: Option<usize>,
+        relation: Relation,
+        cost: impl Fn(&str, &str) -> usize,
+    ) -> HashMap<String, usize> {
+        let (known, unknown) = self.resolve(roots);
+        let mut result: HashMap<String, usize> =
+            unknown.into_iter().map(|root| (root.clone(), 0)).collect();
+        let reached = self.graph.dijkstra(&known, relation, |source, target| {
+            cost(self.graph.id(source), self.graph.id(target))
+        });
+        result.extend(
+            reached
+                .into_iter()
+                .filter(|&(_, distance)| max_distance.is_none_or(|limit| distance <= limit))
+                .map(|(node, distance)| (self.graph.id(node).to_string(), distance)),
+        );
+        result
+    }
+
+    /// Unbounded multi-source BFS from `starts`, computed once per root set and relation
+    fn bfs(&self, mut starts: Vec<u32>, relation: Relation) -> Reachable {
+        starts.sort_unstable();
+        starts.dedup();
+        let key = (starts, relation);
+        if let Some(cached) = self.reachable.borrow().get(&key) {
+            return Rc::clone(cached);
+        }
+
+        let reached = Rc::new(self.graph.bfs(&key.0, relation));
+        self.reachable.borrow_mut().insert(key, Rc::clone(&reached));
+        reached
+    }
+}
@@ -1,26 +1,49 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::rc::Rc;
 
-use petgraph::algo::{dijkstra, floyd_warshall};
+use petgraph::algo::dijkstra;
 use petgraph::graph::NodeIndex;
-use petgraph::visit::Reversed;
 use petgraph::{Direction, Graph};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
+pub mod closure;
+pub mod community;
+pub mod csr;
 pub mod dependency_graph;
+pub mod escape;
 pub mod filters;
-pub use dependency_graph::{DependencyGraph, GraphId};
+pub mod index;
+#[cfg(feature = "json-schema")]
+pub mod schema;
+pub mod style;
+pub use closure::ReachabilityClosure;
+pub use csr::{CompactGraph, CsrAdjacency};
+pub use dependency_graph::{
+    DEPENDENCY_GRAPH_VERSION, DependencyGraph, DotClustering, EdgeKind, GraphId, GraphMetrics,
+    ImportCost, ImportSite, Lifecycle, MermaidEdgeLabels, NamespaceGrouping, OrphanScope, Reached,
+    RenderOptions, chain_to,
+};
+pub use index::GraphIndex;
 
 /// Graph node representation shared between the CLI and frontend.
 #[cfg_attr(feature = "ts-bindings", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GraphNode {
     pub id: String,
     #[serde(rename = "type")]
     #[cfg_attr(
         feature = "ts-bindings",
-        ts(type = "\"module\" | \"script\" | \"namespace\" | \"namespace_group\"")
+        ts(
+            type = "\"module\" | \"script\" | \"namespace\" | \"extension\" | \"namespace_group\" | \"community\" | \"group\""
+        )
     )]
-    pub node_type: String, // "module", "script", "namespace", or "namespace_group"
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(extend("enum" = ["module", "script", "namespace", "extension", "namespace_group", "community", "group"]))
+    )]
+    pub node_type: String, // "module", "script", "namespace", "extension", "namespace_group", "community", or "group"
     pub is_orphan: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[cfg_attr(feature = "ts-bindings", ts(optional))]
@@ -28,18 +51,144 @@ pub struct GraphNode {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[cfg_attr(feature = "ts-bindings", ts(optional))]
     pub parent: Option<String>,
+    /// Self import time in microseconds, when an import profile was supplied
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts-bindings", ts(optional, type = "number"))]
+    pub import_time_us: Option<u64>,
+    /// Line coverage percentage (0-100), when a coverage report was supplied
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts-bindings", ts(optional))]
+    pub coverage: Option<f64>,
+    /// User-defined category from the configuration (e.g. "automation" for `ops/` scripts)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts-bindings", ts(optional))]
+    pub category: Option<String>,
+    /// Lifecycle state declared for the module
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(
+        feature = "ts-bindings",
+        ts(optional, type = "\"experimental\" | \"stable\" | \"sunset\"")
+    )]
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(extend("enum" = ["experimental", "stable", "sunset"]))
+    )]
+    pub lifecycle: Option<String>,
+    /// Set for generated code (e.g. protobuf `_pb2` modules); absent otherwise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts-bindings", ts(optional))]
+    pub generated: Option<bool>,
+    /// How a `diff` output classifies the node: "added", "removed", or "unchanged"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(
+        feature = "ts-bindings",
+        ts(optional, type = "\"added\" | \"removed\" | \"unchanged\"")
+    )]
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(extend("enum" = ["added", "removed", "unchanged"]))
+    )]
+    pub change: Option<String>,
+    /// Free-form metadata from an annotations file (`--annotations`), e.g. team or tier
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts-bindings", ts(optional))]
+    pub annotations: Option<BTreeMap<String, String>>,
+    /// Where the viewer should place the node initially, when the graph was generated
+    /// with layout hints (`--layout-seed`); absent for compound (parent) nodes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts-bindings", ts(optional))]
+    pub position: Option<NodePosition>,
+    /// Style attributes from the renderer's style hooks (`style::RenderStyle`), applied
+    /// by the viewer on top of its stylesheet
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts-bindings", ts(optional))]
+    pub style: Option<BTreeMap<String, String>>,
+    /// Link to the module's source from the link template (`--link-template`), opened
+    /// by the viewer on double click
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts-bindings", ts(optional))]
+    pub href: Option<String>,
+}
+
+/// Position of a node in the viewer's model coordinates
+#[cfg_attr(feature = "ts-bindings", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NodePosition {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Which side of its imports to follow from a module. Edges point from the importer to
+/// the imported module, so "upstream" of a module means its dependencies; taking a
+/// `Relation` instead of a graph direction keeps callers from mixing the two up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Relation {
+    /// What the module imports, directly or not (its upstream)
+    Dependencies,
+    /// What imports the module, directly or not (its downstream)
+    Dependents,
+}
+
+impl Relation {
+    /// The other side
+    pub fn reversed(self) -> Self {
+        match self {
+            Relation::Dependencies => Relation::Dependents,
+            Relation::Dependents => Relation::Dependencies,
+        }
+    }
+
+    /// Direction of the edges to walk in an importer -> imported petgraph graph
+    pub(crate) fn direction(self) -> Direction {
+        match self {
+            Relation::Dependencies => Direction::Outgoing,
+            Relation::Dependents => Direction::Incoming,
+        }
+    }
 }
 
 /// Graph edge representation shared between the CLI and frontend.
 #[cfg_attr(feature = "ts-bindings", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GraphEdge {
     pub source: String,
     pub target: String,
+    /// Set to "deferred" for dependencies that only execute after import (e.g. imports
+    /// inside function bodies); absent for import-time dependencies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts-bindings", ts(optional, type = "\"deferred\""))]
+    #[cfg_attr(feature = "json-schema", schemars(extend("enum" = ["deferred"])))]
+    pub kind: Option<String>,
+    /// Free-form tags, e.g. `platform:win32` for imports guarded by a platform check
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts-bindings", ts(optional))]
+    pub tags: Option<Vec<String>>,
+    /// How many times the source imports the target, when more than once
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts-bindings", ts(optional, type = "number"))]
+    pub count: Option<usize>,
+    /// How a `diff` output classifies the edge: "added", "removed", or "unchanged"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(
+        feature = "ts-bindings",
+        ts(optional, type = "\"added\" | \"removed\" | \"unchanged\"")
+    )]
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(extend("enum" = ["added", "removed", "unchanged"]))
+    )]
+    pub change: Option<String>,
+    /// Style attributes from the renderer's style hooks, as on nodes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts-bindings", ts(optional))]
+    pub style: Option<BTreeMap<String, String>>,
 }
 
 /// Graph configuration for visualization consumers.
 #[cfg_attr(feature = "ts-bindings", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GraphConfig {
     pub include_orphans: bool,
@@ -47,12 +196,45 @@ pub struct GraphConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[cfg_attr(feature = "ts-bindings", ts(optional))]
     pub highlighted_modules: Option<Vec<String>>,
+    /// Named filter presets offered by the viewer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts-bindings", ts(optional))]
+    pub presets: Option<Vec<filters::FilterPreset>>,
+}
+
+/// Version of the [`GraphData`] format written by this build. Bump it whenever a change
+/// would make older readers misinterpret a payload, and teach [`GraphData::from_json`]
+/// to upgrade the previous version.
+pub const GRAPH_DATA_VERSION: u32 = 1;
+
+/// Errors that can occur while reading serialized graph data
+#[derive(Error, Debug)]
+pub enum GraphDataError {
+    #[error("Failed to parse graph JSON: {0}")]
+    Parse(serde_json::Error),
+
+    #[error("Invalid graph data version {0}: expected a non-negative integer")]
+    InvalidVersion(serde_json::Value),
+
+    #[error(
+        "Graph data version {0} is newer than the supported version {GRAPH_DATA_VERSION}; \
+         regenerate it with the deptree-utils release that ships this viewer"
+    )]
+    UnsupportedVersion(u64),
+
+    #[error("Invalid node id in graph data: '{0}'")]
+    InvalidNodeId(String),
 }
 
 /// Complete graph data payload passed from the CLI to the frontend.
 #[cfg_attr(feature = "ts-bindings", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GraphData {
+    /// Format version ([`GRAPH_DATA_VERSION`] when written by this build; 0 when read from
+    /// a payload that predates versioning, until [`GraphData::upgrade`] is applied)
+    #[serde(default)]
+    pub version: u32,
     pub nodes: Vec<GraphNode>,
     pub edges: Vec<GraphEdge>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -60,6 +242,40 @@ pub struct GraphData {
     pub config: Option<GraphConfig>,
 }
 
+impl GraphData {
+    /// Parse a serialized payload, upgrading older format versions to
+    /// [`GRAPH_DATA_VERSION`] and rejecting newer ones.
+    pub fn from_json(json: &str) -> Result<Self, GraphDataError> {
+        let value: serde_json::Value = serde_json::from_str(json).map_err(GraphDataError::Parse)?;
+
+        let version = match value.get("version") {
+            // Payloads written before the format was versioned
+            None => 0,
+            Some(version) => version
+                .as_u64()
+                .ok_or_else(|| GraphDataError::InvalidVersion(version.clone()))?,
+        };
+        if version > u64::from(GRAPH_DATA_VERSION) {
+            return Err(GraphDataError::UnsupportedVersion(version));
+        }
+
+        serde_json::from_value::<GraphData>(value)
+            .map_err(GraphDataError::Parse)?
+            .upgrade()
+    }
+
+    /// Bring a deserialized payload to [`GRAPH_DATA_VERSION`], rejecting newer versions.
+    /// Needed whenever the payload was deserialized without [`GraphData::from_json`].
+    pub fn upgrade(mut self) -> Result<Self, GraphDataError> {
+        if self.version > GRAPH_DATA_VERSION {
+            return Err(GraphDataError::UnsupportedVersion(u64::from(self.version)));
+        }
+        // Version 0 has the same shape as version 1 apart from the missing field
+        self.version = GRAPH_DATA_VERSION;
+        Ok(self)
+    }
+}
+
 /// Build a petgraph graph from node/edge lists.
 pub fn build_graph(
     nodes: &[GraphNode],
@@ -89,10 +305,97 @@ pub fn bfs_distances_from_node(
     graph: &Graph<String, ()>,
     root_idx: NodeIndex,
 ) -> HashMap<String, usize> {
-    dijkstra(graph, root_idx, None, |_| 1usize)
-        .into_iter()
-        .filter_map(|(idx, cost)| graph.node_weight(idx).map(|id| (id.clone(), cost)))
-        .collect()
+    weighted_distances_from_node(graph, root_idx, |_, _| 1)
+}
+
+/// [`bfs_distances_from_node`] with `cost(source, target)` as the length of each edge
+pub fn weighted_distances_from_node(
+    graph: &Graph<String, ()>,
+    root_idx: NodeIndex,
+    cost: impl Fn(&str, &str) -> usize,
+) -> HashMap<String, usize> {
+    use petgraph::visit::EdgeRef;
+
+    dijkstra(graph, root_idx, None, |edge| {
+        cost(&graph[edge.source()], &graph[edge.target()])
+    })
+    .into_iter()
+    .filter_map(|(idx, cost)| graph.node_weight(idx).map(|id| (id.clone(), cost)))
+    .collect()
+}
+
+/// All-pairs distance computation that can be advanced a few source nodes at a time,
+/// so callers can report progress (or give up) on big graphs instead of blocking.
+pub struct DistanceComputation {
+    graph: Rc<CompactGraph>,
+    next_source: usize,
+    distances: HashMap<String, HashMap<String, usize>>,
+}
+
+impl DistanceComputation {
+    /// Distances between `nodes`; edges to nodes not in the list are ignored
+    pub fn new(nodes: &[GraphNode], edges: &[GraphEdge]) -> Self {
+        let ids: HashSet<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+        let graph = CompactGraph::new(
+            nodes.iter().map(|n| n.id.as_str()),
+            edges
+                .iter()
+                .filter(|e| ids.contains(e.source.as_str()) && ids.contains(e.target.as_str()))
+                .map(|e| (e.source.as_str(), e.target.as_str())),
+        );
+        Self::from_graph(Rc::new(graph))
+    }
+
+    fn from_graph(graph: Rc<CompactGraph>) -> Self {
+        DistanceComputation {
+            graph,
+            next_source: 0,
+            distances: HashMap::new(),
+        }
+    }
+
+    /// Compute the distances from up to `max_sources` more nodes.
+    /// Returns `true` once every node has been processed.
+    pub fn step(&mut self, max_sources: usize) -> bool {
+        let end = self
+            .next_source
+            .saturating_add(max_sources)
+            .min(self.total());
+        for source in self.next_source as u32..end as u32 {
+            let distances = self
+                .graph
+                .bfs(&[source], Relation::Dependencies)
+                .into_iter()
+                .map(|(node, distance)| (self.graph.id(node).to_string(), distance as usize))
+                .collect();
+            self.distances
+                .insert(self.graph.id(source).to_string(), distances);
+        }
+        self.next_source = end;
+        self.is_done()
+    }
+
+    /// Number of source nodes processed so far
+    pub fn processed(&self) -> usize {
+        self.next_source
+    }
+
+    pub fn total(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.next_source >= self.total()
+    }
+
+    /// Distances computed so far, keyed by source node
+    pub fn distances(&self) -> &HashMap<String, HashMap<String, usize>> {
+        &self.distances
+    }
+
+    pub fn into_distances(self) -> HashMap<String, HashMap<String, usize>> {
+        self.distances
+    }
 }
 
 /// Compute distances from all nodes to all reachable nodes.
@@ -101,26 +404,14 @@ pub fn compute_all_distances(
     nodes: &[GraphNode],
     edges: &[GraphEdge],
 ) -> HashMap<String, HashMap<String, usize>> {
-    let (graph, _) = build_graph(nodes, edges);
-    let mut all_distances: HashMap<String, HashMap<String, usize>> = HashMap::new();
-
-    if let Ok(floyd) = floyd_warshall(&graph, |_| 1usize) {
-        for ((from_idx, to_idx), dist) in floyd {
-            if let (Some(from_id), Some(to_id)) =
-                (graph.node_weight(from_idx), graph.node_weight(to_idx))
-            {
-                all_distances
-                    .entry(from_id.clone())
-                    .or_default()
-                    .insert(to_id.clone(), dist);
-            }
-        }
-    }
-
-    all_distances
+    let mut computation = DistanceComputation::new(nodes, edges);
+    computation.step(usize::MAX);
+    computation.into_distances()
 }
 
-/// Check if a node is an orphan (has no incoming or outgoing edges).
+/// Check if a node is an orphan with respect to `edges` only. Graph data already carries
+/// the graph-wide answer in [`GraphNode::is_orphan`] (see
+/// [`DependencyGraph::orphans_in`]); this is the one for a subgraph of it.
 pub fn is_orphan_node(node_id: &str, edges: &[GraphEdge]) -> bool {
     let has_incoming = edges.iter().any(|e| e.target == node_id);
     let has_outgoing = edges.iter().any(|e| e.source == node_id);
@@ -150,26 +441,44 @@ pub fn get_nodes_within_distance(
     result
 }
 
-/// Get all upstream dependencies (nodes that the roots depend on).
-pub fn get_upstream_nodes(
+/// Get all nodes related to the roots (roots included).
+pub fn get_related_nodes(
     roots: &[String],
     edges: &[GraphEdge],
+    relation: Relation,
     max_distance: Option<usize>,
 ) -> HashSet<String> {
-    get_upstream_nodes_with_distance(roots, edges, max_distance)
+    get_related_nodes_with_distance(roots, edges, relation, max_distance)
         .into_keys()
         .collect()
 }
 
+/// Get nodes related to the roots with distance information (root has distance 0).
+pub fn get_related_nodes_with_distance(
+    roots: &[String],
+    edges: &[GraphEdge],
+    relation: Relation,
+    max_distance: Option<usize>,
+) -> HashMap<String, usize> {
+    GraphIndex::new(&[], edges).related_with_distance(roots, relation, max_distance)
+}
+
+/// Get all upstream dependencies (nodes that the roots depend on).
+pub fn get_upstream_nodes(
+    roots: &[String],
+    edges: &[GraphEdge],
+    max_distance: Option<usize>,
+) -> HashSet<String> {
+    get_related_nodes(roots, edges, Relation::Dependencies, max_distance)
+}
+
 /// Get all downstream dependents (nodes that depend on the roots).
 pub fn get_downstream_nodes(
     roots: &[String],
     edges: &[GraphEdge],
     max_distance: Option<usize>,
 ) -> HashSet<String> {
-    get_downstream_nodes_with_distance(roots, edges, max_distance)
-        .into_keys()
-        .collect()
+    get_related_nodes(roots, edges, Relation::Dependents, max_distance)
 }
 
 /// Get upstream dependencies with distance information (root has distance 0).
@@ -178,7 +487,7 @@ pub fn get_upstream_nodes_with_distance(
     edges: &[GraphEdge],
     max_distance: Option<usize>,
 ) -> HashMap<String, usize> {
-    collect_reachable_with_distance(roots, edges, max_distance, Direction::Outgoing)
+    get_related_nodes_with_distance(roots, edges, Relation::Dependencies, max_distance)
 }
 
 /// Get downstream dependents with distance information (root has distance 0).
@@ -187,76 +496,7 @@ pub fn get_downstream_nodes_with_distance(
     edges: &[GraphEdge],
     max_distance: Option<usize>,
 ) -> HashMap<String, usize> {
-    collect_reachable_with_distance(roots, edges, max_distance, Direction::Incoming)
-}
-
-fn collect_reachable_with_distance(
-    roots: &[String],
-    edges: &[GraphEdge],
-    max_distance: Option<usize>,
-    direction: Direction,
-) -> HashMap<String, usize> {
-    let node_ids: HashSet<String> = edges
-        .iter()
-        .flat_map(|e| [e.source.clone(), e.target.clone()])
-        .chain(roots.iter().cloned())
-        .collect();
-
-    let graph_nodes: Vec<GraphNode> = node_ids
-        .iter()
-        .map(|id| GraphNode {
-            id: id.clone(),
-            node_type: String::new(),
-            is_orphan: false,
-            highlighted: None,
-            parent: None,
-        })
-        .collect();
-
-    let (graph, node_map) = build_graph(&graph_nodes, edges);
-
-    let mut result: HashMap<String, usize> = HashMap::new();
-
-    for root in roots {
-        if let Some(&start_idx) = node_map.get(root) {
-            let view = match direction {
-                Direction::Outgoing => EitherGraph::Forward(&graph),
-                Direction::Incoming => EitherGraph::Reversed(Reversed(&graph)),
-            };
-
-            for (node_idx, distance) in view.run_dijkstra(start_idx) {
-                if max_distance.map(|limit| distance > limit).unwrap_or(false) {
-                    continue;
-                }
-
-                if let Some(node_id) = graph.node_weight(node_idx) {
-                    match result.get_mut(node_id) {
-                        Some(existing) if *existing <= distance => {}
-                        Some(existing) => *existing = distance,
-                        None => {
-                            result.insert(node_id.clone(), distance);
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    result
-}
-
-enum EitherGraph<'a> {
-    Forward(&'a Graph<String, ()>),
-    Reversed(Reversed<&'a Graph<String, ()>>),
-}
-
-impl<'a> EitherGraph<'a> {
-    fn run_dijkstra(&self, start: NodeIndex) -> HashMap<NodeIndex, usize> {
-        match self {
-            EitherGraph::Forward(graph) => dijkstra(*graph, start, None, |_| 1usize),
-            EitherGraph::Reversed(graph) => dijkstra(*graph, start, None, |_| 1usize),
-        }
-    }
+    get_related_nodes_with_distance(roots, edges, Relation::Dependents, max_distance)
 }
 
 #[cfg(test)]
@@ -278,6 +518,10 @@ mod tests {
         assert_eq!(distances.get("a"), Some(&0));
         assert_eq!(distances.get("b"), Some(&1));
         assert_eq!(distances.get("c"), Some(&2));
+
+        let weighted =
+            weighted_distances_from_node(&graph, a, |_, target| if target == "c" { 10 } else { 1 });
+        assert_eq!(weighted.get("c"), Some(&11));
     }
 
     #[test]
@@ -285,6 +529,11 @@ mod tests {
         let edges = vec![GraphEdge {
             source: "a".to_string(),
             target: "b".to_string(),
+            kind: None,
+            tags: None,
+            count: None,
+            change: None,
+            style: None,
         }];
 
         assert!(!is_orphan_node("a", &edges)); // has outgoing
@@ -298,10 +547,20 @@ mod tests {
             GraphEdge {
                 source: "main".to_string(),
                 target: "utils".to_string(),
+                kind: None,
+                tags: None,
+                count: None,
+                change: None,
+                style: None,
             },
             GraphEdge {
                 source: "utils".to_string(),
                 target: "base".to_string(),
+                kind: None,
+                tags: None,
+                count: None,
+                change: None,
+                style: None,
             },
         ];
 
@@ -318,10 +577,20 @@ mod tests {
             GraphEdge {
                 source: "main".to_string(),
                 target: "utils".to_string(),
+                kind: None,
+                tags: None,
+                count: None,
+                change: None,
+                style: None,
             },
             GraphEdge {
                 source: "app".to_string(),
                 target: "utils".to_string(),
+                kind: None,
+                tags: None,
+                count: None,
+                change: None,
+                style: None,
             },
         ];
 
@@ -0,0 +1,60 @@
+//! JSON Schemas for the payloads exchanged between the CLI, the WASM module, and the
+//! frontend. Field names follow the serde representation, so the schemas match the JSON
+//! on the wire (e.g. `showOrphans`, not `show_orphans`).
+
+use schemars::{JsonSchema, Schema};
+
+use crate::GraphData;
+use crate::filters::{FilterConfig, FilterPreset, FilterResult};
+
+/// Payloads with a published schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Payload {
+    /// Graph data embedded in the Cytoscape viewer and printed by `--format json`
+    GraphData,
+    /// Filter settings passed from the frontend to the WASM module
+    FilterConfig,
+    /// Visible and highlighted node IDs returned by the WASM module
+    FilterResult,
+    /// Named filter configuration exported and imported by the viewer
+    FilterPreset,
+}
+
+impl Payload {
+    pub const ALL: [Payload; 4] = [
+        Payload::GraphData,
+        Payload::FilterConfig,
+        Payload::FilterResult,
+        Payload::FilterPreset,
+    ];
+
+    /// Kebab-case name used on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            Payload::GraphData => "graph-data",
+            Payload::FilterConfig => "filter-config",
+            Payload::FilterResult => "filter-result",
+            Payload::FilterPreset => "filter-preset",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|payload| payload.name() == name)
+    }
+
+    /// JSON Schema (draft 2020-12) describing the payload
+    pub fn json_schema(self) -> Schema {
+        match self {
+            Payload::GraphData => schema_for::<GraphData>(),
+            Payload::FilterConfig => schema_for::<FilterConfig>(),
+            Payload::FilterResult => schema_for::<FilterResult>(),
+            Payload::FilterPreset => schema_for::<FilterPreset>(),
+        }
+    }
+}
+
+fn schema_for<T: JsonSchema>() -> Schema {
+    schemars::generate::SchemaSettings::draft2020_12()
+        .into_generator()
+        .into_root_schema_for::<T>()
+}
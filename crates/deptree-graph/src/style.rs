@@ -0,0 +1,236 @@
+//! Style hooks for the renderers
+//!
+//! A [`RenderStyle`] maps the metadata of nodes and edges to extra style attributes, so
+//! library consumers can change how a graph looks without rewriting the renderers. The
+//! DOT renderer adds the attributes after its own (`color`, `penwidth`, ...), the Mermaid
+//! renderer writes them as `style`/`linkStyle` statements (`stroke`, `fill`, ...), and
+//! Cytoscape graph data carries them in `GraphNode::style`/`GraphEdge::style` for the
+//! viewer to apply (`background-color`, `line-color`, ...). Hooks are callbacks, or
+//! declarative [`StyleRule`]s that can be read from JSON. Renderers take one in
+//! [`crate::RenderOptions::style`].
+
+use crate::filters::matches_pattern;
+use crate::{EdgeKind, Lifecycle};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Style attributes by name; each renderer has its own vocabulary
+pub type Style = BTreeMap<String, String>;
+
+/// The output a hook is asked to style
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Renderer {
+    Dot,
+    Mermaid,
+    Cytoscape,
+}
+
+/// A rendered module, as the hooks see it
+#[derive(Debug, Clone)]
+pub struct NodeInfo<'a> {
+    pub id: &'a str,
+    /// "module", "script", "namespace", or "extension"
+    pub node_type: &'static str,
+    pub highlighted: bool,
+    pub category: Option<&'a str>,
+    pub lifecycle: Option<Lifecycle>,
+    pub generated: bool,
+    pub annotations: Option<&'a BTreeMap<String, String>>,
+}
+
+/// A rendered edge, as the hooks see it
+#[derive(Debug, Clone)]
+pub struct EdgeInfo<'a> {
+    pub source: &'a str,
+    pub target: &'a str,
+    pub kind: EdgeKind,
+    /// How many times the source imports the target
+    pub count: usize,
+}
+
+type NodeHook = Arc<dyn Fn(Renderer, &NodeInfo) -> Style + Send + Sync>;
+type EdgeHook = Arc<dyn Fn(Renderer, &EdgeInfo) -> Style + Send + Sync>;
+
+/// Hooks adding style attributes to rendered nodes and edges. Hooks run in the order they
+/// were added, and a later hook's value for an attribute replaces an earlier one's.
+#[derive(Clone, Default)]
+pub struct RenderStyle {
+    node_hooks: Vec<NodeHook>,
+    edge_hooks: Vec<EdgeHook>,
+}
+
+impl std::fmt::Debug for RenderStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RenderStyle")
+            .field("node_hooks", &self.node_hooks.len())
+            .field("edge_hooks", &self.edge_hooks.len())
+            .finish()
+    }
+}
+
+impl RenderStyle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Style from declarative rules, applied in order
+    pub fn from_rules(rules: impl IntoIterator<Item = StyleRule>) -> Self {
+        rules.into_iter().fold(Self::new(), Self::with_rule)
+    }
+
+    pub fn with_node_hook(
+        mut self,
+        hook: impl Fn(Renderer, &NodeInfo) -> Style + Send + Sync + 'static,
+    ) -> Self {
+        self.node_hooks.push(Arc::new(hook));
+        self
+    }
+
+    pub fn with_edge_hook(
+        mut self,
+        hook: impl Fn(Renderer, &EdgeInfo) -> Style + Send + Sync + 'static,
+    ) -> Self {
+        self.edge_hooks.push(Arc::new(hook));
+        self
+    }
+
+    pub fn with_rule(self, rule: StyleRule) -> Self {
+        match rule {
+            StyleRule::Node {
+                renderer,
+                when,
+                style,
+            } => self.with_node_hook(move |target, node| {
+                if renderer.is_none_or(|r| r == target) && when.matches(node) {
+                    style.clone()
+                } else {
+                    Style::new()
+                }
+            }),
+            StyleRule::Edge {
+                renderer,
+                when,
+                style,
+            } => self.with_edge_hook(move |target, edge| {
+                if renderer.is_none_or(|r| r == target) && when.matches(edge) {
+                    style.clone()
+                } else {
+                    Style::new()
+                }
+            }),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.node_hooks.is_empty() && self.edge_hooks.is_empty()
+    }
+
+    pub fn node_style(&self, renderer: Renderer, node: &NodeInfo) -> Style {
+        let mut style = Style::new();
+        for hook in &self.node_hooks {
+            style.extend(hook(renderer, node));
+        }
+        style
+    }
+
+    pub fn edge_style(&self, renderer: Renderer, edge: &EdgeInfo) -> Style {
+        let mut style = Style::new();
+        for hook in &self.edge_hooks {
+            style.extend(hook(renderer, edge));
+        }
+        style
+    }
+}
+
+/// Attributes to set on the nodes or edges meeting every condition of `when`, e.g.
+/// `{"apply_to": "node", "renderer": "dot", "when": {"annotations": {"team": "payments"}},
+/// "style": {"color": "red"}}`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "apply_to", rename_all = "snake_case")]
+pub enum StyleRule {
+    Node {
+        /// Only for this renderer; all of them when absent
+        #[serde(default)]
+        renderer: Option<Renderer>,
+        #[serde(default)]
+        when: NodeMatch,
+        style: Style,
+    },
+    Edge {
+        #[serde(default)]
+        renderer: Option<Renderer>,
+        #[serde(default)]
+        when: EdgeMatch,
+        style: Style,
+    },
+}
+
+/// Conditions on a node; absent ones always hold
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NodeMatch {
+    /// Wildcard pattern for the module name (`app.*`, `*_pb2`)
+    pub id: Option<String>,
+    #[serde(rename = "type")]
+    pub node_type: Option<String>,
+    pub highlighted: Option<bool>,
+    pub category: Option<String>,
+    pub lifecycle: Option<Lifecycle>,
+    pub generated: Option<bool>,
+    /// Annotations the node must have, with these values
+    #[serde(default)]
+    pub annotations: BTreeMap<String, String>,
+}
+
+impl NodeMatch {
+    pub fn matches(&self, node: &NodeInfo) -> bool {
+        self.id
+            .as_deref()
+            .is_none_or(|pattern| matches_pattern(node.id, pattern))
+            && self
+                .node_type
+                .as_deref()
+                .is_none_or(|t| t == node.node_type)
+            && self.highlighted.is_none_or(|h| h == node.highlighted)
+            && self
+                .category
+                .as_deref()
+                .is_none_or(|c| node.category == Some(c))
+            && self.lifecycle.is_none_or(|l| node.lifecycle == Some(l))
+            && self.generated.is_none_or(|g| g == node.generated)
+            && self.annotations.iter().all(|(key, value)| {
+                node.annotations
+                    .and_then(|annotations| annotations.get(key))
+                    .is_some_and(|v| v == value)
+            })
+    }
+}
+
+/// Conditions on an edge; absent ones always hold
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EdgeMatch {
+    /// Wildcard pattern for the importing module
+    pub source: Option<String>,
+    /// Wildcard pattern for the imported module
+    pub target: Option<String>,
+    pub kind: Option<EdgeKind>,
+    /// Imported at least this many times
+    pub min_count: Option<usize>,
+}
+
+impl EdgeMatch {
+    pub fn matches(&self, edge: &EdgeInfo) -> bool {
+        self.source
+            .as_deref()
+            .is_none_or(|pattern| matches_pattern(edge.source, pattern))
+            && self
+                .target
+                .as_deref()
+                .is_none_or(|pattern| matches_pattern(edge.target, pattern))
+            && self.kind.is_none_or(|k| k == edge.kind)
+            && self.min_count.is_none_or(|n| edge.count >= n)
+    }
+}
@@ -0,0 +1,46 @@
+//! Tests for the precomputed transitive closure
+
+use deptree_graph::Relation;
+use deptree_graph::closure::ReachabilityClosure;
+use deptree_graph::csr::CompactGraph;
+
+/// 0 -> 1 <-> 2 -> 3, and 4 alone
+fn closure() -> ReachabilityClosure {
+    let graph = CompactGraph::new(
+        ["0", "1", "2", "3", "4"],
+        [("0", "1"), ("1", "2"), ("2", "1"), ("2", "3")],
+    );
+    ReachabilityClosure::build_within(&graph, usize::MAX).unwrap()
+}
+
+#[test]
+fn test_closure_follows_cycles_downwards() {
+    assert_eq!(
+        closure().reachable(&[1], Relation::Dependencies),
+        vec![1, 2, 3]
+    );
+}
+
+#[test]
+fn test_closure_follows_cycles_upwards() {
+    assert_eq!(
+        closure().reachable(&[3, 4], Relation::Dependents),
+        vec![0, 1, 2, 3, 4]
+    );
+}
+
+#[test]
+fn test_closure_estimate_counts_both_directions() {
+    // 100 singleton components, 2 words each, in both directions
+    assert_eq!(ReachabilityClosure::estimated_bytes(100, 100), 3200);
+}
+
+#[test]
+fn test_closure_respects_memory_budget() {
+    let ids: Vec<String> = (0..100).map(|i| i.to_string()).collect();
+    let graph = CompactGraph::new(ids.iter().map(String::as_str), []);
+
+    let built =
+        [3199, 3200].map(|budget| ReachabilityClosure::build_within(&graph, budget).is_some());
+    assert_eq!(built, [false, true]);
+}
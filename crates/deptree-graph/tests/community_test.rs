@@ -0,0 +1,34 @@
+//! Tests for the Louvain community detection
+
+use deptree_graph::community::{louvain, modularity};
+
+/// Two triangles joined by a single bridge edge
+const TRIANGLES: [(usize, usize, f64); 7] = [
+    (0, 1, 1.0),
+    (1, 2, 1.0),
+    (0, 2, 1.0),
+    (3, 4, 1.0),
+    (4, 5, 1.0),
+    (3, 5, 1.0),
+    (2, 3, 1.0),
+];
+
+#[test]
+fn test_louvain_separates_two_triangles() {
+    assert_eq!(louvain(6, &TRIANGLES), vec![0, 0, 0, 1, 1, 1]);
+}
+
+#[test]
+fn test_modularity_of_two_triangles() {
+    assert!(modularity(6, &TRIANGLES, &[0, 0, 0, 1, 1, 1]) > 0.3);
+}
+
+#[test]
+fn test_louvain_isolated_nodes_stay_alone() {
+    assert_eq!(louvain(3, &[]), vec![0, 1, 2]);
+}
+
+#[test]
+fn test_modularity_without_edges_is_zero() {
+    assert_eq!(modularity(3, &[], &[0, 1, 2]), 0.0);
+}
@@ -0,0 +1,72 @@
+//! Tests for the compact CSR adjacency
+
+use deptree_graph::Relation;
+use deptree_graph::csr::{CompactGraph, CsrAdjacency};
+
+/// `a` imports `b` twice and `c`, which imports `a` back
+fn interned() -> CompactGraph {
+    CompactGraph::new(["a", "b"], [("a", "b"), ("a", "c"), ("a", "b"), ("c", "a")])
+}
+
+#[test]
+fn test_compact_graph_interns_ids_in_first_seen_order() {
+    let graph = interned();
+    let ids: Vec<(u32, &str)> = (0..graph.node_count() as u32)
+        .map(|index| (index, graph.id(index)))
+        .collect();
+
+    assert_eq!(ids, [(0, "a"), (1, "b"), (2, "c")]);
+}
+
+#[test]
+fn test_compact_graph_dedups_edges_in_both_directions() {
+    let graph = interned();
+    let neighbors: Vec<&[u32]> = [Relation::Dependencies, Relation::Dependents]
+        .into_iter()
+        .flat_map(|relation| (0..3).map(move |index| (index, relation)))
+        .map(|(index, relation)| graph.neighbors(index, relation))
+        .collect();
+
+    assert_eq!(neighbors, [&[1, 2][..], &[], &[0], &[2], &[0], &[0]]);
+}
+
+#[test]
+fn test_bfs_starts_once_from_repeated_roots() {
+    let graph = interned();
+
+    assert_eq!(
+        graph.bfs(&[2, 2], Relation::Dependencies),
+        vec![(2, 0), (0, 1), (1, 2)]
+    );
+}
+
+#[test]
+fn test_dijkstra_uses_edge_costs_in_edge_orientation() {
+    // a -> b -> c costs 1 + 1, the direct a -> c costs 5
+    let graph = CompactGraph::new(["a", "b", "c"], [("a", "b"), ("b", "c"), ("a", "c")]);
+    let cost = |source: u32, target: u32| if (source, target) == (0, 2) { 5 } else { 1 };
+
+    let distances = [
+        graph.dijkstra(&[0], Relation::Dependencies, cost),
+        graph.dijkstra(&[2], Relation::Dependents, cost),
+    ];
+    assert_eq!(
+        distances,
+        [vec![(0, 0), (1, 1), (2, 2)], vec![(2, 0), (1, 1), (0, 2)]]
+    );
+}
+
+#[test]
+fn test_tarjan_runs_on_csr() {
+    let adjacency = CsrAdjacency::from_edges(4, &[(0, 1), (1, 0), (1, 2)]);
+    let mut components: Vec<Vec<u32>> = petgraph::algo::tarjan_scc(&adjacency)
+        .into_iter()
+        .map(|mut c| {
+            c.sort();
+            c
+        })
+        .collect();
+    components.sort();
+
+    assert_eq!(components, vec![vec![0, 1], vec![2], vec![3]]);
+}
@@ -0,0 +1,701 @@
+//! Tests for building, querying, rendering and serializing a `DependencyGraph`
+
+use deptree_graph::style::{RenderStyle, Renderer, Style, StyleRule};
+use deptree_graph::{
+    DEPENDENCY_GRAPH_VERSION, DependencyGraph, DotClustering, EdgeKind, GraphConfig, GraphId,
+    ImportCost, Lifecycle, MermaidEdgeLabels, NamespaceGrouping, OrphanScope, Reached,
+    RenderOptions, chain_to,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+struct Id(String);
+
+impl GraphId for Id {
+    fn to_dotted(&self) -> String {
+        self.0.clone()
+    }
+
+    fn segments(&self) -> Vec<String> {
+        self.0.split('.').map(String::from).collect()
+    }
+}
+
+impl std::str::FromStr for Id {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Id(s.to_string()))
+    }
+}
+
+fn id(name: &str) -> Id {
+    Id(name.to_string())
+}
+
+/// a -> b -> c -> d, plus a shortcut x -> c
+fn chain_graph() -> DependencyGraph<Id> {
+    graph(&[("a", "b"), ("b", "c"), ("c", "d"), ("x", "c")])
+}
+
+fn graph(edges: &[(&str, &str)]) -> DependencyGraph<Id> {
+    let mut graph = DependencyGraph::new();
+    for (from, to) in edges {
+        graph.add_dependency(id(from), id(to));
+    }
+    graph
+}
+
+/// Two subpackages under `app`: app has three children, app.api only two
+fn package_graph() -> DependencyGraph<Id> {
+    graph(&[
+        ("app.api.views", "app.core.db"),
+        ("app.api.auth", "app.core.db"),
+        ("app.cli", "app.api.views"),
+    ])
+}
+
+fn clusters(dot: &str) -> Vec<&str> {
+    dot.lines()
+        .filter_map(|line| line.trim().strip_prefix("subgraph cluster_"))
+        .map(|line| line.trim_end_matches(" {"))
+        .collect()
+}
+
+fn sorted(modules: impl IntoIterator<Item = Id>) -> Vec<String> {
+    let mut modules: Vec<String> = modules.into_iter().map(|module| module.0).collect();
+    modules.sort();
+    modules
+}
+
+fn distances(distances: HashMap<Id, usize>) -> BTreeMap<String, usize> {
+    distances
+        .into_iter()
+        .map(|(module, distance)| (module.0, distance))
+        .collect()
+}
+
+/// Every reached node with its distance and the chain leading to it
+fn chains(reached: &HashMap<Id, Reached<Id>>) -> BTreeMap<String, String> {
+    reached
+        .iter()
+        .map(|(module, how)| {
+            let chain = chain_to(reached, module).unwrap();
+            let chain: Vec<&str> = chain.iter().map(|module| module.0.as_str()).collect();
+            (
+                module.0.clone(),
+                format!("{}: {}", how.distance, chain.join(" -> ")),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_reachability_keeps_minimal_distance_over_roots() {
+    let upstream = chain_graph().find_upstream(&[id("a"), id("x"), id("a")], None);
+
+    insta::assert_debug_snapshot!(distances(upstream));
+}
+
+#[test]
+fn test_reachability_respects_max_rank() {
+    let graph = chain_graph();
+
+    assert!(graph.find_downstream(&[id("missing")], None).is_empty());
+    insta::assert_debug_snapshot!(distances(graph.find_downstream(&[id("d")], Some(2))));
+}
+
+#[test]
+fn test_reachability_with_parents_rebuilds_shortest_upstream_chains() {
+    let upstream = chain_graph().find_upstream_with_parents(&[id("a"), id("x")], None);
+
+    insta::assert_debug_snapshot!(chains(&upstream));
+}
+
+#[test]
+fn test_reachability_with_parents_respects_max_rank() {
+    let downstream = chain_graph().find_downstream_with_parents(&[id("c")], Some(1));
+
+    assert_eq!(chain_to(&downstream, &id("a")), None);
+    insta::assert_debug_snapshot!(chains(&downstream));
+}
+
+#[test]
+fn test_weighted_reachability_bounds_weighted_distance() {
+    // Imports of `c` cost 3, every other import 1
+    let cost = |_: &Id, to: &Id| if *to == id("c") { 3 } else { 1 };
+    let upstream = chain_graph().find_upstream_weighted(&[id("a")], Some(4), cost);
+
+    insta::assert_debug_snapshot!(chains(&upstream));
+}
+
+#[test]
+fn test_unit_weighted_reachability_matches_unweighted() {
+    let graph = chain_graph();
+    let downstream = graph.find_downstream_weighted(&[id("d")], None, |_, _| 1);
+
+    let weighted: HashMap<Id, usize> = downstream
+        .iter()
+        .map(|(module, reached)| (module.clone(), reached.distance))
+        .collect();
+    assert_eq!(weighted, graph.find_downstream(&[id("d")], None));
+    insta::assert_debug_snapshot!(chains(&downstream));
+}
+
+#[test]
+fn test_direct_neighbors_are_sorted_by_name() {
+    let mut graph = chain_graph();
+    graph.add_dependency_with_kind(id("x"), id("c"), EdgeKind::Deferred);
+    graph.add_dependency_with_kind(id("d"), id("d"), EdgeKind::Deferred);
+
+    assert!(graph.direct_dependencies(&id("missing")).is_empty());
+    let names = |modules: Vec<&Id>| {
+        let names: Vec<&str> = modules.iter().map(|module| module.0.as_str()).collect();
+        names.join(", ")
+    };
+    let neighbors: Vec<String> = ["c", "d"]
+        .into_iter()
+        .map(|module| {
+            format!(
+                "{module}: imported by [{}], imports [{}]",
+                names(graph.direct_dependents(&id(module))),
+                names(graph.direct_dependencies(&id(module)))
+            )
+        })
+        .collect();
+    insta::assert_debug_snapshot!(neighbors);
+}
+
+#[test]
+fn test_edges_merge_repeated_imports() {
+    let mut graph = chain_graph();
+    graph.add_dependency_with_kind(id("x"), id("c"), EdgeKind::Deferred);
+    graph.add_dependency_with_kind(id("d"), id("d"), EdgeKind::Deferred);
+
+    assert_eq!(graph.node_count(), 5);
+    assert_eq!(graph.edge_count(), 5);
+    let edges: Vec<(&str, &str, EdgeKind)> = graph
+        .edges()
+        .map(|(from, to, kind)| (from.0.as_str(), to.0.as_str(), kind))
+        .collect();
+    insta::assert_debug_snapshot!(edges);
+}
+
+/// main reaches db through the cycle svc.a <-> svc.b
+fn cyclic_graph() -> DependencyGraph<Id> {
+    graph(&[
+        ("main", "util"),
+        ("main", "cli"),
+        ("cli", "svc.a"),
+        ("svc.a", "svc.b"),
+        ("svc.b", "svc.a"),
+        ("svc.b", "db"),
+        ("db", "driver"),
+    ])
+}
+
+fn joined(chain: Vec<Id>) -> String {
+    chain
+        .into_iter()
+        .map(|module| module.0)
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+#[test]
+fn test_longest_chain_walks_through_cycles() {
+    let graph = cyclic_graph();
+
+    assert_eq!(graph.longest_chain(&id("driver")), Some(vec![id("driver")]));
+    assert_eq!(graph.longest_chain(&id("missing")), None);
+    insta::assert_debug_snapshot!(graph.longest_chain(&id("main")).map(joined));
+}
+
+#[test]
+fn test_import_depths_count_a_cycle_as_one_step() {
+    // Both members of the cycle are one step above db
+    insta::assert_debug_snapshot!(distances(cyclic_graph().import_depths()));
+}
+
+/// a reaches b only through the namespace package ns; c's namespace leads nowhere
+fn namespace_graph() -> DependencyGraph<Id> {
+    let mut graph = graph(&[("a", "ns"), ("ns", "b"), ("c", "empty_ns")]);
+    graph.ensure_node(id("d"));
+    graph.mark_as_namespace_package(&id("ns"));
+    graph.mark_as_namespace_package(&id("empty_ns"));
+    graph
+}
+
+#[test]
+fn test_orphans_bridge_hidden_namespace_packages() {
+    let graph = namespace_graph();
+
+    insta::assert_debug_snapshot!(sorted(graph.orphans_in(OrphanScope::Graph, false)));
+}
+
+#[test]
+fn test_orphans_with_namespace_packages_shown() {
+    let graph = namespace_graph();
+
+    insta::assert_debug_snapshot!(sorted(graph.orphans_in(OrphanScope::Graph, true)));
+}
+
+#[test]
+fn test_orphans_within_a_subgraph() {
+    let graph = namespace_graph();
+    let view: HashSet<Id> = [id("a"), id("c")].into_iter().collect();
+
+    insta::assert_debug_snapshot!(sorted(
+        graph.orphans_in(OrphanScope::Subgraph(&view), false)
+    ));
+}
+
+#[test]
+fn test_orphans_are_left_out_of_dot() {
+    insta::assert_snapshot!(namespace_graph().to_dot(false, false, &RenderOptions::default()));
+}
+
+#[test]
+fn test_orphans_are_flagged_in_graph_data() {
+    let data = namespace_graph().to_cytoscape_graph_data(true, false, &RenderOptions::default());
+
+    let flagged: Vec<&str> = data
+        .nodes
+        .iter()
+        .filter(|node| node.is_orphan)
+        .map(|node| node.id.as_str())
+        .collect();
+    insta::assert_debug_snapshot!(flagged);
+}
+
+fn grouped(min_children: usize, max_depth: Option<usize>) -> RenderOptions {
+    RenderOptions {
+        namespace_grouping: NamespaceGrouping {
+            min_children,
+            max_depth,
+        },
+        ..RenderOptions::default()
+    }
+}
+
+#[test]
+fn test_namespace_grouping_clusters_every_package_by_default() {
+    let dot = package_graph().to_dot(false, false, &RenderOptions::default());
+
+    insta::assert_debug_snapshot!(clusters(&dot));
+}
+
+#[test]
+fn test_namespace_grouping_threshold() {
+    let dot = package_graph().to_dot(false, false, &grouped(3, None));
+
+    insta::assert_debug_snapshot!(clusters(&dot));
+}
+
+#[test]
+fn test_namespace_grouping_depth() {
+    let dot = package_graph().to_dot(false, false, &grouped(1, Some(1)));
+
+    insta::assert_debug_snapshot!(clusters(&dot));
+}
+
+/// `app` is both a module and the package holding the rest
+fn package_module_graph() -> DependencyGraph<Id> {
+    graph(&[
+        ("app.cli", "app"),
+        ("app.cli", "app.api.views"),
+        ("app.api.views", "app.core.db"),
+        ("app.api.auth", "app.core.db"),
+    ])
+}
+
+#[test]
+fn test_package_hierarchy_matches_across_modes() {
+    let graph = package_module_graph();
+    let all: HashSet<Id> = graph.nodes().cloned().collect();
+    let highlighted: HashSet<Id> = [id("app.cli")].into_iter().collect();
+    let options = RenderOptions::default();
+
+    let parents: Vec<BTreeMap<String, String>> = [
+        graph.to_cytoscape_graph_data(false, false, &options),
+        graph.to_cytoscape_graph_data_filtered(&all, false, false, &options),
+        graph.to_cytoscape_graph_data_highlighted(&highlighted, false, false, &options),
+    ]
+    .into_iter()
+    .map(|data| {
+        data.nodes
+            .into_iter()
+            .filter_map(|node| Some((node.id, node.parent?)))
+            .collect()
+    })
+    .collect();
+    assert!(parents.iter().all(|mode| *mode == parents[0]));
+    insta::assert_debug_snapshot!(parents[0]);
+}
+
+#[test]
+fn test_package_module_is_drawn_as_its_dot_cluster() {
+    let graph = package_module_graph();
+    let all: HashSet<Id> = graph.nodes().cloned().collect();
+    let highlighted: HashSet<Id> = [id("app.cli")].into_iter().collect();
+    let options = RenderOptions::default();
+
+    let dot = graph.to_dot(false, false, &options);
+    for other in [
+        graph.to_dot_filtered(&all, false, false, &options),
+        graph.to_dot_highlighted_clustered(
+            &highlighted,
+            false,
+            false,
+            DotClustering::PACKAGES,
+            &options,
+        ),
+    ] {
+        assert_eq!(clusters(&other), clusters(&dot));
+        assert!(other.contains("\"app.cli\" -> \"app.core.db\""), "{other}");
+        assert!(!other.contains("-> \"app\""), "{other}");
+    }
+    insta::assert_snapshot!(dot);
+}
+
+#[test]
+fn test_package_module_is_drawn_as_its_mermaid_subgraph() {
+    let graph = package_module_graph();
+    let all: HashSet<Id> = graph.nodes().cloned().collect();
+    let highlighted: HashSet<Id> = [id("app.cli")].into_iter().collect();
+    let options = RenderOptions::default();
+
+    let mermaid = graph.to_mermaid(false, false, &options);
+    for other in [
+        graph.to_mermaid_filtered(&all, false, false, &options),
+        graph.to_mermaid_highlighted(&highlighted, false, false, &options),
+    ] {
+        assert!(!other.contains("app(\"app\")"), "{other}");
+        assert!(
+            other.contains("app_cli(\"app.cli\") --> app_2\n"),
+            "{other}"
+        );
+    }
+    insta::assert_snapshot!(mermaid);
+}
+
+fn styled_options() -> RenderOptions {
+    let rules: Vec<StyleRule> = serde_json::from_value(serde_json::json!([
+        {"apply_to": "node", "renderer": "dot", "when": {"annotations": {"team": "payments"}},
+         "style": {"color": "red"}},
+        {"apply_to": "node", "renderer": "mermaid", "when": {"id": "b"},
+         "style": {"stroke": "#f00"}},
+        {"apply_to": "edge", "when": {"target": "c"}, "style": {"color": "gray"}},
+    ]))
+    .unwrap();
+    // Callbacks run after the rules and override them
+    let style = RenderStyle::from_rules(rules).with_edge_hook(|renderer, edge| {
+        if renderer == Renderer::Cytoscape && edge.source == "x" {
+            Style::from([("color".to_string(), "blue".to_string())])
+        } else {
+            Style::new()
+        }
+    });
+    RenderOptions {
+        style,
+        ..RenderOptions::default()
+    }
+}
+
+fn styled_graph() -> DependencyGraph<Id> {
+    let mut graph = chain_graph();
+    graph.set_annotation(&id("b"), "team", "payments");
+    graph
+}
+
+#[test]
+fn test_render_style_reaches_dot() {
+    insta::assert_snapshot!(styled_graph().to_dot(false, false, &styled_options()));
+}
+
+#[test]
+fn test_render_style_reaches_mermaid() {
+    insta::assert_snapshot!(styled_graph().to_mermaid(false, false, &styled_options()));
+}
+
+#[test]
+fn test_render_style_hooks_override_rules_in_cytoscape_data() {
+    let data = styled_graph().to_cytoscape_graph_data(false, false, &styled_options());
+
+    assert!(data.nodes.iter().all(|node| node.style.is_none()));
+    let styles: Vec<(&str, &str, Option<&Style>)> = data
+        .edges
+        .iter()
+        .map(|edge| {
+            (
+                edge.source.as_str(),
+                edge.target.as_str(),
+                edge.style.as_ref(),
+            )
+        })
+        .collect();
+    insta::assert_debug_snapshot!(styles);
+}
+
+/// app.main imports app.db three times, once deferred
+fn repeated_import_graph() -> DependencyGraph<Id> {
+    let mut graph = graph(&[
+        ("app.main", "app.db"),
+        ("app.main", "app.db"),
+        ("app.main", "app.api"),
+        ("app.api", "lib.http"),
+        ("app.db", "lib.http"),
+    ]);
+    graph.add_dependency_with_kind(id("app.main"), id("app.db"), EdgeKind::Deferred);
+    graph
+}
+
+fn counted() -> RenderOptions {
+    RenderOptions {
+        show_edge_counts: true,
+        ..RenderOptions::default()
+    }
+}
+
+/// Every edge with its multiplicity, sorted by its endpoints
+fn multiplicities(graph: &DependencyGraph<Id>) -> BTreeSet<(String, String, usize)> {
+    graph
+        .edges()
+        .map(|(from, to, _)| {
+            (
+                from.0.clone(),
+                to.0.clone(),
+                graph.edge_multiplicity(from, to),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_edge_multiplicity_counts_repeated_imports() {
+    let graph = repeated_import_graph();
+
+    assert_eq!(graph.edge_multiplicity(&id("app.db"), &id("app.main")), 0);
+    insta::assert_debug_snapshot!(multiplicities(&graph));
+}
+
+#[test]
+fn test_edge_multiplicity_adds_up_when_aggregating() {
+    let packages = repeated_import_graph().map_ids(|module| id(&module.segments()[0]));
+
+    insta::assert_debug_snapshot!(multiplicities(&packages));
+}
+
+#[test]
+fn test_edge_multiplicity_survives_serialization() {
+    let graph = repeated_import_graph();
+    let data = graph.to_cytoscape_graph_data(false, false, &RenderOptions::default());
+    let rebuilt = DependencyGraph::<Id>::from_graph_data(&data).unwrap();
+
+    assert_eq!(multiplicities(&rebuilt), multiplicities(&graph));
+    insta::assert_debug_snapshot!(multiplicities(&round_trip(&graph)));
+}
+
+#[test]
+fn test_edge_counts_are_only_labeled_on_request() {
+    let graph = repeated_import_graph();
+
+    assert!(
+        !graph
+            .to_dot(false, false, &RenderOptions::default())
+            .contains("label=\"\u{d7}")
+    );
+    insta::assert_snapshot!(graph.to_dot(false, false, &counted()));
+}
+
+#[test]
+fn test_edge_counts_label_mermaid_edges() {
+    insta::assert_snapshot!(repeated_import_graph().to_mermaid(false, false, &counted()));
+}
+
+#[test]
+fn test_edge_counts_join_mermaid_kind_labels() {
+    let labeled = RenderOptions {
+        mermaid_edge_labels: MermaidEdgeLabels {
+            kind: true,
+            ..MermaidEdgeLabels::default()
+        },
+        ..counted()
+    };
+
+    insta::assert_snapshot!(repeated_import_graph().to_mermaid(false, false, &labeled));
+}
+
+#[test]
+fn test_edge_counts_in_graph_data() {
+    let data =
+        repeated_import_graph().to_cytoscape_graph_data(false, false, &RenderOptions::default());
+
+    let counts: Vec<(&str, &str, Option<usize>)> = data
+        .edges
+        .iter()
+        .map(|edge| (edge.source.as_str(), edge.target.as_str(), edge.count))
+        .collect();
+    insta::assert_debug_snapshot!(counts);
+}
+
+/// A graph exercising every attribute the serialized form carries
+fn attributed_graph() -> DependencyGraph<Id> {
+    let mut graph = DependencyGraph::new();
+    graph.add_dependency(id("app.main"), id("app.db"));
+    graph.add_dependency_with_kind(id("app.main"), id("app.db"), EdgeKind::Deferred);
+    graph.add_dependency_with_kind(id("run"), id("ns.util"), EdgeKind::Deferred);
+    graph.ensure_node(id("ns"));
+    graph.mark_as_script(&id("run"));
+    graph.mark_as_namespace_package(&id("ns"));
+    graph.tag_edge(&id("app.main"), &id("app.db"), "platform:win32");
+    graph.set_import_cost(
+        &id("app.db"),
+        ImportCost {
+            self_us: 10,
+            cumulative_us: 25,
+        },
+    );
+    graph.set_lifecycle(&id("ns.util"), Lifecycle::Sunset);
+    graph.set_annotation(&id("app.main"), "team", "core");
+    graph
+}
+
+fn round_trip(graph: &DependencyGraph<Id>) -> DependencyGraph<Id> {
+    serde_json::from_str(&serde_json::to_string(graph).unwrap()).unwrap()
+}
+
+#[test]
+fn test_serde_round_trip_keeps_attributes_and_order() {
+    let graph = attributed_graph();
+    let json = serde_json::to_string_pretty(&graph).unwrap();
+
+    assert_eq!(
+        serde_json::to_string_pretty(&round_trip(&graph)).unwrap(),
+        json
+    );
+    insta::assert_snapshot!(json);
+}
+
+#[test]
+fn test_serde_round_trip_renders_the_same() {
+    let graph = attributed_graph();
+    let restored = round_trip(&graph);
+
+    assert_eq!(
+        serde_json::to_value(restored.to_cytoscape_graph_data(
+            true,
+            true,
+            &RenderOptions::default()
+        ))
+        .unwrap(),
+        serde_json::to_value(graph.to_cytoscape_graph_data(true, true, &RenderOptions::default()))
+            .unwrap()
+    );
+    let dot = restored.to_dot(true, true, &RenderOptions::default());
+    assert_eq!(dot, graph.to_dot(true, true, &RenderOptions::default()));
+    insta::assert_snapshot!(dot);
+}
+
+#[test]
+fn test_serde_round_trip_keeps_the_strongest_edge_kind() {
+    let restored = round_trip(&attributed_graph());
+
+    insta::assert_debug_snapshot!(restored.edge_kind(&id("app.main"), &id("app.db")));
+}
+
+#[test]
+fn test_serde_rejects_dangling_edges() {
+    let dangling = serde_json::json!({
+        "version": 1,
+        "nodes": [{"id": "a"}],
+        "edges": [{"from": "a", "to": "b"}],
+    });
+    let Err(error) = serde_json::from_value::<DependencyGraph<Id>>(dangling) else {
+        panic!("dangling edge accepted");
+    };
+
+    insta::assert_snapshot!(error.to_string());
+}
+
+#[test]
+fn test_serde_rejects_newer_versions() {
+    let newer = serde_json::json!({
+        "version": DEPENDENCY_GRAPH_VERSION + 1,
+        "nodes": [],
+        "edges": [],
+    });
+    let Err(error) = serde_json::from_value::<DependencyGraph<Id>>(newer) else {
+        panic!("newer version accepted");
+    };
+
+    insta::assert_snapshot!(error.to_string());
+}
+
+/// A graph whose graph data carries scripts, tags, coverage and annotations
+fn graph_data_graph() -> DependencyGraph<Id> {
+    let mut graph = DependencyGraph::new();
+    graph.add_dependency(id("app.main"), id("app.db"));
+    graph.add_dependency(id("app.main"), id("app.api"));
+    graph.add_dependency_with_kind(id("run"), id("app.db"), EdgeKind::Deferred);
+    graph.ensure_node(id("lonely"));
+    graph.mark_as_script(&id("run"));
+    graph.tag_edge(&id("run"), &id("app.db"), "platform:win32");
+    graph.set_coverage(&id("app.db"), 87.5);
+    graph.set_annotation(&id("app.main"), "team", "core");
+    graph
+}
+
+fn graph_data_config() -> GraphConfig {
+    GraphConfig {
+        include_orphans: true,
+        include_namespaces: false,
+        highlighted_modules: Some(vec!["app.db".to_string()]),
+        presets: None,
+    }
+}
+
+#[test]
+fn test_graph_data_carries_config_and_namespace_groups() {
+    let data = graph_data_graph().to_graph_data(&graph_data_config(), &RenderOptions::default());
+
+    insta::assert_snapshot!(serde_json::to_string_pretty(&data).unwrap());
+}
+
+#[test]
+fn test_graph_data_round_trip() {
+    let config = graph_data_config();
+    let data = graph_data_graph().to_graph_data(&config, &RenderOptions::default());
+    let restored = DependencyGraph::<Id>::from_graph_data(&data).unwrap();
+
+    assert_eq!(
+        serde_json::to_value(restored.to_graph_data(&config, &RenderOptions::default())).unwrap(),
+        serde_json::to_value(&data).unwrap()
+    );
+    insta::assert_snapshot!(serde_json::to_string_pretty(&restored).unwrap());
+}
+
+#[test]
+fn test_graph_data_matches_highlighted_cytoscape_data() {
+    let graph = graph_data_graph();
+    let data = graph.to_graph_data(&graph_data_config(), &RenderOptions::default());
+
+    assert_eq!(
+        serde_json::to_value(&data).unwrap(),
+        serde_json::to_value(graph.to_cytoscape_graph_data_highlighted(
+            &[id("app.db")].into_iter().collect(),
+            true,
+            false,
+            &RenderOptions::default()
+        ))
+        .unwrap()
+    );
+    let highlighted: Vec<&str> = data
+        .nodes
+        .iter()
+        .filter(|node| node.highlighted == Some(true))
+        .map(|node| node.id.as_str())
+        .collect();
+    insta::assert_debug_snapshot!(highlighted);
+}
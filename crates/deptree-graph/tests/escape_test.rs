@@ -0,0 +1,241 @@
+//! Tests for the escaping of node names: every name survives its output format
+
+use deptree_graph::escape::{
+    IdentifierAssigner, dot_string, identifier, json_for_html, mermaid_label, xml_attribute,
+};
+use deptree_graph::{DependencyGraph, GraphId, RenderOptions};
+use proptest::prelude::*;
+use std::collections::HashSet;
+
+/// Read back a DOT string body the way Graphviz's lexer does
+fn unescape_dot(escaped: &str) -> String {
+    let mut value = String::new();
+    let mut chars = escaped.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('r') => value.push('\r'),
+            Some(other) => value.push(other),
+            None => value.push('\\'),
+        }
+    }
+    value
+}
+
+fn unescape_entities(escaped: &str, entities: &[(&str, &str)]) -> String {
+    let mut value = escaped.to_string();
+    // `&`/`#` first in the escaper, so last here
+    for (entity, raw) in entities.iter().rev() {
+        value = value.replace(entity, raw);
+    }
+    value
+}
+
+/// Characters allowed in an XML 1.0 document
+fn is_xml_char(c: char) -> bool {
+    matches!(c, '\t' | '\n' | '\r' | '\u{20}'..='\u{D7FF}' | '\u{E000}'..='\u{FFFD}' | '\u{10000}'..)
+}
+
+/// Control characters are replaced rather than escaped by the text formats
+fn without_controls(value: &str, keep: &[char]) -> String {
+    value
+        .chars()
+        .map(|c| {
+            if c.is_control() && !keep.contains(&c) {
+                ' '
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Whether every `"` in a DOT line is escaped or delimits a string
+fn quoted_strings(line: &str) -> Option<Vec<String>> {
+    let mut strings = Vec::new();
+    let mut current: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (&mut current, c) {
+            (None, '"') => current = Some(String::new()),
+            (None, _) => {}
+            (Some(s), '\\') => {
+                s.push('\\');
+                s.push(chars.next()?);
+            }
+            (Some(s), '"') => {
+                strings.push(std::mem::take(s));
+                current = None;
+            }
+            (Some(s), c) => s.push(c),
+        }
+    }
+    current.is_none().then_some(strings)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Name(Vec<String>);
+
+impl GraphId for Name {
+    fn to_dotted(&self) -> String {
+        self.0.join(".")
+    }
+
+    fn segments(&self) -> Vec<String> {
+        self.0.clone()
+    }
+}
+
+fn graph_of(names: &[String]) -> DependencyGraph<Name> {
+    let mut graph = DependencyGraph::new();
+    let ids: Vec<Name> = names
+        .iter()
+        .map(|n| Name(vec!["pkg".to_string(), n.clone()]))
+        .collect();
+    for pair in ids.windows(2) {
+        graph.add_dependency(pair[0].clone(), pair[1].clone());
+    }
+    for id in ids {
+        graph.ensure_node(id);
+    }
+    graph
+}
+
+proptest! {
+    #[test]
+    fn dot_string_round_trips(value in any::<String>()) {
+        let escaped = dot_string(&value);
+        prop_assert!(!escaped.contains('\n'));
+        prop_assert_eq!(
+            unescape_dot(&escaped),
+            without_controls(&value, &['\n', '\r'])
+        );
+        prop_assert_eq!(quoted_strings(&format!("\"{escaped}\"")).map(|s| s.len()), Some(1));
+    }
+
+    #[test]
+    fn mermaid_label_round_trips(value in any::<String>()) {
+        let escaped = mermaid_label(&value);
+        prop_assert!(!escaped.contains(['"', '<', '>', '\n']));
+        let entities = [
+            ("#amp;", "&"),
+            ("#35;", "#"),
+            ("#quot;", "\""),
+            ("#lt;", "<"),
+            ("#gt;", ">"),
+        ];
+        prop_assert_eq!(
+            unescape_entities(&escaped, &entities),
+            without_controls(&value, &[])
+        );
+    }
+
+    #[test]
+    fn assigned_identifiers_are_unique(names in prop::collection::vec(any::<String>(), 0..12)) {
+        let mut ids = IdentifierAssigner::new();
+        let assigned: Vec<String> = names.iter().map(|name| ids.assign(name)).collect();
+        let unique: HashSet<&String> = assigned.iter().collect();
+        prop_assert_eq!(unique.len(), names.len());
+    }
+
+    #[test]
+    fn identifiers_are_plain_ascii(value in any::<String>()) {
+        let id = identifier(&value);
+        prop_assert!(!id.is_empty());
+        prop_assert!(id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+        // Sanitized ids only change again when they are Mermaid keywords
+        prop_assert_eq!(identifier(&id), id);
+    }
+
+    #[test]
+    fn xml_attribute_stays_well_formed(value in any::<String>()) {
+        let escaped = xml_attribute(&value);
+        prop_assert!(!escaped.contains(['<', '>', '"', '\n']));
+        prop_assert!(escaped.chars().all(is_xml_char));
+        let entities = [
+            ("&amp;", "&"),
+            ("&lt;", "<"),
+            ("&gt;", ">"),
+            ("&quot;", "\""),
+            ("&#10;", "\n"),
+            ("&#13;", "\r"),
+            ("&#9;", "\t"),
+        ];
+        let expected: String = value.chars().filter(|&c| is_xml_char(c)).collect();
+        prop_assert_eq!(unescape_entities(&escaped, &entities), expected);
+    }
+
+    #[test]
+    fn json_for_html_keeps_the_value(value in any::<String>()) {
+        let json = serde_json::to_string(&value).unwrap();
+        let escaped = json_for_html(&json);
+        prop_assert!(!escaped.contains(['<', '>', '&']));
+        let decoded: String = serde_json::from_str(&escaped).unwrap();
+        prop_assert_eq!(decoded, value);
+    }
+
+    #[test]
+    fn dot_output_quotes_every_name(names in prop::collection::vec(any::<String>(), 1..6)) {
+        let dot = graph_of(&names).to_dot(true, false, &RenderOptions::default());
+        let mut found = Vec::new();
+        for line in dot.lines() {
+            let strings = quoted_strings(line);
+            prop_assert!(strings.is_some(), "unbalanced quotes in {line:?}");
+            found.extend(strings.unwrap().iter().map(|s| unescape_dot(s)));
+        }
+        for name in &names {
+            let expected = without_controls(&format!("pkg.{name}"), &['\n', '\r']);
+            prop_assert!(found.contains(&expected), "{expected:?} missing from {dot}");
+        }
+    }
+
+    #[test]
+    fn mermaid_output_has_one_statement_per_line(
+        names in prop::collection::vec(any::<String>(), 1..6)
+    ) {
+        let mermaid = graph_of(&names).to_mermaid(true, false, &RenderOptions::default());
+        for line in mermaid.lines() {
+            prop_assert_eq!(line.matches('"').count() % 2, 0, "unbalanced quotes in {:?}", line);
+        }
+        for name in &names {
+            let label = mermaid_label(&format!("pkg.{name}"));
+            prop_assert!(mermaid.contains(&format!("\"{label}\"")), "{label:?} missing");
+        }
+    }
+}
+
+#[test]
+fn test_assigner_disambiguates_colliding_names() {
+    let mut ids = IdentifierAssigner::new();
+    // A name that sanitizes to an earlier suffixed id moves on as well
+    let assigned: Vec<String> = ["a.b_c", "a_b.c", "a_b_c_2", "a-b-c", "end", "end_"]
+        .into_iter()
+        .map(|name| format!("{name} -> {}", ids.assign(name)))
+        .collect();
+
+    insta::assert_debug_snapshot!(assigned);
+}
+
+#[test]
+fn test_mermaid_keeps_colliding_modules_apart() {
+    let name = |segments: &[&str]| Name(segments.iter().map(|s| s.to_string()).collect());
+    let mut graph = DependencyGraph::new();
+    graph.add_dependency(name(&["a", "b_c"]), name(&["a_b", "c"]));
+    graph.add_dependency(name(&["a_b", "c"]), name(&["a_b_c"]));
+
+    insta::assert_snapshot!(graph.to_mermaid(false, false, &RenderOptions::default()));
+}
+
+#[test]
+fn test_identifier_avoids_mermaid_keywords() {
+    let ids: Vec<String> = ["end", "End", "", "pkg.mod-1", "模块"]
+        .into_iter()
+        .map(|name| format!("{name:?} -> {}", identifier(name)))
+        .collect();
+
+    insta::assert_debug_snapshot!(ids);
+}
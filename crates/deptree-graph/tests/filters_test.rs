@@ -0,0 +1,83 @@
+//! Tests for the viewer filter configuration and its shareable encoding
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use deptree_graph::filters::{EdgeDirection, FILTER_STATE_VERSION, FilterConfig};
+
+fn sample_config() -> FilterConfig {
+    FilterConfig {
+        show_orphans: false,
+        show_namespaces: true,
+        exclude_patterns: vec!["*test*".to_string()],
+        upstream_roots: vec!["app.api".to_string()],
+        downstream_roots: vec![],
+        max_distance: Some(2),
+        highlighted_only: true,
+        highlight_neighbors: 1,
+        fade_filtered: false,
+        edge_direction: EdgeDirection::Dependencies,
+        weight_key: None,
+    }
+}
+
+fn decode_error(encoded: &str) -> String {
+    let Err(error) = FilterConfig::decode(encoded) else {
+        panic!("{encoded:?} decoded");
+    };
+    error.to_string()
+}
+
+#[test]
+fn test_filter_state_roundtrip() {
+    let encoded = sample_config().encode();
+
+    assert!(
+        encoded
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    );
+    let decoded = FilterConfig::decode(&encoded).unwrap();
+    assert_eq!(decoded, sample_config());
+    insta::assert_debug_snapshot!(decoded);
+}
+
+#[test]
+fn test_filter_state_rejects_invalid_base64() {
+    insta::assert_snapshot!(decode_error("not base64!"));
+}
+
+#[test]
+fn test_filter_state_rejects_empty_input() {
+    insta::assert_snapshot!(decode_error(""));
+}
+
+#[test]
+fn test_filter_state_rejects_unsupported_versions() {
+    let mut bytes = URL_SAFE_NO_PAD.decode(sample_config().encode()).unwrap();
+    bytes[0] = 9;
+
+    insta::assert_snapshot!(decode_error(&URL_SAFE_NO_PAD.encode(&bytes)));
+}
+
+#[test]
+fn test_filter_state_rejects_corrupted_data() {
+    let corrupted = URL_SAFE_NO_PAD.encode([FILTER_STATE_VERSION, 0xff, 0x00]);
+
+    insta::assert_snapshot!(decode_error(&corrupted));
+}
+
+#[test]
+fn test_edge_direction_reads_old_names() {
+    assert_eq!(
+        serde_json::to_string(&EdgeDirection::Dependents).unwrap(),
+        "\"dependents\""
+    );
+    let parsed: Vec<(&str, EdgeDirection)> = ["upstream", "downstream"]
+        .into_iter()
+        .map(|name| {
+            let direction = serde_json::from_str(&format!("\"{name}\"")).unwrap();
+            (name, direction)
+        })
+        .collect();
+    insta::assert_debug_snapshot!(parsed);
+}
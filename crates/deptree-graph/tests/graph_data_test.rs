@@ -0,0 +1,112 @@
+//! Tests for the GraphData payload shared with the viewer and the distances over it
+
+use std::collections::BTreeMap;
+
+use deptree_graph::{
+    DistanceComputation, GRAPH_DATA_VERSION, GraphData, GraphEdge, GraphNode, compute_all_distances,
+};
+
+fn from_json_error(json: &str) -> String {
+    let Err(error) = GraphData::from_json(json) else {
+        panic!("{json} accepted");
+    };
+    error.to_string()
+}
+
+/// a -> b -> c
+fn chain() -> (Vec<GraphNode>, Vec<GraphEdge>) {
+    let nodes = ["a", "b", "c"]
+        .iter()
+        .map(|id| GraphNode {
+            id: id.to_string(),
+            node_type: "module".to_string(),
+            is_orphan: false,
+            highlighted: None,
+            parent: None,
+            import_time_us: None,
+            coverage: None,
+            category: None,
+            lifecycle: None,
+            generated: None,
+            change: None,
+            annotations: None,
+            position: None,
+            style: None,
+            href: None,
+        })
+        .collect();
+    let edges = [("a", "b"), ("b", "c")]
+        .iter()
+        .map(|(source, target)| GraphEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            kind: None,
+            tags: None,
+            count: None,
+            change: None,
+            style: None,
+        })
+        .collect();
+    (nodes, edges)
+}
+
+#[test]
+fn test_distance_computation_reports_progress_in_chunks() {
+    let (nodes, edges) = chain();
+    let mut computation = DistanceComputation::new(&nodes, &edges);
+
+    let mut steps = Vec::new();
+    loop {
+        let finished = computation.step(2);
+        steps.push(format!(
+            "{}/{} sources, finished: {finished}",
+            computation.processed(),
+            computation.total()
+        ));
+        if finished {
+            break;
+        }
+    }
+    insta::assert_debug_snapshot!(steps);
+}
+
+#[test]
+fn test_distance_computation_in_chunks_matches_all_distances() {
+    let (nodes, edges) = chain();
+    let mut computation = DistanceComputation::new(&nodes, &edges);
+    while !computation.step(2) {}
+
+    let distances = computation.into_distances();
+    assert_eq!(distances, compute_all_distances(&nodes, &edges));
+    // Unreachable pairs are left out
+    let sorted: BTreeMap<String, BTreeMap<String, usize>> = distances
+        .into_iter()
+        .map(|(source, targets)| (source, targets.into_iter().collect()))
+        .collect();
+    insta::assert_debug_snapshot!(sorted);
+}
+
+#[test]
+fn test_graph_data_from_json_upgrades_unversioned_payload() {
+    let json = r#"{"nodes": [{"id": "a", "type": "module", "is_orphan": true}], "edges": []}"#;
+
+    let data = GraphData::from_json(json).unwrap();
+    insta::assert_snapshot!(serde_json::to_string_pretty(&data).unwrap());
+}
+
+#[test]
+fn test_graph_data_from_json_rejects_newer_version() {
+    let json = format!(
+        r#"{{"version": {}, "nodes": [], "edges": []}}"#,
+        GRAPH_DATA_VERSION + 1
+    );
+
+    insta::assert_snapshot!(from_json_error(&json));
+}
+
+#[test]
+fn test_graph_data_from_json_rejects_invalid_version() {
+    insta::assert_snapshot!(from_json_error(
+        r#"{"version": "1", "nodes": [], "edges": []}"#
+    ));
+}
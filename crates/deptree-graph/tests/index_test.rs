@@ -0,0 +1,145 @@
+//! Tests for the reusable reachability index
+
+use deptree_graph::{GraphEdge, GraphIndex, GraphNode};
+use std::collections::{HashMap, HashSet};
+
+fn node(id: &str, is_orphan: bool) -> GraphNode {
+    GraphNode {
+        id: id.to_string(),
+        node_type: "module".to_string(),
+        is_orphan,
+        highlighted: None,
+        parent: None,
+        import_time_us: None,
+        coverage: None,
+        category: None,
+        lifecycle: None,
+        generated: None,
+        change: None,
+        annotations: None,
+        position: None,
+        style: None,
+        href: None,
+    }
+}
+
+fn edge(source: &str, target: &str) -> GraphEdge {
+    GraphEdge {
+        source: source.to_string(),
+        target: target.to_string(),
+        kind: None,
+        tags: None,
+        count: None,
+        change: None,
+        style: None,
+    }
+}
+
+fn ids(ids: &[&str]) -> Vec<String> {
+    ids.iter().map(|id| id.to_string()).collect()
+}
+
+#[test]
+fn test_reachability_is_memoized_per_root() {
+    let edges = vec![edge("a", "b"), edge("b", "c"), edge("a", "c")];
+    let index = GraphIndex::with_closure_budget(&[], &edges, 0);
+
+    let upstream = index.upstream_with_distance(&ids(&["a"]), None);
+    assert_eq!(
+        upstream,
+        HashMap::from([("a".into(), 0), ("b".into(), 1), ("c".into(), 1)])
+    );
+    assert_eq!(index.memoized_searches(), 1);
+
+    // A narrower distance reuses the cached search
+    let near = index.upstream(&ids(&["a"]), Some(0));
+    assert_eq!(near, HashSet::from(["a".to_string()]));
+    assert_eq!(index.memoized_searches(), 1);
+
+    let downstream = index.downstream(&ids(&["c"]), None);
+    assert_eq!(downstream.len(), 3);
+    assert_eq!(index.memoized_searches(), 2);
+}
+
+#[test]
+fn test_closure_answers_unbounded_queries() {
+    let edges = vec![
+        edge("a", "b"),
+        edge("b", "c"),
+        edge("c", "b"),
+        edge("x", "c"),
+    ];
+    let index = GraphIndex::new(&[], &edges);
+    assert!(index.has_closure());
+
+    let roots = ids(&["a", "missing"]);
+    let expected: HashSet<String> = ["a", "b", "c", "missing"].map(String::from).into();
+    assert_eq!(index.upstream(&roots, None), expected);
+    assert_eq!(index.downstream(&ids(&["b"]), None).len(), 4);
+    assert_eq!(index.memoized_searches(), 0);
+
+    // Bounded queries still need the distances of a search
+    assert_eq!(index.upstream(&roots, Some(1)).len(), 3);
+    assert_eq!(index.memoized_searches(), 1);
+}
+
+#[test]
+fn test_several_roots_share_one_search() {
+    // a -> b -> c -> d, with x -> c as a shortcut
+    let edges = vec![
+        edge("a", "b"),
+        edge("b", "c"),
+        edge("c", "d"),
+        edge("x", "c"),
+    ];
+    let index = GraphIndex::new(&[], &edges);
+
+    let upstream = index.upstream_with_distance(&ids(&["a", "x", "a"]), None);
+    let expected: HashMap<String, usize> = [("a", 0), ("x", 0), ("b", 1), ("c", 1), ("d", 2)]
+        .map(|(id, d)| (id.to_string(), d))
+        .into();
+    assert_eq!(upstream, expected);
+    assert_eq!(index.memoized_searches(), 1);
+
+    // Root order and repetition don't matter for the cache
+    assert_eq!(
+        index.upstream_with_distance(&ids(&["x", "a"]), None),
+        upstream
+    );
+    assert_eq!(index.memoized_searches(), 1);
+}
+
+#[test]
+fn test_weighted_reachability_bounds_weighted_distance() {
+    let edges = vec![edge("a", "b"), edge("b", "c"), edge("a", "c")];
+    let index = GraphIndex::new(&[], &edges);
+    let heavy = |source: &str, target: &str| if (source, target) == ("a", "c") { 5 } else { 1 };
+
+    let upstream = index.upstream_weighted(&ids(&["a"]), Some(1), heavy);
+    assert_eq!(upstream, HashMap::from([("a".into(), 0), ("b".into(), 1)]));
+    let downstream = index.downstream_weighted(&ids(&["c"]), None, heavy);
+    assert_eq!(downstream.get("a"), Some(&2));
+    assert_eq!(index.memoized_searches(), 0);
+}
+
+#[test]
+fn test_unknown_root_is_its_own_orphan() {
+    let index = GraphIndex::new(&[], &[edge("a", "b")]);
+
+    assert_eq!(
+        index.upstream(&ids(&["missing"]), None),
+        HashSet::from(["missing".to_string()])
+    );
+    assert!(index.is_orphan("missing"));
+    assert!(!index.is_orphan("b"));
+    assert!(index.distances_from("missing").is_empty());
+}
+
+#[test]
+fn test_orphan_flags_of_the_nodes_take_precedence_over_the_edges() {
+    let nodes = [node("a", true), node("c", false)];
+    let index = GraphIndex::new(&nodes, &[edge("a", "b")]);
+
+    let orphans: Vec<bool> = ["a", "b", "c"].map(|id| index.is_orphan(id)).into();
+    assert_eq!(orphans, [true, false, false]);
+}
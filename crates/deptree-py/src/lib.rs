@@ -0,0 +1,220 @@
+//! Python bindings (`import deptree_utils`)
+//!
+//! Exposes an analyzed Python project to notebooks as plain Python values: the node and
+//! edge tables come back as dicts of equally long columns, which `pandas.DataFrame`,
+//! `polars.DataFrame`, or `pyarrow.table` take as is, and queries return lists and dicts.
+//! The conversions live in plain functions ([`node_table`], [`edge_table`], [`related`])
+//! so they are tested without an interpreter; the `#[pymethods]` only wrap them.
+
+use deptree_cli::python::{ModulePath, PythonAnalysisError, PythonGraph, analyze_project};
+use deptree_graph::{DependencyGraph, GraphId, Relation};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Errors raised to Python as `ValueError`
+#[derive(Error, Debug)]
+pub enum BindingError {
+    #[error(transparent)]
+    Analysis(#[from] PythonAnalysisError),
+
+    #[error("Module '{0}' is not part of the analyzed graph")]
+    UnknownModule(String),
+}
+
+impl From<BindingError> for PyErr {
+    fn from(error: BindingError) -> PyErr {
+        PyValueError::new_err(error.to_string())
+    }
+}
+
+/// One row per module, sorted by name, as a dict of columns
+#[derive(Debug, Clone, Default, PartialEq, IntoPyObject)]
+pub struct NodeTable {
+    pub module: Vec<String>,
+    /// Parent package, empty for top-level modules
+    pub package: Vec<String>,
+    pub is_script: Vec<bool>,
+    pub is_orphan: Vec<bool>,
+    /// Modules imported directly
+    pub imports: Vec<usize>,
+    /// Modules importing it directly
+    pub imported_by: Vec<usize>,
+}
+
+/// One row per import, sorted by source then target, as a dict of columns
+#[derive(Debug, Clone, Default, PartialEq, IntoPyObject)]
+pub struct EdgeTable {
+    pub source: Vec<String>,
+    pub target: Vec<String>,
+    /// `import_time` or `deferred`
+    pub kind: Vec<&'static str>,
+}
+
+/// The node table of `graph`
+pub fn node_table<T: GraphId>(graph: &DependencyGraph<T>) -> NodeTable {
+    let orphans = graph.orphans();
+    let mut modules: Vec<&T> = graph.nodes().collect();
+    modules.sort_by_key(|module| module.to_dotted());
+    modules
+        .into_iter()
+        .fold(NodeTable::default(), |mut table, module| {
+            let mut package = module.segments();
+            package.pop();
+            table.module.push(module.to_dotted());
+            table.package.push(package.join("."));
+            table.is_script.push(graph.is_script(module));
+            table.is_orphan.push(orphans.contains(module));
+            table.imports.push(graph.direct_dependencies(module).len());
+            table
+                .imported_by
+                .push(graph.direct_dependents(module).len());
+            table
+        })
+}
+
+/// The edge table of `graph`, parallel imports counted once
+pub fn edge_table<T: GraphId>(graph: &DependencyGraph<T>) -> EdgeTable {
+    let mut edges: Vec<(String, String, &'static str)> = graph
+        .edges()
+        .map(|(from, to, kind)| (from.to_dotted(), to.to_dotted(), kind.as_str()))
+        .collect();
+    edges.sort();
+    edges.dedup();
+    edges
+        .into_iter()
+        .fold(EdgeTable::default(), |mut table, (source, target, kind)| {
+            table.source.push(source);
+            table.target.push(target);
+            table.kind.push(kind);
+            table
+        })
+}
+
+/// Modules reached from `modules` along `relation` with their distance (0 for the modules
+/// themselves), up to `max_distance` imports away
+pub fn related(
+    graph: &PythonGraph,
+    modules: &[String],
+    relation: Relation,
+    max_distance: Option<usize>,
+) -> Result<BTreeMap<String, usize>, BindingError> {
+    let roots = modules
+        .iter()
+        .map(|name| {
+            ModulePath::from_dotted(name)
+                .filter(|module| graph.contains(module))
+                .ok_or_else(|| BindingError::UnknownModule(name.clone()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(graph
+        .find_related(&roots, relation, max_distance)
+        .into_iter()
+        .map(|(module, distance)| (module.to_dotted(), distance))
+        .collect())
+}
+
+/// Internal dependency graph of a Python project
+#[pyclass(name = "Graph", module = "deptree_utils", frozen)]
+pub struct Graph {
+    graph: PythonGraph,
+}
+
+impl Graph {
+    pub fn new(graph: PythonGraph) -> Self {
+        Graph { graph }
+    }
+}
+
+#[pymethods]
+impl Graph {
+    /// Module names, sorted
+    fn modules(&self) -> Vec<String> {
+        node_table(&self.graph).module
+    }
+
+    /// Node table as a dict of columns: module, package, is_script, is_orphan, imports,
+    /// imported_by. `pandas.DataFrame(graph.node_table())` turns it into a frame.
+    fn node_table(&self) -> NodeTable {
+        node_table(&self.graph)
+    }
+
+    /// Edge table as a dict of columns: source, target, kind
+    fn edge_table(&self) -> EdgeTable {
+        edge_table(&self.graph)
+    }
+
+    /// Modules imported by `modules`, directly or not, as `{module: distance}`
+    #[pyo3(signature = (modules, max_distance=None))]
+    fn upstream(
+        &self,
+        modules: Vec<String>,
+        max_distance: Option<usize>,
+    ) -> PyResult<BTreeMap<String, usize>> {
+        Ok(related(
+            &self.graph,
+            &modules,
+            Relation::Dependencies,
+            max_distance,
+        )?)
+    }
+
+    /// Modules importing `modules`, directly or not, as `{module: distance}`
+    #[pyo3(signature = (modules, max_distance=None))]
+    fn downstream(
+        &self,
+        modules: Vec<String>,
+        max_distance: Option<usize>,
+    ) -> PyResult<BTreeMap<String, usize>> {
+        Ok(related(
+            &self.graph,
+            &modules,
+            Relation::Dependents,
+            max_distance,
+        )?)
+    }
+
+    /// Import cycles, each a list of module names
+    fn cycles(&self) -> Vec<Vec<String>> {
+        self.graph
+            .find_cycles()
+            .iter()
+            .map(|cycle| cycle.iter().map(GraphId::to_dotted).collect())
+            .collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "<deptree_utils.Graph: {} modules, {} imports>",
+            self.graph.node_count(),
+            self.graph.edge_count()
+        )
+    }
+}
+
+/// Analyze the Python project at `project_root`. The source root is detected unless given;
+/// `exclude_scripts` takes the glob patterns of `--exclude-scripts`.
+#[pyfunction]
+#[pyo3(signature = (project_root, source_root=None, exclude_scripts=Vec::new()))]
+fn analyze(
+    project_root: PathBuf,
+    source_root: Option<PathBuf>,
+    exclude_scripts: Vec<String>,
+) -> PyResult<Graph> {
+    let graph = analyze_project(&project_root, source_root.as_deref(), &exclude_scripts)
+        .map_err(BindingError::from)?;
+    Ok(Graph::new(graph))
+}
+
+#[pymodule]
+fn deptree_utils(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(analyze, module)?)?;
+    module.add_class::<Graph>()?;
+    Ok(())
+}
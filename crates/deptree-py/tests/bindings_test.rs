@@ -0,0 +1,34 @@
+use deptree_cli::python::analyze_project;
+use deptree_graph::Relation;
+use deptree_py::{BindingError, edge_table, node_table, related};
+use std::path::Path;
+
+fn sample_graph() -> deptree_cli::python::PythonGraph {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../deptree-cli/tests/fixtures/sample_python_project");
+    analyze_project(&root, None, &[]).expect("Failed to analyze the sample project")
+}
+
+#[test]
+fn test_node_and_edge_tables_are_columns() {
+    let graph = sample_graph();
+    insta::assert_debug_snapshot!((node_table(&graph), edge_table(&graph)));
+}
+
+#[test]
+fn test_related_returns_distances_by_module() {
+    let graph = sample_graph();
+    let downstream = related(
+        &graph,
+        &["pkg_b.module_b".to_string()],
+        Relation::Dependents,
+        None,
+    )
+    .unwrap();
+    insta::assert_debug_snapshot!(downstream);
+
+    assert!(matches!(
+        related(&graph, &["pkg_c".to_string()], Relation::Dependencies, None),
+        Err(BindingError::UnknownModule(_))
+    ));
+}
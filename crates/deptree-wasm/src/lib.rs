@@ -1,38 +1,103 @@
-pub use deptree_graph::{GraphConfig, GraphData, GraphEdge, GraphNode};
+pub use deptree_graph::filters::{EdgeDirection, FilterConfig, FilterPreset, FilterResult};
 use deptree_graph::{
-    compute_all_distances, filters::apply_filters, get_downstream_nodes, get_upstream_nodes,
-    is_orphan_node,
+    DistanceComputation, GraphIndex, Relation,
+    filters::{apply_filters, import_weight, matches_pattern},
 };
+pub use deptree_graph::{GraphConfig, GraphData, GraphEdge, GraphNode};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use wasm_bindgen::prelude::*;
 
-/// Filter configuration from JavaScript
-#[derive(Debug, Deserialize)]
-pub struct FilterConfig {
-    #[serde(rename = "showOrphans")]
-    pub show_orphans: bool,
-    #[serde(rename = "showNamespaces")]
-    pub show_namespaces: bool,
-    #[serde(rename = "excludePatterns")]
-    pub exclude_patterns: Vec<String>,
-    #[serde(rename = "upstreamRoots")]
-    pub upstream_roots: Vec<String>,
-    #[serde(rename = "downstreamRoots")]
-    pub downstream_roots: Vec<String>,
-    #[serde(rename = "maxDistance")]
-    pub max_distance: Option<usize>,
-    #[serde(rename = "highlightedOnly")]
-    pub highlighted_only: bool,
+pub mod logging;
+use logging::{log_debug, log_error};
+
+/// One operation of a batch passed to `GraphProcessor::query`, tagged by `op`:
+///
+/// - `{"op": "filter", "config": FilterConfig}`
+/// - `{"op": "upstream", "roots": [...], "maxDistance": n}` (`maxDistance` optional)
+/// - `{"op": "downstream", "roots": [...], "maxDistance": n}`
+/// - `{"op": "distances", "from": "node"}` (`from` optional; all pairs when omitted)
+/// - `{"op": "search", "pattern": "*auth*"}` (wildcards as in `excludePatterns`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum QueryOp {
+    Filter {
+        config: FilterConfig,
+    },
+    Upstream {
+        roots: Vec<String>,
+        #[serde(rename = "maxDistance", default)]
+        max_distance: Option<usize>,
+    },
+    Downstream {
+        roots: Vec<String>,
+        #[serde(rename = "maxDistance", default)]
+        max_distance: Option<usize>,
+    },
+    Distances {
+        #[serde(default)]
+        from: Option<String>,
+    },
+    Search {
+        pattern: String,
+    },
+}
+
+/// Result of one `QueryOp`, at the same position in the returned array
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum QueryResult {
+    /// `filter`: visible and highlighted node IDs
+    Filter(FilterResult),
+    /// `upstream`, `downstream`, `search`: sorted node IDs
+    Nodes(Vec<String>),
+    /// `distances` from one node (`{"target": n}`)
+    Distances(HashMap<String, usize>),
+    /// `distances` between all pairs (`{"source": {"target": n}}`)
+    AllDistances(HashMap<String, HashMap<String, usize>>),
 }
 
-/// Result of filter operation containing both visibility and highlighting information
-#[derive(Debug, Serialize, Deserialize)]
-pub struct FilterResult {
-    /// Node IDs that should be visible
-    pub visible: Vec<String>,
-    /// Node IDs that should be highlighted
-    pub highlighted: Vec<String>,
+fn sorted(nodes: HashSet<String>) -> Vec<String> {
+    let mut nodes: Vec<String> = nodes.into_iter().collect();
+    nodes.sort();
+    nodes
+}
+
+/// Resumable all-pairs distance computation. Call `step` until it returns `true` (yielding
+/// to the event loop in between), then read `result`. To cancel, stop stepping and `free`
+/// the job.
+#[wasm_bindgen]
+pub struct DistanceJob {
+    computation: DistanceComputation,
+}
+
+#[wasm_bindgen]
+impl DistanceJob {
+    /// Process up to `chunk_size` more source nodes; returns `true` once finished
+    pub fn step(&mut self, chunk_size: usize) -> bool {
+        self.computation.step(chunk_size)
+    }
+
+    /// Fraction of source nodes processed so far, from 0 to 1
+    pub fn progress(&self) -> f64 {
+        let total = self.computation.total();
+        if total == 0 {
+            1.0
+        } else {
+            self.computation.processed() as f64 / total as f64
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.computation.is_done()
+    }
+
+    /// Distances computed so far (same shape as `compute_all_distances`; complete once
+    /// `is_done` returns `true`)
+    pub fn result(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(self.computation.distances()).unwrap_or(JsValue::NULL)
+    }
 }
 
 /// Main graph processor exposed to JavaScript
@@ -41,6 +106,13 @@ pub struct GraphProcessor {
     nodes: Vec<GraphNode>,
     edges: Vec<GraphEdge>,
     config: Option<GraphConfig>,
+    /// Built once; memoizes upstream/downstream searches across calls
+    index: GraphIndex,
+    /// Nodes highlighted by the CLI
+    cli_highlighted: HashSet<String>,
+    /// Filter configuration last applied through `filter_nodes`/`filter_nodes_object` or
+    /// `import_filter_state`
+    current_filter: RefCell<Option<FilterConfig>>,
 }
 
 #[wasm_bindgen]
@@ -48,64 +120,306 @@ impl GraphProcessor {
     /// Create a new GraphProcessor from JSON
     #[wasm_bindgen(constructor)]
     pub fn new(graph_json: &str) -> Result<GraphProcessor, JsValue> {
-        let graph_data: GraphData = serde_json::from_str(graph_json)
-            .map_err(|e| JsValue::from_str(&format!("Failed to parse graph JSON: {e}")))?;
+        let graph_data =
+            GraphData::from_json(graph_json).map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+        Ok(Self::from_graph_data(graph_data))
+    }
+
+    /// Create a new GraphProcessor from a graph data object, skipping the JSON
+    /// stringify/parse round-trip
+    pub fn from_object(graph_data: JsValue) -> Result<GraphProcessor, JsValue> {
+        let graph_data: GraphData = serde_wasm_bindgen::from_value(graph_data)
+            .map_err(|e| JsValue::from_str(&format!("Failed to read graph data: {e}")))?;
+        let graph_data = graph_data
+            .upgrade()
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+        Ok(Self::from_graph_data(graph_data))
+    }
 
-        Ok(GraphProcessor {
+    fn from_graph_data(graph_data: GraphData) -> GraphProcessor {
+        let index = GraphIndex::new(&graph_data.nodes, &graph_data.edges);
+        let cli_highlighted = graph_data
+            .nodes
+            .iter()
+            .filter(|n| n.highlighted.unwrap_or(false))
+            .map(|n| n.id.clone())
+            .collect();
+        GraphProcessor {
             nodes: graph_data.nodes,
             edges: graph_data.edges,
             config: graph_data.config,
-        })
+            index,
+            cli_highlighted,
+            current_filter: RefCell::new(None),
+        }
     }
 
     /// Compute all-pairs shortest paths using BFS
     /// Returns JSON object with distances: { "node1": { "node2": 2, "node3": 1 }, ... }
     pub fn compute_all_distances(&self) -> JsValue {
-        let distances = compute_all_distances(&self.nodes, &self.edges);
-        serde_wasm_bindgen::to_value(&distances).unwrap_or(JsValue::NULL)
+        serde_wasm_bindgen::to_value(&self.all_distances()).unwrap_or(JsValue::NULL)
     }
 
-    /// Check if a node is an orphan (no incoming or outgoing edges)
-    pub fn is_orphan(&self, node_id: &str) -> bool {
-        is_orphan_node(node_id, &self.edges)
+    fn all_distances(&self) -> HashMap<String, HashMap<String, usize>> {
+        let mut computation = self.index.distance_computation();
+        computation.step(usize::MAX);
+        computation.into_distances()
     }
 
-    /// Helper method to get the parent ID of a node
-    fn get_parent_id(&self, node_id: &str) -> Option<&str> {
-        for node in &self.nodes {
-            if node.id == node_id {
-                return node.parent.as_deref();
-            }
+    /// Start an all-pairs distance computation that the caller advances in chunks (see
+    /// `DistanceJob`), keeping the UI responsive on big graphs
+    pub fn start_distances(&self) -> DistanceJob {
+        DistanceJob {
+            computation: self.index.distance_computation(),
         }
-        None
+    }
+
+    /// Whether the graph was small enough to precompute its transitive closure, making
+    /// unbounded upstream/downstream filters a bitset lookup
+    pub fn has_reachability_closure(&self) -> bool {
+        self.index.has_closure()
+    }
+
+    /// Check if a node is an orphan, as decided by the graph that produced the data
+    pub fn is_orphan(&self, node_id: &str) -> bool {
+        self.index.is_orphan(node_id)
     }
 
     /// Filter nodes based on criteria
     /// Returns JSON object with both visible and highlighted node IDs
     pub fn filter_nodes(&self, filter_config_json: &str) -> JsValue {
-        #[cfg(target_arch = "wasm32")]
-        web_sys::console::log_1(&"WASM filter_nodes called".into());
+        log_debug!("WASM filter_nodes called");
 
-        let filter_config: FilterConfig = match serde_json::from_str(filter_config_json) {
-            Ok(config) => config,
+        let result = match serde_json::from_str::<FilterConfig>(filter_config_json) {
+            Ok(filter_config) => self.apply(filter_config),
             Err(_e) => {
-                #[cfg(target_arch = "wasm32")]
-                web_sys::console::error_1(&format!("Failed to parse filter config: {}", _e).into());
-                let empty_result = FilterResult {
-                    visible: Vec::new(),
-                    highlighted: Vec::new(),
-                };
-                return serde_wasm_bindgen::to_value(&empty_result).unwrap();
+                log_error!("Failed to parse filter config: {_e}");
+                FilterResult::default()
             }
         };
 
-        #[cfg(target_arch = "wasm32")]
-        web_sys::console::log_1(
-            &format!(
-                "Config parsed: highlightedOnly={}",
-                filter_config.highlighted_only
-            )
-            .into(),
+        serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+    }
+
+    /// Same as `filter_nodes`, but takes the filter configuration as an object instead of
+    /// a JSON string
+    pub fn filter_nodes_object(&self, filter_config: JsValue) -> JsValue {
+        let result = match serde_wasm_bindgen::from_value::<FilterConfig>(filter_config) {
+            Ok(filter_config) => self.apply(filter_config),
+            Err(_e) => {
+                log_error!("Failed to read filter config: {_e}");
+                FilterResult::default()
+            }
+        };
+
+        serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+    }
+
+    /// Export the filters last applied as a named preset (JSON), to be saved or shared
+    pub fn export_filter_state(&self, name: &str) -> Result<String, JsValue> {
+        let config = self.current_filter_config()?;
+        let preset = FilterPreset {
+            name: name.to_string(),
+            config,
+        };
+        serde_json::to_string_pretty(&preset).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Read a preset exported by `export_filter_state` (or listed in the graph
+    /// configuration), make it the current filter state, and return it as an object so
+    /// the UI can reflect it before applying
+    pub fn import_filter_state(&self, preset_json: &str) -> Result<JsValue, JsValue> {
+        let preset = self
+            .parse_preset(preset_json)
+            .map_err(|e| JsValue::from_str(&e))?;
+        *self.current_filter.borrow_mut() = Some(preset.config.clone());
+        serde_wasm_bindgen::to_value(&preset).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Encode the filters last applied compactly for a shareable link (`#filter=...`) or
+    /// the CLI's `--filter-state`
+    pub fn encode_filter_state(&self) -> Result<String, JsValue> {
+        Ok(self.current_filter_config()?.encode())
+    }
+
+    /// Decode a filter state from `encode_filter_state`, make it the current filter state,
+    /// and return the configuration as an object
+    pub fn decode_filter_state(&self, encoded: &str) -> Result<JsValue, JsValue> {
+        let config = self
+            .parse_encoded(encoded)
+            .map_err(|e| JsValue::from_str(&e))?;
+        *self.current_filter.borrow_mut() = Some(config.clone());
+        serde_wasm_bindgen::to_value(&config).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Run a batch of operations in one call (see `QueryOp` for the format) and return
+    /// an array with one result per operation, in order. Each JS/WASM boundary crossing
+    /// has a cost, so the UI should prefer one batch over several individual calls.
+    pub fn query(&self, batch_json: &str) -> Result<JsValue, JsValue> {
+        let ops: Vec<QueryOp> = serde_json::from_str(batch_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse query batch: {e}")))?;
+        serde_wasm_bindgen::to_value(&self.run_queries(&ops))
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Same as `query`, but takes the batch as an array of objects instead of a JSON string
+    pub fn query_object(&self, batch: JsValue) -> Result<JsValue, JsValue> {
+        let ops: Vec<QueryOp> = serde_wasm_bindgen::from_value(batch)
+            .map_err(|e| JsValue::from_str(&format!("Failed to read query batch: {e}")))?;
+        serde_wasm_bindgen::to_value(&self.run_queries(&ops))
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    fn run_queries(&self, ops: &[QueryOp]) -> Vec<QueryResult> {
+        ops.iter().map(|op| self.run_query(op)).collect()
+    }
+
+    fn run_query(&self, op: &QueryOp) -> QueryResult {
+        match op {
+            QueryOp::Filter { config } => QueryResult::Filter(self.filter(config)),
+            QueryOp::Upstream {
+                roots,
+                max_distance,
+            } => QueryResult::Nodes(sorted(self.index.upstream(roots, *max_distance))),
+            QueryOp::Downstream {
+                roots,
+                max_distance,
+            } => QueryResult::Nodes(sorted(self.index.downstream(roots, *max_distance))),
+            QueryOp::Distances { from: None } => QueryResult::AllDistances(self.all_distances()),
+            QueryOp::Distances { from: Some(from) } => {
+                QueryResult::Distances(self.index.distances_from(from))
+            }
+            QueryOp::Search { pattern } => QueryResult::Nodes(sorted(
+                self.nodes
+                    .iter()
+                    .filter(|node| matches_pattern(&node.id, pattern))
+                    .map(|node| node.id.clone())
+                    .collect(),
+            )),
+        }
+    }
+
+    fn current_filter_config(&self) -> Result<FilterConfig, JsValue> {
+        self.current_filter
+            .borrow()
+            .clone()
+            .ok_or_else(|| JsValue::from_str("No filters have been applied yet"))
+    }
+
+    /// First upstream/downstream root of `config` that is not part of this graph
+    fn unknown_root<'a>(&self, config: &'a FilterConfig) -> Option<&'a String> {
+        config
+            .upstream_roots
+            .iter()
+            .chain(&config.downstream_roots)
+            .find(|root| !self.index.contains(root))
+    }
+
+    /// Parse a preset, rejecting roots that are not part of this graph
+    fn parse_preset(&self, preset_json: &str) -> Result<FilterPreset, String> {
+        let preset: FilterPreset = serde_json::from_str(preset_json)
+            .map_err(|e| format!("Failed to parse filter preset: {e}"))?;
+        if let Some(unknown) = self.unknown_root(&preset.config) {
+            return Err(format!(
+                "Preset '{}' refers to unknown module '{unknown}'",
+                preset.name
+            ));
+        }
+        Ok(preset)
+    }
+
+    /// Decode an encoded filter state, rejecting roots that are not part of this graph
+    fn parse_encoded(&self, encoded: &str) -> Result<FilterConfig, String> {
+        let config = FilterConfig::decode(encoded).map_err(|e| e.to_string())?;
+        if let Some(unknown) = self.unknown_root(&config) {
+            return Err(format!("Filter state refers to unknown module '{unknown}'"));
+        }
+        Ok(config)
+    }
+
+    /// Filter with `filter_config` and remember it as the current filter state
+    fn apply(&self, filter_config: FilterConfig) -> FilterResult {
+        let result = self.filter(&filter_config);
+        *self.current_filter.borrow_mut() = Some(filter_config);
+        result
+    }
+
+    /// Edges between visible or faded nodes that `edgeDirection` leaves out
+    fn hidden_edges(
+        &self,
+        filter_config: &FilterConfig,
+        visible: &HashSet<String>,
+        faded: &[String],
+    ) -> Vec<(String, String)> {
+        let direction = filter_config.edge_direction;
+        if direction == EdgeDirection::Both {
+            return Vec::new();
+        }
+        let roots: Vec<String> = filter_config
+            .upstream_roots
+            .iter()
+            .chain(&filter_config.downstream_roots)
+            .cloned()
+            .collect();
+        if roots.is_empty() {
+            return Vec::new();
+        }
+
+        let upstream = self.index.upstream(&roots, None);
+        let downstream = self.index.downstream(&roots, None);
+        let faded: HashSet<&String> = faded.iter().collect();
+        let shown = |id: &String| visible.contains(id) || faded.contains(id);
+        self.edges
+            .iter()
+            .filter(|e| shown(&e.source) && shown(&e.target))
+            .filter(|e| !direction.keeps(&e.source, &e.target, &upstream, &downstream))
+            .map(|e| (e.source.clone(), e.target.clone()))
+            .collect()
+    }
+
+    /// Nodes related to `roots` within the filter's `maxDistance`, measured along imports
+    /// weighted by its `weightKey` if it has one
+    fn related(
+        &self,
+        filter_config: &FilterConfig,
+        roots: &[String],
+        relation: Relation,
+    ) -> HashSet<String> {
+        let max_distance = filter_config.max_distance;
+        let Some(key) = filter_config
+            .weight_key
+            .as_deref()
+            .filter(|_| max_distance.is_some())
+        else {
+            return self.index.related(roots, relation, max_distance);
+        };
+        let counts: HashMap<(&str, &str), usize> = self
+            .edges
+            .iter()
+            .map(|e| ((e.source.as_str(), e.target.as_str()), e.count.unwrap_or(1)))
+            .collect();
+        let annotations: HashMap<&str, _> = self
+            .nodes
+            .iter()
+            .filter_map(|n| Some((n.id.as_str(), n.annotations.as_ref()?)))
+            .collect();
+        let cost = |source: &str, target: &str| {
+            let count = counts.get(&(source, target)).copied().unwrap_or(1);
+            import_weight(key, count, annotations.get(target).copied())
+        };
+        self.index
+            .related_weighted(roots, relation, max_distance, cost)
+            .into_keys()
+            .collect()
+    }
+
+    /// Compute the visible and highlighted node IDs for a filter configuration
+    fn filter(&self, filter_config: &FilterConfig) -> FilterResult {
+        log_debug!(
+            "Config parsed: highlightedOnly={}",
+            filter_config.highlighted_only
         );
 
         // Step 1: Compute filtered_set from upstream/downstream/distance filters
@@ -113,20 +427,20 @@ impl GraphProcessor {
 
         // Apply upstream filtering
         if !filter_config.upstream_roots.is_empty() {
-            let upstream = get_upstream_nodes(
+            let upstream = self.related(
+                filter_config,
                 &filter_config.upstream_roots,
-                &self.edges,
-                filter_config.max_distance,
+                Relation::Dependencies,
             );
             filtered_set = Some(upstream);
         }
 
         // Apply downstream filtering
         if !filter_config.downstream_roots.is_empty() {
-            let downstream = get_downstream_nodes(
+            let downstream = self.related(
+                filter_config,
                 &filter_config.downstream_roots,
-                &self.edges,
-                filter_config.max_distance,
+                Relation::Dependents,
             );
 
             // If we already have upstream filter, intersect; otherwise just use downstream
@@ -143,19 +457,12 @@ impl GraphProcessor {
                 filtered_set.clone()
             } else {
                 // No interactive filters - check for CLI highlighting
-                let cli_highlighted: HashSet<String> = self
-                    .nodes
-                    .iter()
-                    .filter(|n| n.highlighted.unwrap_or(false))
-                    .map(|n| n.id.clone())
-                    .collect();
-
-                if cli_highlighted.is_empty() {
+                if self.cli_highlighted.is_empty() {
                     // No CLI highlighting either - show all nodes (default state)
                     None
                 } else {
                     // Show only CLI-highlighted nodes
-                    Some(cli_highlighted)
+                    Some(self.cli_highlighted.clone())
                 }
             }
         } else {
@@ -175,7 +482,7 @@ impl GraphProcessor {
         // Step 3.5: Include parent nodes if any child is visible
         let mut parent_nodes_to_include = HashSet::new();
         for node_id in &visible {
-            if let Some(parent_id) = self.get_parent_id(node_id) {
+            if let Some(parent_id) = self.index.parent(node_id) {
                 parent_nodes_to_include.insert(parent_id.to_string());
             }
         }
@@ -184,8 +491,7 @@ impl GraphProcessor {
         }
 
         // Step 4: Determine highlighted set based on filter state
-        #[cfg(target_arch = "wasm32")]
-        web_sys::console::log_1(&format!(
+        log_debug!(
             "Filter state: filtered_set={}, upstream_roots={:?}, downstream_roots={:?}, show_orphans={}, show_namespaces={}, exclude_patterns={}, visible_count={}",
             filtered_set.is_some(),
             filter_config.upstream_roots,
@@ -194,14 +500,12 @@ impl GraphProcessor {
             filter_config.show_namespaces,
             filter_config.exclude_patterns.len(),
             visible.len()
-        ).into());
+        );
 
-        let highlighted_nodes: Vec<String> = if filtered_set.is_some() {
-            #[cfg(target_arch = "wasm32")]
-            web_sys::console::log_1(&"Using upstream/downstream highlighting".into());
+        let mut highlighted_nodes: Vec<String> = if let Some(filter_set) = filtered_set.as_ref() {
+            log_debug!("Using upstream/downstream highlighting");
 
             // Upstream/downstream filters active - highlight those filtered nodes (but only if they're visible)
-            let filter_set = filtered_set.as_ref().unwrap();
             visible
                 .iter()
                 .filter(|node_id| filter_set.contains(*node_id))
@@ -211,56 +515,73 @@ impl GraphProcessor {
             || !filter_config.show_namespaces
             || !filter_config.exclude_patterns.is_empty()
         {
-            #[cfg(target_arch = "wasm32")]
-            web_sys::console::log_1(&"Using orphan/namespace/pattern highlighting".into());
+            log_debug!("Using orphan/namespace/pattern highlighting");
 
             // Other interactive filters (orphans/namespaces/patterns) active - highlight visible nodes
             visible.iter().cloned().collect()
         } else {
-            #[cfg(target_arch = "wasm32")]
-            web_sys::console::log_1(&"Using CLI highlighting".into());
+            log_debug!("Using CLI highlighting");
 
             // No interactive filters - use CLI highlighting for backward compatibility
+            self.cli_highlighted.iter().cloned().collect()
+        };
+
+        // Step 5: Extend highlighting to the visible N-hop neighborhood
+        if filter_config.highlight_neighbors > 0 && !highlighted_nodes.is_empty() {
+            let limit = Some(filter_config.highlight_neighbors);
+            let mut neighborhood = self.index.upstream(&highlighted_nodes, limit);
+            neighborhood.extend(self.index.downstream(&highlighted_nodes, limit));
+            let already: HashSet<&String> = highlighted_nodes.iter().collect();
+            let mut added: Vec<String> = neighborhood
+                .into_iter()
+                .filter(|node_id| visible.contains(node_id) && !already.contains(node_id))
+                .collect();
+            added.sort();
+            highlighted_nodes.extend(added);
+        }
+
+        log_debug!(
+            "Highlighted {} out of {} visible nodes",
+            highlighted_nodes.len(),
+            visible.len()
+        );
+
+        // Step 6: Keep filtered-out nodes around as faded when requested
+        let faded: Vec<String> = if filter_config.fade_filtered {
             self.nodes
                 .iter()
-                .filter(|n| n.highlighted.unwrap_or(false))
+                .filter(|n| !visible.contains(&n.id))
                 .map(|n| n.id.clone())
                 .collect()
+        } else {
+            Vec::new()
         };
 
-        #[cfg(target_arch = "wasm32")]
-        web_sys::console::log_1(
-            &format!(
-                "Highlighted {} out of {} visible nodes",
-                highlighted_nodes.len(),
-                visible.len()
-            )
-            .into(),
-        );
+        // Step 7: Hide edges between shown nodes that point the wrong way for edgeDirection
+        let hidden_edges = self.hidden_edges(filter_config, &visible, &faded);
 
-        // Step 6: Return both visible and highlighted sets
+        // Step 8: Return the visible, highlighted, and faded sets and hidden edges
         let result = FilterResult {
             visible: visible.into_iter().collect(),
             highlighted: highlighted_nodes,
+            faded,
+            hidden_edges,
         };
 
-        #[cfg(target_arch = "wasm32")]
-        web_sys::console::log_1(
-            &format!(
-                "filter_nodes result: visible={}, highlighted={}",
-                result.visible.len(),
-                result.highlighted.len()
-            )
-            .into(),
+        log_debug!(
+            "filter_nodes result: visible={}, highlighted={}, faded={}",
+            result.visible.len(),
+            result.highlighted.len(),
+            result.faded.len()
         );
 
-        serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+        result
     }
 
     /// Get all upstream dependencies from given roots
     /// Returns JSON array of node IDs
     pub fn get_upstream(&self, roots: Vec<String>, max_distance: Option<usize>) -> JsValue {
-        let upstream = get_upstream_nodes(&roots, &self.edges, max_distance);
+        let upstream = self.index.upstream(&roots, max_distance);
         let result: Vec<String> = upstream.into_iter().collect();
         serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
     }
@@ -268,7 +589,7 @@ impl GraphProcessor {
     /// Get all downstream dependents from given roots
     /// Returns JSON array of node IDs
     pub fn get_downstream(&self, roots: Vec<String>, max_distance: Option<usize>) -> JsValue {
-        let downstream = get_downstream_nodes(&roots, &self.edges, max_distance);
+        let downstream = self.index.downstream(&roots, max_distance);
         let result: Vec<String> = downstream.into_iter().collect();
         serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
     }
@@ -282,6 +603,7 @@ impl GraphProcessor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use deptree_graph::compute_all_distances;
 
     #[test]
     fn test_graph_processor_creation() {
@@ -311,7 +633,8 @@ mod tests {
 
         let processor = GraphProcessor::new(graph_json).unwrap();
         assert!(!processor.is_orphan("module_a"));
-        assert!(!processor.is_orphan("module_b")); // has incoming edge
+        // The flag baked in by the graph wins over the raw edge list
+        assert!(processor.is_orphan("module_b"));
     }
 
     #[test]
@@ -377,6 +700,7 @@ mod tests {
     #[allow(unused_variables)]
     mod filter_nodes_tests {
         use super::*;
+        use deptree_graph::GRAPH_DATA_VERSION;
         use std::collections::HashSet;
 
         fn create_test_graph() -> (Vec<GraphNode>, Vec<GraphEdge>) {
@@ -387,6 +711,16 @@ mod tests {
                     is_orphan: false,
                     highlighted: None,
                     parent: None,
+                    import_time_us: None,
+                    coverage: None,
+                    category: None,
+                    lifecycle: None,
+                    generated: None,
+                    change: None,
+                    annotations: None,
+                    position: None,
+                    style: None,
+                    href: None,
                 },
                 GraphNode {
                     id: "module_b".to_string(),
@@ -394,6 +728,16 @@ mod tests {
                     is_orphan: false,
                     highlighted: None,
                     parent: None,
+                    import_time_us: None,
+                    coverage: None,
+                    category: None,
+                    lifecycle: None,
+                    generated: None,
+                    change: None,
+                    annotations: None,
+                    position: None,
+                    style: None,
+                    href: None,
                 },
                 GraphNode {
                     id: "orphan_c".to_string(),
@@ -401,12 +745,27 @@ mod tests {
                     is_orphan: true,
                     highlighted: None,
                     parent: None,
+                    import_time_us: None,
+                    coverage: None,
+                    category: None,
+                    lifecycle: None,
+                    generated: None,
+                    change: None,
+                    annotations: None,
+                    position: None,
+                    style: None,
+                    href: None,
                 },
             ];
 
             let edges = vec![GraphEdge {
                 source: "module_a".to_string(),
                 target: "module_b".to_string(),
+                kind: None,
+                tags: None,
+                count: None,
+                change: None,
+                style: None,
             }];
 
             (nodes, edges)
@@ -415,11 +774,12 @@ mod tests {
         #[test]
         fn test_highlighted_only_no_filters_no_cli_highlighting() {
             let (nodes, edges) = create_test_graph();
-            let processor = GraphProcessor {
+            let processor = GraphProcessor::from_graph_data(GraphData {
+                version: GRAPH_DATA_VERSION,
                 nodes,
                 edges,
                 config: None,
-            };
+            });
 
             // Apply filters directly using internal logic
             let filter_config = FilterConfig {
@@ -430,6 +790,10 @@ mod tests {
                 downstream_roots: vec![],
                 max_distance: None,
                 highlighted_only: true,
+                highlight_neighbors: 0,
+                fade_filtered: false,
+                edge_direction: EdgeDirection::Both,
+                weight_key: None,
             };
 
             // Simulate the logic from filter_nodes
@@ -481,6 +845,7 @@ mod tests {
         fn test_orphan_filter_highlights_visible_nodes() {
             let (nodes, edges) = create_test_graph();
             let graph_data = GraphData {
+                version: GRAPH_DATA_VERSION,
                 nodes,
                 edges,
                 config: None,
@@ -524,6 +889,16 @@ mod tests {
                     is_orphan: false,
                     highlighted: None,
                     parent: None,
+                    import_time_us: None,
+                    coverage: None,
+                    category: None,
+                    lifecycle: None,
+                    generated: None,
+                    change: None,
+                    annotations: None,
+                    position: None,
+                    style: None,
+                    href: None,
                 },
                 GraphNode {
                     id: "module_b".to_string(),
@@ -531,6 +906,16 @@ mod tests {
                     is_orphan: false,
                     highlighted: None,
                     parent: None,
+                    import_time_us: None,
+                    coverage: None,
+                    category: None,
+                    lifecycle: None,
+                    generated: None,
+                    change: None,
+                    annotations: None,
+                    position: None,
+                    style: None,
+                    href: None,
                 },
                 GraphNode {
                     id: "namespace_pkg".to_string(),
@@ -538,14 +923,30 @@ mod tests {
                     is_orphan: false,
                     highlighted: None,
                     parent: None,
+                    import_time_us: None,
+                    coverage: None,
+                    category: None,
+                    lifecycle: None,
+                    generated: None,
+                    change: None,
+                    annotations: None,
+                    position: None,
+                    style: None,
+                    href: None,
                 },
             ];
             let edges = vec![GraphEdge {
                 source: "module_a".to_string(),
                 target: "module_b".to_string(),
+                kind: None,
+                tags: None,
+                count: None,
+                change: None,
+                style: None,
             }];
 
             let graph_data = GraphData {
+                version: GRAPH_DATA_VERSION,
                 nodes,
                 edges,
                 config: None,
@@ -589,6 +990,16 @@ mod tests {
                     is_orphan: false,
                     highlighted: None,
                     parent: None,
+                    import_time_us: None,
+                    coverage: None,
+                    category: None,
+                    lifecycle: None,
+                    generated: None,
+                    change: None,
+                    annotations: None,
+                    position: None,
+                    style: None,
+                    href: None,
                 },
                 GraphNode {
                     id: "scripts.old_runner".to_string(),
@@ -596,11 +1007,22 @@ mod tests {
                     is_orphan: false,
                     highlighted: None,
                     parent: None,
+                    import_time_us: None,
+                    coverage: None,
+                    category: None,
+                    lifecycle: None,
+                    generated: None,
+                    change: None,
+                    annotations: None,
+                    position: None,
+                    style: None,
+                    href: None,
                 },
             ];
             let edges = vec![];
 
             let graph_data = GraphData {
+                version: GRAPH_DATA_VERSION,
                 nodes,
                 edges,
                 config: None,
@@ -642,6 +1064,16 @@ mod tests {
                     is_orphan: false,
                     highlighted: Some(true), // CLI-highlighted
                     parent: None,
+                    import_time_us: None,
+                    coverage: None,
+                    category: None,
+                    lifecycle: None,
+                    generated: None,
+                    change: None,
+                    annotations: None,
+                    position: None,
+                    style: None,
+                    href: None,
                 },
                 GraphNode {
                     id: "module_b".to_string(),
@@ -649,6 +1081,16 @@ mod tests {
                     is_orphan: false,
                     highlighted: Some(true), // CLI-highlighted
                     parent: None,
+                    import_time_us: None,
+                    coverage: None,
+                    category: None,
+                    lifecycle: None,
+                    generated: None,
+                    change: None,
+                    annotations: None,
+                    position: None,
+                    style: None,
+                    href: None,
                 },
                 GraphNode {
                     id: "module_c".to_string(),
@@ -656,11 +1098,22 @@ mod tests {
                     is_orphan: false,
                     highlighted: None,
                     parent: None,
+                    import_time_us: None,
+                    coverage: None,
+                    category: None,
+                    lifecycle: None,
+                    generated: None,
+                    change: None,
+                    annotations: None,
+                    position: None,
+                    style: None,
+                    href: None,
                 },
             ];
             let edges = vec![];
 
             let graph_data = GraphData {
+                version: GRAPH_DATA_VERSION,
                 nodes,
                 edges,
                 config: None,
@@ -703,6 +1156,16 @@ mod tests {
                     is_orphan: false,
                     highlighted: None,
                     parent: None,
+                    import_time_us: None,
+                    coverage: None,
+                    category: None,
+                    lifecycle: None,
+                    generated: None,
+                    change: None,
+                    annotations: None,
+                    position: None,
+                    style: None,
+                    href: None,
                 },
                 GraphNode {
                     id: "module_b".to_string(),
@@ -710,6 +1173,16 @@ mod tests {
                     is_orphan: false,
                     highlighted: None,
                     parent: None,
+                    import_time_us: None,
+                    coverage: None,
+                    category: None,
+                    lifecycle: None,
+                    generated: None,
+                    change: None,
+                    annotations: None,
+                    position: None,
+                    style: None,
+                    href: None,
                 },
                 GraphNode {
                     id: "orphan_c".to_string(),
@@ -717,20 +1190,41 @@ mod tests {
                     is_orphan: true,
                     highlighted: None,
                     parent: None,
+                    import_time_us: None,
+                    coverage: None,
+                    category: None,
+                    lifecycle: None,
+                    generated: None,
+                    change: None,
+                    annotations: None,
+                    position: None,
+                    style: None,
+                    href: None,
                 },
             ];
             let edges = vec![
                 GraphEdge {
                     source: "module_a".to_string(),
                     target: "module_b".to_string(),
+                    kind: None,
+                    tags: None,
+                    count: None,
+                    change: None,
+                    style: None,
                 },
                 GraphEdge {
                     source: "module_a".to_string(),
                     target: "orphan_c".to_string(),
+                    kind: None,
+                    tags: None,
+                    count: None,
+                    change: None,
+                    style: None,
                 },
             ];
 
             let graph_data = GraphData {
+                version: GRAPH_DATA_VERSION,
                 nodes,
                 edges,
                 config: None,
@@ -769,6 +1263,7 @@ mod tests {
         fn test_highlighted_only_with_interactive_filters() {
             let (nodes, edges) = create_test_graph();
             let graph_data = GraphData {
+                version: GRAPH_DATA_VERSION,
                 nodes,
                 edges,
                 config: None,
@@ -802,5 +1297,380 @@ mod tests {
                 assert!(result.highlighted.contains(&"module_b".to_string()));
             }
         }
+
+        #[test]
+        fn test_filter_downstream_roots() {
+            let (nodes, edges) = create_test_graph();
+            let processor = GraphProcessor::from_graph_data(GraphData {
+                version: GRAPH_DATA_VERSION,
+                nodes,
+                edges,
+                config: None,
+            });
+
+            // Shared by filter_nodes and filter_nodes_object, so it runs on every target
+            let mut result = processor.filter(&FilterConfig {
+                show_orphans: true,
+                show_namespaces: true,
+                exclude_patterns: vec![],
+                upstream_roots: vec![],
+                downstream_roots: vec!["module_b".to_string()],
+                max_distance: None,
+                highlighted_only: true,
+                highlight_neighbors: 0,
+                fade_filtered: false,
+                edge_direction: EdgeDirection::Both,
+                weight_key: None,
+            });
+            result.visible.sort();
+            result.highlighted.sort();
+
+            assert_eq!(result.visible, vec!["module_a", "module_b"]);
+            assert_eq!(result.highlighted, vec!["module_a", "module_b"]);
+        }
+
+        #[test]
+        fn test_fade_filtered_keeps_hidden_nodes() {
+            let (nodes, edges) = create_test_graph();
+            let processor = GraphProcessor::from_graph_data(GraphData {
+                version: GRAPH_DATA_VERSION,
+                nodes,
+                edges,
+                config: None,
+            });
+            let config = |fade_filtered| FilterConfig {
+                show_orphans: false,
+                show_namespaces: true,
+                exclude_patterns: vec![],
+                upstream_roots: vec![],
+                downstream_roots: vec![],
+                max_distance: None,
+                highlighted_only: true,
+                highlight_neighbors: 0,
+                fade_filtered,
+                edge_direction: EdgeDirection::Both,
+                weight_key: None,
+            };
+
+            let hidden = processor.filter(&config(false));
+            assert!(hidden.faded.is_empty());
+
+            let mut faded = processor.filter(&config(true));
+            faded.visible.sort();
+            assert_eq!(faded.visible, vec!["module_a", "module_b"]);
+            assert_eq!(faded.faded, vec!["orphan_c"]);
+        }
+
+        #[test]
+        fn test_filter_state_presets_and_encoding_roundtrip() {
+            let (nodes, edges) = create_test_graph();
+            let processor = GraphProcessor::from_graph_data(GraphData {
+                version: GRAPH_DATA_VERSION,
+                nodes: nodes.clone(),
+                edges: edges.clone(),
+                config: None,
+            });
+            let config = FilterConfig {
+                show_orphans: false,
+                show_namespaces: true,
+                exclude_patterns: vec!["*test*".to_string()],
+                upstream_roots: vec!["module_a".to_string()],
+                downstream_roots: vec![],
+                max_distance: Some(1),
+                highlighted_only: true,
+                highlight_neighbors: 0,
+                fade_filtered: true,
+                edge_direction: EdgeDirection::Both,
+                weight_key: None,
+            };
+            processor.apply(config.clone());
+
+            let current = processor.current_filter.borrow().clone().unwrap();
+            let exported = serde_json::to_string(&FilterPreset {
+                name: "view".to_string(),
+                config: current,
+            })
+            .unwrap();
+
+            let other = GraphProcessor::from_graph_data(GraphData {
+                version: GRAPH_DATA_VERSION,
+                nodes,
+                edges,
+                config: None,
+            });
+            let imported = other.parse_preset(&exported).unwrap();
+            assert_eq!(imported.name, "view");
+            assert_eq!(imported.config, config);
+
+            let stale = exported.replace("module_a", "module_gone");
+            assert_eq!(
+                other.parse_preset(&stale).unwrap_err(),
+                "Preset 'view' refers to unknown module 'module_gone'"
+            );
+
+            assert_eq!(other.parse_encoded(&config.encode()).unwrap(), config);
+            let stale = FilterConfig {
+                upstream_roots: vec!["module_gone".to_string()],
+                ..config
+            };
+            assert_eq!(
+                other.parse_encoded(&stale.encode()).unwrap_err(),
+                "Filter state refers to unknown module 'module_gone'"
+            );
+        }
+
+        #[test]
+        fn test_highlight_neighbors() {
+            // a -> b -> c -> d, with b highlighted by the CLI
+            let node = |id: &str| GraphNode {
+                id: id.to_string(),
+                node_type: "module".to_string(),
+                is_orphan: false,
+                highlighted: Some(id == "b"),
+                parent: None,
+                import_time_us: None,
+                coverage: None,
+                category: None,
+                lifecycle: None,
+                generated: None,
+                change: None,
+                annotations: None,
+                position: None,
+                style: None,
+                href: None,
+            };
+            let edge = |source: &str, target: &str| GraphEdge {
+                source: source.to_string(),
+                target: target.to_string(),
+                kind: None,
+                tags: None,
+                count: None,
+                change: None,
+                style: None,
+            };
+            let processor = GraphProcessor::from_graph_data(GraphData {
+                version: GRAPH_DATA_VERSION,
+                nodes: vec![node("a"), node("b"), node("c"), node("d")],
+                edges: vec![edge("a", "b"), edge("b", "c"), edge("c", "d")],
+                config: None,
+            });
+            let config = |highlight_neighbors| FilterConfig {
+                show_orphans: true,
+                show_namespaces: true,
+                exclude_patterns: vec![],
+                upstream_roots: vec![],
+                downstream_roots: vec![],
+                max_distance: None,
+                highlighted_only: false,
+                highlight_neighbors,
+                fade_filtered: false,
+                edge_direction: EdgeDirection::Both,
+                weight_key: None,
+            };
+
+            assert_eq!(processor.filter(&config(0)).highlighted, vec!["b"]);
+            assert_eq!(
+                processor.filter(&config(1)).highlighted,
+                vec!["b", "a", "c"]
+            );
+            assert_eq!(
+                processor.filter(&config(2)).highlighted,
+                vec!["b", "a", "c", "d"]
+            );
+        }
+
+        #[test]
+        fn test_weight_key_measures_max_distance_along_weighted_imports() {
+            // a -> b -> c -> d, where a imports b three times and c has 5 lines
+            let node = |id: &str, loc: Option<&str>| GraphNode {
+                id: id.to_string(),
+                node_type: "module".to_string(),
+                is_orphan: false,
+                highlighted: None,
+                parent: None,
+                import_time_us: None,
+                coverage: None,
+                category: None,
+                lifecycle: None,
+                generated: None,
+                change: None,
+                annotations: loc.map(|loc| [("loc".to_string(), loc.to_string())].into()),
+                position: None,
+                style: None,
+                href: None,
+            };
+            let edge = |source: &str, target: &str, count: Option<usize>| GraphEdge {
+                source: source.to_string(),
+                target: target.to_string(),
+                kind: None,
+                tags: None,
+                count,
+                change: None,
+                style: None,
+            };
+            let processor = GraphProcessor::from_graph_data(GraphData {
+                version: GRAPH_DATA_VERSION,
+                nodes: vec![
+                    node("a", None),
+                    node("b", None),
+                    node("c", Some("5")),
+                    node("d", None),
+                ],
+                edges: vec![
+                    edge("a", "b", Some(3)),
+                    edge("b", "c", None),
+                    edge("c", "d", None),
+                ],
+                config: None,
+            });
+            let config = |weight_key: Option<&str>| FilterConfig {
+                show_orphans: true,
+                show_namespaces: true,
+                exclude_patterns: vec![],
+                upstream_roots: vec!["a".to_string()],
+                downstream_roots: vec![],
+                max_distance: Some(4),
+                highlighted_only: true,
+                highlight_neighbors: 0,
+                fade_filtered: false,
+                edge_direction: EdgeDirection::Both,
+                weight_key: weight_key.map(String::from),
+            };
+            let visible = |weight_key| {
+                let mut visible = processor.filter(&config(weight_key)).visible;
+                visible.sort();
+                visible
+            };
+
+            assert_eq!(visible(None), vec!["a", "b", "c", "d"]);
+            assert_eq!(visible(Some("count")), vec!["a", "b", "c"]);
+            assert_eq!(visible(Some("loc")), vec!["a", "b"]);
+            assert_eq!(visible(Some("team")), vec!["a", "b", "c", "d"]);
+        }
+
+        #[test]
+        fn test_edge_direction_hides_edges() {
+            // a -> b -> c and d -> b, selected around b
+            let node = |id: &str| GraphNode {
+                id: id.to_string(),
+                node_type: "module".to_string(),
+                is_orphan: false,
+                highlighted: None,
+                parent: None,
+                import_time_us: None,
+                coverage: None,
+                category: None,
+                lifecycle: None,
+                generated: None,
+                change: None,
+                annotations: None,
+                position: None,
+                style: None,
+                href: None,
+            };
+            let edge = |source: &str, target: &str| GraphEdge {
+                source: source.to_string(),
+                target: target.to_string(),
+                kind: None,
+                tags: None,
+                count: None,
+                change: None,
+                style: None,
+            };
+            let processor = GraphProcessor::from_graph_data(GraphData {
+                version: GRAPH_DATA_VERSION,
+                nodes: vec![node("a"), node("b"), node("c"), node("d")],
+                edges: vec![edge("a", "b"), edge("b", "c"), edge("d", "b")],
+                config: None,
+            });
+            let config = |edge_direction| FilterConfig {
+                show_orphans: true,
+                show_namespaces: true,
+                exclude_patterns: vec![],
+                upstream_roots: vec!["b".to_string()],
+                downstream_roots: vec![],
+                max_distance: None,
+                highlighted_only: false,
+                highlight_neighbors: 0,
+                fade_filtered: false,
+                edge_direction,
+                weight_key: None,
+            };
+            let hidden = |edge_direction| {
+                let mut edges = processor.filter(&config(edge_direction)).hidden_edges;
+                edges.sort();
+                edges
+            };
+            let pair = |source: &str, target: &str| (source.to_string(), target.to_string());
+
+            assert!(hidden(EdgeDirection::Both).is_empty());
+            assert_eq!(
+                hidden(EdgeDirection::Dependencies),
+                vec![pair("a", "b"), pair("d", "b")]
+            );
+            assert_eq!(hidden(EdgeDirection::Dependents), vec![pair("b", "c")]);
+        }
+
+        #[test]
+        fn test_run_queries_batch() {
+            let (nodes, edges) = create_test_graph();
+            let processor = GraphProcessor::from_graph_data(GraphData {
+                version: GRAPH_DATA_VERSION,
+                nodes,
+                edges,
+                config: None,
+            });
+
+            let ops: Vec<QueryOp> = serde_json::from_str(
+                r#"[
+                    {"op": "upstream", "roots": ["module_a"]},
+                    {"op": "downstream", "roots": ["module_b"], "maxDistance": 1},
+                    {"op": "distances", "from": "module_a"},
+                    {"op": "search", "pattern": "module_*"},
+                    {"op": "distances", "from": "unknown"}
+                ]"#,
+            )
+            .unwrap();
+            let results = processor.run_queries(&ops);
+
+            assert_eq!(
+                results,
+                vec![
+                    QueryResult::Nodes(vec!["module_a".to_string(), "module_b".to_string()]),
+                    QueryResult::Nodes(vec!["module_a".to_string(), "module_b".to_string()]),
+                    QueryResult::Distances(HashMap::from([
+                        ("module_a".to_string(), 0),
+                        ("module_b".to_string(), 1),
+                    ])),
+                    QueryResult::Nodes(vec!["module_a".to_string(), "module_b".to_string()]),
+                    QueryResult::Distances(HashMap::new()),
+                ]
+            );
+        }
+
+        #[test]
+        fn test_distance_job_progress() {
+            let (nodes, edges) = create_test_graph();
+            let processor = GraphProcessor::from_graph_data(GraphData {
+                version: GRAPH_DATA_VERSION,
+                nodes,
+                edges,
+                config: None,
+            });
+
+            let mut job = processor.start_distances();
+            assert_eq!(job.progress(), 0.0);
+            assert!(!job.step(2));
+            assert!((job.progress() - 2.0 / 3.0).abs() < f64::EPSILON);
+            assert!(job.step(2));
+            assert!(job.is_done());
+            assert_eq!(job.progress(), 1.0);
+        }
+
+        #[test]
+        fn test_query_op_rejects_unknown_op() {
+            let parsed = serde_json::from_str::<Vec<QueryOp>>(r#"[{"op": "explode"}]"#);
+            assert!(parsed.is_err());
+        }
     }
 }
@@ -0,0 +1,84 @@
+//! Console logging of the graph processor
+//!
+//! Errors (a filter configuration that does not parse) are always compiled in and logged
+//! unless the level is `off`. The per-call trace of `filter_nodes` is only compiled in with
+//! the `debug-logging` feature, so release builds carry neither its strings nor its
+//! formatting; with the feature it still stays quiet until `set_log_level("debug")`.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use wasm_bindgen::prelude::*;
+
+/// How much the processor logs to the browser console
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum LogLevel {
+    Off = 0,
+    Error = 1,
+    Debug = 2,
+}
+
+impl LogLevel {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "off" => Some(LogLevel::Off),
+            "error" => Some(LogLevel::Error),
+            "debug" => Some(LogLevel::Debug),
+            _ => None,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => LogLevel::Off,
+            1 => LogLevel::Error,
+            _ => LogLevel::Debug,
+        }
+    }
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Error as u8);
+
+/// Current level (`error` until changed)
+pub fn log_level() -> LogLevel {
+    LogLevel::from_u8(LEVEL.load(Ordering::Relaxed))
+}
+
+/// Whether messages of `level` are logged at the current level
+pub fn enabled(level: LogLevel) -> bool {
+    level != LogLevel::Off && level <= log_level()
+}
+
+/// Set how much the processor logs: `"off"`, `"error"` (the default), or `"debug"`.
+/// `"debug"` only has an effect in builds with the `debug-logging` feature.
+#[wasm_bindgen]
+pub fn set_log_level(level: &str) -> Result<(), JsValue> {
+    let level = LogLevel::parse(level).ok_or_else(|| {
+        JsValue::from_str(&format!(
+            "Unknown log level '{level}' (expected off, error, or debug)"
+        ))
+    })?;
+    LEVEL.store(level as u8, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Log an error to the console, formatting the message only when errors are logged
+macro_rules! log_error {
+    ($($arg:tt)*) => {
+        #[cfg(target_arch = "wasm32")]
+        if $crate::logging::enabled($crate::logging::LogLevel::Error) {
+            web_sys::console::error_1(&format!($($arg)*).into());
+        }
+    };
+}
+
+/// Log a trace message to the console; compiled out without the `debug-logging` feature
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        #[cfg(all(target_arch = "wasm32", feature = "debug-logging"))]
+        if $crate::logging::enabled($crate::logging::LogLevel::Debug) {
+            web_sys::console::log_1(&format!($($arg)*).into());
+        }
+    };
+}
+
+pub(crate) use {log_debug, log_error};
@@ -0,0 +1,22 @@
+//! Tests for the console log level of the graph processor
+
+use deptree_wasm::logging::{LogLevel, enabled, log_level, set_log_level};
+
+#[test]
+fn test_log_level_parse_rejects_unknown_levels() {
+    assert_eq!(LogLevel::parse("verbose"), None);
+}
+
+// The level is global, so every change of it stays in this one test
+#[test]
+fn test_set_log_level_gates_messages_by_level() {
+    assert_eq!(log_level(), LogLevel::Error);
+    assert!(enabled(LogLevel::Error));
+    assert!(!enabled(LogLevel::Debug));
+
+    set_log_level("debug").unwrap();
+    assert!(enabled(LogLevel::Debug));
+    set_log_level("off").unwrap();
+    assert!(!enabled(LogLevel::Error));
+    assert!(!enabled(LogLevel::Off));
+}
@@ -1,5 +1,10 @@
 import { describe, it, expect } from "vitest";
-import { getCytoscapeStyles, HIGHLIGHT_SELECTOR } from "./cytoscape-manager";
+import {
+  getCytoscapeStyles,
+  hasLayoutHints,
+  HIGHLIGHT_SELECTOR,
+} from "./cytoscape-manager";
+import type { GraphData } from "./types";
 
 describe("cytoscape-manager styles", () => {
   it("uses a truthy selector for highlighted nodes so false values are not styled", () => {
@@ -12,3 +17,17 @@ describe("cytoscape-manager styles", () => {
     expect(highlighted?.selector).toBe(HIGHLIGHT_SELECTOR);
   });
 });
+
+describe("cytoscape-manager layout hints", () => {
+  it("uses the CLI positions only when the graph carries them", () => {
+    const graph: GraphData = {
+      version: 1,
+      nodes: [{ id: "app", type: "module", is_orphan: false }],
+      edges: [],
+    };
+    expect(hasLayoutHints(graph)).toBe(false);
+
+    graph.nodes[0].position = { x: 10, y: 20 };
+    expect(hasLayoutHints(graph)).toBe(true);
+  });
+});
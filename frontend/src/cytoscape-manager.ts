@@ -20,14 +20,17 @@ export function initializeCytoscape(
   // Transform graph data to Cytoscape elements format
   const elements = transformToElements(graphData, distances);
 
-  // Initialize Cytoscape
-  const layoutOptions: LayoutOptionsWithExtensions = {
-    name: "dagre",
-    rankDir: "LR",
-    nodeSep: 50,
-    rankSep: 100,
-    padding: 30,
-  };
+  // Initialize Cytoscape, at the positions computed by the CLI when it was given
+  // --layout-seed
+  const layoutOptions: LayoutOptionsWithExtensions = hasLayoutHints(graphData)
+    ? { name: "preset", fit: true, padding: 30 }
+    : {
+        name: "dagre",
+        rankDir: "LR",
+        nodeSep: 50,
+        rankSep: 100,
+        padding: 30,
+      };
 
   const cy = cytoscape({
     container: document.getElementById("cy"),
@@ -46,6 +49,13 @@ export function initializeCytoscape(
   return cy;
 }
 
+/**
+ * Whether the graph carries node positions from the CLI (`--layout-seed`)
+ */
+export function hasLayoutHints(graphData: GraphData): boolean {
+  return graphData.nodes.some((node) => node.position !== undefined);
+}
+
 /**
  * Register Cytoscape layout extension libraries
  */
@@ -85,6 +95,12 @@ function transformToElements(
 ): cytoscapeTypes.ElementDefinition[] {
   const elements: cytoscapeTypes.ElementDefinition[] = [];
 
+  // Import durations are scaled against the slowest module in the graph
+  const maxImportTime = Math.max(
+    0,
+    ...graphData.nodes.map((node) => node.import_time_us ?? 0),
+  );
+
   // Add nodes
   for (const node of graphData.nodes) {
     const data: Record<string, any> = {
@@ -106,17 +122,88 @@ function transformToElements(
       data.highlighted = true;
     }
 
-    elements.push({ data });
+    // Only set import_heat when an import profile was supplied
+    if (node.import_time_us !== undefined && maxImportTime > 0) {
+      data.import_time_us = node.import_time_us;
+      data.import_heat = node.import_time_us / maxImportTime;
+    }
+
+    // Only set coverage when a coverage report was supplied
+    if (node.coverage !== undefined) {
+      data.coverage = node.coverage;
+    }
+
+    // User-defined category from the configuration (e.g. "automation")
+    if (node.category) {
+      data.category = node.category;
+    }
+
+    // Declared lifecycle state (experimental, stable, or sunset)
+    if (node.lifecycle) {
+      data.lifecycle = node.lifecycle;
+    }
+
+    // Generated code (protobuf modules, @generated files, ...)
+    if (node.generated) {
+      data.generated = true;
+    }
+
+    // Classification in a `diff` output (added, removed, or unchanged)
+    if (node.change) {
+      data.change = node.change;
+    }
+
+    // Free-form metadata from an annotations file (team, tier, ...)
+    if (node.annotations) {
+      data.annotations = node.annotations;
+    }
+
+    // Link to the module's source (`--link-template`)
+    if (node.href) {
+      data.href = node.href;
+    }
+
+    // Style overrides use the stylesheet's property names, which the typings don't
+    // index by string
+    const element: Record<string, any> = { data };
+    // Position hint from the CLI (`--layout-seed`), used by the initial preset layout
+    if (node.position) {
+      element.position = node.position;
+    }
+    // Attributes from the renderer's style hooks override the stylesheet
+    if (node.style) {
+      element.style = node.style;
+    }
+    elements.push(element as cytoscapeTypes.ElementDefinition);
   }
 
   // Add edges
   for (const edge of graphData.edges) {
-    elements.push({
-      data: {
-        source: edge.source,
-        target: edge.target,
-      },
-    });
+    const data: Record<string, any> = {
+      source: edge.source,
+      target: edge.target,
+    };
+
+    // Only set kind for deferred edges so the default edge style applies otherwise
+    if (edge.kind) {
+      data.kind = edge.kind;
+    }
+
+    // Repeated imports are drawn thicker
+    if (edge.count) {
+      data.count = edge.count;
+    }
+
+    // Classification in a `diff` output (added, removed, or unchanged)
+    if (edge.change) {
+      data.change = edge.change;
+    }
+
+    const element: Record<string, any> = { data };
+    if (edge.style) {
+      element.style = edge.style;
+    }
+    elements.push(element as cytoscapeTypes.ElementDefinition);
   }
 
   return elements;
@@ -168,6 +255,88 @@ export function getCytoscapeStyles(): cytoscapeTypes.StylesheetJson {
       },
     },
 
+    // Compiled extension modules (Cython; octagon, purple)
+    {
+      selector: 'node[type="extension"]',
+      style: {
+        shape: "octagon",
+        "background-color": "#ce93d8",
+        "border-color": "#7b1fa2",
+      },
+    },
+
+    // Nodes with a measured import duration (white to red by self import time)
+    {
+      selector: "node[import_heat]",
+      style: {
+        "background-color": "mapData(import_heat, 0, 1, #ffffff, #ff4040)",
+      },
+    },
+
+    // Nodes with test coverage (red at 0% to green at 100%)
+    {
+      selector: "node[coverage]",
+      style: {
+        "background-color": "mapData(coverage, 0, 100, #e57373, #81c784)",
+      },
+    },
+
+    // Experimental modules (dotted border)
+    {
+      selector: 'node[lifecycle="experimental"]',
+      style: {
+        "border-width": 2,
+        "border-style": "dotted",
+      },
+    },
+
+    // Sunset modules, slated for removal (faded, dashed red border)
+    {
+      selector: 'node[lifecycle="sunset"]',
+      style: {
+        "border-width": 2,
+        "border-style": "dashed",
+        "border-color": "#c62828",
+        opacity: 0.7,
+      },
+    },
+
+    // Generated modules (faded, gray background)
+    {
+      selector: "node[?generated]",
+      style: {
+        "background-color": "#bdbdbd",
+        opacity: 0.6,
+      },
+    },
+
+    // Diff output: added modules green, removed ones red and dashed, the rest grey
+    {
+      selector: 'node[change="added"]',
+      style: {
+        "background-color": "#a5d6a7",
+        "border-color": "#2e7d32",
+        "border-width": 3,
+      },
+    },
+    {
+      selector: 'node[change="removed"]',
+      style: {
+        "background-color": "#ef9a9a",
+        "border-color": "#c62828",
+        "border-width": 3,
+        "border-style": "dashed",
+      },
+    },
+    {
+      selector: 'node[change="unchanged"]',
+      style: {
+        "background-color": "#eeeeee",
+        "border-color": "#9e9e9e",
+        color: "#757575",
+      },
+    },
+
     // Highlighted nodes (filtered results)
     {
       // Use truthy check so nodes with highlighted=false won't be styled
@@ -208,6 +377,26 @@ export function getCytoscapeStyles(): cytoscapeTypes.StylesheetJson {
       },
     },
 
+    // Detected communities (--communities) used as compound nodes
+    {
+      selector: 'node[type="community"]',
+      style: {
+        "background-color": "#f3e5f5",
+        "background-opacity": 0.2,
+        "border-color": "#8e24aa",
+      },
+    },
+
+    // Annotation groups (--group-by) used as compound nodes
+    {
+      selector: 'node[type="group"]',
+      style: {
+        "background-color": "#e8f5e9",
+        "background-opacity": 0.2,
+        "border-color": "#388e3c",
+      },
+    },
+
     // Edges
     {
       selector: "edge",
@@ -220,6 +409,47 @@ export function getCytoscapeStyles(): cytoscapeTypes.StylesheetJson {
         "arrow-scale": 1.2,
       },
     },
+
+    // Edges imported several times, thicker up to ten imports
+    {
+      selector: "edge[count]",
+      style: {
+        width: "mapData(count, 2, 10, 3, 8)",
+      },
+    },
+
+    // Deferred edges (imports inside function bodies)
+    {
+      selector: 'edge[kind="deferred"]',
+      style: {
+        "line-style": "dashed",
+      },
+    },
+
+    // Diff output: added imports green, removed ones red and dashed, the rest grey
+    {
+      selector: 'edge[change="added"]',
+      style: {
+        width: 3,
+        "line-color": "#2e7d32",
+        "target-arrow-color": "#2e7d32",
+      },
+    },
+    {
+      selector: 'edge[change="removed"]',
+      style: {
+        "line-color": "#c62828",
+        "target-arrow-color": "#c62828",
+        "line-style": "dashed",
+      },
+    },
+    {
+      selector: 'edge[change="unchanged"]',
+      style: {
+        "line-color": "#d6d6d6",
+        "target-arrow-color": "#d6d6d6",
+      },
+    },
   ];
 }
 
@@ -232,7 +462,18 @@ function setupEventHandlers(cy: cytoscapeTypes.Core): void {
     const node = evt.target;
     const info = document.getElementById("info");
     if (info) {
-      info.textContent = `Selected: ${node.data("label")}`;
+      const annotations: Record<string, string> =
+        node.data("annotations") ?? {};
+      const details = [
+        node.data("category"),
+        node.data("lifecycle"),
+        node.data("generated") && "generated",
+        node.data("change"),
+        ...Object.entries(annotations).map(([key, value]) => `${key}=${value}`),
+      ].filter(Boolean);
+      info.textContent = details.length
+        ? `Selected: ${node.data("label")} (${details.join(", ")})`
+        : `Selected: ${node.data("label")}`;
     }
   });
 
@@ -242,6 +483,11 @@ function setupEventHandlers(cy: cytoscapeTypes.Core): void {
       info.textContent = "";
     }
   });
+
+  // Open the module's source on double click, when the graph has source links
+  cy.on("dbltap", "node[href]", (evt) => {
+    window.open(evt.target.data("href"), "_blank", "noopener");
+  });
 }
 
 /**
@@ -0,0 +1,52 @@
+import { describe, it, expect, vi } from "vitest";
+import { computeDistancesInChunks } from "./distances";
+
+// Mock DistanceJob that finishes after `chunks` calls to step()
+function createMockJob(chunks: number) {
+  let calls = 0;
+  return {
+    step: vi.fn(() => {
+      calls += 1;
+      return calls >= chunks;
+    }),
+    progress: vi.fn(() => calls / chunks),
+    result: vi.fn(() => ({ module_a: { module_a: 0, module_b: 1 } })),
+    free: vi.fn(),
+  };
+}
+
+describe("computeDistancesInChunks", () => {
+  it("should step until done and report progress", async () => {
+    const job = createMockJob(3);
+    const onProgress = vi.fn();
+
+    const distances = await computeDistancesInChunks(job, onProgress, undefined, 10);
+
+    expect(job.step).toHaveBeenCalledTimes(3);
+    expect(job.step).toHaveBeenCalledWith(10);
+    expect(onProgress.mock.calls.map((call) => call[0])).toEqual([
+      1 / 3,
+      2 / 3,
+      1,
+    ]);
+    expect(distances).toEqual({ module_a: { module_a: 0, module_b: 1 } });
+    expect(job.free).toHaveBeenCalled();
+  });
+
+  it("should stop and free the job when aborted", async () => {
+    const job = createMockJob(5);
+    const controller = new AbortController();
+    const onProgress = vi.fn(() => controller.abort());
+
+    const distances = await computeDistancesInChunks(
+      job,
+      onProgress,
+      controller.signal,
+    );
+
+    expect(distances).toBeNull();
+    expect(job.step).toHaveBeenCalledTimes(1);
+    expect(job.result).not.toHaveBeenCalled();
+    expect(job.free).toHaveBeenCalled();
+  });
+});
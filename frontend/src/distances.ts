@@ -0,0 +1,37 @@
+import type { DistanceMap } from "./types";
+
+/** Source nodes processed per chunk before yielding back to the event loop */
+export const DISTANCE_CHUNK_SIZE = 200;
+
+/** The parts of the WASM `DistanceJob` used here */
+export interface DistanceJobLike {
+  step(chunkSize: number): boolean;
+  progress(): number;
+  result(): unknown;
+  free(): void;
+}
+
+/**
+ * Compute all-pairs distances in chunks, yielding to the event loop between chunks so the
+ * page stays responsive on big graphs. Resolves with null if `signal` is aborted.
+ */
+export async function computeDistancesInChunks(
+  job: DistanceJobLike,
+  onProgress: (fraction: number) => void,
+  signal?: AbortSignal,
+  chunkSize: number = DISTANCE_CHUNK_SIZE,
+): Promise<DistanceMap | null> {
+  try {
+    while (!job.step(chunkSize)) {
+      onProgress(job.progress());
+      await new Promise((resolve) => setTimeout(resolve, 0));
+      if (signal?.aborted) {
+        return null;
+      }
+    }
+    onProgress(1);
+    return job.result() as DistanceMap;
+  } finally {
+    job.free();
+  }
+}
@@ -4,7 +4,7 @@ import { FilterState } from "./filter-state";
 // Create mock GraphProcessor directly without importing WASM
 function createMockGraphProcessor() {
   return {
-    filter_nodes: vi.fn(() => ({
+    filter_nodes_object: vi.fn(() => ({
       visible: ["module_a", "module_b"],
       highlighted: ["module_a", "module_b"],
     })),
@@ -53,6 +53,9 @@ describe("FilterState", () => {
       expect(config.excludePatterns).toEqual([]);
       expect(config.maxDistance).toBe(null);
       expect(config.highlightedOnly).toBe(true);
+      expect(config.highlightNeighbors).toBe(0);
+      expect(config.fadeFiltered).toBe(false);
+      expect(config.edgeDirection).toBe("both");
     });
   });
 
@@ -77,6 +80,76 @@ describe("FilterState", () => {
       filterState.setMaxDistance(5);
       expect(filterState.getConfig().maxDistance).toBe(5);
     });
+
+    it("should set highlight neighbors", () => {
+      filterState.setHighlightNeighbors(2);
+      expect(filterState.getConfig().highlightNeighbors).toBe(2);
+    });
+
+    it("should set edge direction", () => {
+      filterState.setEdgeDirection("dependents");
+      expect(filterState.getConfig().edgeDirection).toBe("dependents");
+    });
+  });
+
+  describe("presets", () => {
+    it("should export the applied filters through WASM", () => {
+      mockProcessor.export_filter_state = vi.fn(() => '{"name":"view"}');
+
+      expect(filterState.exportPreset("view")).toBe('{"name":"view"}');
+      expect(mockProcessor.export_filter_state).toHaveBeenCalledWith("view");
+    });
+
+    it("should load an imported preset as the current config", () => {
+      mockProcessor.import_filter_state = vi.fn(() => ({
+        name: "view",
+        config: {
+          showOrphans: false,
+          showNamespaces: true,
+          excludePatterns: ["*test*"],
+          upstreamRoots: ["module_a"],
+          downstreamRoots: [],
+          highlightedOnly: false,
+          highlightNeighbors: 1,
+          fadeFiltered: true,
+          edgeDirection: "dependencies",
+        },
+      }));
+
+      const preset = filterState.importPreset("{}");
+
+      expect(preset.name).toBe("view");
+      const config = filterState.getConfig();
+      expect(config.showOrphans).toBe(false);
+      expect(config.maxDistance).toBe(null);
+      expect(config.fadeFiltered).toBe(true);
+      expect(config.edgeDirection).toBe("dependencies");
+      expect(filterState.getUpstreamRoots()).toEqual(["module_a"]);
+    });
+  });
+
+  describe("encoded filter state", () => {
+    it("should load a decoded state as the current config", () => {
+      mockProcessor.decode_filter_state = vi.fn(() => ({
+        showOrphans: true,
+        showNamespaces: false,
+        excludePatterns: [],
+        upstreamRoots: [],
+        downstreamRoots: ["module_b"],
+        maxDistance: 2,
+        highlightedOnly: true,
+        highlightNeighbors: 0,
+        fadeFiltered: false,
+        edgeDirection: "both",
+      }));
+
+      filterState.decodeState("AXic");
+
+      expect(mockProcessor.decode_filter_state).toHaveBeenCalledWith("AXic");
+      expect(filterState.getConfig().showNamespaces).toBe(false);
+      expect(filterState.getConfig().maxDistance).toBe(2);
+      expect(filterState.getDownstreamRoots()).toEqual(["module_b"]);
+    });
   });
 
   describe("upstream/downstream roots", () => {
@@ -100,11 +173,11 @@ describe("FilterState", () => {
   });
 
   describe("applyFilters", () => {
-    it("should call WASM filter_nodes with correct config", () => {
+    it("should call WASM filter_nodes_object with correct config", () => {
       filterState.applyFilters();
 
-      expect(mockProcessor.filter_nodes).toHaveBeenCalledWith(
-        expect.stringContaining('"showOrphans":true'),
+      expect(mockProcessor.filter_nodes_object).toHaveBeenCalledWith(
+        expect.objectContaining({ showOrphans: true, upstreamRoots: [] }),
       );
     });
 
@@ -118,8 +191,8 @@ describe("FilterState", () => {
     });
 
     it("should hide nodes not in visible set", () => {
-      // Mock filter_nodes to return only one node
-      mockProcessor.filter_nodes.mockReturnValue({
+      // Mock filter_nodes_object to return only one node
+      mockProcessor.filter_nodes_object.mockReturnValue({
         visible: ["module_a"],
         highlighted: ["module_a"],
       });
@@ -130,6 +203,49 @@ describe("FilterState", () => {
       expect(nodes[0].style).toHaveBeenCalledWith("display", "element");
       expect(nodes[1].style).toHaveBeenCalledWith("display", "none");
     });
+
+    it("should keep faded nodes on screen at low opacity", () => {
+      filterState.toggleFadeFiltered(true);
+      mockProcessor.filter_nodes_object.mockReturnValue({
+        visible: ["module_a"],
+        highlighted: ["module_a"],
+        faded: ["module_b"],
+      });
+
+      filterState.applyFilters();
+
+      expect(mockProcessor.filter_nodes_object).toHaveBeenCalledWith(
+        expect.objectContaining({ fadeFiltered: true }),
+      );
+      const nodes = mockCy.nodes();
+      expect(nodes[1].style).toHaveBeenCalledWith("display", "element");
+      expect(nodes[1].style).toHaveBeenCalledWith("opacity", 0.2);
+      expect(nodes[0].style).toHaveBeenCalledWith("opacity", 1);
+      const edges = mockCy.edges();
+      expect(edges[0].style).toHaveBeenCalledWith("display", "element");
+      expect(edges[0].style).toHaveBeenCalledWith("opacity", 0.2);
+    });
+  });
+
+  describe("edge direction", () => {
+    it("should hide edges listed in hiddenEdges", () => {
+      filterState.setEdgeDirection("downstream");
+      mockProcessor.filter_nodes_object.mockReturnValue({
+        visible: ["module_a", "module_b"],
+        highlighted: ["module_b"],
+        hiddenEdges: [["module_a", "module_b"]],
+      });
+
+      filterState.applyFilters();
+
+      expect(mockProcessor.filter_nodes_object).toHaveBeenCalledWith(
+        expect.objectContaining({ edgeDirection: "downstream" }),
+      );
+      const nodes = mockCy.nodes();
+      expect(nodes[0].style).toHaveBeenCalledWith("display", "element");
+      const edges = mockCy.edges();
+      expect(edges[0].style).toHaveBeenCalledWith("display", "none");
+    });
   });
 
   describe("highlightedOnly behavior", () => {
@@ -142,8 +258,7 @@ describe("FilterState", () => {
       // Expected: All nodes should remain visible
 
       // Mock WASM to simulate: no interactive filters, no CLI highlighting
-      mockProcessor.filter_nodes.mockImplementation((configJson: string) => {
-        const config = JSON.parse(configJson);
+      mockProcessor.filter_nodes_object.mockImplementation((config: any) => {
 
         // Verify the config being passed
         expect(config.upstreamRoots).toEqual([]);
@@ -176,7 +291,7 @@ describe("FilterState", () => {
       filterState.addUpstreamRoot("module_a");
 
       // Mock WASM to return: all nodes visible, only upstream highlighted
-      mockProcessor.filter_nodes.mockReturnValue({
+      mockProcessor.filter_nodes_object.mockReturnValue({
         visible: ["module_a", "module_b"], // All nodes
         highlighted: ["module_a"], // Only upstream
       });
@@ -197,7 +312,7 @@ describe("FilterState", () => {
       filterState.addUpstreamRoot("module_a");
 
       // Mock WASM to return: only upstream nodes visible and highlighted
-      mockProcessor.filter_nodes.mockReturnValue({
+      mockProcessor.filter_nodes_object.mockReturnValue({
         visible: ["module_a"], // Only upstream
         highlighted: ["module_a"],
       });
@@ -1,7 +1,15 @@
 import type cytoscape from "cytoscape";
-import type { FilterConfig, FilterResult } from "./types";
+import type {
+  EdgeDirection,
+  FilterConfig,
+  FilterPreset,
+  FilterResult,
+} from "./types";
 import type { GraphProcessor } from "./wasm/deptree_wasm";
 
+/** Opacity of nodes and edges kept on screen although filtered out */
+const FADED_OPACITY = 0.2;
+
 export class FilterState {
   private processor: GraphProcessor;
   private cy: cytoscape.Core;
@@ -25,6 +33,9 @@ export class FilterState {
       downstreamRoots: new Set<string>(),
       maxDistance: null,
       highlightedOnly: true,
+      highlightNeighbors: 0,
+      fadeFiltered: false,
+      edgeDirection: "both",
     };
   }
 
@@ -67,13 +78,17 @@ export class FilterState {
       downstreamRoots: Array.from(this.config.downstreamRoots),
       maxDistance: this.config.maxDistance,
       highlightedOnly: this.config.highlightedOnly,
+      highlightNeighbors: this.config.highlightNeighbors,
+      fadeFiltered: this.config.fadeFiltered,
+      edgeDirection: this.config.edgeDirection,
     };
 
     console.log("Filter config:", wasmFilterConfig);
 
-    // Call WASM to compute visible and highlighted nodes
-    const result: FilterResult = this.processor.filter_nodes(
-      JSON.stringify(wasmFilterConfig),
+    // Call WASM to compute visible and highlighted nodes (passed as an object to skip
+    // the JSON stringify/parse round-trip)
+    const result: FilterResult = this.processor.filter_nodes_object(
+      wasmFilterConfig,
     ) as FilterResult;
 
     console.log("WASM result:", result);
@@ -81,10 +96,15 @@ export class FilterState {
     // Create sets for O(1) lookup
     const visibleSet = new Set(result.visible);
     const highlightedSet = new Set(result.highlighted);
+    const fadedSet = new Set(result.faded ?? []);
+    const isShown = (id: string) => visibleSet.has(id) || fadedSet.has(id);
+    const hiddenEdgeSet = new Set(
+      (result.hiddenEdges ?? []).map(([source, target]) => `${source}\0${target}`),
+    );
 
     // Update Cytoscape node visibility
     this.cy.nodes().forEach((node) => {
-      const isVisible = visibleSet.has(node.id());
+      const isVisible = isShown(node.id());
       const isParent = typeof node.isParent === "function" && node.isParent();
 
       // For parent nodes, check if ANY child is visible
@@ -94,7 +114,7 @@ export class FilterState {
         const hasVisibleChildren =
           typeof (children as any).some === "function"
             ? (children as any).some((child: { id: () => string }) =>
-                visibleSet.has(child.id()),
+                isShown(child.id()),
               )
             : false;
         node.style(
@@ -104,6 +124,7 @@ export class FilterState {
       } else {
         node.style("display", isVisible ? "element" : "none");
       }
+      node.style("opacity", fadedSet.has(node.id()) ? FADED_OPACITY : 1);
     });
 
     // Update Cytoscape node highlighting by directly setting styles
@@ -139,15 +160,65 @@ export class FilterState {
       );
     });
 
-    // Update edge visibility (only show if both source and target are visible)
+    // Update edge visibility (only show if both source and target are shown and the edge
+    // direction filter keeps it; fade it if either end is faded)
     this.cy.edges().forEach((edge) => {
-      const sourceVisible = visibleSet.has(edge.source().id());
-      const targetVisible = visibleSet.has(edge.target().id());
-      const isVisible = sourceVisible && targetVisible;
+      const sourceId = edge.source().id();
+      const targetId = edge.target().id();
+      const isVisible =
+        isShown(sourceId) &&
+        isShown(targetId) &&
+        !hiddenEdgeSet.has(`${sourceId}\0${targetId}`);
       edge.style("display", isVisible ? "element" : "none");
+      const isFaded = fadedSet.has(sourceId) || fadedSet.has(targetId);
+      edge.style("opacity", isFaded ? FADED_OPACITY : 1);
     });
   }
 
+  /**
+   * Export the last applied filters as a named preset (JSON)
+   */
+  exportPreset(name: string): string {
+    return this.processor.export_filter_state(name);
+  }
+
+  /**
+   * Make a preset (JSON) the current configuration; call applyFilters() to show it.
+   * Throws if the preset is malformed or refers to modules missing from the graph.
+   */
+  importPreset(presetJson: string): FilterPreset {
+    const preset = this.processor.import_filter_state(presetJson) as FilterPreset;
+    this.loadWasmConfig(preset.config);
+    return preset;
+  }
+
+  /**
+   * Encode the last applied filters for a shareable link (`#filter=...`)
+   */
+  encodeState(): string {
+    return this.processor.encode_filter_state();
+  }
+
+  /**
+   * Make an encoded filter state the current configuration; call applyFilters() to show
+   * it. Throws if the state is malformed or refers to modules missing from the graph.
+   */
+  decodeState(encoded: string): void {
+    this.loadWasmConfig(this.processor.decode_filter_state(encoded));
+  }
+
+  /**
+   * Replace the configuration with one returned by WASM (roots as arrays)
+   */
+  private loadWasmConfig(config: FilterPreset["config"]): void {
+    this.config = {
+      ...config,
+      maxDistance: config.maxDistance ?? null,
+      upstreamRoots: new Set(config.upstreamRoots),
+      downstreamRoots: new Set(config.downstreamRoots),
+    };
+  }
+
   /**
    * Toggle orphan node visibility
    */
@@ -176,6 +247,20 @@ export class FilterState {
     this.config.excludePatterns = patterns;
   }
 
+  /**
+   * Toggle fading (instead of hiding) filtered-out nodes
+   */
+  toggleFadeFiltered(enabled: boolean): void {
+    this.config.fadeFiltered = enabled;
+  }
+
+  /**
+   * Set which edges to show relative to the selected roots
+   */
+  setEdgeDirection(direction: EdgeDirection): void {
+    this.config.edgeDirection = direction;
+  }
+
   /**
    * Set max distance filter
    */
@@ -183,6 +268,13 @@ export class FilterState {
     this.config.maxDistance = distance;
   }
 
+  /**
+   * Set how many hops around highlighted nodes are highlighted too
+   */
+  setHighlightNeighbors(hops: number): void {
+    this.config.highlightNeighbors = hops;
+  }
+
   /**
    * Add upstream root
    */
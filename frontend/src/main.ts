@@ -1,10 +1,16 @@
 import "./styles/main.css";
 import type { DistanceMap, GraphData } from "./types";
-import { initializeCytoscape } from "./cytoscape-manager";
+import { hasLayoutHints, initializeCytoscape } from "./cytoscape-manager";
 import { LayoutManager } from "./layout-manager";
 import { FilterState } from "./filter-state";
+import { computeDistancesInChunks } from "./distances";
 import { ModuleSelector } from "./ui/module-selector";
-import { setupUIEventHandlers } from "./ui/controls";
+import {
+  FILTER_LINK_PREFIX,
+  setupPresetControls,
+  setupUIEventHandlers,
+  syncFilterControls,
+} from "./ui/controls";
 
 // Import WASM module (will be available after build)
 // @ts-ignore - WASM module will be generated by wasm-pack
@@ -41,10 +47,19 @@ async function main() {
     console.log("WASM module initialized");
 
     // Create graph processor
-    const processor = new GraphProcessor(JSON.stringify(graphData));
-
-    // Compute all-pairs distances
-    const distances = processor.compute_all_distances() as DistanceMap;
+    const processor = GraphProcessor.from_object(graphData);
+
+    // Compute all-pairs distances in chunks, showing progress for big graphs
+    const info = document.getElementById("info");
+    const distances: DistanceMap =
+      (await computeDistancesInChunks(processor.start_distances(), (fraction) => {
+        if (info) {
+          info.textContent = `Computing distances… ${Math.round(fraction * 100)}%`;
+        }
+      })) ?? {};
+    if (info) {
+      info.textContent = "";
+    }
     console.log("Distances computed");
 
     // Initialize Cytoscape
@@ -114,8 +129,38 @@ async function main() {
       });
     }
 
-    // Apply initial layout
-    layoutManager.applyLayout(false);
+    // Load filters (a preset or a shared link) into the filter state, the panel, and
+    // the graph
+    const showLoadedFilters = (load: () => void): boolean => {
+      try {
+        load();
+      } catch (error) {
+        alert(`Could not load filters: ${error}`);
+        return false;
+      }
+      upstreamSelector.setSelected(filterState.getUpstreamRoots());
+      downstreamSelector.setSelected(filterState.getDownstreamRoots());
+      syncFilterControls(filterState.getConfig());
+      filterState.applyFilters();
+      return true;
+    };
+    setupPresetControls(graphData.config?.presets ?? [], filterState, (json) => {
+      if (showLoadedFilters(() => filterState.importPreset(json))) {
+        layoutManager.applyLayout(true);
+      }
+    });
+
+    // Restore a view shared as a link (#filter=...)
+    const hash = window.location.hash.slice(1);
+    if (hash.startsWith(FILTER_LINK_PREFIX)) {
+      const encoded = hash.slice(FILTER_LINK_PREFIX.length);
+      showLoadedFilters(() => filterState.decodeState(encoded));
+    }
+
+    // Apply initial layout, unless the graph came with positions (`--layout-seed`)
+    if (!hasLayoutHints(graphData)) {
+      layoutManager.applyLayout(false);
+    }
 
     console.log("Frontend initialization complete");
   } catch (error) {
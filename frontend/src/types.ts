@@ -1,3 +1,5 @@
+import type { EdgeDirection } from "./bindings/EdgeDirection";
+import type { FilterPreset } from "./bindings/FilterPreset";
 import type { GraphConfig } from "./bindings/GraphConfig";
 import type { GraphData } from "./bindings/GraphData";
 import type { GraphEdge } from "./bindings/GraphEdge";
@@ -11,14 +13,23 @@ export interface FilterConfig {
   downstreamRoots: Set<string>;
   maxDistance: number | null;
   highlightedOnly: boolean;
+  highlightNeighbors: number;
+  fadeFiltered: boolean;
+  edgeDirection: EdgeDirection;
+  weightKey?: string;
 }
 
 export interface FilterResult {
   visible: string[];
   highlighted: string[];
+  faded?: string[];
+  hiddenEdges?: [string, string][];
 }
 
-export type { GraphConfig, GraphData, GraphEdge, GraphNode };
+export type {
+  EdgeDirection,
+  FilterPreset,
+  GraphConfig, GraphData, GraphEdge, GraphNode };
 
 export interface DistanceMap {
   [nodeId: string]: {
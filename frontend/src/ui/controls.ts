@@ -2,6 +2,7 @@ import type cytoscape from "cytoscape";
 import { cytoscapeControls } from "../cytoscape-manager";
 import type { LayoutManager } from "../layout-manager";
 import type { FilterState } from "../filter-state";
+import type { EdgeDirection, FilterConfig, FilterPreset } from "../types";
 
 /**
  * Setup all UI event handlers
@@ -90,6 +91,28 @@ export function setupUIEventHandlers(
     });
   }
 
+  // Fade filtered checkbox
+  const fadeFilteredCheckbox = document.getElementById(
+    "fade-filtered",
+  ) as HTMLInputElement;
+  if (fadeFilteredCheckbox) {
+    fadeFilteredCheckbox.addEventListener("change", (e) => {
+      filterState.toggleFadeFiltered((e.target as HTMLInputElement).checked);
+    });
+  }
+
+  // Edge direction select
+  const edgeDirectionSelect = document.getElementById(
+    "edge-direction",
+  ) as HTMLSelectElement;
+  if (edgeDirectionSelect) {
+    edgeDirectionSelect.addEventListener("change", (e) => {
+      filterState.setEdgeDirection(
+        (e.target as HTMLSelectElement).value as EdgeDirection,
+      );
+    });
+  }
+
   // Distance slider
   const distanceSlider = document.getElementById(
     "distance-slider",
@@ -108,6 +131,19 @@ export function setupUIEventHandlers(
     });
   }
 
+  // Highlight neighbors slider
+  const neighborsSlider = document.getElementById(
+    "neighbors-slider",
+  ) as HTMLInputElement;
+  const neighborsValue = document.getElementById("neighbors-value");
+  if (neighborsSlider && neighborsValue) {
+    neighborsSlider.addEventListener("input", (e) => {
+      const value = parseInt((e.target as HTMLInputElement).value);
+      neighborsValue.textContent = value.toString();
+      filterState.setHighlightNeighbors(value);
+    });
+  }
+
   // Exclude patterns input
   const excludePatternsInput = document.getElementById(
     "exclude-patterns",
@@ -146,10 +182,16 @@ export function setupUIEventHandlers(
       if (showOrphansCheckbox) showOrphansCheckbox.checked = true;
       if (showNamespacesCheckbox) showNamespacesCheckbox.checked = true;
       if (highlightedOnlyCheckbox) highlightedOnlyCheckbox.checked = true;
+      if (fadeFilteredCheckbox) fadeFilteredCheckbox.checked = false;
+      if (edgeDirectionSelect) edgeDirectionSelect.value = "both";
       if (distanceSlider) {
         distanceSlider.value = "10";
         if (distanceValue) distanceValue.textContent = "∞";
       }
+      if (neighborsSlider) {
+        neighborsSlider.value = "0";
+        if (neighborsValue) neighborsValue.textContent = "0";
+      }
       if (excludePatternsInput) excludePatternsInput.value = "";
 
       // Show all nodes
@@ -181,3 +223,127 @@ export function setupUIEventHandlers(
     });
   }
 }
+
+/** URL fragment prefix of a shared view: `#filter=<encoded filter state>` */
+export const FILTER_LINK_PREFIX = "filter=";
+
+/**
+ * Reflect a filter configuration (e.g. a loaded preset) in the filter panel inputs
+ */
+export function syncFilterControls(config: FilterConfig): void {
+  const setChecked = (id: string, checked: boolean) => {
+    const input = document.getElementById(id) as HTMLInputElement | null;
+    if (input) input.checked = checked;
+  };
+  setChecked("show-orphans", config.showOrphans);
+  setChecked("show-namespaces", config.showNamespaces);
+  setChecked("highlighted-only", config.highlightedOnly);
+  setChecked("fade-filtered", config.fadeFiltered);
+
+  const edgeDirectionSelect = document.getElementById(
+    "edge-direction",
+  ) as HTMLSelectElement | null;
+  if (edgeDirectionSelect) {
+    edgeDirectionSelect.value = config.edgeDirection;
+  }
+
+  const distanceSlider = document.getElementById(
+    "distance-slider",
+  ) as HTMLInputElement | null;
+  const distanceValue = document.getElementById("distance-value");
+  if (distanceSlider) {
+    distanceSlider.value = (config.maxDistance ?? 10).toString();
+  }
+  if (distanceValue) {
+    distanceValue.textContent =
+      config.maxDistance === null ? "∞" : config.maxDistance.toString();
+  }
+
+  const neighborsSlider = document.getElementById(
+    "neighbors-slider",
+  ) as HTMLInputElement | null;
+  const neighborsValue = document.getElementById("neighbors-value");
+  if (neighborsSlider) {
+    neighborsSlider.value = config.highlightNeighbors.toString();
+  }
+  if (neighborsValue) {
+    neighborsValue.textContent = config.highlightNeighbors.toString();
+  }
+
+  const excludePatternsInput = document.getElementById(
+    "exclude-patterns",
+  ) as HTMLInputElement | null;
+  if (excludePatternsInput) {
+    excludePatternsInput.value = config.excludePatterns.join(", ");
+  }
+}
+
+/**
+ * Setup the preset controls: presets declared in deptree.toml, saving the current view
+ * to a file, and loading a saved one. `applyPreset` receives the preset as JSON.
+ */
+export function setupPresetControls(
+  presets: FilterPreset[],
+  filterState: FilterState,
+  applyPreset: (presetJson: string) => void,
+): void {
+  const presetSelect = document.getElementById(
+    "preset-select",
+  ) as HTMLSelectElement;
+  if (presetSelect) {
+    presets.forEach((preset, index) => {
+      presetSelect.add(new Option(preset.name, index.toString()));
+    });
+    presetSelect.disabled = presets.length === 0;
+  }
+
+  const applyPresetBtn = document.getElementById("apply-preset");
+  if (applyPresetBtn && presetSelect) {
+    applyPresetBtn.addEventListener("click", () => {
+      const preset = presets[parseInt(presetSelect.value)];
+      if (preset) {
+        applyPreset(JSON.stringify(preset));
+      }
+    });
+  }
+
+  const savePresetBtn = document.getElementById("save-preset");
+  if (savePresetBtn) {
+    savePresetBtn.addEventListener("click", () => {
+      const name = prompt("Name this view:");
+      if (!name) return;
+      filterState.applyFilters();
+      const blob = new Blob([filterState.exportPreset(name)], {
+        type: "application/json",
+      });
+      const link = document.createElement("a");
+      link.download = `${name}.json`;
+      link.href = URL.createObjectURL(blob);
+      link.click();
+      URL.revokeObjectURL(link.href);
+    });
+  }
+
+  const copyLinkBtn = document.getElementById("copy-link");
+  if (copyLinkBtn) {
+    copyLinkBtn.addEventListener("click", () => {
+      filterState.applyFilters();
+      const encoded = filterState.encodeState();
+      history.replaceState(null, "", `#${FILTER_LINK_PREFIX}${encoded}`);
+      navigator.clipboard?.writeText(window.location.href);
+    });
+  }
+
+  const loadPresetInput = document.getElementById(
+    "load-preset",
+  ) as HTMLInputElement;
+  if (loadPresetInput) {
+    loadPresetInput.addEventListener("change", async () => {
+      const file = loadPresetInput.files?.[0];
+      if (file) {
+        applyPreset(await file.text());
+        loadPresetInput.value = "";
+      }
+    });
+  }
+}
@@ -78,6 +78,14 @@ export class ModuleSelector {
     this.render();
   }
 
+  /**
+   * Replace the selection (e.g. when a preset is loaded) without notifying onChange
+   */
+  setSelected(modules: Iterable<string>): void {
+    this.selected = new Set(modules);
+    this.render();
+  }
+
   /**
    * Get all selected modules
    */
@@ -13,6 +13,7 @@ export const sampleEdges: GraphEdge[] = [
 ];
 
 export const sampleGraphData: GraphData = {
+  version: 1,
   nodes: sampleNodes,
   edges: sampleEdges,
   config: {
@@ -67,6 +68,7 @@ export const compoundEdges: GraphEdge[] = [
 ];
 
 export const compoundGraphData: GraphData = {
+  version: 1,
   nodes: compoundNodes,
   edges: compoundEdges,
   config: {
@@ -20,25 +20,44 @@ Object.defineProperty(window, "__GRAPH_DATA__", {
 });
 
 // Mock WASM module - return native JS values, not JSON strings
-vi.mock("../src/wasm/deptree_wasm", () => ({
-  default: vi.fn(() => Promise.resolve()),
-  GraphProcessor: vi.fn().mockImplementation((graphJson: string) => ({
+vi.mock("../src/wasm/deptree_wasm", () => {
+  const createMockProcessor = () => ({
     compute_all_distances: vi.fn(() => ({
       module_a: { module_b: 1 },
       module_b: {},
     })),
+    start_distances: vi.fn(() => ({
+      step: vi.fn(() => true),
+      progress: vi.fn(() => 1),
+      result: vi.fn(() => ({
+        module_a: { module_b: 1 },
+        module_b: {},
+      })),
+      free: vi.fn(),
+    })),
     filter_nodes: vi.fn((configJson: string) => {
       // Return native array, NOT JSON string
       return ["module_a", "module_b"];
     }),
+    filter_nodes_object: vi.fn((config: unknown) => {
+      return ["module_a", "module_b"];
+    }),
     get_upstream: vi.fn((rootsJson: string) => {
       return JSON.parse(rootsJson); // Return native array
     }),
     get_downstream: vi.fn((rootsJson: string) => {
       return JSON.parse(rootsJson); // Return native array
     }),
-  })),
-}));
+  });
+
+  return {
+    default: vi.fn(() => Promise.resolve()),
+    GraphProcessor: Object.assign(
+      vi.fn().mockImplementation((graphJson: string) => createMockProcessor()),
+      { from_object: vi.fn((graphData: unknown) => createMockProcessor()) },
+    ),
+  };
+});
 
 afterEach(() => {
   vi.clearAllMocks();